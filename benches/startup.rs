@@ -0,0 +1,19 @@
+//! The startup path's hot spots, one bench each; see `src/profile.rs`.
+//! Reads the real application directories, icon themes, app cache and
+//! theme.css of the user running it.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tusk_launcher::profile::Startup;
+
+fn startup(c: &mut Criterion) {
+    let mut startup = Startup::load();
+    c.bench_function("desktop scan", |b| b.iter(|| black_box(startup.desktop_scan())));
+    c.bench_function("cache load", |b| b.iter(|| black_box(startup.cache_load())));
+    c.bench_function("icon resolution", |b| b.iter(|| black_box(startup.icon_resolution())));
+    c.bench_function("theme parse", |b| b.iter(|| black_box(startup.theme_parse())));
+}
+
+criterion_group!(benches, startup);
+criterion_main!(benches);
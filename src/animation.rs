@@ -0,0 +1,141 @@
+//! Open/close effects and hover transitions, timed by the theme.
+//!
+//! Design:
+//! - `.main-window` sets `open-animation` (`fade`, `slide` or `none`),
+//!   `open-duration` and `open-easing` for showing the window, and
+//!   `transition-duration` / `transition-easing` for hover colors; any
+//!   themed class can override the transition for itself.
+//! - Closing plays the open effect backwards before the window is hidden.
+//! - Hover transitions ride on egui's per-widget `animate_bool`, so nothing
+//!   is stored here; the open effect is a pure function of the time since
+//!   the window was shown or dismissed.
+//! - `animations = false` in config.toml makes everything instant, egui's
+//!   own animations included.
+
+/// How far the contents start below their place with `open-animation: slide`.
+const SLIDE_DISTANCE: f32 = 24.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// CSS keywords; `ease` is taken as `ease-in-out`.
+    pub fn parse(s: &str) -> Option<Easing> {
+        match s.trim() {
+            "linear"               => Some(Easing::Linear),
+            "ease-in"              => Some(Easing::EaseIn),
+            "ease-out"             => Some(Easing::EaseOut),
+            "ease" | "ease-in-out" => Some(Easing::EaseInOut),
+            _                      => None,
+        }
+    }
+
+    /// Cubic curves, for egui's `animate_bool_with_time_and_easing`.
+    pub fn func(self) -> fn(f32) -> f32 {
+        match self {
+            Easing::Linear    => |t| t,
+            Easing::EaseIn    => |t| t * t * t,
+            Easing::EaseOut   => |t| 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => |t| if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0 },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// Seconds; zero is instant.
+    pub duration: f32,
+    pub easing:   Easing,
+}
+
+impl Transition {
+    /// Eased progress, 0 to 1, `elapsed` seconds in.
+    pub fn progress(&self, elapsed: f32) -> f32 {
+        if self.duration <= 0.0 { return 1.0; }
+        (self.easing.func())((elapsed / self.duration).clamp(0.0, 1.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    None,
+    Fade,
+    Slide,
+}
+
+impl Effect {
+    pub fn parse(s: &str) -> Option<Effect> {
+        match s.trim() {
+            "none"  => Some(Effect::None),
+            "fade"  => Some(Effect::Fade),
+            "slide" => Some(Effect::Slide),
+            _       => None,
+        }
+    }
+
+    /// Opacity and downward offset of the window contents when `shown` of
+    /// the way in (0 hidden, 1 in place). Slide fades as well.
+    pub fn frame(self, shown: f32) -> (f32, f32) {
+        match self {
+            Effect::None  => (1.0, 0.0),
+            Effect::Fade  => (shown, 0.0),
+            Effect::Slide => (shown, ((1.0 - shown) * SLIDE_DISTANCE).round()),
+        }
+    }
+}
+
+/// The window's effect and both transitions, as the theme gives them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Motion {
+    pub effect: Effect,
+    pub open:   Transition,
+    pub hover:  Transition,
+}
+
+/// `150ms`, `0.2s`, or a bare number of milliseconds; in seconds.
+pub fn parse_duration(s: &str) -> Option<f32> {
+    let s = s.trim();
+    let secs = if let Some(ms) = s.strip_suffix("ms") {
+        ms.trim().parse::<f32>().ok()? / 1000.0
+    } else if let Some(sec) = s.strip_suffix('s') {
+        sec.trim().parse::<f32>().ok()?
+    } else {
+        s.parse::<f32>().ok()? / 1000.0
+    };
+    (secs >= 0.0).then_some(secs)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_durations_and_easing() {
+        assert_eq!(parse_duration("150ms"), Some(0.15));
+        assert_eq!(parse_duration("0.2s"), Some(0.2));
+        assert_eq!(parse_duration("80"), Some(0.08));
+        assert_eq!(parse_duration("-1s"), None);
+        assert_eq!(parse_duration("fast"), None);
+
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            let f = easing.func();
+            assert_eq!((f(0.0), f(1.0)), (0.0, 1.0));
+        }
+        assert_eq!(Easing::parse("ease"), Some(Easing::EaseInOut));
+
+        let t = Transition { duration: 0.2, easing: Easing::Linear };
+        assert_eq!(t.progress(0.1), 0.5);
+        assert_eq!(t.progress(1.0), 1.0);
+        assert_eq!(Transition { duration: 0.0, easing: Easing::EaseIn }.progress(0.0), 1.0);
+        assert_eq!(Effect::Slide.frame(0.5), (0.5, 12.0));
+    }
+}
@@ -4,13 +4,17 @@ use std::{
     path::{Path, PathBuf},
     process::{Command, Stdio},
     str::FromStr,
-    sync::{Arc, LazyLock, Mutex},
+    sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc, LazyLock, Mutex, OnceLock},
     thread,
     time,
 };
 use eframe::egui;
 
 use serde::{Serialize, Deserialize};
+use crate::providers::{
+    CalculatorProvider, ClipboardProvider, ConversionProvider, PlacesProvider, RecentProvider, ResultKind, ResultRow,
+    RowAction, SearchProvider, SearchResult, SnippetProvider, WallpaperProvider, WebProvider, WindowProvider,
+};
 
 // ============================================================================
 // Public cache data structures (unchanged public API)
@@ -62,21 +66,57 @@ pub struct AppEntry {
     pub exec_command:     Option<String>,
     pub terminal_command: Option<String>,
     pub last_used:        Option<u64>,
+    /// Label shown instead of the app's own name.
+    pub custom_name:      Option<String>,
+    /// Icon file (or theme icon name) shown instead of the app's own.
+    pub custom_icon:      Option<String>,
+}
+
+/// A user's label and icon for an app, from its settings window. Kept in the
+/// cache under the app's real name, so rescans don't lose it.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct AppOverride {
+    pub name: Option<String>,
+    pub icon: Option<String>,
 }
 
+/// What the user chose for a tray item.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TrayPref { Pinned, Hidden }
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct AppCache {
-    pub apps: Vec<(String, AppEntry)>,
+    pub apps:        Vec<(String, AppEntry)>,
+    /// Commands run via the run-mode prefix, most recent first.
+    pub run_history: Vec<String>,
+    /// Tray items by SNI `Id`; pinned ones in the order they were pinned.
+    pub tray_prefs:  Vec<(String, TrayPref)>,
+    /// Caffeine toggle was on when last changed; re-applied at startup.
+    pub idle_inhibit: bool,
+    /// Result titles hidden with "Hide from launcher".
+    pub hidden_apps: Vec<String>,
+    /// Result titles pinned ahead of the recent apps, in the order pinned.
+    pub pinned_apps: Vec<String>,
+    /// Do not disturb was on when last changed; re-applied at startup.
+    pub do_not_disturb: bool,
 }
 
 #[derive(Default)]
 struct IconCache {
     texture:       Option<egui::TextureHandle>,
+    /// Every frame with its delay, for animated GIF/APNG; empty for stills.
+    frames:        Vec<(egui::TextureHandle, time::Duration)>,
     last_modified: Option<time::SystemTime>,
 }
 
 pub struct IconManager {
     icon_textures: HashMap<String, IconCache>,
+    /// Foreground color `-symbolic` SVGs are recolored to.
+    symbolic_color: Option<egui::Color32>,
+    /// Play animated icons; otherwise their first frame is shown.
+    animate:        bool,
+    /// Animations are timed from here, so identical icons stay in step.
+    epoch:          time::Instant,
 }
 
 // ============================================================================
@@ -87,37 +127,142 @@ pub struct IconManager {
 // ============================================================================
 
 struct App {
-    name:       String,
-    name_lower: String,   // computed once, used for every search
-    exec:       String,
-    icon:       String,
+    name:        String,
+    name_lower:  String,   // computed once, used for every search
+    /// Lowercased GenericName and untranslated Name, also matched by search.
+    alt_lower:   String,
+    exec:        String,
+    icon:        String,
+    /// Localized Comment (falling back to GenericName), shown on hover.
+    description: Option<String>,
+    /// `[Desktop Action ...]` groups listed in the entry's `Actions=` key.
+    actions:     Vec<DesktopAction>,
+    /// Lowercased `StartupWMClass`, for matching the app's open windows.
+    wm_class:    Option<String>,
+    /// `Terminal=true`: run inside a terminal emulator.
+    terminal:    bool,
+    /// Cover art (Steam library portrait/header image) for the games grid.
+    cover:       Option<String>,
+    /// Menu category (one of `CATEGORIES`) from the entry's `Categories=`.
+    category:    Option<&'static str>,
+    /// The `.desktop` file the app was read from.
+    file:        Option<PathBuf>,
 }
 
 impl App {
     fn new(name: String, exec: String, icon: String) -> Self {
         let name_lower = name.to_lowercase();
-        App { name, name_lower, exec, icon }
+        App {
+            name, name_lower, alt_lower: String::new(), exec, icon,
+            description: None, actions: Vec::new(), wm_class: None, terminal: false, cover: None,
+            category: None, file: None,
+        }
+    }
+
+    fn matches(&self, query_lower: &str) -> bool {
+        self.name_lower.contains(query_lower) || self.alt_lower.contains(query_lower)
+    }
+}
+
+/// A single `[Desktop Action <id>]` group, e.g. Firefox's "New Private Window".
+#[derive(Clone, PartialEq, Debug)]
+struct DesktopAction {
+    name: String,
+    exec: String,
+}
+
+/// Everything we keep from a parsed `.desktop` file.
+#[derive(Clone, PartialEq, Debug)]
+struct DesktopEntry {
+    name:         String,
+    generic_name: Option<String>,
+    comment:      Option<String>,
+    untranslated: Option<String>,
+    exec:         String,
+    icon:         String,
+    actions:      Vec<DesktopAction>,
+    wm_class:     Option<String>,
+    terminal:     bool,
+    categories:   Vec<String>,
+    /// `NoDisplay=true`: hidden from menus, but still valid for autostart.
+    no_display:   bool,
+    /// `X-GNOME-Autostart-enabled=false`, GNOME's way of disabling an autostart entry.
+    autostart_disabled: bool,
+    /// `TryExec`, checked when the entry is listed rather than when it's
+    /// parsed, so the desktop index stays valid when the program is installed.
+    try_exec:     Option<String>,
+    /// Where the entry was read from; the index keeps it on the `FILE` line.
+    file:         PathBuf,
+}
+
+impl From<DesktopEntry> for App {
+    fn from(entry: DesktopEntry) -> Self {
+        let mut app = App::new(entry.name, entry.exec, entry.icon);
+        app.alt_lower = [&entry.generic_name, &entry.untranslated].into_iter()
+            .flatten()
+            .map(|s| s.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+        app.description = entry.comment.or(entry.generic_name);
+        app.actions     = entry.actions;
+        app.wm_class    = entry.wm_class.map(|c| c.to_lowercase());
+        app.terminal    = entry.terminal;
+        app.category    = menu_category(&entry.categories);
+        app.file        = Some(entry.file);
+        app
     }
 }
 
-const ICON_EXTS: &[&str] = &["png", "svg", "jpg", "jpeg", "ico"];
+/// Menu categories in chip order, with the freedesktop main categories
+/// (Desktop Menu Specification, appendix A) that fall under each.
+pub const CATEGORIES: &[(&str, &[&str])] = &[
+    ("Internet",    &["Network"]),
+    ("Development", &["Development"]),
+    ("Games",       &["Game"]),
+    ("Graphics",    &["Graphics"]),
+    ("Multimedia",  &["AudioVideo", "Audio", "Video"]),
+    ("Office",      &["Office"]),
+    ("Education",   &["Education", "Science"]),
+    ("Settings",    &["Settings"]),
+    ("System",      &["System"]),
+    ("Utilities",   &["Utility"]),
+];
+
+/// The menu category of the first `Categories=` entry that has one.
+fn menu_category(categories: &[String]) -> Option<&'static str> {
+    categories.iter().find_map(|c| {
+        CATEGORIES.iter().find(|(_, main)| main.contains(&c.as_str())).map(|(name, _)| *name)
+    })
+}
+
+/// `cat:games doom` → `("Games", "doom")`. The word after `prefix` picks a
+/// category by case-insensitive prefix, so `cat:dev` is Development.
+pub fn category_query<'a>(query: &'a str, prefix: &str) -> Option<(&'static str, &'a str)> {
+    if prefix.is_empty() { return None; }
+    let rest = query.strip_prefix(prefix)?;
+    let (word, filter) = rest.split_once(' ').unwrap_or((rest, ""));
+    if word.is_empty() { return None; }
+    let word = word.to_lowercase();
+    let (name, _) = CATEGORIES.iter().find(|(name, _)| name.to_lowercase().starts_with(&word))?;
+    Some((name, filter.trim()))
+}
+
+const ICON_EXTS: &[&str] = &["png", "svg", "jpg", "jpeg", "ico", "gif"];
+/// Longer animations are cut off; each frame is a separate texture.
+const MAX_ANIMATION_FRAMES: usize = 256;
+/// Pixel size app icons are looked up at in the icon theme.
+const ICON_SIZE: u32 = 32;
 
 // ============================================================================
 // Cache management
 // ============================================================================
 
-static CACHE_FILE: LazyLock<PathBuf> = LazyLock::new(|| {
-    let path = crate::paths::config_home().join("tusk-launcher");
-    fs::create_dir_all(&path).ok();
-    path.join("app_cache.txt")
-});
-
+/// In-memory copy of the cache; `crate::store` keeps it on disk.
 pub static APP_CACHE: LazyLock<Mutex<AppCache>> = LazyLock::new(|| {
-    let cache = CACHE_FILE.exists()
-        .then(|| fs::read_to_string(&*CACHE_FILE).ok())
-        .flatten()
-        .and_then(|s: String| deserialize_cache(&s).ok())
-        .unwrap_or_default();
+    let cache = crate::store::STORE.load().unwrap_or_else(|e| {
+        tracing::warn!(target: "apps", "App cache: {e}");
+        AppCache::default()
+    });
     Mutex::new(cache)
 });
 
@@ -144,25 +289,53 @@ fn unescape(s: &str) -> String {
     result
 }
 
-fn serialize_cache(cache: &AppCache) -> String {
-    let mut s = String::from("APP_CACHE_V4\n");
+pub(crate) fn serialize_cache(cache: &AppCache) -> String {
+    let mut s = String::from("APP_CACHE_V11\n");
     for (app_name, entry) in &cache.apps {
-        s.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\n",
+        s.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
             escape(app_name),
             entry.launch_options.as_ref().map(|o| escape(&o.to_string())).unwrap_or_default(),
             entry.icon_path.as_ref().map(|s| escape(s)).unwrap_or_default(),
             entry.exec_command.as_ref().map(|s| escape(s)).unwrap_or_default(),
             entry.terminal_command.as_ref().map(|s| escape(s)).unwrap_or_default(),
             entry.last_used.map(|t| t.to_string()).unwrap_or_default(),
+            entry.custom_name.as_ref().map(|s| escape(s)).unwrap_or_default(),
+            entry.custom_icon.as_ref().map(|s| escape(s)).unwrap_or_default(),
         ));
     }
+    for cmd in &cache.run_history {
+        s.push_str(&format!("HISTORY\t{}\n", escape(cmd)));
+    }
+    for (id, pref) in &cache.tray_prefs {
+        let pref = match pref { TrayPref::Pinned => "pinned", TrayPref::Hidden => "hidden" };
+        s.push_str(&format!("TRAY\t{}\t{}\n", escape(id), pref));
+    }
+    if cache.idle_inhibit {
+        s.push_str("IDLE_INHIBIT\ton\n");
+    }
+    if cache.do_not_disturb {
+        s.push_str("DND\ton\n");
+    }
+    for name in &cache.hidden_apps {
+        s.push_str(&format!("HIDDEN\t{}\n", escape(name)));
+    }
+    for name in &cache.pinned_apps {
+        s.push_str(&format!("PINNED\t{}\n", escape(name)));
+    }
     s
 }
 
-fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
+pub(crate) fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
     let mut lines   = s.lines();
     let version     = lines.next();
-    let is_v4 = version == Some("APP_CACHE_V4");
+    let is_v11 = version == Some("APP_CACHE_V11");
+    let is_v10 = version == Some("APP_CACHE_V10") || is_v11;
+    let is_v9 = version == Some("APP_CACHE_V9") || is_v10;
+    let is_v8 = version == Some("APP_CACHE_V8") || is_v9;
+    let is_v7 = version == Some("APP_CACHE_V7") || is_v8;
+    let is_v6 = version == Some("APP_CACHE_V6") || is_v7;
+    let is_v5 = version == Some("APP_CACHE_V5") || is_v6;
+    let is_v4 = version == Some("APP_CACHE_V4") || is_v5;
     let is_v3 = version == Some("APP_CACHE_V3");
     let is_v2 = version == Some("APP_CACHE_V2");
     let is_v1 = version == Some("APP_CACHE_V1");
@@ -171,18 +344,69 @@ fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
         return Err("Unsupported cache version".into());
     }
 
+    // V5 appends two-column `HISTORY\t<cmd>` lines, V6 three-column
+    // `TRAY\t<id>\t<pref>` lines, V7 an `IDLE_INHIBIT\ton` line and V8
+    // two-column `HIDDEN\t<name>` lines; app lines always have more. V9 app
+    // lines add the custom name and icon, V10 `PINNED\t<name>` lines and V11
+    // a `DND\ton` line.
+    let mut idle_inhibit = false;
+    let mut do_not_disturb = false;
+    let (history, lines): (Vec<&str>, Vec<&str>) = lines
+        .filter(|l| !l.trim().is_empty())
+        .filter(|l| {
+            let setting = is_v7 && *l == "IDLE_INHIBIT\ton";
+            idle_inhibit |= setting;
+            !setting
+        })
+        .filter(|l| {
+            let setting = is_v11 && *l == "DND\ton";
+            do_not_disturb |= setting;
+            !setting
+        })
+        .partition(|l| is_v5 && l.starts_with("HISTORY\t") && l.split('\t').count() == 2);
+    let (tray, lines): (Vec<&str>, Vec<&str>) = lines.into_iter()
+        .partition(|l| is_v6 && l.starts_with("TRAY\t") && l.split('\t').count() == 3);
+    let (hidden, lines): (Vec<&str>, Vec<&str>) = lines.into_iter()
+        .partition(|l| is_v8 && l.starts_with("HIDDEN\t") && l.split('\t').count() == 2);
+    let (pinned, lines): (Vec<&str>, Vec<&str>) = lines.into_iter()
+        .partition(|l| is_v10 && l.starts_with("PINNED\t") && l.split('\t').count() == 2);
+
     Ok(AppCache {
-        apps: lines
-            .filter(|l| !l.trim().is_empty())
+        idle_inhibit,
+        do_not_disturb,
+        hidden_apps: hidden.iter().map(|l| unescape(&l["HIDDEN\t".len()..])).collect(),
+        pinned_apps: pinned.iter().map(|l| unescape(&l["PINNED\t".len()..])).collect(),
+        run_history: history.iter().map(|l| unescape(&l["HISTORY\t".len()..])).collect(),
+        tray_prefs:  tray.iter().filter_map(|l| {
+            let (id, pref) = l["TRAY\t".len()..].split_once('\t')?;
+            let pref = match pref {
+                "pinned" => TrayPref::Pinned,
+                "hidden" => TrayPref::Hidden,
+                _        => return None,
+            };
+            Some((unescape(id), pref))
+        }).collect(),
+        apps: lines.into_iter()
             .filter_map(|line| {
                 let parts: Vec<&str> = line.split('\t').collect();
-                if is_v4 && parts.len() == 6 {
+                if is_v9 && parts.len() == 8 {
+                    Some((unescape(parts[0]), AppEntry {
+                        launch_options:   (!parts[1].is_empty()).then(|| parts[1].parse().ok()).flatten(),
+                        icon_path:        (!parts[2].is_empty()).then(|| unescape(parts[2])),
+                        exec_command:     (!parts[3].is_empty()).then(|| unescape(parts[3])),
+                        terminal_command: (!parts[4].is_empty()).then(|| unescape(parts[4])),
+                        last_used:        (!parts[5].is_empty()).then(|| parts[5].parse().ok()).flatten(),
+                        custom_name:      (!parts[6].is_empty()).then(|| unescape(parts[6])),
+                        custom_icon:      (!parts[7].is_empty()).then(|| unescape(parts[7])),
+                    }))
+                } else if is_v4 && parts.len() == 6 {
                     Some((unescape(parts[0]), AppEntry {
                         launch_options:   (!parts[1].is_empty()).then(|| parts[1].parse().ok()).flatten(),
                         icon_path:        (!parts[2].is_empty()).then(|| unescape(parts[2])),
                         exec_command:     (!parts[3].is_empty()).then(|| unescape(parts[3])),
                         terminal_command: (!parts[4].is_empty()).then(|| unescape(parts[4])),
                         last_used:        (!parts[5].is_empty()).then(|| parts[5].parse().ok()).flatten(),
+                        ..Default::default()
                     }))
                 } else if is_v3 && parts.len() == 5 {
                     Some((unescape(parts[0]), AppEntry {
@@ -191,6 +415,7 @@ fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
                         exec_command:     (!parts[3].is_empty()).then(|| unescape(parts[3])),
                         terminal_command: (!parts[4].is_empty()).then(|| unescape(parts[4])),
                         last_used:        None,
+                        ..Default::default()
                     }))
                 } else if is_v2 && parts.len() == 4 {
                     Some((unescape(parts[0]), AppEntry {
@@ -199,6 +424,7 @@ fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
                         exec_command:     None,
                         terminal_command: (!parts[3].is_empty()).then(|| unescape(parts[3])),
                         last_used:        None,
+                        ..Default::default()
                     }))
                 } else if is_v1 && parts.len() == 3 {
                     Some((unescape(parts[0]), AppEntry {
@@ -207,6 +433,7 @@ fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
                         exec_command:     None,
                         terminal_command: None,
                         last_used:        None,
+                        ..Default::default()
                     }))
                 } else {
                     None
@@ -217,8 +444,7 @@ fn deserialize_cache(s: &str) -> Result<AppCache, Box<dyn std::error::Error>> {
 }
 
 fn save_cache(cache: &AppCache) -> Result<(), Box<dyn std::error::Error>> {
-    fs::write(&*CACHE_FILE, serialize_cache(cache))?;
-    Ok(())
+    crate::store::STORE.save(cache)
 }
 
 fn get_or_create_entry<'a>(cache: &'a mut AppCache, app_name: &str) -> &'a mut AppEntry {
@@ -240,6 +466,9 @@ pub fn update_recent_apps(app_name: &str, enable_recent_apps: bool) -> Result<()
         .ok()
         .map(|d| d.as_secs());
 
+    if let Some(at) = timestamp && let Err(e) = crate::store::STORE.record_launch(app_name, at) {
+        tracing::warn!(target: "apps", "App cache: {e}");
+    }
     if let Some(pos) = cache.apps.iter().position(|(name, _)| name == app_name) {
         let mut entry = cache.apps.remove(pos);
         entry.1.last_used = timestamp;
@@ -250,6 +479,105 @@ pub fn update_recent_apps(app_name: &str, enable_recent_apps: bool) -> Result<()
     }
 }
 
+const RUN_HISTORY_MAX: usize = 100;
+
+/// Move `cmd` to the front of the run history, dropping older duplicates.
+fn record_run_command(cmd: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.run_history.retain(|c| c != cmd);
+    cache.run_history.insert(0, cmd.to_string());
+    cache.run_history.truncate(RUN_HISTORY_MAX);
+    save_cache(&cache)
+}
+
+fn get_run_history() -> Vec<String> {
+    APP_CACHE.lock().map(|c| c.run_history.clone()).unwrap_or_default()
+}
+
+pub fn get_tray_prefs() -> Vec<(String, TrayPref)> {
+    APP_CACHE.lock().map(|c| c.tray_prefs.clone()).unwrap_or_default()
+}
+
+/// Pin or hide tray item `id`, or clear its preference with `None`. A newly
+/// pinned item goes after the ones already pinned.
+pub fn set_tray_pref(id: &str, pref: Option<TrayPref>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.tray_prefs.retain(|(i, _)| i != id);
+    if let Some(pref) = pref { cache.tray_prefs.push((id.to_string(), pref)); }
+    save_cache(&cache)
+}
+
+pub fn get_idle_inhibit() -> bool {
+    APP_CACHE.lock().map(|c| c.idle_inhibit).unwrap_or(false)
+}
+
+pub fn set_idle_inhibit(on: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.idle_inhibit = on;
+    save_cache(&cache)
+}
+
+pub fn get_do_not_disturb() -> bool {
+    APP_CACHE.lock().map(|c| c.do_not_disturb).unwrap_or(false)
+}
+
+pub fn set_do_not_disturb(on: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.do_not_disturb = on;
+    save_cache(&cache)
+}
+
+pub fn get_hidden_apps() -> Vec<String> {
+    APP_CACHE.lock().map(|c| c.hidden_apps.clone()).unwrap_or_default()
+}
+
+/// Hide `app_name` from results, or show it again.
+pub fn set_app_hidden(app_name: &str, hidden: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.hidden_apps.retain(|name| name != app_name);
+    if hidden { cache.hidden_apps.push(app_name.to_string()); }
+    save_cache(&cache)
+}
+
+pub fn get_pinned_apps() -> Vec<String> {
+    APP_CACHE.lock().map(|c| c.pinned_apps.clone()).unwrap_or_default()
+}
+
+/// Pin `app_name` after the apps already pinned, or unpin it.
+pub fn set_app_pinned(app_name: &str, pinned: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    cache.pinned_apps.retain(|name| name != app_name);
+    if pinned { cache.pinned_apps.push(app_name.to_string()); }
+    save_cache(&cache)
+}
+
+pub fn get_app_overrides() -> HashMap<String, AppOverride> {
+    APP_CACHE.lock()
+        .ok()
+        .map(|c| c.apps.iter()
+            .filter(|(_, e)| e.custom_name.is_some() || e.custom_icon.is_some())
+            .map(|(name, e)| (name.clone(), AppOverride { name: e.custom_name.clone(), icon: e.custom_icon.clone() }))
+            .collect()
+        )
+        .unwrap_or_default()
+}
+
+/// Store the custom label and icon of `app_name`. A new entry goes to the end
+/// of the cache, so it doesn't count as recently used.
+pub fn set_app_override(app_name: &str, over: AppOverride) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
+    let idx = match cache.apps.iter().position(|(name, _)| name == app_name) {
+        Some(idx) => idx,
+        None => {
+            cache.apps.push((app_name.to_owned(), AppEntry::default()));
+            cache.apps.len() - 1
+        }
+    };
+    cache.apps[idx].1.custom_name = over.name;
+    cache.apps[idx].1.custom_icon = over.icon;
+    save_cache(&cache)
+}
+
 pub fn update_launch_options(app_name: &str, options: AppLaunchOptions) -> Result<(), Box<dyn std::error::Error>> {
     let mut cache = APP_CACHE.lock().map_err(|e| format!("Lock error: {:?}", e))?;
     get_or_create_entry(&mut cache, app_name).launch_options = Some(options);
@@ -333,7 +661,17 @@ fn get_all_cached_apps() -> Vec<App> {
 
 impl IconManager {
     pub fn new() -> Self {
-        Self { icon_textures: HashMap::new() }
+        Self { icon_textures: HashMap::new(), symbolic_color: None, animate: true, epoch: time::Instant::now() }
+    }
+
+    pub fn with_symbolic_color(mut self, color: egui::Color32) -> Self {
+        self.symbolic_color = Some(color);
+        self
+    }
+
+    pub fn with_animation(mut self, animate: bool) -> Self {
+        self.animate = animate;
+        self
     }
 
     pub fn get_texture(&mut self, ctx: &egui::Context, icon_path: &str) -> Option<egui::TextureHandle> {
@@ -346,16 +684,37 @@ impl IconManager {
             });
 
         if needs_reload {
-            let img = Self::load_image(icon_path).unwrap_or_else(|_| Self::create_placeholder());
-            let tex = ctx.load_texture(icon_path, img, Default::default());
+            let frames = if self.animate {
+                load_animation(icon_path).unwrap_or_else(|e| {
+                    tracing::warn!(target: "icons", "Failed to decode animation {icon_path}: {e}");
+                    Vec::new()
+                })
+            } else {
+                Vec::new()
+            };
+            let frames: Vec<_> = frames.into_iter().enumerate()
+                .map(|(i, (img, delay))| (ctx.load_texture(format!("{icon_path}#{i}"), img, Default::default()), delay))
+                .collect();
+            let texture = match frames.first() {
+                Some((first, _)) => first.clone(),
+                None => {
+                    let mut img = Self::load_image(icon_path).unwrap_or_else(|_| Self::create_placeholder());
+                    if let Some(color) = self.symbolic_color.filter(|_| is_symbolic(icon_path)) {
+                        recolor_symbolic(&mut img, color);
+                    }
+                    ctx.load_texture(icon_path, img, Default::default())
+                }
+            };
             self.icon_textures.insert(icon_path.to_owned(), IconCache {
-                texture:       Some(tex.clone()),
+                texture:       Some(texture),
+                frames,
                 last_modified: fs::metadata(icon_path).and_then(|m| m.modified()).ok(),
             });
-            Some(tex)
-        } else {
-            self.icon_textures.get(icon_path).and_then(|c| c.texture.clone())
         }
+
+        let cache = self.icon_textures.get(icon_path)?;
+        if cache.frames.is_empty() { return cache.texture.clone(); }
+        Some(current_frame(ctx, self.epoch.elapsed(), &cache.frames))
     }
 
     fn load_image(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
@@ -365,7 +724,7 @@ impl IconManager {
             return Ok(egui::ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &rgba));
         }
         if lower.ends_with(".png") { return load_png(path); }
-        if lower.ends_with(".jpg") || lower.ends_with(".jpeg") { return load_jpeg(path); }
+        if lower.ends_with(".jpg") || lower.ends_with(".jpeg") || lower.ends_with(".gif") { return load_with_image(path); }
         if lower.ends_with(".ico") {
             if let Ok(img) = load_ico(path) { return Ok(img); }
             if let Ok(img) = load_png(path) { return Ok(img); }
@@ -378,12 +737,55 @@ impl IconManager {
     }
 }
 
+/// The frame showing `elapsed` into the loop; schedules a repaint for when
+/// the next one is due.
+fn current_frame(ctx: &egui::Context, elapsed: time::Duration, frames: &[(egui::TextureHandle, time::Duration)])
+    -> egui::TextureHandle
+{
+    let total: u128 = frames.iter().map(|(_, d)| d.as_millis()).sum();
+    let mut t = elapsed.as_millis() % total.max(1);
+    for (tex, delay) in frames {
+        let ms = delay.as_millis();
+        if t < ms {
+            ctx.request_repaint_after(time::Duration::from_millis((ms - t) as u64));
+            return tex.clone();
+        }
+        t -= ms;
+    }
+    frames[0].0.clone()
+}
+
+/// `name-symbolic.svg`: a monochrome icon meant to be drawn in the text color.
+fn is_symbolic(path: &str) -> bool {
+    path.to_lowercase().ends_with("-symbolic.svg")
+}
+
+/// Paints the foreground of a symbolic icon `color`, like GTK does. Symbolic
+/// icons draw it in a neutral gray; colored parts (the success/warning/error
+/// accents) keep their color.
+fn recolor_symbolic(img: &mut egui::ColorImage, color: egui::Color32) {
+    for px in &mut img.pixels {
+        let [r, g, b, a] = px.to_srgba_unmultiplied();
+        if a == 0 || r.max(g).max(b) - r.min(g).min(b) > 24 { continue; }
+        let alpha = (a as u16 * color.a() as u16 / 255) as u8;
+        *px = egui::Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha);
+    }
+}
+
 // ── per-format image decoders (replace the `image` crate) ───────────────────
 
 fn load_png(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
+    read_png(std::io::BufReader::new(std::fs::File::open(path)?))
+}
+
+/// PNG bytes held in memory (e.g. a DBusMenu item's `icon-data`).
+pub fn decode_png(bytes: &[u8]) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
+    read_png(std::io::Cursor::new(bytes))
+}
+
+fn read_png(reader: impl std::io::BufRead + std::io::Seek) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
     // png 0.18: Decoder requires BufRead + Seek; output_buffer_size() returns Option<usize>
-    let file    = std::io::BufReader::new(std::fs::File::open(path)?);
-    let decoder = png::Decoder::new(file);
+    let decoder = png::Decoder::new(reader);
     let mut reader = decoder.read_info()?;
     let mut buf = vec![0u8; reader.output_buffer_size().unwrap_or(0)];
     let info = reader.next_frame(&mut buf)?;
@@ -399,7 +801,8 @@ fn load_png(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>>
     Ok(egui::ColorImage::from_rgba_unmultiplied([w, h], &rgba))
 }
 
-fn load_jpeg(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
+/// JPEG, and the first frame of a GIF.
+fn load_with_image(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
     use image::GenericImageView;
     let img = image::open(path)?;
     let (w, h) = img.dimensions();
@@ -407,6 +810,35 @@ fn load_jpeg(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>>
     Ok(egui::ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &rgba))
 }
 
+/// Frames of an animated GIF or APNG with their delays. Empty for still
+/// images (including single-frame GIFs and plain PNGs) and other formats.
+fn load_animation(path: &str) -> Result<Vec<(egui::ColorImage, time::Duration)>, Box<dyn std::error::Error>> {
+    use image::AnimationDecoder;
+    let lower = path.to_lowercase();
+    let file  = std::io::BufReader::new(fs::File::open(path)?);
+    let frames = if lower.ends_with(".gif") {
+        image::codecs::gif::GifDecoder::new(file)?.into_frames()
+    } else if lower.ends_with(".png") {
+        let decoder = image::codecs::png::PngDecoder::new(file)?;
+        if !decoder.is_apng()? { return Ok(Vec::new()); }
+        decoder.apng()?.into_frames()
+    } else {
+        return Ok(Vec::new());
+    };
+
+    let frames = frames.take(MAX_ANIMATION_FRAMES).map(|frame| {
+        let frame = frame?;
+        let (num, den) = frame.delay().numer_denom_ms();
+        let ms = num.checked_div(den).unwrap_or(0);
+        // Browsers play 0–10ms delays at 100ms, and files are authored for that.
+        let delay = time::Duration::from_millis(if ms <= 10 { 100 } else { ms.into() });
+        let buf = frame.into_buffer();
+        let img = egui::ColorImage::from_rgba_unmultiplied([buf.width() as usize, buf.height() as usize], buf.as_raw());
+        Ok((img, delay))
+    }).collect::<Result<Vec<_>, image::ImageError>>()?;
+    Ok(if frames.len() > 1 { frames } else { Vec::new() })
+}
+
 fn load_ico(path: &str) -> Result<egui::ColorImage, Box<dyn std::error::Error>> {
     let file  = std::fs::File::open(path)?;
     let icon  = ico::IconDir::read(file)?;
@@ -460,7 +892,16 @@ pub fn resolve_icon_path(app_name: &str, icon_name: &str, config: &crate::gui::C
         if let Some(icon_file) = find_icon_in_directory(icon_name) { return Some(icon_file); }
     }
 
-    find_system_icon(icon_name)
+    find_system_icon(icon_name, config)
+}
+
+/// A custom icon from an app's settings window: a file (`~/` is expanded) or
+/// an icon name looked up in the theme.
+fn resolve_custom_icon(icon: &str, config: &crate::gui::Config) -> Option<String> {
+    if !config.enable_icons { return None; }
+    let path = expand_home(icon);
+    if path.is_file() { return Some(path.to_string_lossy().into_owned()); }
+    find_system_icon(icon, config)
 }
 
 fn find_steam_icon(appid: &str) -> Option<String> {
@@ -491,22 +932,9 @@ fn find_icon_in_directory(dir: &str) -> Option<String> {
         .and_then(|entry| entry.path().to_str().map(String::from))
 }
 
-fn find_system_icon(icon_name: &str) -> Option<String> {
-    const THEMES:     &[&str] = &["hicolor", "Adwaita", "gnome", "breeze", "oxygen"];
-    const SIZES:      &[&str] = &["512x512", "256x256", "128x128", "64x64", "48x48", "32x32", "24x24", "16x16", "scalable"];
-    const CATEGORIES: &[&str] = &["apps", "devices", "places", "mimetypes", "status", "actions"];
-
-    let base_paths = get_icon_search_paths();
-
-    // Pass 1: structured icon-theme layout  (base/theme/size/category/name.ext)
-    let themed = base_paths.iter()
-        .flat_map(|base| THEMES.iter().map(move |theme| base.join(theme)))
-        .flat_map(|tp| SIZES.iter().map(move |sz| tp.join(sz)))
-        .flat_map(|sp| CATEGORIES.iter().map(move |cat| sp.join(cat)))
-        .flat_map(|cp| ICON_EXTS.iter().map(move |ext| cp.join(format!("{}.{}", icon_name, ext))))
-        .find(|p| p.exists());
-
-    if let Some(p) = themed { return p.to_str().map(String::from); }
+fn find_system_icon(icon_name: &str, config: &crate::gui::Config) -> Option<String> {
+    // Pass 1: the icon theme (index.theme sizes, Inherits, hicolor).
+    if let Some(p) = crate::icon_theme::lookup(icon_name, ICON_SIZE, config) { return Some(p); }
 
     // Pass 2: flat directories — pixmaps layout is name.ext directly in the folder,
     // NOT theme/size/category/name.ext. Many distros ship icons here.
@@ -545,305 +973,1871 @@ fn get_icon_search_paths() -> Vec<PathBuf> {
 // Desktop entry parsing
 // ============================================================================
 
-fn parse_desktop_entry(path: &Path) -> Option<(String, String, String)> {
+/// A parsed entry that passes the visibility rules, `TryExec` included.
+fn parse_desktop_entry(path: &Path) -> Option<DesktopEntry> {
+    read_desktop_entry(path).filter(DesktopEntry::is_available)
+}
+
+/// Parse `path`, applying every visibility rule except `TryExec`.
+fn read_desktop_entry(path: &Path) -> Option<DesktopEntry> {
     let content  = fs::read_to_string(path).ok()?;
-    let mut name     = None;
+    let mut name     = Localized::default();
+    let mut generic  = Localized::default();
+    let mut comment  = Localized::default();
     let mut exec     = None;
     let mut icon     = None;
     let mut wm_class = None;
+    let mut categories: Vec<String> = Vec::new();
+    let mut action_ids: Vec<String> = Vec::new();
+    let mut entry_type   = None;
+    let mut try_exec     = None;
+    let mut no_display   = false;
+    let mut hidden       = false;
+    let mut terminal     = false;
+    let mut autostart_disabled = false;
+    let mut only_show_in: Option<Vec<String>> = None;
+    let mut not_show_in:  Vec<String>         = Vec::new();
+    // (id, Name, Exec) for every `[Desktop Action id]` group, in file order.
+    let mut action_groups: Vec<(String, Localized, Option<String>)> = Vec::new();
+    let mut group = String::new();
 
     for line in content.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            let value = value.trim().to_string();
-            match key.trim() {
-                "Name"           if name.is_none()     => name     = Some(value),
+        let line = line.trim();
+        if line.starts_with('#') { continue; }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            group = header.to_string();
+            if let Some(id) = group.strip_prefix("Desktop Action ") {
+                action_groups.push((id.trim().to_string(), Localized::default(), None));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim().to_string();
+        // `Name[de_DE]=…` → ("Name", Some("de_DE"))
+        let (key, locale) = match key.trim().split_once('[') {
+            Some((base, rest)) => (base, rest.strip_suffix(']')),
+            None               => (key.trim(), None),
+        };
+
+        if group == "Desktop Entry" || group.is_empty() {
+            match key {
+                "Name"        => name.offer(locale, value),
+                "GenericName" => generic.offer(locale, value),
+                "Comment"     => comment.offer(locale, value),
+                _ if locale.is_some() => {}
                 "Exec"           if exec.is_none()     => exec     = Some(value),
                 "Icon"           if icon.is_none()     => icon     = Some(value),
                 "StartupWMClass" if wm_class.is_none() => wm_class = Some(value),
+                "Type"           if entry_type.is_none() => entry_type = Some(value),
+                "TryExec"        if try_exec.is_none()   => try_exec   = Some(value),
+                "NoDisplay"  => no_display   = value == "true",
+                "Hidden"     => hidden       = value == "true",
+                "Terminal"   => terminal     = value == "true",
+                "OnlyShowIn" => only_show_in = Some(split_list(&value)),
+                "NotShowIn"  => not_show_in  = split_list(&value),
+                "Actions"    => action_ids   = split_list(&value),
+                "Categories" => categories   = split_list(&value),
+                "X-GNOME-Autostart-enabled" => autostart_disabled = value == "false",
+                _ => {}
+            }
+        } else if group.starts_with("Desktop Action ")
+            && let Some((_, a_name, a_exec)) = action_groups.last_mut()
+        {
+            match key {
+                "Name" => a_name.offer(locale, value),
+                "Exec" if locale.is_none() && a_exec.is_none() => *a_exec = Some(value),
                 _ => {}
             }
         }
     }
 
-    let mut exec = exec?;
-
-    // Strip all field codes per FreeDesktop Desktop Entry Specification §7.
-    // File/URL codes are removed — we launch without file arguments.
-    // Deprecated codes (%d %D %n %N %v %m) are removed per spec.
-    for placeholder in ["%f", "%F", "%u", "%U", "%c", "%k",
-                         "%d", "%D", "%n", "%N", "%v", "%m", "@@"] {
-        exec = exec.replace(placeholder, "");
-    }
-    // %i expands to "--icon <name>" per spec; remove entirely if no icon.
-    if let Some(icon_val) = &icon {
-        exec = exec.replace("%i", &format!("--icon {}", icon_val));
-    } else {
-        exec = exec.replace("%i", "");
-    }
-    // StartupWMClass is a window-manager hint for taskbar matching.
+    // Visibility rules from Desktop Entry Specification §6. Missing Type is
+    // tolerated because plenty of hand-written entries in ~/.local omit it.
+    if entry_type.as_deref().is_some_and(|t| t != "Application") { return None; }
+    if hidden { return None; }
+    let desktops = current_desktops();
+    if let Some(only) = &only_show_in && !only.iter().any(|d| desktops.contains(d)) { return None; }
+    if not_show_in.iter().any(|d| desktops.contains(d)) { return None; }
+
+    let display_name = name.value.clone()?;
+    let path_str     = path.to_string_lossy();
+    let codes = crate::exec::FieldCodes { name: &display_name, icon: icon.as_deref(), path: &path_str };
+    let Some(exec) = crate::exec::normalize(&exec?, &codes) else {
+        tracing::warn!(target: "apps", "Skipping {}: malformed Exec line", path.display());
+        return None;
+    };
+    // StartupWMClass is a window-manager hint, kept only for focus-or-launch.
     // It must NOT be passed as --class to the executable — apps like
     // Blender and EasyEffects do not accept that flag and exit silently.
-    let _ = wm_class; // suppress unused-variable warning
 
-    Some((name?, exec.trim().to_string(), icon.unwrap_or_default()))
+    // Only actions named in `Actions=` are valid, and they appear in that order.
+    let actions = action_ids.iter()
+        .filter_map(|id| {
+            let (_, a_name, a_exec) = action_groups.iter().find(|(gid, _, _)| gid == id)?;
+            Some(DesktopAction {
+                name: a_name.value.clone()?,
+                exec: crate::exec::normalize(a_exec.as_deref()?, &codes)?,
+            })
+        })
+        .collect();
+
+    Some(DesktopEntry {
+        name:          display_name,
+        generic_name:  generic.value,
+        comment:       comment.value,
+        // The untranslated Name stays searchable when a translation is shown.
+        untranslated:  name.default.filter(|_| name.rank < LOCALES.len()),
+        exec,
+        icon:          icon.unwrap_or_default(),
+        actions,
+        wm_class,
+        terminal,
+        categories,
+        no_display,
+        autostart_disabled,
+        try_exec,
+        file:          path.to_path_buf(),
+    })
 }
 
-fn get_desktop_entries() -> Vec<(String, String, String)> {
-    let data_home = crate::paths::data_home();
-    let mut app_dirs: Vec<PathBuf> = crate::paths::data_dirs().into_iter()
-        .map(|d| d.join("applications"))
-        .collect();
-    app_dirs.push(data_home.join("applications"));
-    app_dirs.push(data_home.join("flatpak/exports/share/applications"));
-    app_dirs.into_iter()
-        .filter_map(|dir| fs::read_dir(dir).ok())
-        .flatten()
-        .filter_map(Result::ok)
-        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "desktop"))
-        .filter_map(|entry| parse_desktop_entry(&entry.path()))
-        .collect()
+impl DesktopEntry {
+    fn is_available(&self) -> bool {
+        self.try_exec.as_deref().is_none_or(is_executable_available)
+    }
 }
 
-// ============================================================================
-// Steam integration
-// ============================================================================
+/// Locale suffixes to try for `Key[locale]` lookups, best match first, per
+/// Desktop Entry Specification §5: for `LC_MESSAGES=sr_YU.UTF-8@Latn` that's
+/// `sr_YU@Latn`, `sr_YU`, `sr@Latn`, `sr`.
+static LOCALES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let raw = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    locale_candidates(&raw)
+});
 
-fn get_steam_entries() -> Vec<(String, String, String)> {
-    let home = match std::env::var("HOME") {
-        Ok(home) => home,
-        Err(_)   => return Vec::new(),
+fn locale_candidates(raw: &str) -> Vec<String> {
+    if raw.is_empty() || raw == "C" || raw == "POSIX" { return Vec::new(); }
+    let (rest, modifier) = match raw.split_once('@') {
+        Some((r, m)) => (r, Some(m)),
+        None         => (raw, None),
+    };
+    let rest = rest.split('.').next().unwrap_or(rest);
+    let (lang, country) = match rest.split_once('_') {
+        Some((l, c)) => (l, Some(c)),
+        None         => (rest, None),
     };
-    let steam_path = PathBuf::from(&home).join(".local/share/Steam");
-    if !steam_path.exists() { return Vec::new(); }
 
-    let library_paths = get_steam_library_paths(&steam_path);
-    let mut seen_appids = HashSet::new();
+    let mut out = Vec::new();
+    if let (Some(c), Some(m)) = (country, modifier) { out.push(format!("{lang}_{c}@{m}")); }
+    if let Some(c) = country                         { out.push(format!("{lang}_{c}")); }
+    if let Some(m) = modifier                        { out.push(format!("{lang}@{m}")); }
+    out.push(lang.to_string());
+    out
+}
 
-    library_paths.into_iter()
-        .map(|lib| lib.join("steamapps"))
-        .filter(|path| path.exists())
-        .filter_map(|steamapps| fs::read_dir(steamapps).ok())
-        .flatten()
-        .filter_map(Result::ok)
-        .filter(|entry| entry.file_name().to_string_lossy().starts_with("appmanifest_"))
-        .filter_map(|entry| parse_steam_manifest(&entry.path(), &mut seen_appids))
-        .collect()
+/// Best-so-far value of a localestring key. `rank` is the position of the
+/// matched locale in `LOCALES` (0 = best); the unlocalized key ranks last.
+struct Localized {
+    value:   Option<String>,
+    default: Option<String>,
+    rank:    usize,
 }
 
-fn get_steam_library_paths(steam_path: &PathBuf) -> Vec<PathBuf> {
-    let mut paths = vec![steam_path.clone()];
-    if let Ok(content) = fs::read_to_string(steam_path.join("steamapps/libraryfolders.vdf")) {
-        for line in content.lines() {
-            if line.contains("\"path\"") {
-                if let Some(path_str) = line.split('"').nth(3) {
-                    let lib_path = PathBuf::from(path_str);
-                    if lib_path.exists() && !paths.contains(&lib_path) {
-                        paths.push(lib_path);
-                    }
-                }
+impl Default for Localized {
+    fn default() -> Self { Localized { value: None, default: None, rank: usize::MAX } }
+}
+
+impl Localized {
+    fn offer(&mut self, locale: Option<&str>, value: String) {
+        let rank = match locale {
+            None => {
+                if self.default.is_none() { self.default = Some(value.clone()); }
+                LOCALES.len()
             }
+            Some(l) => match LOCALES.iter().position(|c| c == l) {
+                Some(r) => r,
+                None    => return,
+            },
+        };
+        if rank < self.rank {
+            self.rank  = rank;
+            self.value = Some(value);
         }
     }
-    paths
 }
 
-fn parse_steam_manifest(path: &PathBuf, seen_appids: &mut HashSet<String>) -> Option<(String, String, String)> {
-    let content    = fs::read_to_string(path).ok()?;
-    let mut appid  = None;
-    let mut name   = None;
-    let mut installdir = None;
+/// Split a `;`-separated desktop-entry list value, dropping empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if appid.is_none()      { appid      = extract_quoted_value(trimmed, "appid"); }
-        if name.is_none()       { name       = extract_quoted_value(trimmed, "name"); }
-        if installdir.is_none() { installdir = extract_quoted_value(trimmed, "installdir"); }
-    }
+/// The colon-separated `$XDG_CURRENT_DESKTOP` list (e.g. "ubuntu:GNOME").
+fn current_desktops() -> Vec<String> {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
 
-    let appid      = appid?;
-    let name       = name?;
-    let installdir = installdir?;
+/// `TryExec` check: absolute paths must exist and be executable, bare names
+/// are looked up in `$PATH`.
+fn is_executable_available(bin: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let is_exec = |p: &Path| fs::metadata(p).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+    if bin.contains('/') { return is_exec(Path::new(bin)); }
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| is_exec(&dir.join(bin))))
+        .unwrap_or(false)
+}
+
+/// Every executable file name on `$PATH`, sorted and deduplicated — the
+/// same set dmenu_run offers.
+fn path_executables() -> Vec<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let Some(path) = std::env::var_os("PATH") else { return Vec::new() };
+    let mut bins: Vec<String> = std::env::split_paths(&path)
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter(|e| fs::metadata(e.path()).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0))
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    bins.sort_unstable();
+    bins.dedup();
+    bins
+}
 
-    if !seen_appids.insert(appid.clone()) { return None; }
+/// Icon shown for `$PATH` binaries, so they stand apart from desktop apps.
+const PATH_BIN_ICON: &str = "utilities-terminal";
 
-    let icon_path = determine_steam_icon_path(path, &appid, &installdir);
-    Some((name, format!("steam steam://rungameid/{}", appid), icon_path))
+/// `$PATH` executables as apps, skipping any that a desktop entry already
+/// launches (matched on the Exec program's basename) or is named after.
+fn get_path_entries(desktop_apps: &[App]) -> Vec<App> {
+    let covered: HashSet<String> = desktop_apps.iter()
+        .flat_map(|app| {
+            let program = exec_program(&app.exec);
+            program.into_iter().chain(std::iter::once(app.name_lower.clone()))
+        })
+        .collect();
+    path_executables().into_iter()
+        .filter(|bin| !covered.contains(bin) && !covered.contains(&bin.to_lowercase()))
+        .map(|bin| App::new(bin.clone(), bin, PATH_BIN_ICON.into()))
+        .collect()
 }
 
-fn extract_quoted_value(line: &str, key: &str) -> Option<String> {
-    line.starts_with(&format!("\"{}\"", key))
-        .then(|| line.split('"').nth(3).map(String::from))
-        .flatten()
+/// Basename of the program an Exec line runs, skipping an `env VAR=…` prefix.
+fn exec_program(exec: &str) -> Option<String> {
+    crate::exec::split(exec)?.into_iter()
+        .find(|w| w != "env" && !w.contains('='))
+        .and_then(|p| p.rsplit('/').next().map(str::to_string))
 }
 
-fn determine_steam_icon_path(manifest_path: &PathBuf, appid: &str, installdir: &str) -> String {
-    let icon_dir = manifest_path.parent().unwrap().join("common").join(installdir);
-    if icon_dir.exists() && has_icon_files(&icon_dir) {
-        icon_dir.to_string_lossy().to_string()
-    } else {
-        format!("steam_icon:{}", appid)
+/// Directories holding `.desktop` files, in precedence order: a desktop-file
+/// ID seen in an earlier directory shadows the same ID further down, so a
+/// user's `~/.local/share/applications/foo.desktop` with `Hidden=true` (or
+/// `NoDisplay=true`) removes the system-wide `foo.desktop` as well.
+fn application_dirs() -> Vec<PathBuf> {
+    let data_home = crate::paths::data_home();
+    let mut app_dirs: Vec<PathBuf> = vec![
+        data_home.join("applications"),
+        data_home.join("flatpak/exports/share/applications"),
+    ];
+    app_dirs.extend(crate::paths::data_dirs().into_iter().map(|d| d.join("applications")));
+    app_dirs
+}
+
+/// Menu entries from every application directory, refreshing the desktop
+/// index (and saving it) when files changed since it was written.
+fn get_desktop_entries() -> Vec<DesktopEntry> {
+    // Refresh a copy so `indexed_desktop_apps` never waits on the disk walk.
+    let index = DESKTOP_INDEX.lock().map(|index| index.clone()).unwrap_or_default();
+    let fresh = index.refresh();
+    if fresh != index {
+        if let Err(e) = fresh.save() { tracing::warn!(target: "apps", "Desktop index: {e}"); }
+        if let Ok(mut guard) = DESKTOP_INDEX.lock() { *guard = fresh.clone(); }
     }
+    fresh.entries()
 }
 
-fn has_icon_files(dir: &PathBuf) -> bool {
-    fs::read_dir(dir)
-        .ok()
-        .map(|entries| entries.filter_map(Result::ok).any(|entry| {
-            entry.path().extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ICON_EXTS.contains(&ext.to_lowercase().as_str()))
-                .unwrap_or(false)
-        }))
-        .unwrap_or(false)
+/// Name and icon of each menu entry, parsing every `.desktop` file afresh as
+/// a first start does; for `crate::profile`.
+pub(crate) fn scan_desktop_files() -> Vec<(String, String)> {
+    DesktopIndex::default().refresh().entries().into_iter().map(|entry| (entry.name, entry.icon)).collect()
+}
+
+/// Name and Exec line of an autostart entry, or `None` when it's hidden,
+/// disabled, or not meant for this desktop.
+pub(crate) fn parse_autostart_entry(path: &Path) -> Option<(String, String)> {
+    parse_desktop_entry(path)
+        .filter(|entry| !entry.autostart_disabled && !entry.exec.is_empty())
+        .map(|entry| (entry.name, entry.exec))
 }
 
 // ============================================================================
-// Search helpers
+// Desktop entry index
 // ============================================================================
 
-/// Filter `apps` by `query_lower` (already lowercased by the caller) and
-/// return up to `max` indices. Storing indices avoids cloning strings on
-/// every keystroke.
-fn search_apps(query_lower: &str, apps: &[App], max: usize) -> Vec<usize> {
-    apps.iter()
-        .enumerate()
-        .filter(|(_, app)| app.name_lower.contains(query_lower))
-        .take(max)
-        .map(|(i, _)| i)
+/// Parsed `.desktop` files as of the last scan, kept in
+/// `~/.cache/tusk-launcher/desktop_index.txt` so the menu is full at startup.
+/// A rescan only lists directories whose mtime moved and only re-parses
+/// files whose mtime moved.
+#[derive(Clone, Default, PartialEq, Debug)]
+struct DesktopIndex {
+    /// Locales and desktops the entries were parsed for; any change
+    /// re-parses everything, since both decide names and visibility.
+    key:  String,
+    dirs: Vec<IndexedDir>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct IndexedDir {
+    path:  PathBuf,
+    mtime: u128,
+    files: Vec<IndexedFile>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct IndexedFile {
+    path:  PathBuf,
+    mtime: u128,
+    /// `None` for hidden or unparseable files, which still shadow their ID.
+    entry: Option<DesktopEntry>,
+}
+
+static DESKTOP_INDEX: LazyLock<Mutex<DesktopIndex>> = LazyLock::new(|| Mutex::new(DesktopIndex::load()));
+
+fn desktop_index_path() -> PathBuf {
+    crate::paths::cache_home().join("tusk-launcher/desktop_index.txt")
+}
+
+fn index_key() -> String {
+    format!("{}|{}", LOCALES.join(";"), current_desktops().join(":"))
+}
+
+/// Modification time in nanoseconds since the epoch (following symlinks,
+/// as Flatpak's exports are).
+fn file_mtime(path: &Path) -> Option<u128> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(time::UNIX_EPOCH).ok()?.as_nanos())
+}
+
+fn list_desktop_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir).into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+        .collect();
+    files.sort();
+    files
+}
+
+impl DesktopIndex {
+    fn load() -> Self {
+        let path = desktop_index_path();
+        let Ok(text) = fs::read_to_string(&path) else { return DesktopIndex::default() };
+        DesktopIndex::deserialize(&text)
+            .filter(|index| index.key == index_key())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = desktop_index_path();
+        if let Some(parent) = path.parent() { fs::create_dir_all(parent)?; }
+        fs::write(path, self.serialize())?;
+        Ok(())
+    }
+
+    /// The index as it is on disk now, re-parsing only what changed.
+    fn refresh(&self) -> DesktopIndex {
+        let key = index_key();
+        let known: HashMap<&Path, &IndexedFile> = if self.key == key {
+            self.dirs.iter().flat_map(|dir| &dir.files).map(|file| (file.path.as_path(), file)).collect()
+        } else {
+            HashMap::new()
+        };
+        let mut stale = Vec::new();
+        let mut dirs: Vec<IndexedDir> = application_dirs().into_iter()
+            .filter_map(|path| {
+                let mtime = file_mtime(&path)?;
+                // Adding, removing or renaming a file moves the directory's
+                // mtime; editing one in place doesn't, so files are still stat'ed.
+                let listing = match self.dirs.iter().find(|dir| dir.path == path && dir.mtime == mtime) {
+                    Some(dir) => dir.files.iter().map(|file| file.path.clone()).collect(),
+                    None      => list_desktop_files(&path),
+                };
+                let files = listing.into_iter()
+                    .filter_map(|file| {
+                        let mtime = file_mtime(&file)?;
+                        match known.get(file.as_path()) {
+                            Some(old) if old.mtime == mtime => Some((*old).clone()),
+                            _ => {
+                                stale.push(file.clone());
+                                Some(IndexedFile { path: file, mtime, entry: None })
+                            }
+                        }
+                    })
+                    .collect();
+                Some(IndexedDir { path, mtime, files })
+            })
+            .collect();
+
+        // Parsing is the slow part on a cold start (hundreds of files), so
+        // it's spread over the cores.
+        let parsed: HashMap<PathBuf, Option<DesktopEntry>> = crate::pool::map(stale, |path| {
+            let entry = read_desktop_entry(&path);
+            (path, entry)
+        }).into_iter().collect();
+        for file in dirs.iter_mut().flat_map(|dir| &mut dir.files) {
+            if let Some(entry) = parsed.get(&file.path) { file.entry = entry.clone(); }
+        }
+        DesktopIndex { key, dirs }
+    }
+
+    /// Entries for the menu: the first file with each ID, minus hidden ones.
+    fn entries(&self) -> Vec<DesktopEntry> {
+        let mut seen_ids = HashSet::new();
+        self.dirs.iter()
+            .flat_map(|dir| &dir.files)
+            .filter(|file| seen_ids.insert(file.path.file_name().map(|n| n.to_os_string())))
+            .filter_map(|file| file.entry.clone())
+            .filter(|entry| !entry.no_display && entry.is_available())
+            .collect()
+    }
+
+    /// `DIR` and `FILE` lines in scan order; a parsed file's `FILE` line is
+    /// followed by an `ENTRY` line and one `ACTION` line per action.
+    fn serialize(&self) -> String {
+        let opt = |s: &Option<String>| s.as_deref().map(escape).unwrap_or_default();
+        let mut s = format!("DESKTOP_INDEX_V1\t{}\n", escape(&self.key));
+        for dir in &self.dirs {
+            s.push_str(&format!("DIR\t{}\t{}\n", escape(&dir.path.to_string_lossy()), dir.mtime));
+            for file in &dir.files {
+                s.push_str(&format!("FILE\t{}\t{}\n", escape(&file.path.to_string_lossy()), file.mtime));
+                let Some(entry) = &file.entry else { continue };
+                let flags: String = [(entry.terminal, 't'), (entry.no_display, 'n'), (entry.autostart_disabled, 'a')]
+                    .into_iter()
+                    .filter_map(|(set, flag)| set.then_some(flag))
+                    .collect();
+                s.push_str(&format!("ENTRY\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                    escape(&entry.name),
+                    opt(&entry.generic_name),
+                    opt(&entry.comment),
+                    opt(&entry.untranslated),
+                    escape(&entry.exec),
+                    escape(&entry.icon),
+                    opt(&entry.wm_class),
+                    escape(&entry.categories.join(";")),
+                    flags,
+                    opt(&entry.try_exec),
+                ));
+                for action in &entry.actions {
+                    s.push_str(&format!("ACTION\t{}\t{}\n", escape(&action.name), escape(&action.exec)));
+                }
+            }
+        }
+        s
+    }
+
+    /// `None` when the text isn't a V1 index; it's rebuilt from scratch then.
+    fn deserialize(text: &str) -> Option<DesktopIndex> {
+        let mut lines = text.lines();
+        let key = unescape(lines.next()?.strip_prefix("DESKTOP_INDEX_V1\t")?);
+        let opt = |s: &str| if s.is_empty() { None } else { Some(unescape(s)) };
+        let mut dirs: Vec<IndexedDir> = Vec::new();
+        for line in lines {
+            let cols: Vec<&str> = line.split('\t').collect();
+            match cols.as_slice() {
+                ["DIR", path, mtime] => dirs.push(IndexedDir {
+                    path:  PathBuf::from(unescape(path)),
+                    mtime: mtime.parse().ok()?,
+                    files: Vec::new(),
+                }),
+                ["FILE", path, mtime] => dirs.last_mut()?.files.push(IndexedFile {
+                    path:  PathBuf::from(unescape(path)),
+                    mtime: mtime.parse().ok()?,
+                    entry: None,
+                }),
+                ["ENTRY", name, generic, comment, untranslated, exec, icon, wm_class, categories, flags, try_exec] => {
+                    let file = dirs.last_mut()?.files.last_mut()?;
+                    let path = file.path.clone();
+                    file.entry = Some(DesktopEntry {
+                        name:          unescape(name),
+                        generic_name:  opt(generic),
+                        comment:       opt(comment),
+                        untranslated:  opt(untranslated),
+                        exec:          unescape(exec),
+                        icon:          unescape(icon),
+                        actions:       Vec::new(),
+                        wm_class:      opt(wm_class),
+                        terminal:      flags.contains('t'),
+                        categories:    split_list(&unescape(categories)),
+                        no_display:    flags.contains('n'),
+                        autostart_disabled: flags.contains('a'),
+                        try_exec:      opt(try_exec),
+                        file:          path,
+                    });
+                }
+                ["ACTION", name, exec] => {
+                    let entry = dirs.last_mut()?.files.last_mut()?.entry.as_mut()?;
+                    entry.actions.push(DesktopAction { name: unescape(name), exec: unescape(exec) });
+                }
+                _ => return None,
+            }
+        }
+        Some(DesktopIndex { key, dirs })
+    }
+}
+
+/// Apps from the desktop index as last saved, without touching the
+/// application directories.
+fn indexed_desktop_apps() -> Vec<App> {
+    DESKTOP_INDEX.lock()
+        .map(|index| index.entries().into_iter().map(App::from).collect())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Flatpak integration
+// ============================================================================
+
+/// One row of `flatpak list --app`.
+#[derive(Debug, PartialEq)]
+struct FlatpakApp {
+    id:     String,
+    name:   String,
+    origin: String,
+    arch:   String,
+    branch: String,
+    /// `user` or `system`; decides where the exported desktop file lives.
+    installation: String,
+}
+
+const FLATPAK_COLUMNS: &str = "application,name,origin,arch,branch,installation";
+
+fn parse_flatpak_list(output: &str) -> Vec<FlatpakApp> {
+    output.lines().filter_map(|line| {
+        let cols: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let [id, name, origin, arch, branch, installation] = cols.as_slice() else { return None };
+        if id.is_empty() { return None; }
+        Some(FlatpakApp {
+            id:     id.to_string(),
+            name:   if name.is_empty() { id } else { name }.to_string(),
+            origin: origin.to_string(),
+            arch:   arch.to_string(),
+            branch: branch.to_string(),
+            installation: installation.to_string(),
+        })
+    }).collect()
+}
+
+/// Installed Flatpak apps, launched with `flatpak run` pinned to the listed
+/// arch and branch. Name, icon, comment and actions come from the exported
+/// desktop file when there is one, so rows line up with the desktop provider's.
+fn get_flatpak_entries() -> Vec<App> {
+    let output = match Command::new("flatpak").args(["list", "--app", &format!("--columns={FLATPAK_COLUMNS}")]).output() {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).into_owned(),
+        _ => return Vec::new(),
+    };
+    let user_exports   = crate::paths::data_home().join("flatpak/exports/share/applications");
+    let system_exports = PathBuf::from("/var/lib/flatpak/exports/share/applications");
+
+    parse_flatpak_list(&output).into_iter().filter_map(|fp| {
+        let exports = if fp.installation == "user" { &user_exports } else { &system_exports };
+        let desktop = exports.join(format!("{}.desktop", fp.id));
+        // An exported entry that's hidden here (NoDisplay, OnlyShowIn, …) hides the app too.
+        let entry = match parse_desktop_entry(&desktop) {
+            Some(entry) if entry.no_display => return None,
+            None if desktop.exists()        => return None,
+            entry                           => entry,
+        };
+        let exec    = crate::exec::join(&[
+            "flatpak".into(), "run".into(),
+            format!("--arch={}", fp.arch), format!("--branch={}", fp.branch),
+            fp.id.clone(),
+        ]);
+        let origin  = format!("Flatpak · {}", fp.origin);
+        let mut app = match entry {
+            Some(entry) => App::from(entry),
+            None        => App::new(fp.name, String::new(), fp.id.clone()),
+        };
+        app.exec        = exec;
+        app.description = Some(match app.description.take() {
+            Some(desc) => format!("{desc}\n{origin}"),
+            None       => origin,
+        });
+        if app.wm_class.is_none() { app.wm_class = Some(fp.id.to_lowercase()); }
+        Some(app)
+    }).collect()
+}
+
+// ============================================================================
+// Steam integration
+// ============================================================================
+
+/// Installed Steam games plus non-Steam shortcuts added to Steam. The row
+/// description names the compatibility tool (Proton version) set for a title.
+fn get_steam_entries() -> Vec<App> {
+    let home = match std::env::var("HOME") {
+        Ok(home) => home,
+        Err(_)   => return Vec::new(),
+    };
+    let steam_path = PathBuf::from(&home).join(".local/share/Steam");
+    if !steam_path.exists() { return Vec::new(); }
+
+    let library_paths = get_steam_library_paths(&steam_path);
+    let mut seen_appids = HashSet::new();
+    let compat_tools = fs::read_to_string(steam_path.join("config/config.vdf"))
+        .map(|content| parse_compat_tools(&content))
+        .unwrap_or_default();
+
+    let manifests: Vec<PathBuf> = library_paths.into_iter()
+        .map(|lib| lib.join("steamapps"))
+        .filter(|path| path.exists())
+        .filter_map(|steamapps| fs::read_dir(steamapps).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("appmanifest_"))
+        .map(|entry| entry.path())
+        .collect();
+    // Parsed in parallel, deduplicated afterwards in library order.
+    let mut games: Vec<(String, App)> = crate::pool::map(manifests, |path| parse_steam_manifest(&path))
+        .into_iter()
+        .flatten()
+        .filter(|(appid, ..)| seen_appids.insert(appid.clone()))
+        .map(|(appid, name, exec, icon)| (appid, App::new(name, exec, icon)))
+        .collect();
+    games.extend(get_steam_shortcuts(&steam_path, &mut seen_appids));
+
+    // Cover art is a handful of `exists` probes per title.
+    crate::pool::map(games, |(appid, mut app)| {
+        app.category = Some("Games");
+        if app.cover.is_none() { app.cover = find_steam_cover(&steam_path, &appid); }
+        if let Some(tool) = compat_tools.get(&appid) {
+            let line = format!("Compatibility tool: {tool}");
+            app.description = Some(match app.description.take() {
+                Some(desc) => format!("{desc}\n{line}"),
+                None       => line,
+            });
+        }
+        app
+    })
+}
+
+fn get_steam_library_paths(steam_path: &PathBuf) -> Vec<PathBuf> {
+    let mut paths = vec![steam_path.clone()];
+    if let Ok(content) = fs::read_to_string(steam_path.join("steamapps/libraryfolders.vdf")) {
+        for line in content.lines() {
+            if line.contains("\"path\"") {
+                if let Some(path_str) = line.split('"').nth(3) {
+                    let lib_path = PathBuf::from(path_str);
+                    if lib_path.exists() && !paths.contains(&lib_path) {
+                        paths.push(lib_path);
+                    }
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// (appid, name, exec, icon) for one `appmanifest_*.acf`.
+fn parse_steam_manifest(path: &PathBuf) -> Option<(String, String, String, String)> {
+    let content    = fs::read_to_string(path).ok()?;
+    let mut appid  = None;
+    let mut name   = None;
+    let mut installdir = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if appid.is_none()      { appid      = extract_quoted_value(trimmed, "appid"); }
+        if name.is_none()       { name       = extract_quoted_value(trimmed, "name"); }
+        if installdir.is_none() { installdir = extract_quoted_value(trimmed, "installdir"); }
+    }
+
+    let appid      = appid?;
+    let name       = name?;
+    let installdir = installdir?;
+
+    let icon_path = determine_steam_icon_path(path, &appid, &installdir);
+    let exec      = format!("steam steam://rungameid/{}", appid);
+    Some((appid, name, exec, icon_path))
+}
+
+/// appid → compatibility tool from the `CompatToolMapping` block of Steam's
+/// `config/config.vdf`. Only per-title choices are kept; the `"0"` entry is
+/// Steam's global default, which also covers native games.
+fn parse_compat_tools(content: &str) -> HashMap<String, String> {
+    let mut tools   = HashMap::new();
+    let mut depth   = None::<usize>;
+    let mut current = None;
+    for line in content.lines().map(str::trim) {
+        let Some(d) = depth else {
+            if line == "\"CompatToolMapping\"" { depth = Some(0); }
+            continue;
+        };
+        match line {
+            "{" => depth = Some(d + 1),
+            "}" if d <= 1 => break,
+            "}" => depth = Some(d - 1),
+            _ if d == 1 => current = line.split('"').nth(1).map(String::from),
+            _ if d == 2 => {
+                let appid = current.as_ref().filter(|id| *id != "0");
+                if let (Some(appid), Some(name)) = (appid, extract_quoted_value(line, "name")) && !name.is_empty() {
+                    tools.insert(appid.clone(), name);
+                }
+            }
+            _ => {}
+        }
+    }
+    tools
+}
+
+// ----------------------------------------------------------------------------
+// Non-Steam shortcuts (userdata/<user>/config/shortcuts.vdf, binary VDF)
+// ----------------------------------------------------------------------------
+
+enum BinVdf {
+    Map(Vec<(String, BinVdf)>),
+    Str(String),
+    Int(u32),
+}
+
+impl BinVdf {
+    /// Case-insensitive lookup; Steam has written both `AppName` and `appname`.
+    fn get(&self, key: &str) -> Option<&BinVdf> {
+        match self {
+            BinVdf::Map(items) => items.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v),
+            _                  => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        if let BinVdf::Str(s) = self { Some(s) } else { None }
+    }
+}
+
+/// Map body: `type name value` records up to a `0x08` terminator. Types are
+/// 0x00 nested map, 0x01 NUL-terminated string, 0x02 little-endian u32.
+fn parse_binary_vdf(data: &[u8], pos: &mut usize) -> Option<BinVdf> {
+    fn cstr(data: &[u8], pos: &mut usize) -> Option<String> {
+        let len = data.get(*pos..)?.iter().position(|&b| b == 0)?;
+        let s   = String::from_utf8_lossy(&data[*pos..*pos + len]).into_owned();
+        *pos += len + 1;
+        Some(s)
+    }
+    let mut items = Vec::new();
+    loop {
+        let kind = *data.get(*pos)?;
+        *pos += 1;
+        if kind == 0x08 { return Some(BinVdf::Map(items)); }
+        let name  = cstr(data, pos)?;
+        let value = match kind {
+            0x00 => parse_binary_vdf(data, pos)?,
+            0x01 => BinVdf::Str(cstr(data, pos)?),
+            0x02 => {
+                let bytes = data.get(*pos..*pos + 4)?;
+                *pos += 4;
+                BinVdf::Int(u32::from_le_bytes(bytes.try_into().ok()?))
+            }
+            _ => return None,
+        };
+        items.push((name, value));
+    }
+}
+
+/// (appid, app) for every non-Steam game in any local user's shortcuts.vdf.
+/// They launch through Steam like regular games, with the 64-bit game id
+/// Steam derives from the shortcut's appid.
+fn get_steam_shortcuts(steam_path: &Path, seen_appids: &mut HashSet<String>) -> Vec<(String, App)> {
+    let Ok(users) = fs::read_dir(steam_path.join("userdata")) else { return Vec::new() };
+    let mut out = Vec::new();
+    for user in users.filter_map(Result::ok) {
+        let config = user.path().join("config");
+        let Ok(data) = fs::read(config.join("shortcuts.vdf")) else { continue };
+        let Some(root) = parse_binary_vdf(&data, &mut 0) else {
+            tracing::warn!(target: "apps", "Failed to parse {}", config.join("shortcuts.vdf").display());
+            continue;
+        };
+        let Some(BinVdf::Map(shortcuts)) = root.get("shortcuts") else { continue };
+        for (_, shortcut) in shortcuts {
+            let Some(BinVdf::Int(appid)) = shortcut.get("appid") else { continue };
+            let Some(name) = shortcut.get("AppName").and_then(BinVdf::as_str).filter(|n| !n.is_empty()) else { continue };
+            if !seen_appids.insert(appid.to_string()) { continue; }
+
+            let game_id = (u64::from(*appid) << 32) | 0x0200_0000;
+            let grid    = config.join("grid");
+            let icon    = shortcut.get("icon").and_then(BinVdf::as_str)
+                .map(|i| i.trim_matches('"').to_string())
+                .filter(|i| !i.is_empty())
+                .or_else(|| [format!("{appid}_icon.png"), format!("{appid}.png"), format!("{appid}p.png")].iter()
+                    .map(|f| grid.join(f))
+                    .find(|p| p.exists())
+                    .map(|p| p.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "steam".into());
+            let mut app = App::new(name.to_string(), format!("steam steam://rungameid/{game_id}"), icon);
+            app.description = Some("Non-Steam game".into());
+            // Custom portrait art set in Steam's "Manage > Set custom artwork".
+            app.cover = [format!("{appid}p.png"), format!("{appid}p.jpg")].iter()
+                .map(|f| grid.join(f))
+                .find(|p| p.exists())
+                .map(|p| p.to_string_lossy().into_owned());
+            out.push((appid.to_string(), app));
+        }
+    }
+    out
+}
+
+/// Library art Steam downloads for an installed game: the 600x900 portrait,
+/// else the wide header. Newer clients keep it in a per-app directory.
+fn find_steam_cover(steam_path: &Path, appid: &str) -> Option<String> {
+    let cache = steam_path.join("appcache/librarycache");
+    [
+        cache.join(format!("{appid}_library_600x900.jpg")),
+        cache.join(appid).join("library_600x900.jpg"),
+        cache.join(format!("{appid}_header.jpg")),
+        cache.join(appid).join("header.jpg"),
+    ].into_iter()
+        .find(|p| p.exists())
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+fn extract_quoted_value(line: &str, key: &str) -> Option<String> {
+    line.starts_with(&format!("\"{}\"", key))
+        .then(|| line.split('"').nth(3).map(String::from))
+        .flatten()
+}
+
+fn determine_steam_icon_path(manifest_path: &PathBuf, appid: &str, installdir: &str) -> String {
+    let icon_dir = manifest_path.parent().unwrap().join("common").join(installdir);
+    if icon_dir.exists() && has_icon_files(&icon_dir) {
+        icon_dir.to_string_lossy().to_string()
+    } else {
+        format!("steam_icon:{}", appid)
+    }
+}
+
+fn has_icon_files(dir: &PathBuf) -> bool {
+    fs::read_dir(dir)
+        .ok()
+        .map(|entries| entries.filter_map(Result::ok).any(|entry| {
+            entry.path().extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ICON_EXTS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false)
+        }))
+        .unwrap_or(false)
+}
+
+// ============================================================================
+// Search helpers
+// ============================================================================
+
+/// Filter `apps` by `query_lower` (already lowercased by the caller), or
+/// by the custom name in `overrides`, and return up to `max` indices.
+/// Storing indices avoids cloning strings on every keystroke.
+fn search_apps(query_lower: &str, apps: &[App], max: usize, overrides: &HashMap<String, AppOverride>) -> Vec<usize> {
+    apps.iter()
+        .enumerate()
+        .filter(|(_, app)| app.matches(query_lower) || overrides.get(&app.name)
+            .and_then(|o| o.name.as_ref())
+            .is_some_and(|name| name.to_lowercase().contains(query_lower)))
+        .take(max)
+        .map(|(i, _)| i)
         .collect()
 }
 
-/// Return indices of the most-recently-used apps.
-///
-/// Old implementation was O(n × m): for each entry in APP_CACHE it did a
-/// linear scan of `apps`. The new version builds a name→index HashMap first
-/// for O(1) lookups, making the whole thing O(n + m).
-fn get_recent_indices(apps: &[App], config: &crate::gui::Config) -> Vec<usize> {
-    let name_to_idx: HashMap<&str, usize> = apps.iter()
-        .enumerate()
-        .map(|(i, app)| (app.name.as_str(), i))
-        .collect();
+/// Case-insensitive shell-style match of `text` against `pattern`, where `*`
+/// is any run of characters and `?` any single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    // Backtrack to the last `*` on a mismatch.
+    let (mut pi, mut ti, mut star) = (0, 0, None);
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Return (index, recents position) of the most-recently-used apps.
+///
+/// Old implementation was O(n × m): for each entry in APP_CACHE it did a
+/// linear scan of `apps`. The new version builds a name→index HashMap first
+/// for O(1) lookups, making the whole thing O(n + m).
+fn get_recent_indices(apps: &[App], max: usize) -> Vec<(usize, usize)> {
+    let name_to_idx: HashMap<&str, usize> = apps.iter()
+        .enumerate()
+        .map(|(i, app)| (app.name.as_str(), i))
+        .collect();
+
+    APP_CACHE.lock()
+        .ok()
+        .map(|cache| {
+            cache.apps.iter()
+                .enumerate()
+                .filter_map(|(rank, (name, _)): (usize, &(String, AppEntry))| {
+                    name_to_idx.get(name.as_str()).map(|&i| (i, rank))
+                })
+                .take(max)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// App launch
+// ============================================================================
+
+fn extract_terminal_command(exec_cmd: &str) -> Option<String> {
+    exec_program(exec_cmd).filter(|s| !s.is_empty() && !s.starts_with('-'))
+}
+
+fn launch_app(
+    app_name: &str,
+    exec_cmd: &str,
+    icon_path: &str,
+    options: &Option<AppLaunchOptions>,
+    enable_recent_apps: bool,
+    terminal: Option<&[String]>,
+    watch: LaunchWatch,
+) -> Result<(), Box<dyn std::error::Error>> {
+    cache_app_metadata(app_name, exec_cmd, icon_path);
+    if enable_recent_apps { update_recent_apps(app_name, true)?; }
+    spawn_with_options(app_name, exec_cmd, options, terminal, watch)
+}
+
+/// Launch one of an app's desktop actions. The action counts as a use of the
+/// parent app for recents, but its Exec line is never written to the cache.
+fn launch_action(
+    app_name: &str,
+    action: &DesktopAction,
+    options: &Option<AppLaunchOptions>,
+    enable_recent_apps: bool,
+    terminal: Option<&[String]>,
+    watch: LaunchWatch,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if enable_recent_apps { update_recent_apps(app_name, true)?; }
+    spawn_with_options(app_name, &action.exec, options, terminal, watch)
+}
+
+/// Launchers whose basename says nothing about the window they end up opening.
+const WRAPPER_PROGRAMS: &[&str] = &["sh", "bash", "flatpak", "steam", "gtk-launch", "xdg-open"];
+
+/// Whether a window of class `class` (lowercased) belongs to `app`. Uses
+/// `StartupWMClass` when the entry sets one; otherwise the icon name (usually
+/// the app id on Wayland) or the Exec program basename.
+fn window_matches(app: &App, class: &str) -> bool {
+    if class.is_empty() { return false; }
+    if let Some(wm_class) = &app.wm_class { return wm_class == class; }
+    let icon_name = (!app.icon.contains('/')).then_some(app.icon.as_str());
+    let program   = exec_program(&app.exec).filter(|p| !WRAPPER_PROGRAMS.contains(&p.as_str()));
+    [icon_name, program.as_deref()].into_iter().flatten().any(|c| c.eq_ignore_ascii_case(class))
+}
+
+/// Focus an open window of `app` if there is one.
+fn focus_existing_window(app: &App) -> bool {
+    crate::windows::list().into_iter()
+        .find(|w| window_matches(app, &w.class))
+        .is_some_and(|w| crate::windows::focus(&w.id))
+}
+
+/// How a process is started: an argv spawned directly, or a line for `sh -c`.
+/// The shell is only used for a user's custom command (or a cached Exec line
+/// that no longer splits), never for what a desktop entry says.
+enum Launch {
+    Argv(Vec<String>),
+    Shell(String),
+}
+
+impl Launch {
+    fn exec(line: &str) -> Self {
+        crate::exec::split(line).map_or_else(|| Launch::Shell(line.to_string()), Launch::Argv)
+    }
+
+    /// The same command inside the first available terminal from `templates`.
+    fn in_terminal(self, templates: &[String]) -> Option<Self> {
+        let argv = match self {
+            Launch::Argv(argv)  => argv,
+            Launch::Shell(line) => vec!["sh".into(), "-c".into(), line],
+        };
+        terminal_argv(&argv, templates).map(Launch::Argv)
+    }
+
+    /// The command as one `sh` line, for splicing and for showing to the user.
+    fn shell_line(&self) -> String {
+        match self {
+            Launch::Argv(argv)  => crate::exec::shell_quote(argv),
+            Launch::Shell(line) => line.clone(),
+        }
+    }
+
+    /// `exec_line` as changed by a custom command: `%command%` is replaced
+    /// by it, a lone word replaces it, and anything else is prepended.
+    fn with_custom(exec_line: &str, custom: Option<&str>) -> Self {
+        let exec = Launch::exec(exec_line);
+        match custom.map(str::trim).filter(|c| !c.is_empty() && *c != "%command%") {
+            None                               => exec,
+            Some(c) if c.contains("%command%") => Launch::Shell(c.replace("%command%", &exec.shell_line())),
+            Some(c) if !c.contains(' ')        => Launch::Shell(c.to_string()),
+            Some(c)                            => Launch::Shell(format!("{c} {}", exec.shell_line())),
+        }
+    }
+
+    fn command(&self) -> Command {
+        match self {
+            Launch::Argv(argv) => {
+                let mut command = Command::new(&argv[0]);
+                command.args(&argv[1..]);
+                command
+            }
+            Launch::Shell(line) => {
+                let mut command = Command::new("sh");
+                command.arg("-c").arg(line);
+                command
+            }
+        }
+    }
+}
+
+/// Apply launch options to `exec_cmd` and spawn it, walking the fallback chain
+/// (custom command → cached terminal command → bare binary) on failure.
+/// `terminal` holds the terminal templates for `Terminal=true` apps, which
+/// get no fallback chain: the terminal itself starting is all we can check.
+/// When every attempt fails, the error is the first attempt's `LaunchFailure`.
+fn spawn_with_options(
+    app_name: &str,
+    exec_cmd: &str,
+    options: &Option<AppLaunchOptions>,
+    terminal: Option<&[String]>,
+    watch: LaunchWatch,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let home_dir = std::env::var("HOME")
+        .map(PathBuf::from)
+        .map_err(|_| "No home directory")?;
+
+    let custom = options.as_ref()
+        .and_then(|opts| opts.custom_command.as_deref())
+        .map(str::trim)
+        .filter(|c| !c.is_empty() && *c != "%command%");
+    let primary = Launch::with_custom(exec_cmd, custom);
+    let dir = options.as_ref()
+        .and_then(|opts| opts.working_directory.as_deref())
+        .map_or(home_dir, expand_home);
+
+    let try_launch = |launch: &Launch| -> Result<(), LaunchFailure> {
+        let failure = |message: String| LaunchFailure {
+            app: app_name.to_string(), command: launch.shell_line(), message,
+        };
+        tracing::debug!(target: "launch", "{app_name}: {} in {}", launch.shell_line(), dir.display());
+        let mut command = launch.command();
+        command.current_dir(&dir);
+        if let Some(opts) = options {
+            for (key, value) in &opts.environment_vars { command.env(key, value); }
+        }
+        let stderr = if watch.capture_stderr { Stdio::piped() } else { Stdio::null() };
+        let mut child = command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(stderr).spawn()
+            .map_err(|e| failure(e.to_string()))?;
+        let stderr = child.stderr.take().map(StderrTail::collect);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        match child.try_wait() {
+            Ok(Some(status)) if !status.success() => Err(failure(exit_message(status, stderr.as_ref()))),
+            Ok(Some(_)) => Ok(()),
+            Ok(None)    => { watch_child(child, stderr, failure(String::new()), watch.window); Ok(()) }
+            Err(e)      => Err(failure(e.to_string())),
+        }
+    };
+
+    if let Some(templates) = terminal {
+        let wrapped = primary.in_terminal(templates).ok_or("No terminal emulator found")?;
+        return try_launch(&wrapped).map_err(Into::into);
+    }
+
+    let Err(first) = try_launch(&primary) else { return Ok(()) };
+    let fallbacks = [
+        custom.map(|c| Launch::Shell(c.to_string())),
+        get_cached_data(app_name).and_then(|(_, _, terminal_cmd)| terminal_cmd).map(|c| Launch::exec(&c)),
+        extract_terminal_command(exec_cmd).map(|c| Launch::Argv(vec![c])),
+    ];
+    if fallbacks.iter().flatten().any(|launch| try_launch(launch).is_ok()) { return Ok(()); }
+    Err(first.into())
+}
+
+// ============================================================================
+// Launch failures
+// ============================================================================
+
+/// A launch that couldn't start, or exited with an error soon after starting.
+#[derive(Clone, Debug)]
+pub struct LaunchFailure {
+    pub app:     String,
+    /// The command line that failed, as `sh` would take it.
+    pub command: String,
+    /// The spawn error or exit status, and the end of what it wrote to stderr.
+    pub message: String,
+}
+
+impl std::fmt::Display for LaunchFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.command)
+    }
+}
+
+impl std::error::Error for LaunchFailure {}
+
+/// How launches are checked after the first 100 ms.
+#[derive(Clone, Copy)]
+struct LaunchWatch {
+    /// An exit with an error this soon after starting is still reported.
+    window:         time::Duration,
+    /// Read the child's stderr. Only in daemon mode: once a one-shot launcher
+    /// exits, a child writing to the pipe would be killed by SIGPIPE.
+    capture_stderr: bool,
+}
+
+/// Failures waiting for the GUI, which shows them as toasts.
+static LAUNCH_FAILURES: LazyLock<Mutex<Vec<LaunchFailure>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+fn report_launch_failure(failure: LaunchFailure) {
+    tracing::warn!(target: "launch", "Failed to launch {}: {failure}", failure.app);
+    if let Ok(mut failures) = LAUNCH_FAILURES.lock() { failures.push(failure); }
+}
+
+pub fn take_launch_failures() -> Vec<LaunchFailure> {
+    LAUNCH_FAILURES.lock().map(|mut f| std::mem::take(&mut *f)).unwrap_or_default()
+}
+
+/// Bytes of a child's stderr kept for the error message.
+const STDERR_TAIL: usize = 2048;
+
+/// The end of a child's stderr, read on a thread until the pipe closes so the
+/// child never blocks on a full pipe.
+struct StderrTail {
+    buf:  Arc<Mutex<Vec<u8>>>,
+    /// Disconnects when the reader thread finishes.
+    done: std::sync::mpsc::Receiver<()>,
+}
+
+impl StderrTail {
+    fn collect(mut pipe: std::process::ChildStderr) -> Self {
+        use std::io::Read;
+        let buf  = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&buf);
+        let (tx, done) = std::sync::mpsc::channel::<()>();
+        thread::spawn(move || {
+            let _tx = tx;
+            let mut chunk = [0u8; 1024];
+            while let Ok(n @ 1..) = pipe.read(&mut chunk) {
+                if let Ok(mut tail) = sink.lock() {
+                    tail.extend_from_slice(&chunk[..n]);
+                    let excess = tail.len().saturating_sub(STDERR_TAIL);
+                    tail.drain(..excess);
+                }
+            }
+        });
+        StderrTail { buf, done }
+    }
+
+    /// The last few lines, after giving the reader a moment to catch up with
+    /// a child that just exited.
+    fn last_lines(&self) -> String {
+        let _ = self.done.recv_timeout(time::Duration::from_millis(100));
+        let text = self.buf.lock().map(|b| String::from_utf8_lossy(&b).into_owned()).unwrap_or_default();
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        lines[lines.len().saturating_sub(5)..].join("\n")
+    }
+}
+
+fn exit_message(status: std::process::ExitStatus, stderr: Option<&StderrTail>) -> String {
+    use std::os::unix::process::ExitStatusExt;
+    let mut message = match (status.code(), status.signal()) {
+        (Some(code), _)   => format!("Exited with status {code}"),
+        (_, Some(signal)) => format!("Killed by signal {signal}"),
+        _                 => "Exited with an error".to_string(),
+    };
+    let tail = stderr.map(StderrTail::last_lines).unwrap_or_default();
+    if !tail.is_empty() {
+        message.push('\n');
+        message.push_str(&tail);
+    }
+    message
+}
+
+/// Wait out the rest of `window` on a thread and report `failure` if the
+/// child exits with an error in that time. The thread then reaps the child.
+fn watch_child(mut child: std::process::Child, stderr: Option<StderrTail>, failure: LaunchFailure, window: time::Duration) {
+    thread::spawn(move || {
+        let started = time::Instant::now();
+        while started.elapsed() < window {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    if !status.success() {
+                        report_launch_failure(LaunchFailure { message: exit_message(status, stderr.as_ref()), ..failure });
+                    }
+                    return;
+                }
+                Ok(None) => thread::sleep(time::Duration::from_millis(50)),
+                Err(_)   => return,
+            }
+        }
+        let _ = child.wait();
+    });
+}
+
+/// Run a free-form shell command from run mode. Unlike app launches there's
+/// no fallback chain: a failing command has simply failed.
+fn run_shell_command(cmd: &str, in_terminal: bool, config: &crate::gui::Config) -> Result<(), Box<dyn std::error::Error>> {
+    let home_dir = std::env::var("HOME").map_err(|_| "No home directory")?;
+    let launch   = if in_terminal {
+        // The shell is exec'd afterwards so output stays readable once `cmd` exits.
+        Launch::Shell(format!("{cmd}; exec \"${{SHELL:-sh}}\""))
+            .in_terminal(&terminal_templates(config))
+            .ok_or("No terminal emulator found")?
+    } else {
+        Launch::Shell(cmd.to_string())
+    };
+    record_run_command(cmd)?;
+    launch.command()
+        .current_dir(home_dir)
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Terminal templates in preference order: `terminal-command`, then the
+/// `terminal-commands` auto-detection list.
+fn terminal_templates(config: &crate::gui::Config) -> Vec<String> {
+    std::iter::once(&config.terminal_command)
+        .filter(|t| !t.trim().is_empty())
+        .chain(&config.terminal_commands)
+        .cloned()
+        .collect()
+}
+
+/// `argv` inside the first installed terminal from `templates`. Each
+/// `%command%` word is replaced by `argv`; without one, `argv` is appended.
+fn terminal_argv(argv: &[String], templates: &[String]) -> Option<Vec<String>> {
+    templates.iter().find_map(|template| {
+        let template = if template.contains("$TERMINAL") {
+            template.replace("$TERMINAL", &std::env::var("TERMINAL").ok().filter(|t| !t.is_empty())?)
+        } else {
+            template.clone()
+        };
+        let words = crate::exec::split(&template)?;
+        if !is_executable_available(&words[0]) { return None; }
+        let mut out    = Vec::with_capacity(words.len() + argv.len());
+        let mut placed = false;
+        for word in words {
+            if word == "%command%" { out.extend_from_slice(argv); placed = true; } else { out.push(word); }
+        }
+        if !placed { out.extend_from_slice(argv); }
+        Some(out)
+    })
+}
+
+/// What stops the launch-options editor from saving: bad variable names,
+/// a missing folder, or a command the cache or `sh` can't take. `env` is the
+/// editor's rows, so a name given twice is caught too.
+pub fn validate_launch_options(env: &[(String, String)], dir: &str, command: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen     = HashSet::new();
+    for (key, value) in env {
+        let key = key.trim();
+        if key.is_empty() {
+            if !value.is_empty() { problems.push(format!("The value \"{value}\" has no variable name")); }
+            continue;
+        }
+        let valid = !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid                { problems.push(format!("{key} isn't a valid variable name")); }
+        if !seen.insert(key)     { problems.push(format!("{key} is set twice")); }
+        // The cache keeps variables as `K=V,K=V`.
+        if value.contains(',')   { problems.push(format!("The value of {key} can't contain a comma")); }
+    }
+    let dir = dir.trim();
+    if !dir.is_empty() && !expand_home(dir).is_dir() {
+        problems.push(format!("No such folder: {dir}"));
+    }
+    if command.contains('|') {
+        problems.push("The command can't contain \"|\"; put a pipeline in a script".into());
+    }
+    if !quotes_balanced(command) {
+        problems.push("The command has an unclosed quote".into());
+    }
+    problems
+}
+
+/// `sh` quoting: single quotes take everything literally, backslashes
+/// escape outside them.
+fn quotes_balanced(line: &str) -> bool {
+    let mut chars = line.chars();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'')        => quote = None,
+            (Some('\''), _)           => {}
+            (_, '\\')                 => { chars.next(); }
+            (Some('"'), '"')          => quote = None,
+            (None, '\'' | '"')        => quote = Some(c),
+            _                         => {}
+        }
+    }
+    quote.is_none()
+}
+
+/// `path` with a leading `~/` replaced by `$HOME`.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _                        => PathBuf::from(path),
+    }
+}
+
+// ============================================================================
+// App providers
+// ============================================================================
+
+/// Shared implementation of the app-like providers (desktop entries, Steam,
+/// `$PATH`): they differ only in how the background scan finds apps.
+struct AppProvider {
+    name:           &'static str,
+    /// Starts with cache contents; the background scan replaces them.
+    apps:           Vec<App>,
+    enable_recent:  bool,
+    /// Focus an app's open window instead of starting another instance.
+    focus_or_launch: bool,
+    /// Terminal templates for `Terminal=true` entries.
+    terminals:      Vec<String>,
+    watch:          LaunchWatch,
+    /// Routed queries (`games-prefix`) list every app as a cover-art grid.
+    grid:           bool,
+    /// Listed by `browse` (`show-all-apps`); off for `$PATH`, which would bury the menu.
+    browsable:      bool,
+    /// `cat:games` lists that category; empty = off.
+    category_prefix: String,
+    /// Receives fresh apps from the background filesystem scan.
+    pending_scan:   Arc<Mutex<Option<Vec<App>>>>,
+}
+
+impl AppProvider {
+    fn new(name: &'static str, scan: fn() -> Vec<App>, config: &crate::gui::Config) -> Self {
+        // The cache doesn't record which provider an app came from, so every
+        // app provider starts from all of it (the launcher dedups rows by title)
+        // and its first scan drops whatever isn't its own. Apps the desktop
+        // index knows come from there, with their descriptions and actions.
+        let mut apps = indexed_desktop_apps();
+        let indexed: HashSet<String> = apps.iter().map(|app| app.name.clone()).collect();
+        apps.extend(get_all_cached_apps().into_iter().filter(|app| !indexed.contains(&app.name)));
+
+        // Scan on a background thread; the main thread never blocks on it.
+        let pending_scan: Arc<Mutex<Option<Vec<App>>>> = Arc::new(Mutex::new(None));
+        {
+            let pending_clone = Arc::clone(&pending_scan);
+            thread::spawn(move || {
+                let mut fresh = scan();
+                // Deduplicate by name while preserving discovery order.
+                let mut seen = HashSet::new();
+                fresh.retain(|app| seen.insert(app.name.clone()));
+                if let Ok(mut guard) = pending_clone.lock() {
+                    *guard = Some(fresh);
+                }
+            });
+        }
+
+        AppProvider {
+            name, apps, pending_scan,
+            enable_recent:   config.enable_recent_apps,
+            focus_or_launch: config.focus_or_launch,
+            terminals:       terminal_templates(config),
+            watch:           LaunchWatch {
+                window:         time::Duration::from_millis(config.launch_check_ms),
+                capture_stderr: config.daemon_mode,
+            },
+            grid:            false,
+            browsable:       true,
+            category_prefix: config.category_prefix.clone(),
+        }
+    }
+
+    fn not_browsable(mut self) -> Self {
+        self.browsable = false;
+        self
+    }
+
+    fn grid_view(mut self) -> Self {
+        self.grid = true;
+        self
+    }
+
+    fn to_result(&self, idx: usize, recent_rank: Option<usize>) -> SearchResult {
+        let app = &self.apps[idx];
+        SearchResult {
+            title:       app.name.clone(),
+            icon:        (!app.icon.is_empty()).then(|| app.icon.clone()),
+            description: app.description.clone(),
+            actions:     app.actions.iter().map(|a| a.name.clone()).collect(),
+            data:        app.exec.clone(),
+            recent_rank,
+            image:       app.cover.clone(),
+            command:     (!app.exec.is_empty()).then(|| app.exec.clone()),
+            file:        app.file.as_ref().map(|f| f.display().to_string()),
+        }
+    }
+}
+
+fn scan_desktop() -> Vec<App> {
+    get_desktop_entries().into_iter().map(App::from).collect()
+}
 
-    APP_CACHE.lock()
-        .ok()
-        .map(|cache| {
-            cache.apps.iter()
-                .filter_map(|(name, _): &(String, AppEntry)| name_to_idx.get(name.as_str()).copied())
-                .take(config.max_search_results)
-                .collect()
-        })
-        .unwrap_or_default()
+fn scan_flatpak() -> Vec<App> {
+    get_flatpak_entries()
 }
 
-// ============================================================================
-// App launch
-// ============================================================================
+fn scan_steam() -> Vec<App> {
+    get_steam_entries()
+}
 
-fn extract_terminal_command(exec_cmd: &str) -> Option<String> {
-    let cleaned = exec_cmd.split_whitespace().next()?.trim_start_matches("env ");
-    cleaned.split('/').last()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty() && !s.starts_with('-'))
-        .map(String::from)
+fn scan_path() -> Vec<App> {
+    // Runs independently of the desktop provider's scan, so it reads the
+    // entries it dedups against itself.
+    get_path_entries(&scan_desktop())
 }
 
-fn launch_app(
-    app_name: &str,
-    exec_cmd: &str,
-    icon_path: &str,
-    options: &Option<AppLaunchOptions>,
-    enable_recent_apps: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    cache_app_metadata(app_name, exec_cmd, icon_path);
-    if enable_recent_apps { update_recent_apps(app_name, true)?; }
+impl SearchProvider for AppProvider {
+    fn name(&self) -> &'static str { self.name }
 
-    let home_dir = std::env::var("HOME")
-        .map(PathBuf::from)
-        .map_err(|_| "No home directory")?;
+    fn mode(&self) -> &'static str {
+        match self.name {
+            "flatpak" => "Flatpak",
+            "steam"   => "Games",
+            "path"    => "Commands",
+            _         => "Apps",
+        }
+    }
 
-    let (cmd, dir) = match options {
-        Some(opts) => {
-            let command = match &opts.custom_command {
-                Some(c) if c.trim() == "%command%"     => exec_cmd.to_string(),
-                Some(c) if c.contains("%command%")     => c.replace("%command%", exec_cmd),
-                Some(c) => {
-                    let ct = c.trim();
-                    if !ct.contains(' ') { c.to_string() } else { format!("{} {}", c, exec_cmd) }
-                }
-                None => exec_cmd.to_string(),
-            };
-            let dir = opts.working_directory.as_deref()
-                .unwrap_or_else(|| home_dir.to_str().unwrap_or(""));
-            (command, dir)
+    fn kind(&self) -> ResultKind {
+        match self.name {
+            "steam" => ResultKind::Game,
+            "path"  => ResultKind::Command,
+            _       => ResultKind::App,
         }
-        None => (exec_cmd.to_string(), home_dir.to_str().unwrap_or("")),
-    };
+    }
 
-    let try_launch = |command_str: &str| -> Result<(), std::io::Error> {
-        let mut command = Command::new("sh");
-        command.arg("-c").arg(command_str).current_dir(dir);
-        if let Some(opts) = options {
-            for (key, value) in &opts.environment_vars { command.env(key, value); }
+    fn grid(&self) -> bool { self.grid }
+
+    /// Every matching app A–Z, like a category.
+    fn routed(&mut self, query: &str, _max: usize) -> Vec<SearchResult> {
+        let filter = query.trim().to_lowercase();
+        let mut indices: Vec<usize> = (0..self.apps.len())
+            .filter(|&i| self.apps[i].matches(&filter))
+            .collect();
+        indices.sort_by(|&a, &b| self.apps[a].name_lower.cmp(&self.apps[b].name_lower));
+        indices.into_iter().map(|i| self.to_result(i, None)).collect()
+    }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        if let Some((category, filter)) = category_query(query, &self.category_prefix) {
+            let filter = filter.to_lowercase();
+            return (0..self.apps.len())
+                .filter(|&i| self.apps[i].category == Some(category) && self.apps[i].matches(&filter))
+                .map(|i| self.to_result(i, None))
+                .collect();
         }
-        let mut child = command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        match child.try_wait() {
-            Ok(Some(status)) if !status.success() =>
-                Err(std::io::Error::new(std::io::ErrorKind::Other, "Process exited with error")),
-            Ok(Some(_)) | Ok(None) => Ok(()),
-            Err(e) => Err(e),
+        if query.trim().is_empty() {
+            if !self.enable_recent { return Vec::new(); }
+            // Pinned apps rank ahead of every recent one, in the order pinned.
+            let pinned  = get_pinned_apps();
+            let pins    = pinned.iter().enumerate()
+                .filter_map(|(rank, name)| Some((self.apps.iter().position(|a| &a.name == name)?, rank)));
+            let recents = get_recent_indices(&self.apps, max + pinned.len()).into_iter()
+                .filter(|&(i, _)| !pinned.contains(&self.apps[i].name))
+                .map(|(i, rank)| (i, pinned.len() + rank));
+            return pins.chain(recents).take(max)
+                .map(|(i, rank)| self.to_result(i, Some(rank)))
+                .collect();
         }
-    };
+        // Pre-lowercase once per query change, not once per app per query change.
+        search_apps(&query.to_lowercase(), &self.apps, max, &get_app_overrides()).into_iter()
+            .map(|i| self.to_result(i, None))
+            .collect()
+    }
+
+    fn categories(&self) -> Vec<&'static str> {
+        self.apps.iter().filter_map(|app| app.category).collect()
+    }
+
+    fn browse(&mut self) -> Vec<SearchResult> {
+        if !self.browsable { return Vec::new(); }
+        (0..self.apps.len()).map(|i| self.to_result(i, None)).collect()
+    }
 
-    try_launch(&cmd)
-        .or_else(|_| {
-            if let Some(opts) = options {
-                if let Some(custom_cmd) = &opts.custom_command { return try_launch(custom_cmd); }
+    /// Steam games bring their own window; everything else can run in a terminal.
+    fn terminal(&self) -> bool { self.name != "steam" }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        let Some(app) = self.apps.iter().find(|a| a.name == result.title) else { return false };
+        let options  = get_launch_options().remove(&app.name);
+        let in_terminal = app.terminal || *action == RowAction::LaunchInTerminal;
+        let terminal = in_terminal.then_some(self.terminals.as_slice());
+        let launched = match action {
+            RowAction::Provider(name) => match app.actions.iter().find(|a| &a.name == name) {
+                Some(action) => launch_action(&app.name, action, &options, self.enable_recent, terminal, self.watch),
+                None         => return false,
+            },
+            RowAction::Launch if self.focus_or_launch && focus_existing_window(app) => {
+                if self.enable_recent { let _ = update_recent_apps(&app.name, true); }
+                return true;
             }
-            Err(std::io::Error::new(std::io::ErrorKind::Other, "Custom command fallback not available"))
-        })
-        .or_else(|_| {
-            if let Some((_, _, Some(terminal_cmd))) = get_cached_data(app_name) {
-                return try_launch(&terminal_cmd);
+            _ => launch_app(&app.name, &app.exec, &app.icon, &options, self.enable_recent, terminal, self.watch),
+        };
+        match launched {
+            Ok(()) => true,
+            Err(e) => {
+                let failure = e.downcast::<LaunchFailure>().map(|f| *f).unwrap_or_else(|e| LaunchFailure {
+                    app: app.name.clone(), command: app.exec.clone(), message: e.to_string(),
+                });
+                report_launch_failure(failure);
+                false
             }
-            Err(std::io::Error::new(std::io::ErrorKind::Other, "Cached terminal command not available"))
-        })
-        .or_else(|_| {
-            if let Some(terminal_cmd) = extract_terminal_command(exec_cmd) {
-                return try_launch(&terminal_cmd);
+        }
+    }
+
+    /// Merges freshly scanned apps into `self.apps` without blocking (uses
+    /// `try_lock` so it never stalls the UI).
+    fn poll(&mut self) -> bool {
+        let fresh = match self.pending_scan.try_lock() {
+            Ok(mut guard) => guard.take(),
+            Err(_)        => return false,
+        };
+
+        let Some(fresh) = fresh else { return false };
+
+        // The scan is authoritative: cached apps it no longer reports (uninstalled,
+        // or now hidden via NoDisplay/OnlyShowIn/TryExec) are dropped. Survivors
+        // keep their cached position and pick up data the cache doesn't store.
+        let fresh_names: HashSet<&str> = fresh.iter().map(|a| a.name.as_str()).collect();
+        self.apps.retain(|a| fresh_names.contains(a.name.as_str()));
+        let existing: HashMap<String, usize> = self.apps.iter()
+            .enumerate()
+            .map(|(i, a)| (a.name.clone(), i))
+            .collect();
+        for app in fresh {
+            match existing.get(&app.name) {
+                Some(&idx) => {
+                    let cached = &mut self.apps[idx];
+                    cached.alt_lower   = app.alt_lower;
+                    cached.description = app.description;
+                    cached.actions     = app.actions;
+                    cached.wm_class    = app.wm_class;
+                    cached.terminal    = app.terminal;
+                    cached.cover       = app.cover;
+                    cached.file        = app.file;
+                    // Cached Exec lines may predate the current quoting rules.
+                    cached.exec        = app.exec;
+                }
+                None       => self.apps.push(app),
             }
-            Err(std::io::Error::new(std::io::ErrorKind::Other, "All launch attempts failed"))
-        })
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+        }
+        true
+    }
+}
+
+// ============================================================================
+// Run mode provider
+// ============================================================================
+
+/// Claims queries starting with `run-prefix` and runs them through `sh`.
+struct RunProvider {
+    config:    crate::gui::Config,
+    /// `$PATH` executables, listed the first time run mode is entered.
+    path_bins: Option<Vec<String>>,
+}
+
+impl SearchProvider for RunProvider {
+    fn name(&self) -> &'static str { "run" }
+    fn icon(&self) -> Option<&str> { Some(PATH_BIN_ICON) }
+    fn mode(&self) -> &'static str { "Run" }
+    fn kind(&self) -> ResultKind { ResultKind::Shell }
+    fn routed_only(&self) -> bool { true }
+
+    /// The typed command, then matching history and `$PATH` completions.
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        let cmd = query.trim_start();
+        let mut rows: Vec<String> = Vec::with_capacity(max);
+        if !cmd.trim().is_empty() { rows.push(cmd.to_string()); }
+        rows.extend(get_run_history().into_iter().filter(|h| h.starts_with(cmd) && h != cmd));
+        // Complete the program name until the first space is typed.
+        if !cmd.is_empty() && !cmd.contains(char::is_whitespace) {
+            let bins = self.path_bins.get_or_insert_with(path_executables);
+            rows.extend(bins.iter().filter(|b| b.starts_with(cmd) && *b != cmd).cloned());
+        }
+        let mut seen = HashSet::new();
+        rows.retain(|r| seen.insert(r.clone()));
+        rows.truncate(max);
+        rows.into_iter().map(|row| SearchResult {
+            title:   row.clone(),
+            command: Some(row.clone()),
+            data:    row,
+            ..Default::default()
+        }).collect()
+    }
+
+    fn terminal(&self) -> bool { true }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        let in_terminal = *action == RowAction::LaunchInTerminal || self.config.run_in_terminal;
+        match run_shell_command(&result.data, in_terminal, &self.config) {
+            Ok(())  => true,
+            Err(e)  => { tracing::warn!(target: "launch", "Failed to run '{}': {e}", result.data); false }
+        }
+    }
+}
+
+/// Instantiate the providers listed in `search-providers`, in that order.
+/// Providers whose own toggle is off are skipped.
+fn build_providers(config: &crate::gui::Config) -> Vec<Box<dyn SearchProvider>> {
+    config.search_providers.iter().filter_map(|name| -> Option<Box<dyn SearchProvider>> {
+        match name.as_str() {
+            "windows"    => Some(Box::new(WindowProvider::default())),
+            "clipboard"  => ClipboardProvider::new(config).map(|p| Box::new(p) as _),
+            "snippets"   => Some(Box::new(SnippetProvider::new(config))),
+            "run"        => Some(Box::new(RunProvider { config: config.clone(), path_bins: None })),
+            "calculator" => config.enable_calculator.then(|| Box::new(CalculatorProvider) as _),
+            "conversion" => config.enable_conversion.then(|| Box::new(ConversionProvider::new(config)) as _),
+            "flatpak"    => Some(Box::new(AppProvider::new("flatpak", scan_flatpak, config))),
+            "desktop"    => Some(Box::new(AppProvider::new("desktop", scan_desktop, config))),
+            "steam"      => Some(Box::new(AppProvider::new("steam", scan_steam, config).grid_view())),
+            "path"       => config.enable_path_binaries
+                .then(|| Box::new(AppProvider::new("path", scan_path, config).not_browsable()) as _),
+            "recent"     => Some(Box::new(RecentProvider::default())),
+            "places"     => Some(Box::new(PlacesProvider::default())),
+            "wallpaper"  => Some(Box::new(WallpaperProvider::new(config))),
+            "web"        => config.enable_web_search.then(|| Box::new(WebProvider::new(config)) as _),
+            other        => { tracing::warn!(target: "search", "Unknown search provider: {other}"); None }
+        }
+    }).collect()
+}
+
+// ============================================================================
+// Search worker
+// ============================================================================
+
+/// Launcher state a search reads besides the providers, copied into each
+/// request so the worker never touches the `AppLauncher`.
+#[derive(Clone)]
+struct SearchScope {
+    hidden:    Vec<String>,
+    pinned:    Vec<String>,
+    overrides: HashMap<String, AppOverride>,
+    config:    crate::gui::Config,
+}
+
+/// The rows for one query, ready to show.
+struct SearchOutcome {
+    generation: u64,
+    /// Rows tagged with the index of the provider that produced them.
+    rows:       Vec<(usize, SearchResult)>,
+    /// The same rows as the GUI draws them.
+    shown:      Vec<ResultRow>,
+    grid:       bool,
+    categories: Vec<&'static str>,
 }
 
-fn parse_launch_options_input(input: &str, _original_command: Option<String>) -> AppLaunchOptions {
-    let mut parts         = input.split_whitespace().peekable();
-    let mut options       = AppLaunchOptions::default();
-    let mut command_parts = Vec::new();
+type Providers = Arc<Mutex<Vec<Box<dyn SearchProvider>>>>;
+type Wake      = Arc<OnceLock<Box<dyn Fn() + Send + Sync>>>;
+
+/// Runs searches off the UI thread. Requests are numbered; the worker waits
+/// `search-debounce-ms` for typing to pause, drops a request that has been
+/// superseded before it starts, and abandons one between providers.
+struct SearchWorker {
+    requests: mpsc::Sender<(u64, String, SearchScope)>,
+    outcomes: mpsc::Receiver<SearchOutcome>,
+    /// The newest generation asked for; anything older is stale.
+    latest:   Arc<AtomicU64>,
+    /// Called when an outcome is ready, so the GUI repaints to pick it up.
+    wake:     Wake,
+}
 
-    while let Some(part) = parts.next() {
-        match part {
-            "-e" => {
-                if let Some(env_var) = parts.next() {
-                    if let Some((key, value)) = env_var.split_once('=') {
-                        options.environment_vars.insert(key.to_string(), value.to_string());
+impl SearchWorker {
+    fn spawn(providers: Providers) -> Self {
+        let (requests, inbox)  = mpsc::channel::<(u64, String, SearchScope)>();
+        let (outbox, outcomes) = mpsc::channel();
+        let latest = Arc::new(AtomicU64::new(0));
+        let wake: Wake = Arc::new(OnceLock::new());
+        {
+            let (latest, wake) = (Arc::clone(&latest), Arc::clone(&wake));
+            thread::spawn(move || {
+                while let Ok(mut request) = inbox.recv() {
+                    let debounce = time::Duration::from_millis(request.2.config.search_debounce_ms);
+                    loop {
+                        match inbox.recv_timeout(debounce) {
+                            Ok(newer) => request = newer,
+                            Err(mpsc::RecvTimeoutError::Timeout)      => break,
+                            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                        }
                     }
+                    let (generation, query, scope) = request;
+                    let stale = || latest.load(Ordering::SeqCst) != generation;
+                    if stale() { continue; }
+                    let outcome = match providers.lock() {
+                        Ok(mut providers) => search(&mut providers, generation, &query, &scope, &stale),
+                        Err(_)            => return,
+                    };
+                    let Some(outcome) = outcome else { continue };
+                    if outbox.send(outcome).is_err() { return; }
+                    if let Some(wake) = wake.get() { wake(); }
                 }
+            });
+        }
+        SearchWorker { requests, outcomes, latest, wake }
+    }
+
+    /// Queue a search for `query`, superseding any still pending.
+    fn request(&self, query: &str, scope: SearchScope) {
+        let generation = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = self.requests.send((generation, query.to_string(), scope));
+    }
+
+    /// Mark every queued or running search stale; returns the generation
+    /// for a search the caller runs itself.
+    fn cancel(&self) -> u64 {
+        self.latest.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.latest.load(Ordering::SeqCst) == generation
+    }
+
+    /// The newest finished search, if it's still the one wanted.
+    fn take(&self) -> Option<SearchOutcome> {
+        self.outcomes.try_iter().last().filter(|outcome| self.is_current(outcome.generation))
+    }
+}
+
+/// Ask every provider (or just the one claiming the query) for rows and
+/// merge them in registration order, dropping duplicate titles. `None` when
+/// `stale` turns true along the way.
+fn search(providers: &mut [Box<dyn SearchProvider>], generation: u64, query: &str, scope: &SearchScope,
+          stale: &dyn Fn() -> bool) -> Option<SearchOutcome> {
+    let config  = &scope.config;
+    let max     = config.max_search_results;
+    // `hidden:` lists (and searches) only what was hidden from the menu.
+    let (query, only_hidden) = match query.strip_prefix(config.hidden_prefix.as_str()) {
+        Some(rest) if !config.hidden_prefix.is_empty() => (rest, true),
+        _                                              => (query, false),
+    };
+    // A prefix routes the rest of the query to one provider; failing that,
+    // a provider may claim it by its look.
+    let routes  = crate::providers::routes(config);
+    let routed  = crate::providers::route(query, &routes)
+        .and_then(|(name, rest)| Some((providers.iter().position(|p| p.name() == name)?, rest)));
+    let (query, claimed) = match routed {
+        Some((i, rest)) => (rest, Some(i)),
+        None            => (query, providers.iter().position(|p| !p.routed_only() && p.claims(query))),
+    };
+    let grid    = routed.is_some() && claimed.is_some_and(|c| providers[c].grid());
+    // Browsing lists every app A–Z instead of the recents.
+    let browse  = claimed.is_none() && query.trim().is_empty() && (config.show_all_apps || only_hidden);
+    // A category lists all of its apps, A–Z too.
+    let listing = browse || (claimed.is_none() && category_query(query, &config.category_prefix).is_some());
+    // A grid lists its whole library, and a listing everything; the cap is for the row list.
+    let max     = if grid || listing { usize::MAX } else { max };
+
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    // Fallback providers only get a turn once the others came up empty.
+    for fallback_pass in [false, true] {
+        if fallback_pass && !rows.is_empty() { break; }
+        for (i, provider) in providers.iter_mut().enumerate() {
+            match claimed {
+                Some(c) if c != i || fallback_pass => continue,
+                None if provider.fallback() != fallback_pass || provider.routed_only() => continue,
+                _ => {}
             }
-            "-w" => {
-                if let Some(dir) = parts.next() { options.working_directory = Some(dir.to_string()); }
-            }
-            _ => {
-                command_parts.push(part.to_string());
-                command_parts.extend(parts.map(|s| s.to_string()));
-                break;
-            }
+            if stale() { return None; }
+            let found = if routed.is_some() { provider.routed(query, max) }
+                else if browse { provider.browse() }
+                else { provider.results(query, max) };
+            rows.extend(found.into_iter()
+                .filter(|r| scope.hidden.contains(&r.title) == only_hidden)
+                .filter(|r| only_hidden || !config.hidden_apps.iter().any(|p| glob_match(p, &r.title)))
+                .filter(|r| seen.insert(r.title.clone()))
+                .map(|r| (i, r)));
         }
     }
+    if listing {
+        rows.sort_by_cached_key(|(_, r)| display_name(&scope.overrides, &r.title).to_lowercase());
+    } else if query.trim().is_empty() {
+        // Recents from several providers interleave by when they were used.
+        rows.sort_by_key(|(_, r)| r.recent_rank.unwrap_or(usize::MAX));
+    }
+    rows.truncate(max);
+
+    if stale() { return None; }
+    // Icons are looked up here, once per search, rather than per frame.
+    let shown = rows.iter().map(|(i, r)| result_row(providers[*i].as_ref(), r, scope)).collect();
+
+    let present: HashSet<&'static str> = providers.iter().flat_map(|p| p.categories()).collect();
+    let categories = CATEGORIES.iter().map(|(name, _)| *name).filter(|name| present.contains(name)).collect();
+    Some(SearchOutcome { generation, rows, shown, grid, categories })
+}
+
+/// `row` as the GUI draws it, with its custom name and icon applied.
+fn result_row(provider: &dyn SearchProvider, row: &SearchResult, scope: &SearchScope) -> ResultRow {
+    let config = &scope.config;
+    let icon   = match scope.overrides.get(&row.title).and_then(|o| o.icon.as_deref()) {
+        Some(custom) => resolve_custom_icon(custom, config),
+        None         => row.icon.as_deref().or(provider.icon())
+            .and_then(|icon| resolve_icon_path(&row.title, icon, config)),
+    };
+    let cover  = row.image.clone().filter(|p| Path::new(p).exists()).or_else(|| icon.clone());
+    let kind   = provider.kind();
+    let mut actions = vec![RowAction::Launch];
+    actions.extend(row.actions.iter().cloned().map(RowAction::Provider));
+    if provider.terminal() { actions.push(RowAction::LaunchInTerminal); }
+    actions.extend(row.file.clone().map(RowAction::ShowDesktopFile));
+    actions.extend(row.command.clone().map(RowAction::CopyCommand));
+    if kind.is_app() {
+        let pinned = scope.pinned.contains(&row.title);
+        let hidden = scope.hidden.contains(&row.title);
+        actions.push(if pinned { RowAction::Unpin } else { RowAction::Pin });
+        actions.push(if hidden { RowAction::Unhide } else { RowAction::Hide });
+        actions.push(RowAction::EditLaunchOptions);
+    }
+    ResultRow {
+        id:       row.title.clone(),
+        title:    display_name(&scope.overrides, &row.title).to_string(),
+        subtitle: row.description.clone(),
+        icon,
+        cover,
+        kind,
+        actions,
+    }
+}
 
-    if !command_parts.is_empty() { options.custom_command = Some(command_parts.join(" ")); }
-    options
+/// The label shown for `title`: its custom name, else the title.
+fn display_name<'a>(overrides: &'a HashMap<String, AppOverride>, title: &'a str) -> &'a str {
+    overrides.get(title).and_then(|o| o.name.as_deref()).unwrap_or(title)
 }
 
 // ============================================================================
@@ -852,144 +2846,151 @@ fn parse_launch_options_input(input: &str, _original_command: Option<String>) ->
 
 pub struct AppLauncher {
     query:          String,
-    /// All known apps. Starts with cache contents; background scan appends new ones.
-    apps:           Vec<App>,
-    /// Indices into `apps` – avoids cloning strings on every query change.
-    results:        Vec<usize>,
+    /// Shared with the search worker, which holds the lock while it searches.
+    providers:      Providers,
+    /// Copied out of each provider, so drawing never waits on the lock.
+    info:           Vec<ProviderInfo>,
+    search:         SearchWorker,
+    /// Current rows, tagged with the index of the provider that produced them.
+    results:        Vec<(usize, SearchResult)>,
+    /// `results` as the GUI draws them.
+    rows:           Vec<ResultRow>,
+    /// Search generation `results` came from; older than the worker's latest
+    /// while a search is pending.
+    shown:          u64,
+    /// The claiming provider wants `results` shown as a cover-art grid.
+    grid:           bool,
+    /// Menu categories that have at least one app, in `CATEGORIES` order.
+    categories:     Vec<&'static str>,
+    /// Titles hidden from the right-click menu (kept in the app cache).
+    hidden:         Vec<String>,
+    /// Titles pinned from the right-click menu (kept in the app cache).
+    pinned:         Vec<String>,
+    /// Custom labels and icons by title.
+    overrides:      HashMap<String, AppOverride>,
     quit:           bool,
     config:         crate::gui::Config,
     launch_options: HashMap<String, AppLaunchOptions>,
-    /// Receives fresh apps from the background filesystem scan.
-    pending_scan:   Arc<Mutex<Option<Vec<App>>>>,
+}
+
+/// The parts of a provider the UI thread reads.
+struct ProviderInfo {
+    name: &'static str,
+    /// Mode badge label while a prefix routes to it.
+    mode: &'static str,
 }
 
 impl Default for AppLauncher {
-    fn default() -> Self {
-        let config = crate::gui::Config::default();
+    fn default() -> Self { Self::new(crate::gui::Config::default()) }
+}
 
-        // Show cached apps immediately so the launcher is usable at once.
-        let apps = get_all_cached_apps();
+impl AppLauncher {
+    pub fn new(config: crate::gui::Config) -> Self {
+        let providers      = build_providers(&config);
+        let info           = providers.iter().map(|p| ProviderInfo {
+            name: p.name(),
+            mode: p.mode(),
+        }).collect();
+        let providers      = Arc::new(Mutex::new(providers));
+        let search         = SearchWorker::spawn(Arc::clone(&providers));
         let launch_options = get_launch_options();
-
-        let results = if config.enable_recent_apps {
-            get_recent_indices(&apps, &config)
-        } else {
-            Vec::new()
+        if config.enable_icons {
+            let config = config.clone();
+            thread::spawn(move || crate::icon_theme::preload(&config));
+        }
+        let mut launcher = AppLauncher {
+            query: String::new(), providers, info, search, results: Vec::new(), rows: Vec::new(), shown: 0,
+            grid: false,
+            categories: Vec::new(), hidden: get_hidden_apps(), pinned: get_pinned_apps(),
+            overrides: get_app_overrides(), quit: false, config, launch_options,
         };
+        // Cached apps are available at once, so recents show on the first frame.
+        launcher.refresh_results_now();
+        launcher
+    }
 
-        // Scan the filesystem for fresh entries on a background thread.
-        // The main thread never blocks waiting for this.
-        let pending_scan: Arc<Mutex<Option<Vec<App>>>> = Arc::new(Mutex::new(None));
-        {
-            let pending_clone = Arc::clone(&pending_scan);
-            thread::spawn(move || {
-                let mut fresh: Vec<App> = get_desktop_entries()
-                    .into_iter()
-                    .chain(get_steam_entries())
-                    .map(|(name, exec, icon)| App::new(name, exec, icon))
-                    .collect();
-
-                // Deduplicate by name while preserving discovery order.
-                let mut seen = HashSet::new();
-                fresh.retain(|app| seen.insert(app.name.clone()));
-
-                if let Ok(mut guard) = pending_clone.lock() {
-                    *guard = Some(fresh);
-                }
-            });
+    fn scope(&self) -> SearchScope {
+        SearchScope {
+            hidden:    self.hidden.clone(),
+            pinned:    self.pinned.clone(),
+            overrides: self.overrides.clone(),
+            config:    self.config.clone(),
         }
+    }
 
-        AppLauncher { query: String::new(), apps, results, quit: false, config, launch_options, pending_scan }
+    /// Search again on the worker; the rows update when it's done.
+    fn refresh_results(&mut self) {
+        self.search.request(&self.query, self.scope());
     }
-}
 
-impl AppLauncher {
-    /// Called each frame. Merges any freshly scanned apps into `self.apps`
-    /// without blocking (uses `try_lock` so it never stalls the UI).
-    fn poll_pending_scan(&mut self) {
-        // try_lock: never stall the render thread waiting for the scan thread.
-        let fresh = match self.pending_scan.try_lock() {
-            Ok(mut guard) => guard.take(),
-            Err(_)        => return,
+    /// Search again on this thread, replacing whatever the worker had queued.
+    fn refresh_results_now(&mut self) {
+        let generation = self.search.cancel();
+        let scope      = self.scope();
+        let outcome    = match self.providers.lock() {
+            Ok(mut providers) => search(&mut providers, generation, &self.query, &scope, &|| false),
+            Err(_)            => None,
         };
+        if let Some(outcome) = outcome { self.show(outcome); }
+    }
 
-        let Some(fresh) = fresh else { return };
-
-        // Merge: only append entries not already in self.apps.
-        // Collect into owned Strings so the borrow on self.apps ends before we push.
-        let existing: HashSet<String> = self.apps.iter().map(|a| a.name.clone()).collect();
-        let had_apps = !self.apps.is_empty();
-        for app in fresh {
-            if !existing.contains(&app.name) {
-                self.apps.push(app);
-            }
-        }
-
-        // If we didn't have any apps before (cold start with empty cache), also
-        // write a fresh snapshot of the metadata so the cache is populated.
-        // Refresh the result list to pick up newly added entries.
-        if !had_apps || !self.query.is_empty() {
-            let q = self.query.to_lowercase();
-            self.results = if q.is_empty() && self.config.enable_recent_apps {
-                get_recent_indices(&self.apps, &self.config)
-            } else if !q.is_empty() {
-                search_apps(&q, &self.apps, self.config.max_search_results)
-            } else {
-                Vec::new()
-            };
-        }
+    fn show(&mut self, outcome: SearchOutcome) {
+        self.results    = outcome.rows;
+        self.rows       = outcome.shown;
+        self.grid       = outcome.grid;
+        self.categories = outcome.categories;
+        self.shown      = outcome.generation;
     }
 
-    fn launch_first_result(&mut self) {
-        if let Some(&idx) = self.results.first() {
-            let app     = &self.apps[idx];
-            let options = self.launch_options.get(&app.name).cloned();
-            if launch_app(&app.name, &app.exec, &app.icon, &options, self.config.enable_recent_apps).is_ok() {
-                self.quit = true;
-            }
-        }
+    fn result(&self, title: &str) -> Option<&(usize, SearchResult)> {
+        self.results.iter().find(|(_, r)| r.title == title)
     }
 
-    fn get_app_command(&self, app_name: &str) -> Option<String> {
-        self.apps.iter().find(|a| a.name == app_name).map(|a| a.exec.clone())
+    fn activate(&mut self, title: &str, action: &RowAction) {
+        let Some((i, row)) = self.result(title).cloned() else { return };
+        let Ok(mut providers) = self.providers.lock() else { return };
+        if providers[i].activate(&row, action) {
+            self.quit = true;
+        }
     }
 }
 
 impl crate::gui::AppInterface for AppLauncher {
     fn update(&mut self) {
-        // Integrate any background-scanned apps without blocking.
-        self.poll_pending_scan();
+        if let Some(outcome) = self.search.take() { self.show(outcome); }
 
-        if self.quit { std::process::exit(0); }
+        // Integrate any background scans without blocking; while a search
+        // holds the providers, they're polled on a later frame.
+        let mut changed = false;
+        if let Ok(mut providers) = self.providers.try_lock() {
+            for provider in providers.iter_mut() { changed |= provider.poll(); }
+        }
+        if changed { self.refresh_results(); }
+
+        // In daemon mode the GUI hides the window and calls reset() instead.
+        if self.quit && !self.config.daemon_mode {
+            crate::ipc::cleanup();
+            std::process::exit(0);
+        }
     }
 
     fn handle_input(&mut self, input: &str) {
         match input {
-            s if s.starts_with("LAUNCH_OPTIONS:") => {
-                let parts: Vec<&str> = s.splitn(3, ':').collect();
-                if parts.len() >= 3 {
-                    let (app_name, opts_str) = (parts[1], parts[2]);
-                    let orig_cmd = self.get_app_command(app_name);
-                    let opts     = parse_launch_options_input(opts_str, orig_cmd);
-                    self.launch_options.insert(app_name.to_string(), opts.clone());
-                    let _ = update_launch_options(app_name, opts);
-                    self.query.clear();
+            "ESC"   => self.quit = true,
+            "ENTER" => {
+                // Enter right after typing launches the match for what's typed.
+                if !self.search.is_current(self.shown) { self.refresh_results_now(); }
+                if let Some(title) = self.results.first().map(|(_, r)| r.title.clone()) {
+                    self.activate(&title, &RowAction::Launch);
                 }
             }
-            "ESC"   => self.quit = true,
-            "ENTER" => self.launch_first_result(),
             "P" if self.config.enable_power_options => crate::system::power_off(&self.config),
             "R" if self.config.enable_power_options => crate::system::restart(&self.config),
             "L" if self.config.enable_power_options => crate::system::logout(&self.config),
+            "S" if self.config.enable_power_options => crate::system::suspend(&self.config),
             _ => {
-                self.query   = input.to_string();
-                // Pre-lowercase once per query change, not once per app per query change.
-                let q_lower  = self.query.to_lowercase();
-                self.results = if self.config.enable_recent_apps && q_lower.trim().is_empty() {
-                    get_recent_indices(&self.apps, &self.config)
-                } else {
-                    search_apps(&q_lower, &self.apps, self.config.max_search_results)
-                };
+                self.query = input.to_string();
+                self.refresh_results();
             }
         }
     }
@@ -998,47 +2999,282 @@ impl crate::gui::AppInterface for AppLauncher {
 
     fn get_query(&self) -> String { self.query.clone() }
 
-    fn get_search_results(&self) -> Vec<String> {
-        self.results.iter()
-            .filter_map(|&i| self.apps.get(i))
-            .map(|a| a.name.clone())
-            .collect()
-    }
+    fn get_search_results(&self) -> Vec<ResultRow> { self.rows.clone() }
 
     fn get_time(&self) -> String {
         crate::system::get_current_time(&self.config)
     }
 
     fn launch_app(&mut self, app_name: &str) {
-        // Find by name in the result set (small, typically ≤5 items).
-        if let Some(&idx) = self.results.iter().find(|&&i| self.apps[i].name == app_name) {
-            let app     = &self.apps[idx];
-            let options = self.launch_options.get(&app.name).cloned();
-            if launch_app(&app.name, &app.exec, &app.icon, &options, self.config.enable_recent_apps).is_ok() {
-                self.quit = true;
-            }
-        }
+        self.activate(app_name, &RowAction::Launch);
     }
 
-    fn get_icon_path(&self, app_name: &str) -> Option<String> {
-        self.results.iter()
-            .find(|&&i| self.apps[i].name == app_name)
-            .and_then(|&i| resolve_icon_path(&self.apps[i].name, &self.apps[i].icon, &self.config))
-    }
+    fn take_launch_failures(&mut self) -> Vec<LaunchFailure> { take_launch_failures() }
 
-    fn get_formatted_launch_options(&self, app_name: &str) -> String {
-        self.launch_options.get(app_name).map(|opts| {
-            let mut result = String::new();
-            for (key, value) in &opts.environment_vars {
-                result.push_str(&format!("-e {}={} ", key, value));
+    fn run_action(&mut self, app_name: &str, action: &RowAction) {
+        match action {
+            RowAction::Pin | RowAction::Unpin => {
+                if let Err(e) = set_app_pinned(app_name, *action == RowAction::Pin) {
+                    tracing::warn!(target: "apps", "Failed to save pinned apps: {e}");
+                }
+                self.pinned = get_pinned_apps();
+                self.refresh_results();
+            }
+            RowAction::Hide | RowAction::Unhide => {
+                if let Err(e) = set_app_hidden(app_name, *action == RowAction::Hide) {
+                    tracing::warn!(target: "apps", "Failed to save hidden apps: {e}");
+                }
+                self.hidden = get_hidden_apps();
+                self.refresh_results();
             }
-            if let Some(dir) = &opts.working_directory {
-                result.push_str(&format!("-w {} ", dir));
+            RowAction::ShowDesktopFile(path) => {
+                let dir = Path::new(path).parent().unwrap_or(Path::new("/"));
+                if crate::system::open_uri(&dir.display().to_string()) { self.quit = true; }
             }
-            if let Some(cmd) = &opts.custom_command {
-                result.push_str(cmd);
+            RowAction::CopyCommand(command) => {
+                if !crate::system::copy_to_clipboard(command) {
+                    tracing::warn!(target: "clipboard", "Failed to copy command: no clipboard tool (wl-copy, xclip, xsel) found");
+                }
             }
-            result.trim().to_string()
-        }).unwrap_or_default()
+            // The GUI opens the editor itself.
+            RowAction::EditLaunchOptions => {}
+            _ => self.activate(app_name, action),
+        }
+    }
+
+    fn is_grid_view(&self) -> bool { self.grid }
+
+    fn get_mode(&self) -> Option<String> {
+        let hidden = &self.config.hidden_prefix;
+        if !hidden.is_empty() && self.query.starts_with(hidden.as_str()) { return Some("Hidden".into()); }
+        let routes = crate::providers::routes(&self.config);
+        if let Some((name, _)) = crate::providers::route(&self.query, &routes)
+            && let Some(info) = self.info.iter().find(|info| info.name == name)
+        {
+            return Some(info.mode.to_string());
+        }
+        category_query(&self.query, &self.config.category_prefix).map(|(category, _)| category.to_string())
+    }
+
+    fn get_categories(&self) -> Vec<&'static str> { self.categories.clone() }
+
+    fn get_app_override(&self, app_name: &str) -> AppOverride {
+        self.overrides.get(app_name).cloned().unwrap_or_default()
+    }
+
+    fn set_app_override(&mut self, app_name: &str, over: AppOverride) {
+        if let Err(e) = set_app_override(app_name, over) {
+            tracing::warn!(target: "apps", "Failed to save app name/icon: {e}");
+        }
+        self.overrides = get_app_overrides();
+        self.refresh_results();
+    }
+
+    fn reset(&mut self) {
+        self.quit = false;
+        // Not debounced: the window may be shown again right away.
+        self.query.clear();
+        self.refresh_results_now();
+    }
+
+    fn on_results(&self, wake: Box<dyn Fn() + Send + Sync>) {
+        let _ = self.search.wake.set(wake);
+    }
+
+    fn set_config(&mut self, config: crate::gui::Config) {
+        self.config = config;
+        self.refresh_results();
+    }
+
+    fn get_launch_options(&self, app_name: &str) -> AppLaunchOptions {
+        self.launch_options.get(app_name).cloned().unwrap_or_default()
+    }
+
+    fn set_launch_options(&mut self, app_name: &str, opts: AppLaunchOptions) {
+        if let Err(e) = update_launch_options(app_name, opts.clone()) {
+            tracing::warn!(target: "apps", "Failed to save launch options: {e}");
+        }
+        self.launch_options.insert(app_name.to_string(), opts);
+    }
+
+    fn preview_launch_command(&self, app_name: &str, opts: &AppLaunchOptions) -> String {
+        let exec = self.result(app_name).map(|(_, r)| r.data.clone())
+            .or_else(|| get_cached_data(app_name).and_then(|(_, exec, _)| exec));
+        exec.map(|exec| Launch::with_custom(&exec, opts.custom_command.as_deref()).shell_line())
+            .unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_candidates_full() {
+        assert_eq!(locale_candidates("sr_YU.UTF-8@Latn"), ["sr_YU@Latn", "sr_YU", "sr@Latn", "sr"]);
+    }
+
+    #[test]
+    fn test_locale_candidates_c_locale() {
+        assert!(locale_candidates("C").is_empty());
+        assert_eq!(locale_candidates("de_DE.UTF-8"), ["de_DE", "de"]);
+    }
+
+    #[test]
+    fn test_cache_run_history_roundtrip() {
+        let cache = AppCache {
+            apps:        vec![("HISTORY".into(), AppEntry {
+                exec_command: Some("history".into()),
+                custom_name:  Some("Shell\thistory".into()),
+                ..Default::default()
+            })],
+            run_history: vec!["echo 'a\tb'".into(), "htop".into()],
+            tray_prefs:  vec![("nm-applet".into(), TrayPref::Pinned), ("chrome_status_icon_1".into(), TrayPref::Hidden)],
+            idle_inhibit: true,
+            hidden_apps: vec!["Avahi Zeroconf Browser".into()],
+            pinned_apps: vec!["Firefox".into(), "PINNED".into()],
+            do_not_disturb: true,
+        };
+        let parsed = deserialize_cache(&serialize_cache(&cache)).unwrap();
+        assert_eq!(parsed.run_history, cache.run_history);
+        assert_eq!(parsed.tray_prefs, cache.tray_prefs);
+        assert!(parsed.idle_inhibit);
+        assert!(parsed.do_not_disturb);
+        assert_eq!(parsed.hidden_apps, cache.hidden_apps);
+        assert_eq!(parsed.pinned_apps, cache.pinned_apps);
+        assert_eq!(parsed.apps.len(), 1);
+        assert_eq!(parsed.apps[0].0, "HISTORY");
+        assert_eq!(parsed.apps[0].1.custom_name.as_deref(), Some("Shell\thistory"));
+        assert_eq!(parsed.apps[0].1.custom_icon, None);
+    }
+
+    #[test]
+    fn test_desktop_index_roundtrip() {
+        let entry = |name: &str, no_display| DesktopEntry {
+            name:          name.into(),
+            generic_name:  Some("Web Browser".into()),
+            comment:       None,
+            untranslated:  None,
+            exec:          "firefox %u".into(),
+            icon:          "firefox".into(),
+            actions:       vec![DesktopAction { name: "New\tWindow".into(), exec: "firefox --new-window".into() }],
+            wm_class:      None,
+            terminal:      false,
+            categories:    vec!["Network".into(), "WebBrowser".into()],
+            no_display,
+            autostart_disabled: false,
+            try_exec:      None,
+            file:          PathBuf::new(),
+        };
+        let file = |path: &str, entry: Option<DesktopEntry>| IndexedFile {
+            path:  path.into(),
+            mtime: 1_700_000_000_123_456_789,
+            entry: entry.map(|entry| DesktopEntry { file: path.into(), ..entry }),
+        };
+        let index = DesktopIndex {
+            key:  "de_DE;de|GNOME".into(),
+            dirs: vec![
+                IndexedDir { path: "/home/u/.local/share/applications".into(), mtime: 5, files: vec![
+                    file("/home/u/.local/share/applications/hidden.desktop", None),
+                    file("/home/u/.local/share/applications/firefox.desktop", Some(entry("Firefox", false))),
+                ]},
+                IndexedDir { path: "/usr/share/applications".into(), mtime: 6, files: vec![
+                    file("/usr/share/applications/firefox.desktop", Some(entry("Shadowed", false))),
+                    file("/usr/share/applications/hidden.desktop", Some(entry("Hidden", false))),
+                    file("/usr/share/applications/tool.desktop", Some(entry("Tool", true))),
+                ]},
+            ],
+        };
+        let parsed = DesktopIndex::deserialize(&index.serialize()).unwrap();
+        assert_eq!(parsed, index);
+        assert_eq!(parsed.entries().iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), ["Firefox"]);
+        assert!(DesktopIndex::deserialize("APP_CACHE_V9\n").is_none());
+    }
+
+    #[test]
+    fn test_window_matches() {
+        let mut app = App::new("Files".into(), "env GTK_THEME=x nautilus --new-window".into(), "org.gnome.Nautilus".into());
+        assert!(window_matches(&app, "org.gnome.nautilus"));
+        assert!(window_matches(&app, "nautilus"));
+        app.wm_class = Some("nautilus-main".into());
+        assert!(!window_matches(&app, "nautilus"));
+        assert!(window_matches(&app, "nautilus-main"));
+        let game = App::new("Game".into(), "steam steam://rungameid/1".into(), "/tmp/game.png".into());
+        assert!(!window_matches(&game, "steam"));
+    }
+
+    #[test]
+    fn test_categories() {
+        let cats = |list: &str| split_list(list);
+        assert_eq!(menu_category(&cats("GTK;GNOME;Network;WebBrowser;")), Some("Internet"));
+        assert_eq!(menu_category(&cats("Qt;AudioVideo;Player")), Some("Multimedia"));
+        assert_eq!(menu_category(&cats("GTK;X-Custom")), None);
+        assert_eq!(category_query("cat:dev vim ", "cat:"), Some(("Development", "vim")));
+        assert_eq!(category_query("cat:Games", "cat:"), Some(("Games", "")));
+        assert_eq!(category_query("cat:nope", "cat:"), None);
+        assert_eq!(category_query("cat:games", ""), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("avahi*", "Avahi Zeroconf Browser"));
+        assert!(glob_match("*zeroconf*", "Avahi Zeroconf Browser"));
+        assert!(glob_match("Qt? *", "Qt5 Settings"));
+        assert!(!glob_match("avahi", "Avahi Zeroconf Browser"));
+        assert!(!glob_match("*ssh", "Avahi SSH Server Browser"));
+    }
+
+    #[test]
+    fn test_validate_launch_options() {
+        let env = |rows: &[(&str, &str)]| rows.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>();
+        assert!(validate_launch_options(&env(&[("DXVK_HUD", "fps"), ("", "")]), "", "gamemoderun %command%").is_empty());
+        assert!(validate_launch_options(&[], "", "sh -c 'echo \"hi\"' %command%").is_empty());
+        assert_eq!(validate_launch_options(&env(&[("1X", "a"), ("A", "1"), ("A", "b,c")]), "", "").len(), 3);
+        assert_eq!(validate_launch_options(&[], "/nonexistent/dir", "a | b 'c").len(), 3);
+        assert_eq!(Launch::with_custom("app --flag", Some("env X=1 %command% -v")).shell_line(), "env X=1 app --flag -v");
+    }
+
+    #[test]
+    fn test_parse_flatpak_list() {
+        let apps = parse_flatpak_list("org.gimp.GIMP\tGNU Image Manipulation Program\tflathub\tx86_64\tstable\tsystem\nbroken line\n");
+        assert_eq!(apps, [FlatpakApp {
+            id:     "org.gimp.GIMP".into(),
+            name:   "GNU Image Manipulation Program".into(),
+            origin: "flathub".into(),
+            arch:   "x86_64".into(),
+            branch: "stable".into(),
+            installation: "system".into(),
+        }]);
+    }
+
+    #[test]
+    fn test_steam_shortcuts_and_compat_tools() {
+        let mut data = b"\x00shortcuts\x00\x000\x00\x02appid\x00".to_vec();
+        data.extend_from_slice(&3_000_000_000u32.to_le_bytes());
+        data.extend_from_slice(b"\x01AppName\x00My Game\x00\x01Exe\x00\"/opt/game\"\x00\x08\x08\x08");
+        let root = parse_binary_vdf(&data, &mut 0).unwrap();
+        let Some(BinVdf::Map(shortcuts)) = root.get("shortcuts") else { panic!("no shortcuts map") };
+        assert_eq!(shortcuts[0].1.get("appname").and_then(BinVdf::as_str), Some("My Game"));
+        assert!(matches!(shortcuts[0].1.get("appid"), Some(BinVdf::Int(3_000_000_000))));
+
+        let config = "\"InstallConfigStore\"\n{\n\t\"CompatToolMapping\"\n\t{\n\t\t\"0\"\n\t\t{\n\t\t\t\"name\"\t\t\"proton_9\"\n\t\t}\n\t\t\"730\"\n\t\t{\n\t\t\t\"name\"\t\t\"GE-Proton9-20\"\n\t\t\t\"config\"\t\t\"\"\n\t\t}\n\t}\n\t\"Other\"\n\t{\n\t\t\"name\"\t\t\"x\"\n\t}\n}\n";
+        let tools = parse_compat_tools(config);
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools.get("730").map(String::as_str), Some("GE-Proton9-20"));
+    }
+
+    #[test]
+    fn test_recolor_symbolic() {
+        // Gray foreground at half alpha, a red accent, a transparent pixel.
+        let mut img = egui::ColorImage::from_rgba_unmultiplied([3, 1], &[46, 52, 54, 128, 204, 0, 0, 255, 0, 0, 0, 0]);
+        recolor_symbolic(&mut img, egui::Color32::WHITE);
+        assert_eq!(img.pixels[0].to_srgba_unmultiplied(), [255, 255, 255, 128]);
+        assert_eq!(img.pixels[1], egui::Color32::from_rgb(204, 0, 0));
+        assert_eq!(img.pixels[2], egui::Color32::TRANSPARENT);
+        assert!(is_symbolic("/x/network-wireless-symbolic.svg") && !is_symbolic("/x/firefox.svg"));
     }
 }
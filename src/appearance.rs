@@ -0,0 +1,228 @@
+//! Desktop dark/light preference (`org.freedesktop.appearance` `color-scheme`).
+//!
+//! Design:
+//! - Read from the XDG Settings portal, and followed through its
+//!   `SettingChanged` signal on a background tokio runtime like `network.rs`.
+//! - Without a portal (or one that doesn't know the key), GNOME's
+//!   `color-scheme` is read and followed with `gsettings monitor` instead.
+//! - Only active when `dark-theme` or `light-theme` is set. The GUI maps the
+//!   scheme to a profile with `profile_for` and reloads when it changes.
+//! - The startup read is blocking so the first frame already has the right
+//!   theme, instead of flashing the other one.
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use zbus::Connection;
+use zbus::zvariant::{OwnedValue, Value};
+use crate::gui::Config;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const PORTAL_BUS:     &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH:    &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_IFACE: &str = "org.freedesktop.portal.Settings";
+const NAMESPACE:      &str = "org.freedesktop.appearance";
+const KEY:            &str = "color-scheme";
+
+const T_CALL: Duration = Duration::from_secs(1);
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScheme {
+    NoPreference = 0,
+    Dark         = 1,
+    Light        = 2,
+}
+
+impl ColorScheme {
+    /// The portal's encoding: 0 no preference, 1 dark, 2 light.
+    fn from_u32(v: u32) -> Self {
+        match v {
+            1 => ColorScheme::Dark,
+            2 => ColorScheme::Light,
+            _ => ColorScheme::NoPreference,
+        }
+    }
+
+    /// gsettings' `'prefer-dark'` / `'prefer-light'` / `'default'`.
+    fn from_gsettings(s: &str) -> Self {
+        match s.trim().trim_matches('\'') {
+            "prefer-dark"  => ColorScheme::Dark,
+            "prefer-light" => ColorScheme::Light,
+            _              => ColorScheme::NoPreference,
+        }
+    }
+}
+
+/// The profile to load: `dark-theme` / `light-theme` for that scheme when
+/// set, otherwise `theme`.
+pub fn profile_for(config: &Config, scheme: ColorScheme) -> &str {
+    let themed = match scheme {
+        ColorScheme::Dark         => config.dark_theme.as_str(),
+        ColorScheme::Light        => config.light_theme.as_str(),
+        ColorScheme::NoPreference => "",
+    };
+    if themed.is_empty() { &config.theme } else { themed }
+}
+
+pub fn is_enabled(config: &Config) -> bool {
+    !config.dark_theme.is_empty() || !config.light_theme.is_empty()
+}
+
+/// The desktop's current preference, asking the portal first and gsettings
+/// second; `NoPreference` when neither answers.
+pub fn current() -> ColorScheme {
+    let portal = tokio::runtime::Builder::new_current_thread().enable_all().build().ok()
+        .and_then(|rt| rt.block_on(async {
+            let conn = Connection::session().await.ok()?;
+            read_portal(&conn).await
+        }));
+    portal.or_else(read_gsettings).unwrap_or(ColorScheme::NoPreference)
+}
+
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+/// Follows the preference in the background; `None` when `dark-theme` and
+/// `light-theme` are both unset.
+pub struct AppearanceWatcher {
+    scheme: Arc<AtomicU8>,
+}
+
+impl AppearanceWatcher {
+    pub fn new(config: &Config, initial: ColorScheme, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
+        if !is_enabled(config) { return None; }
+
+        let scheme = Arc::new(AtomicU8::new(initial as u8));
+        let scheme_bg = Arc::clone(&scheme);
+        let waker: Waker = Arc::new(on_change);
+
+        thread::spawn(move || {
+            let followed = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(follow_portal(&scheme_bg, &waker)),
+                Err(e) => { tracing::warn!(target: "appearance", "runtime error: {e}"); false }
+            };
+            if !followed { follow_gsettings(&scheme_bg, &waker); }
+        });
+
+        Some(AppearanceWatcher { scheme })
+    }
+
+    pub fn scheme(&self) -> ColorScheme {
+        ColorScheme::from_u32(self.scheme.load(Ordering::SeqCst) as u32)
+    }
+}
+
+/// Store `new`, and wake the GUI if it differs from what was there.
+fn update(scheme: &AtomicU8, new: ColorScheme, waker: &Waker) {
+    if scheme.swap(new as u8, Ordering::SeqCst) != new as u8 { waker(); }
+}
+
+// ============================================================================
+// Portal
+// ============================================================================
+
+async fn read_portal(conn: &Connection) -> Option<ColorScheme> {
+    let call = conn.call_method(Some(PORTAL_BUS), PORTAL_PATH, Some(SETTINGS_IFACE), "Read", &(NAMESPACE, KEY));
+    let msg = tokio::time::timeout(T_CALL, call).await.ok()?.ok()?;
+    let v: OwnedValue = msg.body().deserialize().ok()?;
+    scheme_from(&v)
+}
+
+/// `Read` wraps the value in a second variant; signals don't.
+fn scheme_from(v: &OwnedValue) -> Option<ColorScheme> {
+    let n = match &**v {
+        Value::Value(inner) => u32::try_from(inner.as_ref()).ok()?,
+        other               => u32::try_from(other).ok()?,
+    };
+    Some(ColorScheme::from_u32(n))
+}
+
+fn setting_changed_rule() -> zbus::Result<zbus::MatchRule<'static>> {
+    Ok(zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(PORTAL_BUS)?
+        .interface(SETTINGS_IFACE)?
+        .member("SettingChanged")?
+        .build())
+}
+
+/// Follow `SettingChanged` until the bus goes away. `false` when the portal
+/// can't be used at all, so the caller falls back to gsettings.
+async fn follow_portal(scheme: &AtomicU8, waker: &Waker) -> bool {
+    let Ok(conn) = Connection::session().await else { return false };
+    let Some(now) = read_portal(&conn).await else { return false };
+    update(scheme, now, waker);
+
+    let Ok(rule) = setting_changed_rule() else { return false };
+    let Ok(mut signals) = zbus::MessageStream::for_match_rule(rule, &conn, None).await else { return false };
+    while let Some(Ok(msg)) = signals.next().await {
+        let Ok((namespace, key, value)) = msg.body().deserialize::<(String, String, OwnedValue)>() else { continue };
+        if namespace != NAMESPACE || key != KEY { continue; }
+        if let Some(s) = scheme_from(&value) { update(scheme, s, waker); }
+    }
+    true
+}
+
+// ============================================================================
+// gsettings fallback
+// ============================================================================
+
+fn read_gsettings() -> Option<ColorScheme> {
+    let out = Command::new("gsettings").args(["get", "org.gnome.desktop.interface", "color-scheme"]).output().ok()?;
+    out.status.success().then(|| ColorScheme::from_gsettings(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// `gsettings monitor` prints `color-scheme: 'prefer-dark'` on each change.
+fn follow_gsettings(scheme: &AtomicU8, waker: &Waker) {
+    let child = Command::new("gsettings")
+        .args(["monitor", "org.gnome.desktop.interface", "color-scheme"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::warn!(target: "appearance", "no portal and no gsettings ({e}); dark-theme/light-theme won't follow the desktop");
+            return;
+        }
+    };
+    if let Some(now) = read_gsettings() { update(scheme, now, waker); }
+    let Some(stdout) = child.stdout.take() else { return };
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        if let Some((_, value)) = line.split_once(':') {
+            update(scheme, ColorScheme::from_gsettings(value), waker);
+        }
+    }
+    let _ = child.wait();
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_for() {
+        let mut config = Config { theme: "nord".into(), dark_theme: "gruvbox".into(), ..Config::default() };
+        assert_eq!(profile_for(&config, ColorScheme::Dark), "gruvbox");
+        assert_eq!(profile_for(&config, ColorScheme::Light), "nord");
+        assert_eq!(profile_for(&config, ColorScheme::NoPreference), "nord");
+        config.light_theme = "light".into();
+        assert_eq!(profile_for(&config, ColorScheme::Light), "light");
+        assert_eq!(ColorScheme::from_gsettings("'prefer-dark'\n"), ColorScheme::Dark);
+        assert_eq!(ColorScheme::from_gsettings("'default'"), ColorScheme::NoPreference);
+    }
+}
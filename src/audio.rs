@@ -0,0 +1,353 @@
+//! Default-sink and default-source volume, mute and sink switching over the
+//! native PipeWire protocol, so the audio controls follow changes as they
+//! happen instead of `wpctl` being forked on a timer.
+//!
+//! Design:
+//! - A PipeWire main loop runs on its own thread (PipeWire objects aren't
+//!   `Send`); the GUI reaches it through a `pipewire::channel`.
+//! - The `default` metadata object names the default sink and source
+//!   (`default.audio.sink` / `default.audio.source`); every `Audio/Sink` and
+//!   `Audio/Source` node is bound and its `Props` param subscribed, so
+//!   switching devices needs no re-query.
+//! - Switching writes `default.configured.audio.sink`, like `wpctl
+//!   set-default`; the session manager then updates `default.audio.sink`.
+//! - Volumes use wpctl's scale: the cube root of the linear channel volume.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Cursor;
+use std::rc::Rc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use pipewire::metadata::{Metadata, MetadataListener};
+use pipewire::node::{Node, NodeListener};
+use pipewire::spa::param::ParamType;
+use pipewire::spa::pod::deserialize::PodDeserializer;
+use pipewire::spa::pod::serialize::PodSerializer;
+use pipewire::spa::pod::{Object, Pod, Property, PropertyFlags, Value, ValueArray};
+use pipewire::spa::sys::{SPA_PARAM_Props, SPA_PROP_channelVolumes, SPA_PROP_mute, SPA_TYPE_OBJECT_Props};
+use pipewire::types::ObjectType;
+
+use crate::system::{AudioState, SinkInfo};
+
+/// Called after every change pushed by the server.
+pub type Waker = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Sink,
+    Source,
+}
+
+impl Kind {
+    fn from_class(class: &str) -> Option<Kind> {
+        match class {
+            "Audio/Sink"   => Some(Kind::Sink),
+            "Audio/Source" => Some(Kind::Source),
+            _              => None,
+        }
+    }
+}
+
+/// Which node a request applies to.
+enum Target {
+    Default(Kind),
+    Id(u32),
+}
+
+enum Request {
+    Volume(Target, f32),
+    Mute(Target, bool),
+    DefaultSink(u32),
+}
+
+pub struct PipewireVolume {
+    tx: pipewire::channel::Sender<Request>,
+}
+
+impl PipewireVolume {
+    /// Starts the PipeWire thread and waits for it to connect; an error means
+    /// no PipeWire server is reachable and the caller should fall back.
+    pub fn connect(shared: Arc<Mutex<AudioState>>, on_change: Waker) -> Result<Self, Box<dyn Error>> {
+        let (tx, rx)             = pipewire::channel::channel::<Request>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+
+        thread::spawn(move || {
+            if let Err(e) = run(shared, on_change, rx, &ready_tx) {
+                let _ = ready_tx.send(Err(e.to_string()));
+            }
+        });
+        ready_rx.recv().map_err(|_| "PipeWire thread exited")??;
+        Ok(PipewireVolume { tx })
+    }
+
+    /// Set the default sink's volume (wpctl scale, 1.0 = 100%).
+    pub fn set_volume(&self, volume: f32) {
+        let _ = self.tx.send(Request::Volume(Target::Default(Kind::Sink), volume));
+    }
+
+    pub fn set_mute(&self, muted: bool) {
+        let _ = self.tx.send(Request::Mute(Target::Default(Kind::Sink), muted));
+    }
+
+    /// Set one sink's volume, default or not.
+    pub fn set_sink_volume(&self, id: u32, volume: f32) {
+        let _ = self.tx.send(Request::Volume(Target::Id(id), volume));
+    }
+
+    /// Make `id` the default sink.
+    pub fn set_default_sink(&self, id: u32) {
+        let _ = self.tx.send(Request::DefaultSink(id));
+    }
+
+    /// Set the default source's (microphone's) volume.
+    pub fn set_source_volume(&self, volume: f32) {
+        let _ = self.tx.send(Request::Volume(Target::Default(Kind::Source), volume));
+    }
+
+    pub fn set_source_mute(&self, muted: bool) {
+        let _ = self.tx.send(Request::Mute(Target::Default(Kind::Source), muted));
+    }
+}
+
+/// A bound `Audio/Sink` or `Audio/Source` node.
+struct Device {
+    node:        Node,
+    _listener:   NodeListener,
+    kind:        Kind,
+    name:        String,
+    description: String,
+    channels:    usize,
+    volume:      Option<f32>,
+    muted:       bool,
+}
+
+#[derive(Default)]
+struct State {
+    /// Audio nodes by global id.
+    devices:        HashMap<u32, Device>,
+    default_sink:   Option<String>,
+    default_source: Option<String>,
+    metadata:       Option<(Metadata, MetadataListener)>,
+}
+
+impl State {
+    fn default_device(&self, kind: Kind) -> Option<(u32, &Device)> {
+        let name = match kind {
+            Kind::Sink   => self.default_sink.as_ref()?,
+            Kind::Source => self.default_source.as_ref()?,
+        };
+        self.devices.iter()
+            .find(|(_, d)| d.kind == kind && &d.name == name)
+            .map(|(id, d)| (*id, d))
+    }
+
+    fn target(&self, target: &Target) -> Option<&Device> {
+        match target {
+            Target::Default(kind) => self.default_device(*kind).map(|(_, d)| d),
+            Target::Id(id)        => self.devices.get(id),
+        }
+    }
+}
+
+fn run(
+    shared:    Arc<Mutex<AudioState>>,
+    on_change: Waker,
+    rx:        pipewire::channel::Receiver<Request>,
+    ready_tx:  &mpsc::Sender<Result<(), String>>,
+) -> Result<(), Box<dyn Error>> {
+    pipewire::init();
+    let main_loop = pipewire::main_loop::MainLoopRc::new(None)?;
+    let context   = pipewire::context::ContextRc::new(&main_loop, None)?;
+    let core      = context.connect_rc(None)?;
+    let registry  = core.get_registry_rc()?;
+    let state     = Rc::new(RefCell::new(State::default()));
+
+    // Publish the sink list and the default devices' volumes, if we know them.
+    let publish = {
+        let state = Rc::downgrade(&state);
+        Rc::new(move || {
+            let Some(state) = state.upgrade() else { return };
+            let state   = state.borrow();
+            let default = state.default_device(Kind::Sink).map(|(id, _)| id);
+            let mut sinks: Vec<SinkInfo> = state.devices.iter()
+                .filter(|(_, d)| d.kind == Kind::Sink)
+                .map(|(id, d)| SinkInfo { id: *id, description: d.description.clone(), is_default: Some(*id) == default })
+                .collect();
+            sinks.sort_by_key(|s| s.id);
+            let sink   = state.default_device(Kind::Sink).map(|(_, d)| d);
+            let source = state.default_device(Kind::Source).map(|(_, d)| d);
+            {
+                let mut shared = shared.lock().unwrap();
+                if let Some(v) = sink.and_then(|d| d.volume) { shared.volume = v; }
+                shared.muted        = sink.is_some_and(|d| d.muted);
+                shared.sinks        = sinks;
+                shared.has_source   = source.is_some();
+                shared.source_muted = source.is_some_and(|d| d.muted);
+                if let Some(v) = source.and_then(|d| d.volume) { shared.source_volume = v; }
+            }
+            on_change();
+        })
+    };
+
+    let _registry_listener = registry.add_listener_local()
+        .global({
+            let registry = registry.downgrade();
+            let state    = Rc::clone(&state);
+            let publish  = Rc::clone(&publish);
+            move |obj| {
+                let Some(registry) = registry.upgrade() else { return };
+                let Some(props)    = obj.props else { return };
+                match obj.type_ {
+                    ObjectType::Node => {
+                        let Some(kind) = props.get("media.class").and_then(Kind::from_class) else { return };
+                        let Ok(node) = registry.bind::<Node, _>(obj) else { return };
+                        let id        = obj.id;
+                        let listener  = node.add_listener_local()
+                            .param({
+                                let state   = Rc::downgrade(&state);
+                                let publish = Rc::clone(&publish);
+                                move |_, _, _, _, param| {
+                                    let Some((vols, mute)) = param.and_then(node_props) else { return };
+                                    let Some(state) = state.upgrade() else { return };
+                                    if let Some(dev) = state.borrow_mut().devices.get_mut(&id) {
+                                        if let Some(vols) = vols {
+                                            dev.channels = vols.len();
+                                            dev.volume   = vols.iter().cloned().reduce(f32::max).map(f32::cbrt);
+                                        }
+                                        if let Some(mute) = mute { dev.muted = mute; }
+                                    }
+                                    publish();
+                                }
+                            })
+                            .register();
+                        node.subscribe_params(&[ParamType::Props]);
+                        let name        = props.get("node.name").unwrap_or_default().to_string();
+                        let description = props.get("node.description")
+                            .or_else(|| props.get("node.nick"))
+                            .unwrap_or(&name)
+                            .to_string();
+                        let device = Device { node, _listener: listener, kind, name, description, channels: 0, volume: None, muted: false };
+                        state.borrow_mut().devices.insert(id, device);
+                        publish();
+                    }
+                    ObjectType::Metadata if props.get("metadata.name") == Some("default") => {
+                        let Ok(metadata) = registry.bind::<Metadata, _>(obj) else { return };
+                        let listener = metadata.add_listener_local()
+                            .property({
+                                let state   = Rc::downgrade(&state);
+                                let publish = Rc::clone(&publish);
+                                move |_, key, _, value| {
+                                    let Some(state) = state.upgrade() else { return 0 };
+                                    let name = value.and_then(default_device_name);
+                                    match key {
+                                        Some("default.audio.sink")   => state.borrow_mut().default_sink   = name,
+                                        Some("default.audio.source") => state.borrow_mut().default_source = name,
+                                        _                            => return 0,
+                                    }
+                                    publish();
+                                    0
+                                }
+                            })
+                            .register();
+                        state.borrow_mut().metadata = Some((metadata, listener));
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .global_remove({
+            let state   = Rc::clone(&state);
+            let publish = Rc::clone(&publish);
+            move |id| {
+                let removed = state.borrow_mut().devices.remove(&id).is_some();
+                if removed { publish(); }
+            }
+        })
+        .register();
+
+    let _rx = rx.attach(main_loop.loop_(), {
+        let state = Rc::clone(&state);
+        move |request| {
+            let state = state.borrow();
+            let (device, property) = match &request {
+                Request::Volume(target, volume) => {
+                    let Some(dev) = state.target(target) else { return };
+                    let linear = volume.max(0.0).powi(3);
+                    (dev, prop(SPA_PROP_channelVolumes, Value::ValueArray(ValueArray::Float(vec![linear; dev.channels.max(1)]))))
+                }
+                Request::Mute(target, muted) => {
+                    let Some(dev) = state.target(target) else { return };
+                    (dev, prop(SPA_PROP_mute, Value::Bool(*muted)))
+                }
+                Request::DefaultSink(id) => {
+                    let (Some(sink), Some((metadata, _))) = (state.devices.get(id), &state.metadata) else { return };
+                    let value = format!("{{\"name\": {}}}", json_string(&sink.name));
+                    metadata.set_property(0, "default.configured.audio.sink", Some("Spa:String:JSON"), Some(&value));
+                    return;
+                }
+            };
+            match props_pod(property) {
+                Ok(bytes) => if let Some(pod) = Pod::from_bytes(&bytes) {
+                    device.node.set_param(ParamType::Props, 0, pod);
+                },
+                Err(e) => tracing::warn!(target: "audio", "building Props params: {e}"),
+            }
+        }
+    });
+
+    let _ = ready_tx.send(Ok(()));
+    main_loop.run();
+    Ok(())
+}
+
+/// `channelVolumes` and `mute` from a `Props` param, when it's an object.
+fn node_props(param: &Pod) -> Option<(Option<Vec<f32>>, Option<bool>)> {
+    let (_, value) = PodDeserializer::deserialize_any_from(param.as_bytes()).ok()?;
+    let Value::Object(obj) = value else { return None };
+    let (mut volumes, mut mute) = (None, None);
+    for p in obj.properties {
+        match (p.key, p.value) {
+            (SPA_PROP_channelVolumes, Value::ValueArray(ValueArray::Float(v))) if !v.is_empty() => volumes = Some(v),
+            (SPA_PROP_mute, Value::Bool(m))                                                     => mute    = Some(m),
+            _                                                                                   => {}
+        }
+    }
+    Some((volumes, mute))
+}
+
+fn prop(key: u32, value: Value) -> Property {
+    Property { key, flags: PropertyFlags::empty(), value }
+}
+
+/// Serialized `Props { <property> }`.
+fn props_pod(property: Property) -> Result<Vec<u8>, Box<dyn Error>> {
+    let value = Value::Object(Object {
+        type_:      SPA_TYPE_OBJECT_Props,
+        id:         SPA_PARAM_Props,
+        properties: vec![property],
+    });
+    let (cursor, _) = PodSerializer::serialize(Cursor::new(Vec::new()), &value)
+        .map_err(|e| format!("{e:?}"))?;
+    Ok(cursor.into_inner())
+}
+
+/// `default.audio.sink` / `.source` hold `{"name": "<node.name>"}`.
+fn default_device_name(value: &str) -> Option<String> {
+    crate::json::Json::parse(value)?.get("name")?.as_str().map(str::to_string)
+}
+
+/// `s` as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' | '\\'   => { out.push('\\'); out.push(c); }
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c            => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,63 @@
+//! XDG autostart: with `run-autostart: true` the first instance of a login
+//! session launches the entries in `~/.config/autostart` and
+//! `$XDG_CONFIG_DIRS/autostart`. That covers minimal Wayland compositors that
+//! have no session manager of their own: start `tusk-launcher` in daemon mode
+//! from the compositor config and it brings up the usual tray apps, agents
+//! and so on.
+//!
+//! A marker in `$XDG_RUNTIME_DIR` (cleared at logout) keeps daemon restarts
+//! from launching everything twice.
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+};
+
+const MARKER: &str = "tusk-launcher-autostart.done";
+
+/// Launches every applicable autostart entry, once per session.
+pub fn run() {
+    if let Some(marker) = crate::paths::runtime_dir().map(|d| d.join(MARKER)) {
+        if marker.exists() { return; }
+        if let Err(e) = fs::write(&marker, "") {
+            tracing::warn!(target: "launch", "Failed to write autostart marker {}: {e}", marker.display());
+        }
+    }
+    for (name, exec) in entries() {
+        let Some(argv) = crate::exec::split(&exec) else { continue };
+        match Command::new(&argv[0]).args(&argv[1..])
+            .current_dir(std::env::var("HOME").unwrap_or_else(|_| "/".into()))
+            .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+            .spawn()
+        {
+            // Reap in the background; autostart apps usually outlive us anyway.
+            Ok(mut child) => { thread::spawn(move || child.wait()); }
+            Err(e)        => tracing::warn!(target: "launch", "Failed to autostart {name}: {e}"),
+        }
+    }
+}
+
+/// (Name, Exec) of the entries to start. Per the Desktop Application
+/// Autostart Specification a file in `~/.config/autostart` shadows the
+/// system one with the same name, so `Hidden=true` there disables it.
+fn entries() -> Vec<(String, String)> {
+    let dirs: Vec<PathBuf> = std::iter::once(crate::paths::config_home())
+        .chain(crate::paths::config_dirs())
+        .map(|d| d.join("autostart"))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut files: Vec<PathBuf> = dirs.into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|rd| rd.filter_map(Result::ok).map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "desktop"))
+        .filter(|p| p.file_name().is_some_and(|name| seen.insert(name.to_owned())))
+        .collect();
+    files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+
+    files.iter()
+        .filter_map(|path| crate::app_launcher::parse_autostart_entry(path))
+        .collect()
+}
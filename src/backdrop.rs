@@ -0,0 +1,45 @@
+//! Blur behind the launcher window (`backdrop-filter: blur` on `.main-window`),
+//! so a translucent `rgba()` background reads as frosted glass.
+//!
+//! Design:
+//! - Only the compositor can blur what's behind a window, and each one is
+//!   asked differently. SwayFX takes `blur enable` for our window, matched by
+//!   pid; plain Sway rejects the command, which is ignored. KWin on X11
+//!   blurs windows carrying `_KDE_NET_WM_BLUR_BEHIND_REGION`, set with
+//!   `xprop` on the windows `xdotool` finds for our pid.
+//! - Hyprland blurs every translucent window while `decoration:blur` is on,
+//!   and has no per-window switch to flip from outside; its rules match the
+//!   window's `tusk-launcher` class instead. KWin on Wayland only offers a
+//!   protocol on our own Wayland connection, which winit doesn't expose.
+//! - Runs on a thread after the window is shown, like placement; the hints
+//!   stick to the window, so repeating them is harmless.
+use crate::windows::{backend, run, Backend};
+
+/// Window class / Wayland app id, for compositor rules.
+pub const APP_ID: &str = "tusk-launcher";
+
+const KDE_BLUR: &str = "_KDE_NET_WM_BLUR_BEHIND_REGION";
+
+/// Ask the compositor to blur behind this process's windows.
+pub fn request_blur() {
+    let pid = std::process::id().to_string();
+    match backend() {
+        Some(Backend::Sway) => {
+            // Only SwayFX knows `blur`; plain Sway failing is expected.
+            let _ = run(&["swaymsg", &format!("[pid={pid}]"), "blur", "enable"]);
+        }
+        Some(Backend::Wmctrl) => {
+            let Some(ids) = run(&["xdotool", "search", "--pid", &pid]) else {
+                tracing::warn!(target: "window", "Can't find the launcher window to blur (is xdotool installed?)");
+                return;
+            };
+            // An empty region means the whole window.
+            for id in ids.split_whitespace() {
+                if run(&["xprop", "-id", id, "-f", KDE_BLUR, "32c", "-set", KDE_BLUR, "0"]).is_none() {
+                    tracing::warn!(target: "window", "Failed to set {KDE_BLUR} on window {id}");
+                }
+            }
+        }
+        Some(Backend::Hyprland) | None => {}
+    }
+}
@@ -0,0 +1,181 @@
+//! Right-to-left text: bidi reordering for display, and whether the
+//! launcher lays itself out right to left.
+//!
+//! Design:
+//! - egui draws a string's characters left to right in memory order, so
+//!   Hebrew and Arabic names come out backwards. `visual` reorders a line
+//!   the way the Unicode Bidirectional Algorithm would for display: strong
+//!   runs, numbers with their separators, neutrals between them, and
+//!   mirrored brackets. It covers what app names and descriptions contain,
+//!   without explicit embeddings or isolates, and doesn't join Arabic
+//!   letters into their contextual forms — egui has no shaper for that.
+//! - Strings without right-to-left letters come back borrowed, so calling
+//!   it on every label each frame costs a scan.
+//! - The layout direction comes from `.main-window { direction: rtl }`, or
+//!   with `auto` (the default) from the locale's language.
+
+use std::borrow::Cow;
+
+/// Languages written right to left, as locale prefixes.
+const RTL_LANGUAGES: &[&str] = &["ar", "he", "iw", "fa", "ur", "yi", "ps", "sd", "ug", "ckb", "dv"];
+
+/// Whether `LC_ALL`, `LC_MESSAGES` or `LANG` (the first one set) names a
+/// right-to-left language.
+pub fn locale_is_rtl() -> bool {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    let language = locale.split(['_', '.', '@']).next().unwrap_or("");
+    RTL_LANGUAGES.contains(&language)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Class {
+    /// Left-to-right letter.
+    L,
+    /// Right-to-left letter (Hebrew, Arabic and neighbours).
+    R,
+    /// Digit.
+    En,
+    /// Whitespace, punctuation, symbols.
+    N,
+}
+
+fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32,
+        0x0590..=0x065F | 0x066A..=0x06EF | 0x06FA..=0x08FF |
+        0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+}
+
+fn class(c: char) -> Class {
+    if c.is_ascii_digit() || matches!(c, '\u{0660}'..='\u{0669}' | '\u{06F0}'..='\u{06F9}') {
+        Class::En
+    } else if is_rtl_char(c) {
+        Class::R
+    } else if c.is_alphanumeric() {
+        Class::L
+    } else {
+        Class::N
+    }
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')', ')' => '(',
+        '[' => ']', ']' => '[',
+        '{' => '}', '}' => '{',
+        '<' => '>', '>' => '<',
+        '«' => '»', '»' => '«',
+        other => other,
+    }
+}
+
+/// Whether `s` has any right-to-left letters.
+pub fn has_rtl(s: &str) -> bool {
+    s.chars().any(is_rtl_char)
+}
+
+/// `s` in display order: a line to draw left to right that reads correctly.
+/// The paragraph direction is its first strong letter's.
+pub fn visual(s: &str) -> Cow<'_, str> {
+    if !has_rtl(s) { return Cow::Borrowed(s); }
+    let chars: Vec<char> = s.chars().collect();
+    let mut classes: Vec<Class> = chars.iter().map(|&c| class(c)).collect();
+    let base = match classes.iter().find(|c| matches!(c, Class::L | Class::R)) {
+        Some(Class::R) => Class::R,
+        _              => Class::L,
+    };
+
+    // Numbers: one separator between digits (1.5, 10,000, 3:45) joins
+    // them, and currency/percent signs next to digits go with them.
+    for i in 1..chars.len().saturating_sub(1) {
+        if matches!(chars[i], '.' | ',' | ':' | '/' | '+' | '-') && classes[i - 1] == Class::En && classes[i + 1] == Class::En {
+            classes[i] = Class::En;
+        }
+    }
+    let terminator = |c: char| matches!(c, '%' | '$' | '#' | '°' | '€' | '£' | '¢' | '‰');
+    for (i, &c) in chars.iter().enumerate() {
+        if !terminator(c) { continue; }
+        let touches = |range: &mut dyn Iterator<Item = usize>| {
+            range.take_while(|&j| terminator(chars[j]) || classes[j] == Class::En).any(|j| classes[j] == Class::En)
+        };
+        if touches(&mut (0..i).rev()) || touches(&mut (i + 1..chars.len())) { classes[i] = Class::En; }
+    }
+
+    // Digits after a left-to-right letter (or at the start of a
+    // left-to-right line) read as part of that text.
+    let mut strong = base;
+    for c in classes.iter_mut() {
+        match *c {
+            Class::L | Class::R             => strong = *c,
+            Class::En if strong == Class::L => *c = Class::L,
+            _                               => {}
+        }
+    }
+
+    // Neutrals take the direction around them when both sides agree
+    // (digits count as right to left here), else the paragraph's.
+    let as_strong = |c: Class| match c { Class::En => Class::R, other => other };
+    let mut i = 0;
+    while i < classes.len() {
+        if classes[i] != Class::N { i += 1; continue; }
+        let start = i;
+        while i < classes.len() && classes[i] == Class::N { i += 1; }
+        let before = if start == 0 { base } else { as_strong(classes[start - 1]) };
+        let after  = if i == classes.len() { base } else { as_strong(classes[i]) };
+        let dir    = if before == after { before } else { base };
+        for c in &mut classes[start..i] { *c = dir; }
+    }
+
+    let rtl_base = base == Class::R;
+    let mut levels: Vec<u8> = classes.iter().map(|c| match (rtl_base, c) {
+        (false, Class::L) => 0,
+        (false, Class::R) => 1,
+        (_, Class::En)    => 2,
+        (true, Class::R)  => 1,
+        (true, _)         => 2,
+        (false, _)        => 0,
+    }).collect();
+    // Trailing whitespace sits at the paragraph's level.
+    for (c, level) in chars.iter().zip(levels.iter_mut()).rev() {
+        if !c.is_whitespace() { break; }
+        *level = rtl_base as u8;
+    }
+
+    // Reverse every run at or above each level, from the highest down to 1.
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    let max = levels.iter().copied().max().unwrap_or(0);
+    for level in (1..=max).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level { i += 1; continue; }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level { i += 1; }
+            order[start..i].reverse();
+        }
+    }
+    Cow::Owned(order.into_iter().map(|i| if levels[i] % 2 == 1 { mirror(chars[i]) } else { chars[i] }).collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visual_order() {
+        assert!(matches!(visual("Firefox 2.0"), Cow::Borrowed("Firefox 2.0")));
+        assert_eq!(visual("שלום"), "םולש");
+        // Left-to-right line with a Hebrew word, and the reverse.
+        assert_eq!(visual("Firefox שלום עולם"), "Firefox םלוע םולש");
+        assert_eq!(visual("שלום Firefox"), "Firefox םולש");
+        // Numbers keep their digit order; brackets face the right way.
+        assert_eq!(visual("גרסה 2.0"), "2.0 הסרג");
+        assert_eq!(visual("(שלום) 50%"), "50% (םולש)");
+        assert_eq!(visual("متصفح الويب"), "بيولا حفصتم");
+    }
+}
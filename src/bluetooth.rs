@@ -0,0 +1,240 @@
+//! BlueZ integration (`org.bluez`).
+//!
+//! Design:
+//! - One `GetManagedObjects` call yields the adapter and every known device;
+//!   re-run whenever BlueZ emits a signal (PropertiesChanged, InterfacesAdded/
+//!   Removed) and on a slow poll as a fallback.
+//! - Only paired devices are listed — pairing needs an agent for PIN prompts,
+//!   which is out of scope; connecting an already-paired device does not.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use zbus::Connection;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+use crate::gui::Config;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const BLUEZ_BUS:     &str = "org.bluez";
+const ADAPTER_IFACE: &str = "org.bluez.Adapter1";
+const DEVICE_IFACE:  &str = "org.bluez.Device1";
+
+const T_CALL:        Duration = Duration::from_secs(3);
+/// Connect can take a while (profile negotiation), so it gets its own timeout.
+const T_CONNECT:     Duration = Duration::from_secs(20);
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct BluetoothDevice {
+    pub path:      String,
+    /// `Alias` (user-set name, falls back to `Name` inside BlueZ).
+    pub name:      String,
+    /// Freedesktop icon name BlueZ derives from the device class ("audio-headset", …).
+    pub icon:      Option<String>,
+    pub connected: bool,
+    /// `Battery1.Percentage`, when the device reports it.
+    pub battery:   Option<u8>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BluetoothState {
+    /// `None` when there is no adapter (or BlueZ isn't running).
+    pub adapter: Option<String>,
+    pub powered: bool,
+    pub devices: Vec<BluetoothDevice>,
+    /// Device paths with a Connect/Disconnect call in flight.
+    pub pending: Vec<String>,
+}
+
+pub type SharedBluetoothState = Arc<Mutex<BluetoothState>>;
+
+/// Called from the backend after every refresh, so the GUI can repaint.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+enum BluetoothAction {
+    Connect    { path: String },
+    Disconnect { path: String },
+    SetPowered(bool),
+}
+
+pub struct BluetoothHost {
+    pub state: SharedBluetoothState,
+    action_tx: tokio::sync::mpsc::UnboundedSender<BluetoothAction>,
+}
+
+impl BluetoothHost {
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
+        if !config.enable_bluetooth { return None; }
+
+        let state: SharedBluetoothState = Arc::new(Mutex::new(BluetoothState::default()));
+        let state_bg = Arc::clone(&state);
+        let waker: Waker = Arc::new(on_change);
+        let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(async {
+                    if let Err(e) = run_monitor(state_bg, action_rx, waker).await {
+                        tracing::warn!(target: "bluetooth", "{e}");
+                    }
+                }),
+                Err(e) => tracing::error!(target: "bluetooth", "runtime error: {e}"),
+            }
+        });
+
+        Some(BluetoothHost { state, action_tx })
+    }
+
+    pub fn connect(&self, path: &str) {
+        self.mark_pending(path);
+        let _ = self.action_tx.send(BluetoothAction::Connect { path: path.into() });
+    }
+
+    pub fn disconnect(&self, path: &str) {
+        self.mark_pending(path);
+        let _ = self.action_tx.send(BluetoothAction::Disconnect { path: path.into() });
+    }
+
+    pub fn set_powered(&self, on: bool) {
+        let _ = self.action_tx.send(BluetoothAction::SetPowered(on));
+    }
+
+    fn mark_pending(&self, path: &str) {
+        if let Ok(mut s) = self.state.lock() { s.pending.push(path.into()); }
+    }
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+async fn run_monitor(
+    state:         SharedBluetoothState,
+    mut action_rx: tokio::sync::mpsc::UnboundedReceiver<BluetoothAction>,
+    waker:         Waker,
+) -> zbus::Result<()> {
+    let conn = Connection::system().await?;
+    refresh(&conn, &state).await;
+    waker();
+
+    // Any signal from BlueZ (property changes, devices appearing/vanishing).
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(BLUEZ_BUS)?
+        .build();
+    let mut signals = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+    let mut poll    = tokio::time::interval(POLL_INTERVAL);
+
+    // Connect/Disconnect block for seconds; run them as tasks so the monitor
+    // keeps refreshing (and the "connecting…" state stays visible) meanwhile.
+    let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+
+    loop {
+        tokio::select! {
+            Some(_) = signals.next() => {}
+            _ = poll.tick()          => {}
+            Some(path) = done_rx.recv() => {
+                if let Ok(mut s) = state.lock() { s.pending.retain(|p| *p != path); }
+            }
+            action = action_rx.recv() => match action {
+                Some(BluetoothAction::Connect { path }) => spawn_device_call(&conn, path, "Connect", &done_tx),
+                Some(BluetoothAction::Disconnect { path }) => spawn_device_call(&conn, path, "Disconnect", &done_tx),
+                Some(BluetoothAction::SetPowered(on)) => {
+                    let adapter = state.lock().ok().and_then(|s| s.adapter.clone());
+                    if let Some(adapter) = adapter {
+                        let r = conn.call_method(
+                            Some(BLUEZ_BUS), adapter.as_str(), Some("org.freedesktop.DBus.Properties"), "Set",
+                            &(ADAPTER_IFACE, "Powered", Value::from(on)),
+                        ).await;
+                        if let Err(e) = r { tracing::warn!(target: "bluetooth", "set Powered failed: {e}"); }
+                    }
+                }
+                None => return Ok(()),
+            },
+        }
+        refresh(&conn, &state).await;
+        waker();
+    }
+}
+
+fn spawn_device_call(
+    conn:    &Connection,
+    path:    String,
+    method:  &'static str,
+    done_tx: &tokio::sync::mpsc::UnboundedSender<String>,
+) {
+    let conn    = conn.clone();
+    let done_tx = done_tx.clone();
+    tokio::spawn(async move {
+        let call = conn.call_method(Some(BLUEZ_BUS), path.as_str(), Some(DEVICE_IFACE), method, &());
+        match tokio::time::timeout(T_CONNECT, call).await {
+            Ok(Err(e)) => tracing::warn!(target: "bluetooth", "{method} {path} failed: {e}"),
+            Err(_)     => tracing::warn!(target: "bluetooth", "{method} {path} timed out"),
+            Ok(Ok(_))  => {}
+        }
+        let _ = done_tx.send(path);
+    });
+}
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+async fn refresh(conn: &Connection, state: &SharedBluetoothState) {
+    let objects: ManagedObjects = match tokio::time::timeout(T_CALL, conn.call_method(
+        Some(BLUEZ_BUS), "/", Some("org.freedesktop.DBus.ObjectManager"), "GetManagedObjects", &(),
+    )).await {
+        Ok(Ok(msg)) => msg.body().deserialize().unwrap_or_default(),
+        _           => ManagedObjects::new(),
+    };
+
+    let mut adapter = None;
+    let mut powered = false;
+    let mut devices = Vec::new();
+    for (path, ifaces) in &objects {
+        if let Some(props) = ifaces.get(ADAPTER_IFACE) {
+            // First adapter wins; multi-adapter setups are rare on desktops.
+            if adapter.is_none() {
+                adapter = Some(path.to_string());
+                powered = prop_bool(props, "Powered");
+            }
+        }
+        let Some(props) = ifaces.get(DEVICE_IFACE) else { continue };
+        if !prop_bool(props, "Paired") { continue; }
+        devices.push(BluetoothDevice {
+            path:      path.to_string(),
+            name:      prop_str(props, "Alias")
+                .or_else(|| prop_str(props, "Name"))
+                .or_else(|| prop_str(props, "Address"))
+                .unwrap_or_default(),
+            icon:      prop_str(props, "Icon"),
+            connected: prop_bool(props, "Connected"),
+            battery:   ifaces.get("org.bluez.Battery1")
+                .and_then(|b| b.get("Percentage"))
+                .and_then(|v| u8::try_from(v).ok()),
+        });
+    }
+    // Connected first, then alphabetical.
+    devices.sort_by(|a, b| b.connected.cmp(&a.connected).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+
+    if let Ok(mut s) = state.lock() {
+        s.adapter = adapter;
+        s.powered = powered;
+        s.devices = devices;
+    }
+}
+
+fn prop_bool(props: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    matches!(props.get(key).map(|v| &**v), Some(Value::Bool(true)))
+}
+
+fn prop_str(props: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    props.get(key).and_then(|v| <&str>::try_from(v).ok()).filter(|s| !s.is_empty()).map(str::to_string)
+}
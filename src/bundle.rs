@@ -0,0 +1,172 @@
+//! `tusk-launcher export FILE` / `import FILE`: settings in one tar archive,
+//! to move them to another machine or share them.
+//!
+//! Design:
+//! - The archive holds `config.toml`, `theme.css`, the `themes/` profiles and
+//!   the app cache (pins, launch options, names, hidden apps, history) as
+//!   `app_cache.txt`. The text form reads back into either store, so a
+//!   bundle moves between builds with and without SQLite.
+//! - Plain ustar, written and read here; `tar -xf` can open it too.
+//! - Import only takes those names and keeps the file it replaces as
+//!   `.bak`. It refuses while the launcher runs, which would save over the
+//!   imported cache.
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const BLOCK: usize = 512;
+
+/// (path inside the archive, contents).
+type Entry = (String, Vec<u8>);
+
+/// Write the settings to `path`; returns how many files went in.
+pub fn export(path: &Path) -> Result<usize, Box<dyn Error>> {
+    let dir = crate::paths::config_home().join("tusk-launcher");
+    let mut files = Vec::new();
+    for name in ["config.toml", "theme.css"] {
+        if let Ok(data) = fs::read(dir.join(name)) { files.push((name.to_string(), data)); }
+    }
+    if let Ok(entries) = fs::read_dir(dir.join("themes")) {
+        let mut themes: Vec<_> = entries.filter_map(Result::ok)
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| is_theme_file(name))
+            .collect();
+        themes.sort();
+        for name in themes {
+            files.push((format!("themes/{name}"), fs::read(dir.join("themes").join(&name))?));
+        }
+    }
+    let cache = crate::store::STORE.load()?;
+    files.push(("app_cache.txt".into(), crate::app_launcher::serialize_cache(&cache).into_bytes()));
+    fs::write(path, write_tar(&files)?)?;
+    Ok(files.len())
+}
+
+/// Unpack the settings in `path` into the config folder; returns how many
+/// files were taken.
+pub fn import(path: &Path) -> Result<usize, Box<dyn Error>> {
+    if crate::ipc::is_running() {
+        return Err("the launcher is running; quit it first (tusk-launcher quit)".into());
+    }
+    let dir   = crate::paths::config_home().join("tusk-launcher");
+    let files = read_tar(&fs::read(path)?)?;
+    let mut taken = 0;
+    for (name, data) in files {
+        if !is_settings_file(&name) {
+            eprintln!("Skipping {name}: not a launcher setting");
+            continue;
+        }
+        if name == "app_cache.txt" {
+            let cache = crate::app_launcher::deserialize_cache(std::str::from_utf8(&data)?)?;
+            let old   = crate::store::STORE.load()?;
+            fs::write(dir.join("app_cache.txt.bak"), crate::app_launcher::serialize_cache(&old))?;
+            crate::store::STORE.save(&cache)?;
+        } else {
+            let target = crate::paths::place_config_file(&format!("tusk-launcher/{name}"))?;
+            if target.exists() { fs::rename(&target, target.with_extension(format!("{}.bak", ext(&name))))?; }
+            fs::write(&target, data)?;
+        }
+        taken += 1;
+    }
+    Ok(taken)
+}
+
+fn ext(name: &str) -> &str {
+    name.rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+fn is_theme_file(name: &str) -> bool {
+    name.ends_with(".css") && !name.starts_with('.') && !name.contains('/')
+}
+
+fn is_settings_file(name: &str) -> bool {
+    match name.strip_prefix("themes/") {
+        Some(theme) => is_theme_file(theme),
+        None        => matches!(name, "config.toml" | "theme.css" | "app_cache.txt"),
+    }
+}
+
+// ============================================================================
+// ustar
+// ============================================================================
+
+fn write_tar(files: &[Entry]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut out = Vec::new();
+    for (name, data) in files {
+        if name.len() > 100 { return Err(format!("{name}: name too long").into()); }
+        let mut header = [0u8; BLOCK];
+        let mut field  = |at: usize, value: &[u8]| header[at..at + value.len()].copy_from_slice(value);
+        field(0,   name.as_bytes());
+        field(100, b"0000644\0");
+        field(108, b"0000000\0");
+        field(116, b"0000000\0");
+        field(124, format!("{:011o}\0", data.len()).as_bytes());
+        field(136, format!("{mtime:011o}\0").as_bytes());
+        field(148, b"        ");
+        field(156, b"0");
+        field(257, b"ustar\0");
+        field(263, b"00");
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..156].copy_from_slice(format!("{sum:06o}\0 ").as_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(data);
+        out.resize(out.len().next_multiple_of(BLOCK), 0);
+    }
+    out.resize(out.len() + 2 * BLOCK, 0);
+    Ok(out)
+}
+
+/// Regular files in a ustar archive; other entry types are skipped.
+fn read_tar(bytes: &[u8]) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    let mut at    = 0;
+    while at + BLOCK <= bytes.len() {
+        let header = &bytes[at..at + BLOCK];
+        if header.iter().all(|&b| b == 0) { break; }
+        if &header[257..262] != b"ustar" { return Err("not a tar archive".into()); }
+        let text = |range: std::ops::Range<usize>| {
+            let field = &header[range];
+            let end   = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+        let size  = usize::from_str_radix(text(124..136).trim(), 8).map_err(|_| "bad entry size")?;
+        let start = at + BLOCK;
+        let data  = bytes.get(start..start + size).ok_or("archive is cut short")?;
+        let name  = match text(345..500) {
+            prefix if prefix.is_empty() => text(0..100),
+            prefix                      => format!("{prefix}/{}", text(0..100)),
+        };
+        if matches!(header[156], b'0' | 0) {
+            files.push((name.trim_start_matches("./").to_string(), data.to_vec()));
+        }
+        at = start + size.next_multiple_of(BLOCK);
+    }
+    Ok(files)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tar_roundtrip() {
+        let files = vec![
+            ("config.toml".to_string(), b"theme = \"nord\"\n".to_vec()),
+            ("themes/nord.css".to_string(), vec![b'x'; 700]),
+            ("empty".to_string(), Vec::new()),
+        ];
+        let tar = write_tar(&files).unwrap();
+        assert_eq!(tar.len() % BLOCK, 0);
+        assert_eq!(read_tar(&tar).unwrap(), files);
+        assert!(read_tar(b"junk".repeat(200).as_slice()).is_err());
+        assert!(is_settings_file("themes/nord.css"));
+        assert!(!is_settings_file("themes/../../.bashrc") && !is_settings_file("../config.toml"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Inline calculator: a small recursive-descent evaluator for search queries
+//! like `2*(3+4)/5` or `sqrt(2)^2`.
+//!
+//! Grammar (lowest to highest precedence):
+//!   expr   = term   (('+' | '-') term)*
+//!   term   = unary (('*' | '/' | '%') unary)*        — juxtaposition `2pi` too
+//!   unary  = ('-' | '+') unary | power
+//!   power  = atom ('^' unary)?                       — right-associative, `-2^2` = -4
+//!   atom   = number | constant | func '(' expr ')' | '(' expr ')'
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Evaluate `query` if it looks like arithmetic. Bare numbers and plain words
+/// are rejected so ordinary app searches ("7zip", "2048") never turn into a
+/// calculator row.
+pub fn evaluate(query: &str) -> Option<f64> {
+    let q = query.trim().strip_prefix('=').unwrap_or(query.trim());
+    if !q.chars().any(|c| matches!(c, '+' | '-' | '*' | '/' | '%' | '^' | '(')) { return None; }
+
+    let mut p = Parser { s: q.as_bytes(), pos: 0 };
+    let v = p.expr()?;
+    p.skip_ws();
+    (p.pos == p.s.len() && v.is_finite()).then_some(v)
+}
+
+/// Render a result without float noise: integers print without a fraction,
+/// everything else to at most 10 decimals with trailing zeros removed.
+pub fn format_result(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 { return format!("{}", v as i64); }
+    let s = format!("{v:.10}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+struct Parser<'a> {
+    s:   &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.s.get(self.pos).is_some_and(u8::is_ascii_whitespace) { self.pos += 1; }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.s.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) { self.pos += 1; true } else { false }
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut v = self.term()?;
+        loop {
+            if      self.eat(b'+') { v += self.term()?; }
+            else if self.eat(b'-') { v -= self.term()?; }
+            else { return Some(v); }
+        }
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut v = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(b'*') => { self.pos += 1; v *= self.unary()?; }
+                Some(b'/') => { self.pos += 1; v /= self.unary()?; }
+                Some(b'%') => { self.pos += 1; v %= self.unary()?; }
+                // Implicit multiplication: `2pi`, `3(4+1)`.
+                Some(c) if c == b'(' || c.is_ascii_alphabetic() => v *= self.power()?,
+                _ => return Some(v),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        if self.eat(b'-') { return Some(-self.unary()?); }
+        if self.eat(b'+') { return self.unary(); }
+        self.power()
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.atom()?;
+        if self.eat(b'^') { Some(base.powf(self.unary()?)) } else { Some(base) }
+    }
+
+    fn atom(&mut self) -> Option<f64> {
+        match self.peek()? {
+            b'(' => {
+                self.pos += 1;
+                let v = self.expr()?;
+                self.eat(b')').then_some(v)
+            }
+            c if c.is_ascii_digit() || c == b'.' => self.number(),
+            c if c.is_ascii_alphabetic() => {
+                let start = self.pos;
+                while self.s.get(self.pos).is_some_and(u8::is_ascii_alphanumeric) { self.pos += 1; }
+                let name = std::str::from_utf8(&self.s[start..self.pos]).ok()?.to_ascii_lowercase();
+                match name.as_str() {
+                    "pi" => return Some(std::f64::consts::PI),
+                    "e"  => return Some(std::f64::consts::E),
+                    _    => {}
+                }
+                let f: fn(f64) -> f64 = match name.as_str() {
+                    "sqrt" => f64::sqrt,
+                    "abs"  => f64::abs,
+                    "sin"  => f64::sin,
+                    "cos"  => f64::cos,
+                    "tan"  => f64::tan,
+                    "ln"   => f64::ln,
+                    "log"  => f64::log10,
+                    "exp"  => f64::exp,
+                    _      => return None,
+                };
+                if !self.eat(b'(') { return None; }
+                let arg = self.expr()?;
+                self.eat(b')').then(|| f(arg))
+            }
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        while self.s.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == b'.') { self.pos += 1; }
+        std::str::from_utf8(&self.s[start..self.pos]).ok()?.parse().ok()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_precedence() {
+        assert_eq!(evaluate("2*(3+4)/5"), Some(2.8));
+        assert_eq!(evaluate("2^3^2"), Some(512.0));
+        assert_eq!(evaluate("-2^2"), Some(-4.0));
+        assert_eq!(evaluate("2pi + 1"), Some(2.0 * std::f64::consts::PI + 1.0));
+    }
+
+    #[test]
+    fn test_evaluate_rejects_searches() {
+        assert_eq!(evaluate("2048"), None);
+        assert_eq!(evaluate("firefox"), None);
+        assert_eq!(evaluate("7zip"), None);
+        assert_eq!(evaluate("gnome-terminal"), None);
+        assert_eq!(evaluate("1/0"), None);
+    }
+
+    #[test]
+    fn test_format_result() {
+        assert_eq!(format_result(14.0), "14");
+        assert_eq!(format_result(0.1 + 0.2), "0.3");
+        assert_eq!(format_result(2.5), "2.5");
+    }
+}
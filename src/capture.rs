@@ -0,0 +1,330 @@
+//! Screenshots and screen recording for the `.quick-actions` section.
+//!
+//! Design:
+//! - `quick-actions` picks the buttons and their order: `screenshot` (the
+//!   focused output), `screenshot-area`, `record`, or `Label=command` for
+//!   anything else, run with `sh -c`.
+//! - Screenshots are taken with `grim`, and `slurp` to drag out an area, on
+//!   compositors with wlroots screencopy. Without grim, or when it fails
+//!   (GNOME, KDE, X11), the XDG Screenshot portal takes them instead,
+//!   interactively for an area, and its file is moved next to the others.
+//! - A capture waits `CAPTURE_DELAY_MS` on its own thread first, so the
+//!   launcher has closed and isn't in the picture.
+//! - Recording is `wf-recorder` on the focused output, stopped with SIGINT,
+//!   which lets it finish the file. The recorder is found in /proc rather
+//!   than remembered, so one started before a restart (or by a keybinding)
+//!   still shows as recording and can be stopped from here.
+//! - Files go to `capture-dir`, or the XDG pictures / videos directory when
+//!   it's empty, named after the time they were taken. A desktop
+//!   notification says where.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use zbus::Connection;
+use zbus::zvariant::{OwnedValue, Value};
+use crate::gui::LocalTime;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Time for the launcher to close before the screen is captured.
+const CAPTURE_DELAY_MS: u64 = 400;
+/// How often the GUI's `is_recording` looks for the recorder in /proc.
+const RECORDER_POLL: Duration = Duration::from_secs(1);
+const RECORDER: &str = "wf-recorder";
+
+const PORTAL_BUS:       &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH:      &str = "/org/freedesktop/portal/desktop";
+const SCREENSHOT_IFACE: &str = "org.freedesktop.portal.Screenshot";
+const REQUEST_IFACE:    &str = "org.freedesktop.portal.Request";
+/// An interactive portal waits for the user, so this is generous.
+const T_PORTAL: Duration = Duration::from_secs(120);
+
+// ============================================================================
+// Actions
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuickAction {
+    Screenshot,
+    ScreenshotArea,
+    Record,
+    Command { label: String, command: String },
+}
+
+impl QuickAction {
+    pub fn label(&self) -> &str {
+        match self {
+            QuickAction::Screenshot            => "📷 Screenshot",
+            QuickAction::ScreenshotArea        => "⬚ Area",
+            QuickAction::Record                => "⏺ Record",
+            QuickAction::Command { label, .. } => label,
+        }
+    }
+
+    pub fn hint(&self) -> &str {
+        match self {
+            QuickAction::Screenshot              => "Take a screenshot of this screen",
+            QuickAction::ScreenshotArea          => "Take a screenshot of an area you drag out",
+            QuickAction::Record                  => "Record this screen until pressed again",
+            QuickAction::Command { command, .. } => command,
+        }
+    }
+}
+
+/// `quick-actions` entries; unknown ones are reported and skipped.
+pub fn parse_actions(entries: &[String]) -> Vec<QuickAction> {
+    entries.iter()
+        .filter(|e| !e.trim().is_empty())
+        .filter_map(|entry| match entry.trim() {
+            "screenshot"      => Some(QuickAction::Screenshot),
+            "screenshot-area" => Some(QuickAction::ScreenshotArea),
+            "record"          => Some(QuickAction::Record),
+            other => match other.split_once('=') {
+                Some((label, command)) if !label.trim().is_empty() && !command.trim().is_empty() => {
+                    Some(QuickAction::Command { label: label.trim().to_string(), command: command.trim().to_string() })
+                }
+                _ => {
+                    tracing::warn!(target: "config", "Ignoring quick action {entry:?}: expected screenshot, screenshot-area, record or \"Label=command\"");
+                    None
+                }
+            },
+        })
+        .collect()
+}
+
+/// Run a `Label=command` action's command.
+pub fn run(command: &str) {
+    match Command::new("sh").args(["-c", command])
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn()
+    {
+        // Reap it so daemon mode doesn't collect zombies.
+        Ok(mut child) => { thread::spawn(move || child.wait()); }
+        Err(e)        => tracing::warn!(target: "capture", "Failed to run {command:?}: {e}"),
+    }
+}
+
+/// `capture-dir`, or the XDG user directory `xdg` (`$HOME/fallback` without
+/// one), created if needed, joined with `name` formatted as a time.
+fn target(dir: &str, xdg: &str, fallback: &str, name: &str) -> Option<PathBuf> {
+    let dir = match dir {
+        ""  => crate::places::user_dir(xdg).unwrap_or_else(|| crate::paths::home().join(fallback)),
+        dir => match dir.strip_prefix("~/") {
+            Some(rest) => crate::paths::home().join(rest),
+            None       => PathBuf::from(dir),
+        },
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        tracing::warn!(target: "capture", "Can't create {}: {e}", dir.display());
+        return None;
+    }
+    Some(dir.join(crate::strftime::format(&LocalTime::now(), name)))
+}
+
+fn focused_output() -> Option<String> {
+    crate::monitors::list().into_iter().find(|m| m.focused).map(|m| m.name)
+}
+
+// ============================================================================
+// Screenshots
+// ============================================================================
+
+enum Taken { Saved, Cancelled, Failed }
+
+/// Take a screenshot once the launcher has had time to close; `area` lets
+/// the user drag one out.
+pub fn screenshot(area: bool, dir: String) {
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(CAPTURE_DELAY_MS));
+        let Some(file) = target(&dir, "PICTURES", "Pictures", "Screenshot_%Y-%m-%d_%H-%M-%S.png") else { return };
+        let saved = match grim(area, &file) {
+            Taken::Saved     => Some(file),
+            Taken::Cancelled => None,
+            Taken::Failed    => portal_screenshot(area, &file),
+        };
+        if let Some(path) = saved {
+            tracing::info!(target: "capture", "screenshot saved to {}", path.display());
+            crate::notifications::send("camera-photo", "Screenshot saved", &path.display().to_string());
+        }
+    });
+}
+
+fn grim(area: bool, file: &Path) -> Taken {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() { return Taken::Failed; }
+    let mut grim = Command::new("grim");
+    if area {
+        let Ok(out) = Command::new("slurp").stdin(Stdio::null()).stderr(Stdio::null()).output() else { return Taken::Failed };
+        // slurp exits non-zero when the selection is cancelled with Escape.
+        if !out.status.success() { return Taken::Cancelled; }
+        grim.arg("-g").arg(String::from_utf8_lossy(&out.stdout).trim());
+    } else if let Some(output) = focused_output() {
+        grim.args(["-o", &output]);
+    }
+    match grim.arg(file).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        Ok(status) if status.success() => Taken::Saved,
+        Ok(status) => {
+            tracing::debug!(target: "capture", "grim failed ({status}); trying the portal");
+            Taken::Failed
+        }
+        Err(_) => Taken::Failed,
+    }
+}
+
+/// Ask the Screenshot portal, then move its file to `file`. Where it can't
+/// be moved, the portal's own path is returned.
+fn portal_screenshot(interactive: bool, file: &Path) -> Option<PathBuf> {
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            tracing::error!(target: "capture", "runtime error: {e}");
+            return None;
+        }
+    };
+    let uri = match rt.block_on(async { tokio::time::timeout(T_PORTAL, portal_request(interactive)).await }) {
+        Ok(Ok(uri)) => uri?,
+        Ok(Err(e))  => {
+            tracing::warn!(target: "capture", "Can't take a screenshot: grim isn't usable here and the portal failed: {e}");
+            return None;
+        }
+        Err(_) => {
+            tracing::warn!(target: "capture", "The screenshot portal didn't answer");
+            return None;
+        }
+    };
+    let taken = crate::paths::from_file_uri(&uri)?;
+    match fs::rename(&taken, file).or_else(|_| fs::copy(&taken, file).and_then(|_| fs::remove_file(&taken))) {
+        Ok(())  => Some(file.to_path_buf()),
+        Err(_)  => Some(taken),
+    }
+}
+
+/// The portal's `Screenshot` call: the answer comes as a `Response` signal on
+/// a request object, so that's subscribed to before calling. `None` when the
+/// user cancelled.
+async fn portal_request(interactive: bool) -> zbus::Result<Option<String>> {
+    let conn   = Connection::session().await?;
+    let token  = format!("tusk{}", std::process::id());
+    let sender = conn.unique_name().map(|n| n.trim_start_matches(':').replace('.', "_")).unwrap_or_default();
+    let handle = format!("{PORTAL_PATH}/request/{sender}/{token}");
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(PORTAL_BUS)?
+        .interface(REQUEST_IFACE)?
+        .member("Response")?
+        .path(handle.as_str())?
+        .build();
+    let mut responses = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+
+    let options = HashMap::from([
+        ("handle_token", Value::from(token.as_str())),
+        ("interactive",  Value::from(interactive)),
+    ]);
+    conn.call_method(Some(PORTAL_BUS), PORTAL_PATH, Some(SCREENSHOT_IFACE), "Screenshot", &("", options)).await?;
+
+    let Some(msg) = responses.next().await else { return Ok(None) };
+    let (response, results): (u32, HashMap<String, OwnedValue>) = msg?.body().deserialize()?;
+    // 0 is success, 1 cancelled by the user, 2 anything else.
+    match response {
+        0 => Ok(results.get("uri").and_then(|v| String::try_from(v.clone()).ok())),
+        1 => Ok(None),
+        _ => Err(zbus::Error::Failure("the request failed".into())),
+    }
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+pub struct Recorder {
+    /// The running recorder's pid, as last seen.
+    pid:     Option<u32>,
+    checked: Option<Instant>,
+    /// Called when a recording started here ends, so the GUI can repaint.
+    waker:   Waker,
+}
+
+impl Recorder {
+    pub fn new(on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        Recorder { pid: None, checked: None, waker: Arc::new(on_change) }
+    }
+
+    /// Whether a recording is running; /proc is looked at once a second.
+    pub fn is_recording(&mut self) -> bool {
+        if self.checked.is_none_or(|at| at.elapsed() >= RECORDER_POLL) {
+            self.pid     = crate::system::find_processes(RECORDER).first().map(|(pid, _)| *pid);
+            self.checked = Some(Instant::now());
+        }
+        self.pid.is_some()
+    }
+
+    /// Record the focused output into `capture-dir`.
+    pub fn start(&mut self, dir: &str) {
+        if self.is_recording() { return; }
+        let Some(file) = target(dir, "VIDEOS", "Videos", "Recording_%Y-%m-%d_%H-%M-%S.mp4") else { return };
+        let mut recorder = Command::new(RECORDER);
+        if let Some(output) = focused_output() { recorder.args(["-o", &output]); }
+        let spawned = recorder.arg("-f").arg(&file)
+            .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                self.pid     = Some(child.id());
+                self.checked = Some(Instant::now());
+                let waker = Arc::clone(&self.waker);
+                thread::spawn(move || {
+                    let status = child.wait();
+                    waker();
+                    match status {
+                        Ok(s) if s.success() && file.exists() => {
+                            tracing::info!(target: "capture", "recording saved to {}", file.display());
+                            crate::notifications::send("video-x-generic", "Recording saved", &file.display().to_string());
+                        }
+                        Ok(s)  => tracing::warn!(target: "capture", "{RECORDER} stopped ({s}) without saving {}", file.display()),
+                        Err(e) => tracing::warn!(target: "capture", "{RECORDER}: {e}"),
+                    }
+                });
+            }
+            Err(e) => tracing::warn!(target: "capture", "Failed to run {RECORDER}: {e}"),
+        }
+    }
+
+    pub fn stop(&mut self) {
+        let Some(pid) = self.pid.take() else { return };
+        // SIGINT lets wf-recorder write out the file; a killed one leaves it broken.
+        let Ok(pid) = libc::pid_t::try_from(pid) else { return };
+        if unsafe { libc::kill(pid, libc::SIGINT) } != 0 {
+            tracing::warn!(target: "capture", "Failed to stop {RECORDER}: {}", std::io::Error::last_os_error());
+        }
+        self.checked = Some(Instant::now());
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_actions() {
+        let entries = ["record", "", "Color picker=hyprpicker -a", "screenshot-area", "bogus", "=x"].map(String::from);
+        assert_eq!(parse_actions(&entries), [
+            QuickAction::Record,
+            QuickAction::Command { label: "Color picker".into(), command: "hyprpicker -a".into() },
+            QuickAction::ScreenshotArea,
+        ]);
+    }
+}
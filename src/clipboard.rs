@@ -0,0 +1,296 @@
+//! Clipboard history: a watcher records recent text selections, which the
+//! `clip:` prefix mode (`providers::ClipboardProvider`) searches and re-copies.
+//!
+//! On Wayland `wl-paste --watch` is notified of every selection change through
+//! the data-control protocol, so it works without focus; elsewhere the
+//! clipboard is polled once a second with `xclip`/`xsel`. Entries only get
+//! recorded while the launcher runs, which makes this most useful together
+//! with `daemon-mode`.
+//!
+//! `clipboard-store` picks persistence:
+//! - `memory` (default): nothing touches the disk
+//! - `plain`: `clipboard_history.txt`, mode 0600
+//! - `encrypted`: `clipboard_history.enc`, AES-256 via `openssl enc` with a
+//!   random key in `clipboard-key-file` (created 0600 on first use). Keep that
+//!   key out of synced or backed-up directories, or the encryption buys nothing.
+use std::{
+    env,
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use crate::gui::Config;
+
+const HEADER: &str = "CLIP_V1";
+/// Larger selections (huge pastes, binary data offered as text) aren't recorded.
+const MAX_ENTRY_BYTES: usize = 64 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Run by `wl-paste --watch` with the new selection on stdin. Selections that
+/// password managers flag as secret are skipped; a NUL ends each entry since
+/// the text itself may contain newlines.
+const WATCH_SCRIPT: &str =
+    "wl-paste --list-types | grep -q x-kde-passwordManagerHint && exit 0; cat; printf '\\0'";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Store { Memory, Plain, Encrypted }
+
+/// Persistence settings, owned by the watcher thread.
+struct Recorder {
+    store:    Store,
+    key_file: PathBuf,
+    max:      usize,
+}
+
+pub struct ClipboardHistory {
+    /// Most recent first.
+    entries: Arc<Mutex<Vec<String>>>,
+    /// The `wl-paste --watch` process, killed on drop.
+    watcher: Arc<Mutex<Option<Child>>>,
+}
+
+impl ClipboardHistory {
+    /// Returns `None` when `enable-clipboard-history` is off.
+    pub fn new(config: &Config) -> Option<Self> {
+        if !config.enable_clipboard_history { return None; }
+
+        let store = match config.clipboard_store.as_str() {
+            "plain"       => Store::Plain,
+            "encrypted"   => Store::Encrypted,
+            "memory" | "" => Store::Memory,
+            other => {
+                tracing::warn!(target: "clipboard", "Unknown clipboard-store '{other}', keeping clipboard history in memory");
+                Store::Memory
+            }
+        };
+        let key_file = match config.clipboard_key_file.as_str() {
+            ""   => crate::paths::config_home().join("tusk-launcher/clipboard.key"),
+            path => PathBuf::from(path),
+        };
+        let recorder = Recorder { store, key_file, max: config.clipboard_history_size.max(1) };
+
+        let mut initial = recorder.load();
+        initial.truncate(recorder.max);
+        let entries = Arc::new(Mutex::new(initial));
+        let watcher = Arc::new(Mutex::new(None));
+        spawn_watcher(recorder, Arc::clone(&entries), Arc::clone(&watcher));
+        Some(ClipboardHistory { entries, watcher })
+    }
+
+    /// Snapshot of the history, most recent first.
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for ClipboardHistory {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.watcher.lock().ok().and_then(|mut slot| slot.take()) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+// ============================================================================
+// Watching
+// ============================================================================
+
+fn spawn_watcher(recorder: Recorder, entries: Arc<Mutex<Vec<String>>>, slot: Arc<Mutex<Option<Child>>>) {
+    thread::spawn(move || {
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            let spawned = Command::new("wl-paste")
+                .args(["--type", "text", "--watch", "sh", "-c", WATCH_SCRIPT])
+                .stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null())
+                .spawn();
+            match spawned {
+                Ok(mut child) => {
+                    let stdout = child.stdout.take();
+                    if let Ok(mut guard) = slot.lock() { *guard = Some(child); }
+                    let Some(stdout) = stdout else { return };
+                    for chunk in BufReader::new(stdout).split(0) {
+                        let Ok(bytes) = chunk else { break };
+                        recorder.record(&entries, String::from_utf8_lossy(&bytes).into_owned());
+                    }
+                    return;
+                }
+                Err(e) => tracing::info!(target: "clipboard", "wl-paste unavailable ({e}), polling instead"),
+            }
+        }
+
+        let mut last = None;
+        loop {
+            let current = read_clipboard();
+            if let Some(text) = current.as_ref().filter(|t| Some(*t) != last.as_ref()) {
+                recorder.record(&entries, text.clone());
+            }
+            last = current;
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+fn read_clipboard() -> Option<String> {
+    let tools: &[&[&str]] = &[
+        &["xclip", "-o", "-selection", "clipboard"],
+        &["xsel", "--clipboard", "--output"],
+        &["wl-paste", "--no-newline", "--type", "text"],
+    ];
+    tools.iter().find_map(|argv| {
+        let output = Command::new(argv[0]).args(&argv[1..]).stderr(Stdio::null()).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+/// Moves `text` to the front of `list`, capped at `max`. Returns `false` when
+/// nothing changed (empty, oversized or already the newest entry).
+fn push_entry(list: &mut Vec<String>, text: String, max: usize) -> bool {
+    if text.trim().is_empty() || text.len() > MAX_ENTRY_BYTES { return false; }
+    if list.first() == Some(&text) { return false; }
+    list.retain(|e| *e != text);
+    list.insert(0, text);
+    list.truncate(max);
+    true
+}
+
+// ============================================================================
+// Persistence
+// ============================================================================
+
+impl Recorder {
+    fn record(&self, entries: &Mutex<Vec<String>>, text: String) {
+        let snapshot = {
+            let Ok(mut list) = entries.lock() else { return };
+            if !push_entry(&mut list, text, self.max) { return; }
+            list.clone()
+        };
+        if let Err(e) = self.save(&snapshot) {
+            tracing::warn!(target: "clipboard", "Failed to save clipboard history: {e}");
+        }
+    }
+
+    fn history_file(&self) -> std::io::Result<PathBuf> {
+        let name = if self.store == Store::Encrypted { "clipboard_history.enc" } else { "clipboard_history.txt" };
+        crate::paths::place_config_file(&format!("tusk-launcher/{name}"))
+    }
+
+    fn load(&self) -> Vec<String> {
+        if self.store == Store::Memory { return Vec::new(); }
+        let Some(path) = self.history_file().ok().filter(|p| p.exists()) else { return Vec::new() };
+        let text = match self.store {
+            Store::Encrypted => self.openssl(&["-d", "-in", &path.to_string_lossy()], None),
+            _                => fs::read_to_string(&path).map_err(Into::into),
+        };
+        match text {
+            Ok(text) => deserialize(&text).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!(target: "clipboard", "Failed to load clipboard history: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    fn save(&self, entries: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        if self.store == Store::Memory { return Ok(()); }
+        let path = self.history_file()?;
+        let data = serialize(entries);
+        match self.store {
+            Store::Encrypted => self.openssl(&["-out", &path.to_string_lossy()], Some(&data)).map(drop),
+            _                => write_private(&path, data.as_bytes()).map_err(Into::into),
+        }
+    }
+
+    /// `openssl enc` with our key; `extra` selects direction and file.
+    fn openssl(&self, extra: &[&str], input: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        self.ensure_key()?;
+        let pass = format!("file:{}", self.key_file.display());
+        let mut child = Command::new("openssl")
+            .args(["enc", "-aes-256-cbc", "-pbkdf2", "-salt", "-pass", &pass])
+            .args(extra)
+            .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.unwrap_or_default().as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() { return Err("openssl enc failed (wrong key?)".into()); }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn ensure_key(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.key_file.exists() { return Ok(()); }
+        let output = Command::new("openssl").args(["rand", "-hex", "32"]).output()?;
+        if !output.status.success() { return Err("openssl rand failed".into()); }
+        if let Some(parent) = self.key_file.parent() { fs::create_dir_all(parent)?; }
+        write_private(&self.key_file, &output.stdout)?;
+        Ok(())
+    }
+}
+
+/// Writes `data` to a file only the user can read.
+fn write_private(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600)
+        .open(path)?
+        .write_all(data)
+}
+
+fn serialize(entries: &[String]) -> String {
+    let mut out = String::from(HEADER);
+    for entry in entries {
+        out.push('\n');
+        out.push_str(&entry.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r"));
+    }
+    out
+}
+
+fn deserialize(s: &str) -> Option<Vec<String>> {
+    let mut lines = s.lines();
+    if lines.next()? != HEADER { return None; }
+    Some(lines.map(|line| {
+        let mut out   = String::with_capacity(line.len());
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' { out.push(c); continue; }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some(o)   => out.push(o),
+                None      => out.push('\\'),
+            }
+        }
+        out
+    }).collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_entry_dedups_and_caps() {
+        let mut list = Vec::new();
+        assert!(push_entry(&mut list, "a".into(), 2));
+        assert!(push_entry(&mut list, "b".into(), 2));
+        assert!(!push_entry(&mut list, "b".into(), 2));
+        assert!(!push_entry(&mut list, "  \n".into(), 2));
+        assert!(push_entry(&mut list, "a".into(), 2));
+        assert!(push_entry(&mut list, "c".into(), 2));
+        assert_eq!(list, ["c", "a"]);
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let entries = vec!["two\nlines".to_string(), "back\\slash \\n".into(), "plain".into()];
+        assert_eq!(deserialize(&serialize(&entries)), Some(entries));
+        assert_eq!(deserialize("garbage"), None);
+    }
+}
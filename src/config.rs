@@ -0,0 +1,837 @@
+//! `config.toml`: the launcher's settings, separate from the theme.
+//!
+//! Design:
+//! - Keys are the same kebab-case names the old `.config` block of
+//!   `theme.css` used, so `Config::from_values` reads both sources.
+//! - Only the TOML this file needs is understood — `key = value` with bools,
+//!   numbers, strings (multi-line ones too, for snippets.toml) and arrays of
+//!   strings. Tables and other types are
+//!   reported with their line number and skipped, not fatal.
+//! - First start writes `DEFAULT_CONFIG`, or, when `theme.css` still has a
+//!   `.config` block, a copy of it with those values filled in. After that the
+//!   block is ignored; `theme.css` keeps only positions, sizes and colors.
+//! - List settings also accept the old comma-separated string form.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{read_to_string, write};
+
+use crate::gui::{Config, Theme};
+
+const CONFIG_FILE: &str = "tusk-launcher/config.toml";
+
+pub const DEFAULT_CONFIG: &str = r#"# Tusk Launcher settings. Positions, sizes and colors live in theme.css.
+
+# ── Theme ────────────────────────────────────────────────────────────────────
+# a profile from themes/ layered over theme.css: "nord", "catppuccin", "gruvbox",
+# "light" or your own; empty uses theme.css alone. `--theme name` overrides it.
+theme = ""
+# profile picker (.theme-switcher); picking one rewrites `theme` above
+enable-theme-switcher = false
+# follow the desktop's dark/light preference (XDG portal, or GNOME's gsettings)
+# with these profiles; either left empty falls back to `theme`
+dark-theme = ""
+light-theme = ""
+# open/close effects and hover fades from the theme; false makes everything instant
+animations = true
+
+# ── Search ───────────────────────────────────────────────────────────────────
+enable-recent-apps = true
+# with an empty query, list every app A–Z (scrolling, with a letter index)
+# instead of recents
+show-all-apps = false
+max-search-results = 5
+# "list" rows, or "grid" for icon tiles (.app-tile in theme.css) that the
+# arrow keys move through
+results-view = "list"
+grid-columns = 4
+# result sources in display order; drop one to disable it
+search-providers = [
+    "windows",
+    "clipboard",
+    "snippets",
+    "run",
+    "calculator",
+    "conversion",
+    "flatpak",
+    "desktop",
+    "steam",
+    "path",
+    "recent",
+    "places",
+    "wallpaper",
+    "web",
+]
+# "2*(3+4)" shows the result; Enter copies it
+enable-calculator = true
+# "10 km to mi", "72 f to c", "100 usd to eur"
+enable-conversion = true
+# download exchange rates; cached rates are used offline
+fetch-currency-rates = false
+currency-refresh-hours = 12
+# ">htop" runs a shell command; right-click a row to run it in a terminal
+run-prefix = ">"
+run-in-terminal = false
+# "w:term" lists open windows (Hyprland, Sway, X11 via wmctrl)
+window-prefix = "w:"
+# "g:" shows Steam games as a cover-art grid; "g:doom" filters it
+games-prefix = "g:"
+# toggle for the games grid beside the search field
+show-games-button = false
+# more prefix modes as "prefix=provider" (any name from search-providers),
+# e.g. ["d:=desktop", "f:=flatpak", "==calculator"]; the search bar shows
+# the active mode as a badge
+prefixes = []
+# "cat:games" lists one menu category (from Categories= in .desktop files);
+# "cat:dev vim" searches inside it
+category-prefix = "cat:"
+# category chips (All, Internet, Games, …) above the app list
+show-category-chips = false
+# never list results matching these (* and ? wildcards), e.g. ["Avahi*"];
+# rows hidden from the right-click menu are listed by "hidden:" instead
+hidden-apps = []
+hidden-prefix = "hidden:"
+# list $PATH executables (dmenu_run style) after apps
+enable-path-binaries = false
+# focus an app's open window (hyprctl/swaymsg/wmctrl) instead of starting it again
+focus-or-launch = false
+# an app that exits with an error this soon after starting gets an error toast
+launch-check-ms = 2000
+# pause in typing (ms) before results update; searching runs off the UI thread
+search-debounce-ms = 30
+# record copied text (wl-paste/xclip); best with daemon-mode
+enable-clipboard-history = false
+clipboard-prefix = "clip:"
+clipboard-history-size = 50
+# memory | plain | encrypted (openssl, key in clipboard-key-file)
+clipboard-store = "memory"
+clipboard-key-file = ""
+# ";sig" lists snippets from snippets.toml; Enter copies the text, or with
+# "type" types it into the window you came from (wtype / xdotool)
+snippet-prefix = ";"
+snippet-insert = "copy"
+# files other apps opened lately (recently-used.xbel) show up by name;
+# "r:" lists them all, newest first
+recent-prefix = "r:"
+# home, Downloads & co., GTK bookmarks and mounted drives open in the file
+# manager; "p:" lists them all
+places-prefix = "p:"
+# "wp:" shows the images in wallpaper-dir as thumbnails; Enter sets one on
+# every output (right-click for a single one). The setter is "swww",
+# "hyprpaper" or "swaybg"; empty picks the one that's running
+wallpaper-prefix = "wp:"
+wallpaper-dir = "~/Pictures/Wallpapers"
+wallpaper-setter = ""
+# put each output's last wallpaper back when the session starts
+wallpaper-restore = false
+# "Open URL" / web search rows when nothing matches
+enable-web-search = true
+# !key terms; the first is the default
+search-engines = [
+    "ddg=https://duckduckgo.com/?q=%s",
+    "g=https://www.google.com/search?q=%s",
+    "yt=https://www.youtube.com/results?search_query=%s",
+    "w=https://en.wikipedia.org/w/index.php?search=%s",
+]
+
+# ── Terminal ─────────────────────────────────────────────────────────────────
+# terminal for Terminal=true apps and run mode; empty tries terminal-commands in order
+terminal-command = ""
+terminal-commands = [
+    "$TERMINAL -e %command%",
+    "foot %command%",
+    "kitty %command%",
+    "alacritty -e %command%",
+    "wezterm start -- %command%",
+    "konsole -e %command%",
+    "gnome-terminal -- %command%",
+    "xterm -e %command%",
+]
+
+# ── Clock ────────────────────────────────────────────────────────────────────
+show-time = true
+# strftime: %a %A %b %B %d %-d %e %H %I %M %S %p %Y %F %x %X %z ...
+time-format = "%I:%M %p %m/%d/%Y"
+# e.g. "America/New_York"; empty follows the system
+timezone = ""
+# e.g. ["UTC", "Tokyo=Asia/Tokyo"]; shown on a second line (raise .time-display height)
+extra-clocks = []
+extra-clock-format = "%I:%M %p"
+
+# ── Audio ────────────────────────────────────────────────────────────────────
+enable-audio-control = false
+max-volume = 1.5
+volume-update-interval-ms = 500
+# volume change per wheel notch over the slider
+volume-scroll-step = 0.05
+# output-device dropdown beside the volume slider
+enable-sink-switcher = true
+# microphone mute + input level (.mic-control)
+enable-mic-control = false
+
+# ── Power ────────────────────────────────────────────────────────────────────
+enable-power-options = true
+# power off / restart / suspend via logind; the command lists are the fallback
+use-logind = true
+show-suspend-button = false
+power-commands = ["systemctl poweroff", "loginctl poweroff", "poweroff", "halt"]
+restart-commands = ["systemctl reboot", "loginctl reboot", "reboot"]
+suspend-commands = ["systemctl suspend", "loginctl suspend"]
+logout-commands = [
+    "loginctl terminate-session $XDG_SESSION_ID",
+    "hyprctl dispatch exit",
+    "swaymsg exit",
+    "gnome-session-quit --logout --no-prompt",
+    "qdbus org.kde.ksmserver /KSMServer logout 0 0 0",
+]
+
+# ── Widgets ──────────────────────────────────────────────────────────────────
+# caffeine toggle (.idle-inhibitor); remembered across restarts
+enable-idle-inhibitor = false
+# uptime, load, CPU and RAM (.system-stats)
+enable-system-stats = false
+system-stats-interval-ms = 2000
+# countdown / Pomodoro timer (.timer); sends a notification when it ends
+enable-timer = false
+timer-presets = ["Pomodoro=25m", "Short break=5m", "Long break=15m"]
+# screenshot and screen recording buttons (.quick-actions): grim/slurp or the
+# screenshot portal, wf-recorder; "Label=command" adds your own
+enable-quick-actions = false
+quick-actions = ["screenshot", "screenshot-area", "record"]
+# where captures are saved; empty uses the XDG pictures / videos folders
+capture-dir = ""
+# workspace buttons (.workspaces) for Hyprland and Sway, like a small bar;
+# click one to switch to it
+enable-workspaces = false
+# current weather icon and temperature (.weather), fetched with curl;
+# provider "open-meteo" or "wttr.in", location a place name or "lat,lon"
+enable-weather = false
+weather-provider = "open-meteo"
+weather-location = ""
+# "metric" or "imperial"
+weather-units = "metric"
+weather-refresh-minutes = 30
+# NetworkManager status and Wi-Fi picker
+enable-network = false
+# connect/disconnect saved VPNs (NetworkManager) and wg-quick configs;
+# /etc/wireguard is usually root-only, so name yours here, e.g. ["wg0"]
+enable-vpn = false
+wg-quick-configs = []
+# connect/disconnect paired BlueZ devices
+enable-bluetooth = false
+# mount, unmount and eject USB sticks and other drives (UDisks2)
+enable-drives = false
+# act as the notification daemon
+enable-notifications = false
+notification-timeout-ms = 5000
+
+# ── Icons & tray ─────────────────────────────────────────────────────────────
+enable-icons = true
+# e.g. "Papirus-Dark"; empty follows the desktop's setting
+icon-theme = ""
+# tray icons use -symbolic variants, drawn in .main-window symbolic-color
+prefer-symbolic-icons = true
+# play animated GIF/APNG icons and tray attention animations; false saves battery
+animate-icons = true
+show-settings-button = true
+enable-system-tray = true
+# host legacy X11 XEmbed icons too (build with --features xembed)
+enable-xembed-tray = false
+# hover time before a tray item's tooltip shows
+tray-tooltip-delay-ms = 500
+
+# ── Keys ─────────────────────────────────────────────────────────────────────
+# chords like "Ctrl+J" or "Alt+Enter"; an empty list unbinds. Enter and Escape are fixed
+key-next = ["Down", "Ctrl+J", "Ctrl+N"]
+key-previous = ["Up", "Ctrl+K", "Ctrl+P"]
+key-clear = ["Ctrl+L"]
+key-actions = ["Alt+Enter"]
+key-launch-options = ["Ctrl+E"]
+# modifiers that launch the 1st–9th result with the digit keys
+key-launch-nth = ["Ctrl"]
+# scale the UI up/down a step, or back to ui-scale = 1.0 (saved either way)
+key-zoom-in = ["Ctrl+Plus", "Ctrl+Equals"]
+key-zoom-out = ["Ctrl+Minus"]
+key-zoom-reset = ["Ctrl+0"]
+
+# ── Window ───────────────────────────────────────────────────────────────────
+# output to open on: "focused", "cursor" (the one under the pointer) or a name
+# like "DP-1"; empty lets the compositor choose. Placing the window needs
+# Hyprland, Sway or X11 (xrandr, and xdotool for the pointer)
+window-monitor = ""
+# "center", "top-center" or "cursor" (centered on the pointer)
+window-anchor = "center"
+# hide (or quit) when the launcher loses focus or you click outside it
+close-on-focus-loss = false
+# X11: "dialog" or "utility" make tiling WMs (i3, bspwm) float the window;
+# "normal" leaves it to WM rules
+x11-window-type = "dialog"
+# X11: bypass the window manager entirely (no tiling, borders or rules)
+x11-override-redirect = false
+# ⚙ beside the search field opens a window for editing these settings
+enable-settings-window = true
+# size of everything in the window, 0.5–3.0, on top of the output's scaling;
+# the zoom keys change and save it
+ui-scale = 1.0
+
+# ── Logging ──────────────────────────────────────────────────────────────────
+# "error", "warn", "info", "debug" or "trace", also per subsystem, e.g.
+# "warn,sni=debug" (sni, icons, launch, apps, audio, config, theme, search,
+# window, power, network, bluetooth, drives, capture, wallpaper, weather,
+# notifications, clipboard, ipc, gui).
+# Read at startup; --verbose turns the default up to debug
+log-level = "info"
+# also write the log to ~/.local/state/tusk-launcher/tusk-launcher.log
+log-file = false
+
+# ── Session ──────────────────────────────────────────────────────────────────
+# hide instead of exiting; run again to show
+daemon-mode = false
+# launch ~/.config/autostart entries once per session (for compositors without a session manager)
+run-autostart = false
+# answer org.tusk.Launcher calls on the session bus (Show, Hide, Toggle, SetQuery, Quit, DoNotDisturb)
+enable-dbus-interface = true
+"#;
+
+// ============================================================================
+// Values
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Strings, and bools/numbers as written; `Config` parses each per field.
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    pub fn scalar(&self) -> Option<&str> {
+        match self {
+            Value::Scalar(s) => Some(s),
+            Value::List(_)   => None,
+        }
+    }
+
+    /// A list, or a comma-separated scalar as the `.config` block wrote them.
+    pub fn list(&self) -> Vec<String> {
+        match self {
+            Value::Scalar(s) => s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+            Value::List(l)   => l.clone(),
+        }
+    }
+}
+
+pub type Values = HashMap<String, Value>;
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+/// Settings from `config.toml`, creating or migrating it on first start.
+pub fn load(theme: &Theme) -> Config {
+    let css = theme.config_block();
+    match read_or_create(css) {
+        Ok(values) => Config::from_values(&values),
+        Err(e) => {
+            tracing::error!(target: "config", "Failed to load config.toml: {e}");
+            let values = css.map(css_values).unwrap_or_default();
+            Config::from_values(&values)
+        }
+    }
+}
+
+fn read_or_create(css: Option<&HashMap<String, String>>) -> Result<Values, Box<dyn Error>> {
+    let path = crate::paths::place_config_file(CONFIG_FILE)?;
+    if !path.exists() {
+        match css {
+            Some(block) => {
+                write(&path, migrate(block))?;
+                tracing::info!(target: "config", "Moved the .config block of theme.css to {}; the block is no longer read", path.display());
+            }
+            None => write(&path, DEFAULT_CONFIG)?,
+        }
+    } else if css.is_some() {
+        tracing::warn!(target: "config", "Ignoring the .config block in theme.css; settings are read from {}", path.display());
+    }
+
+    let (values, errors) = parse(&read_to_string(&path)?);
+    for e in errors { tracing::warn!(target: "config", "{}: {e}", path.display()); }
+    Ok(values)
+}
+
+/// Whether config.toml has been written yet.
+pub fn exists() -> bool {
+    crate::paths::place_config_file(CONFIG_FILE).is_ok_and(|path| path.exists())
+}
+
+/// What config.toml holds now, without creating it; empty when unreadable.
+pub fn current_values() -> Values {
+    crate::paths::place_config_file(CONFIG_FILE).ok()
+        .and_then(|path| read_to_string(path).ok())
+        .map(|src| parse(&src).0)
+        .unwrap_or_default()
+}
+
+/// Rewrite `key = …` in config.toml to `value`, keeping the rest of the file.
+/// Only for scalar settings.
+pub fn set_value(key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    let path = crate::paths::place_config_file(CONFIG_FILE)?;
+    let src  = read_to_string(&path).unwrap_or_default();
+    write(&path, with_value(&src, key, value))?;
+    Ok(())
+}
+
+/// `set_value` for several settings, lists included, in one write.
+pub fn set_values(changes: &[(String, Value)]) -> Result<(), Box<dyn Error>> {
+    let path = crate::paths::place_config_file(CONFIG_FILE)?;
+    let mut src = read_to_string(&path).unwrap_or_default();
+    for (key, value) in changes {
+        let entry = match value {
+            Value::Scalar(s) => format_entry(key, s, false),
+            Value::List(l)   => format_list(key, l),
+        };
+        src = with_entry(&src, key, &entry);
+    }
+    write(&path, src)?;
+    Ok(())
+}
+
+fn with_value(src: &str, key: &str, value: &str) -> String {
+    with_entry(src, key, &format_entry(key, value, false))
+}
+
+/// `src` with its `key` entry (every line of a multi-line array) replaced by
+/// `entry`, or `entry` appended when the key is missing.
+fn with_entry(src: &str, key: &str, entry: &str) -> String {
+    let mut found = false;
+    let mut skipping_array = false;
+    let mut out = String::with_capacity(src.len());
+    for line in src.lines() {
+        if skipping_array {
+            skipping_array = !line.trim_start().starts_with(']');
+            continue;
+        }
+        let value = line.split_once('=').filter(|(k, _)| k.trim() == key).map(|(_, v)| v.trim());
+        match value {
+            Some(v) if !found => {
+                found = true;
+                skipping_array = v.starts_with('[') && !v.contains(']');
+                out.push_str(entry);
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    if !found { out.push_str(entry); out.push('\n'); }
+    out
+}
+
+fn css_values(block: &HashMap<String, String>) -> Values {
+    block.iter().map(|(k, v)| (k.clone(), Value::Scalar(v.clone()))).collect()
+}
+
+// ============================================================================
+// Migration
+// ============================================================================
+
+/// `DEFAULT_CONFIG` with each key the `.config` block sets replaced by its
+/// value; keys the template doesn't know are appended at the end.
+fn migrate(block: &HashMap<String, String>) -> String {
+    let mut out  = String::with_capacity(DEFAULT_CONFIG.len());
+    let mut seen = HashSet::new();
+    let mut skipping_array = false;
+
+    for line in DEFAULT_CONFIG.lines() {
+        if skipping_array {
+            skipping_array = !line.starts_with(']');
+            continue;
+        }
+        let entry = (!line.starts_with('#'))
+            .then(|| line.split_once(" = "))
+            .flatten()
+            .filter(|(key, _)| block.contains_key(*key));
+        match entry {
+            Some((key, template)) => {
+                let is_list = template.starts_with('[');
+                out.push_str(&format_entry(key, &block[key], is_list));
+                skipping_array = template == "[";
+                seen.insert(key);
+            }
+            None => out.push_str(line),
+        }
+        out.push('\n');
+    }
+
+    let mut extra: Vec<_> = block.iter().filter(|(k, _)| !seen.contains(k.as_str())).collect();
+    if !extra.is_empty() {
+        extra.sort();
+        out.push_str("\n# ── Carried over from theme.css ──────────────────────────────────────────────\n");
+        for (key, value) in extra {
+            out.push_str(&format_entry(key, value, false));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn format_entry(key: &str, css_value: &str, is_list: bool) -> String {
+    if is_list { return format_list(key, &Value::Scalar(css_value.to_string()).list()); }
+    let bare = css_value == "true" || css_value == "false"
+        || (css_value.starts_with(|c: char| c.is_ascii_digit() || c == '-') && css_value.parse::<f64>().is_ok());
+    if bare { format!("{key} = {css_value}") } else { format!("{key} = {}", quote(css_value)) }
+}
+
+fn format_list(key: &str, items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|s| quote(s)).collect();
+    let inline = format!("{key} = [{}]", items.join(", "));
+    if inline.len() <= 100 { return inline; }
+    format!("{key} = [\n{}]", items.iter().map(|i| format!("    {i},\n")).collect::<String>())
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c    => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// ============================================================================
+// Settings window
+// ============================================================================
+
+/// A key of `DEFAULT_CONFIG`, as the settings window lists it.
+#[derive(Clone, Debug)]
+pub struct Setting {
+    pub key:     &'static str,
+    /// The `# ── Name ──` heading it's under.
+    pub section: &'static str,
+    /// The comment just above it, if any.
+    pub help:    String,
+    pub default: Value,
+}
+
+/// Every setting `DEFAULT_CONFIG` documents, in its order. The template is
+/// the one list of settings, so the window can't fall out of step with it.
+pub fn settings() -> Vec<Setting> {
+    let (defaults, _) = parse(DEFAULT_CONFIG);
+    let mut out     = Vec::new();
+    let mut section = "";
+    let mut help    = Vec::new();
+    for line in DEFAULT_CONFIG.lines() {
+        if let Some(comment) = line.strip_prefix('#') {
+            let comment = comment.trim();
+            match comment.strip_prefix("── ") {
+                Some(heading) => { section = heading.trim_end_matches(['─', ' ']); help.clear(); }
+                None          => help.push(comment),
+            }
+            continue;
+        }
+        let key = line.split_once(" = ").map(|(key, _)| key).filter(|key| !key.starts_with(' '));
+        match key.and_then(|key| defaults.get(key).map(|value| (key, value))) {
+            Some((key, default)) if !section.is_empty() => {
+                out.push(Setting { key, section, help: help.join(" "), default: default.clone() });
+                help.clear();
+            }
+            _ => { if line.trim().is_empty() { help.clear(); } }
+        }
+    }
+    out
+}
+
+// ============================================================================
+// TOML subset
+// ============================================================================
+
+/// Parse `key = value` lines; returns what parsed and one message per bad line.
+pub fn parse(src: &str) -> (Values, Vec<String>) {
+    let mut p = Parser { s: src, pos: 0 };
+    let mut values = Values::new();
+    let mut errors = Vec::new();
+
+    loop {
+        p.skip_blank_lines();
+        if p.eof() { break; }
+        let line = p.line();
+        match p.entry() {
+            Ok((key, value)) => {
+                if values.insert(key.clone(), value).is_some() {
+                    errors.push(format!("line {line}: duplicate key {key:?}; the last one wins"));
+                }
+            }
+            Err(msg) => {
+                errors.push(format!("line {line}: {msg}"));
+                p.skip_line();
+            }
+        }
+    }
+    (values, errors)
+}
+
+struct Parser<'a> {
+    s:   &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn eof(&self) -> bool { self.pos >= self.s.len() }
+
+    fn peek(&self) -> Option<char> { self.s[self.pos..].chars().next() }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn line(&self) -> usize { self.s[..self.pos].matches('\n').count() + 1 }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) { self.pos += 1; }
+    }
+
+    fn skip_line(&mut self) {
+        match self.s[self.pos..].find('\n') {
+            Some(i) => self.pos += i + 1,
+            None    => self.pos = self.s.len(),
+        }
+    }
+
+    /// Whitespace, newlines and comments between entries (and inside arrays).
+    fn skip_blank_lines(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => self.pos += 1,
+                Some('#') => self.skip_line(),
+                _ => break,
+            }
+        }
+    }
+
+    fn entry(&mut self) -> Result<(String, Value), String> {
+        if self.peek() == Some('[') {
+            return Err("tables aren't supported; settings are top-level keys".into());
+        }
+        let key = self.key()?;
+        self.skip_spaces();
+        if self.bump() != Some('=') { return Err(format!("expected `=` after {key:?}")); }
+        self.skip_spaces();
+        let value = self.value()?;
+        self.skip_spaces();
+        match self.peek() {
+            None | Some('\n' | '\r' | '#') => { self.skip_line(); Ok((key, value)) }
+            Some(c) => Err(format!("unexpected {c:?} after the value of {key:?}")),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        if self.peek() == Some('"') { return self.basic_string(); }
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '-' || c == '_') { self.pos += 1; }
+        if self.pos == start { return Err("expected a key".into()); }
+        Ok(self.s[start..self.pos].to_string())
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') if self.s[self.pos..].starts_with("\"\"\"") => self.multiline_string("\"\"\"").map(Value::Scalar),
+            Some('\'') if self.s[self.pos..].starts_with("'''") => self.multiline_string("'''").map(Value::Scalar),
+            Some('"')  => self.basic_string().map(Value::Scalar),
+            Some('\'') => self.literal_string().map(Value::Scalar),
+            Some('[')  => self.array().map(Value::List),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if !c.is_whitespace() && c != '#' && c != ',' && c != ']') { self.pos += 1; }
+                let token = &self.s[start..self.pos];
+                let number = token.replace('_', "");
+                if token == "true" || token == "false" || number.parse::<f64>().is_ok() {
+                    Ok(Value::Scalar(if token.contains('_') { number } else { token.to_string() }))
+                } else if token.is_empty() {
+                    Err("missing value".into())
+                } else {
+                    Err(format!("{token:?} isn't a value; quote strings with \"…\""))
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Vec<String>, String> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(']') => { self.bump(); return Ok(items); }
+                Some('"')  => items.push(self.basic_string()?),
+                Some('\'') => items.push(self.literal_string()?),
+                Some(_)    => return Err("arrays may only hold strings".into()),
+                None       => return Err("unclosed array".into()),
+            }
+            self.skip_blank_lines();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(items),
+                _         => return Err("expected `,` or `]` in array".into()),
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(self.escape()?),
+                Some('\n') | None => return Err("unterminated string".into()),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    /// The character an escape stands for; the backslash is already read.
+    fn escape(&mut self) -> Result<char, String> {
+        match self.bump() {
+            Some('"')  => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('n')  => Ok('\n'),
+            Some('t')  => Ok('\t'),
+            Some('r')  => Ok('\r'),
+            Some(u @ ('u' | 'U')) => {
+                let len = if u == 'u' { 4 } else { 8 };
+                let hex = self.s.get(self.pos..self.pos + len).ok_or("truncated \\u escape")?;
+                let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).ok_or("bad \\u escape")?;
+                self.pos += len;
+                Ok(c)
+            }
+            _ => Err("unknown escape in string".into()),
+        }
+    }
+
+    /// `"""…"""` (with escapes) or `'''…'''` (without); they may span lines, and
+    /// a newline right after the opening quotes isn't part of the string.
+    fn multiline_string(&mut self, quotes: &str) -> Result<String, String> {
+        self.pos += quotes.len();
+        if self.s[self.pos..].starts_with("\r\n") { self.pos += 2; } else if self.peek() == Some('\n') { self.pos += 1; }
+        let rest = &self.s[self.pos..];
+        let end  = rest.find(quotes).ok_or("unterminated string")?;
+        self.pos += end + quotes.len();
+        if quotes == "'''" { return Ok(rest[..end].to_string()); }
+        let mut inner = Parser { s: &rest[..end], pos: 0 };
+        let mut out   = String::new();
+        while let Some(c) = inner.bump() {
+            out.push(if c == '\\' { inner.escape()? } else { c });
+        }
+        Ok(out)
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.bump();
+        let rest = &self.s[self.pos..];
+        let end = rest.find(['\'', '\n']).filter(|&i| rest[i..].starts_with('\'')).ok_or("unterminated string")?;
+        self.pos += end + 1;
+        Ok(rest[..end].to_string())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (values, errors) = parse(r#"
+# comment
+max-volume = 1.5   # trailing
+run-prefix = ">"
+icon-theme = 'Papirus\Dark'
+big = 1_000
+power-commands = [
+    "systemctl poweroff",  # first
+    "halt",
+]
+sig = """
+Best,\tMe
+Bye"""
+raw = '''a\nb'''
+[section]
+bad = nope
+"#);
+        assert_eq!(values["max-volume"], Value::Scalar("1.5".into()));
+        assert_eq!(values["run-prefix"].scalar(), Some(">"));
+        assert_eq!(values["icon-theme"].scalar(), Some("Papirus\\Dark"));
+        assert_eq!(values["big"].scalar(), Some("1000"));
+        assert_eq!(values["power-commands"].list(), ["systemctl poweroff", "halt"]);
+        assert_eq!(values["sig"].scalar(), Some("Best,\tMe\nBye"));
+        assert_eq!(values["raw"].scalar(), Some("a\\nb"));
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].starts_with("line 15:"));
+    }
+
+    #[test]
+    fn test_default_config_parses() {
+        let (values, errors) = parse(DEFAULT_CONFIG);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(values["logout-commands"].list().len(), 5);
+        assert_eq!(Value::Scalar("a, b,".into()).list(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let block: HashMap<String, String> = [
+            ("max-search-results", "8"),
+            ("run-prefix", "!"),
+            ("power-commands", "systemctl poweroff, halt"),
+            ("search-engines", "ddg=https://duckduckgo.com/?q=%s"),
+            ("time-order", "YmdHms"),
+        ].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let (values, errors) = parse(&migrate(&block));
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(values["max-search-results"].scalar(), Some("8"));
+        assert_eq!(values["run-prefix"].scalar(), Some("!"));
+        assert_eq!(values["power-commands"].list(), ["systemctl poweroff", "halt"]);
+        assert_eq!(values["search-engines"].list().len(), 1);
+        assert_eq!(values["time-order"].scalar(), Some("YmdHms"));
+        assert_eq!(values["enable-timer"].scalar(), Some("false"));
+    }
+
+    #[test]
+    fn test_with_value() {
+        let src = with_value(DEFAULT_CONFIG, "theme", "nord");
+        let (values, errors) = parse(&src);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(values["theme"].scalar(), Some("nord"));
+        assert_eq!(src.lines().count(), DEFAULT_CONFIG.lines().count());
+        assert!(with_value("a = 1", "b", "x y").ends_with("b = \"x y\"\n"));
+
+        let list = ["a".to_string(), "b".to_string()];
+        let src = with_entry(DEFAULT_CONFIG, "search-providers", &format_list("search-providers", &list));
+        let (values, errors) = parse(&src);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(values["search-providers"].list(), list);
+        assert_eq!(values["enable-calculator"].scalar(), Some("true"));
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = settings();
+        let (values, _) = parse(DEFAULT_CONFIG);
+        assert_eq!(settings.len(), values.len());
+        let theme = settings.iter().find(|s| s.key == "enable-theme-switcher").unwrap();
+        assert_eq!((theme.section, theme.default.scalar()), ("Theme", Some("false")));
+        assert!(theme.help.starts_with("profile picker"));
+        assert!(settings.iter().any(|s| s.key == "search-providers" && matches!(s.default, Value::List(_))));
+    }
+}
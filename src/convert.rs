@@ -0,0 +1,272 @@
+//! Unit and currency conversion for search queries like `10 km to mi`,
+//! `72 f to c` or `100 usd to eur`.
+//!
+//! Units are a static table of linear factors per dimension (temperature is
+//! the one affine special case). Currency rates are optional: fetched with
+//! `curl` on a background thread, cached on disk, and the stale cache is used
+//! as-is when offline.
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::gui::Config;
+use crate::json::Json;
+
+// ============================================================================
+// Units
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Dim { Length, Mass, Volume, Time, Data, Speed, Temperature }
+
+/// (aliases, dimension, factor to the dimension's base unit). The first alias
+/// is the display name.
+const UNITS: &[(&[&str], Dim, f64)] = &[
+    (&["m", "meter", "meters", "metre", "metres"],        Dim::Length, 1.0),
+    (&["km", "kilometer", "kilometers", "kilometre"],     Dim::Length, 1000.0),
+    (&["cm", "centimeter", "centimeters"],                Dim::Length, 0.01),
+    (&["mm", "millimeter", "millimeters"],                Dim::Length, 0.001),
+    (&["mi", "mile", "miles"],                            Dim::Length, 1609.344),
+    (&["yd", "yard", "yards"],                            Dim::Length, 0.9144),
+    (&["ft", "foot", "feet"],                             Dim::Length, 0.3048),
+    (&["in", "inch", "inches"],                           Dim::Length, 0.0254),
+    (&["nmi", "nautical mile", "nautical miles"],         Dim::Length, 1852.0),
+    (&["kg", "kilogram", "kilograms", "kilo", "kilos"],   Dim::Mass,   1.0),
+    (&["g", "gram", "grams"],                             Dim::Mass,   0.001),
+    (&["mg", "milligram", "milligrams"],                  Dim::Mass,   1e-6),
+    (&["t", "tonne", "tonnes"],                           Dim::Mass,   1000.0),
+    (&["lb", "lbs", "pound", "pounds"],                   Dim::Mass,   0.453_592_37),
+    (&["oz", "ounce", "ounces"],                          Dim::Mass,   0.028_349_523_125),
+    (&["st", "stone", "stones"],                          Dim::Mass,   6.350_293_18),
+    (&["l", "liter", "liters", "litre", "litres"],        Dim::Volume, 1.0),
+    (&["ml", "milliliter", "milliliters", "millilitre"],  Dim::Volume, 0.001),
+    (&["gal", "gallon", "gallons"],                       Dim::Volume, 3.785_411_784),
+    (&["qt", "quart", "quarts"],                          Dim::Volume, 0.946_352_946),
+    (&["pt", "pint", "pints"],                            Dim::Volume, 0.473_176_473),
+    (&["cup", "cups"],                                    Dim::Volume, 0.236_588_236_5),
+    (&["floz", "fl oz", "fluid ounce", "fluid ounces"],   Dim::Volume, 0.029_573_529_562_5),
+    (&["tbsp", "tablespoon", "tablespoons"],              Dim::Volume, 0.014_786_764_781_25),
+    (&["tsp", "teaspoon", "teaspoons"],                   Dim::Volume, 0.004_928_921_593_75),
+    (&["s", "sec", "secs", "second", "seconds"],          Dim::Time,   1.0),
+    (&["ms", "millisecond", "milliseconds"],              Dim::Time,   0.001),
+    (&["min", "mins", "minute", "minutes"],               Dim::Time,   60.0),
+    (&["h", "hr", "hrs", "hour", "hours"],                Dim::Time,   3600.0),
+    (&["d", "day", "days"],                               Dim::Time,   86400.0),
+    (&["wk", "week", "weeks"],                            Dim::Time,   604_800.0),
+    (&["B", "b", "byte", "bytes"],                        Dim::Data,   1.0),
+    (&["kB", "kb", "kilobyte", "kilobytes"],              Dim::Data,   1e3),
+    (&["MB", "mb", "megabyte", "megabytes"],              Dim::Data,   1e6),
+    (&["GB", "gb", "gigabyte", "gigabytes"],              Dim::Data,   1e9),
+    (&["TB", "tb", "terabyte", "terabytes"],              Dim::Data,   1e12),
+    (&["KiB", "kib"],                                     Dim::Data,   1024.0),
+    (&["MiB", "mib"],                                     Dim::Data,   1_048_576.0),
+    (&["GiB", "gib"],                                     Dim::Data,   1_073_741_824.0),
+    (&["TiB", "tib"],                                     Dim::Data,   1_099_511_627_776.0),
+    (&["km/h", "kmh", "kph"],                             Dim::Speed,  1.0 / 3.6),
+    (&["m/s", "mps"],                                     Dim::Speed,  1.0),
+    (&["mph"],                                            Dim::Speed,  0.447_04),
+    (&["kn", "knot", "knots"],                            Dim::Speed,  0.514_444),
+    (&["°C", "c", "celsius", "°c"],                       Dim::Temperature, 0.0),
+    (&["°F", "f", "fahrenheit", "°f"],                    Dim::Temperature, 0.0),
+    (&["K", "k", "kelvin"],                               Dim::Temperature, 0.0),
+];
+
+fn find_unit(name: &str) -> Option<&'static (&'static [&'static str], Dim, f64)> {
+    let lower = name.to_lowercase();
+    UNITS.iter().find(|(aliases, _, _)| aliases.iter().any(|a| a.to_lowercase() == lower))
+}
+
+fn to_kelvin(v: f64, unit: &str) -> f64 {
+    match unit { "°C" => v + 273.15, "°F" => (v - 32.0) * 5.0 / 9.0 + 273.15, _ => v }
+}
+
+fn from_kelvin(v: f64, unit: &str) -> f64 {
+    match unit { "°C" => v - 273.15, "°F" => (v - 273.15) * 9.0 / 5.0 + 32.0, _ => v }
+}
+
+// ============================================================================
+// Query parsing
+// ============================================================================
+
+/// Result of a conversion: `text` is shown in the result row, `value` is what
+/// gets copied.
+pub struct Conversion {
+    pub text:  String,
+    pub value: String,
+}
+
+/// Split `"10 km to mi"` / `"10km in miles"` into (amount, from, to).
+fn parse_query(query: &str) -> Option<(f64, &str, &str)> {
+    let q = query.trim();
+    let (lhs, to) = q.rsplit_once(" to ").or_else(|| q.rsplit_once(" in "))?;
+    let lhs = lhs.trim();
+    let num_end = lhs.find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+')))
+        .unwrap_or(lhs.len());
+    let amount: f64 = lhs[..num_end].replace(',', "").parse().ok()?;
+    let from = lhs[num_end..].trim();
+    let to   = to.trim();
+    (!from.is_empty() && !to.is_empty()).then_some((amount, from, to))
+}
+
+/// Convert `query` if it has the shape `<amount> <unit> to|in <unit>`.
+pub fn convert(query: &str, rates: &CurrencyRates) -> Option<Conversion> {
+    let (amount, from, to) = parse_query(query)?;
+
+    if let (Some(f), Some(t)) = (find_unit(from), find_unit(to)) {
+        if f.1 != t.1 { return None; }
+        let (from_name, to_name) = (f.0[0], t.0[0]);
+        let v = if f.1 == Dim::Temperature {
+            from_kelvin(to_kelvin(amount, from_name), to_name)
+        } else {
+            amount * f.2 / t.2
+        };
+        let value = crate::calc::format_result(round_significant(v, 6));
+        return Some(Conversion { text: format!("{value} {to_name}"), value });
+    }
+
+    let (from, to) = (from.to_uppercase(), to.to_uppercase());
+    let v = rates.convert(amount, &from, &to)?;
+    let value = format!("{v:.2}");
+    Some(Conversion { text: format!("{value} {to}"), value })
+}
+
+fn round_significant(v: f64, digits: i32) -> f64 {
+    if v == 0.0 || !v.is_finite() { return v; }
+    let scale = 10f64.powi(digits - 1 - v.abs().log10().floor() as i32);
+    (v * scale).round() / scale
+}
+
+// ============================================================================
+// Currency rates
+// ============================================================================
+
+/// Rates relative to USD. The endpoint needs no API key and updates daily.
+const RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+
+static RATES_FILE: LazyLock<PathBuf> = LazyLock::new(|| {
+    let path = crate::paths::config_home().join("tusk-launcher");
+    fs::create_dir_all(&path).ok();
+    path.join("currency_rates.txt")
+});
+
+#[derive(Default)]
+struct RateTable {
+    /// Unix seconds when the table was fetched.
+    fetched: u64,
+    /// Currency code → units per USD.
+    rates:   HashMap<String, f64>,
+}
+
+/// Shared, possibly-empty rate table. Loaded from disk at startup and
+/// replaced in place when a background refresh completes.
+#[derive(Clone, Default)]
+pub struct CurrencyRates(Arc<Mutex<RateTable>>);
+
+impl CurrencyRates {
+    /// Load the on-disk cache and, if enabled and older than the refresh
+    /// interval, start a background refresh. Any failure keeps the old table.
+    pub fn new(config: &Config) -> Self {
+        let table = fs::read_to_string(&*RATES_FILE).ok()
+            .and_then(|s| deserialize_rates(&s))
+            .unwrap_or_default();
+        let stale = now_secs().saturating_sub(table.fetched) >= config.currency_refresh_hours * 3600;
+        let rates = CurrencyRates(Arc::new(Mutex::new(table)));
+
+        if config.fetch_currency_rates && stale {
+            let shared = rates.clone();
+            thread::spawn(move || match fetch_rates() {
+                Ok(fresh) => {
+                    if let Err(e) = fs::write(&*RATES_FILE, serialize_rates(&fresh)) {
+                        tracing::warn!(target: "search", "Failed to save currency rates: {e}");
+                    }
+                    if let Ok(mut guard) = shared.0.lock() { *guard = fresh; }
+                }
+                Err(e) => tracing::warn!(target: "search", "Failed to fetch currency rates: {e}"),
+            });
+        }
+        rates
+    }
+
+    fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        let table = self.0.lock().ok()?;
+        let (f, t) = (table.rates.get(from)?, table.rates.get(to)?);
+        Some(amount / f * t)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn fetch_rates() -> Result<RateTable, Box<dyn std::error::Error>> {
+    let output = Command::new("curl").args(["-fsSL", "--max-time", "10", RATES_URL]).output()?;
+    if !output.status.success() { return Err(format!("curl exited with {}", output.status).into()); }
+    let body  = String::from_utf8(output.stdout)?;
+    let rates = parse_rates_json(&body).ok_or("unexpected response format")?;
+    Ok(RateTable { fetched: now_secs(), rates })
+}
+
+/// The response's `"rates": {"EUR": 0.92, …}` object.
+fn parse_rates_json(body: &str) -> Option<HashMap<String, f64>> {
+    let json = Json::parse(body)?;
+    let Some(Json::Obj(members)) = json.get("rates") else { return None };
+    let rates: HashMap<String, f64> = members.iter()
+        .filter_map(|(code, rate)| Some((code.clone(), rate.as_f64()?)))
+        .collect();
+    (!rates.is_empty()).then_some(rates)
+}
+
+fn serialize_rates(table: &RateTable) -> String {
+    let mut s = format!("RATES_V1\t{}\n", table.fetched);
+    for (code, rate) in &table.rates {
+        s.push_str(&format!("{code}\t{rate}\n"));
+    }
+    s
+}
+
+fn deserialize_rates(s: &str) -> Option<RateTable> {
+    let mut lines = s.lines();
+    let fetched = lines.next()?.strip_prefix("RATES_V1\t")?.parse().ok()?;
+    let rates = lines
+        .filter_map(|l| {
+            let (code, rate) = l.split_once('\t')?;
+            Some((code.to_string(), rate.parse().ok()?))
+        })
+        .collect();
+    Some(RateTable { fetched, rates })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_units() {
+        let rates = CurrencyRates::default();
+        assert_eq!(convert("10 km to mi", &rates).map(|c| c.text), Some("6.21371 mi".into()));
+        assert_eq!(convert("72 f to c", &rates).map(|c| c.text), Some("22.2222 °C".into()));
+        assert_eq!(convert("1 GiB in MB", &rates).map(|c| c.value), Some("1073.74".into()));
+        assert!(convert("10 km to kg", &rates).is_none());
+    }
+
+    #[test]
+    fn test_convert_currency_from_table() {
+        let mut table = RateTable::default();
+        table.rates.insert("USD".into(), 1.0);
+        table.rates.insert("EUR".into(), 0.5);
+        let rates = CurrencyRates(Arc::new(Mutex::new(table)));
+        assert_eq!(convert("100 usd to eur", &rates).map(|c| c.text), Some("50.00 EUR".into()));
+        assert!(convert("100 usd to xyz", &rates).is_none());
+
+        let parsed = parse_rates_json(r#"{"result":"success","base_code":"USD","rates":{"USD":1,"EUR":0.92}}"#).unwrap();
+        assert_eq!((parsed.get("USD"), parsed.get("EUR")), (Some(&1.0), Some(&0.92)));
+        assert!(parse_rates_json(r#"{"result":"error"}"#).is_none());
+    }
+}
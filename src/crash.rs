@@ -0,0 +1,152 @@
+//! Crash reports: a panic hook that saves what happened and says where.
+//!
+//! Design:
+//! - Each panic writes `$XDG_STATE_HOME/tusk-launcher/crash-<unix time>.txt`:
+//!   the message, where and on which thread, a backtrace (function names
+//!   only in builds that aren't stripped, unlike the release profile), the
+//!   version and desktop, and the settings that differ from the defaults.
+//!   It's logged too, which also covers stderr.
+//! - A panic on a background thread (tray, notifications, …) leaves the
+//!   window up: it's queued here, and the GUI shows a dialog with the path.
+//! - A panic on the main thread takes the window down with it, so `zenity`,
+//!   `kdialog` or `notify-send` says so instead — a daemon bound to a hotkey
+//!   would otherwise just stop answering.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{Value, Values};
+
+const CRASH_DIR: &str = "tusk-launcher";
+
+/// A background thread's panic, for the GUI to show.
+#[derive(Clone, Debug)]
+pub struct Crash {
+    pub thread:  String,
+    pub message: String,
+    /// The report; `None` when it couldn't be written.
+    pub path:    Option<PathBuf>,
+}
+
+static CRASHES: Mutex<Vec<Crash>> = Mutex::new(Vec::new());
+static WAKER: OnceLock<Box<dyn Fn() + Send + Sync>> = OnceLock::new();
+
+/// Replace the default panic hook; after logging is set up.
+pub fn install() {
+    std::panic::set_hook(Box::new(|info| {
+        let thread   = std::thread::current().name().unwrap_or("unnamed").to_string();
+        let message  = info.payload_as_str().unwrap_or("(no message)").to_string();
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        let defaults = crate::config::parse(crate::config::DEFAULT_CONFIG).0;
+        let settings = changed_settings(&crate::config::current_values(), &defaults);
+        let text     = report(&thread, &message, &location, &Backtrace::force_capture().to_string(), &settings);
+        let path     = write_report(&text);
+
+        let saved = path.as_ref().map_or("not saved".into(), |p| format!("report in {}", p.display()));
+        tracing::error!(target: "gui", "thread '{thread}' panicked at {location}: {message} ({saved})");
+        let crash = Crash { thread, message, path };
+        if crash.thread == "main" {
+            tell_desktop(&crash);
+        } else {
+            CRASHES.lock().unwrap_or_else(|e| e.into_inner()).push(crash);
+            if let Some(wake) = WAKER.get() { wake(); }
+        }
+    }));
+}
+
+/// Called when a background thread panics; the GUI repaints with it.
+pub fn on_crash(wake: impl Fn() + Send + Sync + 'static) {
+    let _ = WAKER.set(Box::new(wake));
+}
+
+/// Panics not shown yet.
+pub fn take() -> Vec<Crash> {
+    std::mem::take(&mut *CRASHES.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// `key = value` for each setting of `current` that isn't its default,
+/// sorted; lists on one line.
+fn changed_settings(current: &Values, defaults: &Values) -> Vec<String> {
+    let mut out: Vec<String> = current.iter()
+        .filter(|(key, value)| defaults.get(*key) != Some(*value))
+        .map(|(key, value)| match value {
+            Value::Scalar(s) => format!("{key} = {s}"),
+            Value::List(l)   => format!("{key} = [{}]", l.join(", ")),
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn report(thread: &str, message: &str, location: &str, backtrace: &str, settings: &[String]) -> String {
+    let session = if std::env::var_os("WAYLAND_DISPLAY").is_some() { "Wayland" } else { "X11" };
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_else(|_| "unknown".into());
+    let settings = if settings.is_empty() { "(all defaults)".to_string() } else { settings.join("\n") };
+    format!(
+        "Tusk Launcher {} crashed\n\nThread:   {thread}\nMessage:  {message}\nLocation: {location}\nDesktop:  {desktop} ({session})\n\n\
+         Changed settings:\n{settings}\n\nBacktrace:\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+fn write_report(text: &str) -> Option<PathBuf> {
+    let dir  = crate::paths::state_home().join(CRASH_DIR);
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let path = dir.join(format!("crash-{secs}.txt"));
+    fs::create_dir_all(&dir).and_then(|_| fs::write(&path, text)).ok()?;
+    Some(path)
+}
+
+/// The window is gone: show the first of these the desktop has. They're
+/// left running as this process exits.
+fn tell_desktop(crash: &Crash) {
+    let saved = crash.path.as_ref().map_or("No report could be saved.".into(), |p| format!("A report was saved to {}", p.display()));
+    let text  = format!("Tusk Launcher crashed: {}\n\n{saved}", crash.message);
+    let dialogs: [(&str, Vec<&str>); 3] = [
+        ("zenity",      vec!["--error", "--no-markup", "--title=Tusk Launcher", "--text", &text]),
+        ("kdialog",     vec!["--title", "Tusk Launcher", "--error", &text]),
+        ("notify-send", vec!["--urgency=critical", "Tusk Launcher crashed", &text]),
+    ];
+    for (program, args) in dialogs {
+        let spawned = Command::new(program).args(&args)
+            .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+            .spawn();
+        if spawned.is_ok() { return; }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let values = |pairs: &[(&str, Value)]| -> Values { pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect() };
+        let defaults = values(&[
+            ("theme", Value::Scalar("".into())),
+            ("daemon-mode", Value::Scalar("false".into())),
+            ("key-clear", Value::List(vec!["Ctrl+L".into()])),
+        ]);
+        let current = values(&[
+            ("theme", Value::Scalar("".into())),
+            ("daemon-mode", Value::Scalar("true".into())),
+            ("key-clear", Value::List(vec!["Ctrl+L".into(), "Ctrl+U".into()])),
+        ]);
+        let changed = changed_settings(&current, &defaults);
+        assert_eq!(changed, ["daemon-mode = true", "key-clear = [Ctrl+L, Ctrl+U]"]);
+        assert!(changed_settings(&defaults, &defaults).is_empty());
+
+        let text = report("sni", "index out of bounds", "src/sni.rs:10:5", "0: main", &changed);
+        assert!(text.contains("Thread:   sni\nMessage:  index out of bounds\nLocation: src/sni.rs:10:5"));
+        assert!(text.contains("Changed settings:\ndaemon-mode = true\n"));
+        assert!(report("main", "boom", "", "", &[]).contains("(all defaults)"));
+    }
+}
@@ -0,0 +1,104 @@
+//! `org.tusk.Launcher` on the session bus: the control socket's commands for
+//! desktop environments and scripts that speak D-Bus.
+//!
+//! Design:
+//! - `Show`, `Hide`, `Toggle`, `Quit` and `SetQuery(s)` on
+//!   `/org/tusk/Launcher` become the same `ipc::Command`s the socket
+//!   delivers, on the same channel, so the GUI handles both alike.
+//!   `SetQuery` shows the launcher with that text in the search field.
+//! - `DoNotDisturb` (read/write) and `ToggleDoNotDisturb` go through the same
+//!   channel, so the GUI saves the change as it does for its own toggle.
+//!   Changes from anywhere are announced with `PropertiesChanged`.
+//! - Claimed by the instance that owns the control socket, on its own
+//!   connection. If the name is taken we log it and carry on; the socket
+//!   still works.
+//! - `org.tusk.Launcher.service` (installed to
+//!   `/usr/share/dbus-1/services`) lets the bus start the launcher for a
+//!   call when none is running. It passes `--dbus-activated`, which skips
+//!   the socket handover and, in daemon mode, starts hidden so the call
+//!   decides whether to show.
+
+use std::thread;
+
+use zbus::interface;
+use zbus::connection::Builder as ConnectionBuilder;
+
+use crate::ipc::Command;
+
+const BUS_NAME: &str = "org.tusk.Launcher";
+const OBJ_PATH: &str = "/org/tusk/Launcher";
+
+type OnCommand = Box<dyn Fn(Command) + Send + Sync>;
+
+struct Control {
+    on_command: OnCommand,
+}
+
+#[interface(name = "org.tusk.Launcher")]
+impl Control {
+    fn show(&self) {
+        (self.on_command)(Command::Show);
+    }
+
+    fn hide(&self) {
+        (self.on_command)(Command::Hide);
+    }
+
+    fn toggle(&self) {
+        (self.on_command)(Command::Toggle);
+    }
+
+    fn set_query(&self, query: String) {
+        (self.on_command)(Command::SetQuery(query));
+    }
+
+    fn quit(&self) {
+        (self.on_command)(Command::Quit);
+    }
+
+    fn toggle_do_not_disturb(&self) {
+        (self.on_command)(Command::DoNotDisturb(!crate::notifications::do_not_disturb()));
+    }
+
+    #[zbus(property)]
+    fn do_not_disturb(&self) -> bool {
+        crate::notifications::do_not_disturb()
+    }
+
+    #[zbus(property)]
+    fn set_do_not_disturb(&self, on: bool) {
+        (self.on_command)(Command::DoNotDisturb(on));
+    }
+}
+
+/// Claim the name on a background thread and hand each call to `on_command`.
+pub fn serve(on_command: impl Fn(Command) + Send + Sync + 'static) {
+    let on_command: OnCommand = Box::new(on_command);
+    thread::spawn(move || {
+        match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt.block_on(async {
+                if let Err(e) = run(on_command).await {
+                    tracing::warn!(target: "ipc", "Can't offer {BUS_NAME} on the session bus: {e}");
+                }
+            }),
+            Err(e) => tracing::error!(target: "ipc", "runtime error: {e}"),
+        }
+    });
+}
+
+async fn run(on_command: OnCommand) -> zbus::Result<()> {
+    let mut dnd = crate::notifications::watch_do_not_disturb();
+    let conn = ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJ_PATH, Control { on_command })?
+        .build().await?;
+    tracing::info!(target: "ipc", "claimed {BUS_NAME}");
+    // Calls are handled by tasks on this runtime; this one only announces
+    // do-not-disturb changes, and keeps the runtime going.
+    let control = conn.object_server().interface::<_, Control>(OBJ_PATH).await?;
+    while dnd.changed().await.is_ok() {
+        control.get().await.do_not_disturb_changed(control.signal_emitter()).await?;
+    }
+    std::future::pending::<()>().await;
+    Ok(())
+}
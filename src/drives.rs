@@ -0,0 +1,331 @@
+//! UDisks2 integration (`org.freedesktop.UDisks2`): the drives plugged in
+//! and where they're mounted.
+//!
+//! Design:
+//! - One `GetManagedObjects` call yields every block device with its drive;
+//!   re-run whenever UDisks2 emits a signal (a drive plugged in, a volume
+//!   mounted, …) and on a slow poll as a fallback.
+//! - Only filesystems UDisks2 doesn't hint as system or ignored are listed:
+//!   USB sticks, SD cards, external disks, other partitions of the disks.
+//!   `/`, `/home` and swap stay out.
+//! - Mount, unmount and eject are UDisks2 calls, so they need no file
+//!   manager and no root: polkit lets the active session's user mount
+//!   removable media. They run as tasks so the list keeps refreshing while
+//!   a slow unmount flushes.
+//! - Eject unmounts every volume on the drive, then powers it off, as file
+//!   managers' "Safely Remove" does; drives that can't be powered off
+//!   (optical, card readers) eject their media instead.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use zbus::Connection;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const UDISKS_BUS:       &str = "org.freedesktop.UDisks2";
+const UDISKS_PATH:      &str = "/org/freedesktop/UDisks2";
+const BLOCK_IFACE:      &str = "org.freedesktop.UDisks2.Block";
+const FILESYSTEM_IFACE: &str = "org.freedesktop.UDisks2.Filesystem";
+const DRIVE_IFACE:      &str = "org.freedesktop.UDisks2.Drive";
+
+const T_CALL:        Duration = Duration::from_secs(3);
+/// Unmounting flushes writes to the stick, which can take a while.
+const T_ACTION:      Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct Volume {
+    /// Block device object path, passed back to `mount` and friends.
+    pub path:        String,
+    /// `/dev/sdb1`
+    pub device:      String,
+    /// Filesystem label, else the drive's model, else the device node.
+    pub name:        String,
+    /// `HintIconName`, else a generic drive icon.
+    pub icon:        String,
+    pub mount_point: Option<String>,
+    /// On a drive that can be unplugged or has removable media.
+    pub removable:   bool,
+    /// Bytes.
+    pub size:        u64,
+    /// The drive's object path; `None` for loop devices and the like.
+    pub drive:       Option<String>,
+}
+
+impl Volume {
+    /// "31.9 GB", in the decimal units drives are sold in.
+    pub fn size_label(&self) -> String {
+        let units = ["B", "kB", "MB", "GB", "TB"];
+        let mut size = self.size as f64;
+        let mut unit = 0;
+        while size >= 1000.0 && unit < units.len() - 1 { size /= 1000.0; unit += 1; }
+        if unit == 0 { format!("{size} B") } else { format!("{size:.1} {}", units[unit]) }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DrivesState {
+    /// Removable volumes first, then by name.
+    pub volumes: Vec<Volume>,
+    /// Volume paths with a call in flight.
+    pub pending: Vec<String>,
+    /// Why the last mount/unmount/eject failed ("target is busy", …).
+    pub error:   Option<String>,
+}
+
+pub type SharedDrivesState = Arc<Mutex<DrivesState>>;
+
+/// Called from the backend after every refresh, so the owner can redraw.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+enum DrivesAction {
+    Mount   { path: String },
+    Unmount { path: String },
+    Eject   { path: String },
+}
+
+pub struct DrivesHost {
+    pub state: SharedDrivesState,
+    action_tx: tokio::sync::mpsc::UnboundedSender<DrivesAction>,
+}
+
+impl DrivesHost {
+    pub fn new(on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        let state: SharedDrivesState = Arc::new(Mutex::new(DrivesState::default()));
+        let state_bg = Arc::clone(&state);
+        let waker: Waker = Arc::new(on_change);
+        let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(async {
+                    if let Err(e) = run_monitor(state_bg, action_rx, waker).await {
+                        tracing::warn!(target: "drives", "{e}");
+                    }
+                }),
+                Err(e) => tracing::error!(target: "drives", "runtime error: {e}"),
+            }
+        });
+
+        DrivesHost { state, action_tx }
+    }
+
+    pub fn mount(&self, path: &str) {
+        self.mark_pending(path);
+        let _ = self.action_tx.send(DrivesAction::Mount { path: path.into() });
+    }
+
+    pub fn unmount(&self, path: &str) {
+        self.mark_pending(path);
+        let _ = self.action_tx.send(DrivesAction::Unmount { path: path.into() });
+    }
+
+    /// Unmount the volume's whole drive and eject or power it off.
+    pub fn eject(&self, path: &str) {
+        self.mark_pending(path);
+        let _ = self.action_tx.send(DrivesAction::Eject { path: path.into() });
+    }
+
+    fn mark_pending(&self, path: &str) {
+        if let Ok(mut s) = self.state.lock() {
+            s.pending.push(path.into());
+            s.error = None;
+        }
+    }
+
+    /// Volumes that are mounted, with their mount point.
+    pub fn mounted(&self) -> Vec<Volume> {
+        let Ok(s) = self.state.lock() else { return Vec::new() };
+        s.volumes.iter().filter(|v| v.mount_point.is_some()).cloned().collect()
+    }
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+async fn run_monitor(
+    state:         SharedDrivesState,
+    mut action_rx: tokio::sync::mpsc::UnboundedReceiver<DrivesAction>,
+    waker:         Waker,
+) -> zbus::Result<()> {
+    let conn = Connection::system().await?;
+    refresh(&conn, &state).await;
+    waker();
+
+    // Any signal from UDisks2 (interfaces added/removed, property changes).
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(UDISKS_BUS)?
+        .build();
+    let mut signals = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+    let mut poll    = tokio::time::interval(POLL_INTERVAL);
+    let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel::<(String, Option<String>)>();
+
+    loop {
+        tokio::select! {
+            Some(_) = signals.next() => {}
+            _ = poll.tick()          => {}
+            Some((path, error)) = done_rx.recv() => {
+                if let Ok(mut s) = state.lock() {
+                    s.pending.retain(|p| *p != path);
+                    if error.is_some() { s.error = error; }
+                }
+            }
+            action = action_rx.recv() => {
+                let Some(action) = action else { return Ok(()) };
+                let volumes = state.lock().map(|s| s.volumes.clone()).unwrap_or_default();
+                spawn_action(&conn, action, volumes, &done_tx);
+            }
+        }
+        refresh(&conn, &state).await;
+        waker();
+    }
+}
+
+fn spawn_action(
+    conn:    &Connection,
+    action:  DrivesAction,
+    volumes: Vec<Volume>,
+    done_tx: &tokio::sync::mpsc::UnboundedSender<(String, Option<String>)>,
+) {
+    let conn    = conn.clone();
+    let done_tx = done_tx.clone();
+    tokio::spawn(async move {
+        let (path, result) = match action {
+            DrivesAction::Mount { path }   => { let r = mount(&conn, &path).await; (path, r) }
+            DrivesAction::Unmount { path } => { let r = unmount(&conn, &path).await; (path, r) }
+            DrivesAction::Eject { path }   => { let r = eject(&conn, &path, &volumes).await; (path, r) }
+        };
+        let error = result.err().map(|e| {
+            tracing::warn!(target: "drives", "{path}: {e}");
+            e
+        });
+        let _ = done_tx.send((path, error));
+    });
+}
+
+type Options = HashMap<&'static str, Value<'static>>;
+
+async fn mount(conn: &Connection, path: &str) -> Result<(), String> {
+    let reply = udisks_call(conn, path, FILESYSTEM_IFACE, "Mount", &(Options::new(),)).await?;
+    let at: String = reply.body().deserialize().unwrap_or_default();
+    tracing::info!(target: "drives", "mounted {path} at {at}");
+    Ok(())
+}
+
+async fn unmount(conn: &Connection, path: &str) -> Result<(), String> {
+    udisks_call(conn, path, FILESYSTEM_IFACE, "Unmount", &(Options::new(),)).await.map(drop)
+}
+
+/// Unmount everything on `path`'s drive, then power it off, or eject the
+/// media when it can't be powered off (optical drives, card readers).
+async fn eject(conn: &Connection, path: &str, volumes: &[Volume]) -> Result<(), String> {
+    let drive = volumes.iter().find(|v| v.path == path).and_then(|v| v.drive.clone()).ok_or("not on a drive")?;
+    for volume in volumes.iter().filter(|v| v.drive.as_ref() == Some(&drive) && v.mount_point.is_some()) {
+        unmount(conn, &volume.path).await?;
+    }
+    let props  = drive_props(conn, &drive).await;
+    let method = if prop_bool(&props, "CanPowerOff") { "PowerOff" } else { "Eject" };
+    udisks_call(conn, &drive, DRIVE_IFACE, method, &(Options::new(),)).await.map(drop)
+}
+
+async fn drive_props(conn: &Connection, drive: &str) -> HashMap<String, OwnedValue> {
+    match tokio::time::timeout(T_CALL, conn.call_method(
+        Some(UDISKS_BUS), drive, Some("org.freedesktop.DBus.Properties"), "GetAll", &(DRIVE_IFACE,),
+    )).await {
+        Ok(Ok(msg)) => msg.body().deserialize().unwrap_or_default(),
+        _           => HashMap::new(),
+    }
+}
+
+/// A UDisks2 method call; the error is UDisks2's own message.
+async fn udisks_call<B>(conn: &Connection, path: &str, iface: &str, method: &str, body: &B) -> Result<zbus::Message, String>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    match tokio::time::timeout(T_ACTION, conn.call_method(Some(UDISKS_BUS), path, Some(iface), method, body)).await {
+        Ok(Ok(msg))                                         => Ok(msg),
+        Ok(Err(zbus::Error::MethodError(_, Some(text), _))) => Err(text),
+        Ok(Err(e))                                          => Err(e.to_string()),
+        Err(_)                                              => Err(format!("{method} timed out")),
+    }
+}
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+async fn refresh(conn: &Connection, state: &SharedDrivesState) {
+    let objects: ManagedObjects = match tokio::time::timeout(T_CALL, conn.call_method(
+        Some(UDISKS_BUS), UDISKS_PATH, Some("org.freedesktop.DBus.ObjectManager"), "GetManagedObjects", &(),
+    )).await {
+        Ok(Ok(msg)) => msg.body().deserialize().unwrap_or_default(),
+        _           => ManagedObjects::new(),
+    };
+
+    let drives: HashMap<String, &HashMap<String, OwnedValue>> = objects.iter()
+        .filter_map(|(path, ifaces)| Some((path.to_string(), ifaces.get(DRIVE_IFACE)?)))
+        .collect();
+
+    let mut volumes = Vec::new();
+    for (path, ifaces) in &objects {
+        let (Some(block), Some(fs)) = (ifaces.get(BLOCK_IFACE), ifaces.get(FILESYSTEM_IFACE)) else { continue };
+        if prop_bool(block, "HintIgnore") || prop_bool(block, "HintSystem") { continue; }
+        let drive_path = block.get("Drive")
+            .and_then(|v| v.try_clone().ok())
+            .and_then(|v| OwnedObjectPath::try_from(v).ok())
+            .map(|p| p.to_string())
+            .filter(|p| p != "/");
+        let drive = drive_path.as_ref().and_then(|p| drives.get(p)).copied();
+        let device = prop_bytes(block, "PreferredDevice").or_else(|| prop_bytes(block, "Device")).unwrap_or_default();
+        let model  = drive.and_then(|d| prop_str(d, "Model"));
+        volumes.push(Volume {
+            path:        path.to_string(),
+            name:        prop_str(block, "HintName")
+                .or_else(|| prop_str(block, "IdLabel"))
+                .or(model)
+                .unwrap_or_else(|| device.clone()),
+            icon:        prop_str(block, "HintIconName").unwrap_or_else(|| "drive-removable-media".into()),
+            mount_point: fs.get("MountPoints")
+                .and_then(|v| v.try_clone().ok())
+                .and_then(|v| Vec::<Vec<u8>>::try_from(v).ok())
+                .and_then(|points| points.into_iter().next())
+                .map(|p| c_string(&p)),
+            removable:   drive.is_some_and(|d| prop_bool(d, "Removable") || prop_bool(d, "MediaRemovable")),
+            size:        block.get("Size").and_then(|v| u64::try_from(v).ok()).unwrap_or(0),
+            drive:       drive_path,
+            device,
+        });
+    }
+    volumes.sort_by(|a, b| b.removable.cmp(&a.removable).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+
+    if let Ok(mut s) = state.lock() { s.volumes = volumes; }
+}
+
+fn prop_bool(props: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    matches!(props.get(key).map(|v| &**v), Some(Value::Bool(true)))
+}
+
+fn prop_str(props: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    props.get(key).and_then(|v| <&str>::try_from(v).ok()).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// An `ay` property holding a NUL-terminated path.
+fn prop_bytes(props: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    let bytes = props.get(key).and_then(|v| v.try_clone().ok()).and_then(|v| Vec::<u8>::try_from(v).ok())?;
+    Some(c_string(&bytes)).filter(|s| !s.is_empty())
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
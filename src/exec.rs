@@ -0,0 +1,179 @@
+//! Desktop Entry `Exec` lines (Desktop Entry Specification, "The Exec key").
+//!
+//! `normalize` turns the raw key into an internal command line: string
+//! escapes undone, field codes expanded, every argument re-quoted. That is
+//! what `App.exec` and the app cache hold, and `split` turns it back into an
+//! argv to spawn directly — no `sh -c`, so quoting in an entry can't turn
+//! into shell syntax. Normalized lines are only ever read back by `split`,
+//! never field-code-expanded again.
+
+/// Values for the field codes that don't depend on files/URLs (we never pass any).
+pub struct FieldCodes<'a> {
+    /// `%c`: the translated Name.
+    pub name: &'a str,
+    /// `%i`: the Icon key, expanded to `--icon <icon>` when set.
+    pub icon: Option<&'a str>,
+    /// `%k`: path of the desktop file.
+    pub path: &'a str,
+}
+
+/// Raw `Exec=` value → normalized command line, or `None` if it's malformed
+/// (unbalanced quotes) or empty.
+pub fn normalize(raw: &str, codes: &FieldCodes) -> Option<String> {
+    let mut argv = Vec::new();
+    for (arg, quoted) in tokenize(&unescape_value(raw))? {
+        if quoted { argv.push(arg); continue; }
+        // flatpak's `@@u %u @@` markers delimit file arguments.
+        if arg.starts_with("@@") { continue; }
+        if arg == "%i" {
+            if let Some(icon) = codes.icon.filter(|i| !i.is_empty()) {
+                argv.extend(["--icon".to_string(), icon.to_string()]);
+            }
+            continue;
+        }
+        let expanded = expand_codes(&arg, codes);
+        // An argument that was only a file code (`%U`) disappears entirely.
+        if !expanded.is_empty() || !arg.contains('%') { argv.push(expanded); }
+    }
+    (!argv.is_empty()).then(|| join(&argv))
+}
+
+/// Normalized command line → argv. `None` on unbalanced quotes, which only
+/// happens for hand-edited cache entries.
+pub fn split(line: &str) -> Option<Vec<String>> {
+    let argv: Vec<String> = tokenize(line)?.into_iter().map(|(arg, _)| arg).collect();
+    (!argv.is_empty()).then_some(argv)
+}
+
+/// argv → command line in Exec quoting, readable back with `split`.
+pub fn join(argv: &[String]) -> String {
+    argv.iter().map(|arg| {
+        let needs_quotes = arg.is_empty()
+            || arg.chars().any(|c| c.is_whitespace() || "\"'\\><~|&;$*?#()`%".contains(c));
+        if !needs_quotes { return arg.clone(); }
+        let mut quoted = String::with_capacity(arg.len() + 2);
+        quoted.push('"');
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') { quoted.push('\\'); }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// argv → `sh` command line, for splicing into a user's custom command.
+pub fn shell_quote(argv: &[String]) -> String {
+    argv.iter().map(|arg| {
+        let safe = !arg.is_empty()
+            && arg.chars().all(|c| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c));
+        if safe { arg.clone() } else { format!("'{}'", arg.replace('\'', "'\\''")) }
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// String-value escapes: `\s` `\n` `\t` `\r` `\\`. Other backslashes are left
+/// for the Exec quoting rules.
+fn unescape_value(s: &str) -> String {
+    let mut out   = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue; }
+        match chars.peek() {
+            Some('s')  => { out.push(' ');  chars.next(); }
+            Some('n')  => { out.push('\n'); chars.next(); }
+            Some('t')  => { out.push('\t'); chars.next(); }
+            Some('r')  => { out.push('\r'); chars.next(); }
+            Some('\\') => { out.push('\\'); chars.next(); }
+            _          => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Split on unquoted whitespace. Inside `"…"` a backslash escapes `" ` $ \`;
+/// outside, it escapes any character (lenient, like `g_shell_parse_argv`).
+/// Each argument is flagged if any part of it was quoted.
+fn tokenize(s: &str) -> Option<Vec<(String, bool)>> {
+    let mut args   = Vec::new();
+    let mut cur    = String::new();
+    let mut quoted = false;
+    let mut in_arg = false;
+    let mut chars  = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_arg = true;
+                quoted = true;
+                loop {
+                    match chars.next()? {
+                        '"'  => break,
+                        '\\' => match chars.next()? {
+                            e @ ('"' | '`' | '$' | '\\') => cur.push(e),
+                            e => { cur.push('\\'); cur.push(e); }
+                        },
+                        other => cur.push(other),
+                    }
+                }
+            }
+            '\\' => { in_arg = true; cur.push(chars.next()?); }
+            c if c.is_whitespace() => {
+                if in_arg { args.push((std::mem::take(&mut cur), quoted)); }
+                in_arg = false;
+                quoted = false;
+            }
+            c => { in_arg = true; cur.push(c); }
+        }
+    }
+    if in_arg { args.push((cur, quoted)); }
+    Some(args)
+}
+
+fn expand_codes(arg: &str, codes: &FieldCodes) -> String {
+    let mut out   = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' { out.push(c); continue; }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('c') => out.push_str(codes.name),
+            Some('k') => out.push_str(codes.path),
+            // File/URL codes (we launch without files), embedded %i, and the
+            // deprecated %d %D %n %N %v %m all expand to nothing.
+            Some('f' | 'F' | 'u' | 'U' | 'i' | 'd' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+            Some(other) => { out.push('%'); out.push(other); }
+            None        => out.push('%'),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODES: FieldCodes = FieldCodes { name: "My App", icon: Some("myapp"), path: "/x/my.desktop" };
+
+    #[test]
+    fn test_normalize_field_codes() {
+        assert_eq!(normalize("foo %U", &CODES).as_deref(), Some("foo"));
+        assert_eq!(normalize("foo %i --title=%c %k 100%%", &CODES).as_deref(),
+            Some(r#"foo --icon myapp "--title=My App" /x/my.desktop "100%""#));
+        assert_eq!(normalize("/usr/bin/flatpak run --file-forwarding org.X @@u %u @@", &CODES).as_deref(),
+            Some("/usr/bin/flatpak run --file-forwarding org.X"));
+        assert_eq!(normalize("foo \"bar", &CODES), None);
+    }
+
+    #[test]
+    fn test_quoting_roundtrip() {
+        // As written in a .desktop file: `\\\\` is one literal backslash.
+        let line = normalize(r#""/opt/My App/run" --eval "print(\"\$HOME\")" "a\\\\b" ''"#, &CODES).unwrap();
+        assert_eq!(split(&line).unwrap(), ["/opt/My App/run", "--eval", "print(\"$HOME\")", "a\\b", "''"]);
+        let argv: Vec<String> = vec!["it's".into(), "x y".into(), "plain".into()];
+        assert_eq!(shell_quote(&argv), r"'it'\''s' 'x y' plain");
+        assert_eq!(split(&join(&argv)).unwrap(), argv);
+    }
+}
@@ -0,0 +1,155 @@
+//! Flow layout for sections when `.main-window` sets `display: flex`.
+//!
+//! Design:
+//! - One line, no wrapping: sections stack along `flex-direction` in their
+//!   `order`, separated by `gap`, inside the window's `padding`.
+//! - A section's main-axis size is its `height` (column) / `width` (row) when
+//!   the theme gives one, otherwise what it measured last frame; `grow`
+//!   shares out the leftover space like CSS `flex-grow`.
+//! - The cross axis follows `align` (`start`, `center`, `end`, or the default
+//!   `stretch`), inside each section's `margin`.
+//! - Plain geometry with no egui types, so it's testable on its own; the GUI
+//!   feeds measured sizes back in and repaints once when they change.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Edges {
+    pub top:    f32,
+    pub right:  f32,
+    pub bottom: f32,
+    pub left:   f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Column,
+    Row,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    Stretch,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Item {
+    /// Size along the flow before `grow` is added.
+    pub main:   f32,
+    /// Natural size across the flow; ignored when stretched.
+    pub cross:  f32,
+    pub grow:   f32,
+    pub margin: Edges,
+    pub align:  Align,
+}
+
+/// Place `items` inside `area` along `direction`.
+pub fn layout(area: Rect, direction: Direction, gap: f32, items: &[Item]) -> Vec<Rect> {
+    // Work in (main, cross) coordinates and swap back for rows.
+    let (main_len, cross_len) = match direction {
+        Direction::Column => (area.h, area.w),
+        Direction::Row    => (area.w, area.h),
+    };
+    let margins = |m: &Edges| match direction {
+        Direction::Column => (m.top, m.bottom, m.left, m.right),
+        Direction::Row    => (m.left, m.right, m.top, m.bottom),
+    };
+
+    let used: f32 = items.iter().map(|i| { let (a, b, _, _) = margins(&i.margin); i.main + a + b }).sum::<f32>()
+        + gap * items.len().saturating_sub(1) as f32;
+    let free       = (main_len - used).max(0.0);
+    let total_grow = items.iter().map(|i| i.grow.max(0.0)).sum::<f32>();
+
+    let mut cursor = 0.0;
+    items.iter().map(|item| {
+        let (before, after, cross_start, cross_end) = margins(&item.margin);
+        let main = item.main + if total_grow > 0.0 { free * item.grow.max(0.0) / total_grow } else { 0.0 };
+        let room = (cross_len - cross_start - cross_end).max(0.0);
+        let cross_size = if item.align == Align::Stretch { room } else { item.cross.min(room) };
+        let cross_pos = cross_start + match item.align {
+            Align::Start | Align::Stretch => 0.0,
+            Align::Center                 => (room - cross_size) / 2.0,
+            Align::End                    => room - cross_size,
+        };
+        cursor += before;
+        let main_pos = cursor;
+        cursor += main + after + gap;
+        match direction {
+            Direction::Column => Rect { x: area.x + cross_pos, y: area.y + main_pos, w: cross_size, h: main },
+            Direction::Row    => Rect { x: area.x + main_pos, y: area.y + cross_pos, w: main, h: cross_size },
+        }
+    }).collect()
+}
+
+/// CSS box shorthand: one to four lengths (`4px`, `4px 8px`, `1 2 3 4`).
+pub fn parse_edges(s: &str) -> Option<Edges> {
+    let v: Vec<f32> = s.split_whitespace()
+        .map(|t| t.trim_end_matches("px").parse().ok())
+        .collect::<Option<_>>()?;
+    let (top, right, bottom, left) = match v[..] {
+        [a]          => (a, a, a, a),
+        [a, b]       => (a, b, a, b),
+        [a, b, c]    => (a, b, c, b),
+        [a, b, c, d] => (a, b, c, d),
+        _            => return None,
+    };
+    Some(Edges { top, right, bottom, left })
+}
+
+pub fn parse_align(s: &str) -> Option<Align> {
+    match s.trim() {
+        "start" | "flex-start" | "left" | "top"     => Some(Align::Start),
+        "center"                                    => Some(Align::Center),
+        "end" | "flex-end" | "right" | "bottom"     => Some(Align::End),
+        "stretch"                                   => Some(Align::Stretch),
+        _                                           => None,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(main: f32, grow: f32, align: Align) -> Item {
+        Item { main, cross: 50.0, grow, margin: Edges::default(), align }
+    }
+
+    #[test]
+    fn test_column_grow() {
+        let area = Rect { x: 10.0, y: 10.0, w: 200.0, h: 300.0 };
+        let items = [
+            item(26.0, 0.0, Align::Stretch),
+            item(0.0,  1.0, Align::Stretch),
+            Item { margin: Edges { top: 4.0, ..Edges::default() }, ..item(20.0, 0.0, Align::Center) },
+        ];
+        let rects = layout(area, Direction::Column, 4.0, &items);
+        assert_eq!(rects[0], Rect { x: 10.0, y: 10.0, w: 200.0, h: 26.0 });
+        // 300 - 26 - 20 - 4 (margin) - 8 (gaps) = 242 left for the grower.
+        assert_eq!(rects[1], Rect { x: 10.0, y: 40.0, w: 200.0, h: 242.0 });
+        assert_eq!(rects[2], Rect { x: 85.0, y: 290.0, w: 50.0, h: 20.0 });
+    }
+
+    #[test]
+    fn test_row_align() {
+        let area = Rect { x: 0.0, y: 0.0, w: 100.0, h: 40.0 };
+        let items = [item(30.0, 0.0, Align::End), item(30.0, 0.0, Align::Start)];
+        let rects = layout(area, Direction::Row, 0.0, &items);
+        assert_eq!(rects[0], Rect { x: 0.0, y: 0.0, w: 30.0, h: 40.0 });
+        assert_eq!(rects[1], Rect { x: 30.0, y: 0.0, w: 30.0, h: 40.0 });
+        assert_eq!(parse_edges("2px 4px"), Some(Edges { top: 2.0, right: 4.0, bottom: 2.0, left: 4.0 }));
+        assert_eq!(parse_edges("1 2 3 4 5"), None);
+    }
+}
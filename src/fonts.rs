@@ -0,0 +1,97 @@
+//! System fonts for the theme's `font-family`, `font-weight` and `font-style`.
+//!
+//! Design:
+//! - egui can't pick fonts by name or synthesize bold/italic, so each
+//!   (family, weight, style) combination the theme uses becomes its own
+//!   `FontFamily::Name`, backed by the closest face fontdb finds.
+//! - egui's bundled fonts stay behind every face as fallbacks, so emoji and
+//!   symbols the system face lacks still render.
+//! - A face that can't be found is still registered (as the bundled fonts
+//!   alone): egui panics on an unbound family name.
+//! - System fonts are only scanned when the theme asks for one.
+use std::process::Command;
+use std::sync::Arc;
+
+use eframe::egui::{Context, FontData, FontDefinitions, FontFamily};
+use fontdb::{Database, Family, Query, Stretch, Style, Weight};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Face {
+    /// CSS family list, e.g. `"Inter", sans-serif`.
+    pub families: String,
+    pub weight:   u16,
+    pub italic:   bool,
+}
+
+impl Face {
+    /// Name of the egui font family this face is registered under.
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}", self.families, self.weight, if self.italic { "italic" } else { "normal" })
+    }
+
+    pub fn family(&self) -> FontFamily {
+        FontFamily::Name(self.key().into())
+    }
+}
+
+/// Register `faces` with egui; they take effect from the next frame.
+/// An empty list restores the bundled fonts.
+pub fn install(ctx: &Context, faces: &[Face]) {
+    let mut defs = FontDefinitions::default();
+    if !faces.is_empty() {
+        let db = system_fonts();
+        let bundled = defs.families.get(&FontFamily::Proportional).cloned().unwrap_or_default();
+        for face in faces {
+            let key = face.key();
+            let mut chain = bundled.clone();
+            let data = find(&db, face).and_then(|id| db.with_face_data(id, |bytes, index| {
+                FontData { index, ..FontData::from_owned(bytes.to_vec()) }
+            }));
+            match data {
+                Some(data) => {
+                    defs.font_data.insert(key.clone(), Arc::new(data));
+                    chain.insert(0, key.clone());
+                }
+                None => tracing::warn!(target: "theme", "No font matches font-family: {}; using the default font", face.families),
+            }
+            defs.families.insert(FontFamily::Name(key.into()), chain);
+        }
+    }
+    ctx.set_fonts(defs);
+}
+
+fn system_fonts() -> Database {
+    let mut db = Database::new();
+    db.load_system_fonts();
+    // fontdb's generic families default to Windows names; ask fontconfig instead.
+    let fc_match = |generic: &str| -> Option<String> {
+        let out = Command::new("fc-match").args(["-f", "%{family[0]}", generic]).output().ok()?;
+        let name = String::from_utf8(out.stdout).ok()?;
+        (out.status.success() && !name.is_empty()).then_some(name)
+    };
+    if let Some(name) = fc_match("sans-serif") { db.set_sans_serif_family(name); }
+    if let Some(name) = fc_match("serif")      { db.set_serif_family(name); }
+    if let Some(name) = fc_match("monospace")  { db.set_monospace_family(name); }
+    db
+}
+
+/// First family in the list with an installed face, nearest in weight and style.
+fn find(db: &Database, face: &Face) -> Option<fontdb::ID> {
+    let style = if face.italic { Style::Italic } else { Style::Normal };
+    face.families.split(',').map(|f| f.trim().trim_matches(['"', '\''])).find_map(|name| {
+        // Family names match case-insensitively, as in CSS.
+        let installed = db.faces()
+            .flat_map(|f| f.families.iter())
+            .map(|(family, _)| family.as_str())
+            .find(|family| family.eq_ignore_ascii_case(name));
+        let family = match name.to_ascii_lowercase().as_str() {
+            "sans-serif" | "system-ui" => Family::SansSerif,
+            "serif"                    => Family::Serif,
+            "monospace"                => Family::Monospace,
+            "cursive"                  => Family::Cursive,
+            "fantasy"                  => Family::Fantasy,
+            _                          => Family::Name(installed?),
+        };
+        db.query(&Query { families: &[family], weight: Weight(face.weight), stretch: Stretch::Normal, style })
+    })
+}
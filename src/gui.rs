@@ -4,23 +4,25 @@ use std::{
     fs::{read_to_string, OpenOptions},
     io::Write,
     path::PathBuf,
-    sync::Arc,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
     time::{Duration, Instant},
 };
 use eframe;
 use serde::{Deserialize, Serialize};
-use crate::app_launcher::resolve_icon_path;
+use crate::app_launcher::{resolve_icon_path, AppLaunchOptions, AppOverride, LaunchFailure, TrayPref};
+use crate::providers::{ResultKind, ResultRow, RowAction};
 
 /// Local wall-clock time — replaces `time::OffsetDateTime` with zero extra deps.
 /// Populated via `libc::localtime_r`, which is always available on Linux
 /// (libc is already a transitive dep via zbus → nix → libc).
 pub struct LocalTime {
-    pub year:  i32,
-    pub month: u8,   // 1–12
-    pub day:   u8,
-    pub hour:  u8,
-    pub min:   u8,
-    pub sec:   u8,
+    pub year:   i32,
+    pub month:  u8,   // 1–12
+    pub day:    u8,
+    pub hour:   u8,
+    pub min:    u8,
+    pub sec:    u8,
+    pub offset: i32,  // seconds east of UTC
 }
 
 impl LocalTime {
@@ -32,23 +34,41 @@ impl LocalTime {
             let mut tm: libc::tm = std::mem::zeroed();
             libc::localtime_r(&t, &mut tm);
             Self {
-                year:  (tm.tm_year + 1900),
-                month: (tm.tm_mon + 1) as u8,
-                day:   tm.tm_mday as u8,
-                hour:  tm.tm_hour as u8,
-                min:   tm.tm_min as u8,
-                sec:   tm.tm_sec as u8,
+                year:   (tm.tm_year + 1900),
+                month:  (tm.tm_mon + 1) as u8,
+                day:    tm.tm_mday as u8,
+                hour:   tm.tm_hour as u8,
+                min:    tm.tm_min as u8,
+                sec:    tm.tm_sec as u8,
+                offset: tm.tm_gmtoff as i32,
             }
         }
         #[cfg(not(unix))]
-        Self { year: 2024, month: 1, day: 1, hour: 0, min: 0, sec: 0 }
+        Self { year: 2024, month: 1, day: 1, hour: 0, min: 0, sec: 0, offset: 0 }
     }
 }
 
 const DEFAULT_THEME: &str = r#"
 /* ═══════════════════════════════════════════════════════
    Tusk Launcher — Default Theme
-   Define your palette here; reference it with var(--name).
+   Define your palette here; reference it with var(--name)
+   or var(--name, fallback). Derive shades with
+   rgba(var(--accent), 0.5), darken(var(--accent), 10%),
+   lighten(…, 10%) and mix(var(--a), var(--b), 30%).
+   Search bar, app and power buttons take border-width,
+   border-color and box-shadow: 0 2px 6px rgba(0, 0, 0, 0.4).
+   Sections are placed with left/top (or right/bottom to
+   pin the far edge) in px or % of the main window, or
+   left: center; width/height also take %.
+   Or skip coordinates: .main-window { display: flex;
+   flex-direction: column; gap: 4px; padding: 10px; }
+   stacks sections in order; each takes grow, align
+   (start/center/end/stretch) and margin. Sections with
+   position: absolute stay where left/top put them.
+   A profile in themes/ (theme = "nord" in config.toml)
+   is layered over this file, usually just a new :root.
+   Any section can set font-family (installed fonts,
+   e.g. "Inter", sans-serif), font-weight and font-style.
    Hover states use standard :selector:hover { } blocks.
    ═══════════════════════════════════════════════════════ */
 
@@ -56,12 +76,15 @@ const DEFAULT_THEME: &str = r#"
     --bg-base:     rgba(12,  12,  18,  0.96);
     --bg-raised:   rgba(36,  36,  52,  1);
     --bg-hover:    rgba(52,  52,  74,  1);
+    --bg-field-hover: rgba(48, 48, 68, 1);
     --accent:      rgba(110, 90,  220, 1);
     --accent-hi:   rgba(135, 115, 245, 1);
     --text:        rgba(218, 216, 232, 1);
     --text-bright: rgba(235, 233, 250, 1);
     --text-dim:    rgba(120, 118, 140, 1);
     --green:       rgba(72,  210, 140, 1);
+    --red:         rgba(235, 87,  87,  1);
+    --yellow:      rgba(235, 180, 80,  1);
     --transparent: rgba(0,   0,   0,   0);
 }
 
@@ -79,6 +102,7 @@ const DEFAULT_THEME: &str = r#"
     background-image: url("");
     background-size: stretch;
     background-opacity: 1.0;
+    symbolic-color: var(--text); /* foreground of -symbolic icons (tray) */
 }
 
 /* Search Bar */
@@ -95,7 +119,7 @@ const DEFAULT_THEME: &str = r#"
     font-size: 12px;
 }
 .search-bar:hover {
-    background-color: rgba(48, 48, 68, 1);
+    background-color: var(--bg-field-hover);
 }
 
 /* App List Container */
@@ -124,6 +148,66 @@ const DEFAULT_THEME: &str = r#"
     color: var(--text-bright);
 }
 
+/* Games grid tile (cover art is 2:3 portrait) */
+.game-tile {
+    width: 60px;
+    height: 90px;
+    gap: 4px;
+    border-radius: 4px;
+}
+.game-tile:hover {
+    border-color: var(--accent-hi);
+}
+
+/* Wallpaper grid tile ("wp:"); gap, corners and hover follow .game-tile */
+.wallpaper-tile {
+    width: 96px;
+    height: 54px;
+}
+
+/* Active prefix mode (run, windows, a category, …) inside the search bar */
+.mode-badge {
+    background-color: var(--accent);
+    color: var(--bg-base);
+    border-radius: 6px;
+    font-size: 10px;
+}
+
+/* Category chips above the list (show-category-chips = true) */
+.category-chip {
+    background-color: var(--bg-raised);
+    color: var(--text);
+    active-color: var(--accent);
+}
+.category-chip:hover {
+    background-color: var(--bg-hover);
+}
+
+/* Letter index above the list when show-all-apps = true */
+.app-index {
+    color: var(--text-dim);
+    font-size: 10px;
+}
+.app-index:hover {
+    color: var(--accent-hi);
+}
+
+/* App tile (results-view = "grid"); :hover also marks the
+ * keyboard selection. Width follows grid-columns. */
+.app-tile {
+    height: 60px;
+    gap: 4px;
+    icon-size: 28px;
+    background-color: var(--transparent);
+    color: var(--text);
+    border-radius: 6px;
+    font-size: 10px;
+}
+.app-tile:hover {
+    background-color: var(--bg-hover);
+    color: var(--text-bright);
+}
+
 /* App Icon */
 .app-icon {
     width: 16px;
@@ -159,6 +243,16 @@ const DEFAULT_THEME: &str = r#"
     border-radius: 0px;
     text-align: left;
 }
+/* Hover popup for tray items: title, body and category */
+.tray-tooltip {
+    background-color: var(--bg-raised);
+    color: var(--text);
+    category-color: var(--text-dim);
+    border-radius: 6px;
+    padding: 6px;
+    font-size: 11px;
+    max-width: 220px;
+}
 
 /* Clock */
 .time-display {
@@ -181,6 +275,7 @@ const DEFAULT_THEME: &str = r#"
     height: 16px;
     background-color: var(--bg-raised);
     color: var(--text);
+    muted-color: var(--red);
     border-radius: 6px;
     gap: 5px;
 }
@@ -205,6 +300,217 @@ const DEFAULT_THEME: &str = r#"
     color: var(--text-bright);
 }
 
+/* Notification list — shown when enable-notifications is true.
+ * Grow .main-window height (or move other sections) to make room. */
+.notifications {
+    position: absolute;
+    left: 12px;
+    top: 242px;
+    width: 196px;
+    height: 120px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    critical-color: var(--red);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.notification-action {
+    background-color: var(--accent);
+    color: var(--text);
+    border-radius: 4px;
+}
+.notification-action:hover {
+    background-color: var(--accent-hi);
+}
+
+/* Network status + known Wi-Fi picker — shown when enable-network is true. */
+.network {
+    position: absolute;
+    left: 12px;
+    top: 366px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.network:hover {
+    background-color: var(--bg-hover);
+    color: var(--text-bright);
+}
+
+/* VPN toggles (NetworkManager, wg-quick) — shown when enable-vpn is true.
+ * The label turns active-color while one is up. */
+.vpn {
+    position: absolute;
+    left: 12px;
+    top: 616px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    active-color: var(--green);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.vpn:hover {
+    background-color: var(--bg-hover);
+    color: var(--text-bright);
+}
+
+/* Paired Bluetooth devices — shown when enable-bluetooth is true. */
+.bluetooth {
+    position: absolute;
+    left: 12px;
+    top: 390px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.bluetooth:hover {
+    background-color: var(--bg-hover);
+    color: var(--text-bright);
+}
+
+/* Microphone — shown when enable-mic-control is true. */
+.mic-control {
+    position: absolute;
+    left: 12px;
+    top: 414px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    muted-color: var(--red);
+    border-radius: 6px;
+    font-size: 11px;
+    gap: 5px;
+}
+.mic-control:hover {
+    background-color: var(--bg-hover);
+}
+
+/* Caffeine — shown when enable-idle-inhibitor is true. */
+.idle-inhibitor {
+    position: absolute;
+    left: 12px;
+    top: 438px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    active-color: var(--yellow);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.idle-inhibitor:hover {
+    background-color: var(--bg-hover);
+}
+
+/* System stats — shown when enable-system-stats is true. */
+.system-stats {
+    position: absolute;
+    left: 12px;
+    top: 462px;
+    width: 196px;
+    height: 30px;
+    background-color: var(--transparent);
+    color: var(--text-dim);
+    font-size: 10px;
+    text-align: left;
+}
+
+/* Timer — shown when enable-timer is true. */
+.timer {
+    position: absolute;
+    left: 12px;
+    top: 496px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--transparent);
+    color: var(--text);
+    done-color: var(--yellow);
+    font-size: 11px;
+    gap: 4px;
+}
+/* Theme profile picker (enable-theme-switcher) */
+.theme-switcher {
+    position: absolute;
+    left: 12px;
+    top: 520px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--transparent);
+    color: var(--text);
+    font-size: 11px;
+}
+
+/* Screenshot / recording buttons — shown when enable-quick-actions is true. */
+.quick-actions {
+    position: absolute;
+    left: 12px;
+    top: 568px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--transparent);
+    color: var(--text);
+    recording-color: var(--red);
+    font-size: 11px;
+}
+
+/* Workspace buttons (Hyprland, Sway) — shown when enable-workspaces is true.
+ * The focused one is drawn selected; empty ones use empty-color. */
+.workspaces {
+    position: absolute;
+    left: 12px;
+    top: 592px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--transparent);
+    color: var(--text);
+    empty-color: var(--text-dim);
+    urgent-color: var(--red);
+    font-size: 11px;
+    gap: 2px;
+}
+
+/* Current weather: condition icon and temperature — shown when enable-weather
+ * is true. A report kept from before a failed fetch uses stale-color. */
+.weather {
+    position: absolute;
+    left: 12px;
+    top: 640px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--transparent);
+    color: var(--text);
+    stale-color: var(--text-dim);
+    icon-size: 18px;
+    font-size: 12px;
+    gap: 4px;
+}
+
+/* Removable drives (UDisks2) — shown when enable-drives is true. */
+.drives {
+    position: absolute;
+    left: 12px;
+    top: 544px;
+    width: 196px;
+    height: 20px;
+    background-color: var(--bg-raised);
+    color: var(--text);
+    border-radius: 6px;
+    font-size: 11px;
+}
+.drives:hover {
+    background-color: var(--bg-hover);
+    color: var(--text-bright);
+}
+
 /* Edit / Save / Cancel (env-vars popup) */
 .edit-button {
     background-color: var(--accent);
@@ -218,56 +524,297 @@ const DEFAULT_THEME: &str = r#"
     color: white;
 }
 
-/* Env-Vars Input Field */
+/* Launch Options Window (fields; width/height size the window) */
 .env-input {
     background-color: var(--bg-raised);
     color: var(--text);
     padding: 0px;
     font-size: 12px;
     border-radius: 6px;
-    width: 200px;
-    height: 60px;
-    scaling: 1.0;
-}
-
-/* Configuration */
-.config {
-    enable-recent-apps: true;
-    max-search-results: 5;
-    enable-power-options: true;
-    show-time: true;
-    time-format: "%I:%M %p";
-    time-order: MdyHms; /* Options: MdyHms, YmdHms, DmyHms */
-    enable-audio-control: false;
-    max-volume: 1.5;
-    volume-update-interval-ms: 500;
-    power-commands: "systemctl poweroff, loginctl poweroff, poweroff, halt";
-    restart-commands: "systemctl reboot, loginctl reboot, reboot";
-    logout-commands: "loginctl terminate-session $XDG_SESSION_ID, hyprctl dispatch exit, swaymsg exit, gnome-session-quit --logout --no-prompt, qdbus org.kde.ksmserver /KSMServer logout 0 0 0";
-    enable-icons: true;
-    show-settings-button: true;
-    enable-system-tray: true;
+    width: 360px;
+    height: 340px;
+    error-color: var(--red);
+}
+
+/* Failed-launch toasts along the bottom of the window */
+.launch-error {
+    background-color: darken(var(--red), 30%);
+    color: var(--text);
+    border-radius: 6px;
+    font-size: 12px;
 }
 "#;
 
+/// Profiles written to `themes/` on first start. Each is layered over
+/// theme.css, so it only restates the palette.
+const PRESET_THEMES: &[(&str, &str)] = &[
+    ("nord", r#"/* Nord — https://www.nordtheme.com */
+:root {
+    --bg-base:        rgba(46, 52, 64, 0.96);
+    --bg-raised:      #3b4252;
+    --bg-hover:       #4c566a;
+    --bg-field-hover: #434c5e;
+    --accent:         #5e81ac;
+    --accent-hi:      #88c0d0;
+    --text:           #d8dee9;
+    --text-bright:    #eceff4;
+    --text-dim:       #7b88a1;
+    --green:          #a3be8c;
+    --red:            #bf616a;
+    --yellow:         #ebcb8b;
+}
+"#),
+    ("catppuccin", r#"/* Catppuccin Mocha — https://catppuccin.com */
+:root {
+    --bg-base:        rgba(30, 30, 46, 0.96);
+    --bg-raised:      #313244;
+    --bg-hover:       #45475a;
+    --bg-field-hover: #3b3c50;
+    --accent:         #cba6f7;
+    --accent-hi:      #b4befe;
+    --text:           #cdd6f4;
+    --text-bright:    #f5e0dc;
+    --text-dim:       #7f849c;
+    --green:          #a6e3a1;
+    --red:            #f38ba8;
+    --yellow:         #f9e2af;
+}
+"#),
+    ("gruvbox", r#"/* Gruvbox Dark — https://github.com/morhetz/gruvbox */
+:root {
+    --bg-base:        rgba(40, 40, 40, 0.96);
+    --bg-raised:      #3c3836;
+    --bg-hover:       #504945;
+    --bg-field-hover: #45403d;
+    --accent:         #458588;
+    --accent-hi:      #83a598;
+    --text:           #ebdbb2;
+    --text-bright:    #fbf1c7;
+    --text-dim:       #928374;
+    --green:          #b8bb26;
+    --red:            #fb4934;
+    --yellow:         #fabd2f;
+}
+"#),
+    ("light", r#"/* Light */
+:root {
+    --bg-base:        rgba(246, 246, 250, 0.97);
+    --bg-raised:      #e6e6ee;
+    --bg-hover:       #d6d6e2;
+    --bg-field-hover: #dcdce6;
+    --accent:         #5a48c8;
+    --accent-hi:      #6e5adc;
+    --text:           #2a2a36;
+    --text-bright:    #0e0e16;
+    --text-dim:       #7a7a8c;
+    --green:          #1e9e5a;
+    --red:            #d23c3c;
+    --yellow:         #c8841e;
+}
+"#),
+];
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// Profile in `themes/` layered over theme.css; empty is theme.css alone.
+    pub theme: String,
+    /// Profiles used instead of `theme` while the desktop prefers dark / light.
+    pub dark_theme: String,
+    pub light_theme: String,
+    /// Theme open/close effects and hover transitions; off makes them instant.
+    pub animations: bool,
+    /// Profile picker (`.theme-switcher`).
+    pub enable_theme_switcher: bool,
     pub enable_recent_apps: bool,
+    /// An empty query lists every app A–Z, with a letter index, instead of recents.
+    pub show_all_apps: bool,
     pub max_search_results: usize,
+    /// `list` rows, or a `grid` of icon tiles (`.app-tile`).
+    pub results_view: String,
+    /// Tiles per row in the grid view.
+    pub grid_columns: usize,
     pub enable_power_options: bool,
     pub show_time: bool,
+    /// strftime format, locale-aware (`%a %-d %b %H:%M`).
     pub time_format: String,
+    /// Deprecated: only used to append a date to a time-only `time_format`.
     pub time_order: TimeOrder,
+    /// IANA zone for the main clock ("Europe/Berlin"); empty follows the system.
+    pub timezone: String,
+    /// More clocks under the main one, as "Label=Area/City" or just "Area/City".
+    pub extra_clocks: Vec<String>,
+    pub extra_clock_format: String,
     pub enable_audio_control: bool,
     pub max_volume: f32,
     pub volume_update_interval_ms: u64,
+    /// Volume change per mouse-wheel notch over the volume slider.
+    pub volume_scroll_step: f32,
+    /// Dropdown next to the volume slider for picking the default output.
+    pub enable_sink_switcher: bool,
+    /// Microphone mute button and input level slider (`.mic-control`).
+    pub enable_mic_control: bool,
+    /// Power actions go through logind's D-Bus API when it's reachable.
+    pub use_logind: bool,
+    pub show_suspend_button: bool,
+    /// Caffeine toggle holding a logind idle inhibitor (`.idle-inhibitor`).
+    pub enable_idle_inhibitor: bool,
+    /// Uptime/load/CPU/RAM readout from /proc (`.system-stats`).
+    pub enable_system_stats: bool,
+    pub system_stats_interval_ms: u64,
+    /// Countdown with start/pause/reset (`.timer`); notifies when it ends.
+    pub enable_timer: bool,
+    /// "Label=25m" entries for the timer's preset menu.
+    pub timer_presets: Vec<String>,
+    /// Screenshot and recording buttons (`.quick-actions`).
+    pub enable_quick_actions: bool,
+    /// `screenshot`, `screenshot-area`, `record` or "Label=command", in order.
+    pub quick_actions: Vec<String>,
+    /// Where screenshots and recordings go; empty uses the XDG pictures and
+    /// videos directories.
+    pub capture_dir: String,
+    /// Workspace buttons from Hyprland or Sway (`.workspaces`).
+    pub enable_workspaces: bool,
     pub power_commands: Vec<String>,
     pub restart_commands: Vec<String>,
     pub logout_commands: Vec<String>,
+    pub suspend_commands: Vec<String>,
     pub enable_icons: bool,
+    /// Icon theme name; empty follows gsettings / GTK / KDE settings.
+    pub icon_theme: String,
+    /// Use `-symbolic` tray icons when the theme has them.
+    pub prefer_symbolic_icons: bool,
+    /// Play animated GIF/APNG icons; off shows their first frame.
+    pub animate_icons: bool,
     pub icon_cache_dir: PathBuf,
     pub show_settings_button: bool,
     pub enable_system_tray: bool,
+    /// Also act as an XEmbed tray for X11 apps without SNI support.
+    pub enable_xembed_tray: bool,
+    /// Hover time before a tray item's tooltip appears.
+    pub tray_tooltip_delay_ms: u64,
+    /// Output the window opens on: `focused`, `cursor` or an output name;
+    /// empty leaves it to the compositor.
+    pub window_monitor: String,
+    /// `center`, `top-center` or `cursor`; see `monitors::anchor`.
+    pub window_anchor: String,
+    /// Hide (or quit) when the window loses keyboard focus, like a popup.
+    pub close_on_focus_loss: bool,
+    /// `_NET_WM_WINDOW_TYPE` on X11: `dialog`, `utility` or `normal`.
+    pub x11_window_type: String,
+    /// Unmanaged X11 window; see `x11_hints`.
+    pub x11_override_redirect: bool,
+    /// ⚙ beside the search field, opening the settings window.
+    pub enable_settings_window: bool,
+    /// egui zoom factor over the output's own scale; the zoom keys save it.
+    pub ui_scale: f32,
+    /// Stay resident after launching/Escape; the window is hidden and later
+    /// shown again via the control socket instead of exiting.
+    pub daemon_mode: bool,
+    /// Launch XDG autostart entries when the first instance of a session starts.
+    pub run_autostart: bool,
+    /// Claim `org.tusk.Launcher` on the session bus for Show/Hide/… calls.
+    pub enable_dbus_interface: bool,
+    pub enable_notifications: bool,
+    /// Used when a sender passes `expire_timeout = -1` ("server default").
+    pub notification_timeout_ms: u64,
+    pub enable_network: bool,
+    /// NetworkManager VPN and wg-quick toggles (`.vpn`).
+    pub enable_vpn: bool,
+    /// wg-quick configs (`wg0` or a path) to offer when `/etc/wireguard`
+    /// can't be read.
+    pub wg_quick_configs: Vec<String>,
+    pub enable_bluetooth: bool,
+    /// Mount, unmount and eject drives through UDisks2 (`.drives`).
+    pub enable_drives: bool,
+    pub enable_calculator: bool,
+    pub enable_conversion: bool,
+    /// Download exchange rates (via curl) for `100 usd to eur` queries.
+    pub fetch_currency_rates: bool,
+    pub currency_refresh_hours: u64,
+    /// Current weather in the `.weather` section.
+    pub enable_weather: bool,
+    /// `open-meteo` or `wttr.in`.
+    pub weather_provider: String,
+    /// A place name or `lat,lon`.
+    pub weather_location: String,
+    /// `metric` or `imperial`.
+    pub weather_units: String,
+    pub weather_refresh_minutes: u64,
+    /// Query prefix that switches to shell run mode; empty disables it.
+    pub run_prefix: String,
+    /// Query prefix that lists open windows to focus; empty disables it.
+    pub window_prefix: String,
+    /// Query prefix that shows the Steam library as a cover-art grid; empty disables it.
+    pub games_prefix: String,
+    /// More `prefix=provider` routes (`"d:=desktop"`); they win over the
+    /// prefix keys above when both use the same prefix.
+    pub prefixes: Vec<String>,
+    /// Game-library toggle next to the search field.
+    pub show_games_button: bool,
+    /// Query prefix that lists one menu category (`cat:games`); empty disables it.
+    pub category_prefix: String,
+    /// Category chips above the app list (`.category-chip`).
+    pub show_category_chips: bool,
+    /// Results whose title matches one of these `*`/`?` patterns are never shown.
+    pub hidden_apps: Vec<String>,
+    /// Query prefix that lists apps hidden from the right-click menu, to unhide them.
+    pub hidden_prefix: String,
+    pub run_in_terminal: bool,
+    /// Also offer every executable on `$PATH` as a search result.
+    pub enable_path_binaries: bool,
+    /// Focus an app's open window instead of launching it again.
+    pub focus_or_launch: bool,
+    /// How long after a launch an exit with an error is still reported.
+    pub launch_check_ms: u64,
+    /// Typing pause before a search runs; keystrokes in between are coalesced.
+    pub search_debounce_ms: u64,
+    /// Record copied text for the clipboard-history prefix mode.
+    pub enable_clipboard_history: bool,
+    /// Query prefix that searches clipboard history.
+    pub clipboard_prefix: String,
+    pub clipboard_history_size: usize,
+    /// `memory`, `plain` or `encrypted`; see `clipboard.rs`.
+    pub clipboard_store: String,
+    /// Key for the encrypted store; empty uses the config directory.
+    pub clipboard_key_file: String,
+    /// Query prefix that searches snippets.toml.
+    pub snippet_prefix: String,
+    /// `copy` or `type`: what Enter does with a snippet.
+    pub snippet_insert: String,
+    /// Query prefix that lists recently used files.
+    pub recent_prefix: String,
+    /// Query prefix that lists places (home, bookmarks, drives).
+    pub places_prefix: String,
+    /// Query prefix that shows `wallpaper_dir` as a thumbnail grid.
+    pub wallpaper_prefix: String,
+    /// Images listed in wallpaper mode; `~/` is expanded.
+    pub wallpaper_dir: String,
+    /// `swww`, `hyprpaper` or `swaybg`; empty picks the one that's running.
+    pub wallpaper_setter: String,
+    /// Put the remembered wallpapers back when a session's first instance starts.
+    pub wallpaper_restore: bool,
+    /// Offer "Open URL" / web search rows when nothing else matches.
+    pub enable_web_search: bool,
+    /// `key=url` entries (`%s` = terms), used as `!key terms`; the first is the default.
+    pub search_engines: Vec<String>,
+    /// Preferred terminal (e.g. `kitty` or `foot -e %command%`); empty auto-detects
+    /// from `terminal_commands`.
+    pub terminal_command: String,
+    /// Tried in order; `%command%` is replaced by the command (appended if absent).
+    pub terminal_commands: Vec<String>,
+    /// Result sources in display order; see `providers::DEFAULT_PROVIDERS`.
+    pub search_providers: Vec<String>,
+    /// Chords per in-launcher action; see `keys::ACTIONS`.
+    pub key_next: Vec<String>,
+    pub key_previous: Vec<String>,
+    pub key_clear: Vec<String>,
+    pub key_actions: Vec<String>,
+    pub key_launch_options: Vec<String>,
+    pub key_zoom_in: Vec<String>,
+    pub key_zoom_out: Vec<String>,
+    pub key_zoom_reset: Vec<String>,
+    /// Modifier sets that launch the nth result with 1–9.
+    pub key_launch_nth: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -277,15 +824,40 @@ impl Default for Config {
     fn default() -> Self {
         let icon_cache_dir = crate::paths::config_home().join("tusk-launcher/icons");
         Self {
+            theme: String::new(),
+            dark_theme: String::new(),
+            light_theme: String::new(),
+            animations: true,
+            enable_theme_switcher: false,
             enable_recent_apps: true,
+            show_all_apps: false,
             max_search_results: 5,
+            results_view: "list".into(),
+            grid_columns: 4,
             enable_power_options: true,
             show_time: true,
-            time_format: "%I:%M %p".to_string(),
+            time_format: "%I:%M %p %m/%d/%Y".to_string(),
             time_order: TimeOrder::MdyHms,
+            timezone: String::new(),
+            extra_clocks: Vec::new(),
+            extra_clock_format: "%I:%M %p".to_string(),
             enable_audio_control: true,
             max_volume: 1.5,
             volume_update_interval_ms: 500,
+            volume_scroll_step: 0.05,
+            enable_sink_switcher: true,
+            enable_mic_control: false,
+            use_logind: true,
+            show_suspend_button: false,
+            enable_idle_inhibitor: false,
+            enable_system_stats: false,
+            system_stats_interval_ms: 2000,
+            enable_timer: false,
+            timer_presets: vec!["Pomodoro=25m".into(), "Short break=5m".into(), "Long break=15m".into()],
+            enable_quick_actions: false,
+            quick_actions: vec!["screenshot".into(), "screenshot-area".into(), "record".into()],
+            capture_dir: String::new(),
+            enable_workspaces: false,
             power_commands: vec!["systemctl poweroff".into(), "loginctl poweroff".into(), "poweroff".into(), "halt".into()],
             restart_commands: vec!["systemctl reboot".into(), "loginctl reboot".into(), "reboot".into()],
             logout_commands: vec![
@@ -294,34 +866,252 @@ impl Default for Config {
                 "gnome-session-quit --logout --no-prompt".into(),
                 "qdbus org.kde.ksmserver /KSMServer logout 0 0 0".into(),
             ],
+            suspend_commands: vec!["systemctl suspend".into(), "loginctl suspend".into()],
             enable_icons: true,
+            icon_theme: String::new(),
+            prefer_symbolic_icons: true,
+            animate_icons: true,
             icon_cache_dir,
             show_settings_button: true,
             enable_system_tray: false,
+            enable_xembed_tray: false,
+            tray_tooltip_delay_ms: 500,
+            window_monitor: String::new(),
+            window_anchor: "center".into(),
+            close_on_focus_loss: false,
+            x11_window_type: "dialog".into(),
+            x11_override_redirect: false,
+            enable_settings_window: true,
+            ui_scale: 1.0,
+            daemon_mode: false,
+            run_autostart: false,
+            enable_dbus_interface: true,
+            enable_notifications: false,
+            notification_timeout_ms: 5000,
+            enable_network: false,
+            enable_vpn: false,
+            wg_quick_configs: Vec::new(),
+            enable_bluetooth: false,
+            enable_drives: false,
+            enable_calculator: true,
+            enable_conversion: true,
+            fetch_currency_rates: false,
+            currency_refresh_hours: 12,
+            enable_weather: false,
+            weather_provider: "open-meteo".into(),
+            weather_location: String::new(),
+            weather_units: "metric".into(),
+            weather_refresh_minutes: 30,
+            run_prefix: ">".into(),
+            window_prefix: "w:".into(),
+            games_prefix: "g:".into(),
+            prefixes: Vec::new(),
+            show_games_button: false,
+            category_prefix: "cat:".into(),
+            show_category_chips: false,
+            hidden_apps: Vec::new(),
+            hidden_prefix: "hidden:".into(),
+            run_in_terminal: false,
+            enable_path_binaries: false,
+            focus_or_launch: false,
+            launch_check_ms: 2000,
+            search_debounce_ms: 30,
+            enable_clipboard_history: false,
+            clipboard_prefix: "clip:".into(),
+            clipboard_history_size: 50,
+            clipboard_store: "memory".into(),
+            clipboard_key_file: String::new(),
+            snippet_prefix: ";".into(),
+            snippet_insert: "copy".into(),
+            recent_prefix: "r:".into(),
+            places_prefix: "p:".into(),
+            wallpaper_prefix: "wp:".into(),
+            wallpaper_dir: "~/Pictures/Wallpapers".into(),
+            wallpaper_setter: String::new(),
+            wallpaper_restore: false,
+            enable_web_search: true,
+            search_engines: vec![
+                "ddg=https://duckduckgo.com/?q=%s".into(),
+                "g=https://www.google.com/search?q=%s".into(),
+                "yt=https://www.youtube.com/results?search_query=%s".into(),
+                "w=https://en.wikipedia.org/w/index.php?search=%s".into(),
+            ],
+            terminal_command: String::new(),
+            terminal_commands: vec![
+                "$TERMINAL -e %command%".into(), "foot %command%".into(), "kitty %command%".into(),
+                "alacritty -e %command%".into(), "wezterm start -- %command%".into(),
+                "konsole -e %command%".into(), "gnome-terminal -- %command%".into(), "xterm -e %command%".into(),
+            ],
+            search_providers: crate::providers::DEFAULT_PROVIDERS.iter().map(|s| s.to_string()).collect(),
+            key_next: crate::keys::default_chords("key-next"),
+            key_previous: crate::keys::default_chords("key-previous"),
+            key_clear: crate::keys::default_chords("key-clear"),
+            key_actions: crate::keys::default_chords("key-actions"),
+            key_launch_options: crate::keys::default_chords("key-launch-options"),
+            key_zoom_in: crate::keys::default_chords("key-zoom-in"),
+            key_zoom_out: crate::keys::default_chords("key-zoom-out"),
+            key_zoom_reset: crate::keys::default_chords("key-zoom-reset"),
+            key_launch_nth: crate::keys::LAUNCH_NTH.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Config {
+    /// Settings from config.toml (or a migrated `.config` block); unknown keys
+    /// and unparsable values leave the defaults in place.
+    pub fn from_values(values: &crate::config::Values) -> Config {
+        let mut config = Config::default();
+        let scalar = |key: &str| values.get(key).and_then(|v| v.scalar()).map(str::to_string);
+        macro_rules! set {
+            ($key:expr, $field:ident, $typ:ty) => {
+                if let Some(val) = scalar($key) {
+                    if let Ok(parsed) = val.parse::<$typ>() { config.$field = parsed; }
+                }
+            };
+        }
+        set!("enable-theme-switcher",      enable_theme_switcher,     bool);
+        set!("enable-recent-apps",        enable_recent_apps,        bool);
+        set!("show-all-apps",              show_all_apps,             bool);
+        set!("max-search-results",         max_search_results,        usize);
+        set!("grid-columns",               grid_columns,              usize);
+        set!("enable-power-options",       enable_power_options,      bool);
+        set!("use-logind",                 use_logind,                bool);
+        set!("show-suspend-button",        show_suspend_button,       bool);
+        set!("enable-idle-inhibitor",      enable_idle_inhibitor,     bool);
+        set!("enable-system-stats",        enable_system_stats,       bool);
+        set!("system-stats-interval-ms",   system_stats_interval_ms,  u64);
+        set!("enable-timer",               enable_timer,              bool);
+        set!("enable-quick-actions",       enable_quick_actions,      bool);
+        set!("enable-workspaces",          enable_workspaces,         bool);
+        set!("show-time",                  show_time,                 bool);
+        set!("enable-audio-control",       enable_audio_control,      bool);
+        set!("max-volume",                 max_volume,                f32);
+        set!("volume-update-interval-ms",  volume_update_interval_ms, u64);
+        set!("volume-scroll-step",         volume_scroll_step,        f32);
+        set!("enable-sink-switcher",       enable_sink_switcher,      bool);
+        set!("enable-mic-control",         enable_mic_control,        bool);
+        set!("enable-icons",               enable_icons,              bool);
+        set!("prefer-symbolic-icons",      prefer_symbolic_icons,     bool);
+        set!("animations",                 animations,                bool);
+        set!("animate-icons",              animate_icons,             bool);
+        set!("show-settings-button",       show_settings_button,      bool);
+        set!("enable-system-tray",         enable_system_tray,        bool);
+        set!("enable-xembed-tray",         enable_xembed_tray,        bool);
+        set!("tray-tooltip-delay-ms",      tray_tooltip_delay_ms,     u64);
+        set!("close-on-focus-loss",        close_on_focus_loss,       bool);
+        set!("x11-override-redirect",      x11_override_redirect,     bool);
+        set!("enable-settings-window",     enable_settings_window,    bool);
+        set!("ui-scale",                   ui_scale,                  f32);
+        set!("daemon-mode",                daemon_mode,               bool);
+        set!("run-autostart",              run_autostart,             bool);
+        set!("wallpaper-restore",          wallpaper_restore,         bool);
+        set!("enable-dbus-interface",      enable_dbus_interface,     bool);
+        set!("enable-notifications",       enable_notifications,      bool);
+        set!("notification-timeout-ms",    notification_timeout_ms,   u64);
+        set!("enable-network",             enable_network,            bool);
+        set!("enable-vpn",                 enable_vpn,                bool);
+        set!("enable-bluetooth",           enable_bluetooth,          bool);
+        set!("enable-drives",              enable_drives,             bool);
+        set!("enable-calculator",          enable_calculator,         bool);
+        set!("enable-conversion",          enable_conversion,         bool);
+        set!("fetch-currency-rates",       fetch_currency_rates,      bool);
+        set!("currency-refresh-hours",     currency_refresh_hours,    u64);
+        set!("enable-weather",             enable_weather,            bool);
+        set!("weather-refresh-minutes",    weather_refresh_minutes,   u64);
+        set!("run-in-terminal",            run_in_terminal,           bool);
+        set!("enable-path-binaries",       enable_path_binaries,      bool);
+        set!("focus-or-launch",            focus_or_launch,           bool);
+        set!("launch-check-ms",            launch_check_ms,           u64);
+        set!("search-debounce-ms",         search_debounce_ms,        u64);
+        set!("show-games-button",          show_games_button,         bool);
+        set!("show-category-chips",        show_category_chips,       bool);
+        set!("enable-clipboard-history",   enable_clipboard_history,  bool);
+        set!("clipboard-history-size",     clipboard_history_size,    usize);
+        set!("enable-web-search",          enable_web_search,         bool);
+        if let Some(val) = scalar("theme") { config.theme = val.trim().to_string(); }
+        if let Some(val) = scalar("dark-theme") { config.dark_theme = val.trim().to_string(); }
+        if let Some(val) = scalar("light-theme") { config.light_theme = val.trim().to_string(); }
+        if let Some(val) = scalar("results-view") { config.results_view = val.trim().to_string(); }
+        if let Some(val) = scalar("window-monitor") { config.window_monitor = val.trim().to_string(); }
+        if let Some(val) = scalar("window-anchor") { config.window_anchor = val.trim().to_string(); }
+        if let Some(val) = scalar("x11-window-type") { config.x11_window_type = val.trim().to_string(); }
+        if let Some(val) = scalar("run-prefix") { config.run_prefix = val; }
+        if let Some(val) = scalar("window-prefix") { config.window_prefix = val; }
+        if let Some(val) = scalar("games-prefix") { config.games_prefix = val; }
+        if let Some(val) = scalar("category-prefix") { config.category_prefix = val; }
+        if let Some(val) = scalar("hidden-prefix") { config.hidden_prefix = val; }
+        if let Some(val) = scalar("terminal-command") { config.terminal_command = val; }
+        if let Some(val) = scalar("icon-theme") { config.icon_theme = val; }
+        if let Some(val) = scalar("clipboard-prefix") { config.clipboard_prefix = val; }
+        if let Some(val) = scalar("clipboard-store") { config.clipboard_store = val; }
+        if let Some(val) = scalar("clipboard-key-file") { config.clipboard_key_file = val; }
+        if let Some(val) = scalar("snippet-prefix") { config.snippet_prefix = val; }
+        if let Some(val) = scalar("snippet-insert") { config.snippet_insert = val.trim().to_string(); }
+        if let Some(val) = scalar("capture-dir") { config.capture_dir = val.trim().to_string(); }
+        if let Some(val) = scalar("recent-prefix") { config.recent_prefix = val; }
+        if let Some(val) = scalar("places-prefix") { config.places_prefix = val; }
+        if let Some(val) = scalar("wallpaper-prefix") { config.wallpaper_prefix = val; }
+        if let Some(val) = scalar("wallpaper-dir") { config.wallpaper_dir = val.trim().to_string(); }
+        if let Some(val) = scalar("wallpaper-setter") { config.wallpaper_setter = val.trim().to_string(); }
+        if let Some(val) = scalar("weather-provider") { config.weather_provider = val.trim().to_string(); }
+        if let Some(val) = scalar("weather-location") { config.weather_location = val.trim().to_string(); }
+        if let Some(val) = scalar("weather-units") { config.weather_units = val.trim().to_string(); }
+        if let Some(val) = scalar("time-format") { config.time_format = val; }
+        if let Some(val) = scalar("timezone") { config.timezone = val.trim().to_string(); }
+        if let Some(val) = scalar("extra-clock-format") { config.extra_clock_format = val; }
+        if let Some(val) = scalar("time-order") {
+            tracing::warn!(target: "config", "time-order is deprecated; put the date in time-format (e.g. \"%H:%M %Y/%m/%d\")");
+            config.time_order = match val.as_str() {
+                "YmdHms" => TimeOrder::YmdHms,
+                "DmyHms" => TimeOrder::DmyHms,
+                _        => TimeOrder::MdyHms,
+            };
+        }
+        for (key, field) in [
+            ("power-commands",    &mut config.power_commands),
+            ("restart-commands",  &mut config.restart_commands),
+            ("logout-commands",   &mut config.logout_commands),
+            ("suspend-commands",  &mut config.suspend_commands),
+            ("terminal-commands", &mut config.terminal_commands),
+            ("search-providers",  &mut config.search_providers),
+            ("search-engines",    &mut config.search_engines),
+            ("extra-clocks",      &mut config.extra_clocks),
+            ("timer-presets",     &mut config.timer_presets),
+            ("quick-actions",     &mut config.quick_actions),
+            ("wg-quick-configs",  &mut config.wg_quick_configs),
+            ("hidden-apps",       &mut config.hidden_apps),
+            ("prefixes",          &mut config.prefixes),
+            ("key-next",           &mut config.key_next),
+            ("key-previous",       &mut config.key_previous),
+            ("key-clear",          &mut config.key_clear),
+            ("key-actions",        &mut config.key_actions),
+            ("key-launch-options", &mut config.key_launch_options),
+            ("key-zoom-in",        &mut config.key_zoom_in),
+            ("key-zoom-out",       &mut config.key_zoom_out),
+            ("key-zoom-reset",     &mut config.key_zoom_reset),
+            ("key-launch-nth",     &mut config.key_launch_nth),
+        ] {
+            if let Some(val) = values.get(key) { *field = val.list(); }
         }
+        config
     }
 }
 
+/// Formats with `time-format`; a time-only format (from before it took
+/// dates) still gets the date appended in the deprecated `time-order`.
 pub fn format_datetime(t: &LocalTime, config: &Config) -> String {
+    let time_str = crate::strftime::format(t, &config.time_format);
+    if crate::strftime::has_date(&config.time_format) {
+        return time_str;
+    }
     let date_str = match config.time_order {
         TimeOrder::MdyHms => format!("{:02}/{:02}/{}", t.month, t.day, t.year),
         TimeOrder::YmdHms => format!("{}/{:02}/{:02}", t.year, t.month, t.day),
         TimeOrder::DmyHms => format!("{:02}/{:02}/{}", t.day, t.month, t.year),
     };
-    let time_str = format_time_fields(t.hour, t.min, t.sec, &config.time_format);
     format!("{} {}", time_str, date_str)
 }
 
-fn format_time_fields(hour: u8, min: u8, sec: u8, fmt: &str) -> String {
-    fmt
-        .replace("%I", &format!("{:02}", if hour % 12 == 0 { 12 } else { hour % 12 }))
-        .replace("%H", &format!("{:02}", hour))
-        .replace("%M", &format!("{:02}", min))
-        .replace("%S", &format!("{:02}", sec))
-        .replace("%p", if hour < 12 { "AM" } else { "PM" })
-        .replace("%P", if hour < 12 { "am" } else { "pm" })
-}
-
 // ============================================================================
 // Theme
 // ============================================================================
@@ -341,6 +1131,15 @@ fn strip_comments(css: &str) -> String {
     out
 }
 
+/// `12px`, `12` or `50%` of `basis`.
+fn parse_len(s: &str, basis: f32) -> Option<f32> {
+    let s = s.trim();
+    match s.strip_suffix('%') {
+        Some(pct) => Some(pct.trim().parse::<f32>().ok()? / 100.0 * basis),
+        None      => s.trim_end_matches("px").trim().parse().ok(),
+    }
+}
+
 /// Normalise property names — accept common aliases so old themes written
 /// with `x`/`y`, `text-color`, `hover-*` prefixes still work.
 fn normalize_prop(key: &str) -> &str {
@@ -352,10 +1151,22 @@ fn normalize_prop(key: &str) -> &str {
         "text-color"             => "color",
         "hover-text-color"       => "color-hover",
         "hover-background-color" => "background-color-hover",
+        "flex-grow"              => "grow",
+        "align-self"             => "align",
         other                    => other,
     }
 }
 
+/// Normalise section class names — `.tray` is accepted as a shorter spelling
+/// of the `.tray-icon` section (including its `:hover` block).
+fn normalize_class(class: &str) -> String {
+    match class.split_once(':') {
+        Some(("tray", pseudo)) => format!("tray-icon:{pseudo}"),
+        None if class == "tray" => "tray-icon".to_string(),
+        _ => class.to_string(),
+    }
+}
+
 /// Strip surrounding quotes / `url(...)` wrapper from a raw CSS value.
 fn clean_value(s: &str) -> String {
     let s = s.trim();
@@ -368,7 +1179,8 @@ fn clean_value(s: &str) -> String {
     s.to_string()
 }
 
-/// Replace every `var(--name)` in `val` with the corresponding entry from `vars`.
+/// Replace every `var(--name)` / `var(--name, fallback)` in `val` with the
+/// corresponding entry from `vars`, or the fallback when it's undefined.
 /// Handles up to 8 levels of chaining (e.g. `--a: var(--b); --b: red`).
 fn resolve_vars(val: &str, vars: &HashMap<String, String>) -> String {
     if !val.contains("var(") { return val.to_string(); }
@@ -380,12 +1192,16 @@ fn resolve_vars(val: &str, vars: &HashMap<String, String>) -> String {
         while let Some(start) = rest.find("var(--") {
             next.push_str(&rest[..start]);
             let after = start + 4; // skip "var("
-            match rest[after..].find(')') {
+            match closing_paren(&rest[after..]) {
                 Some(end) => {
-                    let name = &rest[after..after + end];
-                    match vars.get(name) {
-                        Some(v) => next.push_str(v),
-                        None    => next.push_str(&rest[start..after + end + 1]),
+                    let (name, fallback) = match rest[after..after + end].split_once(',') {
+                        Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+                        None                   => (rest[after..after + end].trim(), None),
+                    };
+                    match (vars.get(name), fallback) {
+                        (Some(v), _)     => next.push_str(v),
+                        (None, Some(fb)) => next.push_str(fb),
+                        (None, None)     => next.push_str(&rest[start..after + end + 1]),
                     }
                     rest = &rest[after + end + 1..];
                 }
@@ -399,6 +1215,140 @@ fn resolve_vars(val: &str, vars: &HashMap<String, String>) -> String {
     result
 }
 
+/// Index of the `)` closing an already-opened paren in `s`.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _   => {}
+        }
+    }
+    None
+}
+
+/// `name(a, b(c, d))` → `("name", ["a", "b(c, d)"])`, splitting only on
+/// top-level commas so color arguments can themselves be functions.
+fn css_function(s: &str) -> Option<(&str, Vec<&str>)> {
+    let open  = s.find('(')?;
+    let close = open + 1 + closing_paren(&s[open + 1..])?;
+    if close != s.len() - 1 { return None; }
+    let inner = &s[open + 1..close];
+    let mut args  = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => { args.push(inner[start..i].trim()); start = i + 1; }
+            _   => {}
+        }
+    }
+    args.push(inner[start..].trim());
+    Some((s[..open].trim(), args))
+}
+
+/// CSS color value: `transparent`, names, `#rgb[a]`/`#rrggbb[aa]`, `rgb[a]()`,
+/// `hsl[a]()`, plus the palette helpers `rgba(<color>, a)`, `darken(<color>, 10%)`,
+/// `lighten(<color>, 10%)` and `mix(<color>, <color>, 30%)`. Expects lowercase.
+fn parse_css_color(s: &str) -> Option<eframe::egui::Color32> {
+    use eframe::egui::Color32;
+    let s = s.trim();
+    if s == "transparent" { return Some(Color32::TRANSPARENT); }
+
+    if let Some((name, mut args)) = css_function(s) {
+        // Space-separated syntax: rgb(110 90 220 / 50%), hsl(250deg 65% 60%).
+        if args.len() == 1 && args[0].contains(' ') {
+            args = args[0].split([' ', '/']).filter(|a| !a.is_empty()).collect();
+        }
+        let alpha = |a: &str| -> Option<f32> {
+            match a.strip_suffix('%') {
+                Some(pct) => Some(pct.trim().parse::<f32>().ok()? / 100.0),
+                None      => a.parse().ok(),
+            }
+        };
+        let rgba = |r: u8, g: u8, b: u8, a: f32| Color32::from_rgba_unmultiplied(r, g, b, (a.clamp(0.0, 1.0) * 255.0).round() as u8);
+        return match (name, args.as_slice()) {
+            // rgba(var(--accent), 0.5): same color, new opacity.
+            ("rgb" | "rgba", [color, a]) => {
+                let [r, g, b, _] = parse_css_color(color)?.to_srgba_unmultiplied();
+                Some(rgba(r, g, b, alpha(a)?))
+            }
+            ("rgb" | "rgba", [r, g, b, rest @ ..]) if rest.len() <= 1 => {
+                // 0..255, or 0%..100%
+                let channel = |c: &str| -> Option<u8> {
+                    let v = match c.strip_suffix('%') {
+                        Some(pct) => pct.parse::<f32>().ok()? * 2.55,
+                        None      => c.parse::<f32>().ok()?,
+                    };
+                    Some(v.clamp(0.0, 255.0).round() as u8)
+                };
+                let a = match rest { [a] => alpha(a)?, _ => 1.0 };
+                Some(rgba(channel(r)?, channel(g)?, channel(b)?, a))
+            }
+            ("hsl" | "hsla", [h, sat, l, rest @ ..]) if rest.len() <= 1 => {
+                let h = if let Some(turn) = h.strip_suffix("turn") { turn.parse::<f32>().ok()? * 360.0 }
+                        else if let Some(rad) = h.strip_suffix("rad") { rad.parse::<f32>().ok()?.to_degrees() }
+                        else { h.trim_end_matches("deg").parse::<f32>().ok()? };
+                let h   = h.rem_euclid(360.0) / 360.0;
+                let sat = sat.trim_end_matches('%').parse::<f32>().ok()? / 100.0;
+                let l   = l.trim_end_matches('%').parse::<f32>().ok()? / 100.0;
+                let a   = match rest { [a] => alpha(a)?, _ => 1.0 };
+                let (r, g, b) = hsl_to_rgb(h, sat, l);
+                Some(rgba(r, g, b, a))
+            }
+            ("darken" | "lighten", [color, amount]) => {
+                let [r, g, b, a] = parse_css_color(color)?.to_srgba_unmultiplied();
+                let delta = alpha(amount)? * if name == "darken" { -1.0 } else { 1.0 };
+                let (h, sat, l) = rgb_to_hsl(r, g, b);
+                let (r, g, b) = hsl_to_rgb(h, sat, (l + delta).clamp(0.0, 1.0));
+                Some(Color32::from_rgba_unmultiplied(r, g, b, a))
+            }
+            // mix(a, b, w): w of `a` (default half) blended with `b`.
+            ("mix", [x, y, rest @ ..]) if rest.len() <= 1 => {
+                let w = match rest { [w] => alpha(w)?.clamp(0.0, 1.0), _ => 0.5 };
+                let x = parse_css_color(x)?.to_srgba_unmultiplied();
+                let y = parse_css_color(y)?.to_srgba_unmultiplied();
+                let c = |i: usize| (x[i] as f32 * w + y[i] as f32 * (1.0 - w)).round() as u8;
+                Some(Color32::from_rgba_unmultiplied(c(0), c(1), c(2), c(3)))
+            }
+            _ => None,
+        };
+    }
+
+    // #hex (3, 4, 6, 8 digit)
+    if let Some(h) = s.strip_prefix('#') {
+        let x = |i: usize, n: usize| u8::from_str_radix(&h.get(i..i + n)?.repeat(3 - n), 16).ok();
+        return match h.len() {
+            3 => Some(Color32::from_rgb(x(0, 1)?, x(1, 1)?, x(2, 1)?)),
+            4 => Some(Color32::from_rgba_unmultiplied(x(0, 1)?, x(1, 1)?, x(2, 1)?, x(3, 1)?)),
+            6 => Some(Color32::from_rgb(x(0, 2)?, x(2, 2)?, x(4, 2)?)),
+            8 => Some(Color32::from_rgba_unmultiplied(x(0, 2)?, x(2, 2)?, x(4, 2)?, x(6, 2)?)),
+            _ => None,
+        };
+    }
+
+    css_named_color(s)
+}
+
+/// (r, g, b) bytes → HSL, all components 0..1.
+fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l   = (max + min) / 2.0;
+    if max == min { return (0.0, 0.0, l); }
+    let d = max - min;
+    let s = if l > 0.5 { d / (2.0 - max - min) } else { d / (max + min) };
+    let h = if max == r { (g - b) / d + if g < b { 6.0 } else { 0.0 } }
+            else if max == g { (b - r) / d + 2.0 }
+            else { (r - g) / d + 4.0 };
+    (h / 6.0, s, l)
+}
+
 /// HSL (all components 0..1) → (r, g, b) bytes.
 fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
     fn hue(p: f32, q: f32, mut t: f32) -> f32 {
@@ -418,33 +1368,61 @@ fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
     )
 }
 
-/// CSS named colours (UI-relevant subset).
+/// All 148 CSS named colours, sorted for binary search.
+const CSS_NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff), ("antiquewhite", 0xfaebd7), ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4), ("azure", 0xf0ffff), ("beige", 0xf5f5dc), ("bisque", 0xffe4c4),
+    ("black", 0x000000), ("blanchedalmond", 0xffebcd), ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2), ("brown", 0xa52a2a), ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0), ("chartreuse", 0x7fff00), ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50), ("cornflowerblue", 0x6495ed), ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c), ("cyan", 0x00ffff), ("darkblue", 0x00008b), ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b), ("darkgray", 0xa9a9a9), ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9), ("darkkhaki", 0xbdb76b), ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f), ("darkorange", 0xff8c00), ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000), ("darksalmon", 0xe9967a), ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b), ("darkslategray", 0x2f4f4f), ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1), ("darkviolet", 0x9400d3), ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff), ("dimgray", 0x696969), ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff), ("firebrick", 0xb22222), ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22), ("fuchsia", 0xff00ff), ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff), ("gold", 0xffd700), ("goldenrod", 0xdaa520), ("gray", 0x808080),
+    ("green", 0x008000), ("greenyellow", 0xadff2f), ("grey", 0x808080), ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4), ("indianred", 0xcd5c5c), ("indigo", 0x4b0082), ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c), ("lavender", 0xe6e6fa), ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00), ("lemonchiffon", 0xfffacd), ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080), ("lightcyan", 0xe0ffff), ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3), ("lightgreen", 0x90ee90), ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1), ("lightsalmon", 0xffa07a), ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa), ("lightslategray", 0x778899), ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de), ("lightyellow", 0xffffe0), ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32), ("linen", 0xfaf0e6), ("magenta", 0xff00ff), ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa), ("mediumblue", 0x0000cd), ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db), ("mediumseagreen", 0x3cb371), ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a), ("mediumturquoise", 0x48d1cc), ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xf5fffa), ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5), ("navajowhite", 0xffdead), ("navy", 0x000080), ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000), ("olivedrab", 0x6b8e23), ("orange", 0xffa500), ("orangered", 0xff4500),
+    ("orchid", 0xda70d6), ("palegoldenrod", 0xeee8aa), ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee), ("palevioletred", 0xdb7093), ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9), ("peru", 0xcd853f), ("pink", 0xffc0cb), ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6), ("purple", 0x800080), ("rebeccapurple", 0x663399), ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f), ("royalblue", 0x4169e1), ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072), ("sandybrown", 0xf4a460), ("seagreen", 0x2e8b57), ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d), ("silver", 0xc0c0c0), ("skyblue", 0x87ceeb), ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f), ("steelblue", 0x4682b4), ("tan", 0xd2b48c), ("teal", 0x008080),
+    ("thistle", 0xd8bfd8), ("tomato", 0xff6347), ("turquoise", 0x40e0d0), ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3), ("white", 0xffffff), ("whitesmoke", 0xf5f5f5), ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// CSS named colour (`slategray`, `rebeccapurple`, …) or `transparent`.
 fn css_named_color(s: &str) -> Option<eframe::egui::Color32> {
-    use eframe::egui::Color32;
-    Some(match s {
-        "black"                   => Color32::BLACK,
-        "white"                   => Color32::WHITE,
-        "transparent"             => Color32::TRANSPARENT,
-        "red"                     => Color32::from_rgb(255,   0,   0),
-        "green"                   => Color32::from_rgb(  0, 128,   0),
-        "lime"                    => Color32::from_rgb(  0, 255,   0),
-        "blue"                    => Color32::from_rgb(  0,   0, 255),
-        "yellow"                  => Color32::from_rgb(255, 255,   0),
-        "cyan"  | "aqua"          => Color32::from_rgb(  0, 255, 255),
-        "magenta" | "fuchsia"     => Color32::from_rgb(255,   0, 255),
-        "orange"                  => Color32::from_rgb(255, 165,   0),
-        "purple"                  => Color32::from_rgb(128,   0, 128),
-        "pink"                    => Color32::from_rgb(255, 192, 203),
-        "gray"  | "grey"          => Color32::from_rgb(128, 128, 128),
-        "darkgray"  | "darkgrey"  => Color32::from_rgb(169, 169, 169),
-        "lightgray" | "lightgrey" => Color32::from_rgb(211, 211, 211),
-        "silver"                  => Color32::from_rgb(192, 192, 192),
-        "navy"                    => Color32::from_rgb(  0,   0, 128),
-        "teal"                    => Color32::from_rgb(  0, 128, 128),
-        "maroon"                  => Color32::from_rgb(128,   0,   0),
-        "olive"                   => Color32::from_rgb(128, 128,   0),
-        _                         => return None,
-    })
+    if s == "transparent" { return Some(eframe::egui::Color32::TRANSPARENT); }
+    let i = CSS_NAMED_COLORS.binary_search_by_key(&s, |&(name, _)| name).ok()?;
+    let [_, r, g, b] = CSS_NAMED_COLORS[i].1.to_be_bytes();
+    Some(eframe::egui::Color32::from_rgb(r, g, b))
 }
 
 #[derive(Clone)]
@@ -453,19 +1431,51 @@ pub struct Theme {
 }
 
 impl Theme {
-    pub fn load_or_create() -> Theme {
-        match Self::try_load() {
+    /// theme.css with `themes/<profile>.css` layered over it; `""` is theme.css alone.
+    pub fn load(profile: &str) -> Theme {
+        match Self::try_load(profile) {
             Ok(t)  => t,
-            Err(e) => { eprintln!("Failed to load theme: {}", e); Self::parse_css(DEFAULT_THEME) }
+            Err(e) => { tracing::warn!(target: "theme", "Failed to load theme: {}", e); Self::parse_css(DEFAULT_THEME) }
         }
     }
 
-    fn try_load() -> Result<Theme, Box<dyn Error>> {
+    fn try_load(profile: &str) -> Result<Theme, Box<dyn Error>> {
         let path = crate::paths::place_config_file("tusk-launcher/theme.css")?;
         if !path.exists() {
             OpenOptions::new().write(true).create(true).open(&path)?.write_all(DEFAULT_THEME.as_bytes())?;
         }
-        Ok(Self::parse_css(&read_to_string(&path)?))
+        let mut css = read_to_string(&path)?;
+        if !profile.is_empty() {
+            // Later rules and :root variables win, so appending is enough.
+            let profile_path = Self::profiles_dir().join(format!("{profile}.css"));
+            match read_to_string(&profile_path) {
+                Ok(extra) => { css.push('\n'); css.push_str(&extra); }
+                Err(e)    => tracing::warn!(target: "theme", "Theme {:?} unavailable ({}): {}", profile, profile_path.display(), e),
+            }
+        }
+        Ok(Self::parse_css(&css))
+    }
+
+    /// `themes/`, filled with the bundled presets the first time it's missing.
+    pub fn profiles_dir() -> PathBuf {
+        let dir = crate::paths::config_home().join("tusk-launcher/themes");
+        if !dir.exists() {
+            let written = std::fs::create_dir_all(&dir).and_then(|_| {
+                PRESET_THEMES.iter().try_for_each(|(name, css)| std::fs::write(dir.join(format!("{name}.css")), css))
+            });
+            if let Err(e) = written { tracing::warn!(target: "theme", "Failed to write preset themes to {}: {}", dir.display(), e); }
+        }
+        dir
+    }
+
+    /// Names of the profiles in `themes/`, sorted.
+    pub fn profiles() -> Vec<String> {
+        let dir = Self::profiles_dir();
+        let mut names: Vec<String> = std::fs::read_dir(&dir).into_iter().flatten().flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".css").map(str::to_string))
+            .collect();
+        names.sort();
+        names
     }
 
     fn parse_css(css: &str) -> Theme {
@@ -508,7 +1518,7 @@ impl Theme {
             if selector == ":root" || selector.starts_with('@') { continue; }
 
             // Strip leading '.' → ".app-button:hover" becomes "app-button:hover"
-            let class = selector.trim_start_matches('.').trim().to_string();
+            let class = normalize_class(selector.trim_start_matches('.').trim());
             if class.is_empty() { continue; }
 
             let entry = styles.entry(class).or_default();
@@ -532,68 +1542,7 @@ impl Theme {
     }
 
     fn parse_color(&self, s: &str) -> Option<eframe::egui::Color32> {
-        use eframe::egui::Color32;
-        let s = s.trim().to_lowercase();
-        if s == "transparent" { return Some(Color32::TRANSPARENT); }
-
-        // rgb() / rgba()
-        if s.starts_with("rgba(") || s.starts_with("rgb(") {
-            let is_rgba = s.starts_with("rgba(");
-            let inner   = s.strip_prefix(if is_rgba {"rgba("} else {"rgb("})?.strip_suffix(')')?;
-            let p: Vec<&str> = inner.split(',').map(str::trim).collect();
-            if p.len() < 3 { return None; }
-            let r: u8  = p[0].parse().ok()?;
-            let g: u8  = p[1].parse().ok()?;
-            let b: u8  = p[2].parse().ok()?;
-            let a: f32 = if is_rgba && p.len() == 4 { p[3].parse().ok()? } else { 1.0 };
-            return Some(Color32::from_rgba_unmultiplied(r, g, b, (a*255.0) as u8));
-        }
-
-        // hsl() / hsla()
-        if s.starts_with("hsla(") || s.starts_with("hsl(") {
-            let is_hsla = s.starts_with("hsla(");
-            let inner   = s.strip_prefix(if is_hsla {"hsla("} else {"hsl("})?.strip_suffix(')')?;
-            let p: Vec<&str> = inner.split(',').map(str::trim).collect();
-            if p.len() < 3 { return None; }
-            let h = p[0].trim_end_matches("deg").parse::<f32>().ok()? / 360.0;
-            let s = p[1].trim_end_matches('%').parse::<f32>().ok()? / 100.0;
-            let l = p[2].trim_end_matches('%').parse::<f32>().ok()? / 100.0;
-            let a: f32 = if is_hsla && p.len() == 4 { p[3].parse().ok()? } else { 1.0 };
-            let (r, g, b) = hsl_to_rgb(h, s, l);
-            return Some(Color32::from_rgba_unmultiplied(r, g, b, (a*255.0) as u8));
-        }
-
-        // #hex (3, 4, 6, 8 digit)
-        if s.starts_with('#') {
-            let h = s.trim_start_matches('#');
-            return match h.len() {
-                3 => Some(Color32::from_rgb(
-                    u8::from_str_radix(&h[0..1].repeat(2), 16).ok()?,
-                    u8::from_str_radix(&h[1..2].repeat(2), 16).ok()?,
-                    u8::from_str_radix(&h[2..3].repeat(2), 16).ok()?,
-                )),
-                4 => Some(Color32::from_rgba_unmultiplied(
-                    u8::from_str_radix(&h[0..1].repeat(2), 16).ok()?,
-                    u8::from_str_radix(&h[1..2].repeat(2), 16).ok()?,
-                    u8::from_str_radix(&h[2..3].repeat(2), 16).ok()?,
-                    u8::from_str_radix(&h[3..4].repeat(2), 16).ok()?,
-                )),
-                6 => Some(Color32::from_rgb(
-                    u8::from_str_radix(&h[0..2], 16).ok()?,
-                    u8::from_str_radix(&h[2..4], 16).ok()?,
-                    u8::from_str_radix(&h[4..6], 16).ok()?,
-                )),
-                8 => Some(Color32::from_rgba_unmultiplied(
-                    u8::from_str_radix(&h[0..2], 16).ok()?,
-                    u8::from_str_radix(&h[2..4], 16).ok()?,
-                    u8::from_str_radix(&h[4..6], 16).ok()?,
-                    u8::from_str_radix(&h[6..8], 16).ok()?,
-                )),
-                _ => None,
-            };
-        }
-
-        css_named_color(&s)
+        parse_css_color(&s.to_lowercase())
     }
 
     fn get_px(&self, class: &str, prop: &str) -> Option<f32> {
@@ -604,50 +1553,38 @@ impl Theme {
         self.get(sec, "order").and_then(|s| s.parse().ok()).unwrap_or(0)
     }
 
-    fn get_position(&self, class: &str) -> Option<(f32, f32)> {
+    /// A length in px, or `%` of `basis` (the main window's width or height).
+    fn get_len(&self, class: &str, prop: &str, basis: f32) -> Option<f32> {
+        parse_len(&self.get(class, prop)?, basis)
+    }
+
+    /// Anchor point of `class` in a `win`-sized window and the corner of the
+    /// section pinned to it: `left`/`top` pin the near edge, `right`/`bottom`
+    /// the far one, and `center` the middle. Both axes must be given.
+    fn get_position(&self, class: &str, win: eframe::egui::Vec2) -> Option<((f32, f32), eframe::egui::Align2)> {
+        use eframe::egui::Align;
         // normalize_prop maps x→left and y→top at parse time.
-        Some((self.get_px(class, "left")?, self.get_px(class, "top")?))
+        let axis = |near: &str, far: &str, span: f32| -> Option<(f32, Align)> {
+            match (self.get(class, near), self.get(class, far)) {
+                (Some(v), _) if v == "center" => Some((span / 2.0, Align::Center)),
+                (Some(v), _)                  => Some((parse_len(&v, span)?, Align::Min)),
+                (None, Some(v))               => Some((span - parse_len(&v, span)?, Align::Max)),
+                (None, None)                  => None,
+            }
+        };
+        let (x, align_x) = axis("left", "right", win.x)?;
+        let (y, align_y) = axis("top", "bottom", win.y)?;
+        Some(((x, y), eframe::egui::Align2([align_x, align_y])))
     }
 
-    pub fn get_config(&self) -> Config {
-        let mut config = Config::default();
-        if let Some(props) = self.styles.get("config") {
-            macro_rules! set {
-                ($key:expr, $field:ident, $typ:ty) => {
-                    if let Some(val) = props.get($key) {
-                        if let Ok(parsed) = val.parse::<$typ>() { config.$field = parsed; }
-                    }
-                };
-            }
-            set!("enable-recent-apps",        enable_recent_apps,        bool);
-            set!("max-search-results",         max_search_results,        usize);
-            set!("enable-power-options",       enable_power_options,      bool);
-            set!("show-time",                  show_time,                 bool);
-            set!("enable-audio-control",       enable_audio_control,      bool);
-            set!("max-volume",                 max_volume,                f32);
-            set!("volume-update-interval-ms",  volume_update_interval_ms, u64);
-            set!("enable-icons",               enable_icons,              bool);
-            set!("show-settings-button",       show_settings_button,      bool);
-            set!("enable-system-tray",         enable_system_tray,        bool);
-            if let Some(val) = props.get("time-format") { config.time_format = val.clone(); }
-            if let Some(val) = props.get("time-order") {
-                config.time_order = match val.as_str() {
-                    "YmdHms" => TimeOrder::YmdHms,
-                    "DmyHms" => TimeOrder::DmyHms,
-                    _        => TimeOrder::MdyHms,
-                };
-            }
-            for (key, field) in [
-                ("power-commands",   &mut config.power_commands),
-                ("restart-commands", &mut config.restart_commands),
-                ("logout-commands",  &mut config.logout_commands),
-            ] {
-                if let Some(val) = props.get(key) {
-                    *field = val.split(',').map(|s| s.trim().to_string()).collect();
-                }
-            }
-        }
-        config
+    /// How many selectors have rules; for `--profile-startup`.
+    pub fn selectors(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// The legacy `.config` block, now only read to migrate it to config.toml.
+    pub fn config_block(&self) -> Option<&HashMap<String, String>> {
+        self.styles.get("config")
     }
 
     fn get_frame_props(&self, class: &str, default: eframe::egui::Color32)
@@ -666,7 +1603,68 @@ impl Theme {
         (base, hover, round)
     }
 
+    /// `border-width` + `border-color`; the `:hover` block's color wins while hovered.
+    pub fn get_border(&self, class: &str, hovered: bool) -> Option<eframe::egui::Stroke> {
+        let width = self.get_px(class, "border-width").filter(|w| *w > 0.0)?;
+        let color = hovered.then(|| self.get(&format!("{}:hover", class), "border-color")).flatten()
+                        .or_else(|| self.get(class, "border-color"))
+                        .and_then(|s| self.parse_color(&s))?;
+        Some(eframe::egui::Stroke::new(width, color))
+    }
+
+    /// `box-shadow: <x> <y> [<blur> [<spread>]] <color>` (one shadow, no `inset`);
+    /// the `:hover` block's shadow wins while hovered.
+    pub fn get_shadow(&self, class: &str, hovered: bool) -> Option<eframe::egui::Shadow> {
+        let val = hovered.then(|| self.get(&format!("{}:hover", class), "box-shadow")).flatten()
+                      .or_else(|| self.get(class, "box-shadow"))?;
+        let len = |t: &str| t.trim_end_matches("px").parse::<f32>().ok();
+        let tokens: Vec<&str> = val.split_whitespace().collect();
+        // Lengths come first or last; the color is whatever's left.
+        let lead  = tokens.iter().take_while(|t| len(t).is_some()).count();
+        let trail = tokens.iter().rev().take_while(|t| len(t).is_some()).count();
+        let (lengths, color) = if lead >= 2 { tokens.split_at(lead) } else {
+            let (color, lengths) = tokens.split_at(tokens.len() - trail);
+            (lengths, color)
+        };
+        let px: Vec<f32> = lengths.iter().filter_map(|t| len(t)).collect();
+        if !(2..=4).contains(&px.len()) { return None; }
+        Some(eframe::egui::Shadow {
+            offset: [px[0] as i8, px[1] as i8],
+            blur:   px.get(2).map_or(0, |b| b.max(0.0) as u8),
+            spread: px.get(3).map_or(0, |s| s.max(0.0) as u8),
+            color:  self.parse_color(&color.join(" "))?,
+        })
+    }
+
+    /// `font-family` / `font-weight` / `font-style` of `class`, if it sets any.
+    pub fn font_face(&self, class: &str) -> Option<crate::fonts::Face> {
+        let families = self.get(class, "font-family");
+        let weight   = self.get(class, "font-weight");
+        let style    = self.get(class, "font-style");
+        if families.is_none() && weight.is_none() && style.is_none() { return None; }
+        Some(crate::fonts::Face {
+            families: families.unwrap_or_else(|| "sans-serif".into()),
+            weight:   match weight.as_deref() {
+                Some("bold")          => 700,
+                Some("normal") | None => 400,
+                Some(w)               => w.parse::<u16>().map(|w| w.clamp(1, 1000)).unwrap_or(400),
+            },
+            italic:   matches!(style.as_deref(), Some("italic" | "oblique")),
+        })
+    }
+
+    /// Every distinct face the theme's classes ask for, for `fonts::install`.
+    pub fn font_faces(&self) -> Vec<crate::fonts::Face> {
+        let mut faces: Vec<_> = self.styles.keys().filter_map(|class| self.font_face(class)).collect();
+        faces.sort();
+        faces.dedup();
+        faces
+    }
+
     pub fn apply_style(&self, ui: &mut eframe::egui::Ui, class: &str) {
+        // Right after a reload the new faces aren't bound until the next frame.
+        let family = self.font_face(class).map(|f| f.family())
+            .filter(|family| ui.fonts(|f| f.definitions().families.contains_key(family)));
         let style = ui.style_mut();
         if let Some(bg) = self.get(class, "background-color").and_then(|s| self.parse_color(&s)) {
             if bg != eframe::egui::Color32::TRANSPARENT { style.visuals.panel_fill = bg; }
@@ -688,6 +1686,11 @@ impl Theme {
         if let Some(sz) = self.get_px(class, "font-size") {
             if let Some(text) = style.text_styles.get_mut(&eframe::egui::TextStyle::Body) { text.size = sz; }
         }
+        if let Some(family) = family {
+            for (text_style, font) in style.text_styles.iter_mut() {
+                if *text_style != eframe::egui::TextStyle::Monospace { font.family = family.clone(); }
+            }
+        }
     }
 
     pub fn apply_widget_style(&self, style: &mut eframe::egui::Style, class: &str) {
@@ -770,12 +1773,17 @@ fn custom_button_scroll(
     min_width: Option<f32>,
     scroll_offset: Option<f32>,
 ) -> eframe::egui::Response {
+    let name      = label;
+    let label     = crate::bidi::visual(label);
+    let label     = label.as_ref();
+    let rtl       = ui.layout().main_dir() == eframe::egui::Direction::RightToLeft;
     let font_id   = ui.style().text_styles.get(&eframe::egui::TextStyle::Button).cloned().unwrap_or_default();
     let pad       = ui.spacing().button_padding;
     let full_size = ui.painter().layout_no_wrap(label.to_owned(), font_id.clone(), eframe::egui::Color32::WHITE).size();
     let w         = min_width.unwrap_or(full_size.x + pad.x * 2.0);
     let h         = full_size.y + pad.y * 2.0;
     let (rect, resp) = ui.allocate_exact_size(eframe::egui::vec2(w, h), eframe::egui::Sense::click_and_drag());
+    describe(&resp, eframe::egui::WidgetType::Button, name);
 
     if ui.is_rect_visible(rect) {
         let (base, hover_opt, round) = theme.get_frame_props(class, ui.style().visuals.widgets.inactive.bg_fill);
@@ -784,8 +1792,9 @@ fn custom_button_scroll(
                             .or_else(|| theme.get(class, "color-hover"))
                             .and_then(|s| theme.parse_color(&s))
                             .unwrap_or(normal_tc);
-        let bg = if resp.hovered() { hover_opt.unwrap_or(base) } else { base };
-        let tc = if resp.hovered() { hover_tc } else { normal_tc };
+        let t  = hover_progress(ui, &resp, class, theme);
+        let bg = base.lerp_to_gamma(hover_opt.unwrap_or(base), t);
+        let tc = normal_tc.lerp_to_gamma(hover_tc, t);
 
         let avail_text_w = (w - pad.x * 2.0).max(0.0);
 
@@ -798,11 +1807,17 @@ fn custom_button_scroll(
                 ui.painter().layout_no_wrap(display, font_id.clone(), eframe::egui::Color32::WHITE).size().x
             }
         };
-        let bg_rect = eframe::egui::Rect::from_min_size(
-            rect.min,
-            eframe::egui::vec2((bg_text_w + pad.x * 2.0).min(w), h),
-        );
+        // Right-to-left rows hug the right edge instead.
+        let bg_w    = (bg_text_w + pad.x * 2.0).min(w);
+        let bg_min  = if rtl { eframe::egui::pos2(rect.max.x - bg_w, rect.min.y) } else { rect.min };
+        let bg_rect = eframe::egui::Rect::from_min_size(bg_min, eframe::egui::vec2(bg_w, h));
+        if let Some(shadow) = theme.get_shadow(class, resp.hovered()) {
+            ui.painter().add(shadow.as_shape(bg_rect, round));
+        }
         ui.painter().rect_filled(bg_rect, round, bg);
+        if let Some(border) = theme.get_border(class, resp.hovered()) {
+            ui.painter().rect_stroke(bg_rect, round, border, eframe::egui::StrokeKind::Inside);
+        }
 
         match scroll_offset {
             Some(offset) => {
@@ -818,17 +1833,81 @@ fn custom_button_scroll(
             }
             None => {
                 let display = truncate_text(ui, label, &font_id, avail_text_w);
-                ui.painter().text(
-                    eframe::egui::pos2(rect.min.x + pad.x, rect.center().y),
-                    eframe::egui::Align2::LEFT_CENTER,
-                    &display, font_id, tc,
-                );
+                let (x, align) = if rtl {
+                    (rect.max.x - pad.x, eframe::egui::Align2::RIGHT_CENTER)
+                } else {
+                    (rect.min.x + pad.x, eframe::egui::Align2::LEFT_CENTER)
+                };
+                ui.painter().text(eframe::egui::pos2(x, rect.center().y), align, &display, font_id, tc);
             }
         }
     }
     resp
 }
 
+/// Name a hand-painted widget for screen readers; egui only describes the
+/// widgets it draws itself.
+fn describe(resp: &eframe::egui::Response, kind: eframe::egui::WidgetType, label: &str) {
+    resp.widget_info(|| eframe::egui::WidgetInfo::labeled(kind, resp.enabled(), label));
+}
+
+/// `on_hover_text` for a button whose face is an icon: the hint is also the
+/// name screen readers announce, instead of the glyph.
+fn icon_hint(resp: eframe::egui::Response, hint: &str) -> eframe::egui::Response {
+    describe(&resp, eframe::egui::WidgetType::Button, hint);
+    resp.on_hover_text(hint)
+}
+
+/// The mode a query prefix selected, as a pill before the search text
+/// (`.mode-badge`).
+fn render_mode_badge(ui: &mut eframe::egui::Ui, theme: &Theme, mode: &str) {
+    use eframe::egui;
+    let (bg, _, round) = theme.get_frame_props("mode-badge", egui::Color32::from_rgb(110, 90, 220));
+    let color = theme.get_text_color("mode-badge", false).unwrap_or(egui::Color32::WHITE);
+    let size  = theme.get_px("mode-badge", "font-size").unwrap_or(10.0);
+    egui::Frame::NONE.fill(bg).corner_radius(round).inner_margin(egui::Margin::symmetric(6, 1)).show(ui, |ui| {
+        ui.label(egui::RichText::new(mode).size(size).color(color));
+    });
+}
+
+/// Tell screen readers a result is `index` of `count`, read out as "3 of 12".
+#[cfg_attr(not(feature = "accesskit"), allow(unused_variables))]
+fn set_position(ctx: &eframe::egui::Context, id: eframe::egui::Id, index: usize, count: usize) {
+    #[cfg(feature = "accesskit")]
+    ctx.accesskit_node_builder(id, |node| {
+        node.set_position_in_set(index + 1);
+        node.set_size_of_set(count);
+    });
+}
+
+/// Key of the theme's hover `Transition` in egui's memory; absent with
+/// `animations = false`.
+const HOVER_TRANSITION: &str = "hover-transition";
+
+/// How far into its hover color `resp` is, 0 to 1. A class's own
+/// `transition-duration` / `transition-easing` win over `.main-window`'s.
+fn hover_progress(ui: &eframe::egui::Ui, resp: &eframe::egui::Response, class: &str, theme: &Theme) -> f32 {
+    let hovered = resp.hovered();
+    let Some(base) = ui.ctx().data(|d| d.get_temp::<crate::animation::Transition>(eframe::egui::Id::new(HOVER_TRANSITION))) else {
+        return if hovered { 1.0 } else { 0.0 };
+    };
+    let duration = theme.get(class, "transition-duration").and_then(|s| crate::animation::parse_duration(&s)).unwrap_or(base.duration);
+    let easing   = theme.get(class, "transition-easing").and_then(|s| crate::animation::Easing::parse(&s)).unwrap_or(base.easing);
+    if duration <= 0.0 { return if hovered { 1.0 } else { 0.0 }; }
+    ui.ctx().animate_bool_with_time_and_easing(resp.id.with("hover"), hovered, duration, easing.func())
+}
+
+/// Publish the theme's hover transition for `hover_progress`, and turn
+/// egui's own animations off along with ours.
+fn apply_motion(ctx: &eframe::egui::Context, motion: &crate::animation::Motion, enabled: bool) {
+    let id = eframe::egui::Id::new(HOVER_TRANSITION);
+    ctx.data_mut(|d| {
+        if enabled { d.insert_temp(id, motion.hover); } else { d.remove::<crate::animation::Transition>(id); }
+    });
+    let time = if enabled { eframe::egui::Style::default().animation_time } else { 0.0 };
+    ctx.all_styles_mut(|style| style.animation_time = time);
+}
+
 fn with_custom_style<R>(
     ui: &mut eframe::egui::Ui,
     f: impl FnOnce(&mut eframe::egui::Style),
@@ -860,11 +1939,79 @@ fn with_alignment<R>(
     }
 }
 
+/// Runs `g` with sliders drawn in `sec`'s background, hover color and radius.
+fn with_slider_style<R>(
+    ui: &mut eframe::egui::Ui,
+    theme: &Theme,
+    sec: &str,
+    g: impl FnOnce(&mut eframe::egui::Ui) -> R,
+) -> R {
+    let (base, hover, round) = theme.get_frame_props(sec, ui.style().visuals.widgets.inactive.bg_fill);
+    let vis = { let mut s = ui.style().visuals.widgets.inactive; s.bg_fill = base; s.corner_radius = round; s };
+    with_custom_style(ui, |s| {
+        s.visuals.widgets.inactive        = vis;
+        s.visuals.widgets.hovered.bg_fill = hover.unwrap_or(base);
+        s.visuals.widgets.hovered.weak_bg_fill = hover.unwrap_or(base);
+        s.visuals.widgets.active          = vis;
+        let t = eframe::egui::Color32::TRANSPARENT;
+        s.visuals.widgets.inactive.bg_stroke = eframe::egui::Stroke::new(0.0, t);
+        s.visuals.widgets.hovered.bg_stroke  = eframe::egui::Stroke::new(0.0, t);
+        s.visuals.widgets.active.bg_stroke   = eframe::egui::Stroke::new(0.0, t);
+        s.visuals.widgets.hovered.expansion  = 0.0;
+        s.visuals.widgets.active.expansion   = 0.0;
+    }, g)
+}
+
+/// Scroll distance of one wheel notch (egui's default `line_scroll_speed`);
+/// wheels report lines, touchpads points.
+const VOLUME_SCROLL_NOTCH: f32 = 40.0;
+
+/// Percent formatting for volume sliders, 1.0 = 100%.
+fn percent_slider(value: &mut f32, max: f32) -> eframe::egui::Slider<'_> {
+    eframe::egui::Slider::new(value, 0.0..=max)
+        .custom_formatter(|n, _| format!("{:.0}%", n * 100.0))
+        .custom_parser(|s| s.trim().trim_end_matches('%').parse::<f64>().ok().map(|n| n / 100.0))
+}
+
+/// Heading an app sorts under in the `show-all-apps` index: its first letter,
+/// uppercased, or `#` for digits and symbols.
+fn index_letter(name: &str) -> char {
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => c.to_uppercase().next().unwrap_or(c),
+        _                            => '#',
+    }
+}
+
+/// Where `action` falls in a row's action menu: launching, other ways to
+/// use the row, or managing it. A separator goes between sections.
+fn action_section(action: &RowAction) -> u8 {
+    match action {
+        RowAction::Launch | RowAction::Provider(_)                                             => 0,
+        RowAction::LaunchInTerminal | RowAction::ShowDesktopFile(_) | RowAction::CopyCommand(_) => 1,
+        _                                                                                       => 2,
+    }
+}
+
 /// Build a ViewportId for a tray menu popup.
 fn tray_menu_vp_id(icon_id: &str) -> eframe::egui::ViewportId {
     eframe::egui::ViewportId::from_hash_of(format!("tray_menu_{icon_id}"))
 }
 
+/// Size of `icon-data` images in tray menu rows.
+const MENU_ICON_SZ: f32 = 16.0;
+
+/// Our own rows at the end of a tray item's menu. DBusMenu ids are never
+/// negative, and -1 already means "closed without a choice".
+const TRAY_PIN_ITEM:  i32 = -2;
+const TRAY_HIDE_ITEM: i32 = -3;
+/// Items without a DBusMenu (and XEmbed icons) draw their own menu on
+/// ContextMenu / a right click.
+const TRAY_APP_MENU_ITEM: i32 = -4;
+
+fn tray_pref_entry(id: i32, label: &str) -> crate::sni::MenuItem {
+    crate::sni::MenuItem { id, label: label.into(), enabled: true, visible: true, ..Default::default() }
+}
+
 // ============================================================================
 // AppInterface
 // ============================================================================
@@ -874,11 +2021,35 @@ pub trait AppInterface {
     fn handle_input(&mut self, input: &str);
     fn should_quit(&self) -> bool;
     fn get_query(&self) -> String;
-    fn get_search_results(&self) -> Vec<String>;
+    /// Rows to show, with labels, icons and actions already resolved; a
+    /// row's `id` is what the other methods take as `app_name`.
+    fn get_search_results(&self) -> Vec<ResultRow>;
     fn get_time(&self) -> String;
     fn launch_app(&mut self, app_name: &str);
-    fn get_icon_path(&self, app_name: &str) -> Option<String>;
-    fn get_formatted_launch_options(&self, app_name: &str) -> String;
+    /// Launches that failed since the last call, to show as error toasts.
+    fn take_launch_failures(&mut self) -> Vec<LaunchFailure>;
+    /// Carry out an entry of the row's action menu; `EditLaunchOptions`
+    /// is the GUI's own.
+    fn run_action(&mut self, app_name: &str, action: &RowAction);
+    /// The results are a game library to show as a cover-art grid.
+    fn is_grid_view(&self) -> bool;
+    /// The mode a query prefix selected ("Run", "Windows", a category, …),
+    /// shown as a badge in the search bar.
+    fn get_mode(&self) -> Option<String>;
+    /// Menu categories with at least one app, for the category chips.
+    fn get_categories(&self) -> Vec<&'static str>;
+    fn get_app_override(&self, app_name: &str) -> AppOverride;
+    fn set_app_override(&mut self, app_name: &str, over: AppOverride);
+    fn get_launch_options(&self, app_name: &str) -> AppLaunchOptions;
+    fn set_launch_options(&mut self, app_name: &str, opts: AppLaunchOptions);
+    /// The command line `opts` would run for `app_name`, for the editor's preview.
+    fn preview_launch_command(&self, app_name: &str, opts: &AppLaunchOptions) -> String;
+    /// Clear the query and quit flag so a hidden daemon reopens fresh.
+    fn reset(&mut self);
+    /// Called from the search thread when new results are ready to show.
+    fn on_results(&self, wake: Box<dyn Fn() + Send + Sync>);
+    /// Settings edited while running (hot reload).
+    fn set_config(&mut self, config: Config);
 }
 
 // ============================================================================
@@ -890,13 +2061,47 @@ enum ElemKind { Settings, Icon, App }
 
 struct BgImage { path: String, size_mode: String, opacity: f32 }
 
-struct SectionInfo { name: &'static str, pos: Option<(f32, f32)>, size: Option<eframe::egui::Vec2> }
+struct SectionInfo {
+    name:  &'static str,
+    pos:   Option<(f32, f32)>,
+    /// Which corner (or middle) of the section sits at `pos`.
+    pivot: eframe::egui::Align2,
+    size:  Option<eframe::egui::Vec2>,
+    /// Set when `.main-window` is `display: flex` and the section isn't `position: absolute`.
+    flow:  Option<FlowItem>,
+}
+
+/// `.main-window { display: flex }`: sections without `position: absolute` are
+/// stacked by `crate::flex` instead of placed with left/top.
+struct FlexLayout {
+    direction: crate::flex::Direction,
+    gap:       f32,
+    padding:   crate::flex::Edges,
+}
+
+#[derive(Clone, Copy)]
+struct FlowItem {
+    /// `height` (column) / `width` (row) from the theme; measured when absent.
+    basis:  Option<f32>,
+    grow:   f32,
+    align:  crate::flex::Align,
+    margin: crate::flex::Edges,
+}
+
+/// A section's name, `pos`, `pivot` and `size`, copied out of `SectionInfo`
+/// for one frame.
+type Placement = (&'static str, Option<(f32, f32)>, eframe::egui::Align2, Option<eframe::egui::Vec2>);
 
 struct LayoutCache {
     win_size:             eframe::egui::Vec2,
     win_bg:               eframe::egui::Color32,
+    /// `.main-window` corner rounding; the corners outside it stay transparent.
+    win_round:            eframe::egui::CornerRadius,
+    /// `backdrop-filter: blur`: ask the compositor to blur behind the window.
+    win_blur:             bool,
     bg_image:             Option<BgImage>,
     sections:             Vec<SectionInfo>,
+    flex:                 Option<FlexLayout>,
     elem_order:           Vec<ElemKind>,
     settings_w:           f32,
     settings_h:           f32,
@@ -905,21 +2110,46 @@ struct LayoutCache {
     icon_w:               f32,
     icon_h:               f32,
     vol_gap:              Option<f32>,
+    vol_muted_color:      Option<eframe::egui::Color32>,
+    mic_gap:              Option<f32>,
+    idle_active_color:    Option<eframe::egui::Color32>,
+    mic_muted_color:      Option<eframe::egui::Color32>,
+    timer_gap:            Option<f32>,
+    timer_done_color:     Option<eframe::egui::Color32>,
+    recording_color:      eframe::egui::Color32,
+    workspace_gap:        Option<f32>,
+    vpn_active_color:     Option<eframe::egui::Color32>,
+    workspace_empty_color: Option<eframe::egui::Color32>,
+    workspace_urgent_color: eframe::egui::Color32,
+    weather_icon_size:    f32,
+    weather_gap:          Option<f32>,
+    weather_stale_color:  Option<eframe::egui::Color32>,
     env_w:                f32,
     env_h:                f32,
     tray_w:               f32,
     tray_h:               f32,
     tray_indicator_color: eframe::egui::Color32,
+    tile_w:               f32,
+    tile_h:               f32,
+    wallpaper_tile:       eframe::egui::Vec2,
+    tile_gap:             f32,
+    tile_round:           eframe::egui::CornerRadius,
+    tile_hover:           eframe::egui::Color32,
+    app_tile_h:           f32,
+    app_tile_gap:         f32,
+    app_tile_icon:        f32,
+    symbolic_color:       eframe::egui::Color32,
+    motion:               crate::animation::Motion,
+    /// Right-to-left layout: sections and rows mirrored.
+    rtl:                  bool,
 }
 
 impl LayoutCache {
-    fn build(theme: &Theme, config: &Config) -> Self {
+    /// `screen` is the size of the output the window opens on, in points.
+    fn build(theme: &Theme, config: &Config, screen: eframe::egui::Vec2) -> Self {
         use eframe::egui;
 
-        let win_size = egui::vec2(
-            theme.get_px("main-window", "width").unwrap_or(300.0),
-            theme.get_px("main-window", "height").unwrap_or(200.0),
-        );
+        let win_size = window_size(theme, screen);
         let win_bg = theme.get("main-window", "background-color")
             .and_then(|s| theme.parse_color(&s)).unwrap_or(egui::Color32::BLACK);
 
@@ -937,17 +2167,66 @@ impl LayoutCache {
             ("app-list",   theme.get_order("app-list")),
         ];
         if config.enable_audio_control { raw.push(("volume-slider", theme.get_order("volume-slider"))); }
+        if config.enable_audio_control && config.enable_mic_control {
+            raw.push(("mic-control", theme.get_order("mic-control")));
+        }
         if config.show_time            { raw.push(("time-display",   theme.get_order("time-display"))); }
         if config.enable_power_options { raw.push(("power-button",   theme.get_order("power-button"))); }
         if config.enable_system_tray   { raw.push(("tray-icon",      theme.get_order("tray-icon"))); }
+        if config.enable_notifications { raw.push(("notifications",  theme.get_order("notifications"))); }
+        if config.enable_network       { raw.push(("network",        theme.get_order("network"))); }
+        if config.enable_vpn           { raw.push(("vpn",            theme.get_order("vpn"))); }
+        if config.enable_bluetooth     { raw.push(("bluetooth",      theme.get_order("bluetooth"))); }
+        if config.enable_drives        { raw.push(("drives",         theme.get_order("drives"))); }
+        if config.enable_idle_inhibitor { raw.push(("idle-inhibitor", theme.get_order("idle-inhibitor"))); }
+        if config.enable_system_stats   { raw.push(("system-stats",   theme.get_order("system-stats"))); }
+        if config.enable_timer          { raw.push(("timer",          theme.get_order("timer"))); }
+        if config.enable_quick_actions  { raw.push(("quick-actions",  theme.get_order("quick-actions"))); }
+        if config.enable_workspaces     { raw.push(("workspaces",     theme.get_order("workspaces"))); }
+        if config.enable_weather        { raw.push(("weather",        theme.get_order("weather"))); }
+        if config.enable_theme_switcher { raw.push(("theme-switcher", theme.get_order("theme-switcher"))); }
         raw.sort_by_key(|(_, o)| *o);
 
-        let sections = raw.into_iter().map(|(name, _)| SectionInfo {
-            pos:  theme.get_position(name),
-            size: if matches!(name, "search-bar" | "app-list") {
-                theme.get_px(name, "width").zip(theme.get_px(name, "height")).map(|(w, h)| egui::vec2(w, h))
-            } else { None },
-            name,
+        let flex = (theme.get("main-window", "display").as_deref() == Some("flex")).then(|| FlexLayout {
+            direction: match theme.get("main-window", "flex-direction").as_deref() {
+                Some("row") => crate::flex::Direction::Row,
+                _           => crate::flex::Direction::Column,
+            },
+            gap:       theme.get_px("main-window", "gap").unwrap_or(0.0),
+            padding:   theme.get("main-window", "padding").and_then(|s| crate::flex::parse_edges(&s)).unwrap_or_default(),
+        });
+
+        let rtl = match theme.get("main-window", "direction").as_deref() {
+            Some("rtl") => true,
+            Some("ltr") => false,
+            _           => crate::bidi::locale_is_rtl(),
+        };
+
+        let sections = raw.into_iter().map(|(name, _)| {
+            let flow = flex.as_ref()
+                .filter(|_| theme.get(name, "position").as_deref() != Some("absolute"))
+                .map(|flex| flow_item(theme, name, flex.direction, win_size));
+            if flow.is_some() {
+                return SectionInfo { name, pos: None, pivot: egui::Align2::LEFT_TOP, size: None, flow };
+            }
+            let position = theme.get_position(name, win_size).map(|(pos, pivot)| {
+                if !rtl { return (pos, pivot); }
+                let flipped = match pivot.x() {
+                    egui::Align::Min    => egui::Align::Max,
+                    egui::Align::Max    => egui::Align::Min,
+                    egui::Align::Center => egui::Align::Center,
+                };
+                ((win_size.x - pos.0, pos.1), egui::Align2([flipped, pivot.y()]))
+            });
+            SectionInfo {
+                pos:   position.map(|(pos, _)| pos),
+                pivot: position.map_or(egui::Align2::LEFT_TOP, |(_, pivot)| pivot),
+                size:  if matches!(name, "search-bar" | "app-list" | "notifications") {
+                    theme.get_len(name, "width", win_size.x).zip(theme.get_len(name, "height", win_size.y)).map(|(w, h)| egui::vec2(w, h))
+                } else { None },
+                flow,
+                name,
+            }
         }).collect();
 
         let mut elems: Vec<(i32, ElemKind)> = vec![
@@ -961,13 +2240,27 @@ impl LayoutCache {
             .and_then(|s| theme.parse_color(&s))
             .unwrap_or(egui::Color32::from_rgb(94, 206, 135));
 
-        let win_w = theme.get_px("main-window", "width").unwrap_or(220.0);
+        let win_w = theme.get_len("main-window", "width", screen.x).unwrap_or(220.0);
+
+        use crate::animation::{Easing, Effect, Transition};
+        let duration = |prop: &str, default: f32| theme.get("main-window", prop)
+            .and_then(|s| crate::animation::parse_duration(&s)).unwrap_or(default);
+        let easing   = |prop: &str, default: Easing| theme.get("main-window", prop)
+            .and_then(|s| Easing::parse(&s)).unwrap_or(default);
+        let motion = crate::animation::Motion {
+            effect: theme.get("main-window", "open-animation").and_then(|s| Effect::parse(&s)).unwrap_or(Effect::Fade),
+            open:   Transition { duration: duration("open-duration", 0.12), easing: easing("open-easing", Easing::EaseOut) },
+            hover:  Transition { duration: duration("transition-duration", 0.1), easing: easing("transition-easing", Easing::EaseInOut) },
+        };
 
         LayoutCache {
             win_size,
             win_bg,
+            win_round:   eframe::egui::CornerRadius::same(theme.get_px("main-window", "border-radius").unwrap_or(0.0) as u8),
+            win_blur:    theme.get("main-window", "backdrop-filter").is_some_and(|s| s.trim().starts_with("blur")),
             bg_image,
             sections,
+            flex,
             elem_order:  elems.into_iter().map(|(_, k)| k).collect(),
             settings_w:  theme.get_px("settings-button", "width").unwrap_or(22.0),
             settings_h:  theme.get_px("settings-button", "height").unwrap_or(22.0),
@@ -976,15 +2269,127 @@ impl LayoutCache {
             icon_w:      theme.get_px("app-icon", "width").unwrap_or(22.0),
             icon_h:      theme.get_px("app-icon", "height").unwrap_or(22.0),
             vol_gap:     theme.get_px("volume-slider", "gap"),
-            env_w:       theme.get_px("env-input", "width").unwrap_or(300.0),
-            env_h:       theme.get_px("env-input", "height").unwrap_or(150.0),
-            tray_w:      theme.get_px("tray-icon", "width").unwrap_or(win_w - 24.0),
-            tray_h:      theme.get_px("tray-icon", "height").unwrap_or(18.0),
+            vol_muted_color: theme.get("volume-slider", "muted-color").and_then(|s| theme.parse_color(&s)),
+            mic_gap:     theme.get_px("mic-control", "gap"),
+            idle_active_color: theme.get("idle-inhibitor", "active-color").and_then(|s| theme.parse_color(&s)),
+            mic_muted_color: theme.get("mic-control", "muted-color").and_then(|s| theme.parse_color(&s)),
+            timer_gap:   theme.get_px("timer", "gap"),
+            timer_done_color: theme.get("timer", "done-color").and_then(|s| theme.parse_color(&s)),
+            recording_color: theme.get("quick-actions", "recording-color").and_then(|s| theme.parse_color(&s))
+                .unwrap_or(egui::Color32::from_rgb(235, 87, 87)),
+            workspace_gap: theme.get_px("workspaces", "gap"),
+            vpn_active_color: theme.get("vpn", "active-color").and_then(|s| theme.parse_color(&s)),
+            workspace_empty_color: theme.get("workspaces", "empty-color").and_then(|s| theme.parse_color(&s)),
+            workspace_urgent_color: theme.get("workspaces", "urgent-color").and_then(|s| theme.parse_color(&s))
+                .unwrap_or(egui::Color32::from_rgb(235, 87, 87)),
+            weather_icon_size: theme.get_px("weather", "icon-size").unwrap_or(18.0),
+            weather_gap: theme.get_px("weather", "gap"),
+            weather_stale_color: theme.get("weather", "stale-color").and_then(|s| theme.parse_color(&s)),
+            env_w:       theme.get_px("env-input", "width").unwrap_or(360.0),
+            env_h:       theme.get_px("env-input", "height").unwrap_or(340.0),
+            tray_w:      theme.get_len("tray-icon", "width", win_w).unwrap_or(win_w - 24.0),
+            tray_h:      theme.get_len("tray-icon", "height", win_size.y).unwrap_or(18.0),
             tray_indicator_color,
+            tile_w:      theme.get_px("game-tile", "width").unwrap_or(60.0),
+            tile_h:      theme.get_px("game-tile", "height").unwrap_or(90.0),
+            wallpaper_tile: egui::vec2(theme.get_px("wallpaper-tile", "width").unwrap_or(96.0),
+                theme.get_px("wallpaper-tile", "height").unwrap_or(54.0)),
+            tile_gap:    theme.get_px("game-tile", "gap").unwrap_or(4.0),
+            tile_round:  eframe::egui::CornerRadius::same(theme.get_px("game-tile", "border-radius").unwrap_or(4.0) as u8),
+            tile_hover:  theme.get("game-tile:hover", "border-color")
+                .and_then(|s| theme.parse_color(&s))
+                .unwrap_or(egui::Color32::WHITE),
+            app_tile_h:    theme.get_px("app-tile", "height").unwrap_or(60.0),
+            app_tile_gap:  theme.get_px("app-tile", "gap").unwrap_or(4.0),
+            app_tile_icon: theme.get_px("app-tile", "icon-size").unwrap_or(28.0),
+            symbolic_color: theme.get("main-window", "symbolic-color")
+                .and_then(|s| theme.parse_color(&s))
+                .or_else(|| theme.get_text_color("app-button", false))
+                .unwrap_or(egui::Color32::from_gray(220)),
+            motion,
+            rtl,
         }
     }
 }
 
+/// A section's `grow`, `align`, `margin` (plus `margin-top` etc.) and its
+/// length along `direction`, in flex mode.
+fn flow_item(theme: &Theme, name: &str, direction: crate::flex::Direction, win: eframe::egui::Vec2) -> FlowItem {
+    let mut margin = theme.get(name, "margin").and_then(|s| crate::flex::parse_edges(&s)).unwrap_or_default();
+    for (side, edge) in [("top", &mut margin.top), ("right", &mut margin.right), ("bottom", &mut margin.bottom), ("left", &mut margin.left)] {
+        if let Some(v) = theme.get_px(name, &format!("margin-{side}")) { *edge = v; }
+    }
+    FlowItem {
+        basis:  match direction {
+            crate::flex::Direction::Column => theme.get_len(name, "height", win.y),
+            crate::flex::Direction::Row    => theme.get_len(name, "width", win.x),
+        },
+        grow:   theme.get(name, "grow").and_then(|s| s.parse().ok()).unwrap_or(0.0),
+        align:  theme.get(name, "align").and_then(|s| crate::flex::parse_align(&s)).unwrap_or(crate::flex::Align::Stretch),
+        margin,
+    }
+}
+
+/// The `key-*` settings as egui chords; bad entries are reported and skipped.
+fn key_bindings(config: &Config) -> Vec<(crate::keys::Action, eframe::egui::Modifiers, eframe::egui::Key)> {
+    use crate::keys::Action;
+    let lists: [(Action, &[String]); 8] = [
+        (Action::Next,          &config.key_next),
+        (Action::Previous,      &config.key_previous),
+        (Action::Clear,         &config.key_clear),
+        (Action::Actions,       &config.key_actions),
+        (Action::LaunchOptions, &config.key_launch_options),
+        (Action::ZoomIn,        &config.key_zoom_in),
+        (Action::ZoomOut,       &config.key_zoom_out),
+        (Action::ZoomReset,     &config.key_zoom_reset),
+    ];
+    let (chords, errors) = crate::keys::bindings(&lists, &config.key_launch_nth);
+    for e in errors { tracing::warn!(target: "config", "Keybinding {e}; ignored"); }
+    chords.into_iter().filter_map(|(action, chord)| {
+        // egui names letters in capitals.
+        let name = if chord.key.len() == 1 { chord.key.to_ascii_uppercase() } else { chord.key.clone() };
+        let Some(key) = eframe::egui::Key::from_name(&name) else {
+            tracing::warn!(target: "config", "Keybinding: no key named \"{}\"; ignored", chord.key);
+            return None;
+        };
+        let mods = eframe::egui::Modifiers { alt: chord.alt, ctrl: chord.ctrl, shift: chord.shift, ..eframe::egui::Modifiers::NONE };
+        Some((action, mods, key))
+    }).collect()
+}
+
+/// How much one press of a zoom key changes `ui-scale`, and its limits.
+const UI_SCALE_STEP:  f32        = 0.1;
+const UI_SCALE_RANGE: (f32, f32) = (0.5, 3.0);
+
+/// `ui-scale`, kept in range.
+fn ui_scale(config: &Config) -> f32 {
+    config.ui_scale.clamp(UI_SCALE_RANGE.0, UI_SCALE_RANGE.1)
+}
+
+/// `.env-input { scaling }` was the UI scale before `ui-scale`.
+fn warn_legacy_scaling(theme: &Theme) {
+    let scaling = theme.get("env-input", "scaling").and_then(|s| s.parse::<f32>().ok());
+    if scaling.is_some_and(|s| s != 1.0) {
+        tracing::warn!(target: "theme", "`.env-input {{ scaling }}` in theme.css is no longer read; set ui-scale in config.toml instead");
+    }
+}
+
+/// Output size assumed for `%` window sizes until the real one is known.
+const FALLBACK_SCREEN: eframe::egui::Vec2 = eframe::egui::vec2(1920.0, 1080.0);
+
+/// `.main-window` width/height: px, or `%` of `screen`.
+fn window_size(theme: &Theme, screen: eframe::egui::Vec2) -> eframe::egui::Vec2 {
+    eframe::egui::vec2(
+        theme.get_len("main-window", "width", screen.x).unwrap_or(300.0),
+        theme.get_len("main-window", "height", screen.y).unwrap_or(200.0),
+    ).round()
+}
+
+/// Whether the window's size depends on the output it opens on.
+fn sized_by_screen(theme: &Theme) -> bool {
+    ["width", "height"].iter().any(|prop| theme.get("main-window", prop).is_some_and(|v| v.trim().ends_with('%')))
+}
+
 // ============================================================================
 // EframeGui / EframeWrapper
 // ============================================================================
@@ -992,10 +2397,18 @@ impl LayoutCache {
 pub struct EframeGui;
 
 impl EframeGui {
-    pub fn run(app: Box<dyn AppInterface>) -> Result<(), Box<dyn Error>> {
-        let theme  = Arc::new(Theme::load_or_create());
-        let cfg    = theme.get_config();
-        let layout = LayoutCache::build(&theme, &cfg);
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        app: Box<dyn AppInterface>,
+        theme: Arc<Theme>,
+        cfg: Config,
+        theme_override: Option<String>,
+        scheme: crate::appearance::ColorScheme,
+        ipc: Option<std::os::unix::net::UnixListener>,
+        first_run: bool,
+        hidden: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let layout = LayoutCache::build(&theme, &cfg, FALLBACK_SCREEN);
         let (w, h) = (layout.win_size.x, layout.win_size.y);
 
         let viewport = eframe::egui::ViewportBuilder::default()
@@ -1004,45 +2417,213 @@ impl EframeGui {
             .with_decorations(false)
             .with_resizable(false)
             .with_active(true)
-            .with_transparent(true);
+            .with_transparent(true)
+            .with_title("Tusk Launcher")
+            .with_app_id(crate::backdrop::APP_ID);
+        // Read by X11 window managers when the window is mapped; ignored on Wayland.
+        let viewport = match crate::x11_hints::WindowType::parse(&cfg.x11_window_type) {
+            Some(crate::x11_hints::WindowType::Dialog)  => viewport.with_window_type(eframe::egui::X11WindowType::Dialog),
+            Some(crate::x11_hints::WindowType::Utility) => viewport.with_window_type(eframe::egui::X11WindowType::Utility),
+            Some(crate::x11_hints::WindowType::Normal)  => viewport,
+            None => {
+                tracing::warn!(target: "config", "x11-window-type: expected \"dialog\", \"utility\" or \"normal\", not {:?}", cfg.x11_window_type);
+                viewport
+            }
+        };
 
         let audio    = crate::system::AudioController::new(&cfg)?;
+        if cfg.enable_notifications {
+            crate::notifications::set_do_not_disturb(crate::app_launcher::get_do_not_disturb());
+        }
         audio.start_polling(&cfg);
-        let sni_host = crate::sni::SniHost::new(&cfg);
 
         eframe::run_native(
-            "Application Launcher",
+            crate::backdrop::APP_ID,
             eframe::NativeOptions {
                 viewport,
                 renderer: eframe::Renderer::Wgpu,
                 ..Default::default()
             },
             Box::new(move |cc| {
-                if let Some(s) = theme.get("env-input", "scaling").and_then(|s| s.parse::<f32>().ok()) {
-                    cc.egui_ctx.set_pixels_per_point(s);
-                }
+                // The zoom keys are ours (`key-zoom-*`), and they save the result.
+                cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+                cc.egui_ctx.set_zoom_factor(ui_scale(&cfg));
+                warn_legacy_scaling(&theme);
+                let faces = theme.font_faces();
+                if !faces.is_empty() { crate::fonts::install(&cc.egui_ctx, &faces); }
+                apply_motion(&cc.egui_ctx, &layout.motion, cfg.animations);
                 cc.egui_ctx.request_repaint();
+                // Commands from later invocations arrive on the IPC thread; wake the
+                // UI so they're handled even while the window is idle.
+                let (ipc_tx, ipc_rx) = std::sync::mpsc::channel();
+                // Started in the background: hidden before the first frame shows.
+                if hidden { let _ = ipc_tx.send(crate::ipc::Command::Hide); }
+                if let Some(listener) = ipc {
+                    // The bus name goes with the socket, to the instance that owns it.
+                    if cfg.enable_dbus_interface {
+                        let (ctx, tx) = (cc.egui_ctx.clone(), ipc_tx.clone());
+                        crate::dbus::serve(move |cmd| {
+                            let _ = tx.send(cmd);
+                            ctx.request_repaint();
+                        });
+                    }
+                    let ctx = cc.egui_ctx.clone();
+                    crate::ipc::serve(listener, move |cmd| {
+                        let _ = ipc_tx.send(cmd);
+                        ctx.request_repaint();
+                    });
+                }
+                let notification_host = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::notifications::NotificationHost::new(&cfg, move || ctx.request_repaint())
+                };
+                let network_host = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::network::NetworkHost::new(&cfg, move || ctx.request_repaint())
+                };
+                let bluetooth_host = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::bluetooth::BluetoothHost::new(&cfg, move || ctx.request_repaint())
+                };
+                let logind_host = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::logind::LogindHost::new(&cfg, move || ctx.request_repaint())
+                };
+                if let Some(host) = logind_host.as_ref().filter(|_| cfg.enable_idle_inhibitor)
+                    && crate::app_launcher::get_idle_inhibit()
+                {
+                    host.set_idle_inhibit(true);
+                }
+                let drives_host = cfg.enable_drives.then(|| {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::drives::DrivesHost::new(move || ctx.request_repaint())
+                });
+                let workspaces_host = cfg.enable_workspaces.then(|| {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::workspaces::WorkspacesHost::new(move || ctx.request_repaint())
+                });
+                let weather_host = cfg.enable_weather.then(|| {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::weather::WeatherHost::new(&cfg, move || ctx.request_repaint())
+                });
+                let sni_host = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::sni::SniHost::new(&cfg, move || ctx.request_repaint())
+                };
+                {
+                    let ctx = cc.egui_ctx.clone();
+                    audio.on_change(move || ctx.request_repaint());
+                }
+                {
+                    let ctx = cc.egui_ctx.clone();
+                    app.on_results(Box::new(move || ctx.request_repaint()));
+                }
+                // theme.css / config.toml edits are applied on the next frame.
+                let reload_pending = Arc::new(AtomicBool::new(false));
+                {
+                    let (ctx, pending) = (cc.egui_ctx.clone(), Arc::clone(&reload_pending));
+                    let dir = crate::paths::config_home().join("tusk-launcher");
+                    crate::watch::watch_files(&dir, |name| name == "theme.css" || name == "config.toml", move || {
+                        pending.store(true, Ordering::SeqCst);
+                        ctx.request_repaint();
+                    });
+                }
+                {
+                    let (ctx, pending) = (cc.egui_ctx.clone(), Arc::clone(&reload_pending));
+                    crate::watch::watch_files(&Theme::profiles_dir(), |name| name.ends_with(".css"), move || {
+                        pending.store(true, Ordering::SeqCst);
+                        ctx.request_repaint();
+                    });
+                }
+                // The desktop switching dark/light picks another profile.
+                let appearance = {
+                    let (ctx, pending) = (cc.egui_ctx.clone(), Arc::clone(&reload_pending));
+                    crate::appearance::AppearanceWatcher::new(&cfg, scheme, move || {
+                        pending.store(true, Ordering::SeqCst);
+                        ctx.request_repaint();
+                    })
+                };
+                let timer = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::timer::Timer::new(crate::timer::parse_presets(&cfg.timer_presets), move || ctx.request_repaint())
+                };
+                let recorder = {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::capture::Recorder::new(move || ctx.request_repaint())
+                };
+                let quick_actions = crate::capture::parse_actions(&cfg.quick_actions);
+                {
+                    let ctx = cc.egui_ctx.clone();
+                    crate::crash::on_crash(move || ctx.request_repaint());
+                }
                 let cached_time = app.get_time();
                 Ok(Box::new(EframeWrapper {
                     app,
                     audio_controller: audio,
                     current_volume: 0.0,
+                    volume_scroll: 0.0,
                     editing_windows: HashMap::new(),
+                    settings_window: None,
+                    onboarding: first_run.then(Onboarding::new),
+                    tray_debug: None,
+                    crashes: Vec::new(),
+                    launch_failures: Vec::new(),
+                    stats_sampler: crate::stats::StatsSampler::default(),
+                    stats: None,
+                    timer,
+                    recorder,
+                    quick_actions,
+                    reload_pending,
+                    theme_override,
+                    theme_profiles: Theme::profiles(),
+                    appearance,
                     focused: false,
-                    icon_manager: crate::app_launcher::IconManager::new(),
+                    icon_manager: crate::app_launcher::IconManager::new()
+                        .with_symbolic_color(layout.symbolic_color)
+                        .with_animation(cfg.animate_icons),
                     layout,
+                    flow_sizes: HashMap::new(),
                     cached_time,
                     last_time_update: Instant::now(),
                     theme,
+                    composition: crate::ime::Composition::default(),
+                    bindings: key_bindings(&cfg),
                     config: cfg,
                     sni_host,
+                    notification_host,
+                    network_host,
+                    bluetooth_host,
+                    logind_host,
+                    drives_host,
+                    workspaces_host,
+                    weather_host,
                     // Key: icon.id (or "{id}_attn"). Value: (icon_rev, TextureHandle).
                     // Re-uploaded when icon_rev differs from stored rev.
+                    tray_items: Arc::new(Vec::new()),
+                    tray_generation: 0,
                     tray_textures: HashMap::new(),
                     tray_name_cache: HashMap::new(),
                     tray_menu_open: None,
                     tray_menu_fetched: None,
+                    tray_menu_icons: HashMap::new(),
+                    tray_prefs: crate::app_launcher::get_tray_prefs(),
+                    tray_show_hidden: false,
+                    tray_hover: None,
                     scroll_offsets: HashMap::new(),
+                    selection: (String::new(), 0),
+                    selection_moved: false,
+                    selected_row: None,
+                    browse_jump: None,
+                    action_menu: None,
+                    placed: false,
+                    had_focus: false,
+                    shown_at: Instant::now(),
+                    closing: None,
+                    screen: FALLBACK_SCREEN,
+                    sizing: None,
+                    zoom: 1.0,
+                    ipc_rx,
+                    visible: true,
                 }))
             }),
         )?;
@@ -1050,94 +2631,961 @@ impl EframeGui {
     }
 }
 
+/// A launch-options window's working copy; nothing is stored until "Save"
+/// passes `validate_launch_options`.
+struct LaunchEditor {
+    env:      Vec<(String, String)>,
+    dir:      String,
+    command:  String,
+    name:     String,
+    icon:     String,
+    /// The row's description when the editor was opened, shown as app info.
+    info:     Option<String>,
+    /// The folder picker's answer; `Some` while its dialog is open.
+    picking:  Option<std::sync::mpsc::Receiver<Option<String>>>,
+    problems: Vec<String>,
+}
+
+impl LaunchEditor {
+    fn new(app: &dyn AppInterface, row: &ResultRow) -> Self {
+        let opts = app.get_launch_options(&row.id);
+        let over = app.get_app_override(&row.id);
+        let mut env: Vec<(String, String)> = opts.environment_vars.into_iter().collect();
+        env.sort();
+        LaunchEditor {
+            env,
+            dir:      opts.working_directory.unwrap_or_default(),
+            command:  opts.custom_command.unwrap_or_default(),
+            name:     over.name.unwrap_or_default(),
+            icon:     over.icon.unwrap_or_default(),
+            info:     row.subtitle.clone(),
+            picking:  None,
+            problems: Vec::new(),
+        }
+    }
+
+    fn options(&self) -> AppLaunchOptions {
+        AppLaunchOptions {
+            custom_command:    non_empty(&self.command),
+            working_directory: non_empty(&self.dir),
+            environment_vars:  self.env.iter()
+                .filter(|(key, _)| !key.trim().is_empty())
+                .map(|(key, value)| (key.trim().to_string(), value.clone()))
+                .collect(),
+        }
+    }
+
+    fn app_override(&self) -> AppOverride {
+        AppOverride { name: non_empty(&self.name), icon: non_empty(&self.icon) }
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    Some(s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Settings that name a theme profile, picked from a list.
+const THEME_SETTINGS: &[&str] = &["theme", "dark-theme", "light-theme"];
+
+/// A setting as the settings window edits it.
+#[derive(Clone, PartialEq)]
+enum SettingField {
+    Toggle(bool),
+    Text(String),
+    /// A list, one item per line.
+    Lines(String),
+}
+
+/// The settings window's working copy of config.toml; nothing is written
+/// until "Apply" or "Save", and then only the settings that changed.
+struct SettingsEditor {
+    settings: Vec<crate::config::Setting>,
+    fields:   Vec<SettingField>,
+    /// `fields` as last loaded or written.
+    saved:    Vec<SettingField>,
+    filter:   String,
+    problems: Vec<String>,
+}
+
+impl SettingsEditor {
+    fn new() -> Self {
+        use crate::config::Value;
+        let settings = crate::config::settings();
+        let values   = crate::config::current_values();
+        let fields: Vec<SettingField> = settings.iter().map(|setting| {
+            let value = values.get(setting.key).unwrap_or(&setting.default);
+            match &setting.default {
+                Value::Scalar(d) if d == "true" || d == "false" => SettingField::Toggle(value.scalar() == Some("true")),
+                Value::Scalar(_) => SettingField::Text(value.scalar().unwrap_or_default().to_string()),
+                Value::List(_)   => SettingField::Lines(value.list().join("\n")),
+            }
+        }).collect();
+        SettingsEditor { settings, saved: fields.clone(), fields, filter: String::new(), problems: Vec::new() }
+    }
+
+    /// The edited settings as config values, or what's wrong with them.
+    fn changes(&self) -> Result<Vec<(String, crate::config::Value)>, Vec<String>> {
+        use crate::config::Value;
+        let mut changes  = Vec::new();
+        let mut problems = Vec::new();
+        for ((setting, field), saved) in self.settings.iter().zip(&self.fields).zip(&self.saved) {
+            if field == saved { continue; }
+            let value = match field {
+                SettingField::Toggle(on)  => Value::Scalar(on.to_string()),
+                SettingField::Text(text)  => {
+                    let numeric = setting.default.scalar().is_some_and(|d| d.parse::<f64>().is_ok());
+                    if numeric && text.trim().parse::<f64>().is_err() {
+                        problems.push(format!("{}: \"{text}\" isn't a number", setting.key));
+                        continue;
+                    }
+                    Value::Scalar(if numeric { text.trim().to_string() } else { text.clone() })
+                }
+                SettingField::Lines(text) => Value::List(
+                    text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect(),
+                ),
+            };
+            changes.push((setting.key.to_string(), value));
+        }
+        if problems.is_empty() { Ok(changes) } else { Err(problems) }
+    }
+}
+
+/// The first-start wizard's place and answers.
+struct Onboarding {
+    step:    crate::onboarding::Step,
+    choices: crate::onboarding::Choices,
+    /// Where the hotkey goes, and the line to add there.
+    hotkey:  (&'static str, String),
+}
+
+impl Onboarding {
+    fn new() -> Self {
+        let command = std::env::current_exe().ok()
+            .and_then(|path| path.to_str().map(str::to_string))
+            .unwrap_or_else(|| "tusk-launcher".into());
+        Onboarding {
+            step:    crate::onboarding::Step::Theme,
+            choices: crate::onboarding::Choices::from_values(&crate::config::current_values()),
+            hotkey:  crate::onboarding::keybinding(&command),
+        }
+    }
+}
+
 struct EframeWrapper {
     app:              Box<dyn AppInterface>,
     audio_controller: crate::system::AudioController,
     current_volume:   f32,
-    editing_windows:  HashMap<String, String>,
+    /// Wheel travel over the volume row not yet turned into a step.
+    volume_scroll:    f32,
+    editing_windows:  HashMap<String, LaunchEditor>,
+    /// The settings window, while it's open.
+    settings_window:  Option<SettingsEditor>,
+    /// The first-start wizard, until it's finished or closed.
+    onboarding:       Option<Onboarding>,
+    /// The tray debug view while it's open; true also lists the bus names
+    /// that were scanned, not just items.
+    tray_debug:       Option<bool>,
+    /// Background threads that panicked, until the dialog is dismissed.
+    crashes:          Vec<crate::crash::Crash>,
+    /// Error toasts, kept until dismissed (also across hide/show).
+    launch_failures:  Vec<LaunchFailure>,
+    stats_sampler:    crate::stats::StatsSampler,
+    /// Latest /proc reading and when it was taken; `None` until the first.
+    stats:            Option<(crate::stats::SystemStats, Instant)>,
+    timer:            crate::timer::Timer,
+    recorder:         crate::capture::Recorder,
+    /// `quick-actions`, as parsed at startup.
+    quick_actions:    Vec<crate::capture::QuickAction>,
+    /// Set by the file watcher when theme.css, config.toml or a profile changed.
+    reload_pending:   Arc<AtomicBool>,
+    /// `--theme` from the command line; wins over config.toml until `theme` changes there.
+    theme_override:   Option<String>,
+    theme_profiles:   Vec<String>,
+    /// Follows the desktop's color scheme when `dark-theme`/`light-theme` are set.
+    appearance:       Option<crate::appearance::AppearanceWatcher>,
     focused:          bool,
     icon_manager:     crate::app_launcher::IconManager,
     layout:           LayoutCache,
+    /// Size each flex-flowed section took last frame; feeds `flex::layout`.
+    flow_sizes:       HashMap<&'static str, eframe::egui::Vec2>,
     cached_time:      String,
     last_time_update: Instant,
     theme:            Arc<Theme>,
     config:           Config,
     sni_host:         Option<crate::sni::SniHost>,
+    notification_host: Option<crate::notifications::NotificationHost>,
+    network_host:     Option<crate::network::NetworkHost>,
+    bluetooth_host:   Option<crate::bluetooth::BluetoothHost>,
+    /// Power actions, logind's `Can*` answers and the caffeine lock; `None`
+    /// when neither `use-logind` nor `enable-idle-inhibitor` needs it.
+    logind_host:      Option<crate::logind::LogindHost>,
+    drives_host:      Option<crate::drives::DrivesHost>,
+    workspaces_host:  Option<crate::workspaces::WorkspacesHost>,
+    weather_host:     Option<crate::weather::WeatherHost>,
+    /// Snapshot of the SNI host's items, re-read when its generation moves.
+    tray_items:       Arc<Vec<crate::sni::TrayIcon>>,
+    tray_generation:  u64,
     /// (icon_rev, handle) — re-uploaded when rev changes.
     tray_textures:    HashMap<String, (u32, eframe::egui::TextureHandle)>,
     tray_name_cache:  HashMap<String, Option<String>>,
     tray_menu_open:    Option<String>,
     tray_menu_fetched: Option<String>,
+    /// Decoded `icon-data` of tray menus: (menu_revision, item id → handle).
+    tray_menu_icons:   HashMap<String, (u32, HashMap<i32, eframe::egui::TextureHandle>)>,
+    /// Pinned/hidden tray items, as persisted in the app cache.
+    tray_prefs:        Vec<(String, TrayPref)>,
+    /// Overflow chevron expanded: hidden tray items are drawn too.
+    tray_show_hidden:  bool,
+    /// Tray item under the pointer and since when, for the tooltip delay.
+    tray_hover:        Option<(String, Instant)>,
     /// Per-app scroll offset for marquee text on hover (pixels from left).
     scroll_offsets:   HashMap<String, f32>,
+    /// Keyboard selection in the results: the query it belongs to and the index.
+    selection:        (String, usize),
+    /// The selection moved this frame; scroll it into view.
+    selection_moved:  bool,
+    /// Widget of the selected result, which screen readers are pointed at
+    /// while focus stays in the search field.
+    selected_row:     Option<eframe::egui::Id>,
+    /// Input-method state of the search field.
+    composition:      crate::ime::Composition,
+    /// The `key-*` settings, resolved to egui keys.
+    bindings:         Vec<(crate::keys::Action, eframe::egui::Modifiers, eframe::egui::Key)>,
+    /// Letter clicked in the `show-all-apps` index; the list scrolls to it.
+    browse_jump:      Option<char>,
+    /// Action menu opened with `key-actions`: its row and highlighted entry.
+    action_menu:      Option<(ResultRow, usize)>,
+    /// Moved to its `window-monitor` / `window-anchor` spot since last shown.
+    placed:           bool,
+    /// The window has had keyboard focus since it was shown; losing it
+    /// afterwards is what `close-on-focus-loss` reacts to.
+    had_focus:        bool,
+    /// When the window was last shown; the open effect runs from here.
+    shown_at:         Instant,
+    /// Dismissed and playing the close effect since then.
+    closing:          Option<Instant>,
+    /// Size of the output the window was last placed on, in points.
+    screen:           eframe::egui::Vec2,
+    /// The placement thread's answer for `screen`, while it's pending.
+    sizing:           Option<std::sync::mpsc::Receiver<eframe::egui::Vec2>>,
+    /// egui zoom factor the window was last sized for.
+    zoom:             f32,
+    ipc_rx:           std::sync::mpsc::Receiver<crate::ipc::Command>,
+    /// Daemon mode only: whether the launcher is currently shown.
+    visible:          bool,
 }
 
 impl EframeWrapper {
+    fn handle_ipc_command(&mut self, cmd: crate::ipc::Command, ctx: &eframe::egui::Context) {
+        use crate::ipc::Command;
+        if !self.config.daemon_mode {
+            match cmd {
+                Command::Show => ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Focus),
+                Command::Windows => {
+                    self.app.handle_input(&self.config.window_prefix);
+                    ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Focus);
+                }
+                Command::TrayDebug => self.tray_debug = Some(self.tray_debug.unwrap_or(false)),
+                Command::SetQuery(query) => {
+                    self.app.handle_input(&query);
+                    ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Focus);
+                }
+                Command::Hide | Command::Toggle | Command::Quit => self.app.handle_input("ESC"),
+                Command::DoNotDisturb(on) => self.set_do_not_disturb(on),
+            }
+            return;
+        }
+        match cmd {
+            Command::Show                  => self.show_window(ctx),
+            Command::Windows               => {
+                self.show_window(ctx);
+                self.app.handle_input(&self.config.window_prefix);
+            }
+            Command::Hide                  => self.hide_window(ctx),
+            Command::Toggle if self.visible => self.hide_window(ctx),
+            Command::Toggle                => self.show_window(ctx),
+            Command::Quit                  => ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close),
+            Command::TrayDebug             => {
+                self.show_window(ctx);
+                self.tray_debug = Some(self.tray_debug.unwrap_or(false));
+            }
+            Command::SetQuery(query)       => {
+                self.show_window(ctx);
+                self.app.handle_input(&query);
+            }
+            Command::DoNotDisturb(on)      => self.set_do_not_disturb(on),
+        }
+    }
+
+    fn set_do_not_disturb(&mut self, on: bool) {
+        crate::notifications::set_do_not_disturb(on);
+        if let Err(e) = crate::app_launcher::set_do_not_disturb(on) {
+            tracing::warn!(target: "notifications", "Failed to save do not disturb state: {e}");
+        }
+    }
+
+    /// Daemon mode: hide instead of exiting, so the next invocation skips the
+    /// desktop scan, icon resolution and tray setup.
+    ///
+    /// winit can't hide windows on Wayland, so there the window is additionally
+    /// collapsed to a 1×1 click-through surface and left unpainted until shown.
+    fn hide_window(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui::{vec2, ViewportCommand};
+        if !self.visible { return; }
+        self.visible = false;
+        self.focused = false;
+        self.closing = None;
+        self.app.reset();
+        for app_name in self.editing_windows.drain().map(|(name, _)| name) {
+            ctx.send_viewport_cmd_to(eframe::egui::ViewportId::from_hash_of(format!("env_{app_name}")), ViewportCommand::Close);
+        }
+        if self.settings_window.take().is_some() {
+            ctx.send_viewport_cmd_to(eframe::egui::ViewportId::from_hash_of("settings"), ViewportCommand::Close);
+        }
+        if self.onboarding.take().is_some() {
+            ctx.send_viewport_cmd_to(eframe::egui::ViewportId::from_hash_of("onboarding"), ViewportCommand::Close);
+        }
+        if self.tray_debug.take().is_some() {
+            ctx.send_viewport_cmd_to(eframe::egui::ViewportId::from_hash_of("tray_debug"), ViewportCommand::Close);
+        }
+        if let Some(id) = self.tray_menu_open.take() {
+            ctx.send_viewport_cmd_to(tray_menu_vp_id(&id), ViewportCommand::Close);
+        }
+        ctx.send_viewport_cmd(ViewportCommand::Visible(false));
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            ctx.send_viewport_cmd(ViewportCommand::MousePassthrough(true));
+            ctx.send_viewport_cmd(ViewportCommand::InnerSize(vec2(1.0, 1.0)));
+        }
+    }
+
+    fn show_window(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui::ViewportCommand;
+        if self.visible { ctx.send_viewport_cmd(ViewportCommand::Focus); return; }
+        self.visible   = true;
+        self.placed    = false;
+        self.had_focus = false;
+        self.shown_at  = Instant::now();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            ctx.send_viewport_cmd(ViewportCommand::InnerSize(self.layout.win_size));
+            ctx.send_viewport_cmd(ViewportCommand::MousePassthrough(false));
+        }
+        ctx.send_viewport_cmd(ViewportCommand::Visible(true));
+        ctx.send_viewport_cmd(ViewportCommand::Focus);
+    }
+
+    /// Move the window to the `window-monitor` output at `window-anchor`, and
+    /// size it for that output when the theme gives `%` sizes. The
+    /// compositor queries run on a thread so showing never waits on them.
+    fn place_window(&mut self, ctx: &eframe::egui::Context) {
+        let (monitor, anchor) = (self.config.window_monitor.clone(), self.config.window_anchor.clone());
+        // X11 window managers leave a floating window wherever, so it's placed here.
+        let moves = !monitor.is_empty() || anchor != "center" || crate::x11_hints::active();
+        if !moves && !sized_by_screen(&self.theme) { return; }
+        let (native, fallback) = ctx.input(|i| (i.viewport().native_pixels_per_point, i.viewport().monitor_size));
+        let scale  = crate::monitors::pixels_per_point(ctx.zoom_factor(), native.unwrap_or(1.0));
+        let theme  = Arc::clone(&self.theme);
+        let ctx    = ctx.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.sizing = Some(rx);
+        std::thread::spawn(move || {
+            let Some((area, cursor)) = crate::monitors::target(&monitor, &anchor) else {
+                // No compositor to ask: egui knows the output the window is on.
+                if let Some(screen) = fallback { let _ = tx.send(screen); ctx.request_repaint(); }
+                return;
+            };
+            let screen = eframe::egui::vec2(area.w, area.h) / scale;
+            let _ = tx.send(screen);
+            ctx.request_repaint();
+            if !moves { return; }
+            let size = window_size(&theme, screen) * scale;
+            let pos  = crate::monitors::anchor(&anchor, &area, (size.x, size.y), cursor);
+            if !crate::monitors::move_window(pos) {
+                let pos = eframe::egui::pos2(pos.0, pos.1) / scale;
+                ctx.send_viewport_cmd(eframe::egui::ViewportCommand::OuterPosition(pos));
+            }
+        });
+    }
+
+    /// The open effect's opacity and offset for this frame: running forward
+    /// after `show_window`, backwards while `closing`.
+    fn open_frame(&self, ctx: &eframe::egui::Context) -> (f32, f32) {
+        let motion = self.layout.motion;
+        if !self.config.animations { return (1.0, 0.0); }
+        let shown = match self.closing {
+            Some(at) => 1.0 - motion.open.progress(at.elapsed().as_secs_f32()),
+            None     => motion.open.progress(self.shown_at.elapsed().as_secs_f32()),
+        };
+        if shown < 1.0 { ctx.request_repaint(); }
+        motion.effect.frame(shown)
+    }
+
+    /// Hide (daemon mode) or quit, once the close effect has played.
+    fn dismiss(&mut self, ctx: &eframe::egui::Context) {
+        let motion = self.layout.motion;
+        if self.config.animations && motion.effect != crate::animation::Effect::None {
+            let at = *self.closing.get_or_insert_with(Instant::now);
+            if at.elapsed().as_secs_f32() < motion.open.duration { ctx.request_repaint(); return; }
+        }
+        self.closing = None;
+        if self.config.daemon_mode {
+            self.hide_window(ctx);
+        } else {
+            ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Dismiss the launcher once it loses keyboard focus, which is also what a
+    /// click outside the window does. Focus moving to one of our own windows
+    /// (a launch-options editor, the settings, a tray menu) doesn't count.
+    fn close_on_focus_loss(&mut self, ctx: &eframe::egui::Context) {
+        match ctx.input(|i| i.viewport().focused) {
+            Some(true)  => self.had_focus = true,
+            Some(false) if self.had_focus => {
+                self.had_focus = false;
+                if !self.child_windows_open() && self.tray_menu_open.is_none() {
+                    self.app.handle_input("ESC");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Zoom to `scale` (rounded to a step and clamped) and save it as `ui-scale`.
+    fn set_ui_scale(&mut self, ctx: &eframe::egui::Context, scale: f32) {
+        let scale = ((scale / UI_SCALE_STEP).round() * UI_SCALE_STEP).clamp(UI_SCALE_RANGE.0, UI_SCALE_RANGE.1);
+        if scale == self.config.ui_scale { return; }
+        self.config.ui_scale = scale;
+        ctx.set_zoom_factor(scale);
+        if let Err(e) = crate::config::set_value("ui-scale", &format!("{scale:.1}")) {
+            tracing::warn!(target: "config", "Failed to save ui-scale: {e}");
+        }
+    }
+
+    /// Re-lay out for an output of `screen` points; `%` window sizes follow it.
+    fn set_screen(&mut self, ctx: &eframe::egui::Context, screen: eframe::egui::Vec2) {
+        if screen == self.screen { return; }
+        self.screen = screen;
+        let layout = LayoutCache::build(&self.theme, &self.config, screen);
+        if layout.win_size != self.layout.win_size {
+            ctx.send_viewport_cmd(eframe::egui::ViewportCommand::InnerSize(layout.win_size));
+        }
+        self.layout = layout;
+    }
+
+    /// Re-read theme.css and config.toml. Sections, layout, colors and most
+    /// flags apply at once; background services (tray, notifications,
+    /// network, audio polling), search providers and `daemon-mode` keep their
+    /// startup settings until the next start.
+    /// Where flex mode puts each flowed section, and which axes it's held to
+    /// (the stretched cross axis, and the main axis when it has a `height` /
+    /// `width` or grows). Empty unless `.main-window` is `display: flex`.
+    fn flow_rects(&self) -> HashMap<&'static str, (eframe::egui::Rect, eframe::egui::Vec2b)> {
+        use crate::flex::{Align, Direction, Item};
+        let Some(flex) = &self.layout.flex else { return HashMap::new() };
+        let flowed: Vec<(&'static str, FlowItem)> = self.layout.sections.iter()
+            .filter_map(|s| s.flow.map(|f| (s.name, f)))
+            .collect();
+
+        let items: Vec<Item> = flowed.iter().map(|(name, f)| {
+            let measured = self.flow_sizes.get(name).copied().unwrap_or_default();
+            let (main, cross) = match flex.direction {
+                Direction::Column => (measured.y, measured.x),
+                Direction::Row    => (measured.x, measured.y),
+            };
+            // A grower's measured size already includes last frame's share.
+            let natural = if f.grow > 0.0 { 0.0 } else { main };
+            Item { main: f.basis.unwrap_or(natural), cross, grow: f.grow, margin: f.margin, align: f.align }
+        }).collect();
+
+        let (win, pad) = (self.layout.win_size, flex.padding);
+        let area = crate::flex::Rect {
+            x: pad.left,
+            y: pad.top,
+            w: (win.x - pad.left - pad.right).max(0.0),
+            h: (win.y - pad.top - pad.bottom).max(0.0),
+        };
+        let rects = crate::flex::layout(area, flex.direction, flex.gap, &items);
+
+        flowed.iter().zip(rects).map(|((name, f), r)| {
+            let x       = if self.layout.rtl { win.x - r.x - r.w } else { r.x };
+            let rect    = eframe::egui::Rect::from_min_size(eframe::egui::pos2(x, r.y), eframe::egui::vec2(r.w, r.h));
+            let main    = f.basis.is_some() || f.grow > 0.0;
+            let cross   = f.align == Align::Stretch;
+            let fixed   = match flex.direction {
+                Direction::Column => eframe::egui::Vec2b::new(cross, main),
+                Direction::Row    => eframe::egui::Vec2b::new(main, cross),
+            };
+            (*name, (rect, fixed))
+        }).collect()
+    }
+
+    /// `--theme`, else the profile for the desktop's color scheme, else `theme`.
+    fn profile_for(&self, config: &Config) -> String {
+        let scheme = self.appearance.as_ref().map_or(crate::appearance::ColorScheme::NoPreference, |a| a.scheme());
+        self.theme_override.clone().unwrap_or_else(|| crate::appearance::profile_for(config, scheme).to_string())
+    }
+
+    fn reload_settings(&mut self, ctx: &eframe::egui::Context) {
+        let base = Theme::load("");
+        let mut config = crate::config::load(&base);
+        config.daemon_mode = self.config.daemon_mode;
+        if config.theme != self.config.theme { self.theme_override = None; }
+        if self.appearance.is_none() {
+            // dark-theme / light-theme just set: the watcher's first read triggers another reload.
+            let (ctx, pending) = (ctx.clone(), Arc::clone(&self.reload_pending));
+            self.appearance = crate::appearance::AppearanceWatcher::new(&config, crate::appearance::ColorScheme::NoPreference, move || {
+                pending.store(true, Ordering::SeqCst);
+                ctx.request_repaint();
+            });
+        }
+        let profile = self.profile_for(&config);
+        let theme = Arc::new(if profile.is_empty() { base } else { Theme::load(&profile) });
+        self.theme_profiles = Theme::profiles();
+
+        let layout = LayoutCache::build(&theme, &config, self.screen);
+        if layout.win_size != self.layout.win_size && self.visible {
+            ctx.send_viewport_cmd(eframe::egui::ViewportCommand::InnerSize(layout.win_size));
+        }
+        apply_motion(ctx, &layout.motion, config.animations);
+        ctx.set_zoom_factor(ui_scale(&config));
+        warn_legacy_scaling(&theme);
+        let faces = theme.font_faces();
+        if faces != self.theme.font_faces() { crate::fonts::install(ctx, &faces); }
+        if layout.symbolic_color != self.layout.symbolic_color || config.animate_icons != self.config.animate_icons {
+            self.icon_manager = crate::app_launcher::IconManager::new()
+                .with_symbolic_color(layout.symbolic_color)
+                .with_animation(config.animate_icons);
+            self.tray_textures.clear();
+        }
+        self.app.set_config(config.clone());
+        self.bindings    = key_bindings(&config);
+        self.layout      = layout;
+        self.theme       = theme;
+        self.config      = config;
+        self.cached_time = self.app.get_time();
+        self.flow_sizes.clear();
+    }
+
     fn render_search_bar(&mut self, ui: &mut eframe::egui::Ui) {
         with_alignment(ui, &self.theme, "search-bar", |ui| {
             self.theme.apply_style(ui, "search-bar");
             let (base, hover, round) = self.theme.get_frame_props("search-bar", ui.visuals().panel_fill);
             let rect = ui.available_rect_before_wrap();
             let resp = ui.interact(rect, ui.id().with("search-bar"), eframe::egui::Sense::hover());
-            let fill = if resp.hovered() { hover.unwrap_or(base) } else { base };
-            eframe::egui::Frame::NONE.fill(fill).corner_radius(round).show(ui, |ui| {
+            let fill   = if resp.hovered() { hover.unwrap_or(base) } else { base };
+            let border = self.theme.get_border("search-bar", resp.hovered()).unwrap_or(eframe::egui::Stroke::NONE);
+            let shadow = self.theme.get_shadow("search-bar", resp.hovered()).unwrap_or(eframe::egui::Shadow::NONE);
+            eframe::egui::Frame::NONE.fill(fill).corner_radius(round).stroke(border).shadow(shadow).show(ui, |ui| {
                 with_custom_style(ui, |s| {
                     if let Some(tc) = self.theme.get_text_color("search-bar", resp.hovered()) {
                         s.visuals.override_text_color = Some(tc);
                     }
                 }, |ui| {
-                    let mut query = self.app.get_query();
-                    let r = ui.add(eframe::egui::TextEdit::singleline(&mut query).hint_text("Search...").frame(eframe::egui::Frame::NONE));
-                    if !self.focused { r.request_focus(); self.focused = true; }
-                    if r.changed() && !query.starts_with("LAUNCH_OPTIONS:") { self.app.handle_input(&query); }
+                    let mut query = self.composition.buffer(self.app.get_query());
+                    let games_button    = self.config.show_games_button && !self.config.games_prefix.is_empty();
+                    let settings_button = self.config.enable_settings_window;
+                    let mode = self.app.get_mode();
+                    ui.horizontal(|ui| {
+                        if let Some(mode) = &mode { render_mode_badge(ui, &self.theme, mode); }
+                        let mut edit = eframe::egui::TextEdit::singleline(&mut query).hint_text("Search...").frame(eframe::egui::Frame::NONE);
+                        // Room for the buttons after the field.
+                        let buttons = (games_button as u8 + settings_button as u8) as f32 * 24.0;
+                        if buttons > 0.0 || mode.is_some() {
+                            edit = edit.desired_width(ui.available_width() - buttons);
+                        }
+                        if self.layout.rtl { edit = edit.horizontal_align(eframe::egui::Align::Max); }
+                        let r = ui.add(edit);
+                        // Focus stays here while arrows move the selection, so screen
+                        // readers are pointed at the selected result instead.
+                        #[cfg(feature = "accesskit")]
+                        ui.ctx().accesskit_node_builder(r.id, |node| {
+                            node.set_label("Search applications");
+                            if let Some(row) = self.selected_row { node.set_active_descendant(row.value().into()); }
+                        });
+                        if !self.focused { r.request_focus(); self.focused = true; }
+                        if r.changed() && !query.starts_with("LAUNCH_OPTIONS:") {
+                            // Uncommitted input-method text stays in the field.
+                            if let Some(query) = self.composition.edited(&query) { self.app.handle_input(&query); }
+                        }
+                        if games_button {
+                            let in_grid = self.app.is_grid_view();
+                            if icon_hint(ui.selectable_label(in_grid, "🎮"), "Game library").clicked() {
+                                let next = if in_grid { String::new() } else { self.config.games_prefix.clone() };
+                                self.app.handle_input(&next);
+                                self.focused = false;
+                            }
+                        }
+                        if settings_button && icon_hint(ui.button("⚙"), "Settings").clicked() && self.settings_window.is_none() {
+                            self.settings_window = Some(SettingsEditor::new());
+                        }
+                    });
                 })
             });
         });
     }
 
     fn render_volume_slider(&mut self, ui: &mut eframe::egui::Ui) {
-        with_alignment(ui, &self.theme, "volume-slider", |ui| {
+        // Its own handle, so the row can reach `self` for the sink menu.
+        let theme = Arc::clone(&self.theme);
+        with_alignment(ui, &theme, "volume-slider", |ui| {
             self.theme.apply_style(ui, "volume-slider");
-            ui.horizontal(|ui| {
+            let row = ui.horizontal(|ui| {
                 if let Some(gap) = self.layout.vol_gap { ui.spacing_mut().item_spacing.x = gap; }
-                ui.label("Volume:");
-                let (base, hover, round) = self.theme.get_frame_props("volume-slider", ui.style().visuals.widgets.inactive.bg_fill);
-                let vis = { let mut s = ui.style().visuals.widgets.inactive.clone(); s.bg_fill = base; s.corner_radius = round; s };
-                with_custom_style(ui, |s| {
-                    s.visuals.widgets.inactive        = vis.clone();
-                    s.visuals.widgets.hovered.bg_fill = hover.unwrap_or(base);
-                    s.visuals.widgets.hovered.weak_bg_fill = hover.unwrap_or(base);
-                    s.visuals.widgets.active          = vis;
-                    let t = eframe::egui::Color32::TRANSPARENT;
-                    s.visuals.widgets.inactive.bg_stroke = eframe::egui::Stroke::new(0.0, t);
-                    s.visuals.widgets.hovered.bg_stroke  = eframe::egui::Stroke::new(0.0, t);
-                    s.visuals.widgets.active.bg_stroke   = eframe::egui::Stroke::new(0.0, t);
-                    s.visuals.widgets.hovered.expansion  = 0.0;
-                    s.visuals.widgets.active.expansion   = 0.0;
-                }, |ui| {
-                    let slider = eframe::egui::Slider::new(&mut self.current_volume, 0.0..=self.config.max_volume)
-                        .custom_formatter(|n, _| format!("{:.0}%", n * 100.0))
-                        .custom_parser(|s| s.trim().trim_end_matches('%').parse::<f64>().ok().map(|n| n / 100.0));
+                let muted = self.audio_controller.is_muted();
+                let mut text = eframe::egui::RichText::new(if muted { "🔇" } else { "🔊" });
+                if let Some(c) = self.layout.vol_muted_color.filter(|_| muted) { text = text.color(c); }
+                let hint = if muted { "Unmute" } else { "Mute" };
+                if icon_hint(ui.add(eframe::egui::Button::new(text).selected(muted)), hint).clicked() {
+                    let _ = self.audio_controller.toggle_mute();
+                }
+                with_slider_style(ui, &self.theme, "volume-slider", |ui| {
+                    let slider = percent_slider(&mut self.current_volume, self.config.max_volume);
                     if ui.add(slider).changed() { let _ = self.audio_controller.set_volume(self.current_volume); }
                 });
+                if self.config.enable_sink_switcher { self.render_sink_menu(ui); }
             });
+
+            // Whole wheel notches only, so a touchpad doesn't race through the range.
+            if row.response.hovered() {
+                self.volume_scroll += ui.input(|i| i.events.iter().map(|e| match e {
+                    eframe::egui::Event::MouseWheel { unit: eframe::egui::MouseWheelUnit::Point, delta, .. } => delta.y,
+                    eframe::egui::Event::MouseWheel { delta, .. } => delta.y * VOLUME_SCROLL_NOTCH,
+                    _ => 0.0,
+                }).sum::<f32>());
+                let notches = (self.volume_scroll / VOLUME_SCROLL_NOTCH).trunc();
+                if notches != 0.0 {
+                    self.volume_scroll -= notches * VOLUME_SCROLL_NOTCH;
+                    self.current_volume = (self.current_volume + notches * self.config.volume_scroll_step)
+                        .clamp(0.0, self.config.max_volume);
+                    let _ = self.audio_controller.set_volume(self.current_volume);
+                }
+            } else {
+                self.volume_scroll = 0.0;
+            }
         });
     }
 
-    fn render_app_list(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
-        self.theme.apply_style(ui, "app-list");
-        let query    = self.app.get_query();
-        let filtered: Vec<String> = if query.trim().is_empty() && !self.config.enable_recent_apps {
-            Vec::new()
-        } else {
-            self.app.get_search_results().into_iter().take(self.config.max_search_results).collect()
-        };
+    /// Output-device dropdown; hidden while there's only one sink to pick.
+    fn render_sink_menu(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let sinks = self.audio_controller.sinks();
+        if sinks.len() < 2 { return; }
+        let active = sinks.iter().find(|s| s.is_default).map_or("No default output", |s| s.description.as_str());
+
+        let mut choice = None;
+        let menu = ui.menu_button("▾", |ui| {
+            for sink in &sinks {
+                if ui.add(egui::Button::new(sink.description.as_str()).selected(sink.is_default)).clicked() {
+                    choice = Some(sink.id);
+                    ui.close();
+                }
+            }
+        }).response;
+        describe(&menu, egui::WidgetType::Button, &format!("Output device: {active}"));
+        menu.on_hover_text(active);
+        if let Some(id) = choice { let _ = self.audio_controller.set_default_sink(id); }
+    }
+
+    fn render_system_stats(&mut self, ui: &mut eframe::egui::Ui) {
+        let Some((stats, _)) = &self.stats else { return };
+        with_alignment(ui, &self.theme, "system-stats", |ui| {
+            self.theme.apply_style(ui, "system-stats");
+            ui.vertical(|ui| {
+                ui.label(stats.summary());
+                ui.label(stats.usage());
+            });
+        });
+    }
+
+    /// Countdown: preset menu, remaining time, start/pause and reset.
+    fn render_timer(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+        use crate::timer::format_remaining;
+
+        let now    = Instant::now();
+        let timer  = &mut self.timer;
+        let layout = &self.layout;
+        with_alignment(ui, &self.theme, "timer", |ui| {
+            self.theme.apply_style(ui, "timer");
+            ui.horizontal(|ui| {
+                if let Some(gap) = layout.timer_gap { ui.spacing_mut().item_spacing.x = gap; }
+                let current = timer.selected().map_or("Timer", |p| p.label.as_str());
+                let mut choice = None;
+                ui.menu_button(format!("⏱ {current} ▾"), |ui| {
+                    for (i, preset) in timer.presets().iter().enumerate() {
+                        let text = format!("{} · {}", preset.label, format_remaining(preset.duration));
+                        if ui.add(egui::Button::new(text).selected(i == timer.selected_index())).clicked() {
+                            choice = Some(i);
+                            ui.close();
+                        }
+                    }
+                });
+                if let Some(i) = choice { timer.select(i); }
+
+                let finished = timer.is_finished(now);
+                let mut text = egui::RichText::new(if finished { "⏰ Done".to_string() } else { format_remaining(timer.remaining(now)) });
+                if let Some(c) = layout.timer_done_color.filter(|_| finished) { text = text.color(c); }
+                ui.label(text);
+
+                let running = timer.is_running(now);
+                let (icon, hint) = if running { ("⏸", "Pause") } else { ("▶", "Start") };
+                if icon_hint(ui.button(icon), hint).clicked() { timer.toggle(now); }
+                if icon_hint(ui.button("↺"), "Reset").clicked() { timer.reset(); }
+            });
+        });
+    }
+
+    /// Screenshot and recording buttons; a running recording shows in
+    /// `recording-color` until stopped.
+    fn render_quick_actions(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+        use crate::capture::QuickAction;
+
+        let recording = self.recorder.is_recording();
+        let mut clicked = None;
+        with_alignment(ui, &self.theme, "quick-actions", |ui| {
+            self.theme.apply_style(ui, "quick-actions");
+            ui.horizontal_wrapped(|ui| {
+                for (i, action) in self.quick_actions.iter().enumerate() {
+                    let stop = recording && *action == QuickAction::Record;
+                    let button = if stop {
+                        egui::Button::new(egui::RichText::new("⏹ Recording").color(self.layout.recording_color)).selected(true)
+                    } else {
+                        egui::Button::new(action.label())
+                    };
+                    let hint = if stop { "Stop recording and save it" } else { action.hint() };
+                    if ui.add(button).on_hover_text(hint).clicked() { clicked = Some(i); }
+                }
+            });
+        });
+        let Some(action) = clicked.map(|i| self.quick_actions[i].clone()) else { return };
+        let dir = self.config.capture_dir.clone();
+        match action {
+            QuickAction::Screenshot         => crate::capture::screenshot(false, dir),
+            QuickAction::ScreenshotArea     => crate::capture::screenshot(true, dir),
+            QuickAction::Record if recording => { self.recorder.stop(); return; }
+            QuickAction::Record             => self.recorder.start(&dir),
+            QuickAction::Command { command, .. } => crate::capture::run(&command),
+        }
+        // Out of the way of the capture, or of whatever the command opens.
+        self.app.handle_input("ESC");
+    }
+
+    /// One button per workspace; clicking switches to it. The focused one is
+    /// selected, ones holding no windows are dimmed and urgent ones colored.
+    fn render_workspaces(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let Some(host) = &self.workspaces_host else { return };
+        let workspaces = host.list();
+        let layout = &self.layout;
+        let mut clicked = None;
+        with_alignment(ui, &self.theme, "workspaces", |ui| {
+            self.theme.apply_style(ui, "workspaces");
+            ui.horizontal_wrapped(|ui| {
+                if let Some(gap) = layout.workspace_gap { ui.spacing_mut().item_spacing.x = gap; }
+                for ws in &workspaces {
+                    let color = if ws.urgent {
+                        Some(layout.workspace_urgent_color)
+                    } else if ws.windows == 0 && !ws.focused {
+                        layout.workspace_empty_color
+                    } else {
+                        None
+                    };
+                    let mut text = egui::RichText::new(&ws.name);
+                    if let Some(c) = color { text = text.color(c); }
+                    let windows = match ws.windows {
+                        0 => "no windows".to_string(),
+                        1 => "1 window".to_string(),
+                        n => format!("{n} windows"),
+                    };
+                    let hint = format!("Workspace {} on {} · {windows}", ws.name, ws.output);
+                    let resp = ui.add(egui::Button::new(text).selected(ws.focused)).on_hover_text(hint);
+                    if resp.clicked() && !ws.focused { clicked = Some(ws); }
+                }
+            });
+        });
+        if let Some(ws) = clicked { crate::workspaces::switch(ws); }
+    }
+
+    /// Condition icon (the theme's, else an emoji) and temperature; hover for
+    /// details, click to fetch again.
+    fn render_weather(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+
+        let Some(host) = &self.weather_host else { return };
+        let state  = host.state();
+        let icon   = state.icon.as_deref().and_then(|path| self.icon_manager.get_texture(ctx, path));
+        let layout = &self.layout;
+        let mut clicked = false;
+        with_alignment(ui, &self.theme, "weather", |ui| {
+            self.theme.apply_style(ui, "weather");
+            ui.horizontal(|ui| {
+                if let Some(gap) = layout.weather_gap { ui.spacing_mut().item_spacing.x = gap; }
+                let Some(report) = &state.report else {
+                    let text = if state.error.is_some() { "Weather unavailable" } else { "Weather …" };
+                    let resp = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                    let resp = match &state.error {
+                        Some(e) => resp.on_hover_text(format!("{e}\nClick to try again")),
+                        None    => resp,
+                    };
+                    clicked = resp.clicked();
+                    return;
+                };
+                let size = egui::vec2(layout.weather_icon_size, layout.weather_icon_size);
+                let icon = match &icon {
+                    Some(tex) => ui.add(egui::Image::new(tex).fit_to_exact_size(size).sense(egui::Sense::click())),
+                    None      => ui.add(egui::Label::new(report.condition.emoji(report.is_day)).sense(egui::Sense::click())),
+                };
+                let mut text = egui::RichText::new(report.temperature_text());
+                if let Some(c) = layout.weather_stale_color.filter(|_| state.stale) { text = text.color(c); }
+                let temperature = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+
+                let minutes = report.age().as_secs() / 60;
+                let updated = match minutes {
+                    0     => "just now".to_string(),
+                    1..60 => format!("{minutes} min ago"),
+                    _     => format!("{} h ago", minutes / 60),
+                };
+                let mut hint = format!("{}\n{}\nUpdated {updated}", report.description, report.place);
+                if let Some(e) = state.error.as_ref().filter(|_| state.stale) {
+                    hint.push_str(&format!("\nCouldn't refresh: {e}"));
+                }
+                hint.push_str("\nClick to refresh");
+                clicked = icon.union(temperature).on_hover_text(hint).clicked();
+            });
+        });
+        if clicked { host.refresh(); }
+    }
+
+    /// Profile picker; the choice is saved to config.toml and applied by the reload.
+    fn render_theme_switcher(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let active = self.profile_for(&self.config);
+        let mut choice = None;
+        with_alignment(ui, &self.theme, "theme-switcher", |ui| {
+            self.theme.apply_style(ui, "theme-switcher");
+            let current = if active.is_empty() { "Default" } else { active.as_str() };
+            ui.menu_button(format!("🎨 {current} ▾"), |ui| {
+                let names = std::iter::once("").chain(self.theme_profiles.iter().map(String::as_str));
+                for name in names {
+                    let label = if name.is_empty() { "Default" } else { name };
+                    if ui.add(egui::Button::new(label).selected(name == active)).clicked() {
+                        choice = Some(name.to_string());
+                        ui.close();
+                    }
+                }
+            });
+        });
+        if let Some(name) = choice {
+            match crate::config::set_value("theme", &name) {
+                Ok(()) => {
+                    self.theme_override = None;
+                    self.reload_pending.store(true, Ordering::SeqCst);
+                }
+                Err(e) => tracing::warn!(target: "config", "Failed to save theme choice: {e}"),
+            }
+        }
+    }
+
+    /// Caffeine toggle: holds a logind idle inhibitor while on.
+    fn render_idle_inhibitor(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        with_alignment(ui, &self.theme, "idle-inhibitor", |ui| {
+            self.theme.apply_style(ui, "idle-inhibitor");
+            let Some(host) = &self.logind_host else { return };
+            let active = host.state.lock().is_ok_and(|s| s.idle_inhibited);
+            let mut text = egui::RichText::new(if active { "☕ Staying awake" } else { "☕ Idle allowed" });
+            if let Some(c) = self.layout.idle_active_color.filter(|_| active) { text = text.color(c); }
+            let hint = if active { "Let the screen blank and lock again" } else { "Keep the screen from blanking or locking" };
+            if ui.add(egui::Button::new(text).selected(active)).on_hover_text(hint).clicked() {
+                host.set_idle_inhibit(!active);
+                if let Err(e) = crate::app_launcher::set_idle_inhibit(!active) {
+                    tracing::warn!(target: "power", "Failed to save idle inhibitor state: {e}");
+                }
+            }
+        });
+    }
+
+    /// Microphone mute toggle and input level for the default source.
+    fn render_mic_control(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let audio  = &self.audio_controller;
+        let theme  = &self.theme;
+        let layout = &self.layout;
+        with_alignment(ui, theme, "mic-control", |ui| {
+            theme.apply_style(ui, "mic-control");
+            ui.horizontal(|ui| {
+                if let Some(gap) = layout.mic_gap { ui.spacing_mut().item_spacing.x = gap; }
+                if !audio.has_source() {
+                    ui.add_enabled(false, egui::Label::new("No microphone"));
+                    return;
+                }
+                let muted = audio.is_source_muted();
+                let mut text = egui::RichText::new(if muted { "🎤 Muted" } else { "🎤" });
+                if let Some(c) = layout.mic_muted_color.filter(|_| muted) { text = text.color(c); }
+                let hint = if muted { "Unmute microphone" } else { "Mute microphone" };
+                if icon_hint(ui.add(egui::Button::new(text).selected(muted)), hint).clicked() {
+                    let _ = audio.toggle_source_mute();
+                }
+                let mut level = audio.get_source_volume();
+                with_slider_style(ui, theme, "mic-control", |ui| {
+                    if ui.add_enabled(!muted, percent_slider(&mut level, 1.0)).changed() { let _ = audio.set_source_volume(level); }
+                });
+            });
+        });
+    }
 
+    fn render_app_list(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
+        self.theme.apply_style(ui, "app-list");
+        self.selected_row = None;
+        if self.app.is_grid_view() {
+            self.render_game_grid(ui, ctx);
+            return;
+        }
+        if self.config.show_category_chips && !self.config.category_prefix.is_empty() {
+            self.render_category_chips(ui);
+        }
+        let filtered = self.visible_results();
+        let browsing = self.browsing();
+        if browsing { self.render_letter_index(ui, &filtered); }
+        if self.app_grid_active() {
+            self.render_app_grid(ui, ctx, filtered);
+            return;
+        }
+        if browsing {
+            eframe::egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                self.render_app_rows(ui, ctx, filtered);
+            });
+        } else {
+            self.render_app_rows(ui, ctx, filtered);
+        }
+    }
+
+    fn render_app_rows(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context, filtered: Vec<ResultRow>) {
+        let (_, hover, round) = self.theme.get_frame_props("app-button", eframe::egui::Color32::TRANSPARENT);
+        let lit = hover.unwrap_or(ui.visuals().widgets.hovered.weak_bg_fill);
+        // Right to left, the row's elements are laid out from the right edge.
+        let row_layout = if self.layout.rtl {
+            eframe::egui::Layout::right_to_left(eframe::egui::Align::Center)
+        } else {
+            eframe::egui::Layout::left_to_right(eframe::egui::Align::Center)
+        };
+        let count = filtered.len();
+        // A copy, so the row's buttons can act on `self` while it's walked.
+        let elem_order = self.layout.elem_order.clone();
         ui.vertical(|ui| {
-            for app_name in filtered {
-                let _row_id = ui.id().with(&app_name);
-                ui.horizontal(|ui| {
-                    for &kind in &self.layout.elem_order {
+            for (index, result) in filtered.into_iter().enumerate() {
+                let app_name = &result.id;
+                let _row_id = ui.id().with(app_name);
+                // Filled in once the row's size is known: the keyboard selection looks like hover.
+                let backdrop = ui.painter().add(eframe::egui::Shape::Noop);
+                let row = ui.with_layout(row_layout, |ui| {
+                    for &kind in &elem_order {
                         match kind {
                             ElemKind::Settings if self.config.show_settings_button => {
                                 let (w, h)   = (self.layout.settings_w, self.layout.settings_h);
@@ -1156,8 +3604,9 @@ impl EframeWrapper {
                                     .unwrap_or(eframe::egui::Color32::from_rgb(64, 64, 64));
                                 let font = eframe::egui::TextStyle::Button.resolve(ui.style());
                                 ui.painter().text(paint_center, eframe::egui::Align2::CENTER_CENTER, "⚙", font, color);
+                                describe(&resp, eframe::egui::WidgetType::Button, "Launch options");
                                 if resp.clicked() {
-                                    self.editing_windows.insert(app_name.clone(), self.app.get_formatted_launch_options(&app_name));
+                                    self.open_launch_editor(&result);
                                 }
                             }
                             ElemKind::Icon if self.config.enable_icons => {
@@ -1167,14 +3616,14 @@ impl EframeWrapper {
                                     eframe::egui::vec2(self.layout.icon_w, self.layout.icon_h),
                                     eframe::egui::Sense::hover(),
                                 );
-                                if let Some(icon_path) = self.app.get_icon_path(&app_name) {
-                                    if let Some(tex) = self.icon_manager.get_texture(ctx, &icon_path) {
-                                        ui.painter().image(
-                                            tex.id(), rect,
-                                            eframe::egui::Rect::from_min_max(eframe::egui::Pos2::ZERO, eframe::egui::Pos2::new(1.0, 1.0)),
-                                            eframe::egui::Color32::WHITE,
-                                        );
-                                    }
+                                if let Some(icon_path) = &result.icon
+                                    && let Some(tex) = self.icon_manager.get_texture(ctx, icon_path)
+                                {
+                                    ui.painter().image(
+                                        tex.id(), rect,
+                                        eframe::egui::Rect::from_min_max(eframe::egui::Pos2::ZERO, eframe::egui::Pos2::new(1.0, 1.0)),
+                                        eframe::egui::Color32::WHITE,
+                                    );
                                 }
                             }
                             ElemKind::App => {
@@ -1183,13 +3632,14 @@ impl EframeWrapper {
                                     .get(&eframe::egui::TextStyle::Button).cloned().unwrap_or_default();
                                 let pad = ui.spacing().button_padding;
                                 let avail_text_w = (btn_w - pad.x * 2.0).max(0.0);
+                                let label = result.title.clone();
                                 let full_text_w = ui.painter().layout_no_wrap(
-                                    app_name.clone(), font_id, eframe::egui::Color32::WHITE,
+                                    label.clone(), font_id, eframe::egui::Color32::WHITE,
                                 ).size().x;
                                 // Marquee on hover when text overflows; truncate with … otherwise.
                                 let scroll_offset = if full_text_w > avail_text_w {
                                     let hover_rect = eframe::egui::Rect::from_min_size(
-                                        ui.cursor().min, eframe::egui::vec2(btn_w, 22.0),
+                                        ui.available_rect_before_wrap().min, eframe::egui::vec2(btn_w, 22.0),
                                     );
                                     if ui.rect_contains_pointer(hover_rect) {
                                         let max_scroll = full_text_w - avail_text_w + 20.0;
@@ -1199,28 +3649,876 @@ impl EframeWrapper {
                                         ctx.request_repaint();
                                         Some(off.max(0.0))
                                     } else {
-                                        self.scroll_offsets.remove(&app_name);
+                                        self.scroll_offsets.remove(app_name);
                                         None
                                     }
                                 } else {
-                                    self.scroll_offsets.remove(&app_name);
+                                    self.scroll_offsets.remove(app_name);
                                     None
                                 };
-                                let resp = custom_button_scroll(ui, &app_name, "app-button",
+                                let mut resp = custom_button_scroll(ui, &label, "app-button",
                                     &self.theme, Some(btn_w), scroll_offset);
-                                if resp.clicked()           { self.app.launch_app(&app_name); }
-                                if resp.secondary_clicked() {
-                                    self.editing_windows.insert(app_name.clone(),
-                                        self.app.get_formatted_launch_options(&app_name));
+                                if let Some(desc) = &result.subtitle {
+                                    resp = resp.on_hover_text(crate::bidi::visual(desc).into_owned());
                                 }
+                                set_position(ctx, resp.id, index, count);
+                                if index == self.selection.1 { self.selected_row = Some(resp.id); }
+                                if resp.clicked()           { self.app.launch_app(app_name); }
+                                self.result_menu(&resp, &result, btn_w);
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+                if index == self.selection.1 {
+                    ui.painter().set(backdrop, eframe::egui::Shape::rect_filled(row.response.rect, round, lit));
+                    if self.selection_moved { ui.scroll_to_rect(row.response.rect, None); }
+                }
+                if self.browse_jump == Some(index_letter(&result.title)) {
+                    ui.scroll_to_rect(row.response.rect, Some(eframe::egui::Align::TOP));
+                    self.browse_jump = None;
+                }
+                ui.add_space(4.0);
+            }
+        });
+        self.selection_moved = false;
+    }
+
+    /// Results shown under the search field: nothing for an empty query
+    /// unless recent apps are on, and at most `max-search-results` (all of
+    /// them when browsing).
+    fn visible_results(&self) -> Vec<ResultRow> {
+        if self.browsing() { return self.app.get_search_results(); }
+        if self.app.get_query().trim().is_empty() && !self.config.enable_recent_apps {
+            return Vec::new();
+        }
+        self.app.get_search_results().into_iter().take(self.config.max_search_results).collect()
+    }
+
+    /// Browsing a menu rather than searching: every app for an empty query
+    /// with `show-all-apps`, or one category (`cat:games`). Scrolling, A–Z.
+    fn browsing(&self) -> bool {
+        let query = self.app.get_query();
+        let all   = self.config.show_all_apps && query.trim().is_empty();
+        !self.app.is_grid_view()
+            && (all || crate::app_launcher::category_query(&query, &self.config.category_prefix).is_some())
+    }
+
+    /// "All" plus a chip per menu category, shown while browsing or with an
+    /// empty query; a click lists that category.
+    fn render_category_chips(&mut self, ui: &mut eframe::egui::Ui) {
+        let query  = self.app.get_query();
+        let prefix = self.config.category_prefix.clone();
+        let active = crate::app_launcher::category_query(&query, &prefix).map(|(name, _)| name);
+        if active.is_none() && !query.trim().is_empty() { return; }
+        let categories = self.app.get_categories();
+        with_custom_style(ui, |s| {
+            self.theme.apply_widget_style(s, "category-chip");
+            if let Some(c) = self.theme.get("category-chip", "active-color").and_then(|c| self.theme.parse_color(&c)) {
+                s.visuals.selection.bg_fill = c;
+            }
+        }, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for category in std::iter::once(None).chain(categories.into_iter().map(Some)) {
+                    let label = category.unwrap_or("All");
+                    if ui.add(eframe::egui::Button::new(label).selected(category == active)).clicked() {
+                        let next = category.map_or(String::new(), |c| format!("{prefix}{} ", c.to_lowercase()));
+                        self.app.handle_input(&next);
+                        self.focused = false;
+                    }
+                }
+            });
+        });
+    }
+
+    /// The letters that start an app in `apps`, as a strip of links
+    /// (`.app-index`); a click scrolls the list to that letter.
+    fn render_letter_index(&mut self, ui: &mut eframe::egui::Ui, apps: &[ResultRow]) {
+        use eframe::egui;
+        let mut letters: Vec<char> = Vec::new();
+        for letter in apps.iter().map(|row| index_letter(&row.title)) {
+            if !letters.contains(&letter) { letters.push(letter); }
+        }
+        let size = self.theme.get_px("app-index", "font-size").unwrap_or(10.0);
+        let font = egui::FontId::new(size, egui::FontFamily::Proportional);
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(size * 0.5, 0.0);
+            for letter in letters {
+                let text = letter.to_string();
+                let width = ui.painter().layout_no_wrap(text.clone(), font.clone(), egui::Color32::WHITE).size().x;
+                let (rect, resp) = ui.allocate_exact_size(egui::vec2(width, size * 1.3), egui::Sense::click());
+                describe(&resp, egui::WidgetType::Button, &format!("Jump to {letter}"));
+                let color = self.theme.get_text_color("app-index", resp.hovered()).unwrap_or(egui::Color32::GRAY);
+                ui.painter().text(rect.center(), egui::Align2::CENTER_CENTER, text, font.clone(), color);
+                if resp.clicked() { self.browse_jump = Some(letter); }
+            }
+        });
+    }
+
+    /// `results-view = "grid"`, except in the games view, which has its own grid.
+    fn app_grid_active(&self) -> bool {
+        self.config.results_view == "grid" && !self.app.is_grid_view()
+    }
+
+    /// Right-click menu of a result: the row's actions, a separator
+    /// between launching, the other ways to use it, and managing it.
+    fn result_menu(&mut self, resp: &eframe::egui::Response, row: &ResultRow, btn_w: f32) {
+        resp.context_menu(|ui| {
+            for (i, action) in row.actions.iter().enumerate() {
+                if i > 0 && action_section(action) != action_section(&row.actions[i - 1]) { ui.separator(); }
+                let label = action.label(row.kind);
+                if custom_button_width(ui, label, "app-button", &self.theme, Some(btn_w)).clicked() {
+                    self.run_row_action(row, action);
+                    ui.close();
+                }
+            }
+        });
+    }
+
+    fn run_row_action(&mut self, row: &ResultRow, action: &RowAction) {
+        match action {
+            RowAction::EditLaunchOptions => self.open_launch_editor(row),
+            _                            => self.app.run_action(&row.id, action),
+        }
+    }
+
+    /// The row Enter would open: the keyboard selection, the top row by default.
+    fn selected_row(&self) -> Option<ResultRow> {
+        self.visible_results().into_iter().nth(self.selection.1)
+    }
+
+    /// While the action menu is open the arrow keys, Enter and Escape drive
+    /// it instead of the search field.
+    fn action_menu_keys(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui::{Key, Modifiers};
+        if self.action_menu.is_none() { return; }
+        let (up, down, enter, esc) = ctx.input_mut(|i| (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Escape),
+        ));
+        let Some((row, cursor)) = &mut self.action_menu else { return };
+        let count = row.actions.len().max(1);
+        if up   { *cursor = (*cursor + count - 1) % count; }
+        if down { *cursor = (*cursor + 1) % count; }
+        if esc {
+            self.action_menu = None;
+        } else if enter {
+            let Some((row, cursor)) = self.action_menu.take() else { return };
+            if let Some(action) = row.actions.get(cursor) { self.run_row_action(&row, action); }
+        }
+    }
+
+    /// The keyboard action menu, centered over the window.
+    fn render_action_menu(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let Some((row, cursor)) = self.action_menu.clone() else { return };
+        let mut picked = None;
+        let shown = egui::Area::new("action_menu".into())
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Frame::menu(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(crate::bidi::visual(&row.title)).strong());
+                    ui.separator();
+                    with_custom_style(ui, |s| self.theme.apply_widget_style(s, "app-button"), |ui| {
+                        for (i, action) in row.actions.iter().enumerate() {
+                            if i > 0 && action_section(action) != action_section(&row.actions[i - 1]) { ui.separator(); }
+                            let button = egui::Button::new(action.label(row.kind)).selected(i == cursor);
+                            if ui.add_sized([ui.available_width().max(160.0), 0.0], button).clicked() {
+                                picked = Some(action.clone());
+                            }
+                        }
+                    });
+                });
+            });
+        if let Some(action) = picked {
+            self.action_menu = None;
+            self.run_row_action(&row, &action);
+        } else if shown.response.clicked_elsewhere() {
+            self.action_menu = None;
+        }
+    }
+
+    fn open_launch_editor(&mut self, row: &ResultRow) {
+        if !row.kind.is_app() { return; }
+        if !self.editing_windows.contains_key(&row.id) {
+            self.editing_windows.insert(row.id.clone(), LaunchEditor::new(&*self.app, row));
+        }
+    }
+
+    /// One window per app being edited: environment variables as a table, a
+    /// working folder with a picker, the command template with a preview of
+    /// what will run, and the app's display name and icon.
+    fn render_launch_editors(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let (win_bg, env_w, env_h) = (self.layout.win_bg, self.layout.env_w, self.layout.env_h);
+        let error_color = self.theme.get("env-input", "error-color")
+            .and_then(|s| self.theme.parse_color(&s))
+            .unwrap_or(egui::Color32::from_rgb(235, 87, 87));
+        let mut closed = Vec::new();
+
+        for (app_name, editor) in self.editing_windows.iter_mut() {
+            if let Some(rx) = &editor.picking {
+                match rx.try_recv() {
+                    Ok(picked) => {
+                        if let Some(dir) = picked { editor.dir = dir; }
+                        editor.picking = None;
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => editor.picking = None,
+                    Err(std::sync::mpsc::TryRecvError::Empty)        => {}
+                }
+            }
+            // Description doubles as app info here (e.g. a Steam title's Proton version).
+            let info     = editor.info.clone();
+            let preview  = self.app.preview_launch_command(app_name, &editor.options());
+            let theme    = &self.theme;
+            let vp_id    = egui::ViewportId::from_hash_of(format!("env_{app_name}"));
+            let viewport = egui::ViewportBuilder::default()
+                .with_title(app_name.clone())
+                .with_inner_size([env_w, env_h])
+                .with_resizable(false).with_transparent(true).with_always_on_top();
+
+            // Some(true) saves, Some(false) cancels.
+            let mut action = None;
+            ctx.show_viewport_immediate(vp_id, viewport, |ctx, _| {
+                #[allow(deprecated)]
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE.fill(win_bg))
+                    .show(ctx, |ui| {
+                        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                            ui.label(app_name.as_str());
+                            if let Some(info) = &info {
+                                ui.label(egui::RichText::new(info).small().weak());
+                            }
+                            with_alignment(ui, theme, "env-input", |ui| {
+                                theme.apply_style(ui, "env-input");
+                                let w = ui.available_width();
+
+                                ui.add_space(4.0);
+                                ui.label("Environment");
+                                let mut remove = None;
+                                egui::Grid::new(("env_vars", app_name.as_str())).num_columns(3).show(ui, |ui| {
+                                    for (i, (key, value)) in editor.env.iter_mut().enumerate() {
+                                        ui.add(egui::TextEdit::singleline(key).hint_text("NAME").desired_width(w * 0.35));
+                                        ui.add(egui::TextEdit::singleline(value).hint_text("value").desired_width(w * 0.5));
+                                        if ui.small_button("✕").clicked() { remove = Some(i); }
+                                        ui.end_row();
+                                    }
+                                });
+                                if let Some(i) = remove { editor.env.remove(i); }
+                                if ui.small_button("+ Variable").clicked() { editor.env.push(Default::default()); }
+
+                                ui.add_space(4.0);
+                                ui.label("Working directory");
+                                ui.horizontal(|ui| {
+                                    let browse = ui.add_enabled(editor.picking.is_none(), egui::Button::new("Browse…"));
+                                    ui.add(egui::TextEdit::singleline(&mut editor.dir).hint_text("~").desired_width(f32::INFINITY));
+                                    if browse.clicked() {
+                                        // The dialog blocks, so it runs off the UI thread.
+                                        let (tx, rx)     = std::sync::mpsc::channel();
+                                        let (start, ctx) = (editor.dir.clone(), ctx.clone());
+                                        std::thread::spawn(move || {
+                                            let _ = tx.send(crate::system::pick_folder(&start));
+                                            ctx.request_repaint();
+                                        });
+                                        editor.picking = Some(rx);
+                                    }
+                                });
+
+                                ui.add_space(4.0);
+                                ui.label("Command");
+                                ui.add(egui::TextEdit::singleline(&mut editor.command)
+                                    .hint_text("e.g. gamemoderun %command%")
+                                    .desired_width(f32::INFINITY));
+                                if !preview.is_empty() {
+                                    ui.label(egui::RichText::new(format!("Runs: {preview}")).monospace().weak());
+                                }
+
+                                ui.add_space(4.0);
+                                ui.label("Display name and icon");
+                                ui.add(egui::TextEdit::singleline(&mut editor.name)
+                                    .hint_text(app_name.as_str())
+                                    .desired_width(f32::INFINITY));
+                                ui.add(egui::TextEdit::singleline(&mut editor.icon)
+                                    .hint_text("Icon file or name")
+                                    .desired_width(f32::INFINITY));
+                            });
+                            for problem in &editor.problems {
+                                ui.colored_label(error_color, problem);
+                            }
+                            ui.add_space(4.0);
+                            ui.horizontal(|ui| {
+                                if custom_button(ui, "Save",   "edit-button", theme).clicked() { action = Some(true); }
+                                if custom_button(ui, "Cancel", "edit-button", theme).clicked() { action = Some(false); }
+                            });
+                        });
+                        if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested()) {
+                            action = Some(false);
+                        }
+                    });
+            });
+
+            match action {
+                Some(true) => {
+                    editor.problems = crate::app_launcher::validate_launch_options(&editor.env, &editor.dir, &editor.command);
+                    if editor.problems.is_empty() {
+                        self.app.set_launch_options(app_name, editor.options());
+                        self.app.set_app_override(app_name, editor.app_override());
+                        closed.push(app_name.clone());
+                    }
+                }
+                Some(false) => closed.push(app_name.clone()),
+                None        => {}
+            }
+        }
+        for app_name in closed {
+            ctx.send_viewport_cmd_to(egui::ViewportId::from_hash_of(format!("env_{app_name}")), egui::ViewportCommand::Close);
+            self.editing_windows.remove(&app_name);
+        }
+    }
+
+    /// Every setting config.toml documents, by section: checkboxes for
+    /// switches, text for values, one line per item for lists, and the theme
+    /// profiles to pick from. Apply writes what changed and reloads.
+    fn render_settings_window(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let Some(editor) = self.settings_window.as_mut() else { return };
+        let (win_bg, theme, profiles) = (self.layout.win_bg, &self.theme, &self.theme_profiles);
+        let error_color = theme.get("env-input", "error-color")
+            .and_then(|s| theme.parse_color(&s))
+            .unwrap_or(egui::Color32::from_rgb(235, 87, 87));
+        let viewport = egui::ViewportBuilder::default()
+            .with_title("Tusk Launcher settings")
+            .with_inner_size([theme.get_px("settings-window", "width").unwrap_or(460.0), theme.get_px("settings-window", "height").unwrap_or(560.0)])
+            .with_transparent(true).with_always_on_top();
+
+        // Some(true) writes and closes, Some(false) just writes, via `apply`.
+        let (mut apply, mut close) = (None, false);
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of("settings"), viewport, |ctx, _| {
+            #[allow(deprecated)]
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE.fill(win_bg).inner_margin(8.0))
+                .show(ctx, |ui| {
+                    theme.apply_style(ui, "env-input");
+                    ui.add(egui::TextEdit::singleline(&mut editor.filter).hint_text("Filter settings…").desired_width(f32::INFINITY));
+                    let filter = editor.filter.to_lowercase();
+                    ui.label(egui::RichText::new("Background services, search providers and daemon-mode change at the next start.").small().weak());
+                    ui.add_space(4.0);
+                    let footer = 28.0 + editor.problems.len() as f32 * 18.0;
+                    egui::ScrollArea::vertical().auto_shrink([false, false]).max_height(ui.available_height() - footer).show(ui, |ui| {
+                        let mut sections: Vec<&str> = editor.settings.iter().map(|s| s.section).collect();
+                        sections.dedup();
+                        for section in sections {
+                            let shown: Vec<usize> = (0..editor.settings.len()).filter(|&i| {
+                                let setting = &editor.settings[i];
+                                setting.section == section
+                                    && (filter.is_empty() || setting.key.contains(&filter) || setting.help.to_lowercase().contains(&filter))
+                            }).collect();
+                            if shown.is_empty() { continue; }
+                            egui::CollapsingHeader::new(section)
+                                .open((!filter.is_empty()).then_some(true))
+                                .show(ui, |ui| {
+                                    for i in shown {
+                                        let setting = &editor.settings[i];
+                                        match &mut editor.fields[i] {
+                                            SettingField::Toggle(on) => { ui.checkbox(on, setting.key); }
+                                            SettingField::Text(text) if THEME_SETTINGS.contains(&setting.key) => {
+                                                ui.horizontal(|ui| {
+                                                    ui.label(setting.key);
+                                                    let shown = if text.is_empty() { "Default" } else { text.as_str() };
+                                                    egui::ComboBox::from_id_salt(setting.key).selected_text(shown.to_string()).show_ui(ui, |ui| {
+                                                        ui.selectable_value(text, String::new(), "Default");
+                                                        for name in profiles { ui.selectable_value(text, name.clone(), name.as_str()); }
+                                                    });
+                                                });
+                                            }
+                                            SettingField::Text(text) => {
+                                                ui.horizontal(|ui| {
+                                                    ui.label(setting.key);
+                                                    ui.add(egui::TextEdit::singleline(text).desired_width(f32::INFINITY));
+                                                });
+                                            }
+                                            SettingField::Lines(text) => {
+                                                ui.label(setting.key);
+                                                let rows = text.lines().count().clamp(1, 6);
+                                                ui.add(egui::TextEdit::multiline(text).desired_rows(rows).desired_width(f32::INFINITY));
+                                            }
+                                        }
+                                        if !setting.help.is_empty() {
+                                            ui.label(egui::RichText::new(&setting.help).small().weak());
+                                        }
+                                        ui.add_space(4.0);
+                                    }
+                                });
+                        }
+                    });
+                    for problem in &editor.problems {
+                        ui.colored_label(error_color, problem);
+                    }
+                    ui.horizontal(|ui| {
+                        if custom_button(ui, "Save",   "edit-button", theme).clicked() { apply = Some(true); }
+                        if custom_button(ui, "Apply",  "edit-button", theme).clicked() { apply = Some(false); }
+                        if custom_button(ui, "Cancel", "edit-button", theme).clicked() { close = true; }
+                    });
+                    if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested()) {
+                        close = true;
+                    }
+                });
+        });
+
+        if let Some(then_close) = apply {
+            match editor.changes() {
+                Ok(changes) => {
+                    editor.problems.clear();
+                    match crate::config::set_values(&changes) {
+                        Ok(()) => {
+                            editor.saved = editor.fields.clone();
+                            self.reload_pending.store(true, Ordering::SeqCst);
+                            close |= then_close;
+                        }
+                        Err(e) => editor.problems.push(format!("Failed to save config.toml: {e}")),
+                    }
+                }
+                Err(problems) => editor.problems = problems,
+            }
+        }
+        if close {
+            ctx.send_viewport_cmd_to(egui::ViewportId::from_hash_of("settings"), egui::ViewportCommand::Close);
+            self.settings_window = None;
+        }
+    }
+
+    /// `tusk-launcher tray-debug`: each tray item the SNI host probed (and,
+    /// on request, each bus name it scanned), what came of it and the
+    /// signals it has sent since, for finding out why an icon doesn't show.
+    fn render_tray_debug(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let Some(every_name) = self.tray_debug.as_mut() else { return };
+        let diag = self.sni_host.as_ref().map(|host| host.items.diagnostics());
+        let (win_bg, theme) = (self.layout.win_bg, &self.theme);
+        let viewport = egui::ViewportBuilder::default()
+            .with_title("Tray debug")
+            .with_inner_size([680.0, 440.0])
+            .with_transparent(true).with_always_on_top();
+        let ago = |at: Instant| format!("{}s ago", at.elapsed().as_secs());
+
+        let mut close = false;
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of("tray_debug"), viewport, |ctx, _| {
+            #[allow(deprecated)]
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE.fill(win_bg).inner_margin(8.0))
+                .show(ctx, |ui| {
+                    theme.apply_style(ui, "env-input");
+                    match &diag {
+                        None => { ui.label("The system tray is off (enable-system-tray = false), so nothing was probed."); }
+                        Some(diag) => {
+                            let watcher = match diag.watcher_claimed {
+                                Some(true)  => "claimed by the launcher",
+                                Some(false) => "held by another process",
+                                None        => "not tried yet",
+                            };
+                            let shown = diag.probes.values().filter(|p| p.outcome == "shown").count();
+                            ui.label(format!("Watcher {watcher} · {} registrations · {shown} items shown", diag.registrations));
+                            ui.checkbox(every_name, "Also list the bus names that were scanned");
+                            egui::ScrollArea::both().auto_shrink([false, false]).max_height(ui.available_height() - 32.0).show(ui, |ui| {
+                                egui::Grid::new("tray_debug_grid").striped(true).num_columns(5).show(ui, |ui| {
+                                    for heading in ["Name", "Outcome", "Properties via", "Signals", "Last signal"] { ui.strong(heading); }
+                                    ui.end_row();
+                                    // Items are `bus/path`; bare bus names are scan results.
+                                    for (name, probe) in diag.probes.iter().filter(|(name, _)| *every_name || name.contains('/')) {
+                                        ui.monospace(name);
+                                        ui.label(format!("{} ({})", probe.outcome, ago(probe.at)));
+                                        ui.label(probe.interface.as_deref().unwrap_or("—"));
+                                        ui.label(probe.signals.to_string());
+                                        match &probe.last_signal {
+                                            Some((member, at)) => ui.label(format!("{member}, {}", ago(*at))),
+                                            None               => ui.label("—"),
+                                        };
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if let Some(diag) = &diag && custom_button(ui, "Copy report", "edit-button", theme).clicked() {
+                            ctx.copy_text(diag.report());
+                        }
+                        if custom_button(ui, "Close", "edit-button", theme).clicked() { close = true; }
+                    });
+                    if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested()) {
+                        close = true;
+                    }
+                });
+        });
+        // Keeps the ages current.
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        if close {
+            ctx.send_viewport_cmd_to(egui::ViewportId::from_hash_of("tray_debug"), egui::ViewportCommand::Close);
+            self.tray_debug = None;
+        }
+    }
+
+    /// Background threads that panicked: what they said and where the crash
+    /// report is. The rest of the launcher keeps running without them.
+    fn render_crashes(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        if self.crashes.is_empty() { return; }
+        let (win_bg, theme, crashes) = (self.layout.win_bg, &self.theme, &self.crashes);
+        let viewport = egui::ViewportBuilder::default()
+            .with_title("Tusk Launcher ran into a problem")
+            .with_inner_size([520.0, 240.0])
+            .with_transparent(true).with_always_on_top();
+
+        let mut close = false;
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of("crash"), viewport, |ctx, _| {
+            #[allow(deprecated)]
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE.fill(win_bg).inner_margin(8.0))
+                .show(ctx, |ui| {
+                    theme.apply_style(ui, "env-input");
+                    egui::ScrollArea::vertical().auto_shrink([false, false]).max_height(ui.available_height() - 32.0).show(ui, |ui| {
+                        for crash in crashes {
+                            let thread = match crash.thread.as_str() {
+                                "unnamed" => "A background thread".to_string(),
+                                name      => format!("The {name} thread"),
+                            };
+                            ui.label(format!("{thread} stopped: {}", crash.message));
+                            match &crash.path {
+                                Some(path) => {
+                                    ui.monospace(path.display().to_string());
+                                    ui.horizontal(|ui| {
+                                        if custom_button(ui, "Open report", "edit-button", theme).clicked() {
+                                            crate::system::open_uri(&path.display().to_string());
+                                        }
+                                        if custom_button(ui, "Copy path", "edit-button", theme).clicked() {
+                                            ctx.copy_text(path.display().to_string());
+                                        }
+                                    });
+                                }
+                                None => { ui.label("The crash report couldn't be saved; the log has the details."); }
+                            }
+                            ui.separator();
+                        }
+                        ui.label("Whatever that thread did stays off until the launcher is restarted.");
+                    });
+                    if custom_button(ui, "Dismiss", "edit-button", theme).clicked() { close = true; }
+                    if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested()) {
+                        close = true;
+                    }
+                });
+        });
+
+        if close {
+            ctx.send_viewport_cmd_to(egui::ViewportId::from_hash_of("crash"), egui::ViewportCommand::Close);
+            self.crashes.clear();
+        }
+    }
+
+    /// A launch-options editor, the settings, the wizard or the tray debug
+    /// view is open; keys typed there aren't the launcher's.
+    fn child_windows_open(&self) -> bool {
+        !self.editing_windows.is_empty() || self.settings_window.is_some() || self.onboarding.is_some() || self.tray_debug.is_some()
+    }
+
+    /// The first-start wizard: theme, size, optional sections, and the
+    /// hotkey line to copy. Theme and size preview in the launcher as
+    /// they're picked; Finish writes them to config.toml.
+    fn render_onboarding(&mut self, ctx: &eframe::egui::Context) {
+        use crate::onboarding::{Step, MODULES, SIZES};
+        use eframe::egui;
+        let Some(wizard) = self.onboarding.as_mut() else { return };
+        let (win_bg, theme, profiles) = (self.layout.win_bg, &self.theme, &self.theme_profiles);
+        let viewport = egui::ViewportBuilder::default()
+            .with_title("Welcome to Tusk Launcher")
+            .with_inner_size([420.0, 320.0])
+            .with_resizable(false).with_transparent(true).with_always_on_top();
+
+        let before = wizard.choices.clone();
+        let (mut finish, mut close) = (false, false);
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of("onboarding"), viewport, |ctx, _| {
+            #[allow(deprecated)]
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE.fill(win_bg).inner_margin(12.0))
+                .show(ctx, |ui| {
+                    theme.apply_style(ui, "env-input");
+                    let step = wizard.step;
+                    ui.label(egui::RichText::new(format!("{} of {}", step.index() + 1, Step::ALL.len())).small().weak());
+                    ui.heading(step.title());
+                    ui.add_space(6.0);
+                    let choices = &mut wizard.choices;
+                    egui::ScrollArea::vertical().max_height(ui.available_height() - 36.0).show(ui, |ui| match step {
+                        Step::Theme => {
+                            ui.selectable_value(&mut choices.theme, String::new(), "Default (theme.css)");
+                            for name in profiles { ui.selectable_value(&mut choices.theme, name.clone(), name.as_str()); }
+                            ui.label(egui::RichText::new("More themes can be dropped into ~/.config/tusk-launcher/themes/ later.").small().weak());
+                        }
+                        Step::Size => {
+                            for (label, scale) in SIZES { ui.radio_value(&mut choices.scale, *scale, *label); }
+                            ui.label(egui::RichText::new("Ctrl+Plus and Ctrl+Minus change it any time.").small().weak());
+                        }
+                        Step::Modules => {
+                            for ((_, label), on) in MODULES.iter().zip(choices.modules.iter_mut()) { ui.checkbox(on, *label); }
+                            ui.label(egui::RichText::new("Everything else is in the ⚙ settings.").small().weak());
+                        }
+                        Step::Keybinding => {
+                            ui.label(format!("Add this to {}:", wizard.hotkey.0));
+                            ui.horizontal(|ui| {
+                                let mut line = wizard.hotkey.1.as_str();
+                                ui.add(egui::TextEdit::singleline(&mut line).code_editor().desired_width(ui.available_width() - 60.0));
+                                if ui.button("Copy").clicked() { ctx.copy_text(wizard.hotkey.1.clone()); }
+                            });
+                            ui.label(egui::RichText::new("Pressing it again closes the launcher.").small().weak());
+                        }
+                    });
+                    ui.with_layout(egui::Layout::bottom_up(egui::Align::Min), |ui| {
+                        ui.horizontal(|ui| {
+                            if let Some(previous) = step.previous() && custom_button(ui, "Back", "edit-button", theme).clicked() {
+                                wizard.step = previous;
+                            }
+                            let last = step.next().is_none();
+                            if custom_button(ui, if last { "Finish" } else { "Next" }, "edit-button", theme).clicked() {
+                                match step.next() {
+                                    Some(next) => wizard.step = next,
+                                    None       => finish = true,
+                                }
+                            }
+                            if custom_button(ui, "Skip", "edit-button", theme).clicked() { close = true; }
+                        });
+                    });
+                    if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested()) {
+                        close = true;
+                    }
+                });
+        });
+
+        if wizard.choices.theme != before.theme {
+            self.theme_override = Some(wizard.choices.theme.clone());
+            self.reload_pending.store(true, Ordering::SeqCst);
+        }
+        if wizard.choices.scale != before.scale { ctx.set_zoom_factor(wizard.choices.scale); }
+        if finish {
+            if let Err(e) = crate::config::set_values(&wizard.choices.values()) {
+                tracing::warn!(target: "config", "Failed to save the first-start choices: {e}");
+            }
+        } else if close {
+            // Skipped: back to what config.toml says.
+            ctx.set_zoom_factor(ui_scale(&self.config));
+        }
+        if finish || close {
+            ctx.send_viewport_cmd_to(egui::ViewportId::from_hash_of("onboarding"), egui::ViewportCommand::Close);
+            self.onboarding = None;
+            self.theme_override = None;
+            self.reload_pending.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Failed launches as toasts along the bottom of the window
+    /// (`.launch-error`), each with the command that failed.
+    fn render_launch_failures(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let (bg, _, round) = self.theme.get_frame_props("launch-error", egui::Color32::from_rgb(110, 30, 30));
+        let color = self.theme.get_text_color("launch-error", false).unwrap_or(egui::Color32::WHITE);
+        let width = (self.layout.win_size.x - 24.0).max(120.0);
+        let mut dismiss = None;
+        egui::Area::new("launch_failures".into())
+            .order(egui::Order::Tooltip)
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -8.0))
+            .show(ctx, |ui| {
+                self.theme.apply_style(ui, "launch-error");
+                ui.set_width(width);
+                for (i, failure) in self.launch_failures.iter().enumerate() {
+                    egui::Frame::NONE.fill(bg).corner_radius(round).inner_margin(8.0).show(ui, |ui| {
+                        ui.set_width(width - 16.0);
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("Couldn't start {}", failure.app)).strong().color(color));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("✕").clicked() { dismiss = Some(i); }
+                                if ui.small_button("Copy command").clicked() { ui.ctx().copy_text(failure.command.clone()); }
+                            });
+                        });
+                        ui.label(egui::RichText::new(&failure.message).color(color));
+                        ui.label(egui::RichText::new(&failure.command).monospace().color(color));
+                    });
+                }
+            });
+        if let Some(i) = dismiss { self.launch_failures.remove(i); }
+    }
+
+    /// Arrow keys move the grid selection by a tile or a row. They're taken
+    /// before the search field sees them, so they don't move its text cursor.
+    fn move_grid_cursor(&mut self, ctx: &eframe::egui::Context) {
+        use eframe::egui::{Key, Modifiers};
+        let cols  = self.config.grid_columns.max(1) as isize;
+        let step: isize = ctx.input_mut(|i| {
+            [(Key::ArrowLeft, -1), (Key::ArrowRight, 1), (Key::ArrowUp, -cols), (Key::ArrowDown, cols)]
+                .into_iter()
+                .filter(|(key, _)| i.consume_key(Modifiers::NONE, *key))
+                .map(|(_, step)| step)
+                .sum()
+        });
+        self.move_selection(step);
+    }
+
+    /// Move the keyboard selection by `step` results, if that stays in the list.
+    fn move_selection(&mut self, step: isize) {
+        let count = self.visible_results().len();
+        let next  = self.selection.1 as isize + step;
+        if step != 0 && (0..count as isize).contains(&next) {
+            self.selection.1     = next as usize;
+            self.selection_moved = true;
+        }
+    }
+
+    /// Start the selection over at the top when the query changes, and keep
+    /// it inside the results.
+    fn sync_selection(&mut self) {
+        let query = self.app.get_query();
+        if self.selection.0 != query { self.selection = (query, 0); }
+        let count = self.visible_results().len();
+        self.selection.1 = self.selection.1.min(count.saturating_sub(1));
+    }
+
+    /// The `key-*` bindings. Runs after the action menu and the grid's arrow
+    /// keys had their turn.
+    fn bound_keys(&mut self, ctx: &eframe::egui::Context) {
+        use crate::keys::Action;
+        let pressed: Vec<Action> = ctx.input_mut(|i| {
+            self.bindings.iter().filter(|(_, mods, key)| i.consume_key(*mods, *key)).map(|(action, ..)| *action).collect()
+        });
+        for action in pressed {
+            match action {
+                Action::Next          => self.move_selection(1),
+                Action::Previous      => self.move_selection(-1),
+                Action::Clear         => self.app.handle_input(""),
+                Action::Actions       => self.action_menu = self.selected_row().map(|row| (row, 0)),
+                Action::LaunchOptions => {
+                    let row = self.selected_row().filter(|row| row.actions.contains(&RowAction::EditLaunchOptions));
+                    if let Some(row) = row { self.open_launch_editor(&row); }
+                }
+                Action::LaunchNth(n) => {
+                    if let Some(row) = self.visible_results().into_iter().nth(n) { self.app.launch_app(&row.id); }
+                }
+                Action::ZoomIn        => self.set_ui_scale(ctx, self.config.ui_scale + UI_SCALE_STEP),
+                Action::ZoomOut       => self.set_ui_scale(ctx, self.config.ui_scale - UI_SCALE_STEP),
+                Action::ZoomReset     => self.set_ui_scale(ctx, 1.0),
+            }
+        }
+    }
+
+    /// Results as `grid-columns` icon-over-label tiles (`.app-tile`).
+    fn render_app_grid(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context, apps: Vec<ResultRow>) {
+        use eframe::egui;
+        let cols  = self.config.grid_columns.max(1);
+        let gap   = self.layout.app_tile_gap;
+        let width = ((ui.available_width() - gap * (cols - 1) as f32) / cols as f32).max(1.0);
+        let size  = egui::vec2(width, self.layout.app_tile_h);
+        let icon  = self.layout.app_tile_icon;
+        let (base, hover, round) = self.theme.get_frame_props("app-tile", egui::Color32::TRANSPARENT);
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            self.theme.apply_style(ui, "app-tile");
+            ui.spacing_mut().item_spacing = egui::vec2(gap, gap);
+            let font = egui::TextStyle::Body.resolve(ui.style());
+            for (row, tiles) in apps.chunks(cols).enumerate() {
+                ui.horizontal(|ui| {
+                    for (col, tile) in tiles.iter().enumerate() {
+                        let label = tile.title.clone();
+                        let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::click());
+                        let selected = row * cols + col == self.selection.1;
+                        describe(&resp, egui::WidgetType::Button, &label);
+                        set_position(ctx, resp.id, row * cols + col, apps.len());
+                        if selected { self.selected_row = Some(resp.id); }
+                        if selected && self.selection_moved { resp.scroll_to_me(None); }
+                        if self.browse_jump == Some(index_letter(&label)) {
+                            resp.scroll_to_me(Some(egui::Align::TOP));
+                            self.browse_jump = None;
+                        }
+                        if !ui.is_rect_visible(rect) { continue; }
+                        // The keyboard selection looks like hover.
+                        let lit     = resp.hovered() || selected;
+                        let painter = ui.painter_at(rect);
+                        painter.rect_filled(rect, round, if lit { hover.unwrap_or(base) } else { base });
+                        if let Some(border) = self.theme.get_border("app-tile", lit) {
+                            painter.rect_stroke(rect, round, border, egui::StrokeKind::Inside);
+                        }
+                        if self.config.enable_icons {
+                            let texture = tile.icon.as_ref()
+                                .and_then(|path| self.icon_manager.get_texture(ctx, path));
+                            if let Some(tex) = texture {
+                                let icon_rect = egui::Rect::from_center_size(
+                                    egui::pos2(rect.center().x, rect.min.y + 4.0 + icon / 2.0), egui::vec2(icon, icon));
+                                painter.image(tex.id(), icon_rect,
+                                    egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
                             }
-                            _ => {}
                         }
+                        let color = self.theme.get_text_color("app-tile", lit).unwrap_or(egui::Color32::GRAY);
+                        let mut job = egui::text::LayoutJob::simple_singleline(crate::bidi::visual(&label).into_owned(), font.clone(), color);
+                        job.wrap = egui::text::TextWrapping::truncate_at_width(rect.width() - 6.0);
+                        let galley = painter.layout_job(job);
+                        let pos = egui::pos2(rect.center().x - galley.size().x / 2.0, rect.max.y - 4.0 - galley.size().y);
+                        painter.galley(pos, galley, color);
+
+                        let resp = resp.on_hover_text(crate::bidi::visual(tile.subtitle.as_deref().unwrap_or(&label)).into_owned());
+                        if resp.clicked() { self.app.launch_app(&tile.id); }
+                        self.result_menu(&resp, tile, width.max(120.0));
                     }
                 });
-                ui.add_space(4.0);
             }
         });
+        self.selection_moved = false;
+    }
+
+    /// Cover-art tiles for the games view (or wallpaper thumbnails); the
+    /// whole library scrolls inside the app-list area.
+    fn render_game_grid(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
+        use eframe::egui;
+        let games = self.app.get_search_results();
+        let size  = match games.first() {
+            Some(row) if row.kind == ResultKind::Wallpaper => self.layout.wallpaper_tile,
+            _                                               => egui::vec2(self.layout.tile_w, self.layout.tile_h),
+        };
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(self.layout.tile_gap, self.layout.tile_gap);
+            ui.horizontal_wrapped(|ui| {
+                for game in games {
+                    let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::click());
+                    describe(&resp, egui::WidgetType::Button, &game.title);
+                    if !ui.is_rect_visible(rect) { continue; }
+                    let painter = ui.painter_at(rect);
+                    let texture = game.cover.as_ref()
+                        .and_then(|path| self.icon_manager.get_texture(ctx, path));
+                    match texture {
+                        Some(tex) => {
+                            // Center-crop to the tile so wide header art isn't squashed.
+                            let (img, tile) = (tex.size_vec2(), rect.size());
+                            let scale = (tile.x / img.x).max(tile.y / img.y);
+                            let uv    = egui::Rect::from_center_size(egui::pos2(0.5, 0.5), tile / (img * scale));
+                            egui::Image::new(&tex).uv(uv).corner_radius(self.layout.tile_round)
+                                .paint_at(ui, rect);
+                        }
+                        // No art: the title on a plain tile.
+                        None => {
+                            let bg = self.theme.get_frame_props("app-button", ui.visuals().panel_fill).0;
+                            painter.rect_filled(rect, self.layout.tile_round, bg);
+                            let font  = egui::TextStyle::Small.resolve(ui.style());
+                            let color = self.theme.get_text_color("app-button", false).unwrap_or(egui::Color32::GRAY);
+                            let galley = painter.layout(crate::bidi::visual(&game.title).into_owned(), font, color, rect.width() - 6.0);
+                            painter.galley(rect.center() - galley.size() / 2.0, galley, color);
+                        }
+                    }
+                    if resp.hovered() {
+                        painter.rect_stroke(rect, self.layout.tile_round,
+                            egui::Stroke::new(2.0, self.layout.tile_hover), egui::StrokeKind::Inside);
+                    }
+                    let resp = resp.on_hover_text(crate::bidi::visual(&game.title).into_owned());
+                    if resp.clicked() { self.app.launch_app(&game.id); }
+                    self.result_menu(&resp, &game, size.x.max(120.0));
+                }
+            });
+        });
     }
 
     fn render_time_display(&mut self, ui: &mut eframe::egui::Ui) {
@@ -1234,9 +4532,26 @@ impl EframeWrapper {
         with_alignment(ui, &self.theme, "power-button", |ui| {
             with_custom_style(ui, |s| { self.theme.apply_widget_style(s, "power-button"); }, |ui| {
                 ui.horizontal(|ui| {
-                    for &(lbl, cmd) in &[("Power", "P"), ("Restart", "R"), ("Logout", "L")] {
+                    use crate::logind::PowerAction;
+                    let logind  = self.logind_host.as_ref().filter(|_| self.config.use_logind);
+                    let support = logind
+                        .and_then(|host| host.state.lock().ok().map(|s| s.power))
+                        .unwrap_or_default();
+                    let config = &self.config;
+                    // Power, restart and suspend go to logind when its host runs,
+                    // with the command list as its fallback.
+                    let buttons = [
+                        ("Power",   "P", Some((PowerAction::PowerOff, &config.power_commands)),   support.power_off),
+                        ("Restart", "R", Some((PowerAction::Reboot,   &config.restart_commands)), support.reboot),
+                        ("Logout",  "L", None,                                                    true),
+                        ("Suspend", "S", Some((PowerAction::Suspend,  &config.suspend_commands)), support.suspend && config.show_suspend_button),
+                    ];
+                    for (lbl, cmd, action, _) in buttons.into_iter().filter(|(_, _, _, shown)| *shown) {
                         if custom_button(ui, lbl, "power-button", &self.theme).clicked() {
-                            self.app.handle_input(cmd);
+                            match (logind, action) {
+                                (Some(host), Some((action, fallback))) => host.power(action, fallback),
+                                _ => self.app.handle_input(cmd),
+                            }
                         }
                     }
                 });
@@ -1244,6 +4559,22 @@ impl EframeWrapper {
         });
     }
 
+    /// Icon name (or path) from a tray item → file, cached per theme path.
+    fn resolve_tray_name(&mut self, name: &str, theme_path: Option<&str>) -> Option<String> {
+        let cache_key = format!("{}|{}", name, theme_path.unwrap_or(""));
+        self.tray_name_cache
+            .entry(cache_key)
+            .or_insert_with(|| {
+                resolve_tray_icon_name(name, theme_path, &self.config)
+                    .map(|p| if p.starts_with('/') {
+                        std::fs::canonicalize(&p)
+                            .map(|r| r.to_string_lossy().into_owned())
+                            .unwrap_or(p)
+                    } else { p })
+            })
+            .clone()
+    }
+
     fn render_tray_icon(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
         use eframe::egui;
 
@@ -1273,13 +4604,27 @@ impl EframeWrapper {
         // them out here made those icons disappear entirely, so every discovered
         // item is now shown regardless of status; `status` is still used below to
         // pick the "needs attention" icon variant.
-        let icons: Vec<crate::sni::TrayIcon> = self.sni_host
-            .as_ref()
-            .and_then(|h| h.items.lock().ok())
-            .map(|g| g.iter().cloned().collect())
-            .unwrap_or_default();
+        if let Some(host) = &self.sni_host {
+            let generation = host.items.generation();
+            if generation != self.tray_generation && let Ok(list) = host.items.lock() {
+                self.tray_items      = Arc::new(list.clone());
+                self.tray_generation = generation;
+            }
+        }
+        let icons = Arc::clone(&self.tray_items);
+
+        // Pinned items lead in pin order, the rest follow by category like other
+        // hosts do; hidden ones only show after clicking the overflow chevron.
+        let hidden_count = icons.iter().filter(|i| self.tray_pref(&i.sni_id).is_some_and(|(_, p)| p == TrayPref::Hidden)).count();
+        let mut icons: Vec<&crate::sni::TrayIcon> = icons.iter()
+            .filter(|i| self.tray_show_hidden || !self.tray_pref(&i.sni_id).is_some_and(|(_, p)| p == TrayPref::Hidden))
+            .collect();
+        icons.sort_by_key(|i| match self.tray_pref(&i.sni_id) {
+            Some((pos, TrayPref::Pinned)) => (0, pos),
+            _                             => (1, i.category.priority()),
+        });
 
-        if icons.is_empty() {
+        if icons.is_empty() && hidden_count == 0 {
             let dot_r  = 3.0_f32;
             let center = egui::pos2(strip_rect.min.x + GAP + dot_r, strip_rect.center().y);
             ui.painter().circle_filled(center, dot_r, self.layout.tray_indicator_color);
@@ -1289,12 +4634,29 @@ impl EframeWrapper {
         let cy  = strip_rect.center().y;
         let mut x = strip_rect.min.x + GAP;
 
-        for icon in &icons {
+        let mut hovered = false;
+        for &icon in &icons {
             let icon_rect = egui::Rect::from_min_size(egui::pos2(x, cy - ICON_SZ * 0.5), icon_size);
             x += ICON_SZ + GAP;
-
-            let use_attn = icon.status == crate::sni::TrayStatus::NeedsAttention
-                && (!icon.attention_icon_rgba.is_empty() || icon.attention_icon_name.is_some());
+            let pref   = self.tray_pref(&icon.sni_id).map(|(_, p)| p);
+            let pinned = pref == Some(TrayPref::Pinned);
+            let hidden = pref == Some(TrayPref::Hidden);
+
+            // NeedsAttention: play the AttentionMovie if there is one, else blink
+            // between the normal and attention icons (or fade the normal icon).
+            let needs_attn = icon.status == crate::sni::TrayStatus::NeedsAttention;
+            let has_attn   = !icon.attention_icon_rgba.is_empty() || icon.attention_icon_name.is_some();
+            let movie = icon.attention_movie_name.as_deref()
+                .filter(|_| needs_attn && self.config.animate_icons)
+                .and_then(|name| self.resolve_tray_name(name, icon.icon_theme_path.as_deref()));
+            let blink_off = needs_attn && self.config.animate_icons && movie.is_none() && {
+                let ms = (ctx.input(|i| i.time) * 1000.0) as u64;
+                ctx.request_repaint_after(std::time::Duration::from_millis(ATTENTION_BLINK_MS - ms % ATTENTION_BLINK_MS));
+                (ms / ATTENTION_BLINK_MS) % 2 == 1
+            };
+            let use_attn = needs_attn && has_attn && !blink_off;
+            let tint = if blink_off && !has_attn { egui::Color32::from_white_alpha(90) } else { egui::Color32::WHITE };
+            let tint = if hidden { tint.gamma_multiply(0.5) } else { tint };
 
             let (tex_rgba, tex_w, tex_h, tex_name) = if use_attn {
                 (&icon.attention_icon_rgba, icon.attention_icon_w, icon.attention_icon_h, &icon.attention_icon_name)
@@ -1318,45 +4680,28 @@ impl EframeWrapper {
             }
 
             if ui.is_rect_visible(icon_rect) {
-                if let Some((_, tex)) = self.tray_textures.get(&tex_key) {
-                    ui.painter().image(
-                        tex.id(), icon_rect,
-                        egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
-                } else if let Some(name) = tex_name.as_deref().filter(|s| !s.is_empty()) {
-                    let cache_key = format!("{}|{}", name, icon.icon_theme_path.as_deref().unwrap_or(""));
-                    let resolved  = self.tray_name_cache
-                        .entry(cache_key)
-                        .or_insert_with(|| {
-                            resolve_tray_icon_name(name, icon.icon_theme_path.as_deref(), &self.config)
-                                .map(|p| if p.starts_with('/') {
-                                    std::fs::canonicalize(&p)
-                                        .map(|r| r.to_string_lossy().into_owned())
-                                        .unwrap_or(p)
-                                } else { p })
-                        })
-                        .as_deref();
-                    if let Some(path) = resolved {
-                        if let Some(tex) = self.icon_manager.get_texture(ctx, path) {
-                            ui.painter().image(
-                                tex.id(), icon_rect,
-                                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
-                                egui::Color32::WHITE,
-                            );
-                        } else {
-                            ui.painter().circle_filled(icon_rect.center(), ICON_SZ * 0.4, self.layout.tray_indicator_color);
-                        }
-                    } else {
-                        ui.painter().circle_filled(icon_rect.center(), ICON_SZ * 0.4, self.layout.tray_indicator_color);
-                    }
-                } else {
-                    ui.painter().circle_filled(icon_rect.center(), ICON_SZ * 0.4, self.layout.tray_indicator_color);
-                }
+                let uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+                let tex = match movie {
+                    Some(path) => self.icon_manager.get_texture(ctx, &path),
+                    None => match self.tray_textures.get(&tex_key) {
+                        Some((_, tex)) => Some(tex.clone()),
+                        None => tex_name.as_deref().filter(|s| !s.is_empty())
+                            .and_then(|name| self.resolve_tray_name(name, icon.icon_theme_path.as_deref()))
+                            .and_then(|path| self.icon_manager.get_texture(ctx, &path)),
+                    },
+                };
+                match tex {
+                    Some(tex) => ui.painter().image(tex.id(), icon_rect, uv, tint),
+                    None      => ui.painter().circle_filled(icon_rect.center(), ICON_SZ * 0.4, self.layout.tray_indicator_color),
+                };
             }
 
-            let resp = ui.interact(icon_rect, ui.id().with(&icon.id), egui::Sense::click())
-                .on_hover_text(&icon.tooltip_title);
+            let resp = ui.interact(icon_rect, ui.id().with(&icon.id), egui::Sense::click());
+            describe(&resp, egui::WidgetType::Button, if icon.tooltip_title.is_empty() { &icon.sni_id } else { &icon.tooltip_title });
+            if resp.hovered() {
+                hovered = true;
+                self.show_tray_tooltip(ctx, icon, icon_rect);
+            }
 
             if resp.hovered() || self.tray_menu_open.as_deref() == Some(&icon.id) {
                 ui.painter().rect_stroke(
@@ -1398,8 +4743,9 @@ impl EframeWrapper {
                     }
                     self.tray_menu_open    = Some(icon.id.clone());
                     self.tray_menu_fetched = None;
+                    reset_tray_menu(ctx);
                     if let (Some(host), Some(menu_path)) = (&self.sni_host, &icon.menu_path) {
-                        host.menu_about_to_show(&icon.bus_name, menu_path);
+                        host.menu_about_to_show(&icon.bus_name, menu_path, &icon.id, 0);
                     }
                 }
             }
@@ -1412,87 +4758,511 @@ impl EframeWrapper {
                     self.tray_menu_fetched = Some(icon.id.clone());
                 }
 
-                if icon.menu_path.is_some() {
-                    let menu_loaded = icon.menu_loaded;
-
-                    if !menu_loaded {
-                        // Don't create the popup window until we know how many items it
-                        // actually needs to hold. Sizing it from an empty/stale item list
-                        // (because the async GetLayout fetch hasn't completed yet) and
-                        // resizing later isn't reliably honored on every backend
-                        // (XWayland in particular) -- that's what caused the "opens too
-                        // small until you right-click again" bug: by the second click the
-                        // menu had already finished loading in the background, so it
-                        // happened to size correctly right from the start.
-                        ctx.request_repaint();
-                    } else {
-                        let menu_items   = icon.menu_items.clone();
-                        let icon_id      = icon.id.clone();
-                        let bus_name     = icon.bus_name.clone();
-                        let menu_path    = icon.menu_path.clone();
-                        let indicator    = self.layout.tray_indicator_color;
-                        let win_bg       = self.layout.win_bg;
-                        let tooltip      = icon.tooltip_title.clone();
-                        let action_key   = format!("tray_menu_action_{icon_id}");
-                        let theme_menu   = Arc::clone(&self.theme);
-
-                        let item_count = menu_items.iter().filter(|i| !i.is_separator).count();
-                        let win_h      = (item_count as f32 * 28.0 + 32.0).clamp(60.0, 400.0);
-                        let vp_id      = tray_menu_vp_id(&icon_id);
-                        let viewport   = egui::ViewportBuilder::default()
-                            .with_title(if tooltip.is_empty() { "Menu".into() } else { tooltip })
-                            .with_inner_size([180.0_f32, win_h])
-                            .with_resizable(false).with_transparent(true).with_always_on_top();
-
-                        ctx.show_viewport_immediate(vp_id, viewport, move |ctx, _| {
-                            let action_key = format!("tray_menu_action_{icon_id}");
-                            #[allow(deprecated)]
-                            egui::CentralPanel::default()
-                                .frame(egui::Frame::NONE.fill(win_bg))
-                                .show(ctx, |ui| {
-                                    ui.add_space(4.0);
-                                    if menu_items.is_empty() {
-                                        ui.add_enabled(false, egui::Label::new("No menu items"));
-                                    } else {
-                                        let clicked = render_menu_items(ui, &menu_items, indicator, &theme_menu);
-                                        if let Some(item_id) = clicked {
-                                            ctx.data_mut(|d| d.insert_temp(egui::Id::new(&action_key), item_id));
-                                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                // Items without a DBusMenu still get the popup, for pin/hide.
+                let menu_loaded = icon.menu_loaded || icon.menu_path.is_none();
+
+                if !menu_loaded {
+                    // Don't create the popup window until we know how many items it
+                    // actually needs to hold. Sizing it from an empty/stale item list
+                    // (because the async GetLayout fetch hasn't completed yet) and
+                    // resizing later isn't reliably honored on every backend
+                    // (XWayland in particular) -- that's what caused the "opens too
+                    // small until you right-click again" bug: by the second click the
+                    // menu had already finished loading in the background, so it
+                    // happened to size correctly right from the start. The SNI host
+                    // wakes us once GetLayout completes.
+                } else {
+                    let mut menu_items = icon.menu_items.clone();
+                    if icon.menu_path.is_none() {
+                        menu_items.push(tray_pref_entry(TRAY_APP_MENU_ITEM, "Open app menu"));
+                    }
+                    if !menu_items.is_empty() {
+                        menu_items.push(crate::sni::MenuItem { is_separator: true, visible: true, ..Default::default() });
+                    }
+                    menu_items.push(tray_pref_entry(TRAY_PIN_ITEM, if pinned { "Unpin" } else { "Pin to front" }));
+                    menu_items.push(tray_pref_entry(TRAY_HIDE_ITEM, if hidden { "Unhide" } else { "Hide" }));
+                    let icon_id      = icon.id.clone();
+                    let bus_name     = icon.bus_name.clone();
+                    let menu_path    = icon.menu_path.clone();
+                    let indicator    = self.layout.tray_indicator_color;
+                    let win_bg       = self.layout.win_bg;
+                    let tooltip      = icon.tooltip_title.clone();
+                    let action_key   = format!("tray_menu_action_{icon_id}");
+                    let theme_menu   = Arc::clone(&self.theme);
+
+                    let menu_icons = self.menu_icons(ctx, icon);
+                    let win_h      = (menu_rows(&menu_items) as f32 * 28.0 + 32.0).clamp(60.0, 400.0);
+                    // Room for right-aligned accelerators next to the labels.
+                    let win_w      = if menu_items.iter().any(|i| i.shortcut.is_some()) { 240.0_f32 } else { 180.0 };
+                    // Submenus open to the right, inside the same window: it
+                    // has a transparent column for each level, as popups
+                    // can't be placed next to it on every backend.
+                    let levels     = 1 + menu_depth(&menu_items);
+                    let vp_id      = tray_menu_vp_id(&icon_id);
+                    let host       = self.sni_host.as_ref();
+                    let (menu_bus, menu_mp) = (&bus_name, &menu_path);
+                    let viewport   = egui::ViewportBuilder::default()
+                        .with_title(if tooltip.is_empty() { "Menu".into() } else { tooltip })
+                        .with_inner_size([win_w * levels as f32, win_h])
+                        .with_resizable(false).with_transparent(true).with_always_on_top();
+
+                    ctx.show_viewport_immediate(vp_id, viewport, move |ctx, _| {
+                        let action_key = format!("tray_menu_action_{icon_id}");
+                        let popup = MenuPopup { icons: &menu_icons, indicator, theme: &theme_menu, bg: win_bg, width: win_w };
+                        #[allow(deprecated)]
+                        egui::CentralPanel::default()
+                            .frame(egui::Frame::NONE)
+                            .show(ctx, |ui| {
+                                let column = egui::Rect::from_min_size(ui.max_rect().min, egui::vec2(win_w, ui.max_rect().height()));
+                                ui.painter().rect_filled(column, 0.0, win_bg);
+                                ui.set_max_width(win_w);
+                                ui.add_space(4.0);
+                                let mut signals = Vec::new();
+                                let clicked = render_menu_items(ui, &menu_items, &popup, 0, &mut signals);
+                                if let (Some(host), Some(mp)) = (host, menu_mp) {
+                                    for signal in signals {
+                                        match signal {
+                                            MenuSignal::Hovered(id) if id >= 0 => host.menu_event(menu_bus, mp, id, "hovered"),
+                                            MenuSignal::Opened(id)             => {
+                                                host.menu_about_to_show(menu_bus, mp, &icon_id, id);
+                                                host.menu_event(menu_bus, mp, id, "opened");
+                                            }
+                                            MenuSignal::Closed(id)             => host.menu_event(menu_bus, mp, id, "closed"),
+                                            MenuSignal::Hovered(_)             => {}
                                         }
                                     }
-                                    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-                                        ctx.data_mut(|d| d.insert_temp(egui::Id::new(&action_key), -1i32));
-                                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                                    }
-                                });
-                        });
-
-                        let ak_id = egui::Id::new(&action_key);
-                        if let Some(item_id) = ctx.data_mut(|d| d.get_temp::<i32>(ak_id)) {
-                            if item_id >= 0 {
-                                if let (Some(host), Some(mp)) = (&self.sni_host, &menu_path) {
-                                    host.menu_event(&bus_name, mp, item_id);
                                 }
-                            }
-                            self.tray_menu_open = None;
-                            ctx.data_mut(|d| d.remove::<i32>(ak_id));
-                            ctx.send_viewport_cmd_to(vp_id, egui::ViewportCommand::Close);
+                                if let Some(item_id) = clicked {
+                                    ctx.data_mut(|d| d.insert_temp(egui::Id::new(&action_key), item_id));
+                                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                                }
+                                if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                    ctx.data_mut(|d| d.insert_temp(egui::Id::new(&action_key), -1i32));
+                                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                                }
+                            });
+                    });
+
+                    let ak_id = egui::Id::new(&action_key);
+                    if let Some(item_id) = ctx.data_mut(|d| d.get_temp::<i32>(ak_id)) {
+                        match item_id {
+                            TRAY_PIN_ITEM      => self.set_tray_pref(&icon.sni_id, (!pinned).then_some(TrayPref::Pinned)),
+                            TRAY_HIDE_ITEM     => self.set_tray_pref(&icon.sni_id, (!hidden).then_some(TrayPref::Hidden)),
+                            TRAY_APP_MENU_ITEM => if let Some(host) = &self.sni_host {
+                                let pos = icon_rect.center();
+                                host.context_menu(&bus_name, &icon.obj_path, pos.x as i32, pos.y as i32);
+                            },
+                            id if id >= 0      => if let (Some(host), Some(mp)) = (&self.sni_host, &menu_path) {
+                                host.menu_event(&bus_name, mp, id, "clicked");
+                            },
+                            _                  => {}
                         }
+                        self.tray_menu_open = None;
+                        ctx.data_mut(|d| d.remove::<i32>(ak_id));
+                        ctx.send_viewport_cmd_to(vp_id, egui::ViewportCommand::Close);
+                    }
+                }
+            }
+        }
+        if !hovered { self.tray_hover = None; }
+
+        // Overflow chevron: reveals (dimmed) hidden items.
+        if hidden_count > 0 {
+            let rect = egui::Rect::from_min_size(egui::pos2(x, cy - ICON_SZ * 0.5), icon_size);
+            let hint = if self.tray_show_hidden { "Collapse hidden items".to_string() } else { format!("Show {hidden_count} hidden") };
+            let resp = icon_hint(ui.interact(rect, ui.id().with("tray_overflow"), egui::Sense::click()), &hint);
+            let color = self.theme.get_text_color("tray-icon", resp.hovered())
+                .unwrap_or(egui::Color32::from_gray(if resp.hovered() { 230 } else { 160 }));
+            ui.painter().text(rect.center(), egui::Align2::CENTER_CENTER,
+                if self.tray_show_hidden { "‹" } else { "›" },
+                egui::TextStyle::Button.resolve(ui.style()), color);
+            if resp.clicked() { self.tray_show_hidden = !self.tray_show_hidden; }
+        }
+    }
+
+    /// `.tray-tooltip` popup above a hovered tray item once it has been
+    /// hovered for `tray-tooltip-delay-ms`.
+    fn show_tray_tooltip(&mut self, ctx: &eframe::egui::Context, icon: &crate::sni::TrayIcon, anchor: eframe::egui::Rect) {
+        use eframe::egui;
+
+        let since = match &self.tray_hover {
+            Some((id, since)) if *id == icon.id => *since,
+            _ => {
+                let now = Instant::now();
+                self.tray_hover = Some((icon.id.clone(), now));
+                now
+            }
+        };
+        let delay = Duration::from_millis(self.config.tray_tooltip_delay_ms);
+        if since.elapsed() < delay {
+            ctx.request_repaint_after(delay - since.elapsed());
+            return;
+        }
+        if self.tray_menu_open.as_deref() == Some(&icon.id) { return; }
+
+        let theme = &self.theme;
+        let (bg, _, round) = theme.get_frame_props("tray-tooltip", egui::Color32::from_gray(40));
+        let text_color = theme.get_text_color("tray-tooltip", false).unwrap_or(egui::Color32::from_gray(220));
+        let cat_color  = theme.get("tray-tooltip", "category-color")
+            .and_then(|s| theme.parse_color(&s))
+            .unwrap_or(text_color.gamma_multiply(0.6));
+        let pad       = theme.get_px("tray-tooltip", "padding").unwrap_or(6.0);
+        let font_size = theme.get_px("tray-tooltip", "font-size").unwrap_or(11.0);
+        let max_w     = theme.get_px("tray-tooltip", "max-width").unwrap_or(220.0);
+        let title     = if icon.tooltip_title.is_empty() { &icon.sni_id } else { &icon.tooltip_title };
+
+        egui::Area::new(egui::Id::new("tray_tooltip"))
+            .order(egui::Order::Tooltip)
+            .pivot(egui::Align2::CENTER_BOTTOM)
+            .fixed_pos(anchor.center_top() - egui::vec2(0.0, 4.0))
+            .constrain(true)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.fill(bg).corner_radius(round).inner_margin(pad).show(ui, |ui| {
+                    ui.set_max_width(max_w);
+                    ui.label(egui::RichText::new(title).strong().size(font_size).color(text_color));
+                    if !icon.tooltip_body.is_empty() {
+                        ui.add(egui::Label::new(egui::RichText::new(&icon.tooltip_body).size(font_size).color(text_color)).wrap());
+                    }
+                    ui.label(egui::RichText::new(icon.category.label()).size(font_size * 0.9).color(cat_color));
+                });
+            });
+    }
+
+    /// Textures for the `icon-data` of a tray item's menu rows, decoded once
+    /// per menu revision.
+    fn menu_icons(&mut self, ctx: &eframe::egui::Context, icon: &crate::sni::TrayIcon) -> HashMap<i32, eframe::egui::TextureHandle> {
+        fn collect(ctx: &eframe::egui::Context, items: &[crate::sni::MenuItem], out: &mut HashMap<i32, eframe::egui::TextureHandle>) {
+            for item in items {
+                if !item.icon_data.is_empty() {
+                    match crate::app_launcher::decode_png(&item.icon_data) {
+                        Ok(img) => { out.insert(item.id, ctx.load_texture(format!("tray_menu_icon_{}", item.id), img, Default::default())); }
+                        Err(e)  => tracing::warn!(target: "icons", "Bad icon-data for menu item {}: {e}", item.id),
                     }
                 }
+                collect(ctx, &item.children, out);
+            }
+        }
+        if let Some((rev, icons)) = self.tray_menu_icons.get(&icon.id) && *rev == icon.menu_revision { return icons.clone(); }
+        let mut icons = HashMap::new();
+        collect(ctx, &icon.menu_items, &mut icons);
+        self.tray_menu_icons.insert(icon.id.clone(), (icon.menu_revision, icons.clone()));
+        icons
+    }
+
+    /// Position in the prefs list (the pin order) and the preference itself.
+    fn tray_pref(&self, sni_id: &str) -> Option<(usize, TrayPref)> {
+        self.tray_prefs.iter().position(|(id, _)| id == sni_id).map(|i| (i, self.tray_prefs[i].1))
+    }
+
+    fn set_tray_pref(&mut self, sni_id: &str, pref: Option<TrayPref>) {
+        if let Err(e) = crate::app_launcher::set_tray_pref(sni_id, pref) {
+            tracing::warn!(target: "config", "Failed to save tray preference: {e}");
+        }
+        self.tray_prefs = crate::app_launcher::get_tray_prefs();
+    }
+
+    /// Notification list under a do-not-disturb toggle; what arrived while
+    /// it was on is folded into its own group.
+    fn render_notifications(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let Some(host) = &self.notification_host else { return };
+        let list: Vec<crate::notifications::Notification> = host.items.lock()
+            .map(|g| g.clone())
+            .unwrap_or_default();
+        let (held, shown): (Vec<_>, Vec<_>) = list.iter().partition(|n| n.held);
+
+        self.theme.apply_style(ui, "notifications");
+        let (bg, _, round) = self.theme.get_frame_props("notifications", egui::Color32::TRANSPARENT);
+        let critical = self.theme.get("notifications", "critical-color")
+            .and_then(|s| self.theme.parse_color(&s))
+            .unwrap_or(egui::Color32::from_rgb(235, 87, 87));
+
+        let dnd = crate::notifications::do_not_disturb();
+        let mut toggle = false;
+        let text = if dnd { "🔕 Do not disturb" } else { "🔔 Notifications" };
+        let hint = if dnd { "Show notifications as they arrive" } else { "Hold notifications without showing them" };
+        if ui.add(egui::Button::new(text).selected(dnd)).on_hover_text(hint).clicked() { toggle = true; }
+        let clicked = egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+            let mut clicked = None;
+            for n in shown {
+                clicked = self.notification_card(ui, n, bg, round, critical).or(clicked);
+            }
+            if !held.is_empty() {
+                let in_held = egui::CollapsingHeader::new(format!("{} while Do Not Disturb", held.len()))
+                    .id_salt("held-notifications")
+                    .show(ui, |ui| {
+                        let mut clicked = None;
+                        for n in held {
+                            clicked = self.notification_card(ui, n, bg, round, critical).or(clicked);
+                        }
+                        clicked
+                    })
+                    .body_returned.flatten();
+                clicked = clicked.or(in_held);
             }
+            clicked
+        }).inner;
+
+        match clicked {
+            Some((id, None))      => host.dismiss(id),
+            Some((id, Some(key))) => host.invoke_action(id, &key),
+            None                  => {}
         }
+        if toggle { self.set_do_not_disturb(!dnd); }
+    }
+
+    /// One notification; returns its id and the action clicked, `None` for
+    /// dismiss.
+    fn notification_card(
+        &self,
+        ui: &mut eframe::egui::Ui,
+        n: &crate::notifications::Notification,
+        bg: eframe::egui::Color32,
+        round: eframe::egui::CornerRadius,
+        critical: eframe::egui::Color32,
+    ) -> Option<(u32, Option<String>)> {
+        use eframe::egui;
+        use crate::notifications::Urgency;
+
+        let mut clicked = None;
+        egui::Frame::NONE.fill(bg).corner_radius(round).inner_margin(4.0).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                let title = if n.summary.is_empty() { &n.app_name } else { &n.summary };
+                let mut text = egui::RichText::new(title).strong();
+                if n.urgency == Urgency::Critical { text = text.color(critical); }
+                ui.label(text);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if icon_hint(custom_button(ui, "✕", "notification-action", &self.theme), "Dismiss").clicked() {
+                        clicked = Some((n.id, None));
+                    }
+                });
+            });
+            if !n.body.is_empty() {
+                // Clicking the body invokes the sender's "default" action, if any.
+                let has_default = n.actions.iter().any(|(k, _)| k == "default");
+                let sense = if has_default { egui::Sense::click() } else { egui::Sense::hover() };
+                if ui.add(egui::Label::new(&n.body).wrap().sense(sense)).clicked() {
+                    clicked = Some((n.id, Some("default".into())));
+                }
+            }
+            let buttons: Vec<&(String, String)> = n.actions.iter().filter(|(k, _)| k != "default").collect();
+            if !buttons.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for (key, label) in buttons {
+                        if custom_button(ui, label, "notification-action", &self.theme).clicked() {
+                            clicked = Some((n.id, Some(key.clone())));
+                        }
+                    }
+                });
+            }
+        });
+        ui.add_space(4.0);
+        clicked
+    }
+
+    fn render_network(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+        use crate::network::Connectivity;
+
+        let Some(host) = &self.network_host else { return };
+        let state = host.state.lock().map(|g| g.clone()).unwrap_or_default();
+
+        let label = match (state.connectivity, &state.connection_name) {
+            (Connectivity::Connected, Some(name)) => match state.strength {
+                Some(s) if state.is_wifi => format!("📶 {name} ({s}%)"),
+                _                        => format!("🖧 {name}"),
+            },
+            (Connectivity::Connected, None) => "🖧 Connected".into(),
+            (Connectivity::Connecting, _)   => "Connecting…".into(),
+            (Connectivity::Disconnected, _) => "Disconnected".into(),
+            (Connectivity::Unknown, _)      => "Network unavailable".into(),
+        };
+
+        self.theme.apply_style(ui, "network");
+        let mut connect = None;
+        let resp = ui.menu_button(label, |ui| {
+            if state.known_wifi.is_empty() {
+                ui.add_enabled(false, egui::Label::new("No saved Wi-Fi networks"));
+            }
+            for wifi in &state.known_wifi {
+                let text = match wifi.strength {
+                    Some(s) => format!("{} ({s}%)", wifi.name),
+                    None    => format!("{} (out of range)", wifi.name),
+                };
+                if ui.add_enabled(!wifi.active, egui::Button::new(text).selected(wifi.active)).clicked() {
+                    connect = Some(wifi.path.clone());
+                    ui.close();
+                }
+            }
+        });
+        // Scan once each time the dropdown opens so strengths are current.
+        if resp.response.clicked() { host.rescan(); }
+        if let Some(path) = connect { host.connect(&path); }
+    }
+
+    fn render_vpn(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+        use crate::network::VpnState;
+
+        let Some(host) = &self.network_host else { return };
+        let state = host.state.lock().map(|g| g.clone()).unwrap_or_default();
+
+        let up: Vec<&str> = state.vpns.iter().filter(|v| v.state == VpnState::On).map(|v| v.name.as_str()).collect();
+        let label = match up.as_slice() {
+            _ if state.vpns.is_empty() => "No VPNs".to_string(),
+            []                         => "🔓 VPN off".to_string(),
+            [name]                     => format!("🔒 {name}"),
+            names                      => format!("🔒 {} VPNs", names.len()),
+        };
+        let mut text = egui::RichText::new(label);
+        if let Some(c) = self.layout.vpn_active_color.filter(|_| !up.is_empty()) { text = text.color(c); }
+
+        self.theme.apply_style(ui, "vpn");
+        ui.menu_button(text, |ui| {
+            if state.vpns.is_empty() {
+                ui.add_enabled(false, egui::Label::new("No VPN connections saved"));
+            }
+            for vpn in &state.vpns {
+                let busy = state.vpn_pending.contains(&vpn.id) || vpn.state == VpnState::Connecting;
+                let text = if busy { format!("{} …", vpn.name) } else { vpn.name.clone() };
+                let on   = vpn.state == VpnState::On;
+                let button = egui::Button::new(text).selected(on);
+                // Toggle: clicking a VPN that's up takes it down.
+                let hint = format!("{} · click to {}", vpn.kind, if on { "disconnect" } else { "connect" });
+                if ui.add_enabled(!busy, button).on_hover_text(hint).clicked() {
+                    host.set_vpn(vpn, !on);
+                }
+            }
+        });
+    }
+
+    fn render_bluetooth(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let Some(host) = &self.bluetooth_host else { return };
+        let state = host.state.lock().map(|g| g.clone()).unwrap_or_default();
+
+        let connected: Vec<&str> = state.devices.iter().filter(|d| d.connected).map(|d| d.name.as_str()).collect();
+        let label = match (&state.adapter, state.powered) {
+            (None, _)        => "Bluetooth unavailable".to_string(),
+            (Some(_), false) => "Bluetooth off".to_string(),
+            (Some(_), true)  => match connected.as_slice() {
+                []     => "Bluetooth on".to_string(),
+                [name] => format!("ᛒ {name}"),
+                names  => format!("ᛒ {} devices", names.len()),
+            },
+        };
+
+        self.theme.apply_style(ui, "bluetooth");
+        let ctx = ui.ctx().clone();
+        let (icon_manager, config) = (&mut self.icon_manager, &self.config);
+        ui.menu_button(label, |ui| {
+            if state.adapter.is_none() {
+                ui.add_enabled(false, egui::Label::new("No adapter found"));
+                return;
+            }
+            let mut powered = state.powered;
+            if ui.checkbox(&mut powered, "Powered").changed() { host.set_powered(powered); }
+            ui.separator();
+            if state.devices.is_empty() {
+                ui.add_enabled(false, egui::Label::new("No paired devices"));
+            }
+            for dev in &state.devices {
+                let busy = state.pending.contains(&dev.path);
+                let mut text = dev.name.clone();
+                if let Some(b) = dev.battery { text.push_str(&format!(" ({b}%)")); }
+                if busy { text.push_str(" …"); }
+                let icon = dev.icon.as_deref().filter(|_| config.enable_icons)
+                    .and_then(|name| crate::icon_theme::lookup(name, TRAY_ICON_SIZE, config))
+                    .and_then(|path| icon_manager.get_texture(&ctx, &path));
+                let button = match &icon {
+                    Some(tex) => egui::Button::image_and_text(egui::Image::new(tex).fit_to_exact_size(egui::vec2(16.0, 16.0)), text),
+                    None      => egui::Button::new(text),
+                };
+                let button = button.selected(dev.connected);
+                // Toggle: clicking a connected device disconnects it.
+                if ui.add_enabled(state.powered && !busy, button).clicked() {
+                    if dev.connected { host.disconnect(&dev.path); } else { host.connect(&dev.path); }
+                }
+            }
+        });
+    }
+
+    fn render_drives(&mut self, ui: &mut eframe::egui::Ui) {
+        use eframe::egui;
+
+        let Some(host) = &self.drives_host else { return };
+        let state = host.state.lock().map(|g| g.clone()).unwrap_or_default();
+
+        let mounted: Vec<&str> = state.volumes.iter()
+            .filter(|v| v.removable && v.mount_point.is_some())
+            .map(|v| v.name.as_str())
+            .collect();
+        let label = match mounted.as_slice() {
+            []     => "No drives mounted".to_string(),
+            [name] => format!("⏏ {name}"),
+            names  => format!("⏏ {} drives", names.len()),
+        };
+
+        self.theme.apply_style(ui, "drives");
+        ui.menu_button(label, |ui| {
+            if state.volumes.is_empty() {
+                ui.add_enabled(false, egui::Label::new("No drives plugged in"));
+            }
+            for vol in &state.volumes {
+                let busy = state.pending.contains(&vol.path);
+                let mut text = format!("{} ({})", vol.name, vol.size_label());
+                if busy { text.push_str(" …"); }
+                ui.horizontal(|ui| {
+                    // A mounted volume opens in the file manager; another mounts.
+                    let open = egui::Button::new(text).selected(vol.mount_point.is_some());
+                    let resp = ui.add_enabled(!busy, open).on_hover_text(vol.mount_point.as_deref().unwrap_or(&vol.device));
+                    if resp.clicked() {
+                        match &vol.mount_point {
+                            Some(at) => { crate::system::open_uri(at); ui.close(); }
+                            None     => host.mount(&vol.path),
+                        }
+                    }
+                    if vol.mount_point.is_some() && ui.add_enabled(!busy, egui::Button::new("Unmount")).clicked() {
+                        host.unmount(&vol.path);
+                    }
+                    if vol.removable && ui.add_enabled(!busy, egui::Button::new("⏏")).on_hover_text("Eject").clicked() {
+                        host.eject(&vol.path);
+                    }
+                });
+            }
+            if let Some(error) = &state.error {
+                ui.separator();
+                ui.add_enabled(false, egui::Label::new(error.as_str()));
+            }
+        });
     }
 
     fn render_section(&mut self, ui: &mut eframe::egui::Ui, sec: &str, ctx: &eframe::egui::Context) {
         match sec {
-            "search-bar"    => self.render_search_bar(ui),
-            "volume-slider" => self.render_volume_slider(ui),
-            "app-list"      => self.render_app_list(ui, ctx),
-            "time-display"  => self.render_time_display(ui),
-            "power-button"  => self.render_power_button(ui),
-            "tray-icon"     => self.render_tray_icon(ui, ctx),
-            _               => {}
+            "search-bar"     => self.render_search_bar(ui),
+            "volume-slider"  => self.render_volume_slider(ui),
+            "mic-control"    => self.render_mic_control(ui),
+            "idle-inhibitor" => self.render_idle_inhibitor(ui),
+            "system-stats"   => self.render_system_stats(ui),
+            "timer"          => self.render_timer(ui),
+            "quick-actions"  => self.render_quick_actions(ui),
+            "workspaces"     => self.render_workspaces(ui),
+            "weather"        => self.render_weather(ui, ctx),
+            "theme-switcher" => self.render_theme_switcher(ui),
+            "app-list"       => self.render_app_list(ui, ctx),
+            "time-display"   => self.render_time_display(ui),
+            "power-button"   => self.render_power_button(ui),
+            "tray-icon"      => self.render_tray_icon(ui, ctx),
+            "notifications"  => self.render_notifications(ui),
+            "network"        => self.render_network(ui),
+            "vpn"            => self.render_vpn(ui),
+            "bluetooth"      => self.render_bluetooth(ui),
+            "drives"         => self.render_drives(ui),
+            _                => {}
         }
     }
 }
@@ -1501,6 +5271,11 @@ impl EframeWrapper {
 // Tray icon name resolution
 // ============================================================================
 
+/// Pixel size tray icons are looked up at in the icon theme.
+const TRAY_ICON_SIZE: u32 = 22;
+/// Half-period of the NeedsAttention blink.
+const ATTENTION_BLINK_MS: u64 = 500;
+
 fn resolve_tray_icon_name(name: &str, app_theme_path: Option<&str>, config: &Config) -> Option<String> {
     if name.is_empty() { return None; }
 
@@ -1549,7 +5324,12 @@ fn resolve_tray_icon_name(name: &str, app_theme_path: Option<&str>, config: &Con
         .chain(stripped.map(str::to_string))
         .collect();
     if !name.ends_with("-symbolic") {
-        candidates.push(format!("{name}-symbolic"));
+        // Symbolic variants are recolored to the theme, so they win when preferred.
+        if config.prefer_symbolic_icons {
+            candidates.insert(0, format!("{name}-symbolic"));
+        } else {
+            candidates.push(format!("{name}-symbolic"));
+        }
     }
 
     // Real icon-theme directory trees (as opposed to app_theme_path below,
@@ -1601,6 +5381,7 @@ fn resolve_tray_icon_name(name: &str, app_theme_path: Option<&str>, config: &Con
                 if f.exists() { return Some(f.to_string_lossy().into_owned()); }
             }
         }
+        if let Some(p) = crate::icon_theme::lookup(candidate, TRAY_ICON_SIZE, config) { return Some(p); }
         for root in &theme_roots {
             for theme in &themes {
                 let theme_dir = root.join(theme);
@@ -1708,63 +5489,180 @@ impl MenuStyle {
     }
 }
 
-fn render_menu_items(
-    ui:        &mut eframe::egui::Ui,
-    items:     &[crate::sni::MenuItem],
+/// A tray menu level's shared look, passed down to its submenus.
+struct MenuPopup<'a> {
+    icons:     &'a HashMap<i32, eframe::egui::TextureHandle>,
+    /// Color of check and radio marks.
     indicator: eframe::egui::Color32,
-    theme:     &Theme,
+    theme:     &'a Theme,
+    /// Submenu background, as the menu window's.
+    bg:        eframe::egui::Color32,
+    /// Width of each level, the window's being one per level.
+    width:     f32,
+}
+
+/// What the app behind a tray menu hears about besides clicks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MenuSignal {
+    Hovered(i32),
+    /// A submenu opened: AboutToShow, then the "opened" event.
+    Opened(i32),
+    Closed(i32),
+}
+
+/// Temp-data key of the open submenu chain (item ids, outermost first).
+const TRAY_SUBMENUS: &str = "tray_submenus";
+/// Temp-data key of the last hovered tray menu item.
+const TRAY_MENU_HOVER: &str = "tray_menu_hover";
+
+/// Forget the open submenus and hover; for a menu opening afresh.
+fn reset_tray_menu(ctx: &eframe::egui::Context) {
+    ctx.data_mut(|d| {
+        d.remove::<Vec<i32>>(eframe::egui::Id::new(TRAY_SUBMENUS));
+        d.remove::<i32>(eframe::egui::Id::new(TRAY_MENU_HOVER));
+    });
+}
+
+/// Nesting levels below `items`; the menu window is one column wider per level.
+fn menu_depth(items: &[crate::sni::MenuItem]) -> usize {
+    items.iter().filter(|i| !i.children.is_empty()).map(|i| 1 + menu_depth(&i.children)).max().unwrap_or(0)
+}
+
+/// Rows of the longest level, for the menu window's height.
+fn menu_rows(items: &[crate::sni::MenuItem]) -> usize {
+    let own = items.iter().filter(|i| !i.is_separator).count();
+    items.iter().map(|i| menu_rows(&i.children)).fold(own, usize::max)
+}
+
+/// Open submenu `open` at `depth`, closing whatever was open there and
+/// below; `None` only closes.
+fn open_submenu(ctx: &eframe::egui::Context, depth: usize, open: Option<i32>, signals: &mut Vec<MenuSignal>) {
+    let key = eframe::egui::Id::new(TRAY_SUBMENUS);
+    let mut path: Vec<i32> = ctx.data(|d| d.get_temp(key)).unwrap_or_default();
+    if path.get(depth).copied() == open { return; }
+    let from = depth.min(path.len());
+    signals.extend(path.drain(from..).rev().map(MenuSignal::Closed));
+    if let Some(id) = open {
+        path.push(id);
+        signals.push(MenuSignal::Opened(id));
+    }
+    ctx.data_mut(|d| d.insert_temp(key, path));
+}
+
+/// Draw one menu level, and the submenus open from it as popups to its
+/// right; returns the id of a clicked item.
+fn render_menu_items(
+    ui:      &mut eframe::egui::Ui,
+    items:   &[crate::sni::MenuItem],
+    popup:   &MenuPopup,
+    depth:   usize,
+    signals: &mut Vec<MenuSignal>,
 ) -> Option<i32> {
+    use crate::sni::ToggleType;
     use eframe::egui;
-    let style   = MenuStyle::from_theme(theme, ui);
+    let style   = MenuStyle::from_theme(popup.theme, ui);
     let mut clicked = None;
+    // Labels line up when any row at this level has an icon or a mark.
+    let icon_col  = if items.iter().any(|i| popup.icons.contains_key(&i.id)) { MENU_ICON_SZ + 4.0 } else { 0.0 };
+    let check_col = if items.iter().any(|i| i.toggle_type != ToggleType::None) { MENU_ICON_SZ + 4.0 } else { 0.0 };
+    let open: Option<i32> = ui.ctx().data(|d| d.get_temp::<Vec<i32>>(egui::Id::new(TRAY_SUBMENUS)))
+        .and_then(|path| path.get(depth).copied());
 
     for item in items {
         if item.is_separator { ui.separator(); continue; }
         if item.label.is_empty() { continue; }
 
         let avail_w = ui.available_width();
+        let galley  = ui.painter().layout_no_wrap(item.label.clone(), style.font_id.clone(), egui::Color32::WHITE);
+        let h       = galley.size().y + ui.spacing().button_padding.y * 2.0;
+        let (rect, response) = ui.add_enabled_ui(item.enabled, |ui| {
+            ui.allocate_exact_size(egui::vec2(avail_w, h), egui::Sense::click())
+        }).inner;
+        let submenu = !item.children.is_empty();
+        let is_open = submenu && open == Some(item.id);
+        let kind    = match item.toggle_type {
+            _ if submenu           => egui::WidgetType::Button,
+            ToggleType::Checkmark  => egui::WidgetType::Checkbox,
+            ToggleType::Radio      => egui::WidgetType::RadioButton,
+            ToggleType::None       => egui::WidgetType::Button,
+        };
+        response.widget_info(|| {
+            let mut info = egui::WidgetInfo::labeled(kind, item.enabled, &item.label);
+            if item.toggle_type != ToggleType::None { info.selected = Some(item.toggle_state == 1); }
+            info
+        });
 
-        if item.children.is_empty() {
-            let galley = ui.painter().layout_no_wrap(item.label.clone(), style.font_id.clone(), egui::Color32::WHITE);
-            let h      = galley.size().y + ui.spacing().button_padding.y * 2.0;
-            let (rect, response) = ui.allocate_exact_size(egui::vec2(avail_w, h), egui::Sense::click());
-
-            if ui.is_rect_visible(rect) {
-                let hovered = response.hovered() && item.enabled;
-                ui.painter().rect_filled(rect, style.rounding, if hovered { style.bg_hover } else { style.bg_normal });
+        if ui.is_rect_visible(rect) {
+            let hovered = (response.hovered() && item.enabled) || is_open;
+            let pad     = ui.spacing().button_padding.x;
+            let color   = if item.enabled { style.tc_normal } else { style.tc_disabled };
+            ui.painter().rect_filled(rect, style.rounding, if hovered { style.bg_hover } else { style.bg_normal });
+            let mark = match (&item.toggle_type, item.toggle_state) {
+                (ToggleType::Checkmark, 1) => Some("✔"),
+                (ToggleType::Radio, 1)     => Some("●"),
+                (ToggleType::Radio, _)     => Some("○"),
+                _                          => None,
+            };
+            if let Some(mark) = mark {
+                let mark_color = if item.enabled { popup.indicator } else { style.tc_disabled };
                 ui.painter().text(
-                    egui::pos2(rect.min.x + ui.spacing().button_padding.x, rect.center().y),
-                    egui::Align2::LEFT_CENTER,
-                    &item.label, style.font_id.clone(),
-                    if item.enabled { style.tc_normal } else { style.tc_disabled },
+                    egui::pos2(rect.min.x + pad + MENU_ICON_SZ * 0.5, rect.center().y),
+                    egui::Align2::CENTER_CENTER, mark, style.font_id.clone(), mark_color,
                 );
             }
-            if response.clicked() && item.enabled { clicked = Some(item.id); }
-        } else {
-            let open_key = egui::Id::new(("tray_submenu", &item.label, item.id));
-            let is_open: bool = ui.ctx().data(|d| d.get_temp(open_key).unwrap_or(false));
-            let header = format!("{} {}", if is_open { "▼" } else { "▶" }, item.label);
-            let galley = ui.painter().layout_no_wrap(header.clone(), style.font_id.clone(), egui::Color32::WHITE);
-            let h      = galley.size().y + ui.spacing().button_padding.y * 2.0;
-            let (rect, response) = ui.allocate_exact_size(egui::vec2(avail_w, h), egui::Sense::click());
-
-            if ui.is_rect_visible(rect) {
-                ui.painter().rect_filled(rect, style.rounding, if response.hovered() { style.bg_hover } else { style.bg_normal });
+            if let Some(tex) = popup.icons.get(&item.id) {
+                let icon_rect = egui::Rect::from_center_size(
+                    egui::pos2(rect.min.x + pad + check_col + MENU_ICON_SZ * 0.5, rect.center().y),
+                    egui::vec2(MENU_ICON_SZ, MENU_ICON_SZ),
+                );
+                let tint = if item.enabled { egui::Color32::WHITE } else { egui::Color32::from_white_alpha(100) };
+                ui.painter().image(tex.id(), icon_rect,
+                    egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)), tint);
+            }
+            ui.painter().text(
+                egui::pos2(rect.min.x + pad + check_col + icon_col, rect.center().y),
+                egui::Align2::LEFT_CENTER,
+                &item.label, style.font_id.clone(), color,
+            );
+            let trailing = if submenu { Some("▶") } else { item.shortcut.as_deref() };
+            if let Some(trailing) = trailing {
                 ui.painter().text(
-                    egui::pos2(rect.min.x + ui.spacing().button_padding.x, rect.center().y),
-                    egui::Align2::LEFT_CENTER, &header, style.font_id.clone(), style.tc_normal,
+                    egui::pos2(rect.max.x - pad, rect.center().y),
+                    egui::Align2::RIGHT_CENTER,
+                    trailing, style.font_id.clone(), style.tc_disabled,
                 );
             }
-            if response.clicked() { ui.ctx().data_mut(|d| d.insert_temp(open_key, !is_open)); }
+        }
 
-            if is_open {
-                ui.indent(open_key, |ui| {
-                    if let Some(id) = render_menu_items(ui, &item.children, indicator, theme) {
-                        clicked = Some(id);
-                    }
-                });
+        if response.hovered() {
+            let hover_key = egui::Id::new(TRAY_MENU_HOVER);
+            if ui.ctx().data(|d| d.get_temp::<i32>(hover_key)) != Some(item.id) {
+                ui.ctx().data_mut(|d| d.insert_temp(hover_key, item.id));
+                signals.push(MenuSignal::Hovered(item.id));
+            }
+            // Hovering a row opens its submenu, or closes a sibling's.
+            open_submenu(ui.ctx(), depth, submenu.then_some(item.id), signals);
+        }
+        if response.clicked() {
+            if submenu {
+                open_submenu(ui.ctx(), depth, (!is_open).then_some(item.id), signals);
+            } else {
+                clicked = Some(item.id);
             }
         }
+
+        if is_open {
+            let inner = egui::Area::new(egui::Id::new(("tray_submenu", depth, item.id)))
+                .order(egui::Order::Foreground)
+                .fixed_pos(egui::pos2(rect.max.x + 4.0, rect.min.y - 4.0))
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::NONE.fill(popup.bg).corner_radius(style.rounding).inner_margin(4.0).show(ui, |ui| {
+                        ui.set_width(popup.width - 8.0);
+                        render_menu_items(ui, &item.children, popup, depth + 1, signals)
+                    }).inner
+                }).inner;
+            if inner.is_some() { clicked = inner; }
+        }
     }
     clicked
 }
@@ -1774,29 +5672,107 @@ fn render_menu_items(
 // ============================================================================
 
 impl eframe::App for EframeWrapper {
+    /// Fully transparent: only `.main-window`'s own background shows, so an
+    /// `rgba()` color there is true alpha over the desktop (or its blur).
+    fn clear_color(&self, _visuals: &eframe::egui::Visuals) -> [f32; 4] {
+        [0.0; 4]
+    }
+
     fn ui(&mut self, ui: &mut eframe::egui::Ui, _frame: &mut eframe::Frame) {
         let ctx = ui.ctx().clone();
+        while let Ok(cmd) = self.ipc_rx.try_recv() { self.handle_ipc_command(cmd, &ctx); }
+        if self.reload_pending.swap(false, Ordering::SeqCst) { self.reload_settings(&ctx); }
         self.app.update();
+        // Shown hidden or not: the thread that died may be why nothing else shows.
+        self.crashes.extend(crate::crash::take());
+        self.render_crashes(&ctx);
+        if !self.visible { return; }
 
         if self.config.enable_audio_control {
             self.current_volume = self.audio_controller.get_volume();
         }
 
+        if self.config.enable_system_stats {
+            let interval = Duration::from_millis(self.config.system_stats_interval_ms.max(250));
+            if self.stats.as_ref().is_none_or(|(_, at)| at.elapsed() >= interval) {
+                self.stats = Some((self.stats_sampler.sample(), Instant::now()));
+            }
+            ctx.request_repaint_after(interval);
+        }
+
+        // Redraw on each whole second of the countdown.
+        let now = Instant::now();
+        if self.config.enable_timer && self.timer.is_running(now) {
+            let left = self.timer.remaining(now);
+            ctx.request_repaint_after(Duration::from_nanos(left.subsec_nanos() as u64 + 1_000_000));
+        }
+
         if self.config.show_time && self.last_time_update.elapsed() >= Duration::from_secs(1) {
             self.cached_time      = self.app.get_time();
             self.last_time_update = Instant::now();
         }
 
+        if !self.placed {
+            self.placed = true;
+            self.place_window(&ctx);
+            if self.layout.win_blur { std::thread::spawn(crate::backdrop::request_blur); }
+            if crate::x11_hints::active() {
+                let override_redirect = self.config.x11_override_redirect;
+                std::thread::spawn(move || crate::x11_hints::apply(override_redirect));
+            }
+        }
+        if let Some(screen) = self.sizing.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.sizing = None;
+            self.set_screen(&ctx, screen);
+        }
+        // A new zoom takes effect a frame after it's set; the window keeps its
+        // size in points, so it grows or shrinks in pixels with it.
+        if ctx.zoom_factor() != self.zoom {
+            self.zoom = ctx.zoom_factor();
+            ctx.send_viewport_cmd(eframe::egui::ViewportCommand::InnerSize(self.layout.win_size));
+            if sized_by_screen(&self.theme) { self.place_window(&ctx); }
+        }
+
+        if self.config.close_on_focus_loss { self.close_on_focus_loss(&ctx); }
+
+        ctx.input(|i| {
+            use crate::ime::ImeEvent;
+            self.composition.begin_frame(i.events.iter().filter_map(|event| match event {
+                eframe::egui::Event::Ime(eframe::egui::ImeEvent::Enabled)    => Some(ImeEvent::Enabled),
+                eframe::egui::Event::Ime(eframe::egui::ImeEvent::Preedit(s)) => Some(ImeEvent::Preedit(s)),
+                eframe::egui::Event::Ime(eframe::egui::ImeEvent::Commit(s))  => Some(ImeEvent::Commit(s)),
+                eframe::egui::Event::Ime(eframe::egui::ImeEvent::Disabled)   => Some(ImeEvent::Disabled),
+                _                                                            => None,
+            }));
+        });
+        // Enter, Escape and the arrows belong to the input method while it composes.
+        let composing = self.composition.touched();
+
+        if !self.child_windows_open() && !composing { self.action_menu_keys(&ctx); }
+        // XF86AudioRaiseVolume/Lower/Mute and the media keys can't be handled
+        // here: egui 0.34 has no `Key` for them, so egui-winit drops those
+        // events before they reach `RawInput`, and eframe has no hook for the
+        // winit events themselves. They keep working through compositor
+        // bindings, which see them first; there's no MPRIS client to forward
+        // them to.
         let (esc, enter) = ctx.input(|i| (
-            i.key_pressed(eframe::egui::Key::Escape),
-            i.key_pressed(eframe::egui::Key::Enter),
+            !composing && i.key_pressed(eframe::egui::Key::Escape),
+            !composing && i.key_pressed(eframe::egui::Key::Enter),
         ));
 
+        self.sync_selection();
+        if !self.child_windows_open() && self.action_menu.is_none() && !composing {
+            if self.app_grid_active() { self.move_grid_cursor(&ctx); }
+            self.bound_keys(&ctx);
+        }
+
         let (w, h) = (self.layout.win_size.x, self.layout.win_size.y);
         let bg     = self.layout.win_bg;
-        let rect   = eframe::egui::Rect::from_min_size(eframe::egui::pos2(0.0, 0.0), eframe::egui::vec2(w, h));
+        let (opacity, dy) = self.open_frame(&ctx);
+        let rect   = eframe::egui::Rect::from_min_size(eframe::egui::pos2(0.0, dy), eframe::egui::vec2(w, h));
 
-        eframe::egui::Area::new("main".into()).fixed_pos(eframe::egui::pos2(0.0, 0.0)).show(&ctx, |ui| {
+        eframe::egui::Area::new("main".into()).fixed_pos(rect.min).show(&ctx, |ui| {
+            ui.multiply_opacity(opacity);
             ui.set_min_size(eframe::egui::vec2(w, h));
             ui.set_max_size(eframe::egui::vec2(w, h));
 
@@ -1824,123 +5800,71 @@ impl eframe::App for EframeWrapper {
                     let tint = eframe::egui::Color32::from_white_alpha((bgi.opacity * 255.0) as u8);
                     ui.painter().image(tex.id(), draw_rect, uv, tint);
                 } else {
-                    ui.painter().rect_filled(rect, 0.0, bg);
+                    ui.painter().rect_filled(rect, self.layout.win_round, bg);
                 }
             } else {
-                ui.painter().rect_filled(rect, 0.0, bg);
+                ui.painter().rect_filled(rect, self.layout.win_round, bg);
             }
 
-            let sections: Vec<(&'static str, Option<(f32, f32)>, Option<eframe::egui::Vec2>)> =
-                self.layout.sections.iter().map(|s| (s.name, s.pos, s.size)).collect();
+            let mut flow = self.flow_rects();
+            let sections: Vec<Placement> = self.layout.sections.iter().map(|s| (s.name, s.pos, s.pivot, s.size)).collect();
 
-            for (name, pos, size) in sections {
-                let area = if let Some((x, y)) = pos {
+            for (name, pos, pivot, size) in sections {
+                let flowed = flow.remove(name);
+                let area = if let Some((rect, _)) = flowed {
+                    eframe::egui::Area::new(name.to_owned().into())
+                        .order(eframe::egui::Order::Foreground)
+                        .fixed_pos(rect.min + eframe::egui::vec2(0.0, dy))
+                } else if let Some((x, y)) = pos {
                     eframe::egui::Area::new(name.to_owned().into())
                         .order(eframe::egui::Order::Foreground)
-                        .fixed_pos(eframe::egui::pos2(x, y))
+                        .pivot(pivot)
+                        .fixed_pos(eframe::egui::pos2(x, y + dy))
                 } else {
                     eframe::egui::Area::new(name.to_owned().into())
                         .order(eframe::egui::Order::Foreground)
                 };
-                area.show(&ctx, |ui| {
+                let shown = area.show(&ctx, |ui| {
+                    ui.multiply_opacity(opacity);
                     if let Some(sz) = size { ui.set_min_size(sz); ui.set_max_size(sz); }
+                    if let Some((rect, fixed)) = flowed {
+                        if fixed.x { ui.set_min_width(rect.width());   ui.set_max_width(rect.width()); }
+                        if fixed.y { ui.set_min_height(rect.height()); ui.set_max_height(rect.height()); }
+                    }
                     self.render_section(ui, name, &ctx);
                 });
-            }
-        });
-
-        // Editing windows (env-vars popup)
-        let mut to_remove = Vec::new();
-
-        for (app_name, opts) in self.editing_windows.iter() {
-            let (win_bg, env_w, env_h) = (self.layout.win_bg, self.layout.env_w, self.layout.env_h);
-            let app_clone   = app_name.clone();
-            let opts_clone  = opts.clone();
-            let theme_clone = Arc::clone(&self.theme);
-            let vp_id       = eframe::egui::ViewportId::from_hash_of(format!("env_{app_name}"));
-            let viewport    = eframe::egui::ViewportBuilder::default()
-                .with_title(app_name.clone())
-                .with_inner_size([env_w, env_h])
-                .with_resizable(false).with_transparent(true).with_always_on_top();
-
-            let mem_key    = format!("env_opts_{app_name}");
-            let action_key = format!("env_action_{app_name}");
-
-            let current_opts = ctx.data_mut(|d| {
-                d.get_persisted::<String>(eframe::egui::Id::new(&mem_key))
-                    .unwrap_or_else(|| opts_clone.clone())
-            });
-
-            if current_opts != opts_clone {
-                let stored_app_key = format!("env_app_{app_name}");
-                let stored_app = ctx.data_mut(|d| d.get_persisted::<String>(eframe::egui::Id::new(&stored_app_key)));
-                if stored_app.as_ref() != Some(&app_clone) {
-                    ctx.data_mut(|d| {
-                        d.insert_persisted(eframe::egui::Id::new(&mem_key),        opts_clone.clone());
-                        d.insert_persisted(eframe::egui::Id::new(&stored_app_key), app_clone.clone());
-                    });
+                if flowed.is_some() {
+                    // Natural sizes are only known after drawing; lay out again once they settle.
+                    let measured = shown.response.rect.size();
+                    let previous = self.flow_sizes.insert(name, measured);
+                    if previous.is_none_or(|p| (p - measured).length() > 0.5) { ctx.request_repaint(); }
                 }
             }
+        });
 
-            ctx.show_viewport_immediate(vp_id, viewport, move |ctx, _| {
-                let mem_key    = format!("env_opts_{app_clone}");
-                let action_key = format!("env_action_{app_clone}");
-                let mut opts = ctx.data_mut(|d| {
-                    d.get_persisted::<String>(eframe::egui::Id::new(&mem_key))
-                        .unwrap_or_else(|| opts_clone.clone())
-                });
-                #[allow(deprecated)]
-                eframe::egui::CentralPanel::default()
-                    .frame(eframe::egui::Frame::NONE.fill(win_bg))
-                    .show(ctx, |ui| {
-                        ui.vertical(|ui| {
-                            ui.label(&app_clone);
-                            ui.add_space(4.0);
-                            with_alignment(ui, &theme_clone, "env-input", |ui| {
-                                theme_clone.apply_style(ui, "env-input");
-                                ui.add(eframe::egui::TextEdit::singleline(&mut opts)
-                                    .hint_text("Enter env variables...")
-                                    .desired_width(f32::INFINITY));
-                            });
-                            ui.add_space(4.0);
-                            ui.horizontal(|ui| {
-                                if custom_button(ui, "Save",   "edit-button", &theme_clone).clicked() {
-                                    ctx.data_mut(|d| d.insert_temp(eframe::egui::Id::new(&action_key), "save".to_string()));
-                                }
-                                if custom_button(ui, "Cancel", "edit-button", &theme_clone).clicked() {
-                                    ctx.data_mut(|d| d.insert_temp(eframe::egui::Id::new(&action_key), "cancel".to_string()));
-                                }
-                            });
-                        });
-                        if ctx.input(|i| i.key_pressed(eframe::egui::Key::Escape)) {
-                            ctx.data_mut(|d| d.insert_temp(eframe::egui::Id::new(&action_key), "cancel".to_string()));
-                        }
-                    });
-                ctx.data_mut(|d| d.insert_persisted(eframe::egui::Id::new(&mem_key), opts));
-            });
-
-            if let Some(action) = ctx.data_mut(|d| d.get_temp::<String>(eframe::egui::Id::new(&action_key))) {
-                if action == "save" {
-                    let final_opts = ctx.data_mut(|d| {
-                        d.get_persisted::<String>(eframe::egui::Id::new(&mem_key)).unwrap_or_else(|| opts.clone())
-                    });
-                    self.app.handle_input(&format!("LAUNCH_OPTIONS:{}:{}", app_name, final_opts));
-                }
-                to_remove.push(app_name.clone());
-                ctx.data_mut(|d| {
-                    d.remove::<String>(eframe::egui::Id::new(&mem_key));
-                    d.remove::<String>(eframe::egui::Id::new(&action_key));
-                    d.remove::<String>(eframe::egui::Id::new(&format!("env_app_{app_name}")));
-                });
-                ctx.send_viewport_cmd_to(vp_id, eframe::egui::ViewportCommand::Close);
+        self.render_launch_editors(&ctx);
+        self.render_settings_window(&ctx);
+        self.render_onboarding(&ctx);
+        self.render_tray_debug(&ctx);
+        self.render_action_menu(&ctx);
+
+        if esc   && !self.child_windows_open() { self.app.handle_input("ESC"); }
+        if enter && !self.child_windows_open() {
+            // Enter opens the selected result; the top one goes through the
+            // app so it can catch up with what was just typed.
+            let selected = (self.app_grid_active() || self.selection.1 > 0).then(|| self.selected_row()).flatten();
+            match selected {
+                Some(row) => self.app.launch_app(&row.id),
+                None      => self.app.handle_input("ENTER"),
             }
         }
-        for app_name in to_remove { self.editing_windows.remove(&app_name); }
-
-        if esc   && self.editing_windows.is_empty() { self.app.handle_input("ESC"); }
-        if enter && self.editing_windows.is_empty() { self.app.handle_input("ENTER"); }
-        if self.app.should_quit() { ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close); }
+        self.launch_failures.extend(self.app.take_launch_failures());
+        if !self.launch_failures.is_empty() {
+            self.render_launch_failures(&ctx);
+        }
+        if self.app.should_quit() { self.dismiss(&ctx); }
+        if crate::profile::first_frame() { ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close); }
     }
 }
 
-pub fn load_theme() -> Arc<Theme> { Arc::new(Theme::load_or_create()) }
+pub fn load_theme(profile: &str) -> Arc<Theme> { Arc::new(Theme::load(profile)) }
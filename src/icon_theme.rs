@@ -0,0 +1,337 @@
+//! Icon lookup per the freedesktop Icon Theme Specification.
+//!
+//! The user's theme comes from `icon-theme`, or when that's empty from
+//! gsettings / GTK `settings.ini` / `kdeglobals`. Each theme's `index.theme`
+//! lists its directories with their nominal size, scale and size type; the
+//! lookup takes an exact size match from the theme, else its closest size,
+//! then walks the `Inherits` chain (depth first) and finally `hicolor`.
+//! Unthemed icons in `pixmaps` are left to the caller.
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    process::Command,
+    sync::{LazyLock, Mutex},
+};
+use crate::gui::Config;
+
+/// Checked in this order within a directory, as the spec recommends.
+const EXTS: &[&str] = &["png", "svg", "xpm"];
+const FALLBACK_THEME: &str = "hicolor";
+/// Guards against `Inherits` cycles in broken themes.
+const MAX_INHERIT_DEPTH: usize = 8;
+
+static THEMES: LazyLock<Mutex<ThemeSet>> = LazyLock::new(|| Mutex::new(ThemeSet::new(base_dirs())));
+static DETECTED: LazyLock<String> = LazyLock::new(detect_theme);
+
+/// Path of the icon `name` closest to `size` pixels in the configured theme.
+pub fn lookup(name: &str, size: u32, config: &Config) -> Option<String> {
+    let mut themes = THEMES.lock().ok()?;
+    themes.find(theme_name(config), name, size, 1).map(|p| p.to_string_lossy().into_owned())
+}
+
+/// List every directory of the configured theme, its parents and `hicolor`
+/// in parallel, so the first frame's lookups are answered from memory.
+/// Meant for a background thread at startup.
+pub fn preload(config: &Config) {
+    let dirs = match THEMES.lock() {
+        Ok(mut themes) => themes.unlisted_dirs(theme_name(config)),
+        Err(_)         => return,
+    };
+    let listings = crate::pool::map(dirs, |dir| {
+        let listing = list_dir(&dir);
+        (dir, listing)
+    });
+    if let Ok(mut themes) = THEMES.lock() {
+        for (dir, listing) in listings { themes.listings.entry(dir).or_insert(listing); }
+    }
+}
+
+fn theme_name(config: &Config) -> &str {
+    match config.icon_theme.as_str() {
+        ""    => DETECTED.as_str(),
+        theme => theme,
+    }
+}
+
+/// `$HOME/.icons`, then `icons` under the XDG data dirs (user first), plus
+/// Flatpak's exported icons.
+fn base_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") { dirs.push(PathBuf::from(home).join(".icons")); }
+    dirs.push(crate::paths::data_home().join("icons"));
+    dirs.extend(crate::paths::data_dirs().into_iter().map(|d| d.join("icons")));
+    dirs.push(crate::paths::data_home().join("flatpak/exports/share/icons"));
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/icons"));
+    dirs.dedup();
+    dirs
+}
+
+// ============================================================================
+// Theme detection
+// ============================================================================
+
+fn detect_theme() -> String {
+    let kde = std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.to_uppercase().contains("KDE"));
+    let sources: [fn() -> Option<String>; 3] = if kde {
+        [kde_theme, gtk_settings_theme, gsettings_theme]
+    } else {
+        [gsettings_theme, gtk_settings_theme, kde_theme]
+    };
+    let theme = sources.iter().find_map(|source| source())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| FALLBACK_THEME.into());
+    tracing::debug!(target: "icons", "icon theme from the desktop: {theme}");
+    theme
+}
+
+fn gsettings_theme() -> Option<String> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "icon-theme"])
+        .output().ok()?;
+    if !output.status.success() { return None; }
+    let value = String::from_utf8_lossy(&output.stdout);
+    Some(value.trim().trim_matches('\'').to_string())
+}
+
+fn gtk_settings_theme() -> Option<String> {
+    ["gtk-4.0/settings.ini", "gtk-3.0/settings.ini"].iter()
+        .filter_map(|f| fs::read_to_string(crate::paths::config_home().join(f)).ok())
+        .find_map(|content| ini_value(&content, "Settings", "gtk-icon-theme-name"))
+}
+
+fn kde_theme() -> Option<String> {
+    let content = fs::read_to_string(crate::paths::config_home().join("kdeglobals")).ok()?;
+    ini_value(&content, "Icons", "Theme")
+}
+
+/// `key` in `[section]` of an ini-style file.
+fn ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    parse_ini(content).remove(section)?.remove(key)
+}
+
+fn parse_ini(content: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') { continue; }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.to_string();
+        } else if let Some((k, v)) = line.split_once('=') {
+            sections.entry(current.clone()).or_default()
+                .insert(k.trim().to_string(), v.trim().trim_matches('"').to_string());
+        }
+    }
+    sections
+}
+
+// ============================================================================
+// index.theme
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SizeType { Fixed, Scalable, Threshold }
+
+/// One entry of `Directories=` / `ScaledDirectories=`.
+#[derive(Debug)]
+struct ThemeDir {
+    path:      String,
+    size:      u32,
+    scale:     u32,
+    min_size:  u32,
+    max_size:  u32,
+    threshold: u32,
+    kind:      SizeType,
+}
+
+impl ThemeDir {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale { return false; }
+        match self.kind {
+            SizeType::Fixed     => self.size == size,
+            SizeType::Scalable  => (self.min_size..=self.max_size).contains(&size),
+            SizeType::Threshold => self.size.saturating_sub(self.threshold) <= size && size <= self.size + self.threshold,
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let want = size * scale;
+        let (lo, hi) = match self.kind {
+            SizeType::Fixed     => (self.size, self.size),
+            SizeType::Scalable  => (self.min_size, self.max_size),
+            SizeType::Threshold => (self.size.saturating_sub(self.threshold), self.size + self.threshold),
+        };
+        let (lo, hi) = (lo * self.scale, hi * self.scale);
+        if want < lo { lo - want } else { want.saturating_sub(hi) }
+    }
+}
+
+struct Theme {
+    inherits: Vec<String>,
+    dirs:     Vec<ThemeDir>,
+}
+
+fn parse_index_theme(content: &str) -> Option<Theme> {
+    let mut ini  = parse_ini(content);
+    let header   = ini.remove("Icon Theme")?;
+    let list     = |key: &str| -> Vec<String> {
+        header.get(key).map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+    let mut names = list("Directories");
+    names.extend(list("ScaledDirectories"));
+
+    let dirs = names.into_iter().filter_map(|path| {
+        let props = ini.get(&path)?;
+        let num   = |key: &str| props.get(key).and_then(|v| v.parse::<u32>().ok());
+        let size  = num("Size")?;
+        let kind  = match props.get("Type").map(String::as_str) {
+            Some("Fixed")    => SizeType::Fixed,
+            Some("Scalable") => SizeType::Scalable,
+            _                => SizeType::Threshold,
+        };
+        Some(ThemeDir {
+            size, kind,
+            scale:     num("Scale").unwrap_or(1).max(1),
+            min_size:  num("MinSize").unwrap_or(size),
+            max_size:  num("MaxSize").unwrap_or(size),
+            threshold: num("Threshold").unwrap_or(2),
+            path,
+        })
+    }).collect();
+
+    Some(Theme { inherits: list("Inherits"), dirs })
+}
+
+// ============================================================================
+// Lookup
+// ============================================================================
+
+/// Parsed themes and directory listings, filled in lazily. Listing a
+/// directory once is much cheaper than probing it per name and extension.
+struct ThemeSet {
+    bases:    Vec<PathBuf>,
+    themes:   HashMap<String, Option<Theme>>,
+    listings: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl ThemeSet {
+    fn new(bases: Vec<PathBuf>) -> Self {
+        ThemeSet { bases, themes: HashMap::new(), listings: HashMap::new() }
+    }
+
+    fn find(&mut self, theme: &str, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let mut visited = HashSet::new();
+        self.find_in(theme, name, size, scale, &mut visited, 0)
+            .or_else(|| self.find_in(FALLBACK_THEME, name, size, scale, &mut visited, 0))
+    }
+
+    fn find_in(&mut self, theme: &str, name: &str, size: u32, scale: u32,
+               visited: &mut HashSet<String>, depth: usize) -> Option<PathBuf> {
+        if depth > MAX_INHERIT_DEPTH || !visited.insert(theme.to_string()) { return None; }
+        if let Some(found) = self.lookup_icon(theme, name, size, scale) { return Some(found); }
+        let parents = self.load(theme).map(|t| t.inherits.clone()).unwrap_or_default();
+        parents.iter().find_map(|parent| self.find_in(parent, name, size, scale, visited, depth + 1))
+    }
+
+    fn load(&mut self, theme: &str) -> Option<&Theme> {
+        if !self.themes.contains_key(theme) {
+            let parsed = self.bases.iter()
+                .find_map(|base| fs::read_to_string(base.join(theme).join("index.theme")).ok())
+                .and_then(|content| parse_index_theme(&content));
+            if parsed.is_none() { tracing::debug!(target: "icons", "icon theme {theme} has no readable index.theme"); }
+            self.themes.insert(theme.to_string(), parsed);
+        }
+        self.themes.get(theme)?.as_ref()
+    }
+
+    /// Directories of `theme`, its `Inherits` chain and the fallback that
+    /// exist under some base and haven't been listed yet.
+    fn unlisted_dirs(&mut self, theme: &str) -> Vec<PathBuf> {
+        let mut chain   = vec![theme.to_string(), FALLBACK_THEME.to_string()];
+        let mut visited = HashSet::new();
+        let mut dirs    = Vec::new();
+        while let Some(name) = chain.pop() {
+            if visited.len() > MAX_INHERIT_DEPTH * 2 || !visited.insert(name.clone()) { continue; }
+            let Some(parsed) = self.load(&name) else { continue };
+            chain.extend(parsed.inherits.iter().cloned());
+            let subdirs: Vec<String> = parsed.dirs.iter().map(|dir| dir.path.clone()).collect();
+            for root in self.bases.iter().map(|base| base.join(&name)).filter(|root| root.is_dir()) {
+                dirs.extend(subdirs.iter().map(|dir| root.join(dir)));
+            }
+        }
+        dirs.retain(|dir| !self.listings.contains_key(dir));
+        dirs
+    }
+
+    /// An exact size match anywhere in `theme`, else the closest size.
+    fn lookup_icon(&mut self, theme: &str, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        self.load(theme)?;
+        let Some(Some(parsed)) = self.themes.get(theme) else { return None };
+
+        let mut closest: Option<(u32, PathBuf)> = None;
+        for dir in &parsed.dirs {
+            let exact = dir.matches_size(size, scale);
+            let dist  = dir.size_distance(size, scale);
+            if !exact && closest.as_ref().is_some_and(|(d, _)| *d <= dist) { continue; }
+            let found = self.bases.iter()
+                .find_map(|base| file_in(&mut self.listings, base.join(theme).join(&dir.path), name));
+            match found {
+                Some(file) if exact => return Some(file),
+                Some(file)          => closest = Some((dist, file)),
+                None                => {}
+            }
+        }
+        closest.map(|(_, path)| path)
+    }
+}
+
+/// `dir/name.{png,svg,xpm}` if it exists, from a cached listing of `dir`.
+fn file_in(listings: &mut HashMap<PathBuf, HashSet<String>>, dir: PathBuf, name: &str) -> Option<PathBuf> {
+    let listing = listings.entry(dir.clone()).or_insert_with(|| list_dir(&dir));
+    EXTS.iter().map(|ext| format!("{name}.{ext}"))
+        .find(|file| listing.contains(file))
+        .map(|file| dir.join(file))
+}
+
+fn list_dir(dir: &PathBuf) -> HashSet<String> {
+    fs::read_dir(dir).map(|rd| rd.filter_map(Result::ok)
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect()).unwrap_or_default()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: PathBuf, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_sizes_and_inheritance() {
+        let base = std::env::temp_dir().join(format!("tusk-icon-theme-{}", std::process::id()));
+        write(base.join("Child/index.theme"), "[Icon Theme]\nInherits=Parent\nDirectories=16x16/apps,48x48/apps\n\
+            [16x16/apps]\nSize=16\nType=Fixed\n[48x48/apps]\nSize=48\nType=Fixed\n");
+        write(base.join("Parent/index.theme"), "[Icon Theme]\nInherits=Child\nDirectories=scalable/apps\n\
+            [scalable/apps]\nSize=64\nMinSize=8\nMaxSize=512\nType=Scalable\n");
+        write(base.join("Child/16x16/apps/foo.png"), "");
+        write(base.join("Child/48x48/apps/foo.png"), "");
+        write(base.join("Child/48x48/apps/foo.svg"), "");
+        write(base.join("Parent/scalable/apps/bar.svg"), "");
+
+        let mut set = ThemeSet::new(vec![base.clone()]);
+        assert_eq!(set.find("Child", "foo", 48, 1), Some(base.join("Child/48x48/apps/foo.png")));
+        assert_eq!(set.find("Child", "foo", 20, 1), Some(base.join("Child/16x16/apps/foo.png")));
+        assert_eq!(set.find("Child", "foo", 40, 1), Some(base.join("Child/48x48/apps/foo.png")));
+        // Found through Inherits despite the cycle back to Child.
+        assert_eq!(set.find("Child", "bar", 32, 1), Some(base.join("Parent/scalable/apps/bar.svg")));
+        assert_eq!(set.find("Child", "missing", 32, 1), None);
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -0,0 +1,101 @@
+//! Input-method composition (fcitx, ibus) in the search field.
+//!
+//! Design:
+//! - egui's `TextEdit` shows the preedit string inline and keeps it in its
+//!   buffer until the input method commits. The launcher rebuilds that
+//!   buffer from the app's query every frame, so while composing the field
+//!   edits a copy kept here instead, and the app only hears about committed
+//!   text: "ni" on the way to "你" is never searched.
+//! - Composing also owns Enter (commit), Escape (cancel) and the arrow keys
+//!   (candidate list); `touched` tells the GUI to leave them alone for any
+//!   frame a composition was open in.
+//! - Dead keys need nothing here: winit delivers them already combined, as
+//!   ordinary text.
+
+/// egui's `ImeEvent`, without the dependency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImeEvent<'a> {
+    Enabled,
+    Preedit(&'a str),
+    Commit(&'a str),
+    Disabled,
+}
+
+#[derive(Debug, Default)]
+pub struct Composition {
+    /// A preedit is open after this frame's events.
+    active: bool,
+    /// One was open before them.
+    before: bool,
+    /// The search field's text, preedit included, while composing.
+    text:   String,
+}
+
+impl Composition {
+    /// Take this frame's input-method events; call before the field is drawn.
+    pub fn begin_frame<'a>(&mut self, events: impl IntoIterator<Item = ImeEvent<'a>>) {
+        self.before = self.active;
+        for event in events {
+            self.active = match event {
+                ImeEvent::Preedit(s)                     => !s.is_empty(),
+                ImeEvent::Commit(_) | ImeEvent::Disabled => false,
+                ImeEvent::Enabled                        => self.active,
+            };
+        }
+    }
+
+    /// A composition was open at some point this frame.
+    pub fn touched(&self) -> bool {
+        self.active || self.before
+    }
+
+    /// What the search field shows this frame: the app's `query`, or the
+    /// text being composed.
+    pub fn buffer(&self, query: String) -> String {
+        if self.before { self.text.clone() } else { query }
+    }
+
+    /// The field changed to `text`: the query to search, or `None` while
+    /// the input method is still composing it.
+    pub fn edited(&mut self, text: &str) -> Option<String> {
+        if self.active {
+            self.text = text.to_string();
+            return None;
+        }
+        self.text.clear();
+        Some(text.to_string())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composition() {
+        let mut ime = Composition::default();
+        // Typing "ni" and picking 你 after "fire " was already searched.
+        ime.begin_frame([ImeEvent::Enabled, ImeEvent::Preedit("n")]);
+        assert_eq!(ime.buffer("fire ".into()), "fire ");
+        assert_eq!(ime.edited("fire n"), None);
+
+        ime.begin_frame([ImeEvent::Preedit("ni")]);
+        assert_eq!(ime.buffer("fire ".into()), "fire n");
+        assert_eq!(ime.edited("fire ni"), None);
+        assert!(ime.touched());
+
+        ime.begin_frame([ImeEvent::Preedit(""), ImeEvent::Commit("你")]);
+        assert_eq!(ime.buffer("fire ".into()), "fire ni");
+        assert_eq!(ime.edited("fire 你").as_deref(), Some("fire 你"));
+        assert!(ime.touched());
+
+        // The frame after, Enter is the launcher's again.
+        ime.begin_frame([]);
+        assert!(!ime.touched());
+        assert_eq!(ime.buffer("fire 你".into()), "fire 你");
+    }
+}
@@ -0,0 +1,194 @@
+//! Single-instance control socket.
+//!
+//! The first instance binds `$XDG_RUNTIME_DIR/tusk-launcher.sock`; later
+//! invocations connect to it, send one command line and exit. Protocol is
+//! plain text, one command per connection:
+//!
+//!   client → `SHOW\n` | `HIDE\n` | `TOGGLE\n` | `QUIT\n` | `WINDOWS\n` | `TRAY-DEBUG\n`
+//!            | `QUERY <text>\n` | `DND ON\n` | `DND OFF\n`
+//!   server → `OK\n`   | `ERR <reason>\n`
+//!
+//! The same commands also arrive over D-Bus (`crate::dbus`).
+//!
+//! Only connections from our own UID are accepted (checked via `SO_PEERCRED`),
+//! and the socket file itself is created `0600` inside a `0700` directory.
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        io::AsRawFd,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    sync::LazyLock,
+    thread,
+    time::Duration,
+};
+
+const SOCKET_NAME: &str = "tusk-launcher.sock";
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Show,
+    Hide,
+    Toggle,
+    Quit,
+    /// Show with the query preset to the window-switcher prefix.
+    Windows,
+    /// Open the tray debug view; not in the usage text.
+    TrayDebug,
+    /// Show with this query typed in.
+    SetQuery(String),
+    /// Turn do not disturb on or off; the window stays as it is.
+    DoNotDisturb(bool),
+}
+
+impl Command {
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(query) = s.trim_end_matches(['\r', '\n']).strip_prefix("QUERY ") {
+            return Some(Command::SetQuery(query.to_string()));
+        }
+        match s.trim().to_ascii_uppercase().as_str() {
+            "SHOW"                  => Some(Command::Show),
+            "HIDE"                  => Some(Command::Hide),
+            "TOGGLE"                => Some(Command::Toggle),
+            "QUIT"                  => Some(Command::Quit),
+            "WINDOWS" | "--WINDOWS" => Some(Command::Windows),
+            "TRAY-DEBUG"            => Some(Command::TrayDebug),
+            "DND ON"                => Some(Command::DoNotDisturb(true)),
+            "DND OFF"               => Some(Command::DoNotDisturb(false)),
+            _                       => None,
+        }
+    }
+
+    /// The protocol line, without the newline.
+    fn line(&self) -> String {
+        match self {
+            Command::Show            => "SHOW".into(),
+            Command::Hide            => "HIDE".into(),
+            Command::Toggle          => "TOGGLE".into(),
+            Command::Quit            => "QUIT".into(),
+            Command::Windows         => "WINDOWS".into(),
+            Command::TrayDebug       => "TRAY-DEBUG".into(),
+            Command::SetQuery(query) => format!("QUERY {}", query.replace('\n', " ")),
+            Command::DoNotDisturb(on) => if *on { "DND ON".into() } else { "DND OFF".into() },
+        }
+    }
+}
+
+static SOCKET_PATH: LazyLock<io::Result<PathBuf>> = LazyLock::new(|| {
+    let dir = match crate::paths::runtime_dir() {
+        Some(dir) => dir,
+        None => {
+            // No XDG_RUNTIME_DIR (e.g. started outside a logind session): use a
+            // private per-user directory under /tmp instead.
+            let dir = std::env::temp_dir().join(format!("tusk-launcher-{}", uid()));
+            fs::DirBuilder::new().mode(0o700).create(&dir).or_else(|e| {
+                if e.kind() == io::ErrorKind::AlreadyExists { Ok(()) } else { Err(e) }
+            })?;
+            dir
+        }
+    };
+    let meta = fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() || meta.uid() != uid() || meta.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a private directory owned by us", dir.display()),
+        ));
+    }
+    Ok(dir.join(SOCKET_NAME))
+});
+
+fn socket_path() -> io::Result<PathBuf> {
+    SOCKET_PATH.as_ref().map(Clone::clone).map_err(|e| io::Error::new(e.kind(), e.to_string()))
+}
+
+fn uid() -> u32 { unsafe { libc::getuid() } }
+
+/// Send `cmd` to a running instance. `Ok(())` means it was delivered and
+/// acknowledged; any error means no usable instance is listening.
+pub fn send(cmd: &Command) -> io::Result<()> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.write_all(format!("{}\n", cmd.line()).as_bytes())?;
+    stream.flush()?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    match reply.trim() {
+        "OK" => Ok(()),
+        other => Err(io::Error::other(format!("instance replied {other:?}"))),
+    }
+}
+
+/// Whether an instance is listening, without sending it anything.
+pub fn is_running() -> bool {
+    socket_path().is_ok_and(|path| UnixStream::connect(path).is_ok())
+}
+
+/// Bind the control socket, removing a stale socket file left behind by a
+/// crashed instance. Fails if another instance is actually listening.
+pub fn bind() -> io::Result<UnixListener> {
+    let path = socket_path()?;
+    if let Ok(meta) = fs::symlink_metadata(&path) {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, "another instance is running"));
+        }
+        // Never unlink something we didn't create (e.g. a planted symlink).
+        if meta.uid() != uid() || !std::os::unix::fs::FileTypeExt::is_socket(&meta.file_type()) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("refusing to replace {}", path.display()),
+            ));
+        }
+        fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Remove our socket file. Called on orderly shutdown; a crash leaves it
+/// behind for `bind()` to clean up on the next start.
+pub fn cleanup() {
+    if let Ok(path) = socket_path() { let _ = fs::remove_file(path); }
+}
+
+/// Accept connections on a background thread, invoking `on_command` for each
+/// well-formed command from a peer running as our own user.
+pub fn serve(listener: UnixListener, on_command: impl Fn(Command) + Send + 'static) {
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            if peer_uid(&stream) != Some(uid()) {
+                tracing::warn!(target: "ipc", "rejected connection from another user");
+                continue;
+            }
+            let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_err() { continue; }
+            let mut stream = stream;
+            match Command::parse(&line) {
+                Some(cmd) => {
+                    let _ = stream.write_all(b"OK\n");
+                    on_command(cmd);
+                }
+                None => { let _ = stream.write_all(b"ERR unknown command\n"); }
+            }
+        }
+    });
+}
+
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void, &mut len,
+        )
+    };
+    (rc == 0).then_some(cred.uid)
+}
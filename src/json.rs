@@ -0,0 +1,179 @@
+//! Minimal JSON reader for the few places that consume JSON from other tools
+//! (`hyprctl -j`, `swaymsg -r`) or the web (currency rates, weather).
+//! Read-only and tolerant: numbers are `f64`, objects keep key order, and
+//! malformed input yields `None`.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(s: &str) -> Option<Json> {
+        let mut p = Parser { s: s.as_bytes(), pos: 0 };
+        let v = p.value()?;
+        p.skip_ws();
+        (p.pos == p.s.len()).then_some(v)
+    }
+
+    /// Object member lookup; `None` for non-objects and missing keys.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _                  => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if let Json::Str(s) = self { Some(s) } else { None }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        if let Json::Num(n) = self { Some(*n) } else { None }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let Json::Bool(b) = self { Some(*b) } else { None }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        if let Json::Arr(a) = self { a } else { &[] }
+    }
+}
+
+struct Parser<'a> {
+    s:   &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.s.get(self.pos).is_some_and(u8::is_ascii_whitespace) { self.pos += 1; }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        if self.s.get(self.pos) == Some(&c) { self.pos += 1; true } else { false }
+    }
+
+    fn literal(&mut self, word: &str, v: Json) -> Option<Json> {
+        self.s[self.pos..].starts_with(word.as_bytes()).then(|| { self.pos += word.len(); v })
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_ws();
+        match *self.s.get(self.pos)? {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => self.string().map(Json::Str),
+            b't' => self.literal("true",  Json::Bool(true)),
+            b'f' => self.literal("false", Json::Bool(false)),
+            b'n' => self.literal("null",  Json::Null),
+            _    => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        if self.eat(b'}') { return Some(Json::Obj(members)); }
+        loop {
+            self.skip_ws();
+            let key = self.string()?;
+            if !self.eat(b':') { return None; }
+            members.push((key, self.value()?));
+            if self.eat(b'}') { return Some(Json::Obj(members)); }
+            if !self.eat(b',') { return None; }
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') { return Some(Json::Arr(items)); }
+        loop {
+            items.push(self.value()?);
+            if self.eat(b']') { return Some(Json::Arr(items)); }
+            if !self.eat(b',') { return None; }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.s.get(self.pos) != Some(&b'"') { return None; }
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            match *self.s.get(self.pos)? {
+                b'"'  => { self.pos += 1; return String::from_utf8(out).ok(); }
+                b'\\' => {
+                    self.pos += 1;
+                    let c = match *self.s.get(self.pos)? {
+                        b'n' => '\n', b't' => '\t', b'r' => '\r', b'b' => '\u{8}', b'f' => '\u{c}',
+                        b'u' => self.unicode_escape()?,
+                        other => other as char,
+                    };
+                    self.pos += 1;
+                    let mut buf = [0u8; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b => { out.push(b); self.pos += 1; }
+            }
+        }
+    }
+
+    /// `\uXXXX` (with `self.pos` on the `u`), joining surrogate pairs. Leaves
+    /// `self.pos` on the last hex digit.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let hex = |p: &Self, at: usize| -> Option<u32> {
+            u32::from_str_radix(std::str::from_utf8(p.s.get(at..at + 4)?).ok()?, 16).ok()
+        };
+        let hi = hex(self, self.pos + 1)?;
+        self.pos += 4;
+        if (0xD800..0xDC00).contains(&hi) && self.s.get(self.pos + 1..self.pos + 3) == Some(b"\\u") {
+            let lo = hex(self, self.pos + 3)?;
+            self.pos += 6;
+            return char::from_u32(0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00));
+        }
+        Some(char::from_u32(hi).unwrap_or('\u{FFFD}'))
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.pos;
+        while self.s.get(self.pos).is_some_and(|c| c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E')) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.pos]).ok()?.parse().ok().map(Json::Num)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested() {
+        let v = Json::parse(r#"[{"address": "0x5a", "title": "a \"b\" é", "pid": 42, "mapped": true, "ws": {"id": -1}}]"#).unwrap();
+        let win = &v.as_array()[0];
+        assert_eq!(win.get("address").and_then(Json::as_str), Some("0x5a"));
+        assert_eq!(win.get("title").and_then(Json::as_str), Some("a \"b\" é"));
+        assert_eq!(win.get("pid").and_then(Json::as_f64), Some(42.0));
+        assert_eq!(win.get("mapped").and_then(Json::as_bool), Some(true));
+        assert_eq!(win.get("ws").and_then(|w| w.get("id")).and_then(Json::as_f64), Some(-1.0));
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert_eq!(Json::parse("{\"a\": }"), None);
+        assert_eq!(Json::parse("[1, 2"), None);
+        assert_eq!(Json::parse("\"\\ud83d\\ude00\""), Some(Json::Str("😀".into())));
+    }
+}
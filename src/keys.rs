@@ -0,0 +1,141 @@
+//! In-launcher keybindings: the `key-*` lists in config.toml.
+//!
+//! Design:
+//! - config.toml has no tables, so each action is its own list setting
+//!   (`key-next = ["Down", "Ctrl+J"]`); an empty list unbinds it.
+//! - A chord is modifiers and a key name joined with `+` (`Ctrl+Shift+K`,
+//!   `Alt+Enter`, `PageDown`). Parsing stays free of egui; the GUI turns
+//!   key names into `egui::Key`s with `Key::from_name`.
+//! - `key-launch-nth` lists modifier sets rather than chords: held with
+//!   1–9 they launch that result.
+//! - Bindings are taken before the search field sees the keys, so chords
+//!   that would type text (a plain letter, digit or Space) are refused.
+//! - Enter and Escape keep their fixed meaning; the action menu and the
+//!   grid's arrow keys are handled before these bindings.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Select the next result.
+    Next,
+    /// Select the previous result.
+    Previous,
+    /// Empty the search field.
+    Clear,
+    /// Open the selected result's action menu.
+    Actions,
+    /// Open the selected app's launch options editor.
+    LaunchOptions,
+    /// Launch the nth visible result, counting from 0.
+    LaunchNth(usize),
+    /// Scale the whole UI up or down a step, or back to 1.
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+}
+
+/// Config key and default chords of each action but `LaunchNth`.
+pub const ACTIONS: &[(&str, Action, &[&str])] = &[
+    ("key-next",           Action::Next,          &["Down", "Ctrl+J", "Ctrl+N"]),
+    ("key-previous",       Action::Previous,      &["Up", "Ctrl+K", "Ctrl+P"]),
+    ("key-clear",          Action::Clear,         &["Ctrl+L"]),
+    ("key-actions",        Action::Actions,       &["Alt+Enter"]),
+    ("key-launch-options", Action::LaunchOptions, &["Ctrl+E"]),
+    ("key-zoom-in",        Action::ZoomIn,        &["Ctrl+Plus", "Ctrl+Equals"]),
+    ("key-zoom-out",       Action::ZoomOut,       &["Ctrl+Minus"]),
+    ("key-zoom-reset",     Action::ZoomReset,     &["Ctrl+0"]),
+];
+
+/// `ACTIONS`' default chords for config key `key`.
+pub fn default_chords(key: &str) -> Vec<String> {
+    ACTIONS.iter().find(|(name, ..)| *name == key)
+        .map(|(_, _, chords)| chords.iter().map(|s| s.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Default modifier sets of `key-launch-nth`.
+pub const LAUNCH_NTH: &[&str] = &["Ctrl"];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Chord {
+    pub ctrl:  bool,
+    pub alt:   bool,
+    pub shift: bool,
+    /// egui key name; empty for a bare modifier set.
+    pub key:   String,
+}
+
+/// `Ctrl+Shift+K`; names are case-insensitive and `Control` is `Ctrl`.
+/// With `modifiers_only`, the whole string is modifiers (`Ctrl+Alt`).
+pub fn parse_chord(s: &str, modifiers_only: bool) -> Option<Chord> {
+    let mut chord = Chord::default();
+    let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+    if !modifiers_only { chord.key = parts.pop().filter(|k| !k.is_empty())?.to_string(); }
+    for part in parts {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => chord.ctrl  = true,
+            "alt"              => chord.alt   = true,
+            "shift"            => chord.shift = true,
+            _                  => return None,
+        }
+    }
+    Some(chord)
+}
+
+impl Chord {
+    /// Would type into the search field rather than reach a binding.
+    fn types_text(&self) -> bool {
+        !self.ctrl && !self.alt && (self.key.chars().count() == 1 || self.key.eq_ignore_ascii_case("space"))
+    }
+}
+
+/// Every binding from the configured lists, in `ACTIONS` order then 1–9;
+/// entries that don't parse or would type text are reported and skipped.
+/// `lists` gives each action's chords (config key order), `nth` the
+/// `key-launch-nth` modifier sets.
+pub fn bindings(lists: &[(Action, &[String])], nth: &[String]) -> (Vec<(Action, Chord)>, Vec<String>) {
+    let mut out    = Vec::new();
+    let mut errors = Vec::new();
+    for (action, chords) in lists {
+        for s in chords.iter() {
+            match parse_chord(s, false) {
+                Some(chord) if !chord.types_text() => out.push((*action, chord)),
+                Some(_) => errors.push(format!("\"{s}\" would type into the search field")),
+                None    => errors.push(format!("\"{s}\" isn't a key chord")),
+            }
+        }
+    }
+    for s in nth {
+        match parse_chord(s, true) {
+            Some(mods) if mods.ctrl || mods.alt => out.extend((1..=9).map(|n| {
+                (Action::LaunchNth(n - 1), Chord { key: n.to_string(), ..mods.clone() })
+            })),
+            Some(_) => errors.push(format!("\"{s}\" needs Ctrl or Alt, or the digits would type")),
+            None    => errors.push(format!("\"{s}\" isn't a set of modifiers")),
+        }
+    }
+    (out, errors)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings() {
+        assert_eq!(parse_chord("Ctrl+Shift+K", false), Some(Chord { ctrl: true, alt: false, shift: true, key: "K".into() }));
+        assert_eq!(parse_chord("control + j", false).map(|c| (c.ctrl, c.key)), Some((true, "j".into())));
+        assert_eq!(parse_chord("Hyper+K", false), None);
+        assert_eq!(parse_chord("Ctrl+", false), None);
+
+        let next = vec!["Down".to_string(), "J".to_string(), "Ctrl+J".to_string()];
+        let (out, errors) = bindings(&[(Action::Next, &next)], &["Alt".to_string(), "Shift".to_string()]);
+        assert_eq!(out.iter().filter(|(a, _)| *a == Action::Next).count(), 2);
+        assert_eq!(out.iter().filter(|(a, _)| matches!(a, Action::LaunchNth(_))).count(), 9);
+        assert_eq!(out.last().map(|(a, c)| (*a, c.alt, c.key.as_str())), Some((Action::LaunchNth(8), true, "9")));
+        assert_eq!(errors.len(), 2);
+    }
+}
@@ -0,0 +1,181 @@
+//! Tusk Launcher. The binary (`main.rs`) only calls `run`; the modules live
+//! in this library so `benches/` can reach the startup path through
+//! `profile`.
+
+mod system;
+#[cfg(feature = "pipewire")]
+mod audio;
+mod app_launcher;
+mod gui;
+mod sni;
+#[cfg(feature = "xembed")]
+mod xembed;
+mod paths;
+mod logging;
+mod crash;
+mod svg;
+mod ipc;
+mod notifications;
+mod network;
+mod bluetooth;
+mod logind;
+mod stats;
+mod config;
+mod appearance;
+mod fonts;
+mod flex;
+mod animation;
+mod keys;
+mod ime;
+mod bidi;
+mod onboarding;
+mod strftime;
+mod timer;
+mod watch;
+mod tz;
+mod calc;
+mod convert;
+mod weather;
+mod icon_theme;
+mod providers;
+mod json;
+mod windows;
+mod workspaces;
+mod monitors;
+mod backdrop;
+mod x11_hints;
+mod clipboard;
+mod snippets;
+mod recent;
+mod places;
+mod drives;
+mod capture;
+mod wallpaper;
+mod autostart;
+mod exec;
+mod store;
+mod bundle;
+mod pool;
+mod dbus;
+pub mod profile;
+
+use std::process;
+use crate::gui::{AppInterface, EframeGui, load_theme};
+use crate::ipc::Command;
+use crate::system::get_current_time;
+
+/// The command line: hand a command to the running instance, or start one.
+pub fn run() {
+    // `tusk-launcher [--theme NAME] [--verbose] [--profile-startup] [show|hide|toggle|quit|--windows]` —
+    // bare invocation toggles, which keeps the old "run again to close" keybinding behaviour.
+    fn usage() -> ! {
+        eprintln!("Usage: tusk-launcher [--theme NAME] [--verbose] [--profile-startup] [show|hide|toggle|quit|--windows]");
+        eprintln!("       tusk-launcher export|import FILE.tar");
+        process::exit(2);
+    }
+
+    // `export FILE` / `import FILE` bundle the settings and exit without a window.
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    if let [verb, file] = argv.as_slice() {
+        let path = std::path::Path::new(file);
+        let done = match verb.as_str() {
+            "export" => Some(bundle::export(path).map(|n| println!("Exported {n} files to {file}"))),
+            "import" => Some(bundle::import(path).map(|n| println!("Imported {n} files from {file}; replaced files were kept as .bak"))),
+            _        => None,
+        };
+        if let Some(done) = done {
+            if let Err(e) = done {
+                eprintln!("{verb} failed: {e}");
+                process::exit(1);
+            }
+            return;
+        }
+    }
+    let mut cmd = Command::Toggle;
+    let mut theme_override = None;
+    let mut verbose = false;
+    let mut profile_startup = false;
+    // Set by the D-Bus .service file: the call that started us says what to do.
+    let mut dbus_activated = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--verbose" || arg == "-v" {
+            verbose = true;
+        } else if arg == "--profile-startup" {
+            profile_startup = true;
+        } else if arg == "--dbus-activated" {
+            dbus_activated = true;
+        } else if arg == "--theme" {
+            theme_override = Some(args.next().unwrap_or_else(|| usage()));
+        } else if let Some(name) = arg.strip_prefix("--theme=") {
+            theme_override = Some(name.to_string());
+        } else {
+            cmd = Command::parse(&arg).unwrap_or_else(|| usage());
+        }
+    }
+
+    // Profiling runs beside a daemon rather than talking to it.
+    if profile_startup {
+        logging::init(verbose);
+        crash::install();
+        profile::run_phases();
+        start(cmd, theme_override, None, false);
+        return;
+    }
+
+    // Check if another instance is running and hand the command over.
+    if !dbus_activated && ipc::send(&cmd).is_ok() {
+        if theme_override.is_some() { eprintln!("--theme only applies when starting the launcher; pick a theme in config.toml instead"); }
+        return;
+    }
+    if matches!(cmd, Command::Hide | Command::Quit) { return; }
+    if cmd == Command::TrayDebug {
+        eprintln!("tray-debug: the launcher isn't running; start it first, the view shows what its tray host found");
+        process::exit(1);
+    }
+
+    logging::init(verbose);
+    crash::install();
+
+    // Bind our instance
+    let listener = match ipc::bind() {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::error!(target: "ipc", "Failed to bind control socket: {}", e);
+            process::exit(1);
+        }
+    };
+    start(cmd, theme_override, Some(listener), dbus_activated);
+}
+
+/// Load the theme and config, then run the GUI until it quits. Without a
+/// `listener` (profiling) the socket and autostart belong to another instance.
+/// `hidden` starts a daemon in the background; without daemon-mode the
+/// window opens regardless.
+fn start(cmd: Command, theme_override: Option<String>, listener: Option<std::os::unix::net::UnixListener>, hidden: bool) {
+    let own_socket = listener.is_some();
+    // Load theme and run GUI. theme.css alone first: config.toml may still need migrating from its
+    // .config block, and only then is the profile known.
+    let base = load_theme("");
+    // A brand-new setup gets the first-start wizard; a migrated one doesn't.
+    let first_run = !config::exists() && base.config_block().is_none();
+    let config = config::load(&base);
+    let scheme = if appearance::is_enabled(&config) { appearance::current() } else { appearance::ColorScheme::NoPreference };
+    let profile = theme_override.clone().unwrap_or_else(|| appearance::profile_for(&config, scheme).to_string());
+    let theme = if profile.is_empty() { base } else { load_theme(&profile) };
+    tracing::debug!(target: "gui", "Current time: {}", get_current_time(&config));
+    if config.run_autostart && own_socket { autostart::run(); }
+    if config.wallpaper_restore && own_socket { wallpaper::restore(&config); }
+
+    let hidden = hidden && config.daemon_mode;
+    let mut app = Box::new(app_launcher::AppLauncher::new(config.clone()));
+    if cmd == Command::Windows { app.handle_input(&config.window_prefix); }
+    if let Err(e) = EframeGui::run(app, theme, config, theme_override, scheme, listener, first_run, hidden) {
+        tracing::error!(target: "gui", "Error running GUI: {}", e);
+        if own_socket { ipc::cleanup(); }
+        process::exit(1);
+    }
+
+    if own_socket { ipc::cleanup(); }
+    tracing::info!(target: "gui", "Application exiting normally");
+}
@@ -0,0 +1,105 @@
+//! Log output: `tracing` events on stderr and, with `log-file`, in a file.
+//!
+//! Design:
+//! - Events name a subsystem as their target rather than the module path, so
+//!   `log-level` reads like `warn,sni=debug`. Targets: `sni` (tray, XEmbed
+//!   included), `icons`, `launch`, `apps` (desktop entries, app cache),
+//!   `audio`, `config`, `theme`, `search`, `window`, `power`, `network`,
+//!   `bluetooth`, `drives`, `capture`, `wallpaper`, `weather`,
+//!   `notifications`, `clipboard`, `appearance`, `ipc`, `gui`.
+//! - Set up before config.toml is loaded so its own warnings get logged:
+//!   `log-level` and `log-file` are read from the file directly, and only
+//!   at startup.
+//! - `--verbose` raises the default level to `debug`; targets `log-level`
+//!   names keep their own.
+//! - The file is `$XDG_STATE_HOME/tusk-launcher/tusk-launcher.log`, started
+//!   afresh each run; stderr gets the same events.
+//! - `tracing-subscriber` without `env-filter`: `Targets` takes the same
+//!   `level,target=level` syntax without a regex engine.
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+use crate::config::Value;
+
+const LOG_FILE: &str = "tusk-launcher/tusk-launcher.log";
+
+/// `spec` as a filter: a bare level sets the default, `target=level` pairs
+/// override it. Without a default level it's `info`; `verbose` raises it
+/// to at least `debug`.
+fn filter(spec: &str, verbose: bool) -> Result<Targets, String> {
+    let mut targets: Targets = spec.trim().parse().map_err(|e| format!("log-level {spec:?}: {e}"))?;
+    let default = targets.default_level().unwrap_or(LevelFilter::INFO);
+    targets = targets.with_default(if verbose { default.max(LevelFilter::DEBUG) } else { default });
+    Ok(targets)
+}
+
+/// Create (or empty) the log file.
+fn open_file() -> Result<(PathBuf, File), String> {
+    let path = crate::paths::state_home().join(LOG_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Can't create {}: {e}", dir.display()))?;
+    }
+    let file = File::create(&path).map_err(|e| format!("Can't write {}: {e}", path.display()))?;
+    Ok((path, file))
+}
+
+/// Install the global subscriber; once, at startup.
+pub fn init(verbose: bool) {
+    let values = crate::config::current_values();
+    let scalar = |key: &str| values.get(key).and_then(Value::scalar).map(str::to_string);
+
+    let mut problems = Vec::new();
+    let targets = filter(&scalar("log-level").unwrap_or_else(|| "info".into()), verbose).unwrap_or_else(|e| {
+        problems.push(format!("{e}; logging at info"));
+        filter("info", verbose).unwrap_or_default()
+    });
+    let file = match scalar("log-file").as_deref() {
+        Some("true") => open_file().map_err(|e| problems.push(e)).ok(),
+        _            => None,
+    };
+    let path = file.as_ref().map(|(path, _)| path.clone());
+    let file_layer = file.map(|(_, file)| tracing_subscriber::fmt::layer().with_ansi(false).with_writer(Mutex::new(file)));
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .with(targets)
+        .init();
+
+    for problem in problems { tracing::warn!(target: "config", "{problem}"); }
+    if let Some(path) = path { tracing::info!(target: "gui", "Logging to {}", path.display()); }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+
+    #[test]
+    fn test_filter() {
+        let targets = filter("warn,sni=debug", false).unwrap();
+        assert!(targets.would_enable("sni", &Level::DEBUG));
+        assert!(!targets.would_enable("icons", &Level::INFO));
+        assert!(targets.would_enable("icons", &Level::WARN));
+
+        // No default level: info, not off.
+        assert!(filter("sni=trace", false).unwrap().would_enable("launch", &Level::INFO));
+        // --verbose raises the default but keeps per-target levels.
+        let verbose = filter("warn,audio=error", true).unwrap();
+        assert!(verbose.would_enable("launch", &Level::DEBUG));
+        assert!(!verbose.would_enable("audio", &Level::WARN));
+        assert!(filter("trace", true).unwrap().would_enable("gui", &Level::TRACE));
+
+        assert!(filter("sni=loud", false).is_err());
+    }
+}
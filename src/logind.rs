@@ -0,0 +1,277 @@
+//! systemd-logind power actions (`org.freedesktop.login1`).
+//!
+//! Design:
+//! - Like the network and Bluetooth hosts, a background tokio runtime keeps
+//!   one system-bus connection for the launcher's lifetime, and the GUI hands
+//!   it power actions over a channel — nothing waits on the bus (or on a
+//!   polkit password prompt) on the UI thread.
+//! - `Can*` answers decide which power buttons are drawn; "challenge" (polkit
+//!   will ask for a password) counts as supported. They're asked once the
+//!   host starts; until then, or when logind can't be asked, every action
+//!   counts as supported.
+//! - Actions fall back to the configured command lists only when logind is
+//!   unreachable, not when it refuses — a refusal would fail the same way.
+//! - Action calls are interactive and have no timeout: polkit may be waiting
+//!   on the user, and giving up would run the command list as a second try.
+//! - Logout stays on the command list: `TerminateSession` kills the session
+//!   outright, where the compositor commands let it exit cleanly.
+//! - The idle inhibitor is the fd `Inhibit` returns, kept by the host; logind
+//!   drops the lock when it's closed, so it lasts exactly as long as this
+//!   process holds it.
+use std::os::fd::OwnedFd;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use zbus::Connection;
+use crate::gui::Config;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const LOGIND_BUS:    &str = "org.freedesktop.login1";
+const LOGIND_PATH:   &str = "/org/freedesktop/login1";
+const MANAGER_IFACE: &str = "org.freedesktop.login1.Manager";
+
+/// For the `Can*` queries and `Inhibit`; the actions themselves may sit on a prompt.
+const T_CALL: Duration = Duration::from_secs(2);
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerAction {
+    PowerOff,
+    Reboot,
+    Suspend,
+}
+
+impl PowerAction {
+    fn method(self) -> &'static str {
+        match self {
+            PowerAction::PowerOff => "PowerOff",
+            PowerAction::Reboot   => "Reboot",
+            PowerAction::Suspend  => "Suspend",
+        }
+    }
+
+    fn can_method(self) -> &'static str {
+        match self {
+            PowerAction::PowerOff => "CanPowerOff",
+            PowerAction::Reboot   => "CanReboot",
+            PowerAction::Suspend  => "CanSuspend",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PowerAction::PowerOff => "power off",
+            PowerAction::Reboot   => "restart",
+            PowerAction::Suspend  => "suspend",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum LogindError {
+    /// No system bus, or logind isn't running on it.
+    Unavailable(String),
+    /// logind answered with an error (e.g. polkit denied the action).
+    Failed(String),
+}
+
+/// Which power actions logind allows; everything is assumed supported when
+/// logind can't be asked.
+#[derive(Clone, Copy, Debug)]
+pub struct PowerSupport {
+    pub power_off: bool,
+    pub reboot:    bool,
+    pub suspend:   bool,
+}
+
+impl Default for PowerSupport {
+    fn default() -> Self {
+        PowerSupport { power_off: true, reboot: true, suspend: true }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LogindState {
+    pub power:          PowerSupport,
+    /// The caffeine toggle's idle lock is held.
+    pub idle_inhibited: bool,
+}
+
+pub type SharedLogindState = Arc<Mutex<LogindState>>;
+
+/// Called from the backend after every change, so the GUI can repaint.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+enum LogindAction {
+    /// `fallback` is the command list to try if logind is unreachable.
+    Power { action: PowerAction, fallback: Vec<String> },
+    SetIdleInhibit(bool),
+}
+
+pub struct LogindHost {
+    pub state: SharedLogindState,
+    action_tx: tokio::sync::mpsc::UnboundedSender<LogindAction>,
+}
+
+impl LogindHost {
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
+        let power = config.enable_power_options && config.use_logind;
+        if !power && !config.enable_idle_inhibitor { return None; }
+
+        let state: SharedLogindState = Arc::new(Mutex::new(LogindState::default()));
+        let state_bg = Arc::clone(&state);
+        let waker: Waker = Arc::new(on_change);
+        let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(run_host(state_bg, action_rx, waker)),
+                Err(e) => tracing::error!(target: "power", "runtime error: {e}"),
+            }
+        });
+
+        Some(LogindHost { state, action_tx })
+    }
+
+    /// Run `action` through logind, letting polkit prompt if it needs to;
+    /// `fallback` runs instead when logind turns out to be unreachable.
+    pub fn power(&self, action: PowerAction, fallback: &[String]) {
+        let _ = self.action_tx.send(LogindAction::Power { action, fallback: fallback.to_vec() });
+    }
+
+    /// Take or drop the idle lock; `state.idle_inhibited` follows once it's done.
+    pub fn set_idle_inhibit(&self, on: bool) {
+        let _ = self.action_tx.send(LogindAction::SetIdleInhibit(on));
+    }
+}
+
+// ============================================================================
+// Host
+// ============================================================================
+
+async fn run_host(
+    state:         SharedLogindState,
+    mut action_rx: tokio::sync::mpsc::UnboundedReceiver<LogindAction>,
+    waker:         Waker,
+) {
+    let conn = match Connection::system().await {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            tracing::warn!(target: "power", "no system bus: {e}");
+            None
+        }
+    };
+
+    if let Some(conn) = &conn {
+        let power = PowerSupport {
+            power_off: can(conn, PowerAction::PowerOff).await,
+            reboot:    can(conn, PowerAction::Reboot).await,
+            suspend:   can(conn, PowerAction::Suspend).await,
+        };
+        if let Ok(mut s) = state.lock() { s.power = power; }
+        waker();
+    }
+
+    let mut idle_lock: Option<OwnedFd> = None;
+    while let Some(action) = action_rx.recv().await {
+        match action {
+            // A power call can sit on a polkit prompt; run it as a task so
+            // the caffeine toggle still answers meanwhile.
+            LogindAction::Power { action, fallback } => {
+                let conn = conn.clone();
+                tokio::spawn(async move { run_power_action(conn.as_ref(), action, &fallback).await });
+            }
+            LogindAction::SetIdleInhibit(on) => {
+                if !on {
+                    idle_lock = None;
+                } else if idle_lock.is_none() {
+                    let result = match &conn {
+                        Some(conn) => inhibit_idle(conn).await,
+                        None       => Err(LogindError::Unavailable("no system bus".into())),
+                    };
+                    match result {
+                        Ok(fd) => idle_lock = Some(fd),
+                        Err(LogindError::Unavailable(e) | LogindError::Failed(e)) => tracing::warn!(target: "power", "idle inhibitor: {e}"),
+                    }
+                }
+                if let Ok(mut s) = state.lock() { s.idle_inhibited = idle_lock.is_some(); }
+                waker();
+            }
+        }
+    }
+}
+
+async fn run_power_action(conn: Option<&Connection>, action: PowerAction, fallback: &[String]) {
+    let result = match conn {
+        Some(conn) => call(conn, action).await,
+        None       => Err(LogindError::Unavailable("no system bus".into())),
+    };
+    match result {
+        Ok(()) => {}
+        Err(LogindError::Failed(e)) => tracing::warn!(target: "power", "Failed to {} via logind: {e}", action.label()),
+        Err(LogindError::Unavailable(e)) => {
+            tracing::warn!(target: "power", "logind unreachable ({e}); trying the {} commands", action.label());
+            crate::system::execute_power_action(action.label(), fallback);
+        }
+    }
+}
+
+async fn call(conn: &Connection, action: PowerAction) -> Result<(), LogindError> {
+    let reply = conn.call_method(
+        Some(LOGIND_BUS), LOGIND_PATH, Some(MANAGER_IFACE), action.method(), &(true,),
+    ).await;
+    match reply {
+        Ok(_) => Ok(()),
+        Err(e) if is_unavailable(&e) => Err(LogindError::Unavailable(e.to_string())),
+        Err(e) => Err(LogindError::Failed(e.to_string())),
+    }
+}
+
+async fn inhibit_idle(conn: &Connection) -> Result<OwnedFd, LogindError> {
+    let reply = tokio::time::timeout(T_CALL, conn.call_method(
+        Some(LOGIND_BUS), LOGIND_PATH, Some(MANAGER_IFACE), "Inhibit",
+        &("idle", "Tusk Launcher", "Caffeine toggle", "block"),
+    )).await;
+    match reply {
+        Ok(Ok(msg)) => msg.body().deserialize::<zbus::zvariant::OwnedFd>()
+            .map(OwnedFd::from)
+            .map_err(|e| LogindError::Failed(e.to_string())),
+        Ok(Err(e)) if is_unavailable(&e) => Err(LogindError::Unavailable(e.to_string())),
+        Ok(Err(e)) => Err(LogindError::Failed(e.to_string())),
+        Err(_)     => Err(LogindError::Unavailable("timed out".into())),
+    }
+}
+
+async fn can(conn: &Connection, action: PowerAction) -> bool {
+    let reply = tokio::time::timeout(T_CALL, conn.call_method(
+        Some(LOGIND_BUS), LOGIND_PATH, Some(MANAGER_IFACE), action.can_method(), &(),
+    )).await;
+    match reply {
+        // "yes" | "challenge" | "no" | "na"
+        Ok(Ok(msg)) => msg.body().deserialize::<String>()
+            .map_or(true, |answer| matches!(answer.as_str(), "yes" | "challenge")),
+        _ => true,
+    }
+}
+
+/// Errors meaning nobody owns `org.freedesktop.login1` (e.g. elogind-less
+/// systems), as opposed to logind rejecting the call.
+fn is_unavailable(e: &zbus::Error) -> bool {
+    match e {
+        zbus::Error::MethodError(name, _, _) => matches!(
+            name.as_str(),
+            "org.freedesktop.DBus.Error.ServiceUnknown"
+                | "org.freedesktop.DBus.Error.NameHasNoOwner"
+                | "org.freedesktop.DBus.Error.UnknownMethod"
+        ),
+        zbus::Error::InputOutput(_) | zbus::Error::Address(_) => true,
+        _ => false,
+    }
+}
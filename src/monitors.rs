@@ -0,0 +1,234 @@
+//! Which output the window opens on (`window-monitor`), where on it
+//! (`window-anchor`), and how big that output is for `%` window sizes.
+//!
+//! Design:
+//! - egui only reports the size of the monitor the window is already on, so
+//!   outputs come from the same compositor CLIs as the window switcher:
+//!   `hyprctl monitors -j`, `swaymsg -t get_outputs -r`, and
+//!   `xrandr --listmonitors` on X11. The pointer comes from
+//!   `hyprctl cursorpos` or `xdotool getmouselocation`; Sway has no query
+//!   for it, so "cursor" falls back to the focused output there.
+//! - Wayland clients can't place their own window, so `move_window` asks
+//!   the compositor to move ours (matched by pid). On X11 winit can, and
+//!   the GUI sends `OuterPosition` itself.
+//! - Geometry is in the compositor's units: logical pixels on Wayland,
+//!   physical ones on X11 (`pixels_per_point` converts).
+use crate::json::Json;
+use crate::windows::{backend, run, Backend};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    pub name:    String,
+    pub rect:    Rect,
+    /// Has keyboard focus (Hyprland, Sway) or is the primary output (X11).
+    pub focused: bool,
+}
+
+/// All active outputs; empty when no backend is available.
+pub fn list() -> Vec<Monitor> {
+    let result = match backend() {
+        Some(Backend::Hyprland) => run(&["hyprctl", "monitors", "-j"]).map(|s| parse_hyprland(&s)),
+        Some(Backend::Sway)     => run(&["swaymsg", "-t", "get_outputs", "-r"]).map(|s| parse_sway(&s)),
+        Some(Backend::Wmctrl)   => run(&["xrandr", "--listmonitors"]).map(|s| parse_xrandr(&s)),
+        None                    => None,
+    };
+    result.unwrap_or_default()
+}
+
+/// The pointer position, where the compositor tells.
+pub fn cursor() -> Option<(f32, f32)> {
+    match backend()? {
+        Backend::Hyprland => parse_pair(&run(&["hyprctl", "cursorpos"])?),
+        Backend::Sway     => None,
+        Backend::Wmctrl   => parse_xdotool(&run(&["xdotool", "getmouselocation", "--shell"])?),
+    }
+}
+
+/// Compositor pixels per egui point: Wayland compositors count logical
+/// pixels, so only the `ui-scale` zoom applies; X11 counts physical ones.
+pub fn pixels_per_point(zoom: f32, native_pixels_per_point: f32) -> f32 {
+    match backend() {
+        Some(Backend::Wmctrl) => zoom * native_pixels_per_point,
+        _                     => zoom,
+    }
+}
+
+/// The output `choice` means: `"focused"`, `"cursor"` or an output name.
+/// Falls back to the focused output, then the first.
+pub fn pick<'a>(choice: &str, monitors: &'a [Monitor], cursor: Option<(f32, f32)>) -> Option<&'a Monitor> {
+    let chosen = match choice {
+        "focused" | "" => None,
+        "cursor"       => cursor.and_then(|c| monitors.iter().find(|m| m.rect.contains(c))),
+        name           => monitors.iter().find(|m| m.name == name),
+    };
+    chosen.or_else(|| monitors.iter().find(|m| m.focused)).or_else(|| monitors.first())
+}
+
+/// Top-left corner for a window of `size` on `area`: `"center"`,
+/// `"top-center"` (a fifth of the way down) or `"cursor"` (centered on the
+/// pointer, kept on the output).
+pub fn anchor(anchor: &str, area: &Rect, (w, h): (f32, f32), cursor: Option<(f32, f32)>) -> (f32, f32) {
+    let (x, y) = match (anchor, cursor) {
+        ("cursor", Some((cx, cy))) => (cx - w / 2.0, cy - h / 2.0),
+        ("top-center", _)          => (area.x + (area.w - w) / 2.0, area.y + area.h / 5.0),
+        _                          => (area.x + (area.w - w) / 2.0, area.y + (area.h - h) / 2.0),
+    };
+    // Keep the window on the output, the top-left corner if it's too big.
+    (x.min(area.x + area.w - w).max(area.x).round(), y.min(area.y + area.h - h).max(area.y).round())
+}
+
+/// The output for `window-monitor` and `window-anchor`, with the pointer
+/// when either asks for it; `None` when no output could be found.
+pub fn target(monitor: &str, anchor_at: &str) -> Option<(Rect, Option<(f32, f32)>)> {
+    let monitors = list();
+    let wants_cursor = monitor == "cursor" || anchor_at == "cursor";
+    let cursor   = if wants_cursor { cursor() } else { None };
+    // Anchoring at the pointer implies its output unless one is named.
+    let choice   = if monitor.is_empty() && anchor_at == "cursor" { "cursor" } else { monitor };
+    Some((pick(choice, &monitors, cursor)?.rect, cursor))
+}
+
+/// Ask the compositor to move this process's window to `pos`. `false` when
+/// it can't (X11, where the GUI positions the window itself, or no backend).
+pub fn move_window((x, y): (f32, f32)) -> bool {
+    let pid = std::process::id();
+    let (x, y) = (x as i64, y as i64);
+    let done = match backend() {
+        Some(Backend::Hyprland) => run(&["hyprctl", "--batch", &format!(
+            "dispatch setfloating pid:{pid} ; dispatch movewindowpixel exact {x} {y},pid:{pid}"
+        )]),
+        Some(Backend::Sway) => run(&["swaymsg", &format!("[pid={pid}]"), "floating", "enable,",
+                                     "move", "absolute", "position", &x.to_string(), &y.to_string()]),
+        Some(Backend::Wmctrl) | None => return false,
+    };
+    if done.is_none() { tracing::warn!(target: "window", "Failed to move the launcher window to {x},{y}"); }
+    done.is_some()
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+fn num(json: &Json, key: &str) -> Option<f32> {
+    json.get(key).and_then(Json::as_f64).map(|n| n as f32)
+}
+
+/// `width`/`height` are physical pixels; divide by the scale and swap for a
+/// 90° or 270° `transform` to get the logical size.
+fn parse_hyprland(s: &str) -> Vec<Monitor> {
+    let Some(monitors) = Json::parse(s) else { return Vec::new() };
+    monitors.as_array().iter().filter_map(|m| {
+        let scale = num(m, "scale").filter(|s| *s > 0.0).unwrap_or(1.0);
+        let (w, h) = (num(m, "width")? / scale, num(m, "height")? / scale);
+        let turned = num(m, "transform").is_some_and(|t| t as i32 % 2 == 1);
+        Some(Monitor {
+            name:    m.get("name")?.as_str()?.to_string(),
+            rect:    Rect { x: num(m, "x")?, y: num(m, "y")?, w: if turned { h } else { w }, h: if turned { w } else { h } },
+            focused: m.get("focused").and_then(Json::as_bool).unwrap_or(false),
+        })
+    }).collect()
+}
+
+fn parse_sway(s: &str) -> Vec<Monitor> {
+    let Some(outputs) = Json::parse(s) else { return Vec::new() };
+    outputs.as_array().iter()
+        .filter(|o| o.get("active").and_then(Json::as_bool) != Some(false))
+        .filter_map(|o| {
+            let rect = o.get("rect")?;
+            Some(Monitor {
+                name:    o.get("name")?.as_str()?.to_string(),
+                rect:    Rect { x: num(rect, "x")?, y: num(rect, "y")?, w: num(rect, "width")?, h: num(rect, "height")? },
+                focused: o.get("focused").and_then(Json::as_bool).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// ` 0: +*eDP-1 1920/344x1080/194+0+0  eDP-1`; `*` marks the primary output.
+fn parse_xrandr(s: &str) -> Vec<Monitor> {
+    s.lines().skip(1).filter_map(|line| {
+        let mut cols = line.split_whitespace();
+        let _index   = cols.next()?;
+        let flags    = cols.next()?;
+        let geometry = cols.next()?;
+        let name     = cols.next().unwrap_or(flags.trim_start_matches(['+', '*']));
+        // W/mmxH/mm+X+Y
+        let (size, pos) = geometry.split_once('+')?;
+        let (w, h)   = size.split_once('x')?;
+        let (x, y)   = pos.split_once('+')?;
+        let px = |s: &str| s.split('/').next()?.parse::<f32>().ok();
+        Some(Monitor {
+            name:    name.to_string(),
+            rect:    Rect { x: x.parse().ok()?, y: y.parse().ok()?, w: px(w)?, h: px(h)? },
+            focused: flags.contains('*'),
+        })
+    }).collect()
+}
+
+/// `1234, 567`
+fn parse_pair(s: &str) -> Option<(f32, f32)> {
+    let (x, y) = s.trim().split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// `X=1234` / `Y=567` lines.
+fn parse_xdotool(s: &str) -> Option<(f32, f32)> {
+    let value = |key: &str| s.lines().find_map(|l| l.strip_prefix(key)?.parse::<f32>().ok());
+    Some((value("X=")?, value("Y=")?))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_outputs() {
+        let hypr = parse_hyprland(r#"[{"name":"DP-1","x":0,"y":0,"width":3840,"height":2160,"scale":2.0,"transform":0,"focused":false},
+                                     {"name":"eDP-1","x":1920,"y":0,"width":1920,"height":1080,"scale":1.0,"transform":1,"focused":true}]"#);
+        assert_eq!(hypr[0].rect, Rect { x: 0.0, y: 0.0, w: 1920.0, h: 1080.0 });
+        assert_eq!(hypr[1].rect, Rect { x: 1920.0, y: 0.0, w: 1080.0, h: 1920.0 });
+        assert!(hypr[1].focused);
+
+        let xrandr = parse_xrandr("Monitors: 2\n 0: +*eDP-1 1920/344x1080/194+0+0  eDP-1\n 1: +HDMI-1 2560/597x1440/336+1920+0  HDMI-1\n");
+        assert_eq!(xrandr.len(), 2);
+        assert_eq!(xrandr[1].name, "HDMI-1");
+        assert_eq!(xrandr[1].rect, Rect { x: 1920.0, y: 0.0, w: 2560.0, h: 1440.0 });
+        assert!(xrandr[0].focused && !xrandr[1].focused);
+
+        assert_eq!(parse_pair("1234, 567\n"), Some((1234.0, 567.0)));
+        assert_eq!(parse_xdotool("X=10\nY=20\nSCREEN=0\n"), Some((10.0, 20.0)));
+    }
+
+    #[test]
+    fn test_pick_and_anchor() {
+        let monitors = parse_xrandr("Monitors: 2\n 0: +*A 1000/1x800/1+0+0  A\n 1: +B 2000/1x1000/1+1000+0  B\n");
+        assert_eq!(pick("B", &monitors, None).unwrap().name, "B");
+        assert_eq!(pick("cursor", &monitors, Some((1500.0, 10.0))).unwrap().name, "B");
+        assert_eq!(pick("cursor", &monitors, None).unwrap().name, "A");
+        assert_eq!(pick("missing", &monitors, None).unwrap().name, "A");
+
+        let area = monitors[1].rect;
+        assert_eq!(anchor("center", &area, (400.0, 200.0), None), (1800.0, 400.0));
+        assert_eq!(anchor("top-center", &area, (400.0, 200.0), None), (1800.0, 200.0));
+        // Near the edge the window stays on the output.
+        assert_eq!(anchor("cursor", &area, (400.0, 200.0), Some((2990.0, 5.0))), (2600.0, 0.0));
+    }
+}
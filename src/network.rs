@@ -0,0 +1,500 @@
+//! NetworkManager integration (`org.freedesktop.NetworkManager`).
+//!
+//! Design:
+//! - A background tokio runtime keeps `NetworkState` up to date: refreshed on
+//!   NetworkManager's `StateChanged` signal and on a slow poll (signal strength
+//!   and the visible access-point list change without signals we listen to).
+//! - Known Wi-Fi networks come from saved connection profiles, so connecting
+//!   never needs a secret prompt — we only activate existing profiles.
+//! - VPNs (`enable-vpn`) are the saved `vpn` and `wireguard` profiles, read
+//!   over the same connection and kept current from the active connections'
+//!   own `StateChanged`, which the global one misses. wg-quick configs are
+//!   listed beside them (from `/etc/wireguard` when it's readable, else
+//!   `wg-quick-configs`), up when their interface exists, and toggled with
+//!   `pkexec wg-quick`.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use zbus::Connection;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use crate::gui::Config;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const NM_BUS:      &str = "org.freedesktop.NetworkManager";
+const NM_PATH:     &str = "/org/freedesktop/NetworkManager";
+const NM_SETTINGS: &str = "/org/freedesktop/NetworkManager/Settings";
+const NM_IFACE:    &str = "org.freedesktop.NetworkManager";
+
+const T_CALL:        Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `NM_DEVICE_TYPE_WIFI`
+const DEVICE_TYPE_WIFI: u32 = 2;
+
+const WG_QUICK_DIR: &str = "/etc/wireguard";
+/// wg-quick needs root; polkit asks for the password.
+const WG_QUICK:     &[&str] = &["pkexec", "wg-quick"];
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Connectivity { #[default] Unknown, Disconnected, Connecting, Connected }
+
+#[derive(Clone, Debug)]
+pub struct KnownWifi {
+    /// Saved profile name (`connection.id`).
+    pub name:     String,
+    pub ssid:     String,
+    /// Settings object path, passed back to `connect`.
+    pub path:     String,
+    /// Signal strength in percent when the network is currently in range.
+    pub strength: Option<u8>,
+    pub active:   bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VpnState { #[default] Off, Connecting, On }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VpnBackend { NetworkManager, WgQuick }
+
+#[derive(Clone, Debug)]
+pub struct Vpn {
+    pub name:    String,
+    /// "OpenVPN", "WireGuard", "wg-quick", …
+    pub kind:    String,
+    pub backend: VpnBackend,
+    /// Settings object path for NetworkManager; the config name or path for wg-quick.
+    pub id:      String,
+    pub state:   VpnState,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NetworkState {
+    pub connectivity:    Connectivity,
+    /// `Id` of the primary active connection ("Wired connection 1", an SSID, …).
+    pub connection_name: Option<String>,
+    pub is_wifi:         bool,
+    /// Strength of the access point we're associated with.
+    pub strength:        Option<u8>,
+    pub known_wifi:      Vec<KnownWifi>,
+    /// NetworkManager VPNs by name, then the wg-quick configs.
+    pub vpns:            Vec<Vpn>,
+    /// VPN ids with a connect or disconnect on its way.
+    pub vpn_pending:     Vec<String>,
+}
+
+pub type SharedNetworkState = Arc<Mutex<NetworkState>>;
+
+/// Called from the backend after every refresh, so the GUI can repaint.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+enum NetworkAction {
+    Connect { settings_path: String },
+    Rescan,
+    SetVpn { backend: VpnBackend, id: String, on: bool },
+}
+
+pub struct NetworkHost {
+    pub state: SharedNetworkState,
+    action_tx: tokio::sync::mpsc::UnboundedSender<NetworkAction>,
+}
+
+impl NetworkHost {
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
+        if !config.enable_network && !config.enable_vpn { return None; }
+
+        let state: SharedNetworkState = Arc::new(Mutex::new(NetworkState::default()));
+        let state_bg = Arc::clone(&state);
+        let waker: Waker = Arc::new(on_change);
+        let (action_tx, mut action_rx) = tokio::sync::mpsc::unbounded_channel();
+        let wg_configs = config.enable_vpn.then(|| config.wg_quick_configs.clone());
+
+        thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(async {
+                    if let Err(e) = run_monitor(&state_bg, &mut action_rx, &waker, wg_configs.as_deref()).await {
+                        tracing::warn!(target: "network", "{e}");
+                        // wg-quick doesn't need NetworkManager.
+                        if let Some(configs) = wg_configs {
+                            run_wg_quick_only(&state_bg, &mut action_rx, &waker, &configs).await;
+                        }
+                    }
+                }),
+                Err(e) => tracing::error!(target: "network", "runtime error: {e}"),
+            }
+        });
+
+        Some(NetworkHost { state, action_tx })
+    }
+
+    /// Activate a saved Wi-Fi profile on the Wi-Fi device.
+    pub fn connect(&self, settings_path: &str) {
+        let _ = self.action_tx.send(NetworkAction::Connect { settings_path: settings_path.into() });
+    }
+
+    /// Ask the Wi-Fi device for a fresh scan (called when the picker opens).
+    pub fn rescan(&self) {
+        let _ = self.action_tx.send(NetworkAction::Rescan);
+    }
+
+    /// Bring a VPN up or down.
+    pub fn set_vpn(&self, vpn: &Vpn, on: bool) {
+        if let Ok(mut s) = self.state.lock() {
+            s.vpn_pending.retain(|p| *p != vpn.id);
+            s.vpn_pending.push(vpn.id.clone());
+        }
+        let _ = self.action_tx.send(NetworkAction::SetVpn { backend: vpn.backend, id: vpn.id.clone(), on });
+    }
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+async fn run_monitor(
+    state:      &SharedNetworkState,
+    action_rx:  &mut tokio::sync::mpsc::UnboundedReceiver<NetworkAction>,
+    waker:      &Waker,
+    wg_configs: Option<&[String]>,
+) -> zbus::Result<()> {
+    let conn = Connection::system().await?;
+    refresh(&conn, state, wg_configs).await;
+    waker();
+
+    // NetworkManager emits StateChanged on every connect/disconnect transition.
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(NM_BUS)?
+        .interface(NM_IFACE)?
+        .member("StateChanged")?
+        .build();
+    let mut signals = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+    // A VPN coming up or going down rarely changes the global state.
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(NM_BUS)?
+        .interface("org.freedesktop.NetworkManager.Connection.Active")?
+        .member("StateChanged")?
+        .build();
+    let mut active_signals = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            Some(_) = signals.next()        => { refresh(&conn, state, wg_configs).await; waker(); }
+            Some(_) = active_signals.next() => { refresh(&conn, state, wg_configs).await; waker(); }
+            _ = poll.tick()                 => { refresh(&conn, state, wg_configs).await; waker(); }
+            action = action_rx.recv() => match action {
+                Some(NetworkAction::Connect { settings_path }) => {
+                    if let Err(e) = activate(&conn, &settings_path).await {
+                        tracing::warn!(target: "network", "activation failed: {e}");
+                    }
+                    refresh(&conn, state, wg_configs).await;
+                    waker();
+                }
+                Some(NetworkAction::SetVpn { backend, id, on }) => {
+                    set_vpn(Some(&conn), state, backend, &id, on).await;
+                    refresh(&conn, state, wg_configs).await;
+                    waker();
+                }
+                Some(NetworkAction::Rescan) => {
+                    if let Some(dev) = wifi_device(&conn).await {
+                        let opts: HashMap<String, OwnedValue> = HashMap::new();
+                        let _ = call(&conn, &dev, "org.freedesktop.NetworkManager.Device.Wireless", "RequestScan", &(opts,)).await;
+                    }
+                }
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Without NetworkManager (no system bus, or it isn't running): only the
+/// wg-quick configs, polled.
+async fn run_wg_quick_only(
+    state:     &SharedNetworkState,
+    action_rx: &mut tokio::sync::mpsc::UnboundedReceiver<NetworkAction>,
+    waker:     &Waker,
+    configs:   &[String],
+) {
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {}
+            action = action_rx.recv() => match action {
+                Some(NetworkAction::SetVpn { backend, id, on }) => set_vpn(None, state, backend, &id, on).await,
+                Some(_)                                         => continue,
+                None                                            => return,
+            },
+        }
+        if let Ok(mut s) = state.lock() { s.vpns = wg_quick_vpns(configs); }
+        waker();
+    }
+}
+
+/// Bring a VPN up or down and clear its pending mark.
+async fn set_vpn(conn: Option<&Connection>, state: &SharedNetworkState, backend: VpnBackend, id: &str, on: bool) {
+    let done = match (backend, conn) {
+        (VpnBackend::NetworkManager, Some(conn)) => set_nm_vpn(conn, id, on).await.map_err(|e| e.to_string()),
+        (VpnBackend::NetworkManager, None)       => Err("NetworkManager isn't running".to_string()),
+        // pkexec waits for the password; not on the runtime thread.
+        (VpnBackend::WgQuick, _)                 => {
+            let config = id.to_string();
+            tokio::task::spawn_blocking(move || wg_quick(&config, on)).await
+                .unwrap_or_else(|e| Err(e.to_string()))
+        }
+    };
+    if let Err(e) = done {
+        tracing::warn!(target: "network", "Failed to turn VPN {id} {}: {e}", if on { "on" } else { "off" });
+    }
+    if let Ok(mut s) = state.lock() { s.vpn_pending.retain(|p| p != id); }
+}
+
+async fn refresh(conn: &Connection, state: &SharedNetworkState, wg_configs: Option<&[String]>) {
+    let connectivity = match get_prop(conn, NM_PATH, NM_IFACE, "State").await.and_then(|v| u32::try_from(v).ok()) {
+        // NM_STATE_*: 20 disconnected, 30 disconnecting, 40 connecting, 50–70 connected.
+        Some(s) if s >= 50 => Connectivity::Connected,
+        Some(40)           => Connectivity::Connecting,
+        Some(20 | 30)      => Connectivity::Disconnected,
+        _                  => Connectivity::Unknown,
+    };
+
+    let primary = get_prop(conn, NM_PATH, NM_IFACE, "PrimaryConnection").await
+        .and_then(|v| OwnedObjectPath::try_from(v).ok())
+        .filter(|p| p.as_str() != "/");
+    let active_iface = "org.freedesktop.NetworkManager.Connection.Active";
+    let (connection_name, is_wifi, active_settings) = match &primary {
+        Some(p) => (
+            get_prop(conn, p, active_iface, "Id").await.and_then(|v| String::try_from(v).ok()),
+            get_prop(conn, p, active_iface, "Type").await
+                .and_then(|v| String::try_from(v).ok())
+                .is_some_and(|t| t == "802-11-wireless"),
+            get_prop(conn, p, active_iface, "Connection").await
+                .and_then(|v| OwnedObjectPath::try_from(v).ok())
+                .map(|p| p.to_string()),
+        ),
+        None => (None, false, None),
+    };
+
+    let visible = visible_access_points(conn).await;
+    let (mut known_wifi, mut vpns) = saved_profiles(conn).await;
+    for wifi in &mut known_wifi {
+        wifi.strength = visible.get(&wifi.ssid).copied();
+        wifi.active   = active_settings.as_deref() == Some(wifi.path.as_str());
+    }
+    // In-range networks first (strongest first), then the rest by name.
+    known_wifi.sort_by(|a, b| b.strength.cmp(&a.strength).then_with(|| a.name.cmp(&b.name)));
+    let strength = known_wifi.iter().find(|w| w.active).and_then(|w| w.strength);
+
+    match wg_configs {
+        Some(configs) => {
+            let active = active_connection_states(conn).await;
+            for vpn in &mut vpns {
+                vpn.state = active.iter().find(|(settings, _, _)| *settings == vpn.id).map_or(VpnState::Off, |(_, _, s)| *s);
+            }
+            vpns.sort_by(|a, b| a.name.cmp(&b.name));
+            vpns.extend(wg_quick_vpns(configs));
+        }
+        None => vpns.clear(),
+    }
+
+    let mut current = state.lock().unwrap();
+    let vpn_pending = std::mem::take(&mut current.vpn_pending);
+    *current = NetworkState { connectivity, connection_name, is_wifi, strength, known_wifi, vpns, vpn_pending };
+}
+
+/// SSID → best strength of every access point the Wi-Fi device currently sees.
+async fn visible_access_points(conn: &Connection) -> HashMap<String, u8> {
+    let mut out = HashMap::new();
+    let Some(dev) = wifi_device(conn).await else { return out };
+    let aps = get_prop(conn, &dev, "org.freedesktop.NetworkManager.Device.Wireless", "AccessPoints").await
+        .and_then(|v| Vec::<OwnedObjectPath>::try_from(v).ok())
+        .unwrap_or_default();
+    let ap_iface = "org.freedesktop.NetworkManager.AccessPoint";
+    for ap in aps {
+        let Some(ssid) = get_prop(conn, &ap, ap_iface, "Ssid").await
+            .and_then(|v| Vec::<u8>::try_from(v).ok())
+            .map(|b| String::from_utf8_lossy(&b).into_owned())
+            .filter(|s| !s.is_empty()) else { continue };
+        let strength = get_prop(conn, &ap, ap_iface, "Strength").await
+            .and_then(|v| u8::try_from(v).ok())
+            .unwrap_or(0);
+        let best = out.entry(ssid).or_insert(0);
+        *best = (*best).max(strength);
+    }
+    out
+}
+
+/// Saved Wi-Fi and VPN profiles.
+async fn saved_profiles(conn: &Connection) -> (Vec<KnownWifi>, Vec<Vpn>) {
+    let Some(msg) = call(conn, NM_SETTINGS, "org.freedesktop.NetworkManager.Settings", "ListConnections", &()).await else {
+        return (Vec::new(), Vec::new());
+    };
+    let paths: Vec<OwnedObjectPath> = msg.body().deserialize().unwrap_or_default();
+
+    let (mut wifi, mut vpns) = (Vec::new(), Vec::new());
+    for path in paths {
+        let Some(msg) = call(conn, &path, "org.freedesktop.NetworkManager.Settings.Connection", "GetSettings", &()).await else {
+            continue;
+        };
+        let settings: HashMap<String, HashMap<String, OwnedValue>> = msg.body().deserialize().unwrap_or_default();
+        let Some(connection) = settings.get("connection") else { continue };
+        let ty   = connection.get("type").and_then(|v| <&str>::try_from(v).ok()).unwrap_or_default();
+        let name = connection.get("id").and_then(|v| <&str>::try_from(v).ok()).unwrap_or_default().to_string();
+        match ty {
+            "802-11-wireless" => {
+                let ssid = settings.get("802-11-wireless")
+                    .and_then(|w| w.get("ssid"))
+                    .and_then(|v| v.try_clone().ok())
+                    .and_then(|v| Vec::<u8>::try_from(v).ok())
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .unwrap_or_else(|| name.clone());
+                wifi.push(KnownWifi { name, ssid, path: path.to_string(), strength: None, active: false });
+            }
+            "vpn" | "wireguard" => {
+                // `org.freedesktop.NetworkManager.openvpn` → "openvpn"
+                let service = settings.get("vpn")
+                    .and_then(|v| v.get("service-type"))
+                    .and_then(|v| <&str>::try_from(v).ok())
+                    .map(|s| s.rsplit('.').next().unwrap_or(s).to_string());
+                let kind = match service.as_deref() {
+                    _ if ty == "wireguard"   => "WireGuard".to_string(),
+                    Some("openvpn")          => "OpenVPN".to_string(),
+                    Some(other)              => other.to_string(),
+                    None                     => "VPN".to_string(),
+                };
+                vpns.push(Vpn { name, kind, backend: VpnBackend::NetworkManager, id: path.to_string(), state: VpnState::Off });
+            }
+            _ => {}
+        }
+    }
+    (wifi, vpns)
+}
+
+/// (settings path, active path, state) of every active connection.
+async fn active_connection_states(conn: &Connection) -> Vec<(String, String, VpnState)> {
+    let active = get_prop(conn, NM_PATH, NM_IFACE, "ActiveConnections").await
+        .and_then(|v| Vec::<OwnedObjectPath>::try_from(v).ok())
+        .unwrap_or_default();
+    let iface = "org.freedesktop.NetworkManager.Connection.Active";
+    let mut out = Vec::new();
+    for path in active {
+        let Some(settings) = get_prop(conn, &path, iface, "Connection").await
+            .and_then(|v| OwnedObjectPath::try_from(v).ok()) else { continue };
+        // NM_ACTIVE_CONNECTION_STATE_*: 1 activating, 2 activated, 3 deactivating.
+        let state = match get_prop(conn, &path, iface, "State").await.and_then(|v| u32::try_from(v).ok()) {
+            Some(1) => VpnState::Connecting,
+            Some(2) => VpnState::On,
+            _       => VpnState::Off,
+        };
+        out.push((settings.to_string(), path.to_string(), state));
+    }
+    out
+}
+
+async fn set_nm_vpn(conn: &Connection, settings_path: &str, on: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if on {
+        // "/" for the device lets NetworkManager pick (or create) one.
+        let settings = ObjectPath::try_from(settings_path)?;
+        let any      = ObjectPath::try_from("/")?;
+        conn.call_method(Some(NM_BUS), NM_PATH, Some(NM_IFACE), "ActivateConnection", &(settings, any.clone(), any)).await?;
+    } else {
+        let active = active_connection_states(conn).await;
+        let (_, path, _) = active.iter().find(|(settings, _, _)| settings == settings_path).ok_or("not active")?;
+        let path = ObjectPath::try_from(path.as_str())?;
+        conn.call_method(Some(NM_BUS), NM_PATH, Some(NM_IFACE), "DeactivateConnection", &(path,)).await?;
+    }
+    Ok(())
+}
+
+async fn wifi_device(conn: &Connection) -> Option<String> {
+    let msg = call(conn, NM_PATH, NM_IFACE, "GetDevices", &()).await?;
+    let devices: Vec<OwnedObjectPath> = msg.body().deserialize().ok()?;
+    for dev in devices {
+        let kind = get_prop(conn, &dev, "org.freedesktop.NetworkManager.Device", "DeviceType").await
+            .and_then(|v| u32::try_from(v).ok());
+        if kind == Some(DEVICE_TYPE_WIFI) { return Some(dev.to_string()); }
+    }
+    None
+}
+
+async fn activate(conn: &Connection, settings_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let device   = wifi_device(conn).await.ok_or("no Wi-Fi device")?;
+    let settings = ObjectPath::try_from(settings_path)?;
+    let device   = ObjectPath::try_from(device.as_str())?;
+    let specific = ObjectPath::try_from("/")?;
+    conn.call_method(Some(NM_BUS), NM_PATH, Some(NM_IFACE), "ActivateConnection", &(settings, device, specific)).await?;
+    Ok(())
+}
+
+// ============================================================================
+// wg-quick
+// ============================================================================
+
+/// The configs in `/etc/wireguard` when it can be read, else `configured`.
+fn wg_quick_vpns(configured: &[String]) -> Vec<Vpn> {
+    let mut names: Vec<String> = std::fs::read_dir(WG_QUICK_DIR).map(|entries| {
+        entries.flatten()
+            .filter_map(|e| e.file_name().to_str()?.strip_suffix(".conf").map(str::to_string))
+            .collect()
+    }).unwrap_or_default();
+    names.sort();
+    if names.is_empty() { names = configured.to_vec(); }
+    names.into_iter().map(|id| {
+        let name = wg_interface(&id).to_string();
+        let up   = Path::new("/sys/class/net").join(&name).exists();
+        Vpn { name, kind: "wg-quick".into(), backend: VpnBackend::WgQuick, id, state: if up { VpnState::On } else { VpnState::Off } }
+    }).collect()
+}
+
+/// The interface wg-quick makes for `config`, a name (`wg0`) or a path to a `.conf`.
+fn wg_interface(config: &str) -> &str {
+    let file = config.rsplit('/').next().unwrap_or(config);
+    file.strip_suffix(".conf").unwrap_or(file)
+}
+
+/// `wg-quick up|down config`; the last line of its output on failure.
+fn wg_quick(config: &str, on: bool) -> Result<(), String> {
+    let out = std::process::Command::new(WG_QUICK[0]).args(&WG_QUICK[1..])
+        .arg(if on { "up" } else { "down" }).arg(config)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr);
+        return Err(err.lines().last().unwrap_or("wg-quick failed").trim().to_string());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// D-Bus helpers
+// ============================================================================
+
+async fn call<B>(conn: &Connection, path: &str, iface: &str, method: &str, body: &B) -> Option<zbus::Message>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    tokio::time::timeout(T_CALL, conn.call_method(Some(NM_BUS), path, Some(iface), method, body))
+        .await.ok()?.ok()
+}
+
+/// `Properties.Get`, with the variant wrapper peeled off.
+async fn get_prop(conn: &Connection, path: &str, iface: &str, prop: &str) -> Option<OwnedValue> {
+    let msg = call(conn, path, "org.freedesktop.DBus.Properties", "Get", &(iface, prop)).await?;
+    let v: OwnedValue = msg.body().deserialize().ok()?;
+    match &*v {
+        Value::Value(inner) => OwnedValue::try_from(inner.as_ref()).ok(),
+        _                   => Some(v),
+    }
+}
@@ -0,0 +1,327 @@
+//! Notification daemon (`org.freedesktop.Notifications`).
+//!
+//! Design:
+//! - Claims `org.freedesktop.Notifications` on its own session connection; if
+//!   another daemon (mako, dunst, …) already owns the name we stay out of the way.
+//! - Incoming notifications are stored newest-first in `Notifications`; the GUI
+//!   renders them in the `notifications` theme section.
+//! - Expiry runs on the backend. Dismiss / action clicks come back over a channel
+//!   and are answered with `NotificationClosed` / `ActionInvoked` signals.
+//! - Do not disturb holds notifications instead of showing them as they come:
+//!   they don't expire and the GUI folds them away until looked at.
+//!   Critical ones still come through. The flag lives here, for the GUI
+//!   toggle and the `DoNotDisturb` property on `org.tusk.Launcher`; the GUI
+//!   saves it in the app cache so it survives a restart.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use zbus::{interface, Connection};
+use zbus::connection::Builder as ConnectionBuilder;
+use zbus::fdo::{RequestNameFlags, RequestNameReply};
+use zbus::object_server::SignalEmitter;
+use crate::gui::Config;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+const OBJ_PATH: &str = "/org/freedesktop/Notifications";
+const EXPIRY_TICK: Duration = Duration::from_millis(250);
+
+/// Do-not-disturb state; a watch so D-Bus can announce changes.
+static DO_NOT_DISTURB: LazyLock<tokio::sync::watch::Sender<bool>> = LazyLock::new(|| tokio::sync::watch::Sender::new(false));
+
+/// `NotificationClosed` reason codes from the spec.
+const CLOSED_EXPIRED:   u32 = 1;
+const CLOSED_DISMISSED: u32 = 2;
+const CLOSED_BY_CALL:   u32 = 3;
+
+// ============================================================================
+// Public types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Urgency { Low, #[default] Normal, Critical }
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub id:       u32,
+    pub app_name: String,
+    pub summary:  String,
+    pub body:     String,
+    /// (action key, label) pairs in the order the sender listed them. The
+    /// `"default"` key is invoked by clicking the notification body.
+    pub actions:  Vec<(String, String)>,
+    pub urgency:  Urgency,
+    /// Arrived during do not disturb; kept, but not shown as it came.
+    pub held:     bool,
+    /// `resident` hint: stays after an action is invoked.
+    resident:     bool,
+    expires_at:   Option<Instant>,
+}
+
+pub type Notifications = Arc<Mutex<Vec<Notification>>>;
+
+enum NotifyAction {
+    Dismiss { id: u32 },
+    Invoke  { id: u32, key: String },
+}
+
+/// Called from the backend whenever the list changes, so the GUI can repaint.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+pub struct NotificationHost {
+    pub items: Notifications,
+    action_tx: tokio::sync::mpsc::UnboundedSender<NotifyAction>,
+}
+
+impl NotificationHost {
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
+        if !config.enable_notifications { return None; }
+
+        let items: Notifications = Arc::new(Mutex::new(Vec::new()));
+        let items_bg = Arc::clone(&items);
+        let default_timeout = Duration::from_millis(config.notification_timeout_ms);
+        let waker: Waker = Arc::new(on_change);
+        let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(async {
+                    if let Err(e) = run_server(items_bg, action_rx, default_timeout, waker).await {
+                        tracing::warn!(target: "notifications", "{e}");
+                    }
+                }),
+                Err(e) => tracing::error!(target: "notifications", "runtime error: {e}"),
+            }
+        });
+
+        Some(NotificationHost { items, action_tx })
+    }
+
+    pub fn dismiss(&self, id: u32) {
+        let _ = self.action_tx.send(NotifyAction::Dismiss { id });
+    }
+
+    pub fn invoke_action(&self, id: u32, key: &str) {
+        let _ = self.action_tx.send(NotifyAction::Invoke { id, key: key.into() });
+    }
+}
+
+// ============================================================================
+// D-Bus Notifications interface
+// ============================================================================
+
+struct Server {
+    items:           Notifications,
+    next_id:         AtomicU32,
+    default_timeout: Duration,
+    waker:           Waker,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl Server {
+    #[allow(clippy::too_many_arguments)]
+    async fn notify(
+        &self,
+        app_name:       String,
+        replaces_id:    u32,
+        _app_icon:      String,
+        summary:        String,
+        body:           String,
+        actions:        Vec<String>,
+        hints:          HashMap<String, zbus::zvariant::OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        use zbus::zvariant::Value;
+        let urgency = match hints.get("urgency").map(|v| &**v) {
+            Some(Value::U8(0)) => Urgency::Low,
+            Some(Value::U8(2)) => Urgency::Critical,
+            _                  => Urgency::Normal,
+        };
+        let resident = matches!(hints.get("resident").map(|v| &**v), Some(Value::Bool(true)));
+        let held = do_not_disturb() && urgency != Urgency::Critical;
+        // Critical notifications never time out on their own (spec §Urgency).
+        let expires_at = match expire_timeout {
+            _ if urgency == Urgency::Critical || held => None,
+            0          => None,
+            t if t > 0 => Some(Instant::now() + Duration::from_millis(t as u64)),
+            _          => Some(Instant::now() + self.default_timeout),
+        };
+
+        let mut locked = self.items.lock().unwrap();
+        let id = if replaces_id != 0 && locked.iter().any(|n| n.id == replaces_id) {
+            replaces_id
+        } else {
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        };
+        let notification = Notification {
+            id,
+            app_name,
+            summary,
+            body,
+            actions: actions.chunks_exact(2).map(|c| (c[0].clone(), c[1].clone())).collect(),
+            urgency,
+            held,
+            resident,
+            expires_at,
+        };
+        match locked.iter().position(|n| n.id == id) {
+            Some(pos) => locked[pos] = notification,
+            None      => locked.insert(0, notification),
+        }
+        drop(locked);
+        (self.waker)();
+        id
+    }
+
+    async fn close_notification(&self, id: u32, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+        if remove(&self.items, id) {
+            let _ = Self::notification_closed(&emitter, id, CLOSED_BY_CALL).await;
+            (self.waker)();
+        }
+    }
+
+    fn get_capabilities(&self) -> Vec<String> {
+        vec!["actions".into(), "body".into()]
+    }
+
+    #[zbus(out_args("name", "vendor", "version", "spec_version"))]
+    fn get_server_information(&self) -> (String, String, String, String) {
+        ("Tusk-Launcher".into(), "Tusk-Launcher".into(), env!("CARGO_PKG_VERSION").into(), "1.2".into())
+    }
+
+    #[zbus(signal)]
+    async fn notification_closed(emitter: &SignalEmitter<'_>, id: u32, reason: u32) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn action_invoked(emitter: &SignalEmitter<'_>, id: u32, action_key: &str) -> zbus::Result<()>;
+}
+
+// ============================================================================
+// Server startup
+// ============================================================================
+
+async fn run_server(
+    items:           Notifications,
+    mut action_rx:   tokio::sync::mpsc::UnboundedReceiver<NotifyAction>,
+    default_timeout: Duration,
+    waker:           Waker,
+) -> zbus::Result<()> {
+    let server = Server {
+        items:   Arc::clone(&items),
+        next_id: AtomicU32::new(1),
+        default_timeout,
+        waker:   Arc::clone(&waker),
+    };
+    let conn: Connection = ConnectionBuilder::session()?
+        .serve_at(OBJ_PATH, server)?
+        .build().await?;
+    // Without ReplaceExisting: a running daemon that allows replacement
+    // would otherwise hand the name over.
+    match conn.request_name_with_flags(BUS_NAME, RequestNameFlags::DoNotQueue.into()).await? {
+        RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {}
+        _ => return Err(zbus::Error::NameTaken),
+    }
+    tracing::info!(target: "notifications", "claimed {BUS_NAME}");
+    let emitter = SignalEmitter::new(&conn, OBJ_PATH)?.into_owned();
+
+    // Expiry ticker.
+    {
+        let items   = Arc::clone(&items);
+        let waker   = Arc::clone(&waker);
+        let emitter = emitter.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(EXPIRY_TICK).await;
+                let now = Instant::now();
+                let expired: Vec<u32> = {
+                    let mut locked = items.lock().unwrap();
+                    let ids = locked.iter()
+                        .filter(|n| n.expires_at.is_some_and(|t| t <= now))
+                        .map(|n| n.id)
+                        .collect();
+                    locked.retain(|n| n.expires_at.is_none_or(|t| t > now));
+                    ids
+                };
+                if expired.is_empty() { continue; }
+                for id in expired {
+                    let _ = Server::notification_closed(&emitter, id, CLOSED_EXPIRED).await;
+                }
+                waker();
+            }
+        });
+    }
+
+    // Action handler.
+    while let Some(action) = action_rx.recv().await {
+        match action {
+            NotifyAction::Dismiss { id } => {
+                if remove(&items, id) {
+                    let _ = Server::notification_closed(&emitter, id, CLOSED_DISMISSED).await;
+                }
+            }
+            NotifyAction::Invoke { id, key } => {
+                let _ = Server::action_invoked(&emitter, id, &key).await;
+                let resident = items.lock().unwrap().iter().any(|n| n.id == id && n.resident);
+                if !resident && remove(&items, id) {
+                    let _ = Server::notification_closed(&emitter, id, CLOSED_DISMISSED).await;
+                }
+            }
+        }
+        waker();
+    }
+    Ok(())
+}
+
+pub fn do_not_disturb() -> bool {
+    *DO_NOT_DISTURB.borrow()
+}
+
+pub fn set_do_not_disturb(on: bool) {
+    DO_NOT_DISTURB.send_if_modified(|dnd| std::mem::replace(dnd, on) != on);
+}
+
+/// Changes to do not disturb, as they happen.
+pub fn watch_do_not_disturb() -> tokio::sync::watch::Receiver<bool> {
+    DO_NOT_DISTURB.subscribe()
+}
+
+/// Remove notification `id`; returns whether it was present.
+fn remove(items: &Notifications, id: u32) -> bool {
+    let mut locked = items.lock().unwrap();
+    let before = locked.len();
+    locked.retain(|n| n.id != id);
+    locked.len() != before
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+/// Post a notification through whichever daemon owns the name — ours when
+/// `enable-notifications` is on. Blocks on D-Bus, so call it off the GUI thread.
+pub fn send(icon: &str, summary: &str, body: &str) {
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            tracing::error!(target: "notifications", "runtime error: {e}");
+            return;
+        }
+    };
+    let sent = rt.block_on(async {
+        let conn = Connection::session().await?;
+        conn.call_method(
+            Some(BUS_NAME), OBJ_PATH, Some(BUS_NAME), "Notify",
+            &("Tusk Launcher", 0u32, icon, summary, body,
+              Vec::<&str>::new(), HashMap::<&str, zbus::zvariant::Value>::new(), -1i32),
+        ).await?;
+        Ok::<_, zbus::Error>(())
+    });
+    if let Err(e) = sent {
+        tracing::warn!(target: "notifications", "couldn't send {summary:?}: {e}");
+    }
+}
@@ -0,0 +1,136 @@
+//! First start: a short wizard for the choices most people make right away.
+//!
+//! Design:
+//! - Shown once, when `config.toml` didn't exist yet and there was no
+//!   `.config` block to migrate. By the time it opens the defaults are
+//!   already written, so closing it early leaves a working setup.
+//! - Every answer is an ordinary setting, written with `config::set_values`
+//!   on Finish: the profile as `theme`, the size as `ui-scale` (themes place
+//!   sections in points, so scaling keeps their layout intact), and the
+//!   `enable-*` switches of the modules.
+//! - The launcher can't bind its own hotkey; the last step shows the line
+//!   for the running compositor's config, to copy.
+
+use crate::config::{Value, Values};
+use crate::windows::{backend, Backend};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Theme,
+    Size,
+    Modules,
+    Keybinding,
+}
+
+impl Step {
+    pub const ALL: [Step; 4] = [Step::Theme, Step::Size, Step::Modules, Step::Keybinding];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Step::Theme      => "Pick a theme",
+            Step::Size       => "How big should it be?",
+            Step::Modules    => "What else should it show?",
+            Step::Keybinding => "Open it with a key",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Step::ALL.iter().position(|s| *s == self).unwrap_or(0)
+    }
+
+    /// The step after this one; `None` after the last.
+    pub fn next(self) -> Option<Step> {
+        Step::ALL.get(self.index() + 1).copied()
+    }
+
+    pub fn previous(self) -> Option<Step> {
+        self.index().checked_sub(1).map(|i| Step::ALL[i])
+    }
+}
+
+/// Size choices and the `ui-scale` each one writes.
+pub const SIZES: &[(&str, f32)] = &[("Small", 0.85), ("Medium", 1.0), ("Large", 1.25), ("Extra large", 1.5)];
+
+/// Optional sections offered, by config key.
+pub const MODULES: &[(&str, &str)] = &[
+    ("enable-system-tray",   "System tray icons"),
+    ("enable-audio-control", "Volume slider"),
+    ("enable-power-options", "Power buttons (shut down, reboot, log out)"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Choices {
+    /// Profile name; empty is theme.css alone.
+    pub theme:   String,
+    pub scale:   f32,
+    /// On/off per `MODULES` entry.
+    pub modules: Vec<bool>,
+}
+
+impl Choices {
+    /// What `values` (the config just written) already says.
+    pub fn from_values(values: &Values) -> Self {
+        let scalar = |key: &str| values.get(key).and_then(Value::scalar).map(str::to_string);
+        Choices {
+            theme:   scalar("theme").unwrap_or_default(),
+            scale:   scalar("ui-scale").and_then(|s| s.parse().ok()).unwrap_or(1.0),
+            modules: MODULES.iter().map(|(key, _)| scalar(key).as_deref() == Some("true")).collect(),
+        }
+    }
+
+    /// The settings to write.
+    pub fn values(&self) -> Vec<(String, Value)> {
+        let mut out = vec![
+            ("theme".to_string(),    Value::Scalar(self.theme.clone())),
+            ("ui-scale".to_string(), Value::Scalar(format!("{:.2}", self.scale))),
+        ];
+        out.extend(MODULES.iter().zip(&self.modules).map(|((key, _), on)| (key.to_string(), Value::Scalar(on.to_string()))));
+        out
+    }
+}
+
+/// Where to put the hotkey and the line to add there, for the running
+/// compositor; `command` starts the launcher (running it again toggles it).
+pub fn keybinding(command: &str) -> (&'static str, String) {
+    let i3 = std::env::var_os("I3SOCK").is_some();
+    keybinding_for(backend(), i3, command)
+}
+
+fn keybinding_for(backend: Option<Backend>, i3: bool, command: &str) -> (&'static str, String) {
+    match backend {
+        Some(Backend::Hyprland) => ("~/.config/hypr/hyprland.conf", format!("bind = SUPER, SPACE, exec, {command}")),
+        Some(Backend::Sway)     => ("~/.config/sway/config", format!("bindsym $mod+space exec {command}")),
+        Some(Backend::Wmctrl) if i3 => ("~/.config/i3/config", format!("bindsym $mod+space exec --no-startup-id {command}")),
+        _ => ("your desktop's keyboard settings, as a custom shortcut running", command.to_string()),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choices_and_keybinding() {
+        let values: Values = [
+            ("theme", "nord"), ("ui-scale", "1.0"), ("enable-system-tray", "true"), ("enable-audio-control", "false"),
+        ].into_iter().map(|(k, v)| (k.to_string(), Value::Scalar(v.to_string()))).collect();
+        let mut choices = Choices::from_values(&values);
+        assert_eq!(choices.modules, [true, false, false]);
+        choices.scale = 1.25;
+        let written = choices.values();
+        assert_eq!(written[1], ("ui-scale".to_string(), Value::Scalar("1.25".into())));
+        assert_eq!(written.len(), 2 + MODULES.len());
+
+        assert_eq!(Step::Theme.next(), Some(Step::Size));
+        assert_eq!(Step::Keybinding.next(), None);
+        assert_eq!(Step::Theme.previous(), None);
+
+        assert_eq!(keybinding_for(Some(Backend::Sway), false, "tusk-launcher").1, "bindsym $mod+space exec tusk-launcher");
+        assert!(keybinding_for(Some(Backend::Wmctrl), true, "tl").1.contains("--no-startup-id"));
+        assert_eq!(keybinding_for(None, false, "tl").1, "tl");
+    }
+}
@@ -1,4 +1,4 @@
-use std::{env, fs, path::PathBuf};
+use std::{env, ffi::OsString, fs, os::unix::ffi::OsStringExt, path::PathBuf};
 
 /// Returns `$XDG_CONFIG_HOME` if set and absolute, otherwise `$HOME/.config`.
 pub fn config_home() -> PathBuf {
@@ -18,6 +18,24 @@ pub fn data_home() -> PathBuf {
         .unwrap_or_else(|| home().join(".local/share"))
 }
 
+/// Returns `$XDG_CACHE_HOME` if set and absolute, otherwise `$HOME/.cache`.
+pub fn cache_home() -> PathBuf {
+    env::var("XDG_CACHE_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home().join(".cache"))
+}
+
+/// Returns `$XDG_STATE_HOME` if set and absolute, otherwise `$HOME/.local/state`.
+pub fn state_home() -> PathBuf {
+    env::var("XDG_STATE_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home().join(".local/state"))
+}
+
 /// Returns the colon-separated `$XDG_DATA_DIRS` list, falling back to
 /// `/usr/local/share:/usr/share`. Empty components are skipped.
 pub fn data_dirs() -> Vec<PathBuf> {
@@ -29,6 +47,27 @@ pub fn data_dirs() -> Vec<PathBuf> {
         .collect()
 }
 
+/// Returns the colon-separated `$XDG_CONFIG_DIRS` list, falling back to
+/// `/etc/xdg`. Empty components are skipped.
+pub fn config_dirs() -> Vec<PathBuf> {
+    env::var("XDG_CONFIG_DIRS")
+        .unwrap_or_else(|_| "/etc/xdg".into())
+        .split(':')
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Returns `$XDG_RUNTIME_DIR` if set and absolute. There is deliberately no
+/// fallback here — callers that need a private directory without it must
+/// create and verify one themselves.
+pub fn runtime_dir() -> Option<PathBuf> {
+    env::var("XDG_RUNTIME_DIR")
+        .ok()
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
 /// Resolves `relative` under `config_home()`, creates all parent directories,
 /// and returns the full path.
 pub fn place_config_file(relative: &str) -> std::io::Result<PathBuf> {
@@ -39,6 +78,23 @@ pub fn place_config_file(relative: &str) -> std::io::Result<PathBuf> {
     Ok(path)
 }
 
-fn home() -> PathBuf {
+/// `file:///home/me/a%20b.pdf` → `/home/me/a b.pdf`; `None` for other schemes.
+pub fn from_file_uri(uri: &str) -> Option<PathBuf> {
+    let rest  = uri.strip_prefix("file://")?;
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| rest.get(i + 1..i + 3)).flatten().and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => { out.push(b); i += 3; }
+            None    => { out.push(bytes[i]); i += 1; }
+        }
+    }
+    Some(PathBuf::from(OsString::from_vec(out)))
+}
+
+/// `$HOME`, or the working directory without it.
+pub fn home() -> PathBuf {
     PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".into()))
 }
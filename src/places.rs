@@ -0,0 +1,133 @@
+//! Places: home, the XDG user directories and GTK bookmarks, as a file
+//! manager's sidebar lists them.
+//!
+//! Design:
+//! - User directories come from `user-dirs.dirs`
+//!   (`XDG_DOWNLOAD_DIR="$HOME/Downloads"`); one set to `$HOME` is turned
+//!   off, per `xdg-user-dirs`, and left out.
+//! - Bookmarks are `gtk-3.0/bookmarks`, one `uri [label]` per line, which
+//!   Nautilus, Thunar, Nemo and GTK's file chooser share. Local ones that
+//!   no longer exist are skipped; remote ones (`sftp://`, `smb://`) are kept
+//!   and left to the file manager.
+//! - Both files are read on each query; they're a few lines long.
+//! - Mounted drives come from `drives.rs` and are added by the provider.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const USER_DIRS_FILE: &str = "user-dirs.dirs";
+const BOOKMARKS_FILE: &str = "gtk-3.0/bookmarks";
+
+/// `user-dirs.dirs` keys in sidebar order, with their icons.
+const USER_DIRS: &[(&str, &str)] = &[
+    ("DESKTOP",     "user-desktop"),
+    ("DOCUMENTS",   "folder-documents"),
+    ("DOWNLOAD",    "folder-download"),
+    ("MUSIC",       "folder-music"),
+    ("PICTURES",    "folder-pictures"),
+    ("VIDEOS",      "folder-videos"),
+    ("PUBLICSHARE", "folder-publicshare"),
+    ("TEMPLATES",   "folder-templates"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Place {
+    pub name:   String,
+    /// A path, or a URI for remote bookmarks; handed to `xdg-open`.
+    pub target: String,
+    pub icon:   String,
+}
+
+/// Home, the user directories that exist, then the bookmarks.
+pub fn load() -> Vec<Place> {
+    let home   = crate::paths::home();
+    let config = crate::paths::config_home();
+    let read   = |file: &str| fs::read_to_string(config.join(file)).unwrap_or_default();
+
+    let mut places = vec![Place { name: "Home".into(), target: home.display().to_string(), icon: "user-home".into() }];
+    places.extend(user_dirs(&read(USER_DIRS_FILE), &home).into_iter().filter(|p| Path::new(&p.target).is_dir()));
+    places.extend(bookmarks(&read(BOOKMARKS_FILE)).into_iter()
+        .filter(|p| !p.target.starts_with('/') || Path::new(&p.target).exists()));
+    places
+}
+
+/// An XDG user directory by its key (`"PICTURES"`, `"VIDEOS"`, …), when
+/// `user-dirs.dirs` sets one.
+pub fn user_dir(key: &str) -> Option<PathBuf> {
+    let src = fs::read_to_string(crate::paths::config_home().join(USER_DIRS_FILE)).ok()?;
+    dir_entries(&src, &crate::paths::home()).into_iter().find(|(k, _)| *k == key).map(|(_, path)| path)
+}
+
+fn user_dirs(src: &str, home: &Path) -> Vec<Place> {
+    let dirs = dir_entries(src, home);
+    USER_DIRS.iter()
+        .filter_map(|(key, icon)| {
+            let (_, path) = dirs.iter().find(|(k, _)| k == key)?;
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some(Place { name, target: path.display().to_string(), icon: icon.to_string() })
+        })
+        .collect()
+}
+
+/// `XDG_<KEY>_DIR` lines as (key, path), without the ones turned off.
+fn dir_entries<'a>(src: &'a str, home: &Path) -> Vec<(&'a str, PathBuf)> {
+    src.lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once('=')?;
+            let key   = key.strip_prefix("XDG_")?.strip_suffix("_DIR")?;
+            let value = value.trim().trim_matches('"');
+            let path  = match value.strip_prefix("$HOME") {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None       => PathBuf::from(value),
+            };
+            Some((key, path))
+        })
+        .filter(|(_, path)| path.is_absolute() && path != home)
+        .collect()
+}
+
+fn bookmarks(src: &str) -> Vec<Place> {
+    src.lines()
+        .filter_map(|line| {
+            let (uri, label) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            if uri.is_empty() { return None; }
+            let (target, icon) = match crate::paths::from_file_uri(uri) {
+                Some(path) => (path.display().to_string(), "folder"),
+                None       => (uri.to_string(), "folder-remote"),
+            };
+            let name = match label.trim() {
+                ""    => Path::new(target.trim_end_matches('/')).file_name()
+                    .map_or_else(|| target.clone(), |n| n.to_string_lossy().into_owned()),
+                label => label.to_string(),
+            };
+            Some(Place { name, target, icon: icon.into() })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_places() {
+        let home = Path::new("/home/me");
+        let dirs = user_dirs(
+            "# written by xdg-user-dirs-update\nXDG_DESKTOP_DIR=\"$HOME\"\nXDG_DOWNLOAD_DIR=\"$HOME/Downloads\"\nXDG_MUSIC_DIR=\"/data/Musik\"\n",
+            home,
+        );
+        assert_eq!(dirs, [
+            Place { name: "Downloads".into(), target: "/home/me/Downloads".into(), icon: "folder-download".into() },
+            Place { name: "Musik".into(), target: "/data/Musik".into(), icon: "folder-music".into() },
+        ]);
+
+        let marks = bookmarks("file:///home/me/My%20Projects\nfile:///srv/www Web root\nsftp://host/home/me\n\n");
+        let names: Vec<(&str, &str)> = marks.iter().map(|p| (p.name.as_str(), p.target.as_str())).collect();
+        assert_eq!(names, [("My Projects", "/home/me/My Projects"), ("Web root", "/srv/www"), ("me", "sftp://host/home/me")]);
+        assert_eq!(marks[2].icon, "folder-remote");
+    }
+}
@@ -0,0 +1,59 @@
+//! Fan-out for the startup scans: `.desktop` parsing, Steam manifests and
+//! icon theme listings are many small, independent file reads.
+//!
+//! Design:
+//! - `map` splits the work into one contiguous chunk per core on scoped
+//!   threads and joins them in order, so results line up with the input —
+//!   desktop-file precedence depends on it.
+//! - No pool is kept between calls; each scan runs once per launcher start
+//!   (or rescan), so spawning a handful of threads costs less than the reads.
+//! - Small inputs stay on the calling thread.
+use std::thread;
+
+/// Below this many items a thread costs more than it saves.
+const MIN_PER_THREAD: usize = 8;
+/// Scans are I/O bound; more threads than this just contend on the disk.
+const MAX_THREADS: usize = 8;
+
+/// `items.into_iter().map(f).collect()`, spread over the available cores.
+pub fn map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+    let cores   = thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_THREADS);
+    let threads = cores.min(items.len() / MIN_PER_THREAD).max(1);
+    if threads == 1 { return items.into_iter().map(f).collect(); }
+
+    let per_chunk = items.len().div_ceil(threads);
+    let mut chunks: Vec<Vec<T>> = Vec::with_capacity(threads);
+    let mut items = items.into_iter();
+    loop {
+        let chunk: Vec<T> = items.by_ref().take(per_chunk).collect();
+        if chunk.is_empty() { break; }
+        chunks.push(chunk);
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks.into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_order() {
+        let items: Vec<u32> = (0..1000).collect();
+        assert_eq!(map(items.clone(), |n| n * 2), items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(map(vec![3], |n: u32| n + 1), [4]);
+        assert!(map(Vec::<u32>::new(), |n| n).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Startup timings: `--profile-startup` and the benches in `benches/startup.rs`.
+//!
+//! Design:
+//! - `Startup` runs the hot spots of the startup path one at a time, on the
+//!   calling thread, so the profile mode and the benches time the same code:
+//!   the desktop scan parses every `.desktop` file afresh (a first start;
+//!   later ones read the index), the cache load reads the app cache from its
+//!   store, icon resolution looks up each scanned app's icon, and the theme
+//!   parse reads theme.css and the configured profile.
+//! - `--profile-startup` times those once, then starts the launcher as usual
+//!   but without the control socket, so it runs beside a daemon. The first
+//!   frame counts from there, with the disk cache already warm; once it's
+//!   built the timings go to stdout and the launcher quits.
+//! - The benches repeat each phase; after the first round icon lookups are
+//!   answered from the theme listings kept in memory, as on every open but
+//!   the first.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::gui::Config;
+
+/// When the normal startup began; set only in the profile mode.
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+pub struct Startup {
+    config: Config,
+    /// Name and icon of each app the last scan found.
+    apps:   Vec<(String, String)>,
+}
+
+impl Startup {
+    /// Reads config.toml, which every phase after it depends on.
+    pub fn load() -> Self {
+        let config = crate::config::load(&crate::gui::load_theme(""));
+        Startup { config, apps: Vec::new() }
+    }
+
+    /// Parse every `.desktop` file; the number of menu entries.
+    pub fn desktop_scan(&mut self) -> usize {
+        self.apps = crate::app_launcher::scan_desktop_files();
+        self.apps.len()
+    }
+
+    /// Read the app cache; the number of apps in it.
+    pub fn cache_load(&self) -> usize {
+        crate::store::STORE.load().map_or(0, |cache| cache.apps.len())
+    }
+
+    /// Look up the icon of each app from the last scan; how many were found.
+    pub fn icon_resolution(&self) -> usize {
+        self.apps.iter()
+            .filter(|(name, icon)| crate::app_launcher::resolve_icon_path(name, icon, &self.config).is_some())
+            .count()
+    }
+
+    /// Read and parse theme.css with the configured profile; the number of
+    /// selectors.
+    pub fn theme_parse(&self) -> usize {
+        crate::gui::Theme::load(&self.config.theme).selectors()
+    }
+}
+
+/// `--profile-startup`: time each phase once and print it, then mark the
+/// start of the normal startup for `first_frame`.
+pub fn run_phases() {
+    let mut startup = Startup::load();
+    let (scan,  entries) = timed(|| startup.desktop_scan());
+    let (cache, cached)  = timed(|| startup.cache_load());
+    let (icons, found)   = timed(|| startup.icon_resolution());
+    let (theme, rules)   = timed(|| startup.theme_parse());
+    println!("{}", line("desktop scan", scan, &format!("{entries} entries")));
+    println!("{}", line("cache load", cache, &format!("{cached} apps")));
+    println!("{}", line("icon resolution", icons, &format!("{found} of {entries} found")));
+    println!("{}", line("theme parse", theme, &format!("{rules} selectors")));
+    let _ = STARTED.set(Instant::now());
+}
+
+/// Called after each frame is built: in the profile mode, prints the first
+/// one's time and says to quit.
+pub fn first_frame() -> bool {
+    static DONE: OnceLock<()> = OnceLock::new();
+    let Some(started) = STARTED.get() else { return false };
+    if DONE.set(()).is_err() { return false; }
+    println!("{}", line("first frame", started.elapsed(), "theme, config, providers and window"));
+    true
+}
+
+fn timed(phase: impl FnOnce() -> usize) -> (Duration, usize) {
+    let at = Instant::now();
+    let n  = phase();
+    (at.elapsed(), n)
+}
+
+fn line(phase: &str, took: Duration, detail: &str) -> String {
+    format!("{phase:<16} {:>8.1} ms  ({detail})", took.as_secs_f64() * 1000.0)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        assert_eq!(line("cache load", Duration::from_micros(1340), "12 apps"), "cache load            1.3 ms  (12 apps)");
+        assert!(!first_frame());
+    }
+}
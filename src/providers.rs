@@ -0,0 +1,870 @@
+//! Search providers: every source of result rows (desktop apps, Steam, `$PATH`,
+//! calculator, …) implements `SearchProvider` and is registered in the order
+//! given by the `search-providers` config key.
+//!
+//! `AppLauncher` only fans the query out to the registered providers, merges
+//! their rows and routes activation back to the provider that produced a row.
+//! Providers that need `app_launcher` internals (the app providers and run
+//! mode) live there; the self-contained ones live here.
+//!
+//! Queries are answered on `AppLauncher`'s search thread, so providers must
+//! be `Send`; activation and `poll` still happen on the UI thread.
+//!
+//! The GUI never sees a `SearchResult`: each row is turned into a
+//! `ResultRow` (custom name applied, icon resolved, `ResultKind` from the
+//! provider) on the search thread, so drawing needs no lookups by title.
+//! Its action menu (`RowAction`) is filled in there too, from what the
+//! result carries (`actions`, `command`, `file`) and what the provider can
+//! do (`terminal`).
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::gui::Config;
+
+// ============================================================================
+// Provider interface
+// ============================================================================
+
+/// One result row.
+#[derive(Clone, Default)]
+pub struct SearchResult {
+    /// Text shown in the row; also how the GUI refers to the row.
+    pub title:       String,
+    /// Icon name or path; falls back to the provider's `icon()`.
+    pub icon:        Option<String>,
+    /// Hover text.
+    pub description: Option<String>,
+    /// Secondary actions offered in the row's right-click menu.
+    pub actions:     Vec<String>,
+    /// Provider-private payload (exec line, value to copy, …).
+    pub data:        String,
+    /// Position in the recents list (0 = most recent). Used to order the
+    /// empty-query view across providers.
+    pub recent_rank: Option<usize>,
+    /// Cover art path for the games grid.
+    pub image:       Option<String>,
+    /// Command line the row runs, offered by "Copy Command".
+    pub command:     Option<String>,
+    /// The `.desktop` file the row was read from.
+    pub file:        Option<String>,
+}
+
+/// What a row stands for; decides which right-click entries make sense.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResultKind {
+    App,
+    Game,
+    Command,
+    /// A command line typed in run mode.
+    Shell,
+    Window,
+    Clipboard,
+    Calculation,
+    Web,
+    Snippet,
+    Document,
+    Place,
+    Wallpaper,
+}
+
+impl ResultKind {
+    /// What activating a row of this kind does, as a menu label.
+    pub fn verb(self) -> &'static str {
+        match self {
+            ResultKind::App | ResultKind::Game | ResultKind::Command => "Launch",
+            ResultKind::Shell                                        => "Run",
+            ResultKind::Window                                       => "Focus",
+            ResultKind::Clipboard | ResultKind::Calculation          => "Copy",
+            ResultKind::Web                                          => "Open",
+            ResultKind::Snippet                                      => "Insert",
+            ResultKind::Document | ResultKind::Place                 => "Open",
+            ResultKind::Wallpaper                                    => "Set",
+        }
+    }
+
+    /// Rows backed by an installed app, which can be renamed, hidden and
+    /// given launch options.
+    pub fn is_app(self) -> bool {
+        matches!(self, ResultKind::App | ResultKind::Game | ResultKind::Command)
+    }
+}
+
+/// A row as the GUI draws it: the provider's `SearchResult` with the custom
+/// name applied and the icon resolved to a file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultRow {
+    /// The provider's title; pass it back to launch or edit the row.
+    pub id:       String,
+    /// Label shown in the list or grid.
+    pub title:    String,
+    /// Hover text (description, expression, window class, …).
+    pub subtitle: Option<String>,
+    /// Icon file to draw, if one was found.
+    pub icon:     Option<String>,
+    /// Cover art for the games grid; the icon when there is none.
+    pub cover:    Option<String>,
+    pub kind:     ResultKind,
+    /// The row's action menu, `Launch` first.
+    pub actions:  Vec<RowAction>,
+}
+
+/// An entry in a row's action menu (right-click, or Alt+Enter on the
+/// selected row). Providers are handed `Launch`, `Provider` and
+/// `LaunchInTerminal`; the launcher and GUI carry out the rest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RowAction {
+    Launch,
+    /// One of the row's own `SearchResult::actions` (a desktop action,
+    /// another search engine, …).
+    Provider(String),
+    LaunchInTerminal,
+    /// Open the folder holding the `.desktop` file at this path.
+    ShowDesktopFile(String),
+    /// Put this command line on the clipboard.
+    CopyCommand(String),
+    Pin,
+    Unpin,
+    Hide,
+    Unhide,
+    EditLaunchOptions,
+}
+
+impl RowAction {
+    pub fn label(&self, kind: ResultKind) -> &str {
+        match self {
+            RowAction::Launch             => kind.verb(),
+            RowAction::Provider(name)     => name,
+            RowAction::LaunchInTerminal   => "Launch in Terminal",
+            RowAction::ShowDesktopFile(_) => "Show .desktop File",
+            RowAction::CopyCommand(_)     => "Copy Command",
+            RowAction::Pin                => "Pin",
+            RowAction::Unpin              => "Unpin",
+            RowAction::Hide               => "Hide from launcher",
+            RowAction::Unhide             => "Unhide",
+            RowAction::EditLaunchOptions  => "Launch Options…",
+        }
+    }
+}
+
+pub trait SearchProvider: Send {
+    /// Identifier used in the `search-providers` config key.
+    fn name(&self) -> &'static str;
+
+    /// Default icon for rows that don't carry their own.
+    fn icon(&self) -> Option<&str> { None }
+
+    /// Shown in the search bar's mode badge while a prefix routes here.
+    fn mode(&self) -> &'static str { self.name() }
+
+    /// What this provider's rows stand for.
+    fn kind(&self) -> ResultKind;
+
+    /// Return `true` to take the query exclusively because of what it looks
+    /// like (a `!bang`, a URL); no other provider is asked for results then.
+    /// Prefix modes go through `route` instead.
+    fn claims(&self, _query: &str) -> bool { false }
+
+    /// Only answer queries a prefix routes here (run mode, windows, …),
+    /// which would otherwise match everything.
+    fn routed_only(&self) -> bool { false }
+
+    /// Return `true` if routed rows should be shown as a grid of cover art
+    /// rather than a list. All rows are kept (no `max-search-results` cap).
+    fn grid(&self) -> bool { false }
+
+    /// Up to `max` rows for `query`.
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult>;
+
+    /// Rows for a query a prefix routed here, with the prefix already
+    /// stripped; no other provider is asked then.
+    fn routed(&mut self, query: &str, max: usize) -> Vec<SearchResult> { self.results(query, max) }
+
+    /// Menu categories of the rows this provider can list (repeats are fine).
+    fn categories(&self) -> Vec<&'static str> { Vec::new() }
+
+    /// Every row this provider can list, for browsing with an empty query
+    /// (`show-all-apps`). Sorted by the launcher, so any order will do.
+    fn browse(&mut self) -> Vec<SearchResult> { Vec::new() }
+
+    /// Rows can also be started inside a terminal emulator
+    /// (`RowAction::LaunchInTerminal`).
+    fn terminal(&self) -> bool { false }
+
+    /// Run `result`, one of its `actions`, or it in a terminal. Returns
+    /// `true` when the launcher should close afterwards.
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool;
+
+    /// Integrate background work. Returns `true` if results may have changed.
+    fn poll(&mut self) -> bool { false }
+
+    /// Fallback providers are only asked when no other provider had a row
+    /// (unless they claim the query).
+    fn fallback(&self) -> bool { false }
+}
+
+/// Provider order used when `search-providers` isn't set.
+pub const DEFAULT_PROVIDERS: &[&str] = &["windows", "clipboard", "snippets", "run", "calculator", "conversion", "flatpak", "desktop", "steam", "path", "recent", "places", "wallpaper", "web"];
+
+// ============================================================================
+// Prefix routing
+// ============================================================================
+
+/// (prefix, provider name) pairs: the `prefixes` entries (`"d:=desktop"`)
+/// first, then the providers with a prefix key of their own. An empty
+/// prefix is skipped.
+pub fn routes(config: &Config) -> Vec<(String, String)> {
+    let custom = config.prefixes.iter().filter_map(|spec| {
+        // rsplit, so a prefix may itself contain `=` (`"==calculator"`).
+        let (prefix, provider) = spec.rsplit_once('=')?;
+        Some((prefix.to_string(), provider.trim().to_string()))
+    });
+    let builtin = [
+        (&config.run_prefix,       "run"),
+        (&config.window_prefix,    "windows"),
+        (&config.clipboard_prefix, "clipboard"),
+        (&config.snippet_prefix,   "snippets"),
+        (&config.recent_prefix,    "recent"),
+        (&config.places_prefix,    "places"),
+        (&config.wallpaper_prefix, "wallpaper"),
+        (&config.games_prefix,     "steam"),
+    ].into_iter().map(|(prefix, provider)| (prefix.clone(), provider.to_string()));
+    custom.chain(builtin).filter(|(prefix, _)| !prefix.is_empty()).collect()
+}
+
+/// The provider `query`'s prefix routes to and the rest of the query. The
+/// longest matching prefix wins, and the earlier route on a tie.
+pub fn route<'q, 'r>(query: &'q str, routes: &'r [(String, String)]) -> Option<(&'r str, &'q str)> {
+    routes.iter()
+        .rev()
+        .filter(|(prefix, _)| query.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(prefix, provider)| (provider.as_str(), &query[prefix.len()..]))
+}
+
+// ============================================================================
+// Calculator
+// ============================================================================
+
+pub struct CalculatorProvider;
+
+impl SearchProvider for CalculatorProvider {
+    fn name(&self) -> &'static str { "calculator" }
+    fn icon(&self) -> Option<&str> { Some("accessories-calculator") }
+    fn mode(&self) -> &'static str { "Calculator" }
+    fn kind(&self) -> ResultKind { ResultKind::Calculation }
+
+    fn results(&mut self, query: &str, _max: usize) -> Vec<SearchResult> {
+        crate::calc::evaluate(query).map(crate::calc::format_result).map(|value| SearchResult {
+            title:       format!("= {value}"),
+            description: Some("Copy to clipboard".into()),
+            data:        value,
+            ..Default::default()
+        }).into_iter().collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, _action: &RowAction) -> bool {
+        copy_result(&result.data)
+    }
+}
+
+// ============================================================================
+// Unit / currency conversion
+// ============================================================================
+
+pub struct ConversionProvider {
+    rates: crate::convert::CurrencyRates,
+}
+
+impl ConversionProvider {
+    pub fn new(config: &Config) -> Self {
+        ConversionProvider { rates: crate::convert::CurrencyRates::new(config) }
+    }
+}
+
+impl SearchProvider for ConversionProvider {
+    fn name(&self) -> &'static str { "conversion" }
+    fn icon(&self) -> Option<&str> { Some("accessories-calculator") }
+    fn mode(&self) -> &'static str { "Convert" }
+    fn kind(&self) -> ResultKind { ResultKind::Calculation }
+
+    fn results(&mut self, query: &str, _max: usize) -> Vec<SearchResult> {
+        crate::convert::convert(query, &self.rates).map(|c| SearchResult {
+            title:       format!("= {}", c.text),
+            description: Some("Copy to clipboard".into()),
+            data:        c.value,
+            ..Default::default()
+        }).into_iter().collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, _action: &RowAction) -> bool {
+        copy_result(&result.data)
+    }
+}
+
+// ============================================================================
+// Window switcher
+// ============================================================================
+
+/// The window list is re-read at most this often while typing.
+const WINDOW_LIST_TTL: Duration = Duration::from_secs(1);
+
+/// Answers queries routed by `window-prefix`; rows focus open windows.
+#[derive(Default)]
+pub struct WindowProvider {
+    windows: Vec<crate::windows::Window>,
+    fetched: Option<Instant>,
+}
+
+impl SearchProvider for WindowProvider {
+    fn name(&self) -> &'static str { "windows" }
+    fn icon(&self) -> Option<&str> { Some("preferences-system-windows") }
+    fn mode(&self) -> &'static str { "Windows" }
+    fn kind(&self) -> ResultKind { ResultKind::Window }
+    fn routed_only(&self) -> bool { true }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        if self.fetched.is_none_or(|t| t.elapsed() >= WINDOW_LIST_TTL) {
+            self.windows = crate::windows::list();
+            self.fetched = Some(Instant::now());
+        }
+        let needle = query.trim().to_lowercase();
+        let mut seen = HashMap::new();
+        self.windows.iter()
+            .filter(|w| w.title.to_lowercase().contains(&needle) || w.class.contains(&needle))
+            .take(max)
+            .map(|w| {
+                let title = if w.title.is_empty() { w.class.clone() } else { w.title.clone() };
+                SearchResult {
+                    title:       unique_title(&mut seen, title),
+                    icon:        (!w.class.is_empty()).then(|| w.class.clone()),
+                    description: Some(w.class.clone()),
+                    data:        w.id.clone(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, _action: &RowAction) -> bool {
+        let focused = crate::windows::focus(&result.data);
+        if !focused { tracing::warn!(target: "window", "Failed to focus window {}", result.data); }
+        focused
+    }
+}
+
+// ============================================================================
+// Clipboard history
+// ============================================================================
+
+/// Longest row title; the full entry is shown on hover.
+const CLIP_PREVIEW_CHARS: usize = 80;
+
+/// Answers queries routed by `clipboard-prefix`; rows re-copy past entries.
+pub struct ClipboardProvider {
+    history: crate::clipboard::ClipboardHistory,
+}
+
+impl ClipboardProvider {
+    /// `None` when clipboard history is disabled.
+    pub fn new(config: &Config) -> Option<Self> {
+        Some(ClipboardProvider { history: crate::clipboard::ClipboardHistory::new(config)? })
+    }
+}
+
+impl SearchProvider for ClipboardProvider {
+    fn name(&self) -> &'static str { "clipboard" }
+    fn icon(&self) -> Option<&str> { Some("edit-paste") }
+    fn mode(&self) -> &'static str { "Clipboard" }
+    fn kind(&self) -> ResultKind { ResultKind::Clipboard }
+    fn routed_only(&self) -> bool { true }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        let needle = query.trim().to_lowercase();
+        let mut seen = HashMap::new();
+        self.history.entries().into_iter()
+            .filter(|entry| entry.to_lowercase().contains(&needle))
+            .take(max)
+            .map(|entry| {
+                let flat = entry.split_whitespace().collect::<Vec<_>>().join(" ");
+                let preview = match flat.char_indices().nth(CLIP_PREVIEW_CHARS) {
+                    Some((cut, _)) => format!("{}…", &flat[..cut]),
+                    None           => flat,
+                };
+                SearchResult {
+                    title:       unique_title(&mut seen, preview),
+                    description: Some(entry.clone()),
+                    data:        entry,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, _action: &RowAction) -> bool {
+        copy_result(&result.data)
+    }
+}
+
+// ============================================================================
+// Snippets
+// ============================================================================
+
+const COPY_SNIPPET: &str = "Copy to clipboard";
+const TYPE_SNIPPET: &str = "Type into window";
+
+/// Answers queries routed by `snippet-prefix`; rows copy or type a snippet.
+pub struct SnippetProvider {
+    snippets: crate::snippets::Snippets,
+    /// `snippet-insert = "type"`: Enter types rather than copies.
+    types:    bool,
+}
+
+impl SnippetProvider {
+    pub fn new(config: &Config) -> Self {
+        let types = match config.snippet_insert.as_str() {
+            "type" => true,
+            "copy" => false,
+            other  => {
+                tracing::warn!(target: "config", "snippet-insert: expected \"copy\" or \"type\", not {other:?}");
+                false
+            }
+        };
+        SnippetProvider { snippets: crate::snippets::Snippets::load(), types }
+    }
+}
+
+impl SearchProvider for SnippetProvider {
+    fn name(&self) -> &'static str { "snippets" }
+    fn icon(&self) -> Option<&str> { Some("insert-text") }
+    fn mode(&self) -> &'static str { "Snippets" }
+    fn kind(&self) -> ResultKind { ResultKind::Snippet }
+    fn routed_only(&self) -> bool { true }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        let other = if self.types { COPY_SNIPPET } else { TYPE_SNIPPET };
+        self.snippets.search(query, max).into_iter()
+            .map(|snippet| SearchResult {
+                title:       snippet.name,
+                description: Some(snippet.text.clone()),
+                actions:     vec![other.to_string()],
+                data:        snippet.text,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        let types = match action {
+            RowAction::Provider(name) => name == TYPE_SNIPPET,
+            _                         => self.types,
+        };
+        if types { crate::snippets::type_text(&result.data) } else { copy_result(&result.data) }
+    }
+}
+
+// ============================================================================
+// Recent files
+// ============================================================================
+
+const OPEN_FOLDER:  &str = "Open Containing Folder";
+const CLEAR_RECENT: &str = "Clear Recent Files";
+
+/// Files other apps opened lately whose name matches the query; all of them,
+/// newest first, when `recent-prefix` routes here.
+pub struct RecentProvider {
+    files:   crate::recent::RecentFiles,
+    /// The list was just cleared; the shown rows are gone.
+    cleared: bool,
+}
+
+impl Default for RecentProvider {
+    fn default() -> Self {
+        RecentProvider { files: crate::recent::RecentFiles::load(), cleared: false }
+    }
+}
+
+impl SearchProvider for RecentProvider {
+    fn name(&self) -> &'static str { "recent" }
+    fn icon(&self) -> Option<&str> { Some("document-open-recent") }
+    fn mode(&self) -> &'static str { "Recent Files" }
+    fn kind(&self) -> ResultKind { ResultKind::Document }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        if query.trim().is_empty() { return Vec::new(); }
+        self.routed(query, max)
+    }
+
+    fn routed(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        let mut seen = HashMap::new();
+        self.files.search(query, max).into_iter()
+            .map(|file| SearchResult {
+                title:       unique_title(&mut seen, file.name()),
+                icon:        Some(file.icon().to_string()),
+                description: Some(file.path.display().to_string()),
+                actions:     vec![OPEN_FOLDER.to_string(), CLEAR_RECENT.to_string()],
+                data:        file.path.to_string_lossy().into_owned(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        match action {
+            RowAction::Provider(name) if name == CLEAR_RECENT => {
+                self.cleared = self.files.clear();
+                false
+            }
+            RowAction::Provider(name) if name == OPEN_FOLDER => {
+                let folder = std::path::Path::new(&result.data).parent().map(|p| p.to_string_lossy().into_owned());
+                folder.is_some_and(|folder| crate::system::open_uri(&folder))
+            }
+            _ => crate::system::open_uri(&result.data),
+        }
+    }
+
+    fn poll(&mut self) -> bool { std::mem::take(&mut self.cleared) }
+}
+
+// ============================================================================
+// Places
+// ============================================================================
+
+/// Home, the user directories, GTK bookmarks and mounted drives whose name
+/// matches the query; all of them when `places-prefix` routes here. Rows
+/// open in the file manager.
+pub struct PlacesProvider {
+    drives:  crate::drives::DrivesHost,
+    /// A drive was plugged in, mounted or removed since the last poll.
+    changed: Arc<AtomicBool>,
+}
+
+impl Default for PlacesProvider {
+    fn default() -> Self {
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag    = Arc::clone(&changed);
+        let drives  = crate::drives::DrivesHost::new(move || flag.store(true, Ordering::Relaxed));
+        PlacesProvider { drives, changed }
+    }
+}
+
+impl SearchProvider for PlacesProvider {
+    fn name(&self) -> &'static str { "places" }
+    fn icon(&self) -> Option<&str> { Some("folder") }
+    fn mode(&self) -> &'static str { "Places" }
+    fn kind(&self) -> ResultKind { ResultKind::Place }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        if query.trim().is_empty() { return Vec::new(); }
+        self.routed(query, max)
+    }
+
+    fn routed(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        let needle = query.trim().to_lowercase();
+        let drives = self.drives.mounted().into_iter().filter_map(|volume| {
+            let target = volume.mount_point?;
+            Some(crate::places::Place { name: volume.name, target, icon: volume.icon })
+        });
+        let mut seen = HashMap::new();
+        crate::places::load().into_iter()
+            .chain(drives)
+            .filter(|place| place.name.to_lowercase().contains(&needle))
+            .take(max)
+            .map(|place| SearchResult {
+                title:       unique_title(&mut seen, place.name),
+                icon:        Some(place.icon),
+                description: Some(place.target.clone()),
+                data:        place.target,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn activate(&mut self, result: &SearchResult, _action: &RowAction) -> bool {
+        crate::system::open_uri(&result.data)
+    }
+
+    fn poll(&mut self) -> bool { self.changed.swap(false, Ordering::Relaxed) }
+}
+
+// ============================================================================
+// Wallpapers
+// ============================================================================
+
+/// The output list is re-read at most this often while typing.
+const OUTPUT_LIST_TTL: Duration = Duration::from_secs(5);
+/// Action prefix of the per-output entries, followed by the output name.
+const SET_ON: &str = "Set on ";
+
+/// Answers queries routed by `wallpaper-prefix`: the images in
+/// `wallpaper-dir` as a thumbnail grid. Enter sets one on every output,
+/// "Set on <output>" on one.
+pub struct WallpaperProvider {
+    dir:     std::path::PathBuf,
+    setter:  String,
+    outputs: Vec<String>,
+    fetched: Option<Instant>,
+}
+
+impl WallpaperProvider {
+    pub fn new(config: &Config) -> Self {
+        WallpaperProvider {
+            dir:     crate::wallpaper::dir(config),
+            setter:  config.wallpaper_setter.clone(),
+            outputs: Vec::new(),
+            fetched: None,
+        }
+    }
+}
+
+impl SearchProvider for WallpaperProvider {
+    fn name(&self) -> &'static str { "wallpaper" }
+    fn icon(&self) -> Option<&str> { Some("preferences-desktop-wallpaper") }
+    fn mode(&self) -> &'static str { "Wallpapers" }
+    fn kind(&self) -> ResultKind { ResultKind::Wallpaper }
+    fn routed_only(&self) -> bool { true }
+    fn grid(&self) -> bool { true }
+
+    fn results(&mut self, query: &str, max: usize) -> Vec<SearchResult> {
+        if self.fetched.is_none_or(|t| t.elapsed() >= OUTPUT_LIST_TTL) {
+            self.outputs = crate::monitors::list().into_iter().map(|m| m.name).collect();
+            self.fetched = Some(Instant::now());
+        }
+        let needle = query.trim().to_lowercase();
+        let found: Vec<crate::wallpaper::Wallpaper> = crate::wallpaper::list(&self.dir).into_iter()
+            .filter(|w| w.name.to_lowercase().contains(&needle))
+            .take(max)
+            .collect();
+        let current = crate::wallpaper::remembered();
+        let actions: Vec<String> = if self.outputs.len() > 1 {
+            self.outputs.iter().map(|output| format!("{SET_ON}{output}")).collect()
+        } else {
+            Vec::new()
+        };
+        crate::pool::map(found, |w| {
+            let shown_on: Vec<&str> = current.iter().filter(|(_, path)| *path == w.path).map(|(output, _)| output.as_str()).collect();
+            let description = match shown_on.as_slice() {
+                []    => w.path.display().to_string(),
+                ["*"] => "On every output".to_string(),
+                on    => format!("On {}", on.join(", ")),
+            };
+            SearchResult {
+                title:       w.name,
+                description: Some(description),
+                actions:     actions.clone(),
+                image:       crate::wallpaper::thumbnail(&w.path).map(|p| p.to_string_lossy().into_owned()),
+                data:        w.path.to_string_lossy().into_owned(),
+                ..Default::default()
+            }
+        })
+    }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        let output = match action {
+            RowAction::Provider(name) => name.strip_prefix(SET_ON).map(str::to_string),
+            _                         => None,
+        };
+        let setter = self.setter.clone();
+        let path   = std::path::PathBuf::from(&result.data);
+        // Detecting the setter runs `swww query`, and swaybg takes half a
+        // second to hand over; don't hold the UI for either.
+        std::thread::spawn(move || {
+            let Some(setter) = crate::wallpaper::Setter::pick(&setter) else {
+                tracing::warn!(target: "wallpaper", "No wallpaper setter found (swww, hyprpaper or swaybg)");
+                return;
+            };
+            if let Err(e) = crate::wallpaper::set(setter, &path, output.as_deref()) {
+                tracing::warn!(target: "wallpaper", "Failed to set {}: {e}", path.display());
+            }
+        });
+        true
+    }
+}
+
+// ============================================================================
+// Web search / URLs
+// ============================================================================
+
+/// Offers "Open URL" for URL-like queries and a web search for everything
+/// else nothing matched. `!key terms` searches with a specific engine from
+/// `search-engines` (`key=url` with `%s` for the terms; the first is the default).
+pub struct WebProvider {
+    engines: Vec<(String, String)>,
+}
+
+impl WebProvider {
+    pub fn new(config: &Config) -> Self {
+        let engines = config.search_engines.iter().filter_map(|spec| {
+            let parsed = spec.split_once('=')
+                .map(|(key, url)| (key.trim().trim_start_matches('!').to_string(), url.trim().to_string()))
+                .filter(|(key, url)| !key.is_empty() && !url.is_empty());
+            if parsed.is_none() { tracing::warn!(target: "search", "Ignoring search engine '{spec}': expected key=url"); }
+            parsed
+        }).collect();
+        WebProvider { engines }
+    }
+
+    /// `!key terms` → (engine, terms) for a configured key.
+    fn bang<'q>(&self, query: &'q str) -> Option<(&(String, String), &'q str)> {
+        let rest = query.trim_start().strip_prefix('!')?;
+        let (key, terms) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let engine = self.engines.iter().find(|(k, _)| k.eq_ignore_ascii_case(key))?;
+        Some((engine, terms.trim()))
+    }
+
+    fn search_row(&self, engine: &(String, String), terms: &str, title: String) -> SearchResult {
+        SearchResult {
+            title,
+            description: Some(search_url(&engine.1, terms)),
+            actions:     self.engines.iter()
+                .filter(|(k, _)| *k != engine.0)
+                .map(|(k, _)| format!("Search with !{k}"))
+                .collect(),
+            data:        format!("{}\t{terms}", engine.0),
+            ..Default::default()
+        }
+    }
+}
+
+impl SearchProvider for WebProvider {
+    fn name(&self) -> &'static str { "web" }
+    fn icon(&self) -> Option<&str> { Some("web-browser") }
+    fn mode(&self) -> &'static str { "Web" }
+    fn kind(&self) -> ResultKind { ResultKind::Web }
+    fn fallback(&self) -> bool { true }
+
+    fn claims(&self, query: &str) -> bool {
+        self.bang(query).is_some() || query.trim().contains("://")
+    }
+
+    fn results(&mut self, query: &str, _max: usize) -> Vec<SearchResult> {
+        let query = query.trim();
+        if query.is_empty() { return Vec::new(); }
+        if let Some((engine, terms)) = self.bang(query) {
+            if terms.is_empty() { return Vec::new(); }
+            let title = format!("Search {} for \"{terms}\"", url_host(&engine.1));
+            return vec![self.search_row(engine, terms, title)];
+        }
+
+        let mut rows = Vec::new();
+        if let Some(url) = as_url(query) {
+            rows.push(SearchResult {
+                title:       format!("Open {url}"),
+                description: Some("Open in the default browser".into()),
+                data:        url,
+                ..Default::default()
+            });
+        }
+        if let Some(engine) = self.engines.first() {
+            rows.push(self.search_row(engine, query, format!("Search the web for \"{query}\"")));
+        }
+        rows
+    }
+
+    fn activate(&mut self, result: &SearchResult, action: &RowAction) -> bool {
+        // Search rows carry `key\tterms`; "Open" rows carry the URL, which
+        // never contains whitespace.
+        let url = match result.data.split_once('\t') {
+            Some((key, terms)) => {
+                let key = match action {
+                    RowAction::Provider(name) => name.strip_prefix("Search with !").unwrap_or(key),
+                    _                         => key,
+                };
+                let Some((_, tmpl)) = self.engines.iter().find(|(k, _)| k == key) else { return false };
+                search_url(tmpl, terms)
+            }
+            None => result.data.clone(),
+        };
+        crate::system::open_uri(&url)
+    }
+}
+
+/// `query` as a URL if it looks like one: an explicit scheme, or a bare
+/// `host.tld[/path]` (opened over https).
+fn as_url(query: &str) -> Option<String> {
+    if query.contains(char::is_whitespace) { return None; }
+    if let Some((scheme, rest)) = query.split_once("://") {
+        let valid = !rest.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        return valid.then(|| query.to_string());
+    }
+    let host = query.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once(':').filter(|(_, port)| port.parse::<u16>().is_ok()).map_or(host, |(h, _)| h);
+    if host == "localhost" { return Some(format!("http://{query}")); }
+    let labels: Vec<&str> = host.split('.').collect();
+    let tld = labels.last()?;
+    let ok = labels.len() >= 2
+        && labels.iter().all(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        && tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic());
+    ok.then(|| format!("https://{query}"))
+}
+
+/// Fills `%s` in an engine template with the percent-encoded terms (appended
+/// when the template has no `%s`).
+fn search_url(template: &str, terms: &str) -> String {
+    let encoded: String = terms.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect();
+    if template.contains("%s") { template.replace("%s", &encoded) } else { format!("{template}{encoded}") }
+}
+
+/// `https://www.youtube.com/results?…` → `youtube.com`
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, r)| r);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// Rows are addressed by title, so repeats within one provider get numbered.
+fn unique_title(seen: &mut HashMap<String, usize>, title: String) -> String {
+    let n = seen.entry(title.clone()).or_insert(0);
+    *n += 1;
+    if *n > 1 { format!("{title} ({n})") } else { title }
+}
+
+fn copy_result(value: &str) -> bool {
+    let copied = crate::system::copy_to_clipboard(value);
+    if !copied { tracing::warn!(target: "clipboard", "Failed to copy result: no clipboard tool (wl-copy, xclip, xsel) found"); }
+    copied
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_url() {
+        assert_eq!(as_url("example.com/a?b=c").as_deref(), Some("https://example.com/a?b=c"));
+        assert_eq!(as_url("localhost:8080").as_deref(), Some("http://localhost:8080"));
+        assert_eq!(as_url("ftp://host/file").as_deref(), Some("ftp://host/file"));
+        assert_eq!(as_url("1.5"), None);
+        assert_eq!(as_url("firefox"), None);
+        assert_eq!(as_url("3 km to mi"), None);
+    }
+
+    #[test]
+    fn test_route() {
+        let config = Config {
+            prefixes: vec!["==calculator".into(), "w:=desktop".into(), "wi:=windows".into()],
+            ..Config::default()
+        };
+        let routes = routes(&config);
+        assert_eq!(route("=1+2", &routes), Some(("calculator", "1+2")));
+        // A `prefixes` entry wins over window-prefix; the longest prefix wins overall.
+        assert_eq!(route("w:term", &routes), Some(("desktop", "term")));
+        assert_eq!(route("wi:term", &routes), Some(("windows", "term")));
+        assert_eq!(route(">htop", &routes), Some(("run", "htop")));
+        assert_eq!(route("firefox", &routes), None);
+    }
+
+    #[test]
+    fn test_search_url_and_host() {
+        assert_eq!(search_url("https://duckduckgo.com/?q=%s", "rust & c++"), "https://duckduckgo.com/?q=rust%20%26%20c%2B%2B");
+        assert_eq!(search_url("https://example.com/search/", "a b"), "https://example.com/search/a%20b");
+        assert_eq!(url_host("https://www.youtube.com/results?search_query=%s"), "youtube.com");
+    }
+}
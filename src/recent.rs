@@ -0,0 +1,173 @@
+//! Recently used files: the list GTK and KDE apps add to when a file is
+//! opened or saved, `$XDG_DATA_HOME/recently-used.xbel`.
+//!
+//! Design:
+//! - The file is XBEL; a small scanner picks out each `<bookmark>`'s `href`,
+//!   `modified`/`visited` times and `<mime:mime-type>` rather than pulling in
+//!   an XML crate for three fields.
+//! - Only `file://` entries that still exist are kept, newest first. The file
+//!   is read again when its mtime moves, checked on each query.
+//! - Icons are the generic names from the icon naming spec
+//!   (`image-x-generic`, `x-office-document`, …), which every theme has;
+//!   per-type names like `application-vnd.oasis.opendocument.text` mostly
+//!   aren't drawn.
+//! - Clearing writes back an empty list, as GTK's own "Clear" does. Apps that
+//!   are open keep their copy and may add their entries again.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+const RECENT_FILE: &str = "recently-used.xbel";
+
+const EMPTY_XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+</xbel>
+"#;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub mime: String,
+    /// Last modified or visited, as written (ISO 8601, so it sorts as text).
+    pub used: String,
+}
+
+impl RecentFile {
+    pub fn name(&self) -> String {
+        self.path.file_name().map_or_else(|| self.path.display().to_string(), |n| n.to_string_lossy().into_owned())
+    }
+
+    pub fn icon(&self) -> &'static str { mime_icon(&self.mime) }
+}
+
+pub struct RecentFiles {
+    path:     PathBuf,
+    modified: Option<SystemTime>,
+    items:    Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    pub fn load() -> Self {
+        let mut recent = RecentFiles { path: crate::paths::data_home().join(RECENT_FILE), modified: None, items: Vec::new() };
+        recent.reload();
+        recent
+    }
+
+    /// Read the file again if it changed since the last read.
+    fn reload(&mut self) {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.modified { return; }
+        self.modified = modified;
+        self.items = fs::read_to_string(&self.path)
+            .map(|src| parse(&src).into_iter().filter(|f| f.path.exists()).collect())
+            .unwrap_or_default();
+    }
+
+    /// Files whose name contains `query`, newest first; all of them for an
+    /// empty query.
+    pub fn search(&mut self, query: &str, max: usize) -> Vec<RecentFile> {
+        self.reload();
+        let needle = query.trim().to_lowercase();
+        self.items.iter()
+            .filter(|f| f.name().to_lowercase().contains(&needle))
+            .take(max)
+            .cloned()
+            .collect()
+    }
+
+    /// Empty the list, for every app that reads it.
+    pub fn clear(&mut self) -> bool {
+        match fs::write(&self.path, EMPTY_XBEL) {
+            Ok(()) => { self.items.clear(); true }
+            Err(e) => {
+                tracing::warn!(target: "search", "Failed to clear {}: {e}", self.path.display());
+                false
+            }
+        }
+    }
+}
+
+/// Local files in `src`, newest first.
+fn parse(src: &str) -> Vec<RecentFile> {
+    let mut items: Vec<RecentFile> = src.split("<bookmark ").skip(1).filter_map(|chunk| {
+        let tag  = &chunk[..chunk.find('>')?];
+        let body = chunk.split("</bookmark>").next().unwrap_or(chunk);
+        let path = crate::paths::from_file_uri(&attr(tag, "href")?)?;
+        let mime = body.split_once("<mime:mime-type ").and_then(|(_, rest)| attr(rest, "type")).unwrap_or_default();
+        let used = [attr(tag, "modified"), attr(tag, "visited"), attr(tag, "added")].into_iter().flatten().max().unwrap_or_default();
+        Some(RecentFile { path, mime, used })
+    }).collect();
+    items.sort_by(|a, b| b.used.cmp(&a.used));
+    items
+}
+
+/// `name="value"` in an element's attributes, unescaped.
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let key   = format!("{name}=\"");
+    let at    = tag.match_indices(&key).find(|(i, _)| *i == 0 || tag[..*i].ends_with(char::is_whitespace))?.0;
+    let start = at + key.len();
+    let len   = tag[start..].find('"')?;
+    Some(unescape(&tag[start..start + len]))
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// A generic icon name for `mime`.
+fn mime_icon(mime: &str) -> &'static str {
+    let (major, minor) = mime.split_once('/').unwrap_or((mime, ""));
+    let has = |words: &[&str]| words.iter().any(|w| minor.contains(w));
+    match major {
+        "image"                                                                  => "image-x-generic",
+        "audio"                                                                  => "audio-x-generic",
+        "video"                                                                  => "video-x-generic",
+        "font"                                                                   => "font-x-generic",
+        "inode" if minor == "directory"                                          => "folder",
+        "text" if minor == "html"                                                => "text-html",
+        "application" if minor == "pdf"                                          => "application-pdf",
+        "application" if has(&["spreadsheet", "excel"])                          => "x-office-spreadsheet",
+        "application" if has(&["presentation", "powerpoint"])                    => "x-office-presentation",
+        "application" if has(&["document", "msword", "rtf"])                     => "x-office-document",
+        "application" if has(&["zip", "tar", "compressed", "rar", "xz", "zstd"]) => "package-x-generic",
+        "application" if has(&["executable", "sharedlib", "shellscript"])        => "application-x-executable",
+        _                                                                        => "text-x-generic",
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0" xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info">
+  <bookmark href="file:///home/me/Tom%20&amp;%20Jerry.pdf" added="2024-01-01T10:00:00Z" modified="2024-01-02T10:00:00Z" visited="2024-01-01T10:00:00Z">
+    <info><metadata owner="http://freedesktop.org"><mime:mime-type type="application/pdf"/></metadata></info>
+  </bookmark>
+  <bookmark href="https://example.com/" added="2024-03-01T10:00:00Z" modified="2024-03-01T10:00:00Z" visited="2024-03-01T10:00:00Z"/>
+  <bookmark href="file:///tmp/song.ogg" added="2024-02-01T10:00:00Z" modified="2024-02-01T10:00:00Z" visited="2024-02-05T10:00:00Z">
+    <info><metadata owner="http://freedesktop.org"><mime:mime-type type="audio/ogg"/></metadata></info>
+  </bookmark>
+</xbel>"#;
+        let items = parse(src);
+        assert_eq!(items.iter().map(RecentFile::name).collect::<Vec<_>>(), ["song.ogg", "Tom & Jerry.pdf"]);
+        assert_eq!(items[0].used, "2024-02-05T10:00:00Z");
+        assert_eq!(items[1].path, PathBuf::from("/home/me/Tom & Jerry.pdf"));
+        assert_eq!((items[0].icon(), items[1].icon()), ("audio-x-generic", "application-pdf"));
+
+        assert_eq!(mime_icon("application/vnd.oasis.opendocument.spreadsheet"), "x-office-spreadsheet");
+        assert_eq!(mime_icon("application/vnd.openxmlformats-officedocument.wordprocessingml.document"), "x-office-document");
+        assert_eq!(mime_icon("application/x-something"), "text-x-generic");
+        assert!(parse(EMPTY_XBEL).is_empty());
+    }
+}
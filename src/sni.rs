@@ -5,11 +5,15 @@
 //! - Queries existing watchers for already-registered items on startup.
 //! - Subscribes to `StatusNotifierItemRegistered` signals from all watchers.
 //! - Per-item signal tasks refresh icons on `NewIcon` / `NewStatus` / etc.
-//! - Items removed when their bus name vanishes.
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+//! - Items removed when their bus name vanishes; if we hold the watcher name
+//!   they're unregistered there too, so other hosts on the bus see it.
+//! - What each bus name and item probe came to, and the signals items send,
+//!   is kept in `Diagnostics` for the `tray-debug` view.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_util::StreamExt;
 use zbus::{interface, Connection};
@@ -22,6 +26,9 @@ use crate::gui::Config;
 
 const T_PROBE: Duration = Duration::from_secs(2);
 const T_FETCH: Duration = Duration::from_secs(5);
+/// Item signals arriving within this window of the first are handled with
+/// one refetch; apps like Discord send `NewIcon` in storms.
+const T_COALESCE: Duration = Duration::from_millis(100);
 
 const SNI_INTERFACES: &[&str] = &[
     "org.kde.StatusNotifierItem",
@@ -44,7 +51,6 @@ const WATCHER_NAMES: &[&str] = &[
 // ============================================================================
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-#[allow(dead_code)]
 pub enum TrayCategory {
     #[default] ApplicationStatus,
     Communications,
@@ -52,6 +58,28 @@ pub enum TrayCategory {
     Hardware,
 }
 
+impl TrayCategory {
+    /// Sort rank in the tray, in the spec's order.
+    pub fn priority(&self) -> usize {
+        match self {
+            TrayCategory::ApplicationStatus => 0,
+            TrayCategory::Communications    => 1,
+            TrayCategory::SystemServices    => 2,
+            TrayCategory::Hardware          => 3,
+        }
+    }
+
+    /// Shown in the tray tooltip.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrayCategory::ApplicationStatus => "Application",
+            TrayCategory::Communications    => "Communications",
+            TrayCategory::SystemServices    => "System service",
+            TrayCategory::Hardware          => "Hardware",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum TrayStatus { #[default] Active, Passive, NeedsAttention }
 
@@ -67,6 +95,10 @@ pub struct MenuItem {
     pub visible:      bool,
     pub is_separator: bool,
     pub icon_name:    Option<String>,
+    /// `icon-data`: PNG bytes, drawn in place of `icon_name` when present.
+    pub icon_data:    Vec<u8>,
+    /// `shortcut` formatted for display, e.g. "Ctrl+Q".
+    pub shortcut:     Option<String>,
     pub toggle_type:  ToggleType,
     pub toggle_state: i32,
     pub children:     Vec<MenuItem>,
@@ -76,6 +108,9 @@ pub struct MenuItem {
 #[allow(dead_code)]
 pub struct TrayIcon {
     pub id:              String,
+    /// The item's `Id` property; unlike `id` (the bus service) it's stable
+    /// across restarts, so preferences are keyed by it.
+    pub sni_id:          String,
     pub bus_name:        String,
     pub obj_path:        String,
     pub category:        TrayCategory,
@@ -90,6 +125,9 @@ pub struct TrayIcon {
     pub attention_icon_w:     u32,
     pub attention_icon_h:     u32,
     pub attention_icon_name:  Option<String>,
+    /// `AttentionMovieName`: animation (icon name or file path) to play while
+    /// NeedsAttention.
+    pub attention_movie_name: Option<String>,
 
     pub overlay_icon_rgba: Vec<u8>,
     pub overlay_icon_w:    u32,
@@ -111,7 +149,131 @@ pub struct TrayIcon {
     pub icon_rev: u32,
 }
 
-pub type TrayItems = Arc<Mutex<Vec<TrayIcon>>>;
+/// Tray items shared with the GUI. Writers go through `update`, which bumps
+/// the generation and wakes the GUI: it repaints as soon as something changes
+/// and only re-reads the list when the generation moved.
+pub struct TrayState {
+    items:       Mutex<Vec<TrayIcon>>,
+    generation:  AtomicU64,
+    waker:       Box<dyn Fn() + Send + Sync>,
+    diagnostics: Mutex<Diagnostics>,
+}
+
+pub type TrayItems = Arc<TrayState>;
+
+impl TrayState {
+    fn new(on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        TrayState {
+            items:       Mutex::new(Vec::new()),
+            generation:  AtomicU64::new(0),
+            waker:       Box::new(on_change),
+            diagnostics: Mutex::new(Diagnostics::default()),
+        }
+    }
+
+    /// Read access to the current list.
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, Vec<TrayIcon>>> { self.items.lock() }
+
+    /// Bumped on every change to the list.
+    pub fn generation(&self) -> u64 { self.generation.load(Ordering::Acquire) }
+
+    pub(crate) fn update<R>(&self, f: impl FnOnce(&mut Vec<TrayIcon>) -> R) -> R {
+        let result = {
+            let mut list = self.items.lock().unwrap();
+            let result = f(&mut list);
+            self.generation.fetch_add(1, Ordering::Release);
+            result
+        };
+        (self.waker)();
+        result
+    }
+
+    /// A copy of the host's record so far.
+    pub fn diagnostics(&self) -> Diagnostics { self.diagnostics.lock().unwrap().clone() }
+
+    /// Record what probing `name` (a bus name, or an item's `bus/path`) came to.
+    pub(crate) fn note_probe(&self, name: &str, outcome: &str, interface: Option<&str>) {
+        tracing::debug!(target: "sni", "{name}: {outcome}");
+        let mut diag  = self.diagnostics.lock().unwrap();
+        let probe     = diag.probes.entry(name.to_string()).or_insert_with(|| Probe::new(outcome));
+        probe.outcome = outcome.to_string();
+        probe.at      = Instant::now();
+        if interface.is_some() { probe.interface = interface.map(str::to_string); }
+    }
+
+    fn note_signal(&self, service: &str, member: &str) {
+        let mut diag = self.diagnostics.lock().unwrap();
+        let probe    = diag.probes.entry(service.to_string()).or_insert_with(|| Probe::new("signalling"));
+        probe.signals += 1;
+        probe.last_signal = Some((member.to_string(), Instant::now()));
+    }
+
+    /// `name` left the bus, with any items it had.
+    fn note_vanished(&self, name: &str) {
+        let prefix = format!("{name}/");
+        let mut diag = self.diagnostics.lock().unwrap();
+        for (key, probe) in diag.probes.iter_mut().filter(|(k, _)| *k == name || k.starts_with(&prefix)) {
+            probe.outcome = if *key == name { "left the bus".into() } else { "gone with its bus name".into() };
+            probe.at      = Instant::now();
+        }
+    }
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// What became of one bus name or item the host looked at.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    pub outcome:     String,
+    /// Where the item's properties came from: an SNI interface, or how
+    /// `GetAll` had to be asked.
+    pub interface:   Option<String>,
+    /// When `outcome` was recorded.
+    pub at:          Instant,
+    pub signals:     u64,
+    /// The latest item signal and when it came.
+    pub last_signal: Option<(String, Instant)>,
+}
+
+impl Probe {
+    fn new(outcome: &str) -> Self {
+        Probe { outcome: outcome.into(), interface: None, at: Instant::now(), signals: 0, last_signal: None }
+    }
+}
+
+/// The SNI host's view of the bus, for working out why an icon is missing.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    /// Whether we hold `org.kde.StatusNotifierWatcher`; `None` until tried.
+    pub watcher_claimed: Option<bool>,
+    /// `RegisterStatusNotifierItem` calls our watcher took.
+    pub registrations:   u64,
+    /// By bus name (`:1.42`) or item (`:1.42/StatusNotifierItem`).
+    pub probes:          BTreeMap<String, Probe>,
+}
+
+impl Diagnostics {
+    /// Plain-text copy, for bug reports.
+    pub fn report(&self) -> String {
+        let ago = |at: Instant| format!("{}s ago", at.elapsed().as_secs());
+        let mut out = format!(
+            "watcher: {}\nregistrations: {}\n",
+            match self.watcher_claimed { Some(true) => "claimed", Some(false) => "held by another process", None => "not tried yet" },
+            self.registrations,
+        );
+        for (name, probe) in &self.probes {
+            out.push_str(&format!("{name}: {} ({})", probe.outcome, ago(probe.at)));
+            if let Some(iface) = &probe.interface { out.push_str(&format!(", via {iface}")); }
+            if let Some((member, at)) = &probe.last_signal {
+                out.push_str(&format!(", {} signals, last {member} {}", probe.signals, ago(*at)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
 
 #[allow(dead_code)]
 pub enum SniAction {
@@ -119,8 +281,8 @@ pub enum SniAction {
     SecondaryActivate { bus_name: String, obj_path: String },
     ContextMenu       { bus_name: String, obj_path: String, x: i32, y: i32 },
     Scroll            { bus_name: String, obj_path: String, delta: i32, orientation: String },
-    MenuAboutToShow   { bus_name: String, menu_path: String },
-    MenuEvent         { bus_name: String, menu_path: String, item_id: i32 },
+    MenuAboutToShow   { bus_name: String, menu_path: String, service_id: String, item_id: i32 },
+    MenuEvent         { bus_name: String, menu_path: String, item_id: i32, event: &'static str },
     FetchMenu         { bus_name: String, menu_path: String, service_id: String },
     RefreshMenu       { bus_name: String, menu_path: String, service_id: String },
 }
@@ -128,13 +290,16 @@ pub enum SniAction {
 pub struct SniHost {
     pub items:     TrayItems,
     pub action_tx: tokio::sync::mpsc::UnboundedSender<SniAction>,
+    /// Legacy XEmbed icons, fed into the same `items`.
+    #[cfg(feature = "xembed")]
+    xembed:        Option<crate::xembed::XembedHost>,
 }
 
 impl SniHost {
-    pub fn new(config: &Config) -> Option<Self> {
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Option<Self> {
         if !config.enable_system_tray { return None; }
 
-        let items: TrayItems = Arc::new(Mutex::new(Vec::new()));
+        let items: TrayItems = Arc::new(TrayState::new(on_change));
         let items_bg = Arc::clone(&items);
         let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
 
@@ -142,24 +307,44 @@ impl SniHost {
             match tokio::runtime::Builder::new_current_thread().enable_all().build() {
                 Ok(rt) => rt.block_on(async {
                     if let Err(e) = run_watcher(items_bg, action_rx).await {
-                        eprintln!("SNI watcher: {e}");
+                        tracing::warn!(target: "sni", "watcher: {e}");
                     }
                 }),
-                Err(e) => eprintln!("SNI: runtime error: {e}"),
+                Err(e) => tracing::error!(target: "sni", "runtime error: {e}"),
             }
         });
 
-        Some(SniHost { items, action_tx })
+        #[cfg(feature = "xembed")]
+        let xembed = config.enable_xembed_tray.then(|| crate::xembed::XembedHost::new(Arc::clone(&items)));
+        #[cfg(not(feature = "xembed"))]
+        if config.enable_xembed_tray { tracing::warn!(target: "sni", "enable-xembed-tray: built without the `xembed` feature"); }
+
+        Some(SniHost {
+            items,
+            action_tx,
+            #[cfg(feature = "xembed")]
+            xembed,
+        })
     }
 
     fn send(&self, action: SniAction) { let _ = self.action_tx.send(action); }
 
+    /// The XEmbed host, if `bus_name` belongs to one of its items.
+    #[cfg(feature = "xembed")]
+    fn xembed(&self, bus_name: &str) -> Option<&crate::xembed::XembedHost> {
+        self.xembed.as_ref().filter(|_| bus_name == crate::xembed::BUS_NAME)
+    }
+
     pub fn activate(&self, bus_name: &str, obj_path: &str) {
+        #[cfg(feature = "xembed")]
+        if let Some(xembed) = self.xembed(bus_name) { return xembed.click(obj_path, 1); }
         self.send(SniAction::Activate { bus_name: bus_name.into(), obj_path: obj_path.into() });
     }
 
     #[allow(dead_code)]
     pub fn secondary_activate(&self, bus_name: &str, obj_path: &str) {
+        #[cfg(feature = "xembed")]
+        if let Some(xembed) = self.xembed(bus_name) { return xembed.click(obj_path, 2); }
         self.send(SniAction::SecondaryActivate { bus_name: bus_name.into(), obj_path: obj_path.into() });
     }
 
@@ -170,22 +355,31 @@ impl SniHost {
     }
 
     pub fn scroll(&self, bus_name: &str, obj_path: &str, delta: i32, orientation: &str) {
+        #[cfg(feature = "xembed")]
+        if let Some(xembed) = self.xembed(bus_name) { return xembed.scroll(obj_path, delta, orientation); }
         self.send(SniAction::Scroll {
             bus_name: bus_name.into(), obj_path: obj_path.into(), delta, orientation: orientation.into(),
         });
     }
 
     pub fn context_menu(&self, bus_name: &str, obj_path: &str, x: i32, y: i32) {
+        #[cfg(feature = "xembed")]
+        if let Some(xembed) = self.xembed(bus_name) { return xembed.click(obj_path, 3); }
         self.send(SniAction::ContextMenu { bus_name: bus_name.into(), obj_path: obj_path.into(), x, y });
     }
 
-    pub fn menu_about_to_show(&self, bus_name: &str, menu_path: &str) {
-        self.send(SniAction::MenuAboutToShow { bus_name: bus_name.into(), menu_path: menu_path.into() });
+    /// The menu (`item_id` 0) or one of its submenus is about to open; the
+    /// layout is fetched again if the app says it changed.
+    pub fn menu_about_to_show(&self, bus_name: &str, menu_path: &str, service_id: &str, item_id: i32) {
+        self.send(SniAction::MenuAboutToShow {
+            bus_name: bus_name.into(), menu_path: menu_path.into(), service_id: service_id.into(), item_id,
+        });
     }
 
-    pub fn menu_event(&self, bus_name: &str, menu_path: &str, item_id: i32) {
+    /// A dbusmenu event on `item_id`: "clicked", "hovered", "opened" or "closed".
+    pub fn menu_event(&self, bus_name: &str, menu_path: &str, item_id: i32, event: &'static str) {
         self.send(SniAction::MenuEvent {
-            bus_name: bus_name.into(), menu_path: menu_path.into(), item_id,
+            bus_name: bus_name.into(), menu_path: menu_path.into(), item_id, event,
         });
     }
 }
@@ -204,8 +398,9 @@ impl Watcher {
     async fn register_status_notifier_item(
         &self,
         service: String,
-        #[zbus(header)]     hdr:  zbus::message::Header<'_>,
-        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)]         hdr:     zbus::message::Header<'_>,
+        #[zbus(connection)]     conn:    &Connection,
+        #[zbus(signal_emitter)] emitter: zbus::object_server::SignalEmitter<'_>,
     ) {
         let sender = hdr.sender()
             .map(|s: &zbus::names::UniqueName| s.to_string())
@@ -228,14 +423,13 @@ impl Watcher {
         }
 
         {
-            let conn2 = conn.clone();
-            let full2 = full.clone();
-            tokio::spawn(async move {
-                if let Ok(ctx) = zbus::object_server::SignalEmitter::new(&conn2, "/StatusNotifierWatcher") {
-                    let _ = Watcher::status_notifier_item_registered(&ctx, &full2).await;
-                }
-            });
+            let mut diag = self.items.diagnostics.lock().unwrap();
+            diag.registrations += 1;
         }
+        self.items.note_probe(&full, "registered with our watcher", None);
+
+        let _ = Watcher::status_notifier_item_registered(&emitter, &full).await;
+        let _ = self.registered_status_notifier_items_changed(&emitter).await;
 
         let items = Arc::clone(&self.items);
         let conn  = conn.clone();
@@ -269,7 +463,8 @@ async fn run_watcher(
     let conn = Connection::session().await?;
 
     let watcher_conn = try_become_watcher(Arc::clone(&items)).await;
-    eprintln!("SNI: watcher {}", if watcher_conn.is_some() { "claimed" } else { "not claimed" });
+    tracing::info!(target: "sni", "watcher {}", if watcher_conn.is_some() { "claimed" } else { "not claimed" });
+    items.diagnostics.lock().unwrap().watcher_claimed = Some(watcher_conn.is_some());
 
     let host_name = format!("org.kde.StatusNotifierHost-{}", std::process::id());
     let _ = conn.request_name(host_name.as_str()).await;
@@ -331,6 +526,7 @@ async fn run_watcher(
         let mut stream = dbus.receive_name_owner_changed().await?;
         let items_w    = Arc::clone(&items);
         let conn_w     = conn.clone();
+        let watcher_w  = watcher_conn.clone();
         tokio::spawn(async move {
             while let Some(sig) = stream.next().await {
                 let Ok(args) = sig.args() else { continue };
@@ -342,7 +538,9 @@ async fn run_watcher(
                     }
                 } else {
                     let prefix = format!("{name}/");
-                    items_w.lock().unwrap().retain(|i| i.bus_name != name && !i.id.starts_with(&prefix));
+                    items_w.update(|list| list.retain(|i| i.bus_name != name && !i.id.starts_with(&prefix)));
+                    items_w.note_vanished(&name);
+                    if let Some(wc) = &watcher_w { unregister_vanished(wc, &name).await; }
                 }
             }
         });
@@ -386,13 +584,16 @@ async fn handle_action(conn: &Connection, action: SniAction, items: TrayItems) {
                 Some("org.kde.StatusNotifierItem"), "Scroll", &(delta, orientation.as_str()),
             ).await;
         }
-        SniAction::MenuAboutToShow { bus_name, menu_path } => {
-            let _ = conn.call_method(
+        SniAction::MenuAboutToShow { bus_name, menu_path, service_id, item_id } => {
+            let reply = conn.call_method(
                 Some(bus_name.as_str()), menu_path.as_str(),
-                Some("com.canonical.dbusmenu"), "AboutToShow", &(0i32,),
+                Some("com.canonical.dbusmenu"), "AboutToShow", &(item_id,),
             ).await;
+            // `needUpdate`: not every app follows it with LayoutUpdated.
+            let stale = reply.ok().and_then(|m| m.body().deserialize::<bool>().ok()).unwrap_or(false);
+            if stale { fetch_menu_internal(conn, &bus_name, &menu_path, &service_id, items).await; }
         }
-        SniAction::MenuEvent { bus_name, menu_path, item_id } => {
+        SniAction::MenuEvent { bus_name, menu_path, item_id, event } => {
             let ts = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default().as_secs() as u32;
@@ -400,7 +601,7 @@ async fn handle_action(conn: &Connection, action: SniAction, items: TrayItems) {
             let _ = conn.call_method(
                 Some(bus_name.as_str()), menu_path.as_str(),
                 Some("com.canonical.dbusmenu"), "Event",
-                &(item_id, "clicked", &data, ts),
+                &(item_id, event, &data, ts),
             ).await;
         }
         SniAction::FetchMenu { bus_name, menu_path, service_id }
@@ -434,6 +635,26 @@ async fn try_become_watcher(items: TrayItems) -> Option<Connection> {
     }
 }
 
+/// Drop the registrations owned by a vanished bus name from our watcher,
+/// emitting `StatusNotifierItemUnregistered` and the property change.
+async fn unregister_vanished(wc: &Connection, name: &str) {
+    let Ok(iface) = wc.object_server().interface::<_, Watcher>("/StatusNotifierWatcher").await else { return };
+    let watcher = iface.get().await;
+    let prefix  = format!("{name}/");
+    let gone: Vec<String> = {
+        let mut reg = watcher.registered.lock().unwrap();
+        let (gone, keep): (Vec<String>, Vec<String>) = reg.drain(..).partition(|s| s == name || s.starts_with(&prefix));
+        *reg = keep;
+        gone
+    };
+    if gone.is_empty() { return; }
+    let emitter = iface.signal_emitter();
+    for service in &gone {
+        let _ = Watcher::status_notifier_item_unregistered(emitter, service).await;
+    }
+    let _ = watcher.registered_status_notifier_items_changed(emitter).await;
+}
+
 async fn query_watcher_items(conn: &Connection, watcher_name: &str) -> Vec<String> {
     let msg = match tokio::time::timeout(
         Duration::from_secs(3),
@@ -560,12 +781,18 @@ async fn scan_one_bus_name(conn: &Connection, bus_name: &str, items: TrayItems)
 /// again. Re-probing shortly after catches these "late" icons; we stop as soon as the
 /// bus name has a known item so already-ready apps incur no extra delay or work.
 async fn scan_one_bus_name_with_retries(conn: &Connection, bus_name: &str, items: TrayItems) {
+    items.note_probe(bus_name, "scanning", None);
+    let found = |items: &TrayItems| items.lock().unwrap().iter().filter(|i| i.bus_name == bus_name).count();
     scan_one_bus_name(conn, bus_name, Arc::clone(&items)).await;
     for delay_ms in [600u64, 2000] {
-        if items.lock().unwrap().iter().any(|i| i.bus_name == bus_name) { return; }
+        if found(&items) > 0 { break; }
         tokio::time::sleep(Duration::from_millis(delay_ms)).await;
         scan_one_bus_name(conn, bus_name, Arc::clone(&items)).await;
     }
+    match found(&items) {
+        0 => items.note_probe(bus_name, "no tray item after 3 scans", None),
+        n => items.note_probe(bus_name, &format!("{n} tray item(s)"), None),
+    }
 }
 
 async fn introspect_find_sni_path(conn: &Connection, bus_name: &str) -> Option<String> {
@@ -598,7 +825,10 @@ async fn probe_service(conn: &Connection, service: &str, items: TrayItems) {
     let unique = if bus_name.starts_with(':') {
         bus_name.to_string()
     } else {
-        match resolve_unique_name(conn, bus_name).await { Some(u) => u, None => return }
+        match resolve_unique_name(conn, bus_name).await {
+            Some(u) => u,
+            None    => return items.note_probe(service, "bus name has no owner", None),
+        }
     };
     let canonical = format!("{unique}{obj_path}");
     let ok = tokio::time::timeout(T_FETCH, fetch_and_watch(conn, &canonical, Arc::clone(&items)))
@@ -665,24 +895,39 @@ async fn watch_sni_signals(conn: &Connection, service: &str, items: TrayItems) {
         futures_util::stream::select(prop_stream.map(|r| (2u8, r)).boxed(), s3),
     );
 
-    while let Some((source, result)) = merged.next().await {
-        let member: Option<String> = match result {
+    let member_of = |source: u8, result: MsgResult| -> Option<String> {
+        match result {
             Err(_) => None,
             Ok(_) if source == 2 => Some("PropertiesChanged".into()),
             Ok(_) if source == 3 => Some("LayoutUpdated".into()),
             Ok(m)                => m.header().member().map(|n: &zbus::names::MemberName| n.as_str().to_string()),
-        };
+        }
+    };
 
-        let is_icon = matches!(member.as_deref(),
-            Some("NewIcon") | Some("NewOverlayIcon") | Some("NewAttentionIcon") |
-            Some("NewIconThemePath") | Some("NewStatus") | Some("NewToolTip") |
-            Some("NewTitle") | Some("NewLabel") | Some("PropertiesChanged")
-        );
-        let is_menu = member.as_deref() == Some("LayoutUpdated");
+    let note = |pending: &mut PendingRefresh, member: Option<String>| {
+        if let Some(member) = &member { items.note_signal(&service_owned, member); }
+        pending.note(member.as_deref());
+    };
+
+    while let Some((source, result)) = merged.next().await {
+        let mut pending = PendingRefresh::default();
+        note(&mut pending, member_of(source, result));
+
+        // Fold in everything else that arrives within the window.
+        let deadline = tokio::time::Instant::now() + T_COALESCE;
+        let mut ended = false;
+        while let Ok(next) = tokio::time::timeout_at(deadline, merged.next()).await {
+            match next {
+                Some((source, result)) => note(&mut pending, member_of(source, result)),
+                None                   => { ended = true; break; }
+            }
+        }
 
-        if is_icon { fetch_icon(conn, &service_owned, Arc::clone(&items)).await; }
+        if pending.all || (!pending.props.is_empty() && !refresh_props(conn, &service_owned, &pending.props, &items).await) {
+            fetch_icon(conn, &service_owned, Arc::clone(&items)).await;
+        }
 
-        if is_menu {
+        if pending.menu {
             let menu_info = {
                 let locked = items.lock().unwrap();
                 locked.iter().find(|i| i.id == service_owned)
@@ -694,9 +939,107 @@ async fn watch_sni_signals(conn: &Connection, service: &str, items: TrayItems) {
                 fetch_menu_internal(conn, &bus, &path, &service_owned, Arc::clone(&items)).await;
             }
         }
+        if ended { break; }
     }
 }
 
+/// What a burst of item signals asks us to refetch.
+#[derive(Default)]
+struct PendingRefresh {
+    /// `PropertiesChanged`: re-read everything with `fetch_icon`.
+    all:   bool,
+    /// Properties named by `New*` signals, fetched one by one.
+    props: Vec<&'static str>,
+    menu:  bool,
+}
+
+impl PendingRefresh {
+    fn note(&mut self, member: Option<&str>) {
+        match member {
+            Some("PropertiesChanged") => self.all = true,
+            Some("LayoutUpdated")     => self.menu = true,
+            Some(m)                   => for prop in props_for_signal(m) {
+                if !self.props.contains(prop) { self.props.push(*prop); }
+            },
+            None                      => {}
+        }
+    }
+}
+
+/// The properties an SNI `New*` signal invalidates.
+fn props_for_signal(member: &str) -> &'static [&'static str] {
+    match member {
+        "NewIcon"                 => &["IconName", "IconPixmap"],
+        "NewAttentionIcon"        => &["AttentionIconName", "AttentionIconPixmap", "AttentionMovieName"],
+        "NewOverlayIcon"          => &["OverlayIconName", "OverlayIconPixmap"],
+        "NewIconThemePath"        => &["IconThemePath"],
+        "NewStatus"               => &["Status"],
+        "NewToolTip" | "NewTitle" => &["ToolTip", "Title"],
+        _                         => &[],
+    }
+}
+
+/// Re-read just `props` of a known item and patch them in. False when the
+/// item isn't in the list yet or none of the Gets succeed, so the caller
+/// falls back to a full `fetch_icon`.
+async fn refresh_props(conn: &Connection, service: &str, props: &[&str], items: &TrayItems) -> bool {
+    let (bus, path) = {
+        let locked = items.lock().unwrap();
+        match locked.iter().find(|i| i.id == service) {
+            Some(i) => (i.bus_name.clone(), i.obj_path.clone()),
+            None    => return false,
+        }
+    };
+
+    let mut map = PropMap::new();
+    for iface in SNI_INTERFACES {
+        for prop in props {
+            let Ok(Ok(msg)) = tokio::time::timeout(T_PROBE, conn.call_method(
+                Some(bus.as_str()), path.as_str(), Some("org.freedesktop.DBus.Properties"), "Get", &(iface, prop),
+            )).await else { continue };
+            let Ok(val): Result<zbus::zvariant::OwnedValue, _> = msg.body().deserialize() else { continue };
+            if let Some(inner) = unwrap_variant(val) { map.insert(prop.to_string(), inner); }
+        }
+        if !map.is_empty() { break; }
+    }
+    if map.is_empty() { return false; }
+
+    items.update(|list| match list.iter_mut().find(|i| i.id == service) {
+        Some(icon) => { apply_props(icon, &map); true }
+        None       => false,
+    })
+}
+
+/// Patch the fields backed by the properties present in `map`.
+fn apply_props(icon: &mut TrayIcon, map: &PropMap) {
+    let text = |key: &str| prop_str(map, key).filter(|s| !s.is_empty());
+    let mut changed = false;
+
+    if map.contains_key("IconName")           { icon.icon_name            = text("IconName"); }
+    if map.contains_key("IconThemePath")      { icon.icon_theme_path      = text("IconThemePath"); }
+    if map.contains_key("AttentionIconName")  { icon.attention_icon_name  = text("AttentionIconName"); }
+    if map.contains_key("AttentionMovieName") { icon.attention_movie_name = text("AttentionMovieName"); }
+    if map.contains_key("OverlayIconName")    { icon.overlay_icon_name    = text("OverlayIconName"); }
+    if map.contains_key("IconPixmap") {
+        let (w, h, rgba) = unpack_pixmap(map.get("IconPixmap"));
+        changed |= rgba != icon.icon_rgba;
+        (icon.icon_w, icon.icon_h, icon.icon_rgba) = (w, h, rgba);
+    }
+    if map.contains_key("AttentionIconPixmap") {
+        let (w, h, rgba) = unpack_pixmap(map.get("AttentionIconPixmap"));
+        changed |= rgba != icon.attention_icon_rgba;
+        (icon.attention_icon_w, icon.attention_icon_h, icon.attention_icon_rgba) = (w, h, rgba);
+    }
+    if map.contains_key("OverlayIconPixmap") {
+        (icon.overlay_icon_w, icon.overlay_icon_h, icon.overlay_icon_rgba) = unpack_pixmap(map.get("OverlayIconPixmap"));
+    }
+    if map.contains_key("Status") { icon.status = parse_status(map); }
+    if map.contains_key("ToolTip") || map.contains_key("Title") {
+        (icon.tooltip_title, icon.tooltip_body) = tooltip_or_title(map, &icon.sni_id);
+    }
+    if changed { icon.icon_rev = icon.icon_rev.wrapping_add(1); }
+}
+
 // ============================================================================
 // Core icon fetching
 // ============================================================================
@@ -709,38 +1052,39 @@ async fn resolve_unique_name(conn: &Connection, name: &str) -> Option<String> {
 }
 
 /// Try GetAll with each known interface, then unfiltered, then no-args.
-/// Returns the first PropMap containing the mandatory "Id" key.
-async fn fetch_props(conn: &Connection, bus: &str, path: &str) -> PropMap {
+/// Returns the first PropMap containing the mandatory "Id" key, and which
+/// of those calls gave it.
+async fn fetch_props(conn: &Connection, bus: &str, path: &str) -> (PropMap, &'static str) {
     for iface in SNI_INTERFACES {
         if let Ok(m) = conn.call_method(
             Some(bus), path, Some("org.freedesktop.DBus.Properties"), "GetAll", &(iface,),
         ).await {
             let map: PropMap = m.body().deserialize().unwrap_or_default();
-            if map.contains_key("Id") { return map; }
+            if map.contains_key("Id") { return (map, *iface); }
         }
     }
     if let Ok(m) = conn.call_method(
         Some(bus), path, Some("org.freedesktop.DBus.Properties"), "GetAll", &("",),
     ).await {
         let map: PropMap = m.body().deserialize().unwrap_or_default();
-        if map.contains_key("Id") { return map; }
+        if map.contains_key("Id") { return (map, "GetAll with an empty interface"); }
     }
     if let Ok(m) = conn.call_method(
         Some(bus), path, Some("org.freedesktop.DBus.Properties"), "GetAll", &(),
     ).await {
         let map: PropMap = m.body().deserialize().unwrap_or_default();
-        if map.contains_key("Id") { return map; }
+        if map.contains_key("Id") { return (map, "GetAll without arguments"); }
     }
-    PropMap::new()
+    (PropMap::new(), "")
 }
 
-/// Last-resort: fetch each property individually.
-async fn fetch_props_individually(conn: &Connection, bus: &str, path: &str) -> PropMap {
+/// Last-resort: fetch each property individually; also returns the interface.
+async fn fetch_props_individually(conn: &Connection, bus: &str, path: &str) -> (PropMap, &'static str) {
     use zbus::zvariant::Value;
     const PROPS: &[&str] = &[
         "Id", "Category", "Status", "Title",
         "IconName", "IconThemePath", "IconPixmap",
-        "AttentionIconName", "AttentionIconThemePath", "AttentionIconPixmap",
+        "AttentionIconName", "AttentionIconThemePath", "AttentionIconPixmap", "AttentionMovieName",
         "OverlayIconName", "OverlayIconPixmap", "ToolTip", "ItemIsMenu", "Menu",
     ];
 
@@ -769,15 +1113,19 @@ async fn fetch_props_individually(conn: &Connection, bus: &str, path: &str) -> P
                 Some(bus), path, Some("org.freedesktop.DBus.Properties"), "Get", &(iface, prop),
             ).await else { continue };
             let Ok(val): Result<zbus::zvariant::OwnedValue, _> = msg.body().deserialize() else { continue };
-            let inner = match &*val {
-                Value::Value(v) => zbus::zvariant::OwnedValue::try_from(v.as_ref()).ok(),
-                _               => Some(val),
-            };
-            if let Some(inner) = inner { map.insert(prop.to_string(), inner); }
+            if let Some(inner) = unwrap_variant(val) { map.insert(prop.to_string(), inner); }
         }
-        return map;
+        return (map, *iface);
+    }
+    (PropMap::new(), "")
+}
+
+/// A `Properties.Get` reply arrives variant-wrapped; peel that off.
+fn unwrap_variant(val: zbus::zvariant::OwnedValue) -> Option<zbus::zvariant::OwnedValue> {
+    match &*val {
+        zbus::zvariant::Value::Value(v) => zbus::zvariant::OwnedValue::try_from(v.as_ref()).ok(),
+        _                               => Some(val),
     }
-    PropMap::new()
 }
 
 async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool {
@@ -789,18 +1137,22 @@ async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool
     };
     let bus = effective_bus.as_str();
 
+    let fail = |outcome: &str| { items.note_probe(service, outcome, None); false };
     match try_introspect(conn, bus, obj_path).await {
-        Err(())       => return false,
-        Ok(Some(xml)) if !xml.is_empty() && !xml_has_sni_interface(&xml) && !xml_has_properties_interface(&xml) => return false,
+        Err(())       => return fail("no such object, or no answer"),
+        Ok(Some(xml)) if !xml.is_empty() && !xml_has_sni_interface(&xml) && !xml_has_properties_interface(&xml) => {
+            return fail("object isn't a StatusNotifierItem");
+        }
         _             => {}
     }
 
-    let mut all = fetch_props(conn, bus, obj_path).await;
-    if all.is_empty() { all = fetch_props_individually(conn, bus, obj_path).await; }
-    if all.is_empty() { return false; }
+    let (mut all, mut interface) = fetch_props(conn, bus, obj_path).await;
+    let per_property = all.is_empty();
+    if per_property { (all, interface) = fetch_props_individually(conn, bus, obj_path).await; }
+    if all.is_empty() { return fail("properties unreadable"); }
 
     let id_str = match prop_str(&all, "Id").filter(|s| !s.is_empty()) {
-        Some(s) => s, None => return false,
+        Some(s) => s, None => return fail("no Id property"),
     };
 
     let category = match prop_str(&all, "Category").as_deref() {
@@ -809,15 +1161,8 @@ async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool
         Some("Hardware")       => TrayCategory::Hardware,
         _                      => TrayCategory::ApplicationStatus,
     };
-    let status = match prop_str(&all, "Status").as_deref() {
-        Some("Passive")        => TrayStatus::Passive,
-        Some("NeedsAttention") => TrayStatus::NeedsAttention,
-        _                      => TrayStatus::Active,
-    };
-    let (tooltip_title, tooltip_body) = parse_tooltip(&all).unwrap_or_else(|| {
-        let title = prop_str(&all, "Title").filter(|s| !s.is_empty()).unwrap_or(id_str.clone());
-        (title, String::new())
-    });
+    let status = parse_status(&all);
+    let (tooltip_title, tooltip_body) = tooltip_or_title(&all, &id_str);
 
     let (icon_w, icon_h, icon_rgba)                               = unpack_pixmap(all.get("IconPixmap"));
     let (attention_icon_w, attention_icon_h, attention_icon_rgba) = unpack_pixmap(all.get("AttentionIconPixmap"));
@@ -825,6 +1170,7 @@ async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool
 
     let new_icon = TrayIcon {
         id:       service.to_string(),
+        sni_id:   id_str.clone(),
         bus_name: bus.to_string(),
         obj_path: obj_path.to_string(),
         category,
@@ -833,6 +1179,7 @@ async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool
         icon_theme_path:      prop_str(&all, "IconThemePath").filter(|s| !s.is_empty()),
         attention_icon_rgba, attention_icon_w, attention_icon_h,
         attention_icon_name:  prop_str(&all, "AttentionIconName").filter(|s| !s.is_empty()),
+        attention_movie_name: prop_str(&all, "AttentionMovieName").filter(|s| !s.is_empty()),
         overlay_icon_rgba, overlay_icon_w, overlay_icon_h,
         overlay_icon_name:    prop_str(&all, "OverlayIconName").filter(|s| !s.is_empty()),
         status,
@@ -846,21 +1193,24 @@ async fn fetch_icon(conn: &Connection, service: &str, items: TrayItems) -> bool
         icon_rev:      0,
     };
 
-    let mut locked = items.lock().unwrap();
-    if let Some(existing) = locked.iter_mut().find(|i| i.id == new_icon.id) {
-        let changed = existing.icon_rgba != new_icon.icon_rgba
-            || existing.attention_icon_rgba != new_icon.attention_icon_rgba;
-        let new_rev = if changed { existing.icon_rev.wrapping_add(1) } else { existing.icon_rev };
-        let (menu_items, menu_revision, menu_loaded) =
-            (existing.menu_items.clone(), existing.menu_revision, existing.menu_loaded);
-        *existing = new_icon;
-        existing.icon_rev      = new_rev;
-        existing.menu_items    = menu_items;
-        existing.menu_revision = menu_revision;
-        existing.menu_loaded   = menu_loaded;
-    } else {
-        locked.push(new_icon);
-    }
+    items.update(|list| {
+        if let Some(existing) = list.iter_mut().find(|i| i.id == new_icon.id) {
+            let changed = existing.icon_rgba != new_icon.icon_rgba
+                || existing.attention_icon_rgba != new_icon.attention_icon_rgba;
+            let new_rev = if changed { existing.icon_rev.wrapping_add(1) } else { existing.icon_rev };
+            let (menu_items, menu_revision, menu_loaded) =
+                (std::mem::take(&mut existing.menu_items), existing.menu_revision, existing.menu_loaded);
+            *existing = new_icon;
+            existing.icon_rev      = new_rev;
+            existing.menu_items    = menu_items;
+            existing.menu_revision = menu_revision;
+            existing.menu_loaded   = menu_loaded;
+        } else {
+            list.push(new_icon);
+        }
+    });
+    let interface = if per_property { format!("{interface}, one Get per property") } else { interface.to_string() };
+    items.note_probe(service, "shown", Some(&interface));
     true
 }
 
@@ -887,18 +1237,17 @@ async fn fetch_menu_internal(
     };
 
     let menu_items = parse_menu_items(&root_node.2);
-    let mut locked = items.lock().unwrap();
-    if let Some(icon) = locked.iter_mut().find(|i| i.id == service_id) {
+    items.update(|list| if let Some(icon) = list.iter_mut().find(|i| i.id == service_id) {
         icon.menu_items    = menu_items;
         icon.menu_revision = revision;
         icon.menu_loaded   = true;
-    }
+    });
 }
 
 fn mark_menu_loaded(items: &TrayItems, service_id: &str) {
-    if let Some(icon) = items.lock().unwrap().iter_mut().find(|i| i.id == service_id) {
+    items.update(|list| if let Some(icon) = list.iter_mut().find(|i| i.id == service_id) {
         icon.menu_loaded = true;
-    }
+    });
 }
 
 fn parse_menu_items(children: &[zbus::zvariant::OwnedValue]) -> Vec<MenuItem> {
@@ -937,6 +1286,8 @@ fn parse_menu_items(children: &[zbus::zvariant::OwnedValue]) -> Vec<MenuItem> {
             visible,
             is_separator: prop("type").map(|t| t == "separator").unwrap_or(false),
             icon_name:    prop("icon-name").filter(|s| !s.is_empty()),
+            icon_data:    props.get("icon-data").and_then(|v| bytes_from_value(v)).unwrap_or_default(),
+            shortcut:     props.get("shortcut").and_then(|v| format_shortcut(v)),
             toggle_type:  match prop("toggle-type").as_deref() {
                 Some("checkmark") => ToggleType::Checkmark,
                 Some("radio")     => ToggleType::Radio,
@@ -949,6 +1300,37 @@ fn parse_menu_items(children: &[zbus::zvariant::OwnedValue]) -> Vec<MenuItem> {
     items
 }
 
+fn bytes_from_value(v: &zbus::zvariant::Value) -> Option<Vec<u8>> {
+    use zbus::zvariant::Value;
+    match v {
+        Value::Value(inner) => bytes_from_value(inner),
+        Value::Array(a)     => Some(a.inner().iter().filter_map(|b| match b { Value::U8(b) => Some(*b), _ => None }).collect()),
+        _                   => None,
+    }
+}
+
+/// `shortcut` (`aas`, e.g. `[["Control", "q"]]`) → "Ctrl+Q"; alternatives
+/// are comma-separated.
+fn format_shortcut(v: &zbus::zvariant::Value) -> Option<String> {
+    use zbus::zvariant::Value;
+    let outer = match v {
+        Value::Value(inner) => return format_shortcut(inner),
+        Value::Array(a)     => a,
+        _                   => return None,
+    };
+    let combos: Vec<String> = outer.inner().iter().filter_map(|combo| {
+        let Value::Array(keys) = combo else { return None };
+        let keys: Vec<String> = keys.inner().iter().filter_map(|key| match key {
+            Value::Str(k) if k.as_str() == "Control" => Some("Ctrl".to_string()),
+            Value::Str(k) if k.chars().count() == 1  => Some(k.to_uppercase()),
+            Value::Str(k)                            => Some(k.to_string()),
+            _                                        => None,
+        }).collect();
+        (!keys.is_empty()).then(|| keys.join("+"))
+    }).collect();
+    (!combos.is_empty()).then(|| combos.join(", "))
+}
+
 fn string_from_value(v: &zbus::zvariant::Value) -> Option<String> {
     use zbus::zvariant::Value;
     match v {
@@ -995,6 +1377,22 @@ fn prop_bool(map: &PropMap, key: &str) -> bool {
     }
 }
 
+fn parse_status(map: &PropMap) -> TrayStatus {
+    match prop_str(map, "Status").as_deref() {
+        Some("Passive")        => TrayStatus::Passive,
+        Some("NeedsAttention") => TrayStatus::NeedsAttention,
+        _                      => TrayStatus::Active,
+    }
+}
+
+/// The ToolTip, else the Title (or Id) with no body.
+fn tooltip_or_title(map: &PropMap, id: &str) -> (String, String) {
+    parse_tooltip(map).unwrap_or_else(|| {
+        let title = prop_str(map, "Title").filter(|s| !s.is_empty()).unwrap_or_else(|| id.to_string());
+        (title, String::new())
+    })
+}
+
 fn parse_tooltip(map: &PropMap) -> Option<(String, String)> {
     use zbus::zvariant::Value;
     let raw = map.get("ToolTip")?;
@@ -1003,11 +1401,29 @@ fn parse_tooltip(map: &PropMap) -> Option<(String, String)> {
     let fields = match deref { Value::Structure(s) => s.fields(), _ => return None };
     if fields.len() < 4 { return None; }
     let title = match &fields[2] { Value::Str(s) => s.to_string(), _ => String::new() };
-    let body  = match &fields[3] { Value::Str(s) => s.to_string(), _ => String::new() };
+    let body  = match &fields[3] { Value::Str(s) => strip_markup(s), _ => String::new() };
     if title.is_empty() && body.is_empty() { return None; }
     Some((title, body))
 }
 
+/// Tooltip bodies may use the spec's HTML subset: keep the text, turn
+/// `<br>`/`</p>` into line breaks and undo the common entities.
+fn strip_markup(s: &str) -> String {
+    let mut out  = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let Some(gt) = rest[lt..].find('>') else { rest = &rest[lt..]; break };
+        let tag = rest[lt + 1..lt + gt].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/p" || tag == "/div" || tag == "/li" { out.push('\n'); }
+        rest = &rest[lt + gt + 1..];
+    }
+    out.push_str(rest);
+    let out = out.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"")
+        .replace("&apos;", "'").replace("&#39;", "'").replace("&nbsp;", " ").replace("&amp;", "&");
+    out.trim().to_string()
+}
+
 fn parse_icon_pixmap(val: &zbus::zvariant::OwnedValue) -> Option<(u32, u32, Vec<u8>)> {
     use zbus::zvariant::Value;
     let arr = match &**val { Value::Array(a) => a, _ => return None };
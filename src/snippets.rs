@@ -0,0 +1,147 @@
+//! Text snippets: named pieces of text in `snippets.toml`, found with
+//! `snippet-prefix` (`;sig`) and copied or typed into the focused window.
+//!
+//! Design:
+//! - `~/.config/tusk-launcher/snippets.toml` is read with the same parser as
+//!   config.toml: each key is a snippet name (quoted when it has spaces) and
+//!   its string the text, `"""…"""` for several lines. It's written with a
+//!   commented example the first time the provider starts.
+//! - The file is read again when its mtime moves, checked on each query, so
+//!   edits show up without a restart.
+//! - Names starting with the query come first, then names containing it.
+//! - `snippet-insert = "copy"` puts the text on the clipboard;
+//!   `"type"` types it with `wtype` (Wayland) or `xdotool` (X11) once the
+//!   launcher is gone and focus is back on the previous window. The row's
+//!   action menu offers the other one.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+const SNIPPETS_FILE: &str = "tusk-launcher/snippets.toml";
+/// Time for the launcher to close and the previous window to get focus back.
+const TYPE_DELAY_MS: u32 = 300;
+
+const DEFAULT_SNIPPETS: &str = r#"# Snippets for the launcher: type the snippet prefix (";" by default) and
+# a name, e.g. ";sig". Enter copies the text or types it (snippet-insert
+# in config.toml). Names with spaces need quotes; """…""" spans lines.
+#
+# sig = """
+# Best regards,
+# Jane
+# """
+# shrug = '¯\_(ツ)_/¯'
+"#;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snippet {
+    pub name: String,
+    pub text: String,
+}
+
+pub struct Snippets {
+    path:     Option<PathBuf>,
+    modified: Option<SystemTime>,
+    items:    Vec<Snippet>,
+}
+
+impl Snippets {
+    /// Reads snippets.toml, writing the example file first when it's missing.
+    pub fn load() -> Self {
+        let path = crate::paths::place_config_file(SNIPPETS_FILE).ok();
+        if let Some(path) = path.as_ref().filter(|p| !p.exists()) {
+            let _ = fs::write(path, DEFAULT_SNIPPETS)
+                .map_err(|e| tracing::warn!(target: "search", "Failed to write {}: {e}", path.display()));
+        }
+        let mut snippets = Snippets { path, modified: None, items: Vec::new() };
+        snippets.reload();
+        snippets
+    }
+
+    /// Read the file again if it changed since the last read.
+    fn reload(&mut self) {
+        let Some(path) = &self.path else { return };
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == self.modified { return; }
+        self.modified = modified;
+        self.items = match fs::read_to_string(path) {
+            Ok(src) => parse(&src, &path.display().to_string()),
+            Err(_)  => Vec::new(),
+        };
+    }
+
+    /// Snippets matching `query` by name, best first.
+    pub fn search(&mut self, query: &str, max: usize) -> Vec<Snippet> {
+        self.reload();
+        matching(&self.items, query, max)
+    }
+}
+
+/// Snippets in name order; non-string values are reported and skipped.
+fn parse(src: &str, origin: &str) -> Vec<Snippet> {
+    let (values, errors) = crate::config::parse(src);
+    for e in errors { tracing::warn!(target: "search", "{origin}: {e}"); }
+    let mut items: Vec<Snippet> = values.into_iter()
+        .filter_map(|(name, value)| match value.scalar() {
+            Some(text) => Some(Snippet { name, text: text.to_string() }),
+            None       => { tracing::warn!(target: "search", "{origin}: snippet {name:?} isn't a string"); None }
+        })
+        .collect();
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+    items
+}
+
+fn matching(items: &[Snippet], query: &str, max: usize) -> Vec<Snippet> {
+    let needle = query.trim().to_lowercase();
+    let names: Vec<String> = items.iter().map(|s| s.name.to_lowercase()).collect();
+    let starts   = items.iter().zip(&names).filter(|(_, n)| n.starts_with(&needle));
+    let contains = items.iter().zip(&names).filter(|(_, n)| !n.starts_with(&needle) && n.contains(&needle));
+    starts.chain(contains).map(|(s, _)| s.clone()).take(max).collect()
+}
+
+/// Type `text` into whatever window has focus after a short pause.
+pub fn type_text(text: &str) -> bool {
+    let delay = TYPE_DELAY_MS.to_string();
+    let secs  = format!("{:.1}", f64::from(TYPE_DELAY_MS) / 1000.0);
+    let (program, args): (&str, Vec<&str>) = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wtype", vec!["-s", &delay, "--", text])
+    } else {
+        ("xdotool", vec!["sleep", &secs, "type", "--clearmodifiers", "--", text])
+    };
+    match Command::new(program).args(&args)
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(mut child) => {
+            // Reap it so daemon mode doesn't collect zombies.
+            std::thread::spawn(move || child.wait());
+            true
+        }
+        Err(e) => {
+            tracing::warn!(target: "search", "Failed to run {program} to type the snippet: {e}");
+            false
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets() {
+        let items = parse("sig = \"\"\"\nBest,\nMe\"\"\"\n\"my addr\" = '1 Main St'\nassig = \"x\"\nlist = [\"a\"]\n", "test");
+        assert_eq!(items.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["assig", "my addr", "sig"]);
+        assert_eq!(items[2].text, "Best,\nMe");
+
+        let found: Vec<String> = matching(&items, "SIG", 5).into_iter().map(|s| s.name).collect();
+        assert_eq!(found, ["sig", "assig"]);
+        assert_eq!(matching(&items, "", 2).len(), 2);
+        assert!(parse(DEFAULT_SNIPPETS, "default").is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Uptime, load, memory and CPU usage read straight from `/proc`.
+//!
+//! Design:
+//! - Everything is a small text file read on the GUI thread at the
+//!   `system-stats-interval-ms` rate; no thread, no D-Bus.
+//! - CPU % needs two samples of `/proc/stat`, so the first reading after
+//!   start has none and the section shows the rest without it.
+use std::fs;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SystemStats {
+    pub uptime:      Duration,
+    /// 1, 5 and 15 minute load averages.
+    pub load:        [f32; 3],
+    /// `MemTotal - MemAvailable`, in KiB.
+    pub mem_used:    u64,
+    pub mem_total:   u64,
+    /// Busy share of all CPUs since the previous sample.
+    pub cpu_percent: Option<f32>,
+}
+
+impl SystemStats {
+    /// "up 3d 4h · load 0.52 0.40 0.31"
+    pub fn summary(&self) -> String {
+        let [l1, l5, l15] = self.load;
+        format!("up {} · load {l1:.2} {l5:.2} {l15:.2}", format_uptime(self.uptime))
+    }
+
+    /// "CPU 12% · RAM 5.2/15.6 GiB"
+    pub fn usage(&self) -> String {
+        const GIB: f64 = 1024.0 * 1024.0;
+        let ram = format!("RAM {:.1}/{:.1} GiB", self.mem_used as f64 / GIB, self.mem_total as f64 / GIB);
+        match self.cpu_percent {
+            Some(cpu) => format!("CPU {cpu:.0}% · {ram}"),
+            None      => ram,
+        }
+    }
+}
+
+/// Keeps the previous `/proc/stat` totals for the CPU % delta.
+#[derive(Default)]
+pub struct StatsSampler {
+    prev_cpu: Option<CpuTimes>,
+}
+
+impl StatsSampler {
+    pub fn sample(&mut self) -> SystemStats {
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+        let (mem_used, mem_total) = parse_meminfo(&read("/proc/meminfo")).unwrap_or_default();
+
+        let cpu = read("/proc/stat").lines().next().and_then(parse_cpu_line);
+        let cpu_percent = match (self.prev_cpu, cpu) {
+            (Some(prev), Some(now)) => now.busy_percent_since(prev),
+            _                       => None,
+        };
+        if cpu.is_some() { self.prev_cpu = cpu; }
+
+        SystemStats {
+            uptime: parse_uptime(&read("/proc/uptime")).unwrap_or_default(),
+            load:   parse_loadavg(&read("/proc/loadavg")).unwrap_or_default(),
+            mem_used,
+            mem_total,
+            cpu_percent,
+        }
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CpuTimes {
+    idle:  u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    fn busy_percent_since(self, prev: CpuTimes) -> Option<f32> {
+        let total = self.total.checked_sub(prev.total).filter(|&t| t > 0)?;
+        let idle  = self.idle.saturating_sub(prev.idle).min(total);
+        Some((total - idle) as f32 * 100.0 / total as f32)
+    }
+}
+
+/// `/proc/uptime`: "350735.47 234388.90" — seconds up, then idle seconds.
+fn parse_uptime(s: &str) -> Option<Duration> {
+    let secs: f64 = s.split_whitespace().next()?.parse().ok()?;
+    Some(Duration::from_secs_f64(secs.max(0.0)))
+}
+
+/// `/proc/loadavg`: "0.52 0.40 0.31 1/467 12345".
+fn parse_loadavg(s: &str) -> Option<[f32; 3]> {
+    let mut it = s.split_whitespace().map(|v| v.parse::<f32>().ok());
+    Some([it.next()??, it.next()??, it.next()??])
+}
+
+/// `(used, total)` KiB from `/proc/meminfo`, counting reclaimable cache as free.
+fn parse_meminfo(s: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| -> Option<u64> {
+        s.lines()
+            .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))?
+            .split_whitespace().next()?.parse().ok()
+    };
+    let total     = field("MemTotal")?;
+    let available = field("MemAvailable").or_else(|| field("MemFree"))?;
+    Some((total.saturating_sub(available), total))
+}
+
+/// The aggregate "cpu  user nice system idle iowait irq softirq steal ..." line.
+fn parse_cpu_line(line: &str) -> Option<CpuTimes> {
+    let mut fields = line.split_whitespace();
+    if fields.next()? != "cpu" { return None; }
+    let values: Vec<u64> = fields.map(|v| v.parse().ok()).collect::<Option<_>>()?;
+    if values.len() < 4 { return None; }
+    // guest/guest_nice (fields 9-10) are already counted in user/nice.
+    let total = values.iter().take(8).sum();
+    let idle  = values[3] + values.get(4).copied().unwrap_or(0);
+    Some(CpuTimes { idle, total })
+}
+
+fn format_uptime(d: Duration) -> String {
+    let mins  = d.as_secs() / 60;
+    let (days, hours, mins) = (mins / 1440, mins / 60 % 24, mins % 60);
+    match (days, hours) {
+        (0, 0) => format!("{mins}m"),
+        (0, _) => format!("{hours}h {mins}m"),
+        _      => format!("{days}d {hours}h"),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        assert_eq!(parse_uptime("350735.47 234388.90\n"), Some(Duration::from_secs_f64(350735.47)));
+        assert_eq!(parse_loadavg("0.52 0.40 0.31 1/467 12345\n"), Some([0.52, 0.40, 0.31]));
+        let meminfo = "MemTotal:       16314300 kB\nMemFree:         1203400 kB\nMemAvailable:    9012300 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some((16314300 - 9012300, 16314300)));
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        let a = parse_cpu_line("cpu  100 0 100 700 100 0 0 0 0 0").unwrap();
+        let b = parse_cpu_line("cpu  150 0 150 780 120 0 0 0 0 0").unwrap();
+        assert_eq!(b.busy_percent_since(a), Some(50.0));
+        assert_eq!(parse_cpu_line("cpu0 1 2 3 4"), None);
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(59 * 60)), "59m");
+        assert_eq!(format_uptime(Duration::from_secs(5 * 3600 + 7 * 60)), "5h 7m");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 86400 + 4 * 3600 + 10)), "3d 4h");
+    }
+}
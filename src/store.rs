@@ -0,0 +1,321 @@
+//! Where the app cache (`AppCache`) is kept between runs.
+//!
+//! Design:
+//! - `Store` loads and saves the whole cache. The launcher still works on the
+//!   in-memory copy behind `APP_CACHE` and saves it after each change.
+//! - `TextStore` is the tab-separated `app_cache.txt` (formats V1–V11). It's
+//!   what minimal builds (`--no-default-features`) use.
+//! - With the `sqlite` feature (default), `SqliteStore` keeps the cache in
+//!   `app_cache.db`, one table per kind of data, plus a `launches` table
+//!   with every launch for usage history. The first time it runs, an
+//!   existing `app_cache.txt` is imported and renamed to
+//!   `app_cache.txt.migrated`.
+//! - The schema version is kept in `PRAGMA user_version`, so later changes
+//!   can migrate in place.
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use crate::app_launcher::{deserialize_cache, serialize_cache, AppCache};
+
+pub trait Store: Send + Sync {
+    fn load(&self) -> Result<AppCache, Box<dyn Error>>;
+    fn save(&self, cache: &AppCache) -> Result<(), Box<dyn Error>>;
+    /// Add a launch of `app` at `at` (Unix seconds) to the usage history.
+    /// The text format keeps only the last launch, in the app's entry.
+    fn record_launch(&self, _app: &str, _at: u64) -> Result<(), Box<dyn Error>> { Ok(()) }
+}
+
+pub static STORE: LazyLock<Box<dyn Store>> = LazyLock::new(open);
+
+fn cache_dir() -> PathBuf {
+    let path = crate::paths::config_home().join("tusk-launcher");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+#[cfg(feature = "sqlite")]
+fn open() -> Box<dyn Store> {
+    let dir = cache_dir();
+    match SqliteStore::open(&dir.join("app_cache.db"), &dir.join("app_cache.txt")) {
+        Ok(store) => Box::new(store),
+        Err(e) => {
+            tracing::warn!(target: "apps", "can't open app_cache.db ({e}); using app_cache.txt");
+            Box::new(TextStore { path: dir.join("app_cache.txt") })
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open() -> Box<dyn Store> {
+    Box::new(TextStore { path: cache_dir().join("app_cache.txt") })
+}
+
+// ============================================================================
+// Text file
+// ============================================================================
+
+pub struct TextStore {
+    path: PathBuf,
+}
+
+impl Store for TextStore {
+    fn load(&self) -> Result<AppCache, Box<dyn Error>> {
+        if !self.path.exists() { return Ok(AppCache::default()); }
+        deserialize_cache(&fs::read_to_string(&self.path)?)
+    }
+
+    fn save(&self, cache: &AppCache) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, serialize_cache(cache))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SQLite
+// ============================================================================
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::error::Error;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use rusqlite::{params, Connection, OptionalExtension};
+    use crate::app_launcher::{AppCache, AppEntry, TrayPref};
+    use super::{Store, TextStore};
+
+    const SCHEMA_VERSION: i64 = 2;
+
+    const SCHEMA: &str = "
+        CREATE TABLE apps (
+            position         INTEGER NOT NULL,
+            name             TEXT PRIMARY KEY,
+            launch_options   TEXT,
+            icon_path        TEXT,
+            exec_command     TEXT,
+            terminal_command TEXT,
+            last_used        INTEGER,
+            custom_name      TEXT,
+            custom_icon      TEXT
+        );
+        CREATE TABLE run_history (position INTEGER NOT NULL, command TEXT NOT NULL);
+        CREATE TABLE tray_prefs  (position INTEGER NOT NULL, id TEXT PRIMARY KEY, pref TEXT NOT NULL);
+        CREATE TABLE hidden_apps (name TEXT PRIMARY KEY);
+        CREATE TABLE settings    (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+        CREATE TABLE launches    (app TEXT NOT NULL, at INTEGER NOT NULL);
+        CREATE INDEX launches_app ON launches (app, at);
+    ";
+
+    /// Steps from each older schema to the next, applied in order.
+    const MIGRATIONS: &[(i64, &str)] = &[
+        (1, "CREATE TABLE pinned_apps (position INTEGER NOT NULL, name TEXT PRIMARY KEY);"),
+    ];
+
+    pub struct SqliteStore {
+        /// `Connection` isn't `Sync`; saves are already serialized by `APP_CACHE`.
+        conn: Mutex<Connection>,
+    }
+
+    impl SqliteStore {
+        /// Open (or create) the database at `db`, importing the text cache at
+        /// `legacy` when the database is new.
+        pub fn open(db: &Path, legacy: &Path) -> Result<Self, Box<dyn Error>> {
+            let (store, created) = SqliteStore::with_connection(Connection::open(db)?)?;
+            if created && legacy.exists() {
+                let cache = TextStore { path: legacy.to_path_buf() }.load()?;
+                store.save(&cache)?;
+                std::fs::rename(legacy, legacy.with_extension("txt.migrated"))?;
+            }
+            Ok(store)
+        }
+
+        /// The store, and whether its tables were just created.
+        fn with_connection(conn: Connection) -> Result<(Self, bool), Box<dyn Error>> {
+            let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+            if version > SCHEMA_VERSION {
+                return Err(format!("app_cache.db has schema {version}, newer than this build").into());
+            }
+            if version == 0 {
+                conn.execute_batch(SCHEMA)?;
+                for (_, step) in MIGRATIONS { conn.execute_batch(step)?; }
+                conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            } else if version < SCHEMA_VERSION {
+                for (_, step) in MIGRATIONS.iter().filter(|(from, _)| *from >= version) {
+                    conn.execute_batch(step)?;
+                }
+                conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            Ok((SqliteStore { conn: Mutex::new(conn) }, version == 0))
+        }
+
+        fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, Box<dyn Error>> {
+            self.conn.lock().map_err(|e| format!("Lock error: {e:?}").into())
+        }
+    }
+
+    impl Store for SqliteStore {
+        fn load(&self) -> Result<AppCache, Box<dyn Error>> {
+            let conn = self.lock()?;
+            let text = |s: Option<String>| s.filter(|s| !s.is_empty());
+
+            let apps = conn.prepare(
+                "SELECT name, launch_options, icon_path, exec_command, terminal_command,
+                        last_used, custom_name, custom_icon
+                 FROM apps ORDER BY position",
+            )?.query_map([], |row| Ok((row.get::<_, String>(0)?, AppEntry {
+                launch_options:   text(row.get(1)?).and_then(|s| s.parse().ok()),
+                icon_path:        text(row.get(2)?),
+                exec_command:     text(row.get(3)?),
+                terminal_command: text(row.get(4)?),
+                last_used:        row.get::<_, Option<i64>>(5)?.map(|t| t as u64),
+                custom_name:      text(row.get(6)?),
+                custom_icon:      text(row.get(7)?),
+            })))?.collect::<Result<_, _>>()?;
+
+            let run_history = conn.prepare("SELECT command FROM run_history ORDER BY position")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+
+            let tray_prefs = conn.prepare("SELECT id, pref FROM tray_prefs ORDER BY position")?
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .filter_map(|row| {
+                    let (id, pref) = match row { Ok(r) => r, Err(e) => return Some(Err(e)) };
+                    let pref = match pref.as_str() {
+                        "pinned" => TrayPref::Pinned,
+                        "hidden" => TrayPref::Hidden,
+                        _        => return None,
+                    };
+                    Some(Ok((id, pref)))
+                })
+                .collect::<Result<_, _>>()?;
+
+            let hidden_apps = conn.prepare("SELECT name FROM hidden_apps ORDER BY rowid")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+
+            let idle_inhibit = conn.query_row(
+                "SELECT value FROM settings WHERE key = 'idle_inhibit'", [], |row| row.get::<_, String>(0),
+            ).optional()?.is_some_and(|v| v == "on");
+
+            let pinned_apps = conn.prepare("SELECT name FROM pinned_apps ORDER BY position")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+
+            let do_not_disturb = conn.query_row(
+                "SELECT value FROM settings WHERE key = 'do_not_disturb'", [], |row| row.get::<_, String>(0),
+            ).optional()?.is_some_and(|v| v == "on");
+
+            Ok(AppCache { apps, run_history, tray_prefs, idle_inhibit, hidden_apps, pinned_apps, do_not_disturb })
+        }
+
+        fn save(&self, cache: &AppCache) -> Result<(), Box<dyn Error>> {
+            let mut conn = self.lock()?;
+            let tx = conn.transaction()?;
+            tx.execute_batch("DELETE FROM apps; DELETE FROM run_history; DELETE FROM tray_prefs;
+                              DELETE FROM hidden_apps; DELETE FROM pinned_apps; DELETE FROM settings;")?;
+            {
+                let mut insert = tx.prepare(
+                    "INSERT OR REPLACE INTO apps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )?;
+                for (position, (name, entry)) in cache.apps.iter().enumerate() {
+                    insert.execute(params![
+                        position as i64,
+                        name,
+                        entry.launch_options.as_ref().map(|o| o.to_string()),
+                        entry.icon_path,
+                        entry.exec_command,
+                        entry.terminal_command,
+                        entry.last_used.map(|t| t as i64),
+                        entry.custom_name,
+                        entry.custom_icon,
+                    ])?;
+                }
+                let mut insert = tx.prepare("INSERT INTO run_history VALUES (?1, ?2)")?;
+                for (position, command) in cache.run_history.iter().enumerate() {
+                    insert.execute(params![position as i64, command])?;
+                }
+                let mut insert = tx.prepare("INSERT OR REPLACE INTO tray_prefs VALUES (?1, ?2, ?3)")?;
+                for (position, (id, pref)) in cache.tray_prefs.iter().enumerate() {
+                    let pref = match pref { TrayPref::Pinned => "pinned", TrayPref::Hidden => "hidden" };
+                    insert.execute(params![position as i64, id, pref])?;
+                }
+                let mut insert = tx.prepare("INSERT OR IGNORE INTO hidden_apps VALUES (?1)")?;
+                for name in &cache.hidden_apps {
+                    insert.execute(params![name])?;
+                }
+                let mut insert = tx.prepare("INSERT OR IGNORE INTO pinned_apps VALUES (?1, ?2)")?;
+                for (position, name) in cache.pinned_apps.iter().enumerate() {
+                    insert.execute(params![position as i64, name])?;
+                }
+                if cache.idle_inhibit {
+                    tx.execute("INSERT INTO settings VALUES ('idle_inhibit', 'on')", [])?;
+                }
+                if cache.do_not_disturb {
+                    tx.execute("INSERT INTO settings VALUES ('do_not_disturb', 'on')", [])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        }
+
+        fn record_launch(&self, app: &str, at: u64) -> Result<(), Box<dyn Error>> {
+            self.lock()?.execute("INSERT INTO launches VALUES (?1, ?2)", params![app, at as i64])?;
+            Ok(())
+        }
+    }
+
+    // ========================================================================
+    // Tests
+    // ========================================================================
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_sqlite_roundtrip() {
+            let (store, created) = SqliteStore::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+            assert!(created);
+            let cache = AppCache {
+                apps:        vec![
+                    ("Firefox".into(), AppEntry { exec_command: Some("firefox".into()), last_used: Some(7), ..Default::default() }),
+                    ("Vim".into(),     AppEntry { custom_name: Some("Editor".into()), ..Default::default() }),
+                ],
+                run_history:  vec!["make".into(), "ls".into()],
+                tray_prefs:   vec![("nm-applet".into(), TrayPref::Hidden)],
+                idle_inhibit: true,
+                hidden_apps:  vec!["Avahi".into()],
+                pinned_apps:  vec!["Vim".into(), "Firefox".into()],
+                do_not_disturb: true,
+            };
+            store.save(&cache).unwrap();
+            store.record_launch("Firefox", 7).unwrap();
+            let loaded = store.load().unwrap();
+            assert_eq!(loaded.apps.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), ["Firefox", "Vim"]);
+            assert_eq!(loaded.apps[0].1.last_used, Some(7));
+            assert_eq!(loaded.apps[1].1.custom_name.as_deref(), Some("Editor"));
+            assert_eq!(loaded.run_history, cache.run_history);
+            assert_eq!(loaded.tray_prefs, cache.tray_prefs);
+            assert!(loaded.idle_inhibit);
+            assert!(loaded.do_not_disturb);
+            assert_eq!(loaded.hidden_apps, cache.hidden_apps);
+            assert_eq!(loaded.pinned_apps, cache.pinned_apps);
+        }
+
+        #[test]
+        fn test_sqlite_migrates_v1() {
+            let conn = Connection::open_in_memory().unwrap();
+            conn.execute_batch(SCHEMA).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            let (store, created) = SqliteStore::with_connection(conn).unwrap();
+            assert!(!created);
+            store.save(&AppCache { pinned_apps: vec!["Vim".into()], ..Default::default() }).unwrap();
+            assert_eq!(store.load().unwrap().pinned_apps, ["Vim"]);
+        }
+    }
+}
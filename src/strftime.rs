@@ -0,0 +1,294 @@
+//! strftime-style formatting for the clock's `time-format`.
+//!
+//! Design:
+//! - Works on `LocalTime`, so the main clock and zoned extra clocks share it;
+//!   weekday, day of year and ISO week are derived from the date.
+//! - Day/month names, AM/PM and the `%c`/`%x`/`%X` layouts come from the
+//!   `LC_TIME` locale via `nl_langinfo`, read once; the C locale (English,
+//!   ISO-ish layouts) is what's left if the environment doesn't name one.
+//! - GNU padding flags are honoured: `%-d` (no padding), `%_d` (spaces),
+//!   `%0e` (zeros) and `%^a` (upper case).
+//! - Unknown conversions are written back unchanged, like glibc does.
+use std::sync::OnceLock;
+
+use crate::gui::LocalTime;
+
+// ============================================================================
+// Locale names
+// ============================================================================
+
+struct Names {
+    days:     [String; 7],
+    ab_days:  [String; 7],
+    months:   [String; 12],
+    ab_month: [String; 12],
+    am_pm:    [String; 2],
+    /// `%c`, `%x`, `%X`, `%r`.
+    date_time: String,
+    date:      String,
+    time:      String,
+    time_ampm: String,
+}
+
+fn names() -> &'static Names {
+    static NAMES: OnceLock<Names> = OnceLock::new();
+    NAMES.get_or_init(load_names)
+}
+
+#[cfg(target_os = "linux")]
+fn load_names() -> Names {
+    use libc::*;
+    // LC_TIME only: the rest of the process keeps the C locale's number
+    // formatting, which the theme parser relies on.
+    unsafe { setlocale(LC_TIME, c"".as_ptr()) };
+    let get = |item: nl_item, fallback: &str| -> String {
+        let p = unsafe { nl_langinfo(item) };
+        if p.is_null() { return fallback.to_string(); }
+        match unsafe { std::ffi::CStr::from_ptr(p) }.to_str() {
+            Ok(s) if !s.is_empty() => s.to_string(),
+            _                      => fallback.to_string(),
+        }
+    };
+    let c = c_names();
+    let days     = [DAY_1, DAY_2, DAY_3, DAY_4, DAY_5, DAY_6, DAY_7];
+    let ab_days  = [ABDAY_1, ABDAY_2, ABDAY_3, ABDAY_4, ABDAY_5, ABDAY_6, ABDAY_7];
+    let months   = [MON_1, MON_2, MON_3, MON_4, MON_5, MON_6, MON_7, MON_8, MON_9, MON_10, MON_11, MON_12];
+    let ab_month = [ABMON_1, ABMON_2, ABMON_3, ABMON_4, ABMON_5, ABMON_6, ABMON_7, ABMON_8, ABMON_9, ABMON_10, ABMON_11, ABMON_12];
+    Names {
+        days:      std::array::from_fn(|i| get(days[i], &c.days[i])),
+        ab_days:   std::array::from_fn(|i| get(ab_days[i], &c.ab_days[i])),
+        months:    std::array::from_fn(|i| get(months[i], &c.months[i])),
+        ab_month:  std::array::from_fn(|i| get(ab_month[i], &c.ab_month[i])),
+        // Many locales have no AM/PM; keep 12-hour formats readable anyway.
+        am_pm:     [get(AM_STR, &c.am_pm[0]), get(PM_STR, &c.am_pm[1])],
+        date_time: get(D_T_FMT, &c.date_time),
+        date:      get(D_FMT, &c.date),
+        time:      get(T_FMT, &c.time),
+        time_ampm: get(T_FMT_AMPM, &c.time_ampm),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn load_names() -> Names {
+    c_names()
+}
+
+/// The POSIX ("C") locale.
+fn c_names() -> Names {
+    let owned = |s: &str| s.to_string();
+    Names {
+        days:      ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"].map(owned),
+        ab_days:   ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"].map(owned),
+        months:    ["January", "February", "March", "April", "May", "June", "July",
+                    "August", "September", "October", "November", "December"].map(owned),
+        ab_month:  ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"].map(owned),
+        am_pm:     ["AM", "PM"].map(owned),
+        date_time: owned("%a %b %e %H:%M:%S %Y"),
+        date:      owned("%m/%d/%y"),
+        time:      owned("%H:%M:%S"),
+        time_ampm: owned("%I:%M:%S %p"),
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Whether `fmt` shows any part of the date (used to tell a full
+/// `time-format` from an old time-only one).
+pub fn has_date(fmt: &str) -> bool {
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' { continue; }
+        let conv = chars.by_ref().find(|c| !matches!(c, '-' | '_' | '0' | '^'));
+        if matches!(conv, Some('a' | 'A' | 'b' | 'B' | 'c' | 'C' | 'd' | 'D' | 'e' | 'F' | 'g' | 'G' | 'h'
+                             | 'j' | 'm' | 'u' | 'U' | 'V' | 'w' | 'W' | 'x' | 'y' | 'Y')) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Format `t` per `fmt` in the user's `LC_TIME` locale.
+pub fn format(t: &LocalTime, fmt: &str) -> String {
+    format_with(t, fmt, names(), 0)
+}
+
+fn format_with(t: &LocalTime, fmt: &str, names: &Names, depth: u8) -> String {
+    let d = Derived::new(t);
+    let mut out = String::with_capacity(fmt.len() + 16);
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' { out.push(c); continue; }
+
+        let mut pad   = None;
+        let mut upper = false;
+        while let Some(&f) = chars.peek() {
+            match f {
+                '-' => pad = Some(Pad::None),
+                '_' => pad = Some(Pad::Space),
+                '0' => pad = Some(Pad::Zero),
+                '^' => upper = true,
+                _   => break,
+            }
+            chars.next();
+        }
+        let Some(conv) = chars.next() else { out.push('%'); break };
+
+        let num = |n: i64, width: usize, default: Pad| pad.unwrap_or(default).apply(n, width);
+        // Locale layouts may not expand into themselves; one level is plenty.
+        let nested = |f: &str| if depth == 0 { format_with(t, f, names, 1) } else { String::new() };
+        let piece = match conv {
+            'a'       => names.ab_days[d.weekday as usize].clone(),
+            'A'       => names.days[d.weekday as usize].clone(),
+            'b' | 'h' => names.ab_month[t.month as usize - 1].clone(),
+            'B'       => names.months[t.month as usize - 1].clone(),
+            'c'       => nested(&names.date_time),
+            'C'       => num(t.year.div_euclid(100) as i64, 2, Pad::Zero),
+            'd'       => num(t.day as i64, 2, Pad::Zero),
+            'D'       => format!("{:02}/{:02}/{:02}", t.month, t.day, t.year.rem_euclid(100)),
+            'e'       => num(t.day as i64, 2, Pad::Space),
+            'F'       => format!("{}-{:02}-{:02}", t.year, t.month, t.day),
+            'g'       => num(d.iso_year.rem_euclid(100), 2, Pad::Zero),
+            'G'       => d.iso_year.to_string(),
+            'H'       => num(t.hour as i64, 2, Pad::Zero),
+            'I'       => num(hour12(t.hour), 2, Pad::Zero),
+            'j'       => num(d.yday as i64 + 1, 3, Pad::Zero),
+            'k'       => num(t.hour as i64, 2, Pad::Space),
+            'l'       => num(hour12(t.hour), 2, Pad::Space),
+            'm'       => num(t.month as i64, 2, Pad::Zero),
+            'M'       => num(t.min as i64, 2, Pad::Zero),
+            'n'       => "\n".to_string(),
+            'p'       => names.am_pm[(t.hour >= 12) as usize].clone(),
+            'P'       => names.am_pm[(t.hour >= 12) as usize].to_lowercase(),
+            'r'       => nested(&names.time_ampm),
+            'R'       => format!("{:02}:{:02}", t.hour, t.min),
+            'S'       => num(t.sec as i64, 2, Pad::Zero),
+            't'       => "\t".to_string(),
+            'T'       => format!("{:02}:{:02}:{:02}", t.hour, t.min, t.sec),
+            'u'       => (if d.weekday == 0 { 7 } else { d.weekday }).to_string(),
+            'U'       => num((d.yday as i64 + 7 - d.weekday as i64) / 7, 2, Pad::Zero),
+            'V'       => num(d.iso_week as i64, 2, Pad::Zero),
+            'w'       => d.weekday.to_string(),
+            'W'       => num((d.yday as i64 + 7 - (d.weekday as i64 + 6) % 7) / 7, 2, Pad::Zero),
+            'x'       => nested(&names.date),
+            'X'       => nested(&names.time),
+            'y'       => num(t.year.rem_euclid(100) as i64, 2, Pad::Zero),
+            'Y'       => t.year.to_string(),
+            'z'       => format_offset(t.offset),
+            // Zone abbreviations aren't kept; UTC is the one unambiguous name.
+            'Z'       => if t.offset == 0 { "UTC".to_string() } else { format_offset(t.offset) },
+            '%'       => "%".to_string(),
+            other     => format!("%{other}"),
+        };
+        if upper { out.push_str(&piece.to_uppercase()) } else { out.push_str(&piece) }
+    }
+    out
+}
+
+#[derive(Clone, Copy)]
+enum Pad { None, Space, Zero }
+
+impl Pad {
+    fn apply(self, n: i64, width: usize) -> String {
+        match self {
+            Pad::None  => n.to_string(),
+            Pad::Space => format!("{n:>width$}"),
+            Pad::Zero  => format!("{n:0width$}"),
+        }
+    }
+}
+
+fn hour12(hour: u8) -> i64 {
+    match hour % 12 { 0 => 12, h => h as i64 }
+}
+
+/// `+0530` style.
+fn format_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let mins = offset.unsigned_abs() / 60;
+    format!("{sign}{:02}{:02}", mins / 60, mins % 60)
+}
+
+/// Calendar fields `LocalTime` doesn't carry.
+struct Derived {
+    /// 0 = Sunday.
+    weekday:  u8,
+    /// 0-based day of the year.
+    yday:     u16,
+    iso_year: i64,
+    iso_week: u8,
+}
+
+impl Derived {
+    fn new(t: &LocalTime) -> Self {
+        let year    = t.year as i64;
+        let days    = crate::tz::days_from_civil(year, t.month as i64, t.day as i64);
+        // 1970-01-01 was a Thursday.
+        let weekday = (days + 4).rem_euclid(7) as u8;
+        let yday    = (days - crate::tz::days_from_civil(year, 1, 1)) as u16;
+
+        // ISO 8601: weeks start Monday; week 1 holds the year's first Thursday.
+        let iso_wd = (weekday as i64 + 6) % 7 + 1;
+        let week   = (yday as i64 + 1 - iso_wd + 10) / 7;
+        let (iso_year, iso_week) = if week < 1 {
+            (year - 1, iso_weeks_in(year - 1))
+        } else if week > iso_weeks_in(year) {
+            (year + 1, 1)
+        } else {
+            (year, week)
+        };
+        Derived { weekday, yday, iso_year, iso_week: iso_week as u8 }
+    }
+}
+
+/// 52 or 53: long years start on a Thursday, or on a Wednesday in leap years.
+fn iso_weeks_in(year: i64) -> i64 {
+    let jan1 = (crate::tz::days_from_civil(year, 1, 1) + 4).rem_euclid(7);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    if jan1 == 4 || (leap && jan1 == 3) { 53 } else { 52 }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u8, day: u8, hour: u8, min: u8) -> LocalTime {
+        LocalTime { year, month, day, hour, min, sec: 5, offset: 19_800 }
+    }
+
+    fn fmt(t: &LocalTime, f: &str) -> String {
+        format_with(t, f, &c_names(), 0)
+    }
+
+    #[test]
+    fn test_conversions() {
+        let t = at(2026, 10, 6, 14, 7);
+        assert_eq!(fmt(&t, "%a %A %b %B %d %e %j"), "Tue Tuesday Oct October 06  6 279");
+        assert_eq!(fmt(&t, "%H:%M:%S %I %l %p %P"), "14:07:05 02  2 PM pm");
+        assert_eq!(fmt(&t, "%F %D %T %R %y %C %z"), "2026-10-06 10/06/26 14:07:05 14:07 26 20 +0530");
+        assert_eq!(fmt(&t, "%c"), "Tue Oct  6 14:07:05 2026");
+        assert_eq!(fmt(&t, "%-d/%-m %_H %^a %% %Q"), "6/10 14 TUE % %Q");
+    }
+
+    #[test]
+    fn test_weeks() {
+        // 2021-01-03 (Sunday) belongs to ISO week 53 of 2020.
+        let t = at(2021, 1, 3, 0, 0);
+        assert_eq!(fmt(&t, "%G-W%V-%u %U %W %w"), "2020-W53-7 01 00 0");
+        // 2024-12-30 (Monday) is week 1 of 2025.
+        assert_eq!(fmt(&at(2024, 12, 30, 0, 0), "%G-W%V"), "2025-W01");
+    }
+
+    #[test]
+    fn test_has_date() {
+        assert!(!has_date("%I:%M %p"));
+        assert!(has_date("%H:%M %a %-d %b"));
+        assert!(!has_date("100%% %H"));
+    }
+}
@@ -1,8 +1,9 @@
+use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::process::Command;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
 use std::time::Duration;
 use crate::gui::{Config, format_datetime, LocalTime};
@@ -39,38 +40,136 @@ fn try_commands(commands: &[String]) -> bool {
 // Clock
 // ============================================================================
 
+/// The main clock, plus a second line of `extra-clocks` when any are set.
 pub fn get_current_time(config: &Config) -> String {
-    format_datetime(&LocalTime::now(), config)
+    let now = unix_now();
+    let local = match config.timezone.as_str() {
+        "" => LocalTime::now(),
+        tz => crate::tz::zone(tz).local_time(now),
+    };
+    let main = format_datetime(&local, config);
+    let extra: Vec<String> = config.extra_clocks.iter()
+        .filter(|c| !c.is_empty())
+        .map(|c| {
+            let (label, zone) = parse_clock(c);
+            let t = crate::tz::zone(zone).local_time(now);
+            format!("{label} {}", crate::strftime::format(&t, &config.extra_clock_format))
+        })
+        .collect();
+    if extra.is_empty() { main } else { format!("{main}\n{}", extra.join(" · ")) }
+}
+
+/// "Tokyo=Asia/Tokyo" → ("Tokyo", "Asia/Tokyo"); a bare "America/New_York"
+/// is labelled with its city, "New York".
+fn parse_clock(entry: &str) -> (String, &str) {
+    match entry.split_once('=') {
+        Some((label, zone)) => (label.trim().to_string(), zone.trim()),
+        None => (entry.rsplit('/').next().unwrap_or(entry).replace('_', " "), entry),
+    }
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
 }
 
 // ============================================================================
 // Audio
 // ============================================================================
 
+/// One output device, as offered by the sink switcher.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SinkInfo {
+    /// PipeWire object id (the same id `wpctl` takes).
+    pub id:          u32,
+    /// Human-readable name (`node.description`).
+    pub description: String,
+    pub is_default:  bool,
+}
+
+/// What the audio backend last reported; shared with the PipeWire thread or
+/// the `wpctl` poller.
+#[derive(Default)]
+pub struct AudioState {
+    pub volume:        f32,
+    pub muted:         bool,
+    pub sinks:         Vec<SinkInfo>,
+    /// Whether there is a default source (microphone) at all.
+    pub has_source:    bool,
+    pub source_volume: f32,
+    pub source_muted:  bool,
+}
+
 pub struct AudioController {
-    volume: Arc<Mutex<f32>>,
+    state: Arc<Mutex<AudioState>>,
     max_volume: f32,
     enabled: bool,
+    /// Also track the default source; see `enable-mic-control`.
+    mic_enabled: bool,
+    /// Last volume set from the slider per sink description, restored when
+    /// switching back to that sink.
+    remembered: Mutex<HashMap<String, f32>>,
+    /// Set by the GUI; run when the volume changes outside the slider.
+    waker: Arc<OnceLock<Box<dyn Fn() + Send + Sync>>>,
+    /// Push-based PipeWire connection; `None` falls back to polling `wpctl`.
+    #[cfg(feature = "pipewire")]
+    native: Option<crate::audio::PipewireVolume>,
 }
 
 impl AudioController {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
-        let volume = if config.enable_audio_control {
-            Self::get_current_volume()?
+        let state = Arc::new(Mutex::new(AudioState::default()));
+        let waker: Arc<OnceLock<Box<dyn Fn() + Send + Sync>>> = Arc::new(OnceLock::new());
+
+        #[cfg(feature = "pipewire")]
+        let native = if config.enable_audio_control {
+            let waker = Arc::clone(&waker);
+            let on_change: crate::audio::Waker = Arc::new(move || if let Some(wake) = waker.get() { wake() });
+            crate::audio::PipewireVolume::connect(Arc::clone(&state), on_change)
+                .map_err(|e| tracing::info!(target: "audio", "PipeWire unavailable, polling wpctl instead: {e}"))
+                .ok()
         } else {
-            0.0
+            None
         };
+        #[cfg(feature = "pipewire")]
+        let polled = native.is_none();
+        #[cfg(not(feature = "pipewire"))]
+        let polled = true;
+
+        if config.enable_audio_control && polled {
+            let mut state = state.lock().unwrap();
+            (state.volume, state.muted) = Self::wpctl_volume("@DEFAULT_AUDIO_SINK@")?;
+            state.sinks  = Self::get_sinks().unwrap_or_default();
+            if config.enable_mic_control { Self::read_source(&mut state); }
+        }
 
         Ok(AudioController {
-            volume: Arc::new(Mutex::new(volume)),
+            state,
             max_volume: config.max_volume,
             enabled: config.enable_audio_control,
+            mic_enabled: config.enable_audio_control && config.enable_mic_control,
+            remembered: Mutex::new(HashMap::new()),
+            waker,
+            #[cfg(feature = "pipewire")]
+            native,
         })
     }
 
-    fn get_current_volume() -> Result<f32, Box<dyn Error>> {
+    /// Run `wake` whenever the volume or sink list changes outside our own slider.
+    pub fn on_change(&self, wake: impl Fn() + Send + Sync + 'static) {
+        let _ = self.waker.set(Box::new(wake));
+    }
+
+    #[cfg(feature = "pipewire")]
+    fn is_native(&self) -> bool { self.native.is_some() }
+    #[cfg(not(feature = "pipewire"))]
+    fn is_native(&self) -> bool { false }
+
+    /// `wpctl get-volume` prints `Volume: 0.40` plus ` [MUTED]` when muted.
+    fn wpctl_volume(target: &str) -> Result<(f32, bool), Box<dyn Error>> {
         let output = Command::new("wpctl")
-            .args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
+            .args(["get-volume", target])
             .output()?;
 
         let volume_str = String::from_utf8(output.stdout)?;
@@ -80,7 +179,24 @@ impl AudioController {
             .and_then(|v| f32::from_str(v).ok())
             .ok_or("Failed to parse volume")?;
 
-        Ok(volume)
+        Ok((volume, volume_str.contains("[MUTED]")))
+    }
+
+    /// Default-source fields via `wpctl`; `has_source` is false when it has none.
+    fn read_source(state: &mut AudioState) {
+        match Self::wpctl_volume("@DEFAULT_AUDIO_SOURCE@") {
+            Ok((volume, muted)) => {
+                state.has_source    = true;
+                state.source_volume = volume;
+                state.source_muted  = muted;
+            }
+            Err(_) => state.has_source = false,
+        }
+    }
+
+    fn get_sinks() -> Result<Vec<SinkInfo>, Box<dyn Error>> {
+        let output = Command::new("wpctl").arg("status").output()?;
+        Ok(parse_wpctl_sinks(&String::from_utf8(output.stdout)?))
     }
 
     pub fn set_volume(&self, new_volume: f32) -> Result<(), Box<dyn Error>> {
@@ -89,37 +205,192 @@ impl AudioController {
         }
 
         let clamped = new_volume.clamp(0.0, self.max_volume);
+        if let Some(sink) = self.default_sink() {
+            self.remembered.lock().unwrap().insert(sink.description, clamped);
+        }
+
+        #[cfg(feature = "pipewire")]
+        if let Some(native) = &self.native {
+            native.set_volume(clamped);
+            self.state.lock().unwrap().volume = clamped;
+            return Ok(());
+        }
 
         Command::new("wpctl")
             .args(["set-volume", "@DEFAULT_AUDIO_SINK@", &format!("{:.2}", clamped)])
             .output()?;
 
-        *self.volume.lock().unwrap() = clamped;
+        self.state.lock().unwrap().volume = clamped;
+        Ok(())
+    }
+
+    /// Output devices, in PipeWire id order.
+    pub fn sinks(&self) -> Vec<SinkInfo> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        self.state.lock().unwrap().sinks.clone()
+    }
+
+    fn default_sink(&self) -> Option<SinkInfo> {
+        self.state.lock().unwrap().sinks.iter().find(|s| s.is_default).cloned()
+    }
+
+    /// Make `id` the default sink, restoring the volume last set on it here.
+    pub fn set_default_sink(&self, id: u32) -> Result<(), Box<dyn Error>> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let remembered = {
+            let state = self.state.lock().unwrap();
+            state.sinks.iter().find(|s| s.id == id)
+                .and_then(|s| self.remembered.lock().unwrap().get(&s.description).copied())
+        };
+
+        #[cfg(feature = "pipewire")]
+        if let Some(native) = &self.native {
+            native.set_default_sink(id);
+            if let Some(v) = remembered { native.set_sink_volume(id, v); }
+            return Ok(());
+        }
+
+        Command::new("wpctl").args(["set-default", &id.to_string()]).output()?;
+        if let Some(v) = remembered {
+            Command::new("wpctl").args(["set-volume", &id.to_string(), &format!("{:.2}", v)]).output()?;
+        }
+        let mut state = self.state.lock().unwrap();
+        for sink in &mut state.sinks { sink.is_default = sink.id == id; }
+        if let Some(v) = remembered { state.volume = v; }
+        Ok(())
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.enabled && self.state.lock().unwrap().muted
+    }
+
+    pub fn toggle_mute(&self) -> Result<(), Box<dyn Error>> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let muted = !self.is_muted();
+
+        #[cfg(feature = "pipewire")]
+        if let Some(native) = &self.native {
+            native.set_mute(muted);
+            self.state.lock().unwrap().muted = muted;
+            return Ok(());
+        }
+
+        Command::new("wpctl")
+            .args(["set-mute", "@DEFAULT_AUDIO_SINK@", if muted { "1" } else { "0" }])
+            .output()?;
+
+        self.state.lock().unwrap().muted = muted;
+        Ok(())
+    }
+
+    pub fn has_source(&self) -> bool {
+        self.mic_enabled && self.state.lock().unwrap().has_source
+    }
+
+    pub fn get_source_volume(&self) -> f32 {
+        self.state.lock().unwrap().source_volume
+    }
+
+    pub fn is_source_muted(&self) -> bool {
+        self.state.lock().unwrap().source_muted
+    }
+
+    /// Set the default source's (microphone's) input level, at most 100%.
+    pub fn set_source_volume(&self, new_volume: f32) -> Result<(), Box<dyn Error>> {
+        if !self.mic_enabled {
+            return Ok(());
+        }
+
+        let clamped = new_volume.clamp(0.0, 1.0);
+
+        #[cfg(feature = "pipewire")]
+        if let Some(native) = &self.native {
+            native.set_source_volume(clamped);
+            self.state.lock().unwrap().source_volume = clamped;
+            return Ok(());
+        }
+
+        Command::new("wpctl")
+            .args(["set-volume", "@DEFAULT_AUDIO_SOURCE@", &format!("{:.2}", clamped)])
+            .output()?;
+
+        self.state.lock().unwrap().source_volume = clamped;
+        Ok(())
+    }
+
+    pub fn toggle_source_mute(&self) -> Result<(), Box<dyn Error>> {
+        if !self.mic_enabled {
+            return Ok(());
+        }
+
+        let muted = !self.is_source_muted();
+
+        #[cfg(feature = "pipewire")]
+        if let Some(native) = &self.native {
+            native.set_source_mute(muted);
+            self.state.lock().unwrap().source_muted = muted;
+            return Ok(());
+        }
+
+        Command::new("wpctl")
+            .args(["set-mute", "@DEFAULT_AUDIO_SOURCE@", if muted { "1" } else { "0" }])
+            .output()?;
+
+        self.state.lock().unwrap().source_muted = muted;
         Ok(())
     }
 
     #[allow(dead_code)]
     pub fn update_volume(&self) -> Result<(), Box<dyn Error>> {
-        if !self.enabled {
+        if !self.enabled || self.is_native() {
             return Ok(());
         }
 
-        let current = Self::get_current_volume()?;
-        *self.volume.lock().unwrap() = current;
+        let (volume, muted) = Self::wpctl_volume("@DEFAULT_AUDIO_SINK@")?;
+        let mut state = self.state.lock().unwrap();
+        state.volume = volume;
+        state.muted  = muted;
         Ok(())
     }
 
+    /// `wpctl` fallback only; the PipeWire connection pushes changes itself.
     pub fn start_polling(&self, config: &Config) {
-        if !config.enable_audio_control {
+        if !config.enable_audio_control || self.is_native() {
             return;
         }
 
-        let volume_clone = Arc::clone(&self.volume);
+        let state_clone = Arc::clone(&self.state);
+        let waker = Arc::clone(&self.waker);
         let interval = Duration::from_millis(config.volume_update_interval_ms);
+        let mic = self.mic_enabled;
 
         thread::spawn(move || loop {
-            if let Ok(vol) = Self::get_current_volume() {
-                *volume_clone.lock().unwrap() = vol;
+            if let Ok((vol, muted)) = Self::wpctl_volume("@DEFAULT_AUDIO_SINK@") {
+                let sinks = Self::get_sinks().unwrap_or_default();
+                let mut source = AudioState::default();
+                if mic { Self::read_source(&mut source); }
+                let changed = {
+                    let mut state = state_clone.lock().unwrap();
+                    let changed = state.volume != vol || state.muted != muted || state.sinks != sinks
+                        || (state.has_source, state.source_volume, state.source_muted)
+                            != (source.has_source, source.source_volume, source.source_muted);
+                    state.volume        = vol;
+                    state.muted         = muted;
+                    state.sinks         = sinks;
+                    state.has_source    = source.has_source;
+                    state.source_volume = source.source_volume;
+                    state.source_muted  = source.source_muted;
+                    changed
+                };
+                if changed && let Some(wake) = waker.get() { wake(); }
             }
             thread::sleep(interval);
         });
@@ -129,7 +400,7 @@ impl AudioController {
         if !self.enabled {
             return 0.0;
         }
-        *self.volume.lock().unwrap()
+        self.state.lock().unwrap().volume
     }
 
     #[allow(dead_code)]
@@ -138,13 +409,150 @@ impl AudioController {
     }
 }
 
+/// The `Audio › Sinks` block of `wpctl status`:
+///
+/// ```text
+///  ├─ Sinks:
+///  │  *   46. Built-in Audio Analog Stereo        [vol: 0.40]
+///  │      51. HDMI / DisplayPort 1 Output         [vol: 1.00]
+/// ```
+fn parse_wpctl_sinks(status: &str) -> Vec<SinkInfo> {
+    let mut sinks    = Vec::new();
+    let mut in_audio = false;
+    let mut in_sinks = false;
+    for line in status.lines() {
+        // Top-level headings ("Audio", "Video", "Settings") start in column 0.
+        if line.starts_with(|c: char| c.is_alphanumeric()) {
+            in_audio = line.trim() == "Audio";
+            in_sinks = false;
+            continue;
+        }
+        let body = line.trim_start_matches(|c: char| c.is_whitespace() || "│├└─".contains(c));
+        if body.ends_with(':') {
+            in_sinks = in_audio && body == "Sinks:";
+            continue;
+        }
+        if !in_sinks || body.is_empty() { continue; }
+
+        let (is_default, body) = match body.strip_prefix('*') {
+            Some(rest) => (true, rest.trim_start()),
+            None       => (false, body),
+        };
+        let Some((id, rest)) = body.split_once(". ") else { continue };
+        let Ok(id) = id.trim().parse() else { continue };
+        let description = rest.split(" [vol:").next().unwrap_or(rest).trim().to_string();
+        sinks.push(SinkInfo { id, description, is_default });
+    }
+    sinks
+}
+
+// ============================================================================
+// Clipboard
+// ============================================================================
+
+/// Copies `text` via an external clipboard tool. The tool owns the selection
+/// after we exit, which an in-process clipboard would not (the launcher usually
+/// quits right after copying). Returns `true` if one of them accepted the text.
+pub fn copy_to_clipboard(text: &str) -> bool {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let wayland: &[&[&str]] = &[&["wl-copy"]];
+    let x11:     &[&[&str]] = &[&["xclip", "-selection", "clipboard"], &["xsel", "--clipboard", "--input"]];
+    let order = if env::var_os("WAYLAND_DISPLAY").is_some() { [wayland, x11] } else { [x11, wayland] };
+
+    order.into_iter().flatten().any(|argv| {
+        let Ok(mut child) = Command::new(argv[0]).args(&argv[1..]).stdin(Stdio::piped()).spawn() else {
+            return false;
+        };
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        // wl-copy/xclip fork a server and exit; wait so the status is meaningful.
+        written && child.wait().is_ok_and(|s| s.success())
+    })
+}
+
+// ============================================================================
+// Opening URIs
+// ============================================================================
+
+/// Hands `uri` to the desktop's default handler via `xdg-open`.
+pub fn open_uri(uri: &str) -> bool {
+    use std::process::Stdio;
+    match Command::new("xdg-open").arg(uri)
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(mut child) => {
+            // Reap it so daemon mode doesn't collect zombies.
+            thread::spawn(move || child.wait());
+            true
+        }
+        Err(e) => {
+            tracing::warn!(target: "launch", "Failed to run xdg-open: {e}");
+            false
+        }
+    }
+}
+
+// ============================================================================
+// Folder picker
+// ============================================================================
+
+/// Asks for a folder with `zenity`, or `kdialog` without it, starting in
+/// `start`. Blocks until the dialog closes; `None` when it's cancelled or
+/// neither tool is installed.
+pub fn pick_folder(start: &str) -> Option<String> {
+    let start = if start.is_empty() { env::var("HOME").unwrap_or_default() } else { start.to_string() };
+    let dialogs: [(&str, Vec<String>); 2] = [
+        ("zenity",  vec!["--file-selection".into(), "--directory".into(), format!("--filename={start}/")]),
+        ("kdialog", vec!["--getexistingdirectory".into(), start]),
+    ];
+    for (program, args) in dialogs {
+        match Command::new(program).args(&args).output() {
+            Ok(out) => {
+                let path = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                return (out.status.success() && !path.is_empty()).then_some(path);
+            }
+            Err(_) => continue,
+        }
+    }
+    tracing::warn!(target: "launch", "No folder picker found; install zenity or kdialog");
+    None
+}
+
+// ============================================================================
+// Processes
+// ============================================================================
+
+/// Our processes named `name`, with their command lines, read from /proc.
+pub fn find_processes(name: &str) -> Vec<(u32, Vec<String>)> {
+    use std::os::unix::fs::MetadataExt;
+    let uid = unsafe { libc::getuid() };
+    let Ok(entries) = std::fs::read_dir("/proc") else { return Vec::new() };
+    entries.flatten()
+        .filter_map(|entry| Some((entry.file_name().to_str()?.parse::<u32>().ok()?, entry.path())))
+        .filter(|(_, dir)| {
+            std::fs::metadata(dir).is_ok_and(|m| m.uid() == uid)
+                && std::fs::read_to_string(dir.join("comm")).is_ok_and(|comm| comm.trim_end() == name)
+        })
+        .map(|(pid, dir)| {
+            let cmdline = std::fs::read(dir.join("cmdline")).unwrap_or_default();
+            let args = cmdline.split(|&b| b == 0).filter(|a| !a.is_empty())
+                .map(|a| String::from_utf8_lossy(a).into_owned())
+                .collect();
+            (pid, args)
+        })
+        .collect()
+}
+
 // ============================================================================
 // Power
 // ============================================================================
 
-fn execute_power_action(label: &str, commands: &[String]) {
+/// Tries the command list; also logind's fallback when it's unreachable.
+pub fn execute_power_action(label: &str, commands: &[String]) {
     if !try_commands(commands) {
-        eprintln!("Failed to {}: No working commands found in config", label);
+        tracing::warn!(target: "power", "Failed to {}: No working commands found in config", label);
     }
 }
 
@@ -156,6 +564,10 @@ pub fn restart(config: &Config) {
     execute_power_action("restart", &config.restart_commands);
 }
 
+pub fn suspend(config: &Config) {
+    execute_power_action("suspend", &config.suspend_commands);
+}
+
 pub fn logout(config: &Config) {
     execute_power_action("logout", &config.logout_commands);
 }
@@ -184,4 +596,39 @@ mod tests {
         config.time_order = TimeOrder::YmdHms;
         assert!(!get_current_time(&config).is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extra_clocks() {
+        assert_eq!(parse_clock("Tokyo = Asia/Tokyo"), ("Tokyo".into(), "Asia/Tokyo"));
+        assert_eq!(parse_clock("America/New_York"), ("New York".into(), "America/New_York"));
+        let config = Config { extra_clocks: vec!["UTC".into()], ..Config::default() };
+        assert!(get_current_time(&config).split('\n').nth(1).unwrap().starts_with("UTC "));
+    }
+
+    #[test]
+    fn test_parse_wpctl_sinks() {
+        let status = "PipeWire 'pipewire-0' [1.2.7, user@host, cookie:1]
+ └─ Clients:
+        33. WirePlumber                         [1.2.7, user@host, pid:1201]
+
+Audio
+ ├─ Devices:
+ │      42. Built-in Audio                      [alsa]
+ │
+ ├─ Sinks:
+ │      46. Built-in Audio Analog Stereo        [vol: 0.40]
+ │  *   51. HDMI / DisplayPort 1 Output         [vol: 1.00 MUTED]
+ │
+ ├─ Sources:
+ │  *   47. Built-in Audio Analog Stereo        [vol: 1.00]
+
+Video
+ ├─ Sinks:
+ │      60. Dummy video sink
+";
+        let sinks = parse_wpctl_sinks(status);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[0], SinkInfo { id: 46, description: "Built-in Audio Analog Stereo".into(), is_default: false });
+        assert_eq!(sinks[1], SinkInfo { id: 51, description: "HDMI / DisplayPort 1 Output".into(), is_default: true });
+    }
+}
@@ -0,0 +1,224 @@
+//! Countdown / Pomodoro timer for the `timer` theme section.
+//!
+//! Design:
+//! - Presets come from `timer-presets` ("Pomodoro=25m, Break=5m"); picking
+//!   one resets the countdown to its length.
+//! - The deadline is an `Instant`, so the display is computed on demand and
+//!   nothing ticks while the timer is idle or paused.
+//! - Each start arms a sleeper thread that posts the desktop notification at
+//!   the deadline — it fires even while the window is hidden, when the GUI
+//!   isn't repainting. Pausing or resetting bumps a generation counter so a
+//!   stale sleeper wakes up and does nothing.
+//! - A finished timer stays on "Done" in the section until started or reset,
+//!   which doubles as the in-launcher alert when no notification daemon runs.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub label:    String,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Idle,
+    Running { ends: Instant },
+    Paused  { left: Duration },
+}
+
+/// Called from the sleeper thread when a countdown ends, so the GUI can repaint.
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+pub struct Timer {
+    presets:    Vec<Preset>,
+    selected:   usize,
+    state:      State,
+    generation: Arc<AtomicU64>,
+    waker:      Waker,
+}
+
+impl Timer {
+    pub fn new(presets: Vec<Preset>, on_finish: impl Fn() + Send + Sync + 'static) -> Self {
+        Timer {
+            presets,
+            selected:   0,
+            state:      State::Idle,
+            generation: Arc::new(AtomicU64::new(0)),
+            waker:      Arc::new(on_finish),
+        }
+    }
+
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub fn selected(&self) -> Option<&Preset> {
+        self.presets.get(self.selected)
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    /// Switch to preset `index`, stopping any countdown.
+    pub fn select(&mut self, index: usize) {
+        if index < self.presets.len() {
+            self.selected = index;
+            self.reset();
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.state {
+            State::Idle             => self.selected().map_or(Duration::ZERO, |p| p.duration),
+            State::Running { ends } => ends.saturating_duration_since(now),
+            State::Paused { left }  => left,
+        }
+    }
+
+    /// Counting down and not yet at zero.
+    pub fn is_running(&self, now: Instant) -> bool {
+        matches!(self.state, State::Running { ends } if ends > now)
+    }
+
+    pub fn is_finished(&self, now: Instant) -> bool {
+        matches!(self.state, State::Running { ends } if ends <= now)
+    }
+
+    /// Start, pause or resume; a finished timer starts over.
+    pub fn toggle(&mut self, now: Instant) {
+        let left = match self.state {
+            State::Running { .. } if self.is_running(now) => {
+                self.disarm();
+                self.state = State::Paused { left: self.remaining(now) };
+                return;
+            }
+            State::Paused { left } => left,
+            _ => match self.selected() {
+                Some(p) => p.duration,
+                None    => return,
+            },
+        };
+        self.state = State::Running { ends: now + left };
+        self.arm(left);
+    }
+
+    pub fn reset(&mut self) {
+        self.disarm();
+        self.state = State::Idle;
+    }
+
+    fn disarm(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn arm(&self, left: Duration) {
+        let armed      = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let generation = Arc::clone(&self.generation);
+        let waker      = Arc::clone(&self.waker);
+        let label      = self.selected().map(|p| p.label.clone()).unwrap_or_default();
+        thread::spawn(move || {
+            thread::sleep(left);
+            if generation.load(Ordering::SeqCst) != armed { return; }
+            waker();
+            crate::notifications::send("alarm-symbolic", &format!("{label} finished"), &format!("{} is up", format_remaining(left)));
+        });
+    }
+}
+
+// ============================================================================
+// Parsing & formatting
+// ============================================================================
+
+/// "Label=25m" entries; a bare duration is labelled with itself.
+pub fn parse_presets(entries: &[String]) -> Vec<Preset> {
+    entries.iter()
+        .filter(|e| !e.is_empty())
+        .filter_map(|entry| {
+            let (label, length) = entry.split_once('=').unwrap_or((entry, entry));
+            match parse_duration(length.trim()) {
+                Some(duration) => Some(Preset { label: label.trim().to_string(), duration }),
+                None => {
+                    tracing::warn!(target: "config", "Ignoring timer preset {entry:?}: expected e.g. \"Pomodoro=25m\"");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// "25m", "1h30m", "90s"; a bare number means minutes.
+fn parse_duration(s: &str) -> Option<Duration> {
+    if let Ok(mins) = s.parse::<u64>() {
+        return (mins > 0).then(|| Duration::from_secs(mins * 60));
+    }
+    let mut secs = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let n: u64 = rest[..digits].parse().ok()?;
+        let unit = match rest[digits..].chars().next()? {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _   => return None,
+        };
+        secs += n * unit;
+        rest = rest[digits + 1..].trim_start();
+    }
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// "24:59", or "1:04:59" past an hour; partial seconds round up so the
+/// display reaches 0:00 exactly when the timer ends.
+pub fn format_remaining(d: Duration) -> String {
+    let secs = d.as_secs() + (d.subsec_nanos() > 0) as u64;
+    match secs / 3600 {
+        0 => format!("{:02}:{:02}", secs / 60, secs % 60),
+        h => format!("{h}:{:02}:{:02}", secs / 60 % 60, secs % 60),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_presets() {
+        let entries = ["Pomodoro=25m".to_string(), "Tea = 3m30s".into(), "90s".into(), "Bad=soon".into()];
+        let presets = parse_presets(&entries);
+        assert_eq!(presets.iter().map(|p| (p.label.as_str(), p.duration.as_secs())).collect::<Vec<_>>(),
+                   [("Pomodoro", 1500), ("Tea", 210), ("90s", 90)]);
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("10"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("0m"), None);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut timer = Timer::new(parse_presets(&["Pomodoro=25m".into(), "Break=5m".into()]), || {});
+        let t0 = Instant::now();
+        timer.toggle(t0);
+        assert!(timer.is_running(t0 + Duration::from_secs(60)));
+        timer.toggle(t0 + Duration::from_secs(60));
+        assert_eq!(timer.remaining(t0 + Duration::from_secs(600)), Duration::from_secs(24 * 60));
+        timer.toggle(t0 + Duration::from_secs(600));
+        assert!(timer.is_finished(t0 + Duration::from_secs(600 + 24 * 60)));
+        timer.select(1);
+        assert_eq!(format_remaining(timer.remaining(t0)), "05:00");
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(Duration::from_millis(1_499_100)), "25:00");
+        assert_eq!(format_remaining(Duration::from_secs(3899)), "1:04:59");
+        assert_eq!(format_remaining(Duration::ZERO), "00:00");
+    }
+}
@@ -0,0 +1,361 @@
+//! IANA time zones for the extra clocks, read from the system tzdata.
+//!
+//! Design:
+//! - `/usr/share/zoneinfo/<Area/City>` (TZif) gives the historical
+//!   transitions; its POSIX TZ footer (e.g. `CET-1CEST,M3.5.0,M10.5.0/3`)
+//!   covers everything after the last one, which for "slim" tzdata builds is
+//!   nearly always — so both halves are needed.
+//! - Zones are parsed once and cached for the process; a missing or corrupt
+//!   file makes the clock fall back to UTC rather than disappear.
+//! - libc's `localtime_r` keeps serving the local clock; switching `TZ` at
+//!   runtime would race with other threads reading the environment.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::gui::LocalTime;
+
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+// ============================================================================
+// Zones
+// ============================================================================
+
+#[derive(Debug, Default)]
+pub struct TimeZone {
+    /// Transition instants (Unix seconds), ascending.
+    transitions: Vec<i64>,
+    /// UTC offset in effect from the matching transition on.
+    offsets:     Vec<i32>,
+    /// Offset before the first transition.
+    initial:     i32,
+    /// Rule for instants past the last transition.
+    rule:        Option<PosixTz>,
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        TimeZone::default()
+    }
+
+    /// UTC offset in seconds at Unix time `t`.
+    pub fn offset_at(&self, t: i64) -> i32 {
+        match self.transitions.partition_point(|&tr| tr <= t) {
+            0 if self.transitions.is_empty() => self.rule.as_ref().map_or(self.initial, |r| r.offset_at(t)),
+            0                                => self.initial,
+            n if n == self.transitions.len() => self.rule.as_ref().map_or(self.offsets[n - 1], |r| r.offset_at(t)),
+            n                                => self.offsets[n - 1],
+        }
+    }
+
+    /// Wall-clock time in this zone at Unix time `t`.
+    pub fn local_time(&self, t: i64) -> LocalTime {
+        let offset = self.offset_at(t);
+        let local  = t + offset as i64;
+        let (year, month, day) = civil_from_days(local.div_euclid(86_400));
+        let secs = local.rem_euclid(86_400);
+        LocalTime {
+            year:   year as i32,
+            month:  month as u8,
+            day:    day as u8,
+            hour:   (secs / 3600) as u8,
+            min:    (secs / 60 % 60) as u8,
+            sec:    (secs % 60) as u8,
+            offset,
+        }
+    }
+}
+
+/// The zone called `name` ("Asia/Tokyo", "UTC"), parsed on first use.
+pub fn zone(name: &str) -> Arc<TimeZone> {
+    static ZONES: OnceLock<Mutex<HashMap<String, Arc<TimeZone>>>> = OnceLock::new();
+    let mut zones = ZONES.get_or_init(Default::default).lock().unwrap();
+    Arc::clone(zones.entry(name.to_string()).or_insert_with(|| Arc::new(load(name).unwrap_or_else(|| {
+        tracing::warn!(target: "config", "Unknown time zone {name:?}; showing UTC");
+        TimeZone::utc()
+    }))))
+}
+
+fn load(name: &str) -> Option<TimeZone> {
+    // Zone names are relative paths under zoneinfo; nothing may climb out.
+    if name.is_empty() || name.starts_with('/') || name.split('/').any(|c| c == "..") {
+        return None;
+    }
+    match std::fs::read(format!("{ZONEINFO}/{name}")) {
+        Ok(data) => parse_tzif(&data),
+        Err(_) if matches!(name, "UTC" | "Etc/UTC" | "GMT") => Some(TimeZone::utc()),
+        Err(_) => None,
+    }
+}
+
+// ============================================================================
+// TZif
+// ============================================================================
+
+/// RFC 8536. Uses the 64-bit (v2+) block and footer when present.
+fn parse_tzif(data: &[u8]) -> Option<TimeZone> {
+    if data.get(..4)? != b"TZif" { return None; }
+    let version = *data.get(4)?;
+    let (v1_len, v1) = parse_block(data, 4)?;
+    if version == 0 {
+        return Some(v1);
+    }
+    let rest = data.get(v1_len..)?;
+    if rest.get(..4)? != b"TZif" { return Some(v1); }
+    let (v2_len, mut tz) = parse_block(rest, 8)?;
+    // Footer: "\n<POSIX TZ>\n".
+    let footer = rest.get(v2_len..).and_then(|f| std::str::from_utf8(f).ok()).unwrap_or("");
+    tz.rule = footer.trim_matches('\n').lines().next().and_then(PosixTz::parse);
+    Some(tz)
+}
+
+/// One header + data block with `time_size`-byte transition times; returns
+/// the block's length in bytes and the zone it describes.
+fn parse_block(data: &[u8], time_size: usize) -> Option<(usize, TimeZone)> {
+    let count = |i: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(data.get(20 + 4 * i..24 + 4 * i)?.try_into().ok()?) as usize)
+    };
+    let (isut, isstd, leap, time, types, chars) = (count(0)?, count(1)?, count(2)?, count(3)?, count(4)?, count(5)?);
+    if types == 0 { return None; }
+
+    let times_at = 44;
+    let idx_at   = times_at + time * time_size;
+    let types_at = idx_at + time;
+    let len = types_at + types * 6 + chars + leap * (time_size + 4) + isstd + isut;
+    if data.len() < len { return None; }
+
+    let ttinfo = |i: usize| -> Option<i32> {
+        let at = types_at + 6 * i;
+        Some(i32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+    };
+    let mut transitions = Vec::with_capacity(time);
+    let mut offsets     = Vec::with_capacity(time);
+    for i in 0..time {
+        let raw = &data[times_at + i * time_size..times_at + (i + 1) * time_size];
+        transitions.push(match time_size {
+            4 => i32::from_be_bytes(raw.try_into().ok()?) as i64,
+            _ => i64::from_be_bytes(raw.try_into().ok()?),
+        });
+        offsets.push(ttinfo(data[idx_at + i] as usize)?);
+    }
+    // RFC 8536 §3.2: local time before the first transition uses type 0.
+    let initial = ttinfo(0)?;
+    Some((len, TimeZone { transitions, offsets, initial, rule: None }))
+}
+
+// ============================================================================
+// POSIX TZ strings
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RuleDate {
+    /// `Jn`: day 1..=365, never counting Feb 29.
+    Julian(u16),
+    /// `n`: zero-based day of year, counting Feb 29.
+    Ordinal(u16),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) of month `m`.
+    MonthWeek(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PosixTz {
+    std_offset: i32,
+    /// `(offset, start, start time, end, end time)`, times in seconds of local day.
+    dst: Option<(i32, RuleDate, i32, RuleDate, i32)>,
+}
+
+impl PosixTz {
+    fn parse(s: &str) -> Option<PosixTz> {
+        let mut p = Parser(s);
+        p.name()?;
+        let std_offset = -p.offset()?;
+        if p.0.is_empty() {
+            return Some(PosixTz { std_offset, dst: None });
+        }
+        p.name()?;
+        let dst_offset = if p.0.starts_with(',') { std_offset + 3600 } else { -p.offset()? };
+        // Without a rule POSIX leaves the dates implementation-defined; the
+        // US rules are what glibc assumes.
+        let (start, start_t, end, end_t) = if p.0.is_empty() {
+            (RuleDate::MonthWeek(3, 2, 0), 7200, RuleDate::MonthWeek(11, 1, 0), 7200)
+        } else {
+            p.eat(',')?;
+            let (start, start_t) = p.rule()?;
+            p.eat(',')?;
+            let (end, end_t) = p.rule()?;
+            (start, start_t, end, end_t)
+        };
+        Some(PosixTz { std_offset, dst: Some((dst_offset, start, start_t, end, end_t)) })
+    }
+
+    fn offset_at(&self, t: i64) -> i32 {
+        let Some((dst_offset, start, start_t, end, end_t)) = self.dst else { return self.std_offset };
+        let (year, _, _) = civil_from_days((t + self.std_offset as i64).div_euclid(86_400));
+        // Start is given in standard local time, end in daylight local time.
+        let begins = start.day(year) * 86_400 + start_t as i64 - self.std_offset as i64;
+        let ends   = end.day(year) * 86_400 + end_t as i64 - dst_offset as i64;
+        let in_dst = if begins < ends { begins <= t && t < ends } else { !(ends <= t && t < begins) };
+        if in_dst { dst_offset } else { self.std_offset }
+    }
+}
+
+impl RuleDate {
+    /// Days since the epoch of this date in `year`.
+    fn day(self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        match self {
+            RuleDate::Julian(n) => {
+                let leap_skip = (is_leap(year) && n >= 60) as i64;
+                jan1 + n as i64 - 1 + leap_skip
+            }
+            RuleDate::Ordinal(n) => jan1 + n as i64,
+            RuleDate::MonthWeek(m, w, d) => {
+                let first = days_from_civil(year, m as i64, 1);
+                // 1970-01-01 was a Thursday (4).
+                let first_wd = (first + 4).rem_euclid(7);
+                let mut day = first + (d as i64 - first_wd).rem_euclid(7) + (w as i64 - 1) * 7;
+                let next_month = if m == 12 { days_from_civil(year + 1, 1, 1) } else { days_from_civil(year, m as i64 + 1, 1) };
+                while day >= next_month { day -= 7; }
+                day
+            }
+        }
+    }
+}
+
+struct Parser<'a>(&'a str);
+
+impl Parser<'_> {
+    fn eat(&mut self, c: char) -> Option<()> {
+        self.0 = self.0.strip_prefix(c)?;
+        Some(())
+    }
+
+    /// `CET` or quoted `<+0530>`.
+    fn name(&mut self) -> Option<()> {
+        let len = if let Some(rest) = self.0.strip_prefix('<') {
+            rest.find('>')? + 2
+        } else {
+            self.0.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(self.0.len())
+        };
+        if len < 3 { return None; }
+        self.0 = &self.0[len..];
+        Some(())
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds, sign as written.
+    fn offset(&mut self) -> Option<i32> {
+        let sign = match self.0.chars().next()? {
+            '-' => { self.0 = &self.0[1..]; -1 }
+            '+' => { self.0 = &self.0[1..]; 1 }
+            _   => 1,
+        };
+        let mut secs = 0;
+        for (i, scale) in [3600, 60, 1].into_iter().enumerate() {
+            if i > 0 && self.eat(':').is_none() { break; }
+            let digits = self.0.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.0.len());
+            if digits == 0 { return None; }
+            secs += self.0[..digits].parse::<i32>().ok()? * scale;
+            self.0 = &self.0[digits..];
+        }
+        Some(sign * secs)
+    }
+
+    fn number(&mut self) -> Option<u16> {
+        let digits = self.0.find(|c: char| !c.is_ascii_digit()).unwrap_or(self.0.len());
+        let n = self.0[..digits].parse().ok()?;
+        self.0 = &self.0[digits..];
+        Some(n)
+    }
+
+    /// `date[/time]`, time defaulting to 02:00.
+    fn rule(&mut self) -> Option<(RuleDate, i32)> {
+        let date = if self.eat('J').is_some() {
+            RuleDate::Julian(self.number().filter(|n| (1..=365).contains(n))?)
+        } else if self.eat('M').is_some() {
+            let m = self.number().filter(|n| (1..=12).contains(n))?;
+            self.eat('.')?;
+            let w = self.number().filter(|n| (1..=5).contains(n))?;
+            self.eat('.')?;
+            let d = self.number().filter(|n| *n <= 6)?;
+            RuleDate::MonthWeek(m as u8, w as u8, d as u8)
+        } else {
+            RuleDate::Ordinal(self.number().filter(|n| *n <= 365)?)
+        };
+        let time = if self.eat('/').is_some() { self.offset()? } else { 7200 };
+        Some((date, time))
+    }
+}
+
+// ============================================================================
+// Calendar arithmetic
+// ============================================================================
+
+fn is_leap(y: i64) -> bool {
+    y % 4 == 0 && (y % 100 != 0 || y % 400 == 0)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (H. Hinnant's algorithm).
+pub(crate) fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y   = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`: `(year, month, day)`.
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z   = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp  = (5 * doy + 2) / 153;
+    let d   = doy - (153 * mp + 2) / 5 + 1;
+    let m   = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-15 12:00 UTC and 2024-07-01 12:00 UTC.
+    const WINTER: i64 = 1_705_320_000;
+    const SUMMER: i64 = 1_719_835_200;
+
+    #[test]
+    fn test_civil_roundtrip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_posix_rules() {
+        let cet = PosixTz::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        assert_eq!((cet.offset_at(WINTER), cet.offset_at(SUMMER)), (3600, 7200));
+
+        let us = PosixTz::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        assert_eq!((us.offset_at(WINTER), us.offset_at(SUMMER)), (-18_000, -14_400));
+        // 2024-03-10 06:59:59 / 07:00:00 UTC straddle 02:00 EST.
+        assert_eq!((us.offset_at(1_710_053_999), us.offset_at(1_710_054_000)), (-18_000, -14_400));
+
+        let sydney = PosixTz::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!((sydney.offset_at(WINTER), sydney.offset_at(SUMMER)), (39_600, 36_000));
+
+        let india = PosixTz::parse("<+0530>-5:30").unwrap();
+        assert_eq!(india.offset_at(SUMMER), 19_800);
+    }
+
+    #[test]
+    fn test_local_time() {
+        let tokyo = TimeZone { rule: PosixTz::parse("JST-9"), ..TimeZone::default() };
+        let t = tokyo.local_time(SUMMER);
+        assert_eq!((t.year, t.month, t.day, t.hour, t.min), (2024, 7, 1, 21, 0));
+    }
+}
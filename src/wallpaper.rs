@@ -0,0 +1,265 @@
+//! Wallpapers for the `wallpaper` mode: the images in `wallpaper-dir`, shown
+//! as a grid of thumbnails and set with swww, hyprpaper or swaybg.
+//!
+//! Design:
+//! - Thumbnails are small PNGs in `$XDG_CACHE_HOME/tusk-launcher/thumbnails`,
+//!   named after the image's path, size and mtime. They're made on the
+//!   search thread the first time an image is listed, so the grid's
+//!   `IconManager` only ever decodes small files.
+//! - `wallpaper-setter` names the tool; empty picks swww when its daemon
+//!   answers, hyprpaper on Hyprland when it's running, and swaybg otherwise.
+//!   swww and hyprpaper are told over their IPC; swaybg is started anew for
+//!   the output, and the one drawing there before is stopped once the new
+//!   one has had time to draw.
+//! - Each output's wallpaper is remembered in
+//!   `$XDG_STATE_HOME/tusk-launcher/wallpapers` (`output<TAB>path`, `*` for
+//!   all outputs). With `wallpaper-restore` the first instance of a session
+//!   puts them back, behind a marker in `$XDG_RUNTIME_DIR` like autostart.
+
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::gui::Config;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+const THUMBNAIL_DIR:    &str = "tusk-launcher/thumbnails";
+const STATE_FILE:       &str = "tusk-launcher/wallpapers";
+const MARKER:           &str = "tusk-launcher-wallpaper.done";
+/// Longest side of a thumbnail.
+const THUMBNAIL_PX: u32 = 256;
+/// Subdirectories of `wallpaper-dir` are looked in this deep.
+const MAX_DEPTH: usize = 3;
+/// Time for a new swaybg to draw before the old one is stopped.
+const SWAYBG_HANDOVER: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wallpaper {
+    pub path: PathBuf,
+    /// Path below `wallpaper-dir`, e.g. `nature/lake.jpg`.
+    pub name: String,
+}
+
+/// The images in `dir` and its subdirectories, by name.
+pub fn list(dir: &Path) -> Vec<Wallpaper> {
+    let mut found = Vec::new();
+    walk(dir, dir, 0, &mut found);
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    found
+}
+
+fn walk(root: &Path, dir: &Path, depth: usize, found: &mut Vec<Wallpaper>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            if depth < MAX_DEPTH { walk(root, &path, depth + 1, found); }
+            continue;
+        }
+        let is_image = path.extension().and_then(|e| e.to_str())
+            .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if !is_image { continue; }
+        let name = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().into_owned();
+        found.push(Wallpaper { path, name });
+    }
+}
+
+/// `wallpaper-dir`, with a leading `~/` expanded.
+pub fn dir(config: &Config) -> PathBuf {
+    match config.wallpaper_dir.strip_prefix("~/") {
+        Some(rest) => crate::paths::home().join(rest),
+        None       => PathBuf::from(&config.wallpaper_dir),
+    }
+}
+
+/// A cached thumbnail of `image`, made if there isn't one yet.
+pub fn thumbnail(path: &Path) -> Option<PathBuf> {
+    let meta = fs::metadata(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    (path, meta.len(), meta.modified().ok()).hash(&mut hasher);
+    let thumb = crate::paths::cache_home().join(THUMBNAIL_DIR).join(format!("{:016x}.png", hasher.finish()));
+    if thumb.exists() { return Some(thumb); }
+
+    let made = (|| -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(thumb.parent().ok_or("no parent")?)?;
+        image::open(path)?.thumbnail(THUMBNAIL_PX, THUMBNAIL_PX).save_with_format(&thumb, image::ImageFormat::Png)?;
+        Ok(())
+    })();
+    match made {
+        Ok(()) => Some(thumb),
+        Err(e) => {
+            tracing::warn!(target: "wallpaper", "Can't make a thumbnail of {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+// ============================================================================
+// Setting
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setter { Swww, Hyprpaper, Swaybg }
+
+impl Setter {
+    /// `wallpaper-setter`, or the first one usable here when it's empty.
+    pub fn pick(name: &str) -> Option<Setter> {
+        match name.trim() {
+            "swww"      => Some(Setter::Swww),
+            "hyprpaper" => Some(Setter::Hyprpaper),
+            "swaybg"    => Some(Setter::Swaybg),
+            ""          => Self::detect(),
+            other       => {
+                tracing::warn!(target: "config", "wallpaper-setter: expected \"swww\", \"hyprpaper\" or \"swaybg\", not {other:?}");
+                None
+            }
+        }
+    }
+
+    fn detect() -> Option<Setter> {
+        if run(&["swww", "query"]).is_ok() { return Some(Setter::Swww); }
+        if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() && !crate::system::find_processes("hyprpaper").is_empty() {
+            return Some(Setter::Hyprpaper);
+        }
+        std::env::var_os("WAYLAND_DISPLAY").is_some().then_some(Setter::Swaybg)
+    }
+}
+
+/// Put `image` on `output` (every output for `None`) and remember it.
+pub fn set(setter: Setter, image: &Path, output: Option<&str>) -> Result<(), Box<dyn Error>> {
+    apply(setter, image, output)?;
+    let outputs = match output {
+        Some(output) => vec![output.to_string()],
+        None         => {
+            let all: Vec<String> = crate::monitors::list().into_iter().map(|m| m.name).collect();
+            if all.is_empty() { vec!["*".to_string()] } else { all }
+        }
+    };
+    remember(&outputs, image)
+}
+
+fn apply(setter: Setter, image: &Path, output: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let image = image.to_str().ok_or("path isn't UTF-8")?;
+    match setter {
+        Setter::Swww => {
+            let mut args = vec!["swww", "img"];
+            if let Some(output) = output { args.extend(["-o", output]); }
+            args.push(image);
+            run(&args)?;
+        }
+        Setter::Hyprpaper => {
+            // hyprctl exits 0 either way; hyprpaper answers "ok" or the error.
+            let target = format!("{},{image}", output.unwrap_or(""));
+            for args in [["preload", image], ["wallpaper", target.as_str()]] {
+                let answer = run(&["hyprctl", "hyprpaper", args[0], args[1]])?;
+                if answer.trim() != "ok" { return Err(format!("hyprpaper: {}", answer.trim()).into()); }
+            }
+            let _ = run(&["hyprctl", "hyprpaper", "unload", "unused"]);
+        }
+        Setter::Swaybg => {
+            let output = output.unwrap_or("*");
+            let old: Vec<u32> = crate::system::find_processes("swaybg").into_iter()
+                .filter(|(_, args)| output == "*" || args.windows(2).any(|w| w[0] == "-o" && w[1] == output))
+                .map(|(pid, _)| pid)
+                .collect();
+            let mut child = Command::new("swaybg").args(["-o", output, "-i", image, "-m", "fill"])
+                .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+                .spawn()?;
+            // It keeps drawing after we exit; reap it in case it doesn't.
+            thread::spawn(move || child.wait());
+            thread::sleep(SWAYBG_HANDOVER);
+            for pid in old {
+                let Ok(pid) = libc::pid_t::try_from(pid) else { continue };
+                unsafe { libc::kill(pid, libc::SIGTERM); }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run a setter's command; its stdout, or why it failed.
+fn run(argv: &[&str]) -> Result<String, Box<dyn Error>> {
+    let out = Command::new(argv[0]).args(&argv[1..]).stdin(Stdio::null()).output()?;
+    if !out.status.success() {
+        return Err(format!("{} failed: {}", argv[0], String::from_utf8_lossy(&out.stderr).trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+// ============================================================================
+// Remembering
+// ============================================================================
+
+fn state_file() -> PathBuf {
+    crate::paths::state_home().join(STATE_FILE)
+}
+
+/// (output, image) as last set; `*` stands for every output.
+pub fn remembered() -> Vec<(String, PathBuf)> {
+    parse_state(&fs::read_to_string(state_file()).unwrap_or_default())
+}
+
+fn parse_state(src: &str) -> Vec<(String, PathBuf)> {
+    src.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(output, image)| (output.to_string(), PathBuf::from(image)))
+        .collect()
+}
+
+fn remember(outputs: &[String], image: &Path) -> Result<(), Box<dyn Error>> {
+    let mut state = remembered();
+    // `*` goes first and replaces everything; an output's own line then
+    // overrides it, so restoring in file order ends the same way.
+    if outputs.iter().any(|o| o == "*") { state.clear(); }
+    state.retain(|(output, _)| !outputs.contains(output));
+    state.extend(outputs.iter().map(|output| (output.clone(), image.to_path_buf())));
+    let path = state_file();
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+    let text: String = state.iter().map(|(output, image)| format!("{output}\t{}\n", image.display())).collect();
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Put the remembered wallpapers back, once per session.
+pub fn restore(config: &Config) {
+    if let Some(marker) = crate::paths::runtime_dir().map(|d| d.join(MARKER)) {
+        if marker.exists() { return; }
+        if let Err(e) = fs::write(&marker, "") {
+            tracing::warn!(target: "wallpaper", "Failed to write wallpaper marker {}: {e}", marker.display());
+        }
+    }
+    let setter_name = config.wallpaper_setter.clone();
+    thread::spawn(move || {
+        let state = remembered();
+        if state.is_empty() { return; }
+        let Some(setter) = Setter::pick(&setter_name) else { return };
+        for (output, image) in state {
+            let output = (output != "*").then_some(output.as_str());
+            if let Err(e) = apply(setter, &image, output) {
+                tracing::warn!(target: "wallpaper", "Can't restore {}: {e}", image.display());
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_state() {
+        let state = parse_state("DP-1\t/home/me/Wallpapers/lake.jpg\nbroken line\n*\t/w/a b.png\n");
+        assert_eq!(state, [
+            ("DP-1".to_string(), PathBuf::from("/home/me/Wallpapers/lake.jpg")),
+            ("*".to_string(), PathBuf::from("/w/a b.png")),
+        ]);
+    }
+}
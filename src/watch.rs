@@ -0,0 +1,78 @@
+//! Hot reload: notices edits to `theme.css`, `config.toml` and theme profiles via inotify.
+//!
+//! Design:
+//! - Watches the directory, not the files: editors usually save by writing a
+//!   temp file and renaming it over the original, which would orphan a watch
+//!   on the old inode.
+//! - A save is often several events (truncate, write, rename); they're
+//!   coalesced until the directory has been quiet for `SETTLE`, then
+//!   `on_change` runs once.
+//! - Raw libc inotify on its own thread — no extra crate, no async runtime.
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+const SETTLE: Duration = Duration::from_millis(150);
+
+/// Call `on_change` (from a background thread) whenever a file in `dir` that
+/// `wanted` accepts is written, created, replaced or removed.
+pub fn watch_files(
+    dir: &Path,
+    wanted: impl Fn(&str) -> bool + Send + 'static,
+    on_change: impl Fn() + Send + 'static,
+) {
+    let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+    if fd < 0 {
+        tracing::warn!(target: "config", "Hot reload disabled: inotify_init1: {}", std::io::Error::last_os_error());
+        return;
+    }
+    let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else { return };
+    let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE | libc::IN_DELETE;
+    if unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) } < 0 {
+        tracing::warn!(target: "config", "Hot reload disabled: watching {}: {}", dir.display(), std::io::Error::last_os_error());
+        unsafe { libc::close(fd) };
+        return;
+    }
+
+    thread::spawn(move || {
+        // Aligned for `inotify_event`; big enough for a burst of events.
+        let mut buf = [0u64; 512];
+        loop {
+            let mut pending = false;
+            // Block for the first event, then drain until things settle.
+            let mut timeout = -1;
+            loop {
+                let mut pfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+                let ready = unsafe { libc::poll(&mut pfd, 1, timeout) };
+                if ready < 0 && std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted { continue; }
+                if ready <= 0 { break; }
+                let n = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), std::mem::size_of_val(&buf)) };
+                if n <= 0 {
+                    tracing::warn!(target: "config", "Hot reload stopped: {}", std::io::Error::last_os_error());
+                    return;
+                }
+                let bytes = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), n as usize) };
+                pending |= event_names(bytes).any(&wanted);
+                timeout = SETTLE.as_millis() as i32;
+            }
+            if pending { on_change(); }
+        }
+    });
+}
+
+/// File names in a buffer of `inotify_event` records.
+fn event_names(mut bytes: &[u8]) -> impl Iterator<Item = &str> {
+    const HEADER: usize = std::mem::size_of::<libc::inotify_event>();
+    std::iter::from_fn(move || {
+        if bytes.len() < HEADER { return None; }
+        // `len` is the last u32 of the header: wd, mask, cookie, len.
+        let len = u32::from_ne_bytes(bytes[HEADER - 4..HEADER].try_into().ok()?) as usize;
+        let name = bytes.get(HEADER..HEADER + len)?;
+        bytes = &bytes[HEADER + len..];
+        // The name is NUL-padded to an aligned length.
+        let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        Some(std::str::from_utf8(&name[..end]).unwrap_or(""))
+    })
+}
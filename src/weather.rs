@@ -0,0 +1,541 @@
+//! Current weather for the `.weather` section: an icon and the temperature,
+//! from a pluggable provider.
+//!
+//! Design:
+//! - `weather-provider` picks a `Provider`: `open-meteo` (the default, no API
+//!   key) or `wttr.in`. A provider turns a location into a `Report`; another
+//!   one is a type implementing the trait and a line in `provider`.
+//! - `weather-location` is a place name or `lat,lon`. Open-Meteo geocodes a
+//!   name once per run; wttr.in takes either, and guesses from the IP
+//!   address when it's empty.
+//! - Fetched with `curl` on a background thread, like currency rates, every
+//!   `weather-refresh-minutes`. The last report is cached in
+//!   `$XDG_CACHE_HOME/tusk-launcher/weather` and shown at startup while it's
+//!   for the same location and units. When a fetch fails the old report stays
+//!   up marked stale, and the fetch is retried after `RETRY`.
+//! - Conditions are grouped from WMO codes (wttr.in's WWO codes are mapped
+//!   onto the same groups) and shown as the icon theme's `weather-*` icons,
+//!   or an emoji when the theme has none.
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::gui::Config;
+use crate::json::Json;
+
+const CACHE_FILE: &str = "tusk-launcher/weather";
+const CACHE_VERSION: &str = "WEATHER_V1";
+/// Wait before trying again after a failed fetch.
+const RETRY: Duration = Duration::from_secs(5 * 60);
+/// Size the themed icon is looked up at; the section scales it.
+const ICON_SIZE: u32 = 32;
+
+const OPEN_METEO_FORECAST:  &str = "https://api.open-meteo.com/v1/forecast";
+const OPEN_METEO_GEOCODING: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const WTTR_IN:              &str = "https://wttr.in";
+
+// ============================================================================
+// Reports
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Units { Metric, Imperial }
+
+impl Units {
+    pub fn parse(name: &str) -> Units {
+        match name.trim() {
+            "metric" | "" => Units::Metric,
+            "imperial"    => Units::Imperial,
+            other         => {
+                tracing::warn!(target: "config", "weather-units: expected \"metric\" or \"imperial\", not {other:?}");
+                Units::Metric
+            }
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Units::Metric   => "metric",
+            Units::Imperial => "imperial",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Units::Metric   => "°C",
+            Units::Imperial => "°F",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition { Clear, PartlyCloudy, Cloudy, Fog, Drizzle, Rain, Showers, Snow, Storm }
+
+impl Condition {
+    /// Grouped from a WMO weather interpretation code; unknown codes read as cloudy.
+    pub fn from_wmo(code: u32) -> Condition {
+        match code {
+            0 | 1   => Condition::Clear,
+            2       => Condition::PartlyCloudy,
+            45 | 48 => Condition::Fog,
+            51..=57 => Condition::Drizzle,
+            61..=67 => Condition::Rain,
+            71..=77 => Condition::Snow,
+            80..=82 => Condition::Showers,
+            85 | 86 => Condition::Snow,
+            95..=99 => Condition::Storm,
+            _       => Condition::Cloudy,
+        }
+    }
+
+    /// Grouped from a WWO code, as wttr.in reports them.
+    pub fn from_wwo(code: u32) -> Condition {
+        match code {
+            113                                     => Condition::Clear,
+            116                                     => Condition::PartlyCloudy,
+            143 | 248 | 260                         => Condition::Fog,
+            263 | 266 | 281 | 284                   => Condition::Drizzle,
+            293..=314                               => Condition::Rain,
+            176 | 353..=359                         => Condition::Showers,
+            179 | 182 | 185 | 227 | 230 | 317..=350 => Condition::Snow,
+            362..=377                               => Condition::Snow,
+            200 | 386..=395                         => Condition::Storm,
+            _                                       => Condition::Cloudy,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Condition::Clear        => "Clear",
+            Condition::PartlyCloudy => "Partly cloudy",
+            Condition::Cloudy       => "Cloudy",
+            Condition::Fog          => "Fog",
+            Condition::Drizzle      => "Drizzle",
+            Condition::Rain         => "Rain",
+            Condition::Showers      => "Showers",
+            Condition::Snow         => "Snow",
+            Condition::Storm        => "Thunderstorm",
+        }
+    }
+
+    /// Freedesktop icon name; clear and partly cloudy skies have night variants.
+    pub fn icon(self, is_day: bool) -> &'static str {
+        match (self, is_day) {
+            (Condition::Clear, true)         => "weather-clear",
+            (Condition::Clear, false)        => "weather-clear-night",
+            (Condition::PartlyCloudy, true)  => "weather-few-clouds",
+            (Condition::PartlyCloudy, false) => "weather-few-clouds-night",
+            (Condition::Cloudy, _)           => "weather-overcast",
+            (Condition::Fog, _)              => "weather-fog",
+            (Condition::Drizzle, _)          => "weather-showers-scattered",
+            (Condition::Rain, _)             => "weather-showers",
+            (Condition::Showers, _)          => "weather-showers-scattered",
+            (Condition::Snow, _)             => "weather-snow",
+            (Condition::Storm, _)            => "weather-storm",
+        }
+    }
+
+    /// Shown in place of the icon when the theme has none.
+    pub fn emoji(self, is_day: bool) -> &'static str {
+        match (self, is_day) {
+            (Condition::Clear, true)         => "☀",
+            (Condition::Clear, false)        => "🌙",
+            (Condition::PartlyCloudy, true)  => "⛅",
+            (Condition::PartlyCloudy, false) => "☁",
+            (Condition::Cloudy, _)           => "☁",
+            (Condition::Fog, _)              => "🌫",
+            (Condition::Drizzle, _)          => "🌦",
+            (Condition::Rain, _)             => "🌧",
+            (Condition::Showers, _)          => "🌦",
+            (Condition::Snow, _)             => "❄",
+            (Condition::Storm, _)            => "⛈",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    /// Where the provider says the report is for.
+    pub place:       String,
+    pub temperature: f64,
+    pub units:       Units,
+    pub condition:   Condition,
+    pub is_day:      bool,
+    /// The provider's own words where it has them, else `Condition::describe`.
+    pub description: String,
+    /// Unix seconds.
+    pub fetched:     u64,
+}
+
+impl Report {
+    /// Whole degrees with the unit, e.g. `-3°C`.
+    pub fn temperature_text(&self) -> String {
+        // Adding 0.0 turns the -0.0 that rounds from small negatives into 0.
+        format!("{}{}", self.temperature.round() + 0.0, self.units.symbol())
+    }
+
+    /// Time since it was fetched.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.fetched))
+    }
+}
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+pub trait Provider: Send {
+    /// The weather at `location` (a place name or `lat,lon`) now.
+    fn current(&mut self, location: &str, units: Units) -> Result<Report, Box<dyn Error>>;
+}
+
+/// The provider `weather-provider` names.
+pub fn provider(name: &str) -> Option<Box<dyn Provider>> {
+    match name.trim() {
+        "open-meteo" | ""  => Some(Box::new(OpenMeteo::default())),
+        "wttr.in" | "wttr" => Some(Box::new(WttrIn)),
+        other              => {
+            tracing::warn!(target: "config", "weather-provider: expected \"open-meteo\" or \"wttr.in\", not {other:?}");
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct OpenMeteo {
+    /// (location, place, latitude, longitude) of the last geocoded name.
+    geocoded: Option<(String, String, f64, f64)>,
+}
+
+impl OpenMeteo {
+    fn locate(&mut self, location: &str) -> Result<(String, f64, f64), Box<dyn Error>> {
+        if let Some((lat, lon)) = parse_coordinates(location) { return Ok((location.to_string(), lat, lon)); }
+        if location.is_empty() { return Err("weather-location is empty; Open-Meteo needs a place or \"lat,lon\"".into()); }
+        if let Some((_, place, lat, lon)) = self.geocoded.as_ref().filter(|g| g.0 == location) {
+            return Ok((place.clone(), *lat, *lon));
+        }
+        let body = fetch(&format!("{OPEN_METEO_GEOCODING}?name={}&count=1", percent_encode(location)))?;
+        let found = parse_geocoding(&body).ok_or_else(|| format!("no place called {location:?}"))?;
+        self.geocoded = Some((location.to_string(), found.0.clone(), found.1, found.2));
+        Ok(found)
+    }
+}
+
+impl Provider for OpenMeteo {
+    fn current(&mut self, location: &str, units: Units) -> Result<Report, Box<dyn Error>> {
+        let (place, lat, lon) = self.locate(location)?;
+        let mut url = format!("{OPEN_METEO_FORECAST}?latitude={lat}&longitude={lon}&current=temperature_2m,weather_code,is_day");
+        if units == Units::Imperial { url.push_str("&temperature_unit=fahrenheit"); }
+        parse_open_meteo(&fetch(&url)?, place, units).ok_or_else(|| "unexpected response from Open-Meteo".into())
+    }
+}
+
+/// `lat,lon` in degrees, e.g. `52.52,13.41`.
+fn parse_coordinates(location: &str) -> Option<(f64, f64)> {
+    let (lat, lon) = location.split_once(',')?;
+    let (lat, lon): (f64, f64) = (lat.trim().parse().ok()?, lon.trim().parse().ok()?);
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)).then_some((lat, lon))
+}
+
+/// The first result's (place, latitude, longitude), the place as
+/// `Name, Region, Country` with whichever parts are there.
+fn parse_geocoding(body: &str) -> Option<(String, f64, f64)> {
+    let json  = Json::parse(body)?;
+    let first = json.get("results")?.as_array().first()?;
+    let place: Vec<&str> = ["name", "admin1", "country"].iter()
+        .filter_map(|key| first.get(key).and_then(Json::as_str))
+        .collect();
+    Some((place.join(", "), first.get("latitude")?.as_f64()?, first.get("longitude")?.as_f64()?))
+}
+
+fn parse_open_meteo(body: &str, place: String, units: Units) -> Option<Report> {
+    let json    = Json::parse(body)?;
+    let current = json.get("current")?;
+    let condition = Condition::from_wmo(current.get("weather_code")?.as_f64()? as u32);
+    Some(Report {
+        place,
+        temperature: current.get("temperature_2m")?.as_f64()?,
+        units,
+        condition,
+        is_day:      current.get("is_day").and_then(Json::as_f64).is_none_or(|d| d != 0.0),
+        description: condition.describe().to_string(),
+        fetched:     now_secs(),
+    })
+}
+
+struct WttrIn;
+
+impl Provider for WttrIn {
+    fn current(&mut self, location: &str, units: Units) -> Result<Report, Box<dyn Error>> {
+        let body = fetch(&format!("{WTTR_IN}/{}?format=j1", percent_encode(location)))?;
+        parse_wttr(&body, units).ok_or_else(|| "unexpected response from wttr.in".into())
+    }
+}
+
+/// wttr.in's `j1` format: numbers come as strings, and whether it's day is
+/// worked out from the observation time against today's sunrise and sunset.
+fn parse_wttr(body: &str, units: Units) -> Option<Report> {
+    let json    = Json::parse(body)?;
+    let current = json.get("current_condition")?.as_array().first()?;
+    let text    = |v: &Json, key: &str| v.get(key).and_then(Json::as_str).map(str::to_string);
+    let first_value = |v: &Json, key: &str| v.get(key)?.as_array().first().and_then(|x| text(x, "value"));
+
+    let temp_key  = if units == Units::Imperial { "temp_F" } else { "temp_C" };
+    let condition = Condition::from_wwo(text(current, "weatherCode")?.parse().ok()?);
+    let area  = json.get("nearest_area").and_then(|a| a.as_array().first().cloned());
+    let place = area.map(|a| {
+        let parts: Vec<String> = ["areaName", "region", "country"].iter()
+            .filter_map(|key| first_value(&a, key))
+            .filter(|s| !s.is_empty())
+            .collect();
+        parts.join(", ")
+    });
+    let astronomy = json.get("weather")
+        .and_then(|w| w.as_array().first()?.get("astronomy")?.as_array().first().cloned());
+    let observed  = text(current, "localObsDateTime")
+        .and_then(|t| t.split_once(' ').and_then(|(_, time)| clock_minutes(time)));
+    let is_day = match (astronomy, observed) {
+        (Some(a), Some(now)) => {
+            let rise = text(&a, "sunrise").and_then(|t| clock_minutes(&t));
+            let set  = text(&a, "sunset").and_then(|t| clock_minutes(&t));
+            match (rise, set) {
+                (Some(rise), Some(set)) => (rise..set).contains(&now),
+                _                       => true,
+            }
+        }
+        _ => true,
+    };
+
+    Some(Report {
+        place:       place.unwrap_or_default(),
+        temperature: text(current, temp_key)?.parse().ok()?,
+        units,
+        condition,
+        is_day,
+        description: first_value(current, "weatherDesc").unwrap_or_else(|| condition.describe().to_string()),
+        fetched:     now_secs(),
+    })
+}
+
+/// `07:05 AM` as minutes since midnight.
+fn clock_minutes(time: &str) -> Option<u32> {
+    let (clock, half) = time.trim().split_once(' ')?;
+    let (h, m) = clock.split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    let h = match half {
+        "AM" => h % 12,
+        "PM" => h % 12 + 12,
+        _    => return None,
+    };
+    Some(h * 60 + m)
+}
+
+fn fetch(url: &str) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("curl").args(["-fsSL", "--max-time", "10", url]).output()?;
+    if !output.status.success() { return Err(format!("curl exited with {}", output.status).into()); }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Escape everything but unreserved characters for a URL.
+fn percent_encode(s: &str) -> String {
+    s.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// ============================================================================
+// Cache
+// ============================================================================
+
+fn cache_file() -> PathBuf {
+    crate::paths::cache_home().join(CACHE_FILE)
+}
+
+/// What a cached report must match to be shown: provider, location, units.
+fn cache_key(config: &Config, units: Units) -> String {
+    format!("{}|{}|{}", config.weather_provider.trim(), config.weather_location.trim(), units.name())
+}
+
+fn serialize_report(key: &str, report: &Report) -> String {
+    format!(
+        "{CACHE_VERSION}\t{key}\nfetched\t{}\nplace\t{}\ntemperature\t{}\nunits\t{}\ncondition\t{}\nday\t{}\ndescription\t{}\n",
+        report.fetched, report.place, report.temperature, report.units.name(),
+        report.condition.describe(), report.is_day, report.description,
+    )
+}
+
+/// The cached report, if it was made for `key`.
+fn deserialize_report(s: &str, key: &str) -> Option<Report> {
+    let mut lines = s.lines();
+    if lines.next()?.strip_prefix(CACHE_VERSION)?.strip_prefix('\t')? != key { return None; }
+    let fields: Vec<(&str, &str)> = lines.filter_map(|l| l.split_once('\t')).collect();
+    let field = |name: &str| fields.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+    let condition = [
+        Condition::Clear, Condition::PartlyCloudy, Condition::Cloudy, Condition::Fog, Condition::Drizzle,
+        Condition::Rain, Condition::Showers, Condition::Snow, Condition::Storm,
+    ].into_iter().find(|c| Some(c.describe()) == field("condition"))?;
+    Some(Report {
+        place:       field("place")?.to_string(),
+        temperature: field("temperature")?.parse().ok()?,
+        units:       if field("units")? == "imperial" { Units::Imperial } else { Units::Metric },
+        condition,
+        is_day:      field("day")? == "true",
+        description: field("description")?.to_string(),
+        fetched:     field("fetched")?.parse().ok()?,
+    })
+}
+
+// ============================================================================
+// Host
+// ============================================================================
+
+#[derive(Clone, Debug, Default)]
+pub struct WeatherState {
+    pub report: Option<Report>,
+    /// The report is older than the refresh interval: the last fetch failed.
+    pub stale:  bool,
+    /// Why the last fetch failed.
+    pub error:  Option<String>,
+    /// The icon theme's file for the report's condition.
+    pub icon:   Option<String>,
+}
+
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+/// The current weather, kept fresh from a background thread.
+pub struct WeatherHost {
+    state:   Arc<Mutex<WeatherState>>,
+    refresh: mpsc::Sender<()>,
+}
+
+impl WeatherHost {
+    /// Shows the cached report and starts fetching; `on_change` runs (from a
+    /// background thread) whenever the state changes.
+    pub fn new(config: &Config, on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        let state = Arc::new(Mutex::new(WeatherState::default()));
+        let (refresh, rx) = mpsc::channel();
+        if let Some(provider) = provider(&config.weather_provider) {
+            let shared = Arc::clone(&state);
+            let waker: Waker = Arc::new(on_change);
+            let config = config.clone();
+            thread::spawn(move || follow(&config, provider, &shared, &waker, &rx));
+        }
+        WeatherHost { state, refresh }
+    }
+
+    pub fn state(&self) -> WeatherState {
+        self.state.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Fetch now rather than at the next interval.
+    pub fn refresh(&self) {
+        let _ = self.refresh.send(());
+    }
+}
+
+fn follow(config: &Config, mut provider: Box<dyn Provider>, shared: &Mutex<WeatherState>, waker: &Waker, rx: &mpsc::Receiver<()>) {
+    let units    = Units::parse(&config.weather_units);
+    let location = config.weather_location.trim();
+    let key      = cache_key(config, units);
+    let every    = Duration::from_secs(config.weather_refresh_minutes.max(1) * 60);
+    let publish  = |report: Option<Report>, error: Option<String>| {
+        let icon = report.as_ref().and_then(|r| crate::icon_theme::lookup(r.condition.icon(r.is_day), ICON_SIZE, config));
+        if let Ok(mut state) = shared.lock() {
+            state.stale  = error.is_some();
+            state.error  = error;
+            state.report = report;
+            state.icon   = icon;
+        }
+        waker();
+    };
+
+    let mut report = fs::read_to_string(cache_file()).ok().and_then(|s| deserialize_report(&s, &key));
+    let mut wait = Duration::ZERO;
+    if let Some(cached) = &report {
+        wait = every.saturating_sub(cached.age());
+        publish(report.clone(), None);
+    }
+
+    loop {
+        if let Err(RecvTimeoutError::Disconnected) = rx.recv_timeout(wait) { return; }
+        match provider.current(location, units) {
+            Ok(fresh) => {
+                let path = cache_file();
+                let saved = path.parent().map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::write(&path, serialize_report(&key, &fresh)));
+                if let Err(e) = saved { tracing::warn!(target: "weather", "Failed to save the weather cache: {e}"); }
+                report = Some(fresh);
+                publish(report.clone(), None);
+                wait = every;
+            }
+            Err(e) => {
+                tracing::warn!(target: "weather", "Failed to fetch the weather: {e}");
+                publish(report.clone(), Some(e.to_string()));
+                wait = RETRY.min(every);
+            }
+        }
+        // Clicks that came in during the fetch are answered by it.
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_meteo() {
+        let body = r#"{"latitude":52.52,"longitude":13.4,"current":{"time":"2026-01-10T22:00","temperature_2m":-0.3,"weather_code":71,"is_day":0}}"#;
+        let report = parse_open_meteo(body, "Berlin".into(), Units::Metric).unwrap();
+        assert_eq!((report.condition, report.is_day), (Condition::Snow, false));
+        assert_eq!(report.temperature_text(), "0°C");
+        assert_eq!(report.condition.icon(report.is_day), "weather-snow");
+
+        let geo = r#"{"results":[{"name":"Berlin","latitude":52.52,"longitude":13.41,"country":"Germany","admin1":"Land Berlin"}]}"#;
+        assert_eq!(parse_geocoding(geo), Some(("Berlin, Land Berlin, Germany".into(), 52.52, 13.41)));
+        assert_eq!(parse_geocoding(r#"{"generationtime_ms":0.5}"#), None);
+        assert_eq!(parse_coordinates("52.52, 13.41"), Some((52.52, 13.41)));
+        assert_eq!(parse_coordinates("Paris, France"), None);
+    }
+
+    #[test]
+    fn test_parse_wttr() {
+        let body = r#"{
+            "current_condition": [{"localObsDateTime": "2026-07-01 09:40 PM", "temp_C": "18", "temp_F": "64",
+                                   "weatherCode": "116", "weatherDesc": [{"value": "Partly cloudy"}]}],
+            "nearest_area": [{"areaName": [{"value": "Lisbon"}], "region": [{"value": ""}], "country": [{"value": "Portugal"}]}],
+            "weather": [{"astronomy": [{"sunrise": "06:15 AM", "sunset": "09:05 PM"}]}]
+        }"#;
+        let report = parse_wttr(body, Units::Imperial).unwrap();
+        assert_eq!(report.place, "Lisbon, Portugal");
+        assert_eq!(report.temperature_text(), "64°F");
+        assert_eq!((report.condition, report.is_day), (Condition::PartlyCloudy, false));
+        assert_eq!(clock_minutes("12:30 AM"), Some(30));
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let report = Report {
+            place: "Oslo".into(), temperature: -4.5, units: Units::Metric, condition: Condition::Fog,
+            is_day: true, description: "Freezing fog".into(), fetched: 1_700_000_000,
+        };
+        let text = serialize_report("open-meteo|Oslo|metric", &report);
+        assert_eq!(deserialize_report(&text, "open-meteo|Oslo|metric"), Some(report));
+        assert_eq!(deserialize_report(&text, "open-meteo|Bergen|metric"), None);
+        assert_eq!(percent_encode("São Paulo"), "S%C3%A3o%20Paulo");
+    }
+}
@@ -0,0 +1,115 @@
+//! Open-window listing and focusing through compositor IPC.
+//!
+//! Backends, picked from the environment:
+//! - Hyprland (`$HYPRLAND_INSTANCE_SIGNATURE`): `hyprctl clients -j` / `dispatch focuswindow`
+//! - Sway (`$SWAYSOCK`): `swaymsg -t get_tree` / `[con_id=N] focus`
+//! - X11 (`$DISPLAY`): `wmctrl -lx` / `wmctrl -ia`
+//!
+//! wlr-foreign-toplevel-management would cover other wlroots compositors but
+//! needs a Wayland client library; these CLIs are what's available in-tree.
+use std::env;
+use std::process::Command;
+use crate::json::Json;
+
+#[derive(Clone, Debug)]
+pub struct Window {
+    /// Backend-specific handle passed back to `focus`.
+    pub id:    String,
+    pub title: String,
+    /// App id / WM_CLASS, lowercased — also used as the icon name.
+    pub class: String,
+}
+
+/// The compositor IPC in use; `Wmctrl` stands for X11 in general.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Backend { Hyprland, Sway, Wmctrl }
+
+pub(crate) fn backend() -> Option<Backend> {
+    if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() { return Some(Backend::Hyprland); }
+    if env::var_os("SWAYSOCK").is_some()                     { return Some(Backend::Sway); }
+    if env::var_os("DISPLAY").is_some()                      { return Some(Backend::Wmctrl); }
+    None
+}
+
+/// All mapped top-level windows, in the compositor's order. Empty when no
+/// backend is available.
+pub fn list() -> Vec<Window> {
+    let result = match backend() {
+        Some(Backend::Hyprland) => run(&["hyprctl", "clients", "-j"]).map(|s| parse_hyprland(&s)),
+        Some(Backend::Sway)     => run(&["swaymsg", "-t", "get_tree", "-r"]).map(|s| parse_sway(&s)),
+        Some(Backend::Wmctrl)   => run(&["wmctrl", "-lx"]).map(|s| parse_wmctrl(&s)),
+        None                    => None,
+    };
+    result.unwrap_or_default()
+}
+
+/// Raise and focus the window `id` (as returned by `list`).
+pub fn focus(id: &str) -> bool {
+    let ok = match backend() {
+        Some(Backend::Hyprland) => run(&["hyprctl", "dispatch", "focuswindow", &format!("address:{id}")]),
+        Some(Backend::Sway)     => run(&["swaymsg", &format!("[con_id={id}]"), "focus"]),
+        Some(Backend::Wmctrl)   => run(&["wmctrl", "-ia", id]),
+        None                    => None,
+    };
+    ok.is_some()
+}
+
+/// stdout of `argv` when it exits successfully.
+pub(crate) fn run(argv: &[&str]) -> Option<String> {
+    let output = Command::new(argv[0]).args(&argv[1..]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_hyprland(s: &str) -> Vec<Window> {
+    let Some(clients) = Json::parse(s) else { return Vec::new() };
+    clients.as_array().iter()
+        .filter(|c| c.get("mapped").and_then(Json::as_bool) != Some(false))
+        .filter_map(|c| Some(Window {
+            id:    c.get("address")?.as_str()?.to_string(),
+            title: c.get("title").and_then(Json::as_str).unwrap_or_default().to_string(),
+            class: c.get("class").and_then(Json::as_str).unwrap_or_default().to_lowercase(),
+        }))
+        .collect()
+}
+
+fn parse_sway(s: &str) -> Vec<Window> {
+    fn walk(node: &Json, out: &mut Vec<Window>) {
+        // Leaf containers with a pid are real windows (Wayland or XWayland).
+        if node.get("pid").is_some() {
+            let class = node.get("app_id").and_then(Json::as_str)
+                .or_else(|| node.get("window_properties").and_then(|p| p.get("class")).and_then(Json::as_str))
+                .unwrap_or_default();
+            if let Some(id) = node.get("id").and_then(Json::as_f64) {
+                out.push(Window {
+                    id:    (id as i64).to_string(),
+                    title: node.get("name").and_then(Json::as_str).unwrap_or_default().to_string(),
+                    class: class.to_lowercase(),
+                });
+            }
+        }
+        for key in ["nodes", "floating_nodes"] {
+            for child in node.get(key).map(Json::as_array).unwrap_or_default() { walk(child, out); }
+        }
+    }
+    let mut out = Vec::new();
+    if let Some(tree) = Json::parse(s) { walk(&tree, &mut out); }
+    out
+}
+
+/// `0x03a00003  0 firefox.Firefox  host Title words…`
+fn parse_wmctrl(s: &str) -> Vec<Window> {
+    s.lines().filter_map(|line| {
+        let mut cols = line.split_whitespace();
+        let id      = cols.next()?;
+        let desktop = cols.next()?;
+        let class   = cols.next()?;
+        let _host   = cols.next()?;
+        // Desktop -1 marks sticky panels/docks.
+        if desktop == "-1" { return None; }
+        Some(Window {
+            id:    id.to_string(),
+            title: cols.collect::<Vec<_>>().join(" "),
+            class: class.rsplit('.').next().unwrap_or(class).to_lowercase(),
+        })
+    }).collect()
+}
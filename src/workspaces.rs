@@ -0,0 +1,330 @@
+//! Workspace list for the `.workspaces` section: which exist, which one is
+//! focused, which hold windows or want attention, and switching between them.
+//!
+//! Design:
+//! - Same compositor CLIs as the window switcher: `hyprctl workspaces`,
+//!   `monitors` and `clients` on Hyprland, `swaymsg -t get_workspaces` and
+//!   `get_tree` on Sway. X11 desktops aren't covered; the section stays
+//!   empty there.
+//! - Instead of polling, a thread follows the compositor's events (Hyprland's
+//!   `.socket2.sock`, `swaymsg -t subscribe -m` on Sway) and re-reads the
+//!   list once they settle; a switch is several events in a row. When the
+//!   event stream ends (compositor restart) it reconnects after a pause;
+//!   when it can't be opened at all the list is read once and left.
+//! - Sway reports urgency per workspace. Hyprland only sends `urgent>>ADDR`
+//!   for a window, so the addresses are kept until that window is focused
+//!   and mapped to workspaces through the client list.
+//! - Special (scratchpad) workspaces are left out.
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::json::Json;
+use crate::windows::{backend, run, Backend};
+
+/// Events closer together than this are read as one change.
+const SETTLE: Duration = Duration::from_millis(50);
+/// Wait before reconnecting to an event stream that ended.
+const RECONNECT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workspace {
+    /// What `switch` is given: the number, or the name for named ones.
+    pub id:      String,
+    pub name:    String,
+    pub output:  String,
+    /// The workspace with keyboard focus.
+    pub focused: bool,
+    /// Shown on some output (always true for the focused one).
+    pub visible: bool,
+    pub windows: usize,
+    /// A window on it asked for attention.
+    pub urgent:  bool,
+}
+
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+/// The workspace list, kept current from a background thread.
+pub struct WorkspacesHost {
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+}
+
+impl WorkspacesHost {
+    /// Starts following the compositor; `on_change` runs (from a background
+    /// thread) whenever the list changes.
+    pub fn new(on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        let workspaces = Arc::new(Mutex::new(Vec::new()));
+        if let Some(backend @ (Backend::Hyprland | Backend::Sway)) = backend() {
+            let shared = Arc::clone(&workspaces);
+            let waker: Waker = Arc::new(on_change);
+            thread::spawn(move || follow(backend, &shared, &waker));
+        }
+        WorkspacesHost { workspaces }
+    }
+
+    pub fn list(&self) -> Vec<Workspace> {
+        self.workspaces.lock().map(|w| w.clone()).unwrap_or_default()
+    }
+}
+
+/// Ask the compositor to show `ws` (on its output, focusing it).
+pub fn switch(ws: &Workspace) -> bool {
+    let done = match backend() {
+        Some(Backend::Hyprland) => run(&["hyprctl", "dispatch", "workspace", &ws.id]),
+        Some(Backend::Sway)     => {
+            let name = ws.id.replace('\\', "\\\\").replace('"', "\\\"");
+            run(&["swaymsg", &format!("workspace --no-auto-back-and-forth \"{name}\"")])
+        }
+        Some(Backend::Wmctrl) | None => None,
+    };
+    if done.is_none() { tracing::warn!(target: "window", "Failed to switch to workspace {}", ws.name); }
+    done.is_some()
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+fn follow(backend: Backend, shared: &Mutex<Vec<Workspace>>, waker: &Waker) {
+    let (tx, rx) = mpsc::channel::<String>();
+    thread::spawn(move || loop {
+        let ended = match backend {
+            Backend::Hyprland => hyprland_events(&tx),
+            _                 => sway_events(&tx),
+        };
+        if let Err(e) = ended {
+            tracing::warn!(target: "window", "Workspace events unavailable: {e}");
+            return;
+        }
+        tracing::debug!(target: "window", "Workspace events ended; reconnecting");
+        thread::sleep(RECONNECT);
+        if tx.send(String::new()).is_err() { return; }
+    });
+
+    // Hyprland window addresses that asked for attention, as `0x…`.
+    let mut urgent: HashSet<String> = HashSet::new();
+    let mut event = Some(String::new());
+    while let Some(first) = event {
+        if matters(&first, &mut urgent) {
+            while let Ok(more) = rx.recv_timeout(SETTLE) { matters(&more, &mut urgent); }
+            let list = match backend {
+                Backend::Hyprland => read_hyprland(&urgent),
+                _                 => read_sway(),
+            };
+            if let Some(list) = list {
+                let changed = match shared.lock() {
+                    Ok(mut current) if *current != list => { *current = list; true }
+                    _                                   => false,
+                };
+                if changed { waker(); }
+            }
+        }
+        event = rx.recv().ok();
+    }
+}
+
+/// Whether an event line can change the list; title changes and the like
+/// come often and don't. An empty line (start, reconnect) always does.
+/// Also keeps track of Hyprland's urgent windows: `urgent>>ADDR` until that
+/// window is focused or closed.
+fn matters(line: &str, urgent: &mut HashSet<String>) -> bool {
+    if line.is_empty() { return true; }
+    if line.starts_with('{') {
+        let event  = Json::parse(line);
+        let change = event.as_ref().and_then(|e| e.get("change")).and_then(Json::as_str);
+        return !matches!(change, Some("title" | "mark"));
+    }
+    let Some((name, data)) = line.split_once(">>") else { return false };
+    match name {
+        "urgent"                         => urgent.insert(format!("0x{data}")),
+        "activewindowv2" | "closewindow" => urgent.remove(&format!("0x{data}")) || name == "closewindow",
+        _                                => name.contains("workspace") || name.starts_with("focusedmon")
+            || matches!(name, "openwindow" | "movewindow" | "movewindowv2"),
+    }
+}
+
+/// Forward Hyprland's event lines until the socket closes.
+fn hyprland_events(tx: &mpsc::Sender<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")?;
+    // Hyprland 0.40 moved its sockets from /tmp/hypr to the runtime dir.
+    let candidates = [
+        crate::paths::runtime_dir().map(|d| d.join("hypr")),
+        Some(PathBuf::from("/tmp/hypr")),
+    ];
+    let socket = candidates.into_iter().flatten()
+        .map(|dir| dir.join(&signature).join(".socket2.sock"))
+        .find(|path| path.exists())
+        .ok_or("no Hyprland event socket")?;
+    for line in BufReader::new(UnixStream::connect(socket)?).lines() {
+        let Ok(line) = line else { break };
+        if tx.send(line).is_err() { break; }
+    }
+    Ok(())
+}
+
+/// Forward Sway's workspace and window events, one JSON object per line,
+/// until swaymsg exits.
+fn sway_events(tx: &mpsc::Sender<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut child = Command::new("swaymsg").args(["-t", "subscribe", "-m", "-r", r#"["workspace","window"]"#])
+        .stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child.stdout.take().ok_or("no stdout")?;
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else { break };
+        if tx.send(line).is_err() { break; }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(())
+}
+
+// ============================================================================
+// Reading the list
+// ============================================================================
+
+fn read_hyprland(urgent: &HashSet<String>) -> Option<Vec<Workspace>> {
+    let workspaces = run(&["hyprctl", "workspaces", "-j"])?;
+    let monitors   = run(&["hyprctl", "monitors", "-j"])?;
+    let clients    = if urgent.is_empty() { String::new() } else { run(&["hyprctl", "clients", "-j"]).unwrap_or_default() };
+    Some(parse_hyprland(&workspaces, &monitors, &clients, urgent))
+}
+
+fn parse_hyprland(workspaces: &str, monitors: &str, clients: &str, urgent: &HashSet<String>) -> Vec<Workspace> {
+    let id_of = |node: Option<&Json>| node.and_then(|w| w.get("id")).and_then(Json::as_f64).map(|id| id as i64);
+    let monitors = Json::parse(monitors);
+    let monitors = monitors.as_ref().map(Json::as_array).unwrap_or_default();
+    let visible: Vec<i64> = monitors.iter().filter_map(|m| id_of(m.get("activeWorkspace"))).collect();
+    let focused = monitors.iter()
+        .find(|m| m.get("focused").and_then(Json::as_bool) == Some(true))
+        .and_then(|m| id_of(m.get("activeWorkspace")));
+    let clients = Json::parse(clients);
+    let urgent_on: Vec<i64> = clients.as_ref().map(Json::as_array).unwrap_or_default().iter()
+        .filter(|c| c.get("address").and_then(Json::as_str).is_some_and(|a| urgent.contains(a)))
+        .filter_map(|c| id_of(c.get("workspace")))
+        .collect();
+
+    let Some(list) = Json::parse(workspaces) else { return Vec::new() };
+    let mut found: Vec<(i64, Workspace)> = list.as_array().iter()
+        .filter_map(|w| {
+            let id   = id_of(Some(w))?;
+            let name = w.get("name").and_then(Json::as_str).unwrap_or_default().to_string();
+            if name == "special" || name.starts_with("special:") { return None; }
+            let ws = Workspace {
+                // Named workspaces have negative ids and are switched to by name.
+                id:      if id > 0 { id.to_string() } else { format!("name:{name}") },
+                output:  w.get("monitor").and_then(Json::as_str).unwrap_or_default().to_string(),
+                focused: focused == Some(id),
+                visible: visible.contains(&id),
+                windows: w.get("windows").and_then(Json::as_f64).unwrap_or(0.0) as usize,
+                urgent:  urgent_on.contains(&id),
+                name,
+            };
+            Some((id, ws))
+        })
+        .collect();
+    // Numbered ones in order, then the named ones.
+    found.sort_by_key(|(id, _)| (*id < 0, id.abs()));
+    found.into_iter().map(|(_, ws)| ws).collect()
+}
+
+fn read_sway() -> Option<Vec<Workspace>> {
+    let workspaces = run(&["swaymsg", "-t", "get_workspaces", "-r"])?;
+    let tree       = run(&["swaymsg", "-t", "get_tree", "-r"]).unwrap_or_default();
+    Some(parse_sway(&workspaces, &tree))
+}
+
+fn parse_sway(workspaces: &str, tree: &str) -> Vec<Workspace> {
+    /// Windows below `node`: containers with a pid, tiled or floating.
+    fn count(node: &Json) -> usize {
+        let own = usize::from(node.get("pid").is_some());
+        own + ["nodes", "floating_nodes"].iter()
+            .flat_map(|key| node.get(key).map(Json::as_array).unwrap_or_default())
+            .map(count)
+            .sum::<usize>()
+    }
+    fn walk(node: &Json, out: &mut Vec<(String, usize)>) {
+        if node.get("type").and_then(Json::as_str) == Some("workspace") {
+            out.push((node.get("name").and_then(Json::as_str).unwrap_or_default().to_string(), count(node)));
+            return;
+        }
+        for child in node.get("nodes").map(Json::as_array).unwrap_or_default() { walk(child, out); }
+    }
+    let mut windows = Vec::new();
+    if let Some(tree) = Json::parse(tree) { walk(&tree, &mut windows); }
+
+    let Some(list) = Json::parse(workspaces) else { return Vec::new() };
+    list.as_array().iter()
+        .filter_map(|w| {
+            let name = w.get("name").and_then(Json::as_str)?.to_string();
+            let flag = |key: &str| w.get(key).and_then(Json::as_bool).unwrap_or(false);
+            Some(Workspace {
+                id:      name.clone(),
+                output:  w.get("output").and_then(Json::as_str).unwrap_or_default().to_string(),
+                focused: flag("focused"),
+                visible: flag("visible"),
+                windows: windows.iter().find(|(n, _)| *n == name).map_or(0, |(_, n)| *n),
+                urgent:  flag("urgent"),
+                name,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let workspaces = r#"[
+            {"id": 2, "name": "2", "monitor": "DP-1", "windows": 0},
+            {"id": -1337, "name": "mail", "monitor": "HDMI-A-1", "windows": 1},
+            {"id": 1, "name": "1", "monitor": "DP-1", "windows": 3},
+            {"id": -98, "name": "special:magic", "monitor": "DP-1", "windows": 1}
+        ]"#;
+        let monitors = r#"[
+            {"name": "DP-1", "focused": true, "activeWorkspace": {"id": 2, "name": "2"}},
+            {"name": "HDMI-A-1", "focused": false, "activeWorkspace": {"id": -1337, "name": "mail"}}
+        ]"#;
+        let clients = r#"[{"address": "0x55aa", "workspace": {"id": 1, "name": "1"}}]"#;
+        let urgent  = HashSet::from(["0x55aa".to_string()]);
+        let list = parse_hyprland(workspaces, monitors, clients, &urgent);
+        let brief: Vec<(&str, bool, bool, usize, bool)> = list.iter()
+            .map(|w| (w.id.as_str(), w.focused, w.visible, w.windows, w.urgent))
+            .collect();
+        assert_eq!(brief, [("1", false, false, 3, true), ("2", true, true, 0, false), ("name:mail", false, true, 1, false)]);
+
+        let mut marks = HashSet::new();
+        assert!(matters("urgent>>55aa", &mut marks));
+        assert!(marks.contains("0x55aa"));
+        assert!(!matters("windowtitle>>55aa", &mut marks));
+        assert!(matters("activewindowv2>>55aa", &mut marks));
+        assert!(!matters("activewindowv2>>55aa", &mut marks));
+        assert!(matters("workspacev2>>2,2", &mut marks));
+        assert!(!matters(r#"{"change":"title","container":{}}"#, &mut marks));
+        assert!(matters(r#"{"change":"new","container":{}}"#, &mut marks));
+
+        let workspaces = r#"[
+            {"num": 1, "name": "1", "focused": false, "visible": false, "urgent": true, "output": "eDP-1"},
+            {"num": 2, "name": "2: web", "focused": true, "visible": true, "urgent": false, "output": "eDP-1"}
+        ]"#;
+        let tree = r#"{"type": "root", "nodes": [{"type": "output", "name": "eDP-1", "nodes": [
+            {"type": "workspace", "name": "1", "nodes": [{"type": "con", "pid": 10, "nodes": []},
+                {"type": "con", "nodes": [{"type": "con", "pid": 11}]}], "floating_nodes": [{"type": "floating_con", "pid": 12}]},
+            {"type": "workspace", "name": "2: web", "nodes": []}
+        ]}]}"#;
+        let list = parse_sway(workspaces, tree);
+        let brief: Vec<(&str, bool, usize, bool)> = list.iter().map(|w| (w.id.as_str(), w.focused, w.windows, w.urgent)).collect();
+        assert_eq!(brief, [("1", false, 3, true), ("2: web", true, 0, false)]);
+    }
+}
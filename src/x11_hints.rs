@@ -0,0 +1,71 @@
+//! Popup behaviour on X11: hints that make tiling window managers (i3,
+//! bspwm, …) float the launcher instead of tiling it.
+//!
+//! Design:
+//! - The window type (`x11-window-type`, `dialog` by default) is set by
+//!   winit before the window is mapped, which is when WMs read it; i3 and
+//!   bspwm float dialogs and utility windows.
+//! - Sticky, above and skip-taskbar/pager are `_NET_WM_STATE` changes the WM
+//!   has to make, so they're asked for with `wmctrl` once the window is up;
+//!   WMs drop them when the window is hidden, so it's repeated on each show.
+//! - `x11-override-redirect = true` takes the window away from the WM
+//!   altogether (no borders, no tiling, no focus stealing rules). It can only
+//!   change while unmapped, so `xdotool` unmaps, flips it and maps again,
+//!   then gives the window focus, which no WM will do for it.
+//! - The window is centered (or anchored) by the launcher itself on X11, as
+//!   floating windows land wherever the WM puts them.
+use crate::windows::{backend, run, Backend};
+
+/// The window is an X11 one, where these hints apply. With a Wayland
+/// display too, winit picks Wayland.
+pub fn active() -> bool {
+    matches!(backend(), Some(Backend::Wmctrl)) && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+/// `x11-window-type` values, as the `_NET_WM_WINDOW_TYPE` they stand for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowType {
+    Normal,
+    Dialog,
+    Utility,
+}
+
+impl WindowType {
+    pub fn parse(s: &str) -> Option<WindowType> {
+        match s.trim() {
+            "normal"  => Some(WindowType::Normal),
+            "dialog"  => Some(WindowType::Dialog),
+            "utility" => Some(WindowType::Utility),
+            _         => None,
+        }
+    }
+}
+
+/// Ask the WM to keep this process's windows on every workspace, above the
+/// rest and out of taskbars; with `override_redirect`, unmanage them.
+pub fn apply(override_redirect: bool) {
+    let pid = std::process::id().to_string();
+    let Some(ids) = run(&["xdotool", "search", "--pid", &pid]) else {
+        tracing::warn!(target: "window", "Can't find the launcher window to set its X11 hints (is xdotool installed?)");
+        return;
+    };
+    for id in ids.split_whitespace() {
+        if override_redirect {
+            let done = run(&[
+                "xdotool", "windowunmap", "--sync", id,
+                "set_window", "--overrideredirect", "1", id,
+                "windowmap", "--sync", id,
+                "windowfocus", id,
+            ]);
+            if done.is_none() { tracing::warn!(target: "window", "Failed to set override-redirect on window {id}"); }
+            continue;
+        }
+        // wmctrl changes at most two states per call.
+        for states in ["add,sticky,above", "add,skip_taskbar,skip_pager"] {
+            if run(&["wmctrl", "-i", "-r", id, "-b", states]).is_none() {
+                tracing::warn!(target: "window", "Failed to set {states} on window {id} (is wmctrl installed?)");
+                break;
+            }
+        }
+    }
+}
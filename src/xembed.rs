@@ -0,0 +1,342 @@
+//! XEmbed system tray (freedesktop System Tray Protocol) for old X11 apps
+//! that never learned StatusNotifierItem. Built with `--features xembed` and
+//! enabled by `enable-xembed-tray: true`.
+//!
+//! Design:
+//! - Claims `_NET_SYSTEM_TRAY_S<screen>` only when nobody else holds it, so a
+//!   real X11 panel keeps its icons.
+//! - Each docking icon is reparented into an offscreen container that
+//!   Composite redirects, so it keeps painting without being on screen.
+//! - Icon pixels are snapshotted with GetImage and pushed into the shared
+//!   `TrayItems` as ordinary `TrayIcon`s (`bus_name` = `BUS_NAME`,
+//!   `obj_path` = the icon window id), so the GUI draws them like SNI items.
+//! - Clicks and scrolls come back as synthetic button events on the icon.
+use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11rb::connection::Connection;
+use x11rb::protocol::composite::{ConnectionExt as _, Redirect};
+use x11rb::protocol::xproto::*;
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{CURRENT_TIME, NONE};
+
+use crate::sni::{TrayIcon, TrayItems};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// `TrayIcon::bus_name` of every XEmbed item; `SniHost` routes on it.
+pub const BUS_NAME: &str = "xembed";
+
+/// Size we configure docked icons to.
+const ICON_PX: u16 = 22;
+
+/// How often docked icons are re-captured. XEmbed has no "icon changed"
+/// notification, so this is the update latency.
+const T_SNAPSHOT: Duration = Duration::from_millis(500);
+const T_TICK:     Duration = Duration::from_millis(30);
+
+const SYSTEM_TRAY_REQUEST_DOCK: u32 = 0;
+const XEMBED_EMBEDDED_NOTIFY:   u32 = 0;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        MANAGER,
+        UTF8_STRING,
+        WM_CLASS,
+        WM_NAME,
+        _NET_WM_NAME,
+        _NET_SYSTEM_TRAY_OPCODE,
+        _NET_SYSTEM_TRAY_ORIENTATION,
+        _XEMBED,
+    }
+}
+
+enum XembedAction {
+    Click { window: Window, button: u8 },
+}
+
+// ============================================================================
+// Public host
+// ============================================================================
+
+pub struct XembedHost {
+    action_tx: mpsc::Sender<XembedAction>,
+}
+
+impl XembedHost {
+    pub fn new(items: TrayItems) -> Self {
+        let (action_tx, action_rx) = mpsc::channel();
+        thread::spawn(move || {
+            if let Err(e) = run(items, action_rx) {
+                tracing::warn!(target: "sni", "XEmbed tray: {e}");
+            }
+        });
+        XembedHost { action_tx }
+    }
+
+    /// Press and release `button` (1 left, 2 middle, 3 right) on the icon
+    /// whose `obj_path` is given.
+    pub fn click(&self, obj_path: &str, button: u8) {
+        if let Ok(window) = obj_path.parse() {
+            let _ = self.action_tx.send(XembedAction::Click { window, button });
+        }
+    }
+
+    /// Wheel buttons 4/5 (vertical) or 6/7 (horizontal).
+    pub fn scroll(&self, obj_path: &str, delta: i32, orientation: &str) {
+        let button = match (orientation, delta > 0) {
+            ("horizontal", true)  => 7,
+            ("horizontal", false) => 6,
+            (_, true)             => 4,
+            (_, false)            => 5,
+        };
+        self.click(obj_path, button);
+    }
+}
+
+// ============================================================================
+// Tray manager
+// ============================================================================
+
+struct Docked {
+    icon:      Window,
+    container: Window,
+    /// Last snapshot, to skip updates when nothing changed.
+    rgba:      Vec<u8>,
+}
+
+struct Tray {
+    conn:   RustConnection,
+    root:   Window,
+    black:  u32,
+    atoms:  Atoms,
+    items:  TrayItems,
+    docked: Vec<Docked>,
+}
+
+fn run(items: TrayItems, action_rx: mpsc::Receiver<XembedAction>) -> Result<(), Box<dyn Error>> {
+    let (conn, screen_num) = x11rb::connect(None)?;
+    let (root, black) = {
+        let screen = &conn.setup().roots[screen_num];
+        (screen.root, screen.black_pixel)
+    };
+    let atoms     = Atoms::new(&conn)?.reply()?;
+    let selection = conn.intern_atom(false, format!("_NET_SYSTEM_TRAY_S{screen_num}").as_bytes())?.reply()?.atom;
+
+    if conn.get_selection_owner(selection)?.reply()?.owner != NONE {
+        return Err("another tray already owns the XEmbed selection".into());
+    }
+    conn.composite_query_version(0, 4)?.reply()?;
+
+    let manager = conn.generate_id()?;
+    conn.create_window(
+        x11rb::COPY_DEPTH_FROM_PARENT, manager, root, -1, -1, 1, 1, 0,
+        WindowClass::INPUT_OUTPUT, x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new().override_redirect(1),
+    )?;
+    conn.change_property32(PropMode::REPLACE, manager, atoms._NET_SYSTEM_TRAY_ORIENTATION, AtomEnum::CARDINAL, &[0])?;
+    conn.set_selection_owner(manager, selection, CURRENT_TIME)?;
+    if conn.get_selection_owner(selection)?.reply()?.owner != manager {
+        return Err("lost the race for the XEmbed selection".into());
+    }
+    // Announce ourselves so icons started before us dock now.
+    let announce = ClientMessageEvent::new(32, root, atoms.MANAGER, [CURRENT_TIME, selection, manager, 0, 0]);
+    conn.send_event(false, root, EventMask::STRUCTURE_NOTIFY, announce)?;
+    conn.flush()?;
+    tracing::info!(target: "sni", "XEmbed tray: claimed _NET_SYSTEM_TRAY_S{screen_num}");
+
+    let mut tray = Tray { conn, root, black, atoms, items, docked: Vec::new() };
+    let mut last_snapshot = Instant::now();
+    loop {
+        while let Some(event) = tray.conn.poll_for_event()? {
+            match event {
+                Event::ClientMessage(ev) if ev.window == manager && ev.type_ == tray.atoms._NET_SYSTEM_TRAY_OPCODE => {
+                    let data = ev.data.as_data32();
+                    if data[1] == SYSTEM_TRAY_REQUEST_DOCK && let Err(e) = tray.dock(data[2]) {
+                        tracing::warn!(target: "sni", "XEmbed tray: dock {:#x}: {e}", data[2]);
+                    }
+                }
+                Event::DestroyNotify(ev)                                   => tray.undock(ev.window),
+                Event::ReparentNotify(ev) if !tray.owns(ev.window, ev.parent) => tray.undock(ev.window),
+                Event::PropertyNotify(ev) if ev.atom == tray.atoms._NET_WM_NAME || ev.atom == tray.atoms.WM_NAME => {
+                    tray.retitle(ev.window);
+                }
+                Event::SelectionClear(ev) if ev.selection == selection => {
+                    tracing::info!(target: "sni", "XEmbed tray: selection taken over, releasing icons");
+                    for icon in tray.docked.iter().map(|d| d.icon).collect::<Vec<_>>() { tray.undock(icon); }
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        while let Ok(action) = action_rx.try_recv() {
+            match action {
+                XembedAction::Click { window, button } => { let _ = tray.click(window, button); }
+            }
+        }
+        if last_snapshot.elapsed() >= T_SNAPSHOT {
+            tray.snapshot_all();
+            last_snapshot = Instant::now();
+        }
+        thread::sleep(T_TICK);
+    }
+}
+
+impl Tray {
+    fn owns(&self, icon: Window, parent: Window) -> bool {
+        self.docked.iter().any(|d| d.icon == icon && d.container == parent)
+    }
+
+    fn dock(&mut self, icon: Window) -> Result<(), Box<dyn Error>> {
+        if self.docked.iter().any(|d| d.icon == icon) { return Ok(()); }
+        let conn = &self.conn;
+
+        // Offscreen and manually redirected: it renders, but never on screen.
+        let container = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT, container, self.root, -(ICON_PX as i16) * 4, 0, ICON_PX, ICON_PX, 0,
+            WindowClass::INPUT_OUTPUT, x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new().override_redirect(1).background_pixel(self.black),
+        )?;
+        conn.composite_redirect_window(container, Redirect::MANUAL)?;
+        conn.change_window_attributes(icon, &ChangeWindowAttributesAux::new()
+            .event_mask(EventMask::STRUCTURE_NOTIFY | EventMask::PROPERTY_CHANGE))?;
+        conn.reparent_window(icon, container, 0, 0)?;
+        conn.configure_window(icon, &ConfigureWindowAux::new().width(ICON_PX as u32).height(ICON_PX as u32))?;
+        conn.map_window(container)?;
+        conn.map_window(icon)?;
+        let notify = ClientMessageEvent::new(32, icon, self.atoms._XEMBED, [CURRENT_TIME, XEMBED_EMBEDDED_NOTIFY, 0, container, 0]);
+        conn.send_event(false, icon, EventMask::NO_EVENT, notify)?;
+        conn.flush()?;
+
+        let class = self.text_property(icon, self.atoms.WM_CLASS, AtomEnum::STRING.into())
+            .and_then(|c| c.split('\0').nth(1).map(str::to_string))
+            .unwrap_or_else(|| format!("xembed-{icon:x}"));
+        let title = self.title(icon).unwrap_or_else(|| class.clone());
+        self.docked.push(Docked { icon, container, rgba: Vec::new() });
+        self.items.update(|list| list.push(TrayIcon {
+            id:            item_id(icon),
+            sni_id:        class,
+            bus_name:      BUS_NAME.into(),
+            obj_path:      icon.to_string(),
+            tooltip_title: title,
+            ..Default::default()
+        }));
+        Ok(())
+    }
+
+    fn undock(&mut self, icon: Window) {
+        let Some(pos) = self.docked.iter().position(|d| d.icon == icon) else { return };
+        let docked = self.docked.remove(pos);
+        // Hand a still-living icon back to the root window so the app can
+        // re-dock with whoever takes the tray next.
+        let _ = self.conn.reparent_window(icon, self.root, 0, 0);
+        let _ = self.conn.destroy_window(docked.container);
+        let _ = self.conn.flush();
+        let id = item_id(icon);
+        self.items.update(|list| list.retain(|i| i.id != id));
+    }
+
+    fn retitle(&mut self, icon: Window) {
+        if !self.docked.iter().any(|d| d.icon == icon) { return; }
+        let Some(title) = self.title(icon) else { return };
+        let id = item_id(icon);
+        self.items.update(|list| if let Some(item) = list.iter_mut().find(|i| i.id == id) {
+            item.tooltip_title = title;
+        });
+    }
+
+    fn title(&self, window: Window) -> Option<String> {
+        self.text_property(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)
+            .or_else(|| self.text_property(window, self.atoms.WM_NAME, AtomEnum::STRING.into()))
+            .filter(|s| !s.is_empty())
+    }
+
+    fn text_property(&self, window: Window, prop: Atom, type_: Atom) -> Option<String> {
+        let reply = self.conn.get_property(false, window, prop, type_, 0, 1024).ok()?.reply().ok()?;
+        (!reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn snapshot_all(&mut self) {
+        let mut changed = Vec::new();
+        for docked in &mut self.docked {
+            let Some((w, h, rgba)) = capture(&self.conn, docked.icon) else { continue };
+            if rgba == docked.rgba { continue; }
+            docked.rgba = rgba.clone();
+            changed.push((item_id(docked.icon), w, h, rgba));
+        }
+        if changed.is_empty() { return; }
+        self.items.update(|list| for (id, w, h, rgba) in changed {
+            if let Some(item) = list.iter_mut().find(|i| i.id == id) {
+                (item.icon_w, item.icon_h, item.icon_rgba) = (w, h, rgba);
+                item.icon_rev = item.icon_rev.wrapping_add(1);
+            }
+        });
+    }
+
+    /// Synthetic press + release at the icon's centre. Most toolkits accept
+    /// these; the pointer's root position is passed along so popup menus
+    /// open where the user clicked in our window.
+    fn click(&self, icon: Window, button: u8) -> Result<(), Box<dyn Error>> {
+        let pointer = self.conn.query_pointer(self.root)?.reply()?;
+        let half    = (ICON_PX / 2) as i16;
+        // The release carries the button's own mask bit (Button1Mask = 0x100 …).
+        let held    = if button <= 5 { 0x80u16 << button } else { 0 };
+        for (kind, state) in [(BUTTON_PRESS_EVENT, 0u16), (BUTTON_RELEASE_EVENT, held)] {
+            let event = ButtonPressEvent {
+                response_type: kind,
+                detail:        button,
+                sequence:      0,
+                time:          CURRENT_TIME,
+                root:          self.root,
+                event:         icon,
+                child:         NONE,
+                root_x:        pointer.root_x,
+                root_y:        pointer.root_y,
+                event_x:       half,
+                event_y:       half,
+                state:         state.into(),
+                same_screen:   true,
+            };
+            let mask = if kind == BUTTON_PRESS_EVENT { EventMask::BUTTON_PRESS } else { EventMask::BUTTON_RELEASE };
+            self.conn.send_event(true, icon, mask, event)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+fn item_id(icon: Window) -> String { format!("{BUS_NAME}/{icon}") }
+
+/// The icon's current pixels as RGBA. 24/32-bit ZPixmap data is BGRX/BGRA in
+/// memory on the little-endian servers this runs against. Depth-24 icons
+/// paint over our container's black background, so when all four corners
+/// share a colour that colour is treated as transparent.
+fn capture(conn: &RustConnection, icon: Window) -> Option<(u32, u32, Vec<u8>)> {
+    let geom = conn.get_geometry(icon).ok()?.reply().ok()?;
+    let (w, h) = (geom.width as usize, geom.height as usize);
+    if w == 0 || h == 0 { return None; }
+    let img = conn.get_image(ImageFormat::Z_PIXMAP, icon, 0, 0, geom.width, geom.height, !0).ok()?.reply().ok()?;
+    if img.depth < 24 || img.data.len() < w * h * 4 { return None; }
+
+    let opaque = img.depth == 24;
+    let mut rgba: Vec<u8> = img.data.chunks_exact(4).take(w * h)
+        .flat_map(|p| [p[2], p[1], p[0], if opaque { 255 } else { p[3] }])
+        .collect();
+    if opaque {
+        let px = |i: usize| [rgba[i * 4], rgba[i * 4 + 1], rgba[i * 4 + 2]];
+        let bg = px(0);
+        if [w - 1, (h - 1) * w, h * w - 1].iter().all(|&i| px(i) == bg) {
+            for p in rgba.chunks_exact_mut(4) {
+                if p[..3] == bg { p[3] = 0; }
+            }
+        }
+    }
+    Some((w as u32, h as u32, rgba))
+}